@@ -46,6 +46,11 @@ pub enum MentionUri {
     Fetch {
         url: Url,
     },
+    McpResource {
+        server_id: String,
+        uri: String,
+        name: String,
+    },
 }
 
 impl MentionUri {
@@ -150,6 +155,23 @@ impl MentionUri {
                     Ok(Self::Directory {
                         abs_path: path.into(),
                     })
+                } else if path.starts_with("/agent/mcp-resource") {
+                    let mut server_id = None;
+                    let mut uri = None;
+                    let mut name = None;
+                    for (key, value) in url.query_pairs() {
+                        match key.as_ref() {
+                            "server" => server_id = Some(value.into_owned()),
+                            "uri" => uri = Some(value.into_owned()),
+                            "name" => name = Some(value.into_owned()),
+                            _ => bail!("invalid query parameter"),
+                        }
+                    }
+                    Ok(Self::McpResource {
+                        server_id: server_id.context("Missing server for MCP resource")?,
+                        uri: uri.context("Missing uri for MCP resource")?,
+                        name: name.context("Missing name for MCP resource")?,
+                    })
                 } else if path.starts_with("/agent/selection") {
                     let fragment = url.fragment().context("Missing fragment for selection")?;
                     let line_range = parse_line_range(fragment)?;
@@ -186,6 +208,7 @@ impl MentionUri {
                 ..
             } => selection_name(path.as_deref(), line_range),
             MentionUri::Fetch { url } => url.to_string(),
+            MentionUri::McpResource { name, .. } => name.clone(),
         }
     }
 
@@ -203,6 +226,7 @@ impl MentionUri {
             MentionUri::Rule { .. } => IconName::Reader.path().into(),
             MentionUri::Selection { .. } => IconName::Reader.path().into(),
             MentionUri::Fetch { .. } => IconName::ToolWeb.path().into(),
+            MentionUri::McpResource { .. } => IconName::ZedMcpCustom.path().into(),
         }
     }
 
@@ -284,6 +308,19 @@ impl MentionUri {
                 url
             }
             MentionUri::Fetch { url } => url.clone(),
+            MentionUri::McpResource {
+                server_id,
+                uri,
+                name,
+            } => {
+                let mut url = Url::parse("zed:///").unwrap();
+                url.set_path("/agent/mcp-resource");
+                url.query_pairs_mut()
+                    .append_pair("server", server_id)
+                    .append_pair("uri", uri)
+                    .append_pair("name", name);
+                url
+            }
         }
     }
 }
@@ -495,6 +532,28 @@ mod tests {
         assert_eq!(parsed.to_uri().to_string(), https_uri);
     }
 
+    #[test]
+    fn test_parse_mcp_resource_uri() {
+        let resource_uri = "zed:///agent/mcp-resource?server=github&uri=repo%3A%2F%2Fzed%2FREADME.md&name=README.md";
+        let parsed = MentionUri::parse(resource_uri).unwrap();
+        match &parsed {
+            MentionUri::McpResource {
+                server_id,
+                uri,
+                name,
+            } => {
+                assert_eq!(server_id, "github");
+                assert_eq!(uri, "repo://zed/README.md");
+                assert_eq!(name, "README.md");
+            }
+            _ => panic!("Expected McpResource variant"),
+        }
+        assert_eq!(parsed.to_uri().to_string(), resource_uri);
+        assert!(
+            MentionUri::parse("zed:///agent/mcp-resource?server=github&name=README.md").is_err()
+        );
+    }
+
     #[test]
     fn test_invalid_scheme() {
         assert!(MentionUri::parse("ftp://example.com").is_err());
@@ -803,6 +803,11 @@ impl ActionLog {
             .collect()
     }
 
+    /// Iterate over buffers read or edited by the model
+    pub fn tracked_buffers(&self) -> impl Iterator<Item = &Entity<Buffer>> {
+        self.tracked_buffers.keys()
+    }
+
     /// Iterate over buffers changed since last read or edited by the model
     pub fn stale_buffers<'a>(&'a self, cx: &'a App) -> impl Iterator<Item = &'a Entity<Buffer>> {
         self.tracked_buffers
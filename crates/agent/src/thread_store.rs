@@ -335,6 +335,7 @@ impl ThreadStore {
             root_name,
             abs_path,
             rules_file: None,
+            nested_rules_files: Vec::new(),
         };
 
         let rules_task = Self::load_worktree_rules_file(worktree, project, cx);
@@ -563,6 +564,7 @@ impl ThreadStore {
                     }
                 }
             }
+            project::context_server_store::Event::ResourceUpdated { .. } => {}
         }
     }
 
@@ -7,7 +7,7 @@ use acp_thread::{AcpThread, AgentModelSelector};
 use action_log::ActionLog;
 use agent_client_protocol as acp;
 use anyhow::{Context as _, Result, anyhow};
-use collections::{HashSet, IndexMap};
+use collections::{BTreeMap, HashSet, IndexMap};
 use fs::Fs;
 use futures::channel::{mpsc, oneshot};
 use futures::future::Shared;
@@ -16,7 +16,7 @@ use gpui::{
     App, AppContext, AsyncApp, Context, Entity, SharedString, Subscription, Task, WeakEntity,
 };
 use language_model::{LanguageModel, LanguageModelProvider, LanguageModelRegistry};
-use project::{Project, ProjectItem, ProjectPath, Worktree};
+use project::{Project, ProjectItem, ProjectPath, Worktree, WorktreeId};
 use prompt_store::{
     ProjectContext, PromptId, PromptStore, RulesFileContext, UserRulesContext, WorktreeContext,
 };
@@ -353,8 +353,12 @@ impl NativeAgent {
                     Self::build_project_context(&this.project, this.prompt_store.as_ref(), cx)
                 })?
                 .await;
+            // Update the shared context in place, so that existing threads pick up the changes.
             this.update(cx, |this, cx| {
-                this.project_context = cx.new(|_| project_context);
+                this.project_context.update(cx, |this_project_context, cx| {
+                    *this_project_context = project_context;
+                    cx.notify();
+                });
             })?;
         }
 
@@ -442,24 +446,27 @@ impl NativeAgent {
             root_name,
             abs_path,
             rules_file: None,
+            nested_rules_files: Vec::new(),
         };
 
-        let rules_task = Self::load_worktree_rules_file(worktree, project, cx);
-        let Some(rules_task) = rules_task else {
-            return Task::ready((context, None));
-        };
+        let rules_task = Self::load_worktree_rules_file(worktree.clone(), project.clone(), cx);
+        let nested_rules_task = Self::load_nested_rules_files(worktree, project, cx);
 
         cx.spawn(async move |_| {
-            let (rules_file, rules_file_error) = match rules_task.await {
-                Ok(rules_file) => (Some(rules_file), None),
-                Err(err) => (
-                    None,
-                    Some(RulesLoadingError {
-                        message: format!("{err}").into(),
-                    }),
-                ),
+            let (rules_file, rules_file_error) = match rules_task {
+                Some(rules_task) => match rules_task.await {
+                    Ok(rules_file) => (Some(rules_file), None),
+                    Err(err) => (
+                        None,
+                        Some(RulesLoadingError {
+                            message: format!("{err}").into(),
+                        }),
+                    ),
+                },
+                None => (None, None),
             };
             context.rules_file = rules_file;
+            context.nested_rules_files = nested_rules_task.await;
             (context, rules_file_error)
         })
     }
@@ -484,26 +491,90 @@ impl NativeAgent {
         // Note that Cline supports `.clinerules` being a directory, but that is not currently
         // supported. This doesn't seem to occur often in GitHub repositories.
         selected_rules_file.map(|path_in_worktree| {
-            let project_path = ProjectPath {
-                worktree_id,
-                path: path_in_worktree.clone(),
+            Self::load_rules_file(worktree_id, path_in_worktree, &project, cx)
+        })
+    }
+
+    /// Loads the rules files found in the worktree's subdirectories, picking one per directory
+    /// in the same order of preference as at the root.
+    fn load_nested_rules_files(
+        worktree: Entity<Worktree>,
+        project: Entity<Project>,
+        cx: &mut App,
+    ) -> Task<Vec<RulesFileContext>> {
+        let snapshot = worktree.read(cx).snapshot();
+        let worktree_id = snapshot.id();
+        let paths_task = cx.background_spawn(async move {
+            let mut rules_files = BTreeMap::<Arc<RelPath>, (usize, Arc<RelPath>)>::new();
+            for entry in snapshot.files(false, 0) {
+                for (preference, name) in RULES_FILE_NAMES.iter().enumerate() {
+                    let name = RelPath::unix(name).unwrap();
+                    if !entry.path.ends_with(name) {
+                        continue;
+                    }
+                    let Some(directory) = entry.path.ancestors().nth(name.len()) else {
+                        continue;
+                    };
+                    if directory.is_empty() {
+                        continue;
+                    }
+                    let rules_file = rules_files
+                        .entry(directory.into_arc())
+                        .or_insert_with(|| (preference, entry.path.clone()));
+                    if preference < rules_file.0 {
+                        *rules_file = (preference, entry.path.clone());
+                    }
+                }
+            }
+            // Parent directories sort before their children, so broader rules come first.
+            rules_files
+                .into_values()
+                .map(|(_, path)| path)
+                .collect::<Vec<_>>()
+        });
+
+        cx.spawn(async move |cx| {
+            let paths = paths_task.await;
+            let Ok(load_tasks) = cx.update(|cx| {
+                paths
+                    .into_iter()
+                    .map(|path| Self::load_rules_file(worktree_id, path, &project, cx))
+                    .collect::<Vec<_>>()
+            }) else {
+                return Vec::new();
             };
-            let buffer_task =
-                project.update(cx, |project, cx| project.open_buffer(project_path, cx));
-            let rope_task = cx.spawn(async move |cx| {
-                buffer_task.await?.read_with(cx, |buffer, cx| {
-                    let project_entry_id = buffer.entry_id(cx).context("buffer has no file")?;
-                    anyhow::Ok((project_entry_id, buffer.as_rope().clone()))
-                })?
-            });
-            // Build a string from the rope on a background thread.
-            cx.background_spawn(async move {
-                let (project_entry_id, rope) = rope_task.await?;
-                anyhow::Ok(RulesFileContext {
-                    path_in_worktree,
-                    text: rope.to_string().trim().to_string(),
-                    project_entry_id: project_entry_id.to_usize(),
-                })
+            future::join_all(load_tasks)
+                .await
+                .into_iter()
+                .filter_map(|rules_file| rules_file.log_err())
+                .collect()
+        })
+    }
+
+    fn load_rules_file(
+        worktree_id: WorktreeId,
+        path_in_worktree: Arc<RelPath>,
+        project: &Entity<Project>,
+        cx: &mut App,
+    ) -> Task<Result<RulesFileContext>> {
+        let project_path = ProjectPath {
+            worktree_id,
+            path: path_in_worktree.clone(),
+        };
+        let buffer_task = project.update(cx, |project, cx| project.open_buffer(project_path, cx));
+        let rope_task = cx.spawn(async move |cx| {
+            buffer_task.await?.read_with(cx, |buffer, cx| {
+                let project_entry_id = buffer.entry_id(cx).context("buffer has no file")?;
+                anyhow::Ok((project_entry_id, buffer.as_rope().clone()))
+            })?
+        });
+        // Build a string from the rope on a background thread.
+        cx.background_spawn(async move {
+            let (project_entry_id, rope) = rope_task.await?;
+            anyhow::Ok(RulesFileContext {
+                path_in_worktree,
+                text: rope.to_string().trim().to_string(),
+                project_entry_id: project_entry_id.to_usize(),
             })
         })
     }
@@ -556,10 +627,11 @@ impl NativeAgent {
                 self.project_context_needs_refresh.send(()).ok();
             }
             project::Event::WorktreeUpdatedEntries(_, items) => {
+                // Rules files may be nested in any directory.
                 if items.iter().any(|(path, _, _)| {
                     RULES_FILE_NAMES
                         .iter()
-                        .any(|name| path.as_ref() == RelPath::unix(name).unwrap())
+                        .any(|name| path.ends_with(RelPath::unix(name).unwrap()))
                 }) {
                     self.project_context_needs_refresh.send(()).ok();
                 }
@@ -1250,7 +1322,8 @@ mod tests {
                 vec![WorktreeContext {
                     root_name: "a".into(),
                     abs_path: Path::new("/a").into(),
-                    rules_file: None
+                    rules_file: None,
+                    nested_rules_files: Vec::new(),
                 }]
             )
         });
@@ -1272,10 +1345,42 @@ mod tests {
                         path_in_worktree: rel_path(".rules").into(),
                         text: "".into(),
                         project_entry_id: rules_entry.id.to_usize()
-                    })
+                    }),
+                    nested_rules_files: Vec::new(),
                 }]
             )
         });
+
+        // Rules files in subdirectories are loaded too, preferring the same names as at the root.
+        fs.insert_tree(
+            "/a/sub",
+            json!({
+                "AGENTS.md": "Sub rules",
+                ".rules": "Preferred sub rules",
+                "deeper": {
+                    "CLAUDE.md": "Deeper rules"
+                }
+            }),
+        )
+        .await;
+        cx.run_until_parked();
+        agent.read_with(cx, |agent, cx| {
+            let nested_rules_files = agent.project_context.read(cx).worktrees[0]
+                .nested_rules_files
+                .iter()
+                .map(|rules_file| (rules_file.path_in_worktree.clone(), rules_file.text.clone()))
+                .collect::<Vec<_>>();
+            assert_eq!(
+                nested_rules_files,
+                vec![
+                    (rel_path("sub/.rules").into(), "Preferred sub rules".into()),
+                    (
+                        rel_path("sub/deeper/CLAUDE.md").into(),
+                        "Deeper rules".into()
+                    ),
+                ]
+            );
+        });
     }
 
     #[gpui::test]
@@ -1,9 +1,11 @@
 mod agent;
 mod db;
+mod export;
 mod history_store;
 mod native_agent_server;
 mod templates;
 mod thread;
+mod tool_permissions;
 mod tool_schema;
 mod tools;
 
@@ -16,4 +18,5 @@ pub use history_store::*;
 pub use native_agent_server::NativeAgentServer;
 pub use templates::*;
 pub use thread::*;
+pub use tool_permissions::*;
 pub use tools::*;
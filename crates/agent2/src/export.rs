@@ -0,0 +1,439 @@
+use crate::{
+    AgentMessage, AgentMessageContent, DbMessage, DbThread, NestedRulesFile, UserMessage,
+    UserMessageContent,
+};
+use acp_thread::{MentionUri, UserMessageId};
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Utc};
+use collections::{HashMap, IndexMap};
+use gpui::{DevicePixels, size};
+use language_model::{
+    LanguageModelImage, LanguageModelToolResult, LanguageModelToolResultContent,
+    LanguageModelToolUse, LanguageModelToolUseId,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// A thread as it's exported to a file and imported from one.
+///
+/// It's versioned separately from [`DbThread`], which follows how threads are stored and can
+/// change in any release, so that threads exported from one version of Zed can be imported by
+/// later ones. Changing it means adding a version and converting the previous ones on import.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "version")]
+enum ExportedThread {
+    #[serde(rename = "1")]
+    V1(ExportedThreadV1),
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ExportedThreadV1 {
+    title: String,
+    updated_at: DateTime<Utc>,
+    messages: Vec<ExportedMessageV1>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "role", rename_all = "snake_case")]
+enum ExportedMessageV1 {
+    User {
+        content: Vec<ExportedUserContentV1>,
+    },
+    Agent {
+        content: Vec<ExportedAgentContentV1>,
+        tool_results: Vec<ExportedToolResultV1>,
+    },
+    Resume,
+    NestedRules {
+        files: Vec<ExportedNestedRulesFileV1>,
+    },
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ExportedNestedRulesFileV1 {
+    path: String,
+    text: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ExportedUserContentV1 {
+    Text { text: String },
+    Mention { uri: String, content: String },
+    Image(ExportedImageV1),
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ExportedAgentContentV1 {
+    Text {
+        text: String,
+    },
+    Thinking {
+        text: String,
+        signature: Option<String>,
+    },
+    RedactedThinking {
+        data: String,
+    },
+    ToolUse {
+        id: String,
+        name: String,
+        input: serde_json::Value,
+    },
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ExportedToolResultV1 {
+    tool_use_id: String,
+    tool_name: String,
+    is_error: bool,
+    content: ExportedToolResultContentV1,
+    /// What the tool produced besides the content sent to the model, such as an edit's diff,
+    /// which is used to show its result. Its format is up to each tool.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    output: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ExportedToolResultContentV1 {
+    Text { text: String },
+    Image(ExportedImageV1),
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ExportedImageV1 {
+    /// A base64-encoded PNG image.
+    source: String,
+    width: i32,
+    height: i32,
+}
+
+impl DbThread {
+    /// Serializes the thread in the format it's exported in, which [`DbThread::from_export_json`]
+    /// reads back.
+    ///
+    /// Only the conversation is exported, not its token usage or the settings it ran with.
+    pub fn to_export_json(&self) -> Result<String> {
+        let thread = ExportedThread::V1(ExportedThreadV1 {
+            title: self.title.to_string(),
+            updated_at: self.updated_at,
+            messages: self.messages.iter().map(export_message).collect(),
+        });
+        Ok(serde_json::to_string_pretty(&thread)?)
+    }
+
+    /// Parses a thread exported with [`DbThread::to_export_json`], by this or an earlier version
+    /// of Zed.
+    pub fn from_export_json(json: &[u8]) -> Result<Self> {
+        let json = serde_json::from_slice::<serde_json::Value>(json)?;
+        match json.get("version").and_then(|version| version.as_str()) {
+            Some("1") => {}
+            Some(version) => {
+                return Err(anyhow!(
+                    "The thread was exported in version {version} of the format, which this \
+                    version of Zed can't import"
+                ));
+            }
+            None => return Err(anyhow!("The file isn't an exported thread")),
+        }
+
+        let ExportedThread::V1(thread) = serde_json::from_value(json)?;
+        let messages = thread
+            .messages
+            .into_iter()
+            .map(import_message)
+            .collect::<Result<_>>()?;
+        Ok(Self {
+            title: thread.title.into(),
+            messages,
+            updated_at: thread.updated_at,
+            detailed_summary: None,
+            initial_project_snapshot: None,
+            cumulative_token_usage: Default::default(),
+            request_token_usage: HashMap::default(),
+            model: None,
+            completion_mode: None,
+            profile: None,
+        })
+    }
+}
+
+fn export_message(message: &DbMessage) -> ExportedMessageV1 {
+    match message {
+        DbMessage::User(message) => ExportedMessageV1::User {
+            content: message
+                .content
+                .iter()
+                .map(|content| match content {
+                    UserMessageContent::Text(text) => {
+                        ExportedUserContentV1::Text { text: text.clone() }
+                    }
+                    UserMessageContent::Mention { uri, content } => {
+                        ExportedUserContentV1::Mention {
+                            uri: uri.to_uri().to_string(),
+                            content: content.clone(),
+                        }
+                    }
+                    UserMessageContent::Image(image) => {
+                        ExportedUserContentV1::Image(export_image(image))
+                    }
+                })
+                .collect(),
+        },
+        DbMessage::Agent(message) => ExportedMessageV1::Agent {
+            content: message
+                .content
+                .iter()
+                .map(|content| match content {
+                    AgentMessageContent::Text(text) => {
+                        ExportedAgentContentV1::Text { text: text.clone() }
+                    }
+                    AgentMessageContent::Thinking { text, signature } => {
+                        ExportedAgentContentV1::Thinking {
+                            text: text.clone(),
+                            signature: signature.clone(),
+                        }
+                    }
+                    AgentMessageContent::RedactedThinking(data) => {
+                        ExportedAgentContentV1::RedactedThinking { data: data.clone() }
+                    }
+                    AgentMessageContent::ToolUse(tool_use) => ExportedAgentContentV1::ToolUse {
+                        id: tool_use.id.to_string(),
+                        name: tool_use.name.to_string(),
+                        input: tool_use.input.clone(),
+                    },
+                })
+                .collect(),
+            tool_results: message
+                .tool_results
+                .values()
+                .map(|result| ExportedToolResultV1 {
+                    tool_use_id: result.tool_use_id.to_string(),
+                    tool_name: result.tool_name.to_string(),
+                    is_error: result.is_error,
+                    content: match &result.content {
+                        LanguageModelToolResultContent::Text(text) => {
+                            ExportedToolResultContentV1::Text {
+                                text: text.to_string(),
+                            }
+                        }
+                        LanguageModelToolResultContent::Image(image) => {
+                            ExportedToolResultContentV1::Image(export_image(image))
+                        }
+                    },
+                    output: result.output.clone(),
+                })
+                .collect(),
+        },
+        DbMessage::Resume => ExportedMessageV1::Resume,
+        DbMessage::NestedRules(files) => ExportedMessageV1::NestedRules {
+            files: files
+                .iter()
+                .map(|file| ExportedNestedRulesFileV1 {
+                    path: file.path.clone(),
+                    text: file.text.clone(),
+                })
+                .collect(),
+        },
+    }
+}
+
+fn import_message(message: ExportedMessageV1) -> Result<DbMessage> {
+    Ok(match message {
+        ExportedMessageV1::User { content } => DbMessage::User(UserMessage {
+            id: UserMessageId::new(),
+            content: content
+                .into_iter()
+                .map(|content| {
+                    anyhow::Ok(match content {
+                        ExportedUserContentV1::Text { text } => UserMessageContent::Text(text),
+                        ExportedUserContentV1::Mention { uri, content } => {
+                            UserMessageContent::Mention {
+                                uri: MentionUri::parse(&uri)?,
+                                content,
+                            }
+                        }
+                        ExportedUserContentV1::Image(image) => {
+                            UserMessageContent::Image(import_image(image))
+                        }
+                    })
+                })
+                .collect::<Result<_>>()?,
+        }),
+        ExportedMessageV1::Agent {
+            content,
+            tool_results,
+        } => DbMessage::Agent(AgentMessage {
+            content: content
+                .into_iter()
+                .map(|content| match content {
+                    ExportedAgentContentV1::Text { text } => AgentMessageContent::Text(text),
+                    ExportedAgentContentV1::Thinking { text, signature } => {
+                        AgentMessageContent::Thinking { text, signature }
+                    }
+                    ExportedAgentContentV1::RedactedThinking { data } => {
+                        AgentMessageContent::RedactedThinking(data)
+                    }
+                    ExportedAgentContentV1::ToolUse { id, name, input } => {
+                        AgentMessageContent::ToolUse(LanguageModelToolUse {
+                            id: id.into(),
+                            name: name.into(),
+                            raw_input: input.to_string(),
+                            input,
+                            is_input_complete: true,
+                        })
+                    }
+                })
+                .collect(),
+            tool_results: tool_results
+                .into_iter()
+                .map(|result| {
+                    let tool_use_id = LanguageModelToolUseId::from(result.tool_use_id);
+                    let result = LanguageModelToolResult {
+                        tool_use_id: tool_use_id.clone(),
+                        tool_name: Arc::from(result.tool_name),
+                        is_error: result.is_error,
+                        content: match result.content {
+                            ExportedToolResultContentV1::Text { text } => {
+                                LanguageModelToolResultContent::Text(text.into())
+                            }
+                            ExportedToolResultContentV1::Image(image) => {
+                                LanguageModelToolResultContent::Image(import_image(image))
+                            }
+                        },
+                        output: result.output,
+                    };
+                    (tool_use_id, result)
+                })
+                .collect::<IndexMap<_, _>>(),
+        }),
+        ExportedMessageV1::Resume => DbMessage::Resume,
+        ExportedMessageV1::NestedRules { files } => DbMessage::NestedRules(
+            files
+                .into_iter()
+                .map(|file| NestedRulesFile {
+                    path: file.path,
+                    text: file.text,
+                })
+                .collect(),
+        ),
+    })
+}
+
+fn export_image(image: &LanguageModelImage) -> ExportedImageV1 {
+    ExportedImageV1 {
+        source: image.source.to_string(),
+        width: image.size.width.0,
+        height: image.size.height.0,
+    }
+}
+
+fn import_image(image: ExportedImageV1) -> LanguageModelImage {
+    LanguageModelImage {
+        source: image.source.into(),
+        size: size(DevicePixels(image.width), DevicePixels(image.height)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use language_model::TokenUsage;
+
+    #[test]
+    fn test_exported_thread_round_trip() {
+        let tool_use_id = LanguageModelToolUseId::from("tool_1");
+        let thread = DbThread {
+            title: "Fixing the build".into(),
+            messages: vec![
+                DbMessage::User(UserMessage {
+                    id: UserMessageId::new(),
+                    content: vec![
+                        UserMessageContent::Text("Why does it fail?".into()),
+                        UserMessageContent::Mention {
+                            uri: MentionUri::Fetch {
+                                url: "https://zed.dev/docs".parse().unwrap(),
+                            },
+                            content: "Docs".into(),
+                        },
+                    ],
+                }),
+                DbMessage::Agent(AgentMessage {
+                    content: vec![
+                        AgentMessageContent::Text("Let me check.".into()),
+                        AgentMessageContent::ToolUse(LanguageModelToolUse {
+                            id: tool_use_id.clone(),
+                            name: "grep".into(),
+                            raw_input: r#"{"regex":"use"}"#.into(),
+                            input: serde_json::json!({ "regex": "use" }),
+                            is_input_complete: true,
+                        }),
+                    ],
+                    tool_results: IndexMap::from_iter([(
+                        tool_use_id.clone(),
+                        LanguageModelToolResult {
+                            tool_use_id,
+                            tool_name: "grep".into(),
+                            is_error: false,
+                            content: LanguageModelToolResultContent::Text("No matches".into()),
+                            output: Some(serde_json::json!({ "matches": [] })),
+                        },
+                    )]),
+                }),
+                DbMessage::NestedRules(vec![NestedRulesFile {
+                    path: "project/crates/a/AGENTS.md".into(),
+                    text: "Run the tests".into(),
+                }]),
+                DbMessage::Resume,
+            ],
+            updated_at: Utc::now(),
+            detailed_summary: Some("A summary".into()),
+            initial_project_snapshot: None,
+            cumulative_token_usage: TokenUsage {
+                input_tokens: 10,
+                ..Default::default()
+            },
+            request_token_usage: HashMap::default(),
+            model: None,
+            completion_mode: None,
+            profile: None,
+        };
+
+        let json = thread.to_export_json().unwrap();
+        let imported = DbThread::from_export_json(json.as_bytes()).unwrap();
+        assert_eq!(imported.title, thread.title);
+        assert_eq!(imported.updated_at, thread.updated_at);
+        assert_eq!(imported.detailed_summary, None);
+        assert_eq!(imported.cumulative_token_usage, TokenUsage::default());
+        // User messages get new ids, which aren't exported.
+        assert_eq!(imported.messages[1..], thread.messages[1..]);
+        assert_eq!(imported.to_export_json().unwrap(), json);
+    }
+
+    #[test]
+    fn test_importing_unsupported_versions() {
+        assert_eq!(
+            DbThread::from_export_json(br#"{"version":"2","title":"Later"}"#)
+                .unwrap_err()
+                .to_string(),
+            "The thread was exported in version 2 of the format, which this version of Zed can't \
+            import"
+        );
+        // Threads are stored in another format, which isn't imported.
+        assert_eq!(
+            DbThread::from_export_json(br#"{"version":"0.3.0","title":"Stored"}"#)
+                .unwrap_err()
+                .to_string(),
+            "The thread was exported in version 0.3.0 of the format, which this version of Zed \
+            can't import"
+        );
+        assert_eq!(
+            DbThread::from_export_json(br#"{"title":"Untitled"}"#)
+                .unwrap_err()
+                .to_string(),
+            "The file isn't an exported thread"
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{DbThreadMetadata, ThreadsDatabase};
+use crate::{DbThread, DbThreadMetadata, ThreadsDatabase};
 use acp_thread::MentionUri;
 use agent_client_protocol as acp;
 use anyhow::{Context as _, Result, anyhow};
@@ -140,6 +140,28 @@ impl HistoryStore {
         })
     }
 
+    /// Saves a thread exported with [`DbThread::to_export_json`] as a new thread in the history,
+    /// leaving any thread it was exported from untouched.
+    pub fn import_thread(
+        &mut self,
+        json: String,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<DbThreadMetadata>> {
+        let database_future = ThreadsDatabase::connect(cx);
+        cx.spawn(async move |this, cx| {
+            let thread = DbThread::from_export_json(json.as_bytes()).context("parsing thread")?;
+            let metadata = DbThreadMetadata {
+                id: acp::SessionId(uuid::Uuid::new_v4().to_string().into()),
+                title: thread.title.clone(),
+                updated_at: thread.updated_at,
+            };
+            let database = database_future.await.map_err(|err| anyhow!(err))?;
+            database.save_thread(metadata.id.clone(), thread).await?;
+            this.update(cx, |this, cx| this.reload(cx))?;
+            Ok(metadata)
+        })
+    }
+
     pub fn delete_text_thread(
         &mut self,
         path: Arc<Path>,
@@ -29,14 +29,14 @@ use pretty_assertions::assert_eq;
 use project::{
     Project, context_server_store::ContextServerStore, project_settings::ProjectSettings,
 };
-use prompt_store::ProjectContext;
+use prompt_store::{ProjectContext, RulesFileContext, WorktreeContext};
 use reqwest_client::ReqwestClient;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use settings::{Settings, SettingsStore};
 use std::{path::Path, rc::Rc, sync::Arc, time::Duration};
-use util::path;
+use util::{path, rel_path::RelPath};
 
 mod test_tools;
 use test_tools::*;
@@ -160,6 +160,106 @@ async fn test_system_prompt(cx: &mut TestAppContext) {
     );
 }
 
+#[gpui::test]
+async fn test_nested_rules(cx: &mut TestAppContext) {
+    let ThreadTest {
+        model,
+        thread,
+        project_context,
+        fs,
+        ..
+    } = setup(cx, TestModel::Fake).await;
+    let fake_model = model.as_fake();
+
+    fs.insert_tree(
+        path!("/test/a"),
+        json!({ "AGENTS.md": "Use tabs", "lib.rs": "" }),
+    )
+    .await;
+    project_context.update(cx, |project_context, _cx| {
+        project_context.worktrees = vec![WorktreeContext {
+            root_name: "test".into(),
+            abs_path: Path::new(path!("/test")).into(),
+            rules_file: None,
+            nested_rules_files: vec![RulesFileContext {
+                path_in_worktree: RelPath::unix("a/AGENTS.md").unwrap().into(),
+                text: "Use tabs".into(),
+                project_entry_id: 0,
+            }],
+        }];
+    });
+
+    // The rules aren't added until a file in their directory is read.
+    thread
+        .update(cx, |thread, cx| {
+            thread.send(UserMessageId::new(), ["Message 1"], cx)
+        })
+        .unwrap();
+    cx.run_until_parked();
+    let completion = fake_model.pending_completions().pop().unwrap();
+    assert_eq!(completion.messages.len(), 2);
+    fake_model.send_last_completion_stream_text_chunk("Done");
+    fake_model.end_last_completion_stream();
+    cx.run_until_parked();
+
+    let project = thread.read_with(cx, |thread, _| thread.project().clone());
+    let buffer = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer(path!("/test/a/lib.rs"), cx)
+        })
+        .await
+        .unwrap();
+    thread.update(cx, |thread, cx| {
+        thread
+            .action_log()
+            .update(cx, |action_log, cx| action_log.buffer_read(buffer, cx))
+    });
+
+    let rules_content: Vec<MessageContent> = vec![
+        indoc! {"
+            <rules>
+            The following rules apply to files in the directory of each rules file:
+
+            `test/a/AGENTS.md`:
+            ```
+            Use tabs
+            ```
+            </rules>"}
+        .into(),
+    ];
+    thread
+        .update(cx, |thread, cx| {
+            thread.send(UserMessageId::new(), ["Message 2"], cx)
+        })
+        .unwrap();
+    cx.run_until_parked();
+    let completion = fake_model.pending_completions().pop().unwrap();
+    let system_prompt = completion.messages[0].content[0].to_str().unwrap();
+    assert!(!system_prompt.contains("Use tabs"));
+    // The rules are added after the message that the request is made for.
+    let last_message = completion.messages.last().unwrap();
+    assert_eq!(last_message.role, Role::User);
+    assert_eq!(last_message.content, rules_content);
+    fake_model.send_last_completion_stream_text_chunk("Done");
+    fake_model.end_last_completion_stream();
+    cx.run_until_parked();
+
+    // The rules are only added once.
+    thread
+        .update(cx, |thread, cx| {
+            thread.send(UserMessageId::new(), ["Message 3"], cx)
+        })
+        .unwrap();
+    cx.run_until_parked();
+    let completion = fake_model.pending_completions().pop().unwrap();
+    let rules_messages = completion
+        .messages
+        .iter()
+        .filter(|message| message.content == rules_content)
+        .count();
+    assert_eq!(rules_messages, 1);
+}
+
 #[gpui::test]
 async fn test_prompt_caching(cx: &mut TestAppContext) {
     let ThreadTest { model, thread, .. } = setup(cx, TestModel::Fake).await;
@@ -538,6 +638,102 @@ async fn test_tool_authorization(cx: &mut TestAppContext) {
     );
 }
 
+#[gpui::test]
+async fn test_tool_permission_rules(cx: &mut TestAppContext) {
+    let ThreadTest {
+        model, thread, fs, ..
+    } = setup(cx, TestModel::Fake).await;
+    let fake_model = model.as_fake();
+
+    // Ask before echoing, even though echoing never asks by itself, and never run the tool that
+    // requires permission.
+    fs.insert_file(
+        paths::settings_file(),
+        json!({
+            "agent": {
+                "default_profile": "test-profile",
+                "profiles": {
+                    "test-profile": {
+                        "name": "Test Profile",
+                        "tools": {
+                            EchoTool::name(): true,
+                            ToolRequiringPermission::name(): true,
+                        }
+                    }
+                }
+            },
+            "agent_tool_permissions": [
+                { "tool": EchoTool::name(), "action": "ask" },
+                { "tool": ToolRequiringPermission::name(), "action": "deny" },
+            ]
+        })
+        .to_string()
+        .into_bytes(),
+    )
+    .await;
+    cx.run_until_parked();
+
+    let mut events = thread
+        .update(cx, |thread, cx| {
+            thread.add_tool(EchoTool);
+            thread.add_tool(ToolRequiringPermission);
+            thread.send(UserMessageId::new(), ["abc"], cx)
+        })
+        .unwrap();
+    cx.run_until_parked();
+    fake_model.send_last_completion_stream_event(LanguageModelCompletionEvent::ToolUse(
+        LanguageModelToolUse {
+            id: "tool_id_1".into(),
+            name: ToolRequiringPermission::name().into(),
+            raw_input: "{}".into(),
+            input: json!({}),
+            is_input_complete: true,
+        },
+    ));
+    fake_model.send_last_completion_stream_event(LanguageModelCompletionEvent::ToolUse(
+        LanguageModelToolUse {
+            id: "tool_id_2".into(),
+            name: EchoTool::name().into(),
+            raw_input: json!({"text": "def"}).to_string(),
+            input: json!({"text": "def"}),
+            is_input_complete: true,
+        },
+    ));
+    fake_model.end_last_completion_stream();
+
+    let tool_call_auth = next_tool_call_authorization(&mut events).await;
+    assert_eq!(tool_call_auth.tool_call.id.0.as_ref(), "tool_id_2");
+    tool_call_auth
+        .response
+        .send(tool_call_auth.options[1].id.clone())
+        .unwrap();
+    cx.run_until_parked();
+
+    let completion = fake_model.pending_completions().pop().unwrap();
+    let message = completion.messages.last().unwrap();
+    assert_eq!(
+        message.content,
+        vec![
+            language_model::MessageContent::ToolResult(LanguageModelToolResult {
+                tool_use_id: "tool_id_1".into(),
+                tool_name: ToolRequiringPermission::name().into(),
+                is_error: true,
+                content: "Permission to run tool_requiring_permission was denied by the tool \
+                    permission rules"
+                    .into(),
+                output: None
+            }),
+            language_model::MessageContent::ToolResult(LanguageModelToolResult {
+                tool_use_id: "tool_id_2".into(),
+                tool_name: EchoTool::name().into(),
+                is_error: false,
+                content: "def".into(),
+                output: Some("def".into())
+            })
+        ]
+    );
+}
+
 #[gpui::test]
 async fn test_tool_hallucination(cx: &mut TestAppContext) {
     let ThreadTest { model, thread, .. } = setup(cx, TestModel::Fake).await;
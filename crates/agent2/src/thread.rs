@@ -1,8 +1,9 @@
 use crate::{
     ContextServerRegistry, CopyPathTool, CreateDirectoryTool, DbLanguageModel, DbThread,
-    DeletePathTool, DiagnosticsTool, EditFileTool, FetchTool, FindPathTool, GrepTool,
-    ListDirectoryTool, MovePathTool, NowTool, OpenTool, ReadFileTool, SystemPromptTemplate,
-    Template, Templates, TerminalTool, ThinkingTool, WebSearchTool,
+    DeletePathTool, DiagnosticsTool, EditFileTool, FetchTool, FindDefinitionTool, FindPathTool,
+    FindReferencesTool, FindSymbolsTool, GrepTool, HoverTool, ListDirectoryTool, MovePathTool,
+    NowTool, OpenTool, ReadFileTool, RenameSymbolTool, SemanticSearchTool, SystemPromptTemplate,
+    Template, Templates, TerminalTool, ThinkingTool, ToolCallPermission, WebSearchTool,
 };
 use acp_thread::{MentionUri, UserMessageId};
 use action_log::ActionLog;
@@ -10,7 +11,7 @@ use agent::thread::{GitState, ProjectSnapshot, WorktreeSnapshot};
 use agent_client_protocol as acp;
 use agent_settings::{
     AgentProfileId, AgentProfileSettings, AgentSettings, CompletionMode,
-    SUMMARIZE_THREAD_DETAILED_PROMPT, SUMMARIZE_THREAD_PROMPT,
+    SUMMARIZE_THREAD_DETAILED_PROMPT, SUMMARIZE_THREAD_PROMPT, ToolPermissionSubject,
 };
 use anyhow::{Context as _, Result, anyhow};
 use assistant_tool::adapt_schema_to_format;
@@ -37,13 +38,14 @@ use language_model::{
     LanguageModelToolUseId, Role, SelectedModel, StopReason, TokenUsage,
 };
 use project::{
-    Project,
+    Project, ProjectItem as _,
     git_store::{GitStore, RepositoryState},
 };
 use prompt_store::ProjectContext;
 use schemars::{JsonSchema, Schema};
+use semantic_index::SemanticIndex;
 use serde::{Deserialize, Serialize};
-use settings::{Settings, update_settings_file};
+use settings::{Settings, ToolPermissionAction, update_settings_file};
 use smol::stream::StreamExt;
 use std::{
     collections::BTreeMap,
@@ -98,6 +100,16 @@ pub enum Message {
     User(UserMessage),
     Agent(AgentMessage),
     Resume,
+    /// The rules files of subdirectories the agent started working in, which are added to the
+    /// thread as they come into play rather than to the system prompt, so that it stays cached.
+    NestedRules(Vec<NestedRulesFile>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct NestedRulesFile {
+    /// The path of the rules file, starting with the name of its worktree.
+    pub path: String,
+    pub text: String,
 }
 
 impl Message {
@@ -117,6 +129,30 @@ impl Message {
                 content: vec!["Continue where you left off".into()],
                 cache: false,
             }],
+            Message::NestedRules(files) => {
+                const OPEN_NESTED_RULES: &str = "<rules>\n\
+                    The following rules apply to files in the directory of each rules file:\n";
+
+                let mut text = OPEN_NESTED_RULES.to_string();
+                for file in files {
+                    write!(
+                        &mut text,
+                        "\n`{}`:\n{}",
+                        file.path,
+                        MarkdownCodeBlock {
+                            tag: "",
+                            text: &file.text
+                        }
+                    )
+                    .ok();
+                }
+                text.push_str("</rules>");
+                vec![LanguageModelRequestMessage {
+                    role: Role::User,
+                    content: vec![text.into()],
+                    cache: false,
+                }]
+            }
         }
     }
 
@@ -125,12 +161,19 @@ impl Message {
             Message::User(message) => message.to_markdown(),
             Message::Agent(message) => message.to_markdown(),
             Message::Resume => "[resume]\n".into(),
+            Message::NestedRules(files) => {
+                let mut markdown = String::new();
+                for file in files {
+                    writeln!(&mut markdown, "[rules: {}]", file.path).ok();
+                }
+                markdown
+            }
         }
     }
 
     pub fn role(&self) -> Role {
         match self {
-            Message::User(_) | Message::Resume => Role::User,
+            Message::User(_) | Message::Resume | Message::NestedRules(_) => Role::User,
             Message::Agent(_) => Role::Assistant,
         }
     }
@@ -192,6 +235,7 @@ impl UserMessage {
         const OPEN_SELECTIONS_TAG: &str = "<selections>";
         const OPEN_THREADS_TAG: &str = "<threads>";
         const OPEN_FETCH_TAG: &str = "<fetched_urls>";
+        const OPEN_MCP_RESOURCES_TAG: &str = "<mcp_resources>";
         const OPEN_RULES_TAG: &str =
             "<rules>\nThe user has specified the following rules that should be applied:\n";
 
@@ -201,6 +245,7 @@ impl UserMessage {
         let mut selection_context = OPEN_SELECTIONS_TAG.to_string();
         let mut thread_context = OPEN_THREADS_TAG.to_string();
         let mut fetch_context = OPEN_FETCH_TAG.to_string();
+        let mut mcp_resource_context = OPEN_MCP_RESOURCES_TAG.to_string();
         let mut rules_context = OPEN_RULES_TAG.to_string();
 
         for chunk in &self.content {
@@ -283,6 +328,14 @@ impl UserMessage {
                         MentionUri::Fetch { url } => {
                             write!(&mut fetch_context, "\nFetch: {}\n\n{}", url, content).ok();
                         }
+                        MentionUri::McpResource { server_id, uri, .. } => {
+                            write!(
+                                &mut mcp_resource_context,
+                                "\nResource: {} (from the {} MCP server)\n\n{}",
+                                uri, server_id, content
+                            )
+                            .ok();
+                        }
                     }
 
                     language_model::MessageContent::Text(uri.as_link().to_string())
@@ -336,6 +389,13 @@ impl UserMessage {
                 .push(language_model::MessageContent::Text(fetch_context));
         }
 
+        if mcp_resource_context.len() > OPEN_MCP_RESOURCES_TAG.len() {
+            mcp_resource_context.push_str("</mcp_resources>\n");
+            message
+                .content
+                .push(language_model::MessageContent::Text(mcp_resource_context));
+        }
+
         if rules_context.len() > OPEN_RULES_TAG.len() {
             rules_context.push_str("</user_rules>\n");
             message
@@ -699,7 +759,7 @@ impl Thread {
                         }
                     }
                 }
-                Message::Resume => {}
+                Message::Resume | Message::NestedRules(_) => {}
             }
         }
         rx
@@ -971,6 +1031,62 @@ impl Thread {
         &self.project_context
     }
 
+    /// The project context the thread is working with, which only includes the rules files of
+    /// subdirectories containing files the agent has read or edited.
+    pub fn active_project_context(&self, cx: &App) -> ProjectContext {
+        let project = self.project.read(cx);
+        let abs_paths = self
+            .action_log
+            .read(cx)
+            .tracked_buffers()
+            .filter_map(|buffer| {
+                let project_path = buffer.read(cx).project_path(cx)?;
+                project.absolute_path(&project_path, cx)
+            })
+            .collect::<Vec<_>>();
+        let mut project_context = self.project_context.read(cx).clone();
+        project_context.retain_nested_rules_for_paths(&abs_paths);
+        project_context
+    }
+
+    /// Adds the rules files that came into play since the last request, because the agent read
+    /// or edited a file in their directory, to the thread. Rules files that changed since they
+    /// were added are added again.
+    fn add_nested_rules(&mut self, cx: &App) {
+        let added_files = self
+            .messages
+            .iter()
+            .filter_map(|message| match message {
+                Message::NestedRules(files) => Some(files),
+                _ => None,
+            })
+            .flatten()
+            .cloned()
+            .collect::<HashSet<_>>();
+        let files = self
+            .active_project_context(cx)
+            .worktrees
+            .into_iter()
+            .flat_map(|worktree| {
+                worktree
+                    .nested_rules_files
+                    .into_iter()
+                    .map(move |rules_file| NestedRulesFile {
+                        path: format!(
+                            "{}/{}",
+                            worktree.root_name,
+                            rules_file.path_in_worktree.as_unix_str()
+                        ),
+                        text: rules_file.text,
+                    })
+            })
+            .filter(|file| !added_files.contains(file))
+            .collect::<Vec<_>>();
+        if !files.is_empty() {
+            self.messages.push(Message::NestedRules(files));
+        }
+    }
+
     pub fn project(&self) -> &Entity<Project> {
         &self.project
     }
@@ -1054,8 +1170,12 @@ impl Thread {
             language_registry,
         ));
         self.add_tool(FetchTool::new(self.project.read(cx).client().http_client()));
+        self.add_tool(FindDefinitionTool::new(self.project.clone()));
         self.add_tool(FindPathTool::new(self.project.clone()));
+        self.add_tool(FindReferencesTool::new(self.project.clone()));
+        self.add_tool(FindSymbolsTool::new(self.project.clone()));
         self.add_tool(GrepTool::new(self.project.clone()));
+        self.add_tool(HoverTool::new(self.project.clone()));
         self.add_tool(ListDirectoryTool::new(self.project.clone()));
         self.add_tool(MovePathTool::new(self.project.clone()));
         self.add_tool(NowTool);
@@ -1064,6 +1184,13 @@ impl Thread {
             self.project.clone(),
             self.action_log.clone(),
         ));
+        self.add_tool(RenameSymbolTool::new(
+            self.project.clone(),
+            self.action_log.clone(),
+        ));
+        if SemanticIndex::global(cx).is_some_and(|index| index.read(cx).is_enabled()) {
+            self.add_tool(SemanticSearchTool::new(self.project.clone()));
+        }
         self.add_tool(TerminalTool::new(self.project.clone(), environment));
         self.add_tool(ThinkingTool);
         self.add_tool(WebSearchTool);
@@ -1116,7 +1243,7 @@ impl Thread {
                 Message::User(message) => {
                     self.request_token_usage.remove(&message.id);
                 }
-                Message::Agent(_) | Message::Resume => {}
+                Message::Agent(_) | Message::Resume | Message::NestedRules(_) => {}
             }
         }
         self.summary = None;
@@ -1236,8 +1363,10 @@ impl Thread {
         let mut attempt = 0;
         let mut intent = CompletionIntent::UserPrompt;
         loop {
-            let request =
-                this.update(cx, |this, cx| this.build_completion_request(intent, cx))??;
+            let request = this.update(cx, |this, cx| {
+                this.add_nested_rules(cx);
+                this.build_completion_request(intent, cx)
+            })??;
 
             telemetry::event!(
                 "Agent Thread Completion",
@@ -1555,15 +1684,53 @@ impl Thread {
             }));
         };
 
+        let permission = ToolCallPermission::new(
+            &tool_use.name,
+            tool.permission_subjects(&tool_use.input),
+            &self.project,
+            cx,
+        );
+        if permission.action == Some(ToolPermissionAction::Deny) {
+            let content = format!(
+                "Permission to run {} was denied by the tool permission rules",
+                tool_use.name
+            );
+            return Some(Task::ready(LanguageModelToolResult {
+                content: LanguageModelToolResultContent::Text(Arc::from(content)),
+                tool_use_id: tool_use.id,
+                tool_name: tool_use.name,
+                is_error: true,
+                output: None,
+            }));
+        }
+
         let fs = self.project.read(cx).fs().clone();
         let tool_event_stream =
-            ToolCallEventStream::new(tool_use.id.clone(), event_stream.clone(), Some(fs));
+            ToolCallEventStream::new(tool_use.id.clone(), event_stream.clone(), Some(fs))
+                .with_permission(permission.clone());
         tool_event_stream.update_fields(acp::ToolCallUpdateFields {
             status: Some(acp::ToolCallStatus::InProgress),
             ..Default::default()
         });
         let supports_images = self.model().is_some_and(|model| model.supports_images());
-        let tool_result = tool.run(tool_use.input, tool_event_stream, cx);
+        let tool_result = if permission.action == Some(ToolPermissionAction::Ask) {
+            // Tools that don't ask for permission themselves are asked for here, after which the
+            // tool runs as if it was allowed so that it doesn't ask again.
+            let title = tool.initial_title(tool_use.input.clone(), cx);
+            let authorize = tool_event_stream.authorize(title, cx);
+            let tool_event_stream = tool_event_stream.with_permission(ToolCallPermission {
+                action: Some(ToolPermissionAction::Allow),
+                ..permission
+            });
+            let input = tool_use.input.clone();
+            cx.spawn(async move |_, cx| {
+                authorize.await?;
+                cx.update(|cx| tool.run(input, tool_event_stream, cx))?
+                    .await
+            })
+        } else {
+            tool.run(tool_use.input, tool_event_stream, cx)
+        };
         log::debug!("Running tool {}", tool_use.name);
         Some(cx.foreground_executor().spawn(async move {
             let tool_result = tool_result.await.and_then(|output| {
@@ -1766,7 +1933,7 @@ impl Thread {
             .find_map(|message| match message {
                 Message::User(user_message) => Some(user_message),
                 Message::Agent(_) => None,
-                Message::Resume => None,
+                Message::Resume | Message::NestedRules(_) => None,
             })
     }
 
@@ -1933,8 +2100,12 @@ impl Thread {
             self.messages.len()
         );
 
+        // Nested rules files are added to the thread as they come into play instead, so that the
+        // system prompt doesn't change as the agent works.
+        let mut project_context = self.project_context.read(cx).clone();
+        project_context.retain_nested_rules_for_paths(&[]);
         let system_prompt = SystemPromptTemplate {
-            project: self.project_context.read(cx),
+            project: &project_context,
             available_tools: self.tools.keys().cloned().collect(),
         }
         .render(&self.templates)
@@ -2155,6 +2326,11 @@ where
         true
     }
 
+    /// The paths or commands the tool works on, which tool permission rules are matched against.
+    fn permission_subjects(&self, _input: &Self::Input) -> Vec<ToolPermissionSubject> {
+        Vec::new()
+    }
+
     /// Runs the tool with the provided input.
     fn run(
         self: Arc<Self>,
@@ -2195,6 +2371,9 @@ pub trait AnyAgentTool {
     fn supported_provider(&self, _provider: &LanguageModelProviderId) -> bool {
         true
     }
+    fn permission_subjects(&self, _input: &serde_json::Value) -> Vec<ToolPermissionSubject> {
+        Vec::new()
+    }
     fn run(
         self: Arc<Self>,
         input: serde_json::Value,
@@ -2241,6 +2420,12 @@ where
         self.0.supported_provider(provider)
     }
 
+    fn permission_subjects(&self, input: &serde_json::Value) -> Vec<ToolPermissionSubject> {
+        serde_json::from_value(input.clone())
+            .map(|input| self.0.permission_subjects(&input))
+            .unwrap_or_default()
+    }
+
     fn run(
         self: Arc<Self>,
         input: serde_json::Value,
@@ -2372,6 +2557,7 @@ pub struct ToolCallEventStream {
     tool_use_id: LanguageModelToolUseId,
     stream: ThreadEventStream,
     fs: Option<Arc<dyn Fs>>,
+    permission: ToolCallPermission,
 }
 
 impl ToolCallEventStream {
@@ -2393,9 +2579,15 @@ impl ToolCallEventStream {
             tool_use_id,
             stream,
             fs,
+            permission: ToolCallPermission::default(),
         }
     }
 
+    fn with_permission(mut self, permission: ToolCallPermission) -> Self {
+        self.permission = permission;
+        self
+    }
+
     pub fn update_fields(&self, fields: acp::ToolCallUpdateFields) {
         self.stream
             .update_tool_call_fields(&self.tool_use_id, fields);
@@ -2414,11 +2606,75 @@ impl ToolCallEventStream {
             .ok();
     }
 
+    /// Matches the tool permission rules against paths or commands the tool only finds out about
+    /// while it runs, asking for permission or failing when the rules for any of them say to.
+    pub fn authorize_subjects(
+        &self,
+        tool: &str,
+        subjects: Vec<ToolPermissionSubject>,
+        title: impl Into<String>,
+        project: &Entity<Project>,
+        cx: &mut App,
+    ) -> Task<Result<()>> {
+        let permission = ToolCallPermission::new(tool, subjects, project, cx);
+        match permission.action {
+            Some(ToolPermissionAction::Ask | ToolPermissionAction::Deny) => self
+                .clone()
+                .with_permission(permission)
+                .authorize(title, cx),
+            Some(ToolPermissionAction::Allow) | None => Task::ready(Ok(())),
+        }
+    }
+
     pub fn authorize(&self, title: impl Into<String>, cx: &mut App) -> Task<Result<()>> {
-        if agent_settings::AgentSettings::get_global(cx).always_allow_tool_actions {
-            return Task::ready(Ok(()));
+        match self.permission.action {
+            Some(ToolPermissionAction::Allow) => return Task::ready(Ok(())),
+            Some(ToolPermissionAction::Deny) => {
+                return Task::ready(Err(anyhow!(
+                    "Permission to run tool denied by the tool permission rules"
+                )));
+            }
+            Some(ToolPermissionAction::Ask) => {}
+            None => {
+                if agent_settings::AgentSettings::get_global(cx).always_allow_tool_actions {
+                    return Task::ready(Ok(()));
+                }
+            }
         }
 
+        let mut options = Vec::new();
+        if let Some(suggested_rule) = &self.permission.suggested_rule {
+            options.push(acp::PermissionOption {
+                id: acp::PermissionOptionId("always_allow_pattern".into()),
+                name: format!(
+                    "Always Allow `{}` in All Projects",
+                    suggested_rule.pattern()
+                ),
+                kind: acp::PermissionOptionKind::AllowAlways,
+                meta: None,
+            });
+        }
+        options.extend([
+            acp::PermissionOption {
+                id: acp::PermissionOptionId("always_allow".into()),
+                name: "Always Allow".into(),
+                kind: acp::PermissionOptionKind::AllowAlways,
+                meta: None,
+            },
+            acp::PermissionOption {
+                id: acp::PermissionOptionId("allow".into()),
+                name: "Allow".into(),
+                kind: acp::PermissionOptionKind::AllowOnce,
+                meta: None,
+            },
+            acp::PermissionOption {
+                id: acp::PermissionOptionId("deny".into()),
+                name: "Deny".into(),
+                kind: acp::PermissionOptionKind::RejectOnce,
+                meta: None,
+            },
+        ]);
+
         let (response_tx, response_rx) = oneshot::channel();
         self.stream
             .0
@@ -2432,32 +2688,20 @@ impl ToolCallEventStream {
                             ..Default::default()
                         },
                     },
-                    options: vec![
-                        acp::PermissionOption {
-                            id: acp::PermissionOptionId("always_allow".into()),
-                            name: "Always Allow".into(),
-                            kind: acp::PermissionOptionKind::AllowAlways,
-                            meta: None,
-                        },
-                        acp::PermissionOption {
-                            id: acp::PermissionOptionId("allow".into()),
-                            name: "Allow".into(),
-                            kind: acp::PermissionOptionKind::AllowOnce,
-                            meta: None,
-                        },
-                        acp::PermissionOption {
-                            id: acp::PermissionOptionId("deny".into()),
-                            name: "Deny".into(),
-                            kind: acp::PermissionOptionKind::RejectOnce,
-                            meta: None,
-                        },
-                    ],
+                    options,
                     response: response_tx,
                 },
             )))
             .ok();
         let fs = self.fs.clone();
+        let suggested_rule = self.permission.suggested_rule.clone();
         cx.spawn(async move |cx| match response_rx.await?.0.as_ref() {
+            "always_allow_pattern" => {
+                if let Some((fs, suggested_rule)) = fs.zip(suggested_rule) {
+                    suggested_rule.save(fs, cx).log_err();
+                }
+                Ok(())
+            }
             "always_allow" => {
                 if let Some(fs) = fs.clone() {
                     cx.update(|cx| {
@@ -0,0 +1,136 @@
+use std::sync::Arc;
+
+use agent_settings::{AgentSettings, ToolPermissionSubject};
+use anyhow::Result;
+use fs::Fs;
+use gpui::{App, AsyncApp, Entity};
+use project::{Project, WorktreeId};
+use settings::{
+    Settings as _, SettingsLocation, ToolPermissionAction, ToolPermissionRuleContent,
+    update_settings_file,
+};
+use util::rel_path::RelPath;
+
+/// How the tool permission rules in the settings apply to a tool call.
+#[derive(Clone, Debug, Default)]
+pub struct ToolCallPermission {
+    /// What the rules decided, or `None` when no rule applies and `always_allow_tool_actions`
+    /// decides instead.
+    pub action: Option<ToolPermissionAction>,
+    /// A rule allowing this call and others like it, offered when asking for permission.
+    pub suggested_rule: Option<SuggestedToolPermissionRule>,
+}
+
+#[derive(Clone, Debug)]
+pub struct SuggestedToolPermissionRule {
+    pub rule: ToolPermissionRuleContent,
+}
+
+impl ToolCallPermission {
+    pub fn new(
+        tool: &str,
+        subjects: Vec<ToolPermissionSubject>,
+        project: &Entity<Project>,
+        cx: &App,
+    ) -> Self {
+        let project = project.read(cx);
+        let default_worktree_id = project
+            .visible_worktrees(cx)
+            .next()
+            .map(|worktree| worktree.read(cx).id());
+
+        // Rules match paths relative to their worktree, and come from that worktree's settings.
+        let subjects = subjects
+            .into_iter()
+            .map(|subject| match subject {
+                ToolPermissionSubject::Path(path) => match project.find_project_path(&path, cx) {
+                    Some(project_path) => (
+                        ToolPermissionSubject::Path(project_path.path.as_unix_str().to_string()),
+                        Some(project_path.worktree_id),
+                    ),
+                    None => (ToolPermissionSubject::Path(path), None),
+                },
+                ToolPermissionSubject::Command(_) => (subject, default_worktree_id),
+            })
+            .collect::<Vec<_>>();
+
+        let action_for = |subject: Option<&ToolPermissionSubject>,
+                          worktree_id: Option<WorktreeId>| {
+            let location = worktree_id.map(|worktree_id| SettingsLocation {
+                worktree_id,
+                path: RelPath::empty(),
+            });
+            AgentSettings::get(location, cx)
+                .tool_permissions
+                .action(tool, subject)
+        };
+        let action = if subjects.is_empty() {
+            action_for(None, default_worktree_id)
+        } else {
+            let actions = subjects
+                .iter()
+                .map(|(subject, worktree_id)| action_for(Some(subject), *worktree_id))
+                .collect::<Vec<_>>();
+            // The call is only allowed when everything it works on is, but asking or denying
+            // for any of it applies to the whole call.
+            if actions
+                .iter()
+                .all(|action| *action == Some(ToolPermissionAction::Allow))
+            {
+                Some(ToolPermissionAction::Allow)
+            } else {
+                actions
+                    .into_iter()
+                    .flatten()
+                    .filter(|action| *action != ToolPermissionAction::Allow)
+                    .max()
+            }
+        };
+
+        let suggested_rule = subjects.into_iter().next().map(|(subject, _)| {
+            let pattern = subject.suggested_pattern();
+            let (path, command) = match subject {
+                ToolPermissionSubject::Path(_) => (Some(pattern), None),
+                ToolPermissionSubject::Command(_) => (None, Some(pattern)),
+            };
+            SuggestedToolPermissionRule {
+                rule: ToolPermissionRuleContent {
+                    tool: tool.into(),
+                    path,
+                    command,
+                    action: ToolPermissionAction::Allow,
+                },
+            }
+        });
+
+        Self {
+            action,
+            suggested_rule,
+        }
+    }
+}
+
+impl SuggestedToolPermissionRule {
+    pub fn pattern(&self) -> &str {
+        self.rule
+            .path
+            .as_deref()
+            .or(self.rule.command.as_deref())
+            .unwrap_or_default()
+    }
+
+    /// Adds the rule to the user settings, since `allow` rules aren't honored in project settings.
+    pub fn save(self, fs: Arc<dyn Fs>, cx: &mut AsyncApp) -> Result<()> {
+        let rule = self.rule;
+        cx.update(|cx| {
+            update_settings_file(fs, cx, move |settings, _| {
+                settings
+                    .project
+                    .agent_tool_permissions
+                    .get_or_insert_default()
+                    .0
+                    .push(rule);
+            })
+        })
+    }
+}
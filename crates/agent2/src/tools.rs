@@ -5,13 +5,20 @@ mod delete_path_tool;
 mod diagnostics_tool;
 mod edit_file_tool;
 mod fetch_tool;
+mod find_definition_tool;
 mod find_path_tool;
+mod find_references_tool;
+mod find_symbols_tool;
 mod grep_tool;
+mod hover_tool;
 mod list_directory_tool;
 mod move_path_tool;
 mod now_tool;
 mod open_tool;
 mod read_file_tool;
+mod rename_symbol_tool;
+mod semantic_search_tool;
+mod symbol_position;
 mod terminal_tool;
 mod thinking_tool;
 mod web_search_tool;
@@ -25,13 +32,19 @@ pub fn default_tool_names() -> impl Iterator<Item = &'static str> {
         DiagnosticsTool::name(),
         EditFileTool::name(),
         FetchTool::name(),
+        FindDefinitionTool::name(),
         FindPathTool::name(),
+        FindReferencesTool::name(),
+        FindSymbolsTool::name(),
         GrepTool::name(),
+        HoverTool::name(),
         ListDirectoryTool::name(),
         MovePathTool::name(),
         NowTool::name(),
         OpenTool::name(),
         ReadFileTool::name(),
+        RenameSymbolTool::name(),
+        SemanticSearchTool::name(),
         TerminalTool::name(),
         ThinkingTool::name(),
         WebSearchTool::name(),
@@ -46,13 +59,19 @@ pub use delete_path_tool::*;
 pub use diagnostics_tool::*;
 pub use edit_file_tool::*;
 pub use fetch_tool::*;
+pub use find_definition_tool::*;
 pub use find_path_tool::*;
+pub use find_references_tool::*;
+pub use find_symbols_tool::*;
 pub use grep_tool::*;
+pub use hover_tool::*;
 pub use list_directory_tool::*;
 pub use move_path_tool::*;
 pub use now_tool::*;
 pub use open_tool::*;
 pub use read_file_tool::*;
+pub use rename_symbol_tool::*;
+pub use semantic_search_tool::*;
 pub use terminal_tool::*;
 pub use thinking_tool::*;
 pub use web_search_tool::*;
@@ -108,6 +108,7 @@ impl ContextServerRegistry {
                     }
                 }
             }
+            project::context_server_store::Event::ResourceUpdated { .. } => {}
         }
     }
 }
@@ -1,5 +1,6 @@
 use crate::{AgentTool, ToolCallEventStream};
 use agent_client_protocol::ToolKind;
+use agent_settings::ToolPermissionSubject;
 use anyhow::{Context as _, Result, anyhow};
 use gpui::{App, AppContext, Entity, Task};
 use project::Project;
@@ -58,6 +59,13 @@ impl AgentTool for CopyPathTool {
         ToolKind::Move
     }
 
+    fn permission_subjects(&self, input: &Self::Input) -> Vec<ToolPermissionSubject> {
+        vec![
+            ToolPermissionSubject::Path(input.source_path.clone()),
+            ToolPermissionSubject::Path(input.destination_path.clone()),
+        ]
+    }
+
     fn initial_title(
         &self,
         input: Result<Self::Input, serde_json::Value>,
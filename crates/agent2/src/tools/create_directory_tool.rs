@@ -1,4 +1,5 @@
 use agent_client_protocol::ToolKind;
+use agent_settings::ToolPermissionSubject;
 use anyhow::{Context as _, Result, anyhow};
 use gpui::{App, Entity, SharedString, Task};
 use project::Project;
@@ -49,6 +50,10 @@ impl AgentTool for CreateDirectoryTool {
         ToolKind::Read
     }
 
+    fn permission_subjects(&self, input: &Self::Input) -> Vec<ToolPermissionSubject> {
+        vec![ToolPermissionSubject::Path(input.path.clone())]
+    }
+
     fn initial_title(
         &self,
         input: Result<Self::Input, serde_json::Value>,
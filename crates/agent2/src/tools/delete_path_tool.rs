@@ -1,6 +1,7 @@
 use crate::{AgentTool, ToolCallEventStream};
 use action_log::ActionLog;
 use agent_client_protocol::ToolKind;
+use agent_settings::ToolPermissionSubject;
 use anyhow::{Context as _, Result, anyhow};
 use futures::{SinkExt, StreamExt, channel::mpsc};
 use gpui::{App, AppContext, Entity, SharedString, Task};
@@ -52,6 +53,10 @@ impl AgentTool for DeletePathTool {
         ToolKind::Delete
     }
 
+    fn permission_subjects(&self, input: &Self::Input) -> Vec<ToolPermissionSubject> {
+        vec![ToolPermissionSubject::Path(input.path.clone())]
+    }
+
     fn initial_title(
         &self,
         input: Result<Self::Input, serde_json::Value>,
@@ -1,6 +1,7 @@
 use crate::{AgentTool, Thread, ToolCallEventStream};
 use acp_thread::Diff;
 use agent_client_protocol::{self as acp, ToolCallLocation, ToolCallUpdateFields};
+use agent_settings::ToolPermissionSubject;
 use anyhow::{Context as _, Result, anyhow};
 use assistant_tools::edit_agent::{EditAgent, EditAgentOutput, EditAgentOutputEvent, EditFormat};
 use cloud_llm_client::CompletionIntent;
@@ -203,6 +204,12 @@ impl AgentTool for EditFileTool {
         acp::ToolKind::Edit
     }
 
+    fn permission_subjects(&self, input: &Self::Input) -> Vec<ToolPermissionSubject> {
+        vec![ToolPermissionSubject::Path(
+            input.path.to_string_lossy().into_owned(),
+        )]
+    }
+
     fn initial_title(
         &self,
         input: Result<Self::Input, serde_json::Value>,
@@ -0,0 +1,187 @@
+use crate::tools::symbol_position::{describe_location, resolve_symbol_position};
+use crate::{AgentTool, ToolCallEventStream};
+use agent_client_protocol as acp;
+use anyhow::Result;
+use gpui::{App, Entity, SharedString, Task};
+use project::Project;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::{fmt::Write, sync::Arc};
+use util::markdown::MarkdownInlineCode;
+
+/// Finds where a symbol is defined, using the language server for the file it appears in.
+///
+/// Prefer this over searching the project with `grep` when you need the definition of a specific function, type, or variable that you can see used in a file: it resolves the exact symbol, including through imports and into dependencies.
+///
+/// <example>
+/// To find the definition of `parse_config` called on line 42 of `src/main.rs`:
+/// {
+///     "path": "project/src/main.rs",
+///     "line": 42,
+///     "symbol": "parse_config"
+/// }
+/// </example>
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct FindDefinitionToolInput {
+    /// The path of the file the symbol appears in.
+    ///
+    /// This path should never be absolute, and the first component
+    /// of the path should always be a root directory in a project.
+    pub path: String,
+    /// The 1-based line number the symbol appears on.
+    pub line: u32,
+    /// The symbol to find the definition of, exactly as it appears on the line.
+    pub symbol: String,
+}
+
+pub struct FindDefinitionTool {
+    project: Entity<Project>,
+}
+
+impl FindDefinitionTool {
+    pub fn new(project: Entity<Project>) -> Self {
+        Self { project }
+    }
+}
+
+impl AgentTool for FindDefinitionTool {
+    type Input = FindDefinitionToolInput;
+    type Output = String;
+
+    fn name() -> &'static str {
+        "find_definition"
+    }
+
+    fn kind() -> acp::ToolKind {
+        acp::ToolKind::Search
+    }
+
+    fn initial_title(
+        &self,
+        input: Result<Self::Input, serde_json::Value>,
+        _cx: &mut App,
+    ) -> SharedString {
+        if let Ok(input) = input {
+            format!("Find definition of {}", MarkdownInlineCode(&input.symbol)).into()
+        } else {
+            "Find definition".into()
+        }
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: Self::Input,
+        _event_stream: ToolCallEventStream,
+        cx: &mut App,
+    ) -> Task<Result<Self::Output>> {
+        let position =
+            resolve_symbol_position(&self.project, &input.path, input.line, &input.symbol, cx);
+        let project = self.project.clone();
+
+        cx.spawn(async move |cx| {
+            let (buffer, position) = position.await?;
+            let definitions = project
+                .update(cx, |project, cx| project.definitions(&buffer, position, cx))?
+                .await?
+                .unwrap_or_default();
+            if definitions.is_empty() {
+                return Ok(format!(
+                    "No definition found for `{}` on line {} of {}",
+                    input.symbol, input.line, input.path
+                ));
+            }
+
+            cx.update(|cx| {
+                let mut output = format!("Definitions of `{}`:\n", input.symbol);
+                for definition in &definitions {
+                    let target = &definition.target;
+                    writeln!(
+                        output,
+                        "- {}",
+                        describe_location(&target.buffer, &target.range, cx)
+                    )?;
+                }
+                anyhow::Ok(output)
+            })?
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::symbol_position::tests::{
+        FakeServerProject, fake_server_project, greet_declaration,
+    };
+    use gpui::TestAppContext;
+
+    #[gpui::test]
+    async fn test_find_definition(cx: &mut TestAppContext) {
+        let FakeServerProject {
+            project, server, ..
+        } = fake_server_project(
+            lsp::ServerCapabilities {
+                definition_provider: Some(lsp::OneOf::Left(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+        server.set_request_handler::<lsp::request::GotoDefinition, _, _>(|params, _| async move {
+            assert_eq!(
+                params.text_document_position_params.position,
+                lsp::Position::new(3, 12)
+            );
+            Ok(Some(lsp::GotoDefinitionResponse::Scalar(
+                greet_declaration(),
+            )))
+        });
+
+        let tool = Arc::new(FindDefinitionTool::new(project));
+        let input = FindDefinitionToolInput {
+            path: "root/src/main.rs".into(),
+            line: 4,
+            symbol: "greet".into(),
+        };
+        let output = cx
+            .update(|cx| tool.run(input, ToolCallEventStream::test().0, cx))
+            .await
+            .unwrap();
+        assert_eq!(
+            output,
+            "Definitions of `greet`:\n- root/src/helper.rs:1: pub fn greet(name: &str) {\n"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_find_definition_without_results(cx: &mut TestAppContext) {
+        let FakeServerProject {
+            project, server, ..
+        } = fake_server_project(
+            lsp::ServerCapabilities {
+                definition_provider: Some(lsp::OneOf::Left(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+        server.set_request_handler::<lsp::request::GotoDefinition, _, _>(
+            |_, _| async move { Ok(None) },
+        );
+
+        let tool = Arc::new(FindDefinitionTool::new(project));
+        let input = FindDefinitionToolInput {
+            path: "root/src/main.rs".into(),
+            line: 3,
+            symbol: "main".into(),
+        };
+        let output = cx
+            .update(|cx| tool.run(input, ToolCallEventStream::test().0, cx))
+            .await
+            .unwrap();
+        assert_eq!(
+            output,
+            "No definition found for `main` on line 3 of root/src/main.rs"
+        );
+    }
+}
@@ -0,0 +1,197 @@
+use crate::tools::symbol_position::{describe_location, resolve_symbol_position};
+use crate::{AgentTool, ToolCallEventStream};
+use agent_client_protocol as acp;
+use anyhow::Result;
+use gpui::{App, Entity, SharedString, Task};
+use project::Project;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::{fmt::Write, sync::Arc};
+use util::markdown::MarkdownInlineCode;
+
+const MAX_REFERENCES: usize = 100;
+
+/// Finds every reference to a symbol across the project, using the language server for the file it appears in.
+///
+/// Use this to understand how a function, type, or variable is used before changing it. Unlike `grep`, it only returns references to this exact symbol, not to others with the same name.
+///
+/// <example>
+/// To find everywhere `Config` declared on line 10 of `src/config.rs` is used:
+/// {
+///     "path": "project/src/config.rs",
+///     "line": 10,
+///     "symbol": "Config"
+/// }
+/// </example>
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct FindReferencesToolInput {
+    /// The path of the file the symbol appears in.
+    ///
+    /// This path should never be absolute, and the first component
+    /// of the path should always be a root directory in a project.
+    pub path: String,
+    /// The 1-based line number the symbol appears on.
+    pub line: u32,
+    /// The symbol to find references to, exactly as it appears on the line.
+    pub symbol: String,
+}
+
+pub struct FindReferencesTool {
+    project: Entity<Project>,
+}
+
+impl FindReferencesTool {
+    pub fn new(project: Entity<Project>) -> Self {
+        Self { project }
+    }
+}
+
+impl AgentTool for FindReferencesTool {
+    type Input = FindReferencesToolInput;
+    type Output = String;
+
+    fn name() -> &'static str {
+        "find_references"
+    }
+
+    fn kind() -> acp::ToolKind {
+        acp::ToolKind::Search
+    }
+
+    fn initial_title(
+        &self,
+        input: Result<Self::Input, serde_json::Value>,
+        _cx: &mut App,
+    ) -> SharedString {
+        if let Ok(input) = input {
+            format!("Find references to {}", MarkdownInlineCode(&input.symbol)).into()
+        } else {
+            "Find references".into()
+        }
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: Self::Input,
+        _event_stream: ToolCallEventStream,
+        cx: &mut App,
+    ) -> Task<Result<Self::Output>> {
+        let position =
+            resolve_symbol_position(&self.project, &input.path, input.line, &input.symbol, cx);
+        let project = self.project.clone();
+
+        cx.spawn(async move |cx| {
+            let (buffer, position) = position.await?;
+            let references = project
+                .update(cx, |project, cx| project.references(&buffer, position, cx))?
+                .await?
+                .unwrap_or_default();
+            if references.is_empty() {
+                return Ok(format!(
+                    "No references found for `{}` on line {} of {}",
+                    input.symbol, input.line, input.path
+                ));
+            }
+
+            cx.update(|cx| {
+                let mut output = format!(
+                    "Found {} references to `{}`:\n",
+                    references.len(),
+                    input.symbol
+                );
+                for reference in references.iter().take(MAX_REFERENCES) {
+                    writeln!(
+                        output,
+                        "- {}",
+                        describe_location(&reference.buffer, &reference.range, cx)
+                    )?;
+                }
+                if references.len() > MAX_REFERENCES {
+                    writeln!(output, "\nShowing the first {MAX_REFERENCES} references.")?;
+                }
+                anyhow::Ok(output)
+            })?
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::symbol_position::tests::{
+        FakeServerProject, fake_server_project, greet_call, greet_declaration,
+    };
+    use gpui::TestAppContext;
+
+    #[gpui::test]
+    async fn test_find_references(cx: &mut TestAppContext) {
+        let FakeServerProject {
+            project, server, ..
+        } = fake_server_project(
+            lsp::ServerCapabilities {
+                references_provider: Some(lsp::OneOf::Left(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+        server.set_request_handler::<lsp::request::References, _, _>(|params, _| async move {
+            assert_eq!(
+                params.text_document_position.position,
+                lsp::Position::new(0, 7)
+            );
+            Ok(Some(vec![greet_declaration(), greet_call()]))
+        });
+
+        let tool = Arc::new(FindReferencesTool::new(project));
+        let input = FindReferencesToolInput {
+            path: "root/src/helper.rs".into(),
+            line: 1,
+            symbol: "greet".into(),
+        };
+        let output = cx
+            .update(|cx| tool.run(input, ToolCallEventStream::test().0, cx))
+            .await
+            .unwrap();
+        assert_eq!(
+            output,
+            concat!(
+                "Found 2 references to `greet`:\n",
+                "- root/src/helper.rs:1: pub fn greet(name: &str) {\n",
+                "- root/src/main.rs:4: helper::greet(\"world\");\n",
+            )
+        );
+    }
+
+    #[gpui::test]
+    async fn test_find_references_without_results(cx: &mut TestAppContext) {
+        let FakeServerProject {
+            project, server, ..
+        } = fake_server_project(
+            lsp::ServerCapabilities {
+                references_provider: Some(lsp::OneOf::Left(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+        server.set_request_handler::<lsp::request::References, _, _>(|_, _| async move {
+            Ok(Some(Vec::new()))
+        });
+
+        let tool = Arc::new(FindReferencesTool::new(project));
+        let input = FindReferencesToolInput {
+            path: "root/src/main.rs".into(),
+            line: 3,
+            symbol: "main".into(),
+        };
+        let output = cx
+            .update(|cx| tool.run(input, ToolCallEventStream::test().0, cx))
+            .await
+            .unwrap();
+        assert_eq!(
+            output,
+            "No references found for `main` on line 3 of root/src/main.rs"
+        );
+    }
+}
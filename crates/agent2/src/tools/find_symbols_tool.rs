@@ -0,0 +1,323 @@
+use crate::{AgentTool, ToolCallEventStream};
+use agent_client_protocol as acp;
+use anyhow::{Result, anyhow};
+use gpui::{App, Entity, SharedString, Task};
+use project::{DocumentSymbol, Project, lsp_store::SymbolLocation};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::{fmt::Write, sync::Arc};
+use util::markdown::MarkdownInlineCode;
+
+const MAX_SYMBOLS: usize = 50;
+
+/// Finds symbols such as functions, types, and constants by name, using the project's language servers.
+///
+/// When a path is provided, returns an outline of the symbols in that file, limited to those matching the query if one is given.
+/// When no path is provided, searches the symbols of the whole project for the query.
+///
+/// Prefer this over `grep` when looking for where a function or type with a known name lives.
+///
+/// <example>
+/// To find symbols named like `ThreadStore` anywhere in the project:
+/// {
+///     "query": "ThreadStore"
+/// }
+///
+/// To get an outline of `src/main.rs`:
+/// {
+///     "query": "",
+///     "path": "project/src/main.rs"
+/// }
+/// </example>
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct FindSymbolsToolInput {
+    /// The name, or part of the name, of the symbols to find. May be empty when a path is provided.
+    pub query: String,
+    /// The path of a file to list the symbols of. If not provided, searches the whole project.
+    ///
+    /// This path should never be absolute, and the first component
+    /// of the path should always be a root directory in a project.
+    #[serde(default)]
+    pub path: Option<String>,
+}
+
+pub struct FindSymbolsTool {
+    project: Entity<Project>,
+}
+
+impl FindSymbolsTool {
+    pub fn new(project: Entity<Project>) -> Self {
+        Self { project }
+    }
+}
+
+impl AgentTool for FindSymbolsTool {
+    type Input = FindSymbolsToolInput;
+    type Output = String;
+
+    fn name() -> &'static str {
+        "find_symbols"
+    }
+
+    fn kind() -> acp::ToolKind {
+        acp::ToolKind::Search
+    }
+
+    fn initial_title(
+        &self,
+        input: Result<Self::Input, serde_json::Value>,
+        _cx: &mut App,
+    ) -> SharedString {
+        match input {
+            Ok(FindSymbolsToolInput {
+                path: Some(path), ..
+            }) => format!("List symbols in {}", MarkdownInlineCode(&path)).into(),
+            Ok(input) if !input.query.is_empty() => {
+                format!("Find symbols matching {}", MarkdownInlineCode(&input.query)).into()
+            }
+            _ => "Find symbols".into(),
+        }
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: Self::Input,
+        _event_stream: ToolCallEventStream,
+        cx: &mut App,
+    ) -> Task<Result<Self::Output>> {
+        let query = input.query.to_lowercase();
+
+        if let Some(path) = input.path {
+            let Some(project_path) = self.project.read(cx).find_project_path(&path, cx) else {
+                return Task::ready(Err(anyhow!("Could not find path {path} in project")));
+            };
+            let buffer = self
+                .project
+                .update(cx, |project, cx| project.open_buffer(project_path, cx));
+            let project = self.project.clone();
+
+            return cx.spawn(async move |cx| {
+                let buffer = buffer.await?;
+                let symbols = project
+                    .update(cx, |project, cx| project.document_symbols(&buffer, cx))?
+                    .await?;
+
+                let mut output = String::new();
+                for symbol in &symbols {
+                    write_outline(&mut output, symbol, &query, 0)?;
+                }
+                if output.is_empty() {
+                    Ok(format!("No symbols found in {path}"))
+                } else {
+                    Ok(output)
+                }
+            });
+        }
+
+        if query.is_empty() {
+            return Task::ready(Err(anyhow!(
+                "A query is required when searching the whole project"
+            )));
+        }
+        let symbols = self
+            .project
+            .update(cx, |project, cx| project.symbols(&input.query, cx));
+        let project = self.project.clone();
+
+        cx.spawn(async move |cx| {
+            let symbols = symbols.await?;
+            if symbols.is_empty() {
+                return Ok(format!("No symbols found matching `{}`", input.query));
+            }
+
+            project.read_with(cx, |project, cx| {
+                let mut output = String::new();
+                for symbol in symbols.iter().take(MAX_SYMBOLS) {
+                    let path = match &symbol.path {
+                        SymbolLocation::InProject(project_path) => {
+                            let Some(worktree) =
+                                project.worktree_for_id(project_path.worktree_id, cx)
+                            else {
+                                continue;
+                            };
+                            worktree.read(cx).full_path(&project_path.path)
+                        }
+                        SymbolLocation::OutsideProject { abs_path, .. } => abs_path.to_path_buf(),
+                    };
+                    writeln!(
+                        output,
+                        "- {:?} `{}` at {}:{}",
+                        symbol.kind,
+                        symbol.name,
+                        path.display(),
+                        symbol.range.start.0.row + 1
+                    )?;
+                }
+                if symbols.len() > MAX_SYMBOLS {
+                    writeln!(output, "\nShowing the first {MAX_SYMBOLS} symbols.")?;
+                }
+                anyhow::Ok(output)
+            })?
+        })
+    }
+}
+
+/// Writes the symbol and its children as an indented list, skipping those that don't match the
+/// query and have no children that do.
+fn write_outline(
+    output: &mut String,
+    symbol: &DocumentSymbol,
+    query: &str,
+    depth: usize,
+) -> Result<()> {
+    if !outline_matches(symbol, query) {
+        return Ok(());
+    }
+    writeln!(
+        output,
+        "{}- {:?} `{}` (lines {}-{})",
+        "  ".repeat(depth),
+        symbol.kind,
+        symbol.name,
+        symbol.range.start.0.row + 1,
+        symbol.range.end.0.row + 1
+    )?;
+    for child in &symbol.children {
+        write_outline(output, child, query, depth + 1)?;
+    }
+    Ok(())
+}
+
+fn outline_matches(symbol: &DocumentSymbol, query: &str) -> bool {
+    symbol.name.to_lowercase().contains(query)
+        || symbol
+            .children
+            .iter()
+            .any(|child| outline_matches(child, query))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::symbol_position::tests::{
+        FakeServerProject, fake_server_project, greet_declaration,
+    };
+    use gpui::TestAppContext;
+
+    #[gpui::test]
+    async fn test_find_symbols_in_file(cx: &mut TestAppContext) {
+        let FakeServerProject {
+            project, server, ..
+        } = fake_server_project(
+            lsp::ServerCapabilities {
+                document_symbol_provider: Some(lsp::OneOf::Left(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+        server.set_request_handler::<lsp::request::DocumentSymbolRequest, _, _>(
+            |_, _| async move {
+                let range = |start, end| {
+                    lsp::Range::new(lsp::Position::new(start, 0), lsp::Position::new(end, 0))
+                };
+                #[allow(deprecated)]
+                let symbol = |name: &str, kind, start, end, children| lsp::DocumentSymbol {
+                    name: name.into(),
+                    detail: None,
+                    kind,
+                    tags: None,
+                    deprecated: None,
+                    range: range(start, end),
+                    selection_range: range(start, start),
+                    children: Some(children),
+                };
+                Ok(Some(lsp::DocumentSymbolResponse::Nested(vec![
+                    symbol("helper", lsp::SymbolKind::MODULE, 0, 0, Vec::new()),
+                    symbol(
+                        "main",
+                        lsp::SymbolKind::FUNCTION,
+                        2,
+                        4,
+                        vec![symbol("name", lsp::SymbolKind::VARIABLE, 3, 3, Vec::new())],
+                    ),
+                ])))
+            },
+        );
+
+        let tool = Arc::new(FindSymbolsTool::new(project));
+        let run = |query: &str| {
+            let input = FindSymbolsToolInput {
+                query: query.into(),
+                path: Some("root/src/main.rs".into()),
+            };
+            cx.update(|cx| tool.clone().run(input, ToolCallEventStream::test().0, cx))
+        };
+
+        assert_eq!(
+            run("").await.unwrap(),
+            concat!(
+                "- MODULE `helper` (lines 1-1)\n",
+                "- FUNCTION `main` (lines 3-5)\n",
+                "  - VARIABLE `name` (lines 4-4)\n",
+            )
+        );
+        // Symbols are kept when any of their children match.
+        assert_eq!(
+            run("NAME").await.unwrap(),
+            "- FUNCTION `main` (lines 3-5)\n  - VARIABLE `name` (lines 4-4)\n"
+        );
+        assert_eq!(
+            run("missing").await.unwrap(),
+            "No symbols found in root/src/main.rs"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_find_symbols_in_project(cx: &mut TestAppContext) {
+        let FakeServerProject {
+            project, server, ..
+        } = fake_server_project(
+            lsp::ServerCapabilities {
+                workspace_symbol_provider: Some(lsp::OneOf::Left(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+        server.set_request_handler::<lsp::request::WorkspaceSymbolRequest, _, _>(
+            |params, _| async move {
+                assert_eq!(params.query, "gree");
+                Ok(Some(lsp::WorkspaceSymbolResponse::Flat(vec![
+                    #[allow(deprecated)]
+                    lsp::SymbolInformation {
+                        name: "greet".into(),
+                        kind: lsp::SymbolKind::FUNCTION,
+                        tags: None,
+                        deprecated: None,
+                        location: greet_declaration(),
+                        container_name: None,
+                    },
+                ])))
+            },
+        );
+
+        let tool = Arc::new(FindSymbolsTool::new(project));
+        let run = |query: &str| {
+            let input = FindSymbolsToolInput {
+                query: query.into(),
+                path: None,
+            };
+            cx.update(|cx| tool.clone().run(input, ToolCallEventStream::test().0, cx))
+        };
+
+        assert_eq!(
+            run("gree").await.unwrap(),
+            "- FUNCTION `greet` at root/src/helper.rs:1\n"
+        );
+        assert_eq!(
+            run("").await.unwrap_err().to_string(),
+            "A query is required when searching the whole project"
+        );
+    }
+}
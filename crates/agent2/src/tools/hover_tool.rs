@@ -0,0 +1,167 @@
+use crate::tools::symbol_position::resolve_symbol_position;
+use crate::{AgentTool, ToolCallEventStream};
+use agent_client_protocol as acp;
+use anyhow::Result;
+use gpui::{App, Entity, SharedString, Task};
+use project::{HoverBlockKind, Project};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use util::markdown::MarkdownInlineCode;
+
+/// Shows the type and documentation of a symbol, as the language server for its file describes it when hovering over the symbol in the editor.
+///
+/// Use this to find out the inferred type of a variable, the signature of a function, or the documentation of a type without reading its definition.
+///
+/// <example>
+/// To get the type of `settings` on line 18 of `src/app.rs`:
+/// {
+///     "path": "project/src/app.rs",
+///     "line": 18,
+///     "symbol": "settings"
+/// }
+/// </example>
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct HoverToolInput {
+    /// The path of the file the symbol appears in.
+    ///
+    /// This path should never be absolute, and the first component
+    /// of the path should always be a root directory in a project.
+    pub path: String,
+    /// The 1-based line number the symbol appears on.
+    pub line: u32,
+    /// The symbol to describe, exactly as it appears on the line.
+    pub symbol: String,
+}
+
+pub struct HoverTool {
+    project: Entity<Project>,
+}
+
+impl HoverTool {
+    pub fn new(project: Entity<Project>) -> Self {
+        Self { project }
+    }
+}
+
+impl AgentTool for HoverTool {
+    type Input = HoverToolInput;
+    type Output = String;
+
+    fn name() -> &'static str {
+        "hover"
+    }
+
+    fn kind() -> acp::ToolKind {
+        acp::ToolKind::Read
+    }
+
+    fn initial_title(
+        &self,
+        input: Result<Self::Input, serde_json::Value>,
+        _cx: &mut App,
+    ) -> SharedString {
+        if let Ok(input) = input {
+            format!("Get type info for {}", MarkdownInlineCode(&input.symbol)).into()
+        } else {
+            "Get type info".into()
+        }
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: Self::Input,
+        _event_stream: ToolCallEventStream,
+        cx: &mut App,
+    ) -> Task<Result<Self::Output>> {
+        let position =
+            resolve_symbol_position(&self.project, &input.path, input.line, &input.symbol, cx);
+        let project = self.project.clone();
+
+        cx.spawn(async move |cx| {
+            let (buffer, position) = position.await?;
+            let hovers = project
+                .update(cx, |project, cx| project.hover(&buffer, position, cx))?
+                .await
+                .unwrap_or_default();
+
+            let blocks = hovers
+                .iter()
+                .flat_map(|hover| &hover.contents)
+                .filter(|block| !block.text.trim().is_empty())
+                .map(|block| match &block.kind {
+                    HoverBlockKind::Code { language } => {
+                        format!("```{language}\n{}\n```", block.text.trim())
+                    }
+                    HoverBlockKind::PlainText | HoverBlockKind::Markdown => {
+                        block.text.trim().to_string()
+                    }
+                })
+                .collect::<Vec<_>>();
+
+            if blocks.is_empty() {
+                Ok(format!(
+                    "No information available for `{}` on line {} of {}",
+                    input.symbol, input.line, input.path
+                ))
+            } else {
+                Ok(blocks.join("\n\n"))
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::symbol_position::tests::{FakeServerProject, fake_server_project};
+    use gpui::TestAppContext;
+
+    #[gpui::test]
+    async fn test_hover(cx: &mut TestAppContext) {
+        let FakeServerProject {
+            project, server, ..
+        } = fake_server_project(
+            lsp::ServerCapabilities {
+                hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+        server.set_request_handler::<lsp::request::HoverRequest, _, _>(|params, _| async move {
+            if params.text_document_position_params.position != lsp::Position::new(3, 12) {
+                return Ok(None);
+            }
+            Ok(Some(lsp::Hover {
+                contents: lsp::HoverContents::Array(vec![
+                    lsp::MarkedString::LanguageString(lsp::LanguageString {
+                        language: "rust".into(),
+                        value: "pub fn greet(name: &str)".into(),
+                    }),
+                    lsp::MarkedString::String("Greets someone.".into()),
+                ]),
+                range: None,
+            }))
+        });
+
+        let tool = Arc::new(HoverTool::new(project));
+        let run = |line: u32, symbol: &str| {
+            let input = HoverToolInput {
+                path: "root/src/main.rs".into(),
+                line,
+                symbol: symbol.into(),
+            };
+            cx.update(|cx| tool.clone().run(input, ToolCallEventStream::test().0, cx))
+        };
+
+        assert_eq!(
+            run(4, "greet").await.unwrap(),
+            "```rust\npub fn greet(name: &str)\n```\n\nGreets someone."
+        );
+        assert_eq!(
+            run(3, "main").await.unwrap(),
+            "No information available for `main` on line 3 of root/src/main.rs"
+        );
+    }
+}
@@ -1,5 +1,6 @@
 use crate::{AgentTool, ToolCallEventStream};
 use agent_client_protocol::ToolKind;
+use agent_settings::ToolPermissionSubject;
 use anyhow::{Result, anyhow};
 use gpui::{App, Entity, SharedString, Task};
 use project::{Project, ProjectPath, WorktreeSettings};
@@ -59,6 +60,10 @@ impl AgentTool for ListDirectoryTool {
         ToolKind::Read
     }
 
+    fn permission_subjects(&self, input: &Self::Input) -> Vec<ToolPermissionSubject> {
+        vec![ToolPermissionSubject::Path(input.path.clone())]
+    }
+
     fn initial_title(
         &self,
         input: Result<Self::Input, serde_json::Value>,
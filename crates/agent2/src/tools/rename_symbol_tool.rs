@@ -0,0 +1,406 @@
+use crate::tools::symbol_position::resolve_symbol_position;
+use crate::{AgentTool, ToolCallEventStream};
+use action_log::ActionLog;
+use agent_client_protocol as acp;
+use agent_settings::ToolPermissionSubject;
+use anyhow::{Result, anyhow};
+use collections::HashSet;
+use futures::future::try_join_all;
+use gpui::{App, Entity, SharedString, Task};
+use project::Project;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::{fmt::Write, sync::Arc};
+use util::markdown::MarkdownInlineCode;
+
+/// Renames a symbol and every reference to it across the project, using the language server for the file it appears in.
+///
+/// Prefer this over editing each occurrence with `edit_file` when renaming a function, type, variable, or field: the language server only changes references to this exact symbol, including in files you haven't read.
+///
+/// <example>
+/// To rename the `load` method declared on line 27 of `src/store.rs` to `load_from_disk`:
+/// {
+///     "path": "project/src/store.rs",
+///     "line": 27,
+///     "symbol": "load",
+///     "new_name": "load_from_disk"
+/// }
+/// </example>
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct RenameSymbolToolInput {
+    /// The path of the file the symbol appears in.
+    ///
+    /// This path should never be absolute, and the first component
+    /// of the path should always be a root directory in a project.
+    pub path: String,
+    /// The 1-based line number the symbol appears on.
+    pub line: u32,
+    /// The symbol to rename, exactly as it appears on the line.
+    pub symbol: String,
+    /// The new name for the symbol.
+    pub new_name: String,
+}
+
+pub struct RenameSymbolTool {
+    project: Entity<Project>,
+    action_log: Entity<ActionLog>,
+}
+
+impl RenameSymbolTool {
+    pub fn new(project: Entity<Project>, action_log: Entity<ActionLog>) -> Self {
+        Self {
+            project,
+            action_log,
+        }
+    }
+}
+
+impl AgentTool for RenameSymbolTool {
+    type Input = RenameSymbolToolInput;
+    type Output = String;
+
+    fn name() -> &'static str {
+        "rename_symbol"
+    }
+
+    fn kind() -> acp::ToolKind {
+        acp::ToolKind::Edit
+    }
+
+    fn permission_subjects(&self, input: &Self::Input) -> Vec<ToolPermissionSubject> {
+        vec![ToolPermissionSubject::Path(input.path.clone())]
+    }
+
+    fn initial_title(
+        &self,
+        input: Result<Self::Input, serde_json::Value>,
+        _cx: &mut App,
+    ) -> SharedString {
+        if let Ok(input) = input {
+            format!(
+                "Rename {} to {}",
+                MarkdownInlineCode(&input.symbol),
+                MarkdownInlineCode(&input.new_name)
+            )
+            .into()
+        } else {
+            "Rename symbol".into()
+        }
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: Self::Input,
+        event_stream: ToolCallEventStream,
+        cx: &mut App,
+    ) -> Task<Result<Self::Output>> {
+        let position =
+            resolve_symbol_position(&self.project, &input.path, input.line, &input.symbol, cx);
+        let project = self.project.clone();
+        let action_log = self.action_log.clone();
+
+        cx.spawn(async move |cx| {
+            let (buffer, position) = position.await?;
+
+            // The rename changes the symbol's references, so those are the files it's checked
+            // against the tool permission rules for before anything is changed.
+            let references = project
+                .update(cx, |project, cx| project.references(&buffer, position, cx))?
+                .await?
+                .unwrap_or_default();
+            let mut affected_buffers = HashSet::from_iter([buffer.clone()]);
+            affected_buffers.extend(references.into_iter().map(|reference| reference.buffer));
+
+            let authorize = cx.update(|cx| {
+                let subjects = affected_buffers
+                    .iter()
+                    .filter(|affected_buffer| **affected_buffer != buffer)
+                    .filter_map(|affected_buffer| {
+                        let path = affected_buffer.read(cx).file()?.full_path(cx);
+                        Some(ToolPermissionSubject::Path(
+                            path.to_string_lossy().into_owned(),
+                        ))
+                    })
+                    .collect();
+                let title = format!(
+                    "Rename {} to {} in {} file(s)",
+                    MarkdownInlineCode(&input.symbol),
+                    MarkdownInlineCode(&input.new_name),
+                    affected_buffers.len()
+                );
+                event_stream.authorize_subjects(Self::name(), subjects, title, &project, cx)
+            })?;
+            authorize.await?;
+
+            // Track the buffers the rename is going to change before it does, so that the action
+            // log shows its edits for review.
+            action_log.update(cx, |action_log, cx| {
+                for affected_buffer in &affected_buffers {
+                    action_log.buffer_read(affected_buffer.clone(), cx);
+                }
+            })?;
+
+            let transaction = project
+                .update(cx, |project, cx| {
+                    project.perform_rename(buffer.clone(), position, input.new_name.clone(), cx)
+                })?
+                .await?;
+            if transaction.0.is_empty() {
+                return Err(anyhow!(
+                    "The language server didn't rename `{}` on line {} of {}",
+                    input.symbol,
+                    input.line,
+                    input.path
+                ));
+            }
+
+            // Language servers may change more than the references they reported, and those
+            // changes weren't authorized, so they're undone before anything is saved.
+            if transaction
+                .0
+                .keys()
+                .any(|changed_buffer| !affected_buffers.contains(changed_buffer))
+            {
+                cx.update(|cx| {
+                    for (changed_buffer, transaction) in &transaction.0 {
+                        changed_buffer.update(cx, |changed_buffer, cx| {
+                            changed_buffer.undo_transaction(transaction.id, cx);
+                        });
+                    }
+                })?;
+                return Err(anyhow!(
+                    "The language server tried to change files that don't reference `{}`, \
+                    so the rename was undone",
+                    input.symbol
+                ));
+            }
+
+            let buffers = transaction.0.into_keys().collect::<HashSet<_>>();
+            let saves = project.update(cx, |project, cx| {
+                buffers
+                    .iter()
+                    .map(|buffer| project.save_buffer(buffer.clone(), cx))
+                    .collect::<Vec<_>>()
+            })?;
+            try_join_all(saves).await?;
+
+            cx.update(|cx| {
+                let mut paths = Vec::new();
+                action_log.update(cx, |action_log, cx| {
+                    for buffer in &buffers {
+                        action_log.buffer_edited(buffer.clone(), cx);
+                    }
+                });
+                for buffer in &buffers {
+                    if let Some(file) = buffer.read(cx).file() {
+                        paths.push(file.full_path(cx).display().to_string());
+                    }
+                }
+                paths.sort();
+
+                let mut output = format!(
+                    "Renamed `{}` to `{}` in {} file(s):\n",
+                    input.symbol,
+                    input.new_name,
+                    buffers.len()
+                );
+                for path in paths {
+                    writeln!(output, "- {path}")?;
+                }
+                anyhow::Ok(output)
+            })?
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::symbol_position::tests::{
+        FakeServerProject, fake_server_project, greet_call, greet_declaration,
+    };
+    use fs::Fs as _;
+    use gpui::{TestAppContext, UpdateGlobal};
+    use indoc::indoc;
+    use settings::SettingsStore;
+    use std::path::Path;
+    use util::path;
+
+    const MAIN_RS: &str = indoc! {"
+        mod helper;
+
+        fn main() {
+            helper::greet(\"world\");
+        }
+    "};
+    const HELPER_RS: &str = indoc! {"
+        pub fn greet(name: &str) {
+            println!(\"Hello, {name}!\");
+        }
+    "};
+
+    /// Starts a language server reporting the given references to `greet`, and renaming it to
+    /// `welcome` in both files regardless.
+    async fn rename_tool(
+        references: Vec<lsp::Location>,
+        cx: &mut TestAppContext,
+    ) -> (Entity<Project>, Arc<RenameSymbolTool>) {
+        let FakeServerProject {
+            project, server, ..
+        } = fake_server_project(
+            lsp::ServerCapabilities {
+                references_provider: Some(lsp::OneOf::Left(true)),
+                rename_provider: Some(lsp::OneOf::Left(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+        server.set_request_handler::<lsp::request::References, _, _>(move |_, _| {
+            let references = references.clone();
+            async move { Ok(Some(references)) }
+        });
+        server.set_request_handler::<lsp::request::Rename, _, _>(|params, _| async move {
+            assert_eq!(params.new_name, "welcome");
+            let changes = [greet_declaration(), greet_call()]
+                .into_iter()
+                .map(|location| {
+                    let edit = lsp::TextEdit::new(location.range, params.new_name.clone());
+                    (location.uri, vec![edit])
+                })
+                .collect();
+            Ok(Some(lsp::WorkspaceEdit::new(changes)))
+        });
+
+        let action_log = cx.new(|_| ActionLog::new(project.clone()));
+        let tool = Arc::new(RenameSymbolTool::new(project.clone(), action_log));
+        (project, tool)
+    }
+
+    fn rename_greet(
+        tool: &Arc<RenameSymbolTool>,
+        event_stream: ToolCallEventStream,
+        cx: &mut TestAppContext,
+    ) -> Task<Result<String>> {
+        let input = RenameSymbolToolInput {
+            path: "root/src/main.rs".into(),
+            line: 4,
+            symbol: "greet".into(),
+            new_name: "welcome".into(),
+        };
+        cx.update(|cx| tool.clone().run(input, event_stream, cx))
+    }
+
+    async fn load(project: &Entity<Project>, path: &str, cx: &mut TestAppContext) -> String {
+        let fs = project.read_with(cx, |project, _| project.fs().clone());
+        fs.load(Path::new(path)).await.unwrap()
+    }
+
+    #[gpui::test]
+    async fn test_rename_symbol(cx: &mut TestAppContext) {
+        let (project, tool) = rename_tool(vec![greet_declaration(), greet_call()], cx).await;
+
+        let output = rename_greet(&tool, ToolCallEventStream::test().0, cx)
+            .await
+            .unwrap();
+        assert_eq!(
+            output,
+            "Renamed `greet` to `welcome` in 2 file(s):\n- root/src/helper.rs\n- root/src/main.rs\n"
+        );
+        assert_eq!(
+            load(&project, path!("/root/src/main.rs"), cx).await,
+            MAIN_RS.replace("greet", "welcome")
+        );
+        assert_eq!(
+            load(&project, path!("/root/src/helper.rs"), cx).await,
+            HELPER_RS.replace("greet", "welcome")
+        );
+    }
+
+    #[gpui::test]
+    async fn test_rename_symbol_permission_rules(cx: &mut TestAppContext) {
+        let (project, tool) = rename_tool(vec![greet_declaration(), greet_call()], cx).await;
+        let set_rules = |rules: &str, cx: &mut TestAppContext| {
+            cx.update(|cx| {
+                SettingsStore::update_global(cx, |store, cx| {
+                    store
+                        .set_user_settings(
+                            &format!(r#"{{ "agent_tool_permissions": [{rules}] }}"#),
+                            cx,
+                        )
+                        .unwrap();
+                });
+            });
+        };
+
+        // The rules are checked against the files the symbol is referenced in, not only the one
+        // the rename was requested in.
+        set_rules(
+            r#"{ "tool": "rename_symbol", "path": "src/helper.rs", "action": "ask" }"#,
+            cx,
+        );
+        let (event_stream, mut events) = ToolCallEventStream::test();
+        let task = rename_greet(&tool, event_stream, cx);
+        let authorization = events.expect_authorization().await;
+        assert_eq!(
+            authorization.tool_call.fields.title.as_deref(),
+            Some("Rename `greet` to `welcome` in 2 file(s)")
+        );
+        authorization
+            .response
+            .send(acp::PermissionOptionId("deny".into()))
+            .unwrap();
+        assert_eq!(
+            task.await.unwrap_err().to_string(),
+            "Permission to run tool denied by user"
+        );
+
+        set_rules(
+            r#"{ "tool": "rename_symbol", "path": "src/helper.rs", "action": "deny" }"#,
+            cx,
+        );
+        assert_eq!(
+            rename_greet(&tool, ToolCallEventStream::test().0, cx)
+                .await
+                .unwrap_err()
+                .to_string(),
+            "Permission to run tool denied by the tool permission rules"
+        );
+
+        assert_eq!(
+            load(&project, path!("/root/src/main.rs"), cx).await,
+            MAIN_RS
+        );
+        assert_eq!(
+            load(&project, path!("/root/src/helper.rs"), cx).await,
+            HELPER_RS
+        );
+    }
+
+    #[gpui::test]
+    async fn test_rename_symbol_undoes_unreferenced_changes(cx: &mut TestAppContext) {
+        // The language server changes `helper.rs` even though it didn't report it as a reference.
+        let (project, tool) = rename_tool(vec![greet_call()], cx).await;
+
+        assert_eq!(
+            rename_greet(&tool, ToolCallEventStream::test().0, cx)
+                .await
+                .unwrap_err()
+                .to_string(),
+            "The language server tried to change files that don't reference `greet`, \
+            so the rename was undone"
+        );
+
+        for (path, text) in [
+            (path!("/root/src/main.rs"), MAIN_RS),
+            (path!("/root/src/helper.rs"), HELPER_RS),
+        ] {
+            let buffer = project
+                .update(cx, |project, cx| project.open_local_buffer(path, cx))
+                .await
+                .unwrap();
+            buffer.read_with(cx, |buffer, _| assert_eq!(buffer.text(), text));
+            assert_eq!(load(&project, path, cx).await, text);
+        }
+    }
+}
@@ -0,0 +1,135 @@
+use crate::{AgentTool, ToolCallEventStream};
+use agent_client_protocol as acp;
+use anyhow::{Result, anyhow};
+use gpui::{App, Entity, SharedString, Task};
+use language::{Bias, ToPoint as _};
+use project::{Project, ProjectPath};
+use schemars::JsonSchema;
+use semantic_index::{SemanticIndex, Status};
+use serde::{Deserialize, Serialize};
+use std::{fmt::Write, sync::Arc};
+use util::ResultExt as _;
+use util::markdown::MarkdownInlineCode;
+
+const RESULTS_LIMIT: usize = 8;
+
+/// Searches the project's code by meaning, returning the snippets most related to the query even when they don't contain its words.
+///
+/// - Use this to find where a concept or behavior is implemented when you don't know the names involved, such as "where user sessions are persisted" or "retrying failed network requests".
+/// - Prefer `grep` when you know the exact text or symbol name to look for.
+/// - Describe what the code does in plain language rather than guessing identifiers.
+///
+/// <example>
+/// {
+///     "query": "where database migrations are run at startup"
+/// }
+/// </example>
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct SemanticSearchToolInput {
+    /// A description of the code to find.
+    pub query: String,
+}
+
+pub struct SemanticSearchTool {
+    project: Entity<Project>,
+}
+
+impl SemanticSearchTool {
+    pub fn new(project: Entity<Project>) -> Self {
+        Self { project }
+    }
+}
+
+impl AgentTool for SemanticSearchTool {
+    type Input = SemanticSearchToolInput;
+    type Output = String;
+
+    fn name() -> &'static str {
+        "semantic_search"
+    }
+
+    fn kind() -> acp::ToolKind {
+        acp::ToolKind::Search
+    }
+
+    fn initial_title(
+        &self,
+        input: Result<Self::Input, serde_json::Value>,
+        _cx: &mut App,
+    ) -> SharedString {
+        if let Ok(input) = input {
+            format!("Search code for {}", MarkdownInlineCode(&input.query)).into()
+        } else {
+            "Search code by meaning".into()
+        }
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: Self::Input,
+        _event_stream: ToolCallEventStream,
+        cx: &mut App,
+    ) -> Task<Result<Self::Output>> {
+        let Some(project_index) = SemanticIndex::global(cx)
+            .and_then(|semantic_index| semantic_index.read(cx).project_index(&self.project))
+        else {
+            return Task::ready(Err(anyhow!(
+                "The semantic index isn't available for this project"
+            )));
+        };
+        let project_index = project_index.read(cx);
+        let status = project_index.status(cx);
+        let search = project_index.search(input.query, RESULTS_LIMIT, cx);
+        let project = self.project.clone();
+
+        cx.spawn(async move |cx| {
+            let results = search.await?;
+            if results.is_empty() {
+                return Ok("No results found".into());
+            }
+
+            let mut output = String::new();
+            if status != Status::Idle {
+                writeln!(
+                    output,
+                    "The project is still being indexed, so these results may be incomplete."
+                )?;
+            }
+            for result in results {
+                let project_path = ProjectPath {
+                    worktree_id: result.worktree_id,
+                    path: result.path,
+                };
+                let Some(buffer) = project
+                    .update(cx, |project, cx| project.open_buffer(project_path, cx))?
+                    .await
+                    .log_err()
+                else {
+                    continue;
+                };
+
+                buffer.read_with(cx, |buffer, cx| {
+                    // The file may have changed since it was indexed.
+                    let start =
+                        buffer.clip_offset(result.range.start.min(buffer.len()), Bias::Left);
+                    let end = buffer.clip_offset(result.range.end.min(buffer.len()), Bias::Right);
+                    let start_row = start.to_point(buffer).row;
+                    let end_row = end.to_point(buffer).row;
+                    let path = buffer
+                        .file()
+                        .map(|file| file.full_path(cx).display().to_string())
+                        .unwrap_or_default();
+                    let text = buffer.text_for_range(start..end).collect::<String>();
+                    writeln!(
+                        output,
+                        "\n## {path} L{}-{}\n```\n{}\n```",
+                        start_row + 1,
+                        end_row + 1,
+                        text.trim_end()
+                    )
+                })??;
+            }
+            Ok(output)
+        })
+    }
+}
@@ -0,0 +1,215 @@
+use anyhow::{Context as _, Result, anyhow};
+use gpui::{App, Entity, Task};
+use language::{Anchor, Buffer, Point, ToPoint as _};
+use project::Project;
+use std::ops::Range;
+
+/// Opens the file at `path` and finds the first occurrence of `symbol` on its 1-based `line`,
+/// which is the position the language server tools send their requests for.
+pub(crate) fn resolve_symbol_position(
+    project: &Entity<Project>,
+    path: &str,
+    line: u32,
+    symbol: &str,
+    cx: &mut App,
+) -> Task<Result<(Entity<Buffer>, Point)>> {
+    let Some(project_path) = project.read(cx).find_project_path(path, cx) else {
+        return Task::ready(Err(anyhow!("Could not find path {path} in project")));
+    };
+    let buffer = project.update(cx, |project, cx| project.open_buffer(project_path, cx));
+    let path = path.to_string();
+    let symbol = symbol.to_string();
+
+    cx.spawn(async move |cx| {
+        let buffer = buffer.await?;
+        let position = buffer.read_with(cx, |buffer, _cx| {
+            let row = line
+                .checked_sub(1)
+                .filter(|row| *row <= buffer.max_point().row)
+                .with_context(|| format!("Line {line} is outside of {path}"))?;
+            let column = line_text(buffer, row)
+                .find(&symbol)
+                .with_context(|| format!("Could not find `{symbol}` on line {line} of {path}"))?;
+            anyhow::Ok(Point::new(row, column as u32))
+        })??;
+        Ok((buffer, position))
+    })
+}
+
+/// Describes where a range starts as `path:line`, followed by the text of that line.
+pub(crate) fn describe_location(
+    buffer: &Entity<Buffer>,
+    range: &Range<Anchor>,
+    cx: &App,
+) -> String {
+    let buffer = buffer.read(cx);
+    let path = buffer
+        .file()
+        .map(|file| file.full_path(cx).to_string_lossy().into_owned())
+        .unwrap_or_else(|| "untitled".to_string());
+    let row = range.start.to_point(buffer).row;
+    format!("{path}:{}: {}", row + 1, line_text(buffer, row).trim())
+}
+
+fn line_text(buffer: &Buffer, row: u32) -> String {
+    buffer
+        .text_for_range(Point::new(row, 0)..Point::new(row, buffer.line_len(row)))
+        .collect()
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use futures::StreamExt as _;
+    use gpui::TestAppContext;
+    use indoc::indoc;
+    use project::{FakeFs, lsp_store::OpenLspBufferHandle};
+    use serde_json::json;
+    use settings::SettingsStore;
+    use std::{path::Path, sync::Arc};
+    use util::path;
+
+    /// A project whose Rust files are handled by a fake language server.
+    pub(crate) struct FakeServerProject {
+        pub project: Entity<Project>,
+        pub server: lsp::FakeLanguageServer,
+        _main_rs: OpenLspBufferHandle,
+    }
+
+    /// Creates a project with a `main.rs` calling `greet` from `helper.rs`, opening `main.rs` to
+    /// start a fake language server with the given capabilities.
+    pub(crate) async fn fake_server_project(
+        capabilities: lsp::ServerCapabilities,
+        cx: &mut TestAppContext,
+    ) -> FakeServerProject {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            language::init(cx);
+            Project::init_settings(cx);
+            agent_settings::init(cx);
+        });
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            path!("/root"),
+            json!({
+                "src": {
+                    "main.rs": indoc! {"
+                        mod helper;
+
+                        fn main() {
+                            helper::greet(\"world\");
+                        }
+                    "},
+                    "helper.rs": indoc! {"
+                        pub fn greet(name: &str) {
+                            println!(\"Hello, {name}!\");
+                        }
+                    "},
+                }
+            }),
+        )
+        .await;
+        let project = Project::test(fs, [path!("/root").as_ref()], cx).await;
+
+        let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+        language_registry.add(Arc::new(language::Language::new(
+            language::LanguageConfig {
+                name: "Rust".into(),
+                matcher: language::LanguageMatcher {
+                    path_suffixes: vec!["rs".to_string()],
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            None,
+        )));
+        let mut fake_servers = language_registry.register_fake_lsp(
+            "Rust",
+            language::FakeLspAdapter {
+                capabilities,
+                ..Default::default()
+            },
+        );
+
+        let buffer = project
+            .update(cx, |project, cx| {
+                project.open_local_buffer(path!("/root/src/main.rs"), cx)
+            })
+            .await
+            .unwrap();
+        let main_rs = project.update(cx, |project, cx| {
+            project.register_buffer_with_language_servers(&buffer, cx)
+        });
+        cx.run_until_parked();
+        let server = fake_servers.next().await.unwrap();
+
+        FakeServerProject {
+            project,
+            server,
+            _main_rs: main_rs,
+        }
+    }
+
+    /// The location of `greet` in `main.rs`, where it's called.
+    pub(crate) fn greet_call() -> lsp::Location {
+        lsp::Location::new(
+            lsp::Uri::from_file_path(path!("/root/src/main.rs")).unwrap(),
+            lsp::Range::new(lsp::Position::new(3, 12), lsp::Position::new(3, 17)),
+        )
+    }
+
+    /// The location of `greet` in `helper.rs`, where it's declared.
+    pub(crate) fn greet_declaration() -> lsp::Location {
+        lsp::Location::new(
+            lsp::Uri::from_file_path(path!("/root/src/helper.rs")).unwrap(),
+            lsp::Range::new(lsp::Position::new(0, 7), lsp::Position::new(0, 12)),
+        )
+    }
+
+    #[gpui::test]
+    async fn test_resolve_symbol_position(cx: &mut TestAppContext) {
+        let FakeServerProject { project, .. } =
+            fake_server_project(lsp::ServerCapabilities::default(), cx).await;
+
+        let (buffer, position) = cx
+            .update(|cx| resolve_symbol_position(&project, "root/src/main.rs", 4, "greet", cx))
+            .await
+            .unwrap();
+        assert_eq!(position, Point::new(3, 12));
+        buffer.read_with(cx, |buffer, cx| {
+            assert_eq!(
+                buffer.file().unwrap().full_path(cx),
+                Path::new("root/src/main.rs")
+            );
+        });
+
+        let (_, position) = cx
+            .update(|cx| resolve_symbol_position(&project, "root/src/helper.rs", 2, "name", cx))
+            .await
+            .unwrap();
+        assert_eq!(position, Point::new(1, 22));
+
+        let error = |path: &'static str, line: u32, symbol: &'static str| {
+            let task = cx.update(|cx| resolve_symbol_position(&project, path, line, symbol, cx));
+            async move { task.await.unwrap_err().to_string() }
+        };
+        assert_eq!(
+            error("other/src/main.rs", 1, "main").await,
+            "Could not find path other/src/main.rs in project"
+        );
+        assert_eq!(
+            error("root/src/main.rs", 40, "main").await,
+            "Line 40 is outside of root/src/main.rs"
+        );
+        assert_eq!(
+            error("root/src/main.rs", 0, "main").await,
+            "Line 0 is outside of root/src/main.rs"
+        );
+        assert_eq!(
+            error("root/src/main.rs", 1, "main").await,
+            "Could not find `main` on line 1 of root/src/main.rs"
+        );
+    }
+}
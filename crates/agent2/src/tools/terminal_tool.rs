@@ -1,4 +1,5 @@
 use agent_client_protocol as acp;
+use agent_settings::ToolPermissionSubject;
 use anyhow::Result;
 use gpui::{App, Entity, SharedString, Task};
 use project::Project;
@@ -60,6 +61,10 @@ impl AgentTool for TerminalTool {
         acp::ToolKind::Execute
     }
 
+    fn permission_subjects(&self, input: &Self::Input) -> Vec<ToolPermissionSubject> {
+        vec![ToolPermissionSubject::Command(input.command.clone())]
+    }
+
     fn initial_title(
         &self,
         input: Result<Self::Input, serde_json::Value>,
@@ -1,4 +1,5 @@
 mod agent_profile;
+mod tool_permissions;
 
 use std::sync::Arc;
 
@@ -14,6 +15,7 @@ use settings::{
 };
 
 pub use crate::agent_profile::*;
+pub use crate::tool_permissions::*;
 
 pub const SUMMARIZE_THREAD_PROMPT: &str =
     include_str!("../../agent/src/prompts/summarize_thread_prompt.txt");
@@ -40,6 +42,7 @@ pub struct AgentSettings {
     pub default_view: DefaultAgentView,
     pub profiles: IndexMap<AgentProfileId, AgentProfileSettings>,
     pub always_allow_tool_actions: bool,
+    pub tool_permissions: ToolPermissions,
     pub notify_when_agent_waiting: NotifyWhenAgentWaiting,
     pub play_sound_when_agent_done: bool,
     pub stream_edits: bool,
@@ -51,6 +54,21 @@ pub struct AgentSettings {
     pub expand_terminal_card: bool,
     pub use_modifier_to_send: bool,
     pub message_editor_min_lines: usize,
+    pub context_server_sampling: ContextServerSamplingSettings,
+}
+
+#[derive(Clone, Debug)]
+pub struct ContextServerSamplingSettings {
+    pub model: Option<LanguageModelSelection>,
+    pub auto_approve: Vec<Arc<str>>,
+    pub max_tokens: u32,
+    pub max_prompt_tokens: u64,
+}
+
+impl ContextServerSamplingSettings {
+    pub fn is_auto_approved(&self, server_id: &str) -> bool {
+        self.auto_approve.iter().any(|id| id.as_ref() == server_id)
+    }
 }
 
 impl AgentSettings {
@@ -173,6 +191,9 @@ impl Settings for AgentSettings {
                 .map(|(key, val)| (AgentProfileId(key), val.into()))
                 .collect(),
             always_allow_tool_actions: agent.always_allow_tool_actions.unwrap(),
+            tool_permissions: ToolPermissions::new(
+                &content.project.agent_tool_permissions.as_ref().unwrap().0,
+            ),
             notify_when_agent_waiting: agent.notify_when_agent_waiting.unwrap(),
             play_sound_when_agent_done: agent.play_sound_when_agent_done.unwrap(),
             stream_edits: agent.stream_edits.unwrap(),
@@ -184,6 +205,15 @@ impl Settings for AgentSettings {
             expand_terminal_card: agent.expand_terminal_card.unwrap(),
             use_modifier_to_send: agent.use_modifier_to_send.unwrap(),
             message_editor_min_lines: agent.message_editor_min_lines.unwrap(),
+            context_server_sampling: {
+                let sampling = agent.context_server_sampling.unwrap();
+                ContextServerSamplingSettings {
+                    model: sampling.model,
+                    auto_approve: sampling.auto_approve.unwrap(),
+                    max_tokens: sampling.max_tokens.unwrap(),
+                    max_prompt_tokens: sampling.max_prompt_tokens.unwrap(),
+                }
+            },
         }
     }
 
@@ -0,0 +1,272 @@
+use std::sync::Arc;
+
+use settings::{ToolPermissionAction, ToolPermissionRuleContent};
+use util::ResultExt as _;
+use util::paths::{PathMatcher, PathStyle};
+
+/// Shell syntax that runs further commands or redirects output, which a command matching an
+/// `allow` rule could use to do more than the rule intends.
+const COMMAND_CHAINING: [&str; 8] = [";", "&", "|", "`", "$(", "<(", ">", "\n"];
+
+/// What a tool is about to work on, which tool permission rules are matched against.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ToolPermissionSubject {
+    /// A path relative to its worktree, or an absolute path when it's outside of the project.
+    Path(String),
+    /// A command run in a shell.
+    Command(String),
+}
+
+impl ToolPermissionSubject {
+    /// Suggests a pattern matching this subject and others like it, so that they can all be
+    /// allowed at once: the directory containing a path, or a command with its arguments.
+    pub fn suggested_pattern(&self) -> String {
+        match self {
+            Self::Path(path) => match path.rsplit_once('/') {
+                Some((directory, _)) if !directory.is_empty() => format!("{directory}/**"),
+                _ => path.clone(),
+            },
+            Self::Command(command) => {
+                let mut words = command.split_whitespace();
+                let mut prefix = words.next().unwrap_or_default().to_string();
+                if let Some(subcommand) = words.next()
+                    && !subcommand.starts_with('-')
+                {
+                    prefix.push(' ');
+                    prefix.push_str(subcommand);
+                }
+                format!("{prefix}*")
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ToolPermissions {
+    rules: Vec<ToolPermissionRule>,
+}
+
+#[derive(Clone, Debug)]
+struct ToolPermissionRule {
+    tool: Arc<str>,
+    path: Option<PathMatcher>,
+    command: Option<PathMatcher>,
+    action: ToolPermissionAction,
+}
+
+impl ToolPermissions {
+    /// Compiles the given rules, skipping those with invalid globs.
+    pub fn new(rules: &[ToolPermissionRuleContent]) -> Self {
+        let rules = rules
+            .iter()
+            .filter_map(|rule| {
+                let matcher = |glob: &Option<String>| {
+                    glob.as_ref()
+                        .map(|glob| PathMatcher::new([glob], PathStyle::Posix))
+                        .transpose()
+                };
+                Some(ToolPermissionRule {
+                    tool: rule.tool.clone(),
+                    path: matcher(&rule.path).log_err()?,
+                    command: matcher(&rule.command).log_err()?,
+                    action: rule.action,
+                })
+            })
+            .collect();
+        Self { rules }
+    }
+
+    /// Returns the most restrictive action among the rules that apply to a use of the tool, or
+    /// `None` when no rule applies.
+    pub fn action(
+        &self,
+        tool: &str,
+        subject: Option<&ToolPermissionSubject>,
+    ) -> Option<ToolPermissionAction> {
+        self.rules
+            .iter()
+            .filter(|rule| rule.applies_to(tool, subject))
+            .map(|rule| rule.action)
+            .max()
+    }
+}
+
+impl ToolPermissionRule {
+    fn applies_to(&self, tool: &str, subject: Option<&ToolPermissionSubject>) -> bool {
+        if self.tool.as_ref() != "*" && self.tool.as_ref() != tool {
+            return false;
+        }
+        match subject {
+            Some(ToolPermissionSubject::Path(path)) => {
+                self.command.is_none() && self.path.as_ref().is_none_or(|glob| glob.is_match(path))
+            }
+            Some(ToolPermissionSubject::Command(command)) => {
+                if self.path.is_some() {
+                    return false;
+                }
+                if self.action == ToolPermissionAction::Allow
+                    && COMMAND_CHAINING
+                        .iter()
+                        .any(|syntax| command.contains(syntax))
+                {
+                    return false;
+                }
+                self.command
+                    .as_ref()
+                    .is_none_or(|glob| glob.is_match(command))
+            }
+            None => self.path.is_none() && self.command.is_none(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AgentSettings;
+    use gpui::App;
+    use settings::{LocalSettingsKind, Settings as _, SettingsLocation, SettingsStore, WorktreeId};
+    use util::rel_path::RelPath;
+
+    fn rule(
+        tool: &str,
+        path: Option<&str>,
+        command: Option<&str>,
+        action: ToolPermissionAction,
+    ) -> ToolPermissionRuleContent {
+        ToolPermissionRuleContent {
+            tool: tool.into(),
+            path: path.map(Into::into),
+            command: command.map(Into::into),
+            action,
+        }
+    }
+
+    #[test]
+    fn test_tool_permission_rules() {
+        let permissions = ToolPermissions::new(&[
+            rule(
+                "edit_file",
+                Some("src/**"),
+                None,
+                ToolPermissionAction::Allow,
+            ),
+            rule("*", Some("**/.env*"), None, ToolPermissionAction::Deny),
+            rule(
+                "terminal",
+                None,
+                Some("cargo test*"),
+                ToolPermissionAction::Allow,
+            ),
+            rule("fetch", None, None, ToolPermissionAction::Ask),
+        ]);
+        let path = |path: &str| ToolPermissionSubject::Path(path.into());
+        let command = |command: &str| ToolPermissionSubject::Command(command.into());
+
+        assert_eq!(
+            permissions.action("edit_file", Some(&path("src/main.rs"))),
+            Some(ToolPermissionAction::Allow)
+        );
+        assert_eq!(
+            permissions.action("edit_file", Some(&path("docs/README.md"))),
+            None
+        );
+        assert_eq!(
+            permissions.action("edit_file", Some(&path("src/.env.local"))),
+            Some(ToolPermissionAction::Deny)
+        );
+        assert_eq!(
+            permissions.action("read_file", Some(&path(".env"))),
+            Some(ToolPermissionAction::Deny)
+        );
+        assert_eq!(
+            permissions.action("terminal", Some(&command("cargo test -p agent2"))),
+            Some(ToolPermissionAction::Allow)
+        );
+        assert_eq!(
+            permissions.action("terminal", Some(&command("cargo test && rm -rf target"))),
+            None
+        );
+        assert_eq!(
+            permissions.action("terminal", Some(&command("cargo test <(curl example.com)"))),
+            None
+        );
+        assert_eq!(
+            permissions.action("terminal", Some(&command("cargo build"))),
+            None
+        );
+        assert_eq!(
+            permissions.action("fetch", None),
+            Some(ToolPermissionAction::Ask)
+        );
+    }
+
+    #[test]
+    fn test_suggested_pattern() {
+        let path = |path: &str| ToolPermissionSubject::Path(path.into()).suggested_pattern();
+        let command =
+            |command: &str| ToolPermissionSubject::Command(command.into()).suggested_pattern();
+
+        assert_eq!(path("src/agent/thread.rs"), "src/agent/**");
+        assert_eq!(path("Cargo.toml"), "Cargo.toml");
+        assert_eq!(command("cargo test -p agent2"), "cargo test*");
+        assert_eq!(command("ls -la"), "ls*");
+    }
+
+    #[gpui::test]
+    fn test_project_settings_cannot_allow_tools(cx: &mut App) {
+        let store = SettingsStore::test(cx);
+        cx.set_global(store);
+        AgentSettings::register(cx);
+        let worktree_id = WorktreeId::from_usize(1);
+        SettingsStore::update_global(cx, |store, cx| {
+            store
+                .set_user_settings(
+                    r#"{
+                        "agent_tool_permissions": [
+                            { "tool": "terminal", "command": "cargo test*", "action": "allow" }
+                        ]
+                    }"#,
+                    cx,
+                )
+                .unwrap();
+            store
+                .set_local_settings(
+                    worktree_id,
+                    RelPath::empty().into(),
+                    LocalSettingsKind::Settings,
+                    Some(
+                        r#"{
+                            "agent_tool_permissions": [
+                                { "tool": "edit_file", "path": "src/**", "action": "allow" },
+                                { "tool": "*", "path": "**/.env*", "action": "deny" }
+                            ]
+                        }"#,
+                    ),
+                    cx,
+                )
+                .unwrap();
+        });
+
+        let location = SettingsLocation {
+            worktree_id,
+            path: RelPath::empty(),
+        };
+        let permissions = &AgentSettings::get(Some(location), cx).tool_permissions;
+        let path = |path: &str| ToolPermissionSubject::Path(path.into());
+        let command = |command: &str| ToolPermissionSubject::Command(command.into());
+
+        assert_eq!(
+            permissions.action("edit_file", Some(&path("src/main.rs"))),
+            None
+        );
+        assert_eq!(
+            permissions.action("read_file", Some(&path(".env"))),
+            Some(ToolPermissionAction::Deny)
+        );
+        assert_eq!(
+            permissions.action("terminal", Some(&command("cargo test"))),
+            Some(ToolPermissionAction::Allow)
+        );
+    }
+}
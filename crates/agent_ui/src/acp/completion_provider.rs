@@ -9,6 +9,7 @@ use acp_thread::MentionUri;
 use agent_client_protocol as acp;
 use agent2::{HistoryEntry, HistoryStore};
 use anyhow::Result;
+use context_server::protocol::ServerCapability;
 use editor::{CompletionProvider, Editor, ExcerptId};
 use fuzzy::{StringMatch, StringMatchCandidate};
 use gpui::{App, Entity, Task, WeakEntity};
@@ -29,6 +30,7 @@ use workspace::Workspace;
 use crate::AgentPanel;
 use crate::acp::message_editor::{MessageEditor, MessageEditorEvent};
 use crate::context_picker::file_context_picker::{FileMatch, search_files};
+use crate::context_picker::resource_context_picker::{ResourceMatch, search_resources};
 use crate::context_picker::rules_context_picker::{RulesContextEntry, search_rules};
 use crate::context_picker::symbol_context_picker::SymbolMatch;
 use crate::context_picker::symbol_context_picker::search_symbols;
@@ -43,6 +45,7 @@ pub(crate) enum Match {
     RecentThread(HistoryEntry),
     Fetch(SharedString),
     Rules(RulesContextEntry),
+    Resource(ResourceMatch),
     Entry(EntryMatch),
 }
 
@@ -61,6 +64,7 @@ impl Match {
             Match::Symbol(_) => 1.,
             Match::Rules(_) => 1.,
             Match::Fetch(_) => 1.,
+            Match::Resource(_) => 1.,
         }
     }
 }
@@ -187,6 +191,59 @@ impl ContextPickerCompletionProvider {
         }
     }
 
+    fn completion_for_resource(
+        resource: ResourceMatch,
+        source_range: Range<Anchor>,
+        editor: WeakEntity<MessageEditor>,
+        cx: &mut App,
+    ) -> Completion {
+        let label =
+            build_code_label_for_full_path(&resource.name, Some(resource.server_id.0.as_ref()), cx);
+        let documentation = resource
+            .description
+            .map(|description| CompletionDocumentation::MultiLinePlainText(description.into()));
+        if resource.is_template {
+            let mode = ContextPickerMode::Resources;
+            return Completion {
+                replace_range: source_range,
+                new_text: format!("@{} {}", mode.keyword(), resource.uri),
+                label,
+                documentation,
+                insert_text_mode: None,
+                source: project::CompletionSource::Custom,
+                icon_path: Some(mode.icon().path().into()),
+                // Keeps the completion menu open, so that the template's next argument can
+                // be completed
+                confirm: Some(Arc::new(|_, _, _| true)),
+            };
+        }
+
+        let uri = MentionUri::McpResource {
+            server_id: resource.server_id.0.to_string(),
+            uri: resource.uri,
+            name: resource.name.clone(),
+        };
+        let new_text = format!("{} ", uri.as_link());
+        let new_text_len = new_text.len();
+        let icon_path = uri.icon_path(cx);
+        Completion {
+            replace_range: source_range.clone(),
+            new_text,
+            label,
+            documentation,
+            insert_text_mode: None,
+            source: project::CompletionSource::Custom,
+            icon_path: Some(icon_path),
+            confirm: Some(confirm_completion_callback(
+                resource.name.into(),
+                source_range.start,
+                new_text_len - 1,
+                editor,
+                uri,
+            )),
+        }
+    }
+
     pub(crate) fn completion_for_path(
         project_path: ProjectPath,
         path_prefix: &RelPath,
@@ -490,6 +547,18 @@ impl ContextPickerCompletionProvider {
                 }
             }
 
+            Some(ContextPickerMode::Resources) => {
+                let search_resources_task =
+                    search_resources(query, cancellation_flag, &workspace, cx);
+                cx.background_spawn(async move {
+                    search_resources_task
+                        .await
+                        .into_iter()
+                        .map(Match::Resource)
+                        .collect()
+                })
+            }
+
             None if query.is_empty() => {
                 let mut matches = self.recent_context_picker_entries(&workspace, cx);
 
@@ -665,6 +734,20 @@ impl ContextPickerCompletionProvider {
             }
 
             entries.push(ContextPickerEntry::Mode(ContextPickerMode::Fetch));
+
+            let has_resources = workspace
+                .read(cx)
+                .project()
+                .read(cx)
+                .context_server_store()
+                .read(cx)
+                .running_servers()
+                .iter()
+                .filter_map(|server| server.client())
+                .any(|client| client.capable(ServerCapability::Resources));
+            if has_resources {
+                entries.push(ContextPickerEntry::Mode(ContextPickerMode::Resources));
+            }
         }
 
         entries
@@ -863,6 +946,13 @@ impl CompletionProvider for ContextPickerCompletionProvider {
                                     cx,
                                 ),
 
+                                Match::Resource(resource) => Some(Self::completion_for_resource(
+                                    resource,
+                                    source_range.clone(),
+                                    editor.clone(),
+                                    cx,
+                                )),
+
                                 Match::Entry(EntryMatch { entry, .. }) => {
                                     Self::completion_for_entry(
                                         entry,
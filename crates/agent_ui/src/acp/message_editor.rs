@@ -10,6 +10,7 @@ use anyhow::{Result, anyhow};
 use assistant_slash_commands::codeblock_fence_for_path;
 use assistant_tool::outline;
 use collections::{HashMap, HashSet};
+use context_server::{ContextServer, ContextServerId};
 use editor::{
     Addon, Anchor, AnchorRangeExt, ContextMenuOptions, ContextMenuPlacement, Editor, EditorElement,
     EditorEvent, EditorMode, EditorSnapshot, EditorStyle, ExcerptId, FoldPlaceholder, InlayId,
@@ -31,6 +32,7 @@ use language_model::LanguageModelImage;
 use postage::stream::Stream as _;
 use project::{
     CompletionIntent, InlayHint, InlayHintLabel, Project, ProjectItem, ProjectPath, Worktree,
+    context_server_store,
 };
 use prompt_store::{PromptId, PromptStore};
 use rope::Point;
@@ -62,6 +64,10 @@ pub struct MessageEditor {
     prompt_capabilities: Rc<RefCell<acp::PromptCapabilities>>,
     available_commands: Rc<RefCell<Vec<acp::AvailableCommand>>>,
     agent_name: SharedString,
+    /// The context server resources that mentions subscribed to, which are unsubscribed from
+    /// once they're no longer mentioned. Other editors may be subscribed to the same resources,
+    /// so the store only unsubscribes once none of them mentions it anymore.
+    mcp_resource_subscriptions: HashSet<(ContextServerId, String)>,
     _subscriptions: Vec<Subscription>,
     _parse_slash_command_task: Task<()>,
 }
@@ -139,6 +145,15 @@ impl MessageEditor {
         let mut has_hint = false;
         let mut subscriptions = Vec::new();
 
+        subscriptions.push(cx.subscribe(
+            &project.read(cx).context_server_store(),
+            |this, _, event, cx| {
+                if let context_server_store::Event::ResourceUpdated { server_id, uri } = event {
+                    this.reload_mcp_resource(server_id, uri, cx);
+                }
+            },
+        ));
+
         subscriptions.push(cx.subscribe_in(&editor, window, {
             move |this, editor, event, window, cx| {
                 if let EditorEvent::Edited { .. } = event {
@@ -162,12 +177,19 @@ impl MessageEditor {
                         editor.snapshot(window, cx)
                     });
                     this.mention_set.remove_invalid(snapshot);
+                    this.unsubscribe_from_unmentioned_mcp_resources(cx);
 
                     cx.notify();
                 }
             }
         }));
 
+        cx.on_release(|this, cx| {
+            let subscriptions = this.mcp_resource_subscriptions.drain();
+            unsubscribe_from_mcp_resources(&this.project, subscriptions, cx);
+        })
+        .detach();
+
         Self {
             editor,
             project,
@@ -178,6 +200,7 @@ impl MessageEditor {
             prompt_capabilities,
             available_commands,
             agent_name,
+            mcp_resource_subscriptions: HashSet::default(),
             _subscriptions: subscriptions,
             _parse_slash_command_task: Task::ready(()),
         }
@@ -369,6 +392,9 @@ impl MessageEditor {
                 ..
             } => self.confirm_mention_for_symbol(abs_path, line_range, cx),
             MentionUri::Rule { id, .. } => self.confirm_mention_for_rule(id, cx),
+            MentionUri::McpResource { server_id, uri, .. } => {
+                self.confirm_mention_for_mcp_resource(server_id, uri, cx)
+            }
             MentionUri::PastedImage => {
                 debug_panic!("pasted image URI should not be included in completions");
                 Task::ready(Err(anyhow!(
@@ -399,6 +425,7 @@ impl MessageEditor {
                         editor.edit([(start_anchor..end_anchor, "")], cx);
                     });
                     this.mention_set.mentions.remove(&crease_id);
+                    this.unsubscribe_from_unmentioned_mcp_resources(cx);
                 })
                 .ok();
             }
@@ -536,6 +563,98 @@ impl MessageEditor {
         })
     }
 
+    fn confirm_mention_for_mcp_resource(
+        &mut self,
+        server_id: String,
+        uri: String,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Mention>> {
+        let server_id = ContextServerId(server_id.into());
+        let Some(server) = self
+            .project
+            .read(cx)
+            .context_server_store()
+            .read(cx)
+            .get_running_server(&server_id)
+        else {
+            return Task::ready(Err(anyhow!("MCP server {server_id} is not running")));
+        };
+        let subscribe = self
+            .mcp_resource_subscriptions
+            .insert((server_id.clone(), uri.clone()))
+            .then(|| {
+                self.project
+                    .read(cx)
+                    .context_server_store()
+                    .update(cx, |store, cx| {
+                        store.subscribe_to_resource(&server_id, uri.clone(), cx)
+                    })
+            });
+        cx.spawn(async move |_, _| {
+            if let Some(subscribe) = subscribe {
+                subscribe.await.log_err();
+            }
+            read_mcp_resource(server, uri).await
+        })
+    }
+
+    fn unsubscribe_from_unmentioned_mcp_resources(&mut self, cx: &mut Context<Self>) {
+        let mentioned = self
+            .mention_set
+            .mentions
+            .values()
+            .filter_map(|(mention_uri, _)| match mention_uri {
+                MentionUri::McpResource { server_id, uri, .. } => {
+                    Some((server_id.as_str(), uri.as_str()))
+                }
+                _ => None,
+            })
+            .collect::<HashSet<_>>();
+        let unmentioned = self
+            .mcp_resource_subscriptions
+            .extract_if(|(server_id, uri)| {
+                !mentioned.contains(&(server_id.0.as_ref(), uri.as_str()))
+            })
+            .collect::<Vec<_>>();
+        if !unmentioned.is_empty() {
+            unsubscribe_from_mcp_resources(&self.project, unmentioned, cx);
+        }
+    }
+
+    /// Reloads the mentions of a resource whose contents the server reported to have changed,
+    /// so that the message is sent with its latest contents.
+    fn reload_mcp_resource(
+        &mut self,
+        server_id: &ContextServerId,
+        uri: &str,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(server) = self
+            .project
+            .read(cx)
+            .context_server_store()
+            .read(cx)
+            .get_running_server(server_id)
+        else {
+            return;
+        };
+        for (mention_uri, task) in self.mention_set.mentions.values_mut() {
+            if let MentionUri::McpResource {
+                server_id: mention_server_id,
+                uri: mention_resource_uri,
+                ..
+            } = mention_uri
+                && mention_server_id.as_str() == server_id.0.as_ref()
+                && mention_resource_uri.as_str() == uri
+            {
+                let read = read_mcp_resource(server.clone(), uri.to_string());
+                *task = cx
+                    .spawn(async move |_, _| read.await.map_err(|e| e.to_string()))
+                    .shared();
+            }
+        }
+    }
+
     pub fn confirm_mention_for_selection(
         &mut self,
         source_range: Range<text::Anchor>,
@@ -1490,6 +1609,52 @@ impl Render for ImageHover {
     }
 }
 
+/// Reads the text contents of a context server's resource.
+async fn read_mcp_resource(server: Arc<ContextServer>, uri: String) -> Result<Mention> {
+    let client = server
+        .client()
+        .ok_or_else(|| anyhow!("MCP server {} is not initialized", server.id()))?;
+    let uri = url::Url::parse(&uri)?;
+
+    let response = client
+        .request::<context_server::types::requests::ResourcesRead>(
+            context_server::types::ResourcesReadParams { uri, meta: None },
+        )
+        .await?;
+    let content = response
+        .contents
+        .into_iter()
+        .filter_map(|contents| match contents {
+            context_server::types::ResourceContentsType::Text(contents) => Some(contents.text),
+            context_server::types::ResourceContentsType::Blob(_) => None,
+        })
+        .collect::<Vec<_>>();
+    if content.is_empty() {
+        return Err(anyhow!("The resource has no text contents"));
+    }
+    Ok(Mention::Text {
+        content: content.join("\n\n"),
+        tracked_buffers: Vec::new(),
+    })
+}
+
+fn unsubscribe_from_mcp_resources(
+    project: &Entity<Project>,
+    resources: impl IntoIterator<Item = (ContextServerId, String)>,
+    cx: &mut App,
+) {
+    project
+        .read(cx)
+        .context_server_store()
+        .update(cx, |store, cx| {
+            for (server_id, uri) in resources {
+                store
+                    .unsubscribe_from_resource(&server_id, uri, cx)
+                    .detach_and_log_err(cx);
+            }
+        });
+}
+
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub enum Mention {
     Text {
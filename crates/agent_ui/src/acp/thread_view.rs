@@ -1245,12 +1245,17 @@ impl AcpThreadView {
         let Some(thread) = self.as_native_thread(cx) else {
             return;
         };
-        let project_context = thread.read(cx).project_context().read(cx);
+        let project_context = thread.read(cx).active_project_context(cx);
 
         let project_entry_ids = project_context
             .worktrees
             .iter()
-            .flat_map(|worktree| worktree.rules_file.as_ref())
+            .flat_map(|worktree| {
+                worktree
+                    .rules_file
+                    .iter()
+                    .chain(&worktree.nested_rules_files)
+            })
             .map(|rules_file| ProjectEntryId::from_usize(rules_file.project_entry_id))
             .collect::<Vec<_>>();
 
@@ -2941,8 +2946,7 @@ impl AcpThreadView {
         let project_context = self
             .as_native_thread(cx)?
             .read(cx)
-            .project_context()
-            .read(cx);
+            .active_project_context(cx);
 
         let user_rules_text = if project_context.user_rules.is_empty() {
             None
@@ -2968,7 +2972,12 @@ impl AcpThreadView {
         let rules_files = project_context
             .worktrees
             .iter()
-            .filter_map(|worktree| worktree.rules_file.as_ref())
+            .flat_map(|worktree| {
+                worktree
+                    .rules_file
+                    .iter()
+                    .chain(&worktree.nested_rules_files)
+            })
             .collect::<Vec<_>>();
 
         let rules_file_text = match rules_files.as_slice() {
@@ -4366,6 +4375,11 @@ impl AcpThreadView {
                 MentionUri::Fetch { url } => {
                     cx.open_url(url.as_str());
                 }
+                MentionUri::McpResource { uri, .. } => {
+                    if uri.starts_with("http://") || uri.starts_with("https://") {
+                        cx.open_url(&uri);
+                    }
+                }
             })
         } else {
             cx.open_url(&url);
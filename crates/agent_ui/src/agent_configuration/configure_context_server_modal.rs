@@ -4,7 +4,7 @@ use std::{
 };
 
 use anyhow::{Context as _, Result};
-use context_server::{ContextServerCommand, ContextServerId};
+use context_server::{ContextServerCommand, ContextServerHttpConfig, ContextServerId};
 use editor::{Editor, EditorElement, EditorStyle};
 use gpui::{
     AsyncWindowContext, DismissEvent, Entity, EventEmitter, FocusHandle, Focusable, Task,
@@ -34,7 +34,7 @@ enum ConfigurationTarget {
     New,
     Existing {
         id: ContextServerId,
-        command: ContextServerCommand,
+        settings: ContextServerSettings,
     },
     Extension {
         id: ContextServerId,
@@ -97,9 +97,9 @@ impl ConfigurationSource {
             ConfigurationTarget::New => ConfigurationSource::New {
                 editor: create_editor(context_server_input(None), jsonc_language, window, cx),
             },
-            ConfigurationTarget::Existing { id, command } => ConfigurationSource::Existing {
+            ConfigurationTarget::Existing { id, settings } => ConfigurationSource::Existing {
                 editor: create_editor(
-                    context_server_input(Some((id, command))),
+                    context_server_input(Some((id, settings))),
                     jsonc_language,
                     window,
                     cx,
@@ -141,15 +141,7 @@ impl ConfigurationSource {
     fn output(&self, cx: &mut App) -> Result<(ContextServerId, ContextServerSettings)> {
         match self {
             ConfigurationSource::New { editor } | ConfigurationSource::Existing { editor } => {
-                parse_input(&editor.read(cx).text(cx)).map(|(id, command)| {
-                    (
-                        id,
-                        ContextServerSettings::Custom {
-                            enabled: true,
-                            command,
-                        },
-                    )
-                })
+                parse_input(&editor.read(cx).text(cx))
             }
             ConfigurationSource::Extension {
                 id,
@@ -180,14 +172,33 @@ impl ConfigurationSource {
     }
 }
 
-fn context_server_input(existing: Option<(ContextServerId, ContextServerCommand)>) -> String {
+fn context_server_input(existing: Option<(ContextServerId, ContextServerSettings)>) -> String {
+    if let Some((id, ContextServerSettings::Http { http, .. })) = existing {
+        let headers = serde_json::to_string(&http.headers).unwrap();
+        let transport = serde_json::to_value(http.transport).unwrap();
+        return format!(
+            r#"{{
+  /// The name of your MCP server
+  "{}": {{
+    /// The URL of the MCP server
+    "url": "{}",
+    /// The headers to send, such as an "Authorization" header
+    "headers": {headers},
+    /// Either "streamable_http" or the legacy "sse"
+    "transport": {transport}
+  }}
+}}"#,
+            id.0, http.url
+        );
+    }
+
     let (name, command, args, env) = match existing {
-        Some((id, cmd)) => {
+        Some((id, ContextServerSettings::Custom { command: cmd, .. })) => {
             let args = serde_json::to_string(&cmd.args).unwrap();
             let env = serde_json::to_string(&cmd.env.unwrap_or_default()).unwrap();
             (id.0.to_string(), cmd.path, args, env)
         }
-        None => (
+        _ => (
             "some-mcp-server".to_string(),
             PathBuf::new(),
             "[]".to_string(),
@@ -303,13 +314,12 @@ impl ConfigureContextServerModal {
 
         window.spawn(cx, async move |cx| {
             let target = match settings {
-                ContextServerSettings::Custom {
-                    enabled: _,
-                    command,
-                } => Some(ConfigurationTarget::Existing {
-                    id: server_id,
-                    command,
-                }),
+                ContextServerSettings::Custom { .. } | ContextServerSettings::Http { .. } => {
+                    Some(ConfigurationTarget::Existing {
+                        id: server_id,
+                        settings,
+                    })
+                }
                 ContextServerSettings::Extension { .. } => {
                     match workspace
                         .update(cx, |workspace, cx| {
@@ -464,13 +474,28 @@ impl ConfigureContextServerModal {
     }
 }
 
-fn parse_input(text: &str) -> Result<(ContextServerId, ContextServerCommand)> {
+fn parse_input(text: &str) -> Result<(ContextServerId, ContextServerSettings)> {
     let value: serde_json::Value = serde_json_lenient::from_str(text)?;
     let object = value.as_object().context("Expected object")?;
     anyhow::ensure!(object.len() == 1, "Expected exactly one key-value pair");
     let (context_server_name, value) = object.into_iter().next().unwrap();
-    let command: ContextServerCommand = serde_json::from_value(value.clone())?;
-    Ok((ContextServerId(context_server_name.clone().into()), command))
+    let settings = if value.get("url").is_some() {
+        let http: ContextServerHttpConfig = serde_json::from_value(value.clone())?;
+        ContextServerSettings::Http {
+            enabled: true,
+            http,
+        }
+    } else {
+        let command: ContextServerCommand = serde_json::from_value(value.clone())?;
+        ContextServerSettings::Custom {
+            enabled: true,
+            command,
+        }
+    };
+    Ok((
+        ContextServerId(context_server_name.clone().into()),
+        settings,
+    ))
 }
 
 impl ModalView for ConfigureContextServerModal {}
@@ -749,6 +774,7 @@ fn wait_for_context_server(
                 _ => {}
             }
         }
+        project::context_server_store::Event::ResourceUpdated { .. } => {}
     });
 
     cx.spawn(async move |_cx| {
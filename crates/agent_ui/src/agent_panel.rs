@@ -19,9 +19,10 @@ use zed_actions::agent::{OpenClaudeCodeOnboardingModal, ReauthenticateAgent};
 use crate::acp::{AcpThreadHistory, ThreadHistoryEvent};
 use crate::ui::{AcpOnboardingModal, ClaudeCodeOnboardingModal};
 use crate::{
-    AddContextServer, DeleteRecentlyOpenThread, Follow, InlineAssistant, NewTextThread, NewThread,
-    OpenActiveThreadAsMarkdown, OpenHistory, ResetTrialEndUpsell, ResetTrialUpsell,
-    ToggleNavigationMenu, ToggleNewThreadMenu, ToggleOptionsMenu,
+    AddContextServer, DeleteRecentlyOpenThread, ExportThreadAsJson, ExportThreadAsMarkdown, Follow,
+    ImportThread, InlineAssistant, NewTextThread, NewThread, OpenActiveThreadAsMarkdown,
+    OpenHistory, ResetTrialEndUpsell, ResetTrialUpsell, ToggleNavigationMenu, ToggleNewThreadMenu,
+    ToggleOptionsMenu,
     acp::AcpThreadView,
     agent_configuration::{AgentConfiguration, AssistantConfigurationEvent},
     slash_command::SlashCommandCompletionProvider,
@@ -68,6 +69,7 @@ use util::ResultExt as _;
 use workspace::{
     CollaboratorId, DraggedSelection, DraggedTab, ToggleZoom, ToolbarItemView, Workspace,
     dock::{DockPosition, Panel, PanelEvent},
+    notifications::DetachAndPromptErr,
 };
 use zed_actions::{
     DecreaseBufferFontSize, IncreaseBufferFontSize, ResetBufferFontSize,
@@ -1202,6 +1204,106 @@ impl AgentPanel {
         }
     }
 
+    fn export_thread_as_markdown(
+        &mut self,
+        _: &ExportThreadAsMarkdown,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(thread) = self
+            .active_thread_view()
+            .and_then(|thread_view| thread_view.read(cx).thread().cloned())
+        else {
+            return;
+        };
+        let thread = thread.read(cx);
+        let title = thread.title();
+        let markdown = format!("# {title}\n\n{}", thread.to_markdown(cx));
+        self.export_thread(&title, "md", Task::ready(Ok(markdown)), window, cx);
+    }
+
+    fn export_thread_as_json(
+        &mut self,
+        _: &ExportThreadAsJson,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        // Only threads of Zed's own agent are stored in a format that can be imported again.
+        let Some(thread) = self
+            .active_thread_view()
+            .and_then(|thread_view| thread_view.read(cx).as_native_thread(cx))
+        else {
+            return;
+        };
+        let title = thread.read(cx).title();
+        let db_thread = thread.read(cx).to_db(cx);
+        let json = cx.background_spawn(async move { db_thread.await.to_export_json() });
+        self.export_thread(&title, "json", json, window, cx);
+    }
+
+    fn export_thread(
+        &self,
+        title: &str,
+        extension: &str,
+        content: Task<Result<String>>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let directory = self
+            .project
+            .read(cx)
+            .visible_worktrees(cx)
+            .next()
+            .map(|worktree| worktree.read(cx).abs_path().to_path_buf())
+            .unwrap_or_else(|| paths::home_dir().clone());
+        let file_name = export_file_name(title, extension);
+        let path = cx.prompt_for_new_path(&directory, Some(&file_name));
+        let fs = self.fs.clone();
+        cx.spawn(async move |_, _| {
+            let Some(path) = path.await.ok().transpose()?.flatten() else {
+                return Ok(());
+            };
+            fs.atomic_write(path, content.await?).await
+        })
+        .detach_and_prompt_err("Failed to export thread", window, cx, |error, _, _| {
+            Some(error.to_string())
+        });
+    }
+
+    fn import_thread(&mut self, _: &ImportThread, window: &mut Window, cx: &mut Context<Self>) {
+        let paths = cx.prompt_for_paths(gpui::PathPromptOptions {
+            files: true,
+            directories: false,
+            multiple: false,
+            prompt: Some("Import Thread".into()),
+        });
+        let fs = self.fs.clone();
+        let history_store = self.acp_history_store.clone();
+        cx.spawn_in(window, async move |this, cx| {
+            let Some(path) = paths
+                .await
+                .ok()
+                .transpose()?
+                .flatten()
+                .and_then(|paths| paths.into_iter().next())
+            else {
+                return Ok(());
+            };
+            let json = fs.load(&path).await?;
+            let thread = history_store
+                .update(cx, |history_store, cx| {
+                    history_store.import_thread(json, cx)
+                })?
+                .await?;
+            this.update_in(cx, |this, window, cx| {
+                this.load_agent_thread(thread, window, cx)
+            })
+        })
+        .detach_and_prompt_err("Failed to import thread", window, cx, |error, _, _| {
+            Some(error.to_string())
+        });
+    }
+
     fn handle_agent_configuration_event(
         &mut self,
         _entity: &Entity<AgentConfiguration>,
@@ -1474,6 +1576,26 @@ fn agent_panel_dock_position(cx: &App) -> DockPosition {
     AgentSettings::get_global(cx).dock.into()
 }
 
+/// Names a file after the thread's title, replacing the characters that aren't allowed in file
+/// names on any platform.
+fn export_file_name(title: &str, extension: &str) -> String {
+    let name = title
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '-',
+            c if c.is_control() => ' ',
+            c => c,
+        })
+        .collect::<String>();
+    // Windows drops trailing dots and spaces from file names.
+    let name = name.trim().trim_end_matches(['.', ' ']);
+    if name.is_empty() {
+        format!("Thread.{extension}")
+    } else {
+        format!("{name}.{extension}")
+    }
+}
+
 impl EventEmitter<PanelEvent> for AgentPanel {}
 
 impl Panel for AgentPanel {
@@ -1670,6 +1792,11 @@ impl AgentPanel {
         };
 
         let selected_agent = self.selected_agent.clone();
+        let active_thread_view = self.active_thread_view();
+        let has_thread =
+            active_thread_view.is_some_and(|thread_view| thread_view.read(cx).thread().is_some());
+        let has_native_thread = active_thread_view
+            .is_some_and(|thread_view| thread_view.read(cx).as_native_thread(cx).is_some());
 
         PopoverMenu::new("agent-options-menu")
             .trigger_with_tooltip(
@@ -1731,6 +1858,17 @@ impl AgentPanel {
                                 .separator()
                         }
 
+                        menu = menu
+                            .header("Threads")
+                            .when(has_thread, |menu| {
+                                menu.action("Export as Markdown…", Box::new(ExportThreadAsMarkdown))
+                            })
+                            .when(has_native_thread, |menu| {
+                                menu.action("Export as JSON…", Box::new(ExportThreadAsJson))
+                            })
+                            .action("Import…", Box::new(ImportThread))
+                            .separator();
+
                         menu = menu
                             .header("MCP Servers")
                             .action(
@@ -2475,6 +2613,9 @@ impl Render for AgentPanel {
                 this.open_configuration(window, cx);
             }))
             .on_action(cx.listener(Self::open_active_thread_as_markdown))
+            .on_action(cx.listener(Self::export_thread_as_markdown))
+            .on_action(cx.listener(Self::export_thread_as_json))
+            .on_action(cx.listener(Self::import_thread))
             .on_action(cx.listener(Self::deploy_rules_library))
             .on_action(cx.listener(Self::go_back))
             .on_action(cx.listener(Self::toggle_navigation_menu))
@@ -2687,3 +2828,20 @@ struct TrialEndUpsell;
 impl Dismissable for TrialEndUpsell {
     const KEY: &'static str = "dismissed-trial-end-upsell";
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_export_file_name() {
+        assert_eq!(export_file_name("Fix the build", "md"), "Fix the build.md");
+        assert_eq!(
+            export_file_name("What's in src/*.rs? <all> \"of it\"|C:\\x", "json"),
+            "What's in src--.rs- -all- -of it--C--x.json"
+        );
+        assert_eq!(export_file_name("Line\nbreak\t", "md"), "Line break.md");
+        assert_eq!(export_file_name("Wait for it...", "md"), "Wait for it.md");
+        assert_eq!(export_file_name(" . ", "md"), "Thread.md");
+    }
+}
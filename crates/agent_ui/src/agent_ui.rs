@@ -6,6 +6,7 @@ mod agent_panel;
 mod buffer_codegen;
 mod context_picker;
 mod context_server_configuration;
+mod context_server_sampling;
 mod context_strip;
 mod inline_assistant;
 mod inline_prompt_editor;
@@ -98,6 +99,12 @@ actions!(
         AcceptSuggestedContext,
         /// Opens the active thread as a markdown file.
         OpenActiveThreadAsMarkdown,
+        /// Exports the active thread to a Markdown file for sharing.
+        ExportThreadAsMarkdown,
+        /// Exports the active thread to a JSON file that can be imported again.
+        ExportThreadAsJson,
+        /// Imports a thread from a JSON file and adds it to the history.
+        ImportThread,
         /// Opens the agent diff view to review changes.
         OpenAgentDiff,
         /// Keeps the current suggestion or change.
@@ -267,6 +274,7 @@ pub fn init(
     agent::init(fs.clone(), cx);
     agent_panel::init(cx);
     context_server_configuration::init(language_registry.clone(), fs.clone(), cx);
+    context_server_sampling::init(fs.clone(), cx);
     TextThreadEditor::init(cx);
 
     register_slash_commands(cx);
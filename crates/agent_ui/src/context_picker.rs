@@ -1,6 +1,7 @@
 mod completion_provider;
 pub(crate) mod fetch_context_picker;
 pub(crate) mod file_context_picker;
+pub(crate) mod resource_context_picker;
 pub(crate) mod rules_context_picker;
 pub(crate) mod symbol_context_picker;
 pub(crate) mod thread_context_picker;
@@ -80,6 +81,7 @@ pub(crate) enum ContextPickerMode {
     Fetch,
     Thread,
     Rules,
+    Resources,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -117,6 +119,7 @@ impl TryFrom<&str> for ContextPickerMode {
             "fetch" => Ok(Self::Fetch),
             "thread" => Ok(Self::Thread),
             "rule" => Ok(Self::Rules),
+            "resource" => Ok(Self::Resources),
             _ => Err(format!("Invalid context picker mode: {}", value)),
         }
     }
@@ -130,6 +133,7 @@ impl ContextPickerMode {
             Self::Fetch => "fetch",
             Self::Thread => "thread",
             Self::Rules => "rule",
+            Self::Resources => "resource",
         }
     }
 
@@ -140,6 +144,7 @@ impl ContextPickerMode {
             Self::Fetch => "Fetch",
             Self::Thread => "Threads",
             Self::Rules => "Rules",
+            Self::Resources => "MCP Resources",
         }
     }
 
@@ -150,6 +155,7 @@ impl ContextPickerMode {
             Self::Fetch => IconName::ToolWeb,
             Self::Thread => IconName::Thread,
             Self::Rules => RULES_ICON,
+            Self::Resources => IconName::ZedMcpCustom,
         }
     }
 }
@@ -372,6 +378,9 @@ impl ContextPicker {
                         }));
                     }
                 }
+                // MCP resources can only be mentioned in agent threads, which use the
+                // completion menu instead of this picker.
+                ContextPickerMode::Resources => {}
             },
             ContextPickerEntry::Action(action) => match action {
                 ContextPickerAction::AddSelections => {
@@ -149,6 +149,9 @@ fn search(
             }
         }
 
+        // MCP resources are only offered when mentioning context in agent threads.
+        Some(ContextPickerMode::Resources) => Task::ready(Vec::new()),
+
         None => {
             if query.is_empty() {
                 let mut matches = recent_entries
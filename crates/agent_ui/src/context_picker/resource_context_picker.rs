@@ -0,0 +1,382 @@
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+
+use context_server::ContextServerId;
+use context_server::protocol::{InitializedContextServerProtocol, ServerCapability};
+use context_server::types;
+use futures::future::join_all;
+use fuzzy::StringMatchCandidate;
+use gpui::{App, Entity, Task};
+use util::ResultExt as _;
+use workspace::Workspace;
+
+/// A resource offered by a context server, or a resource template's URI filled in up to its
+/// next argument.
+#[derive(Clone, Debug)]
+pub(crate) struct ResourceMatch {
+    pub server_id: ContextServerId,
+    pub name: String,
+    pub uri: String,
+    pub description: Option<String>,
+    /// Whether `uri` comes from a template that still has arguments to fill in.
+    pub is_template: bool,
+}
+
+struct ServerResources {
+    server_id: ContextServerId,
+    client: Arc<InitializedContextServerProtocol>,
+    resources: Vec<types::Resource>,
+    templates: Vec<types::ResourceTemplate>,
+}
+
+/// Searches the resources and resource templates of every running context server.
+///
+/// When the query is the start of a URI matching a template, the server is asked to complete
+/// the argument being typed, so that templates can be filled in one argument at a time.
+pub(crate) fn search_resources(
+    query: String,
+    cancellation_flag: Arc<AtomicBool>,
+    workspace: &Entity<Workspace>,
+    cx: &mut App,
+) -> Task<Vec<ResourceMatch>> {
+    let clients = workspace
+        .read(cx)
+        .project()
+        .read(cx)
+        .context_server_store()
+        .read(cx)
+        .running_servers()
+        .into_iter()
+        .filter_map(|server| {
+            let client = server.client()?;
+            client
+                .capable(ServerCapability::Resources)
+                .then(|| (server.id(), client))
+        })
+        .collect::<Vec<_>>();
+    if clients.is_empty() {
+        return Task::ready(Vec::new());
+    }
+
+    let executor = cx.background_executor().clone();
+    cx.spawn(async move |_| {
+        let servers = join_all(clients.into_iter().map(|(server_id, client)| async move {
+            let resources = client
+                .request::<types::requests::ResourcesList>(())
+                .await
+                .log_err()
+                .map(|response| response.resources)
+                .unwrap_or_default();
+            // Templates are optional, so servers without any may not implement the request.
+            let templates = client
+                .request::<types::requests::ListResourceTemplates>(())
+                .await
+                .map(|response| response.resource_templates)
+                .unwrap_or_default();
+            ServerResources {
+                server_id,
+                client,
+                resources,
+                templates,
+            }
+        }))
+        .await;
+
+        let mut matches = Vec::new();
+        let mut candidates = Vec::new();
+        let mut candidate_matches = Vec::new();
+        for server in &servers {
+            for template in &server.templates {
+                let parts = parse_template(&template.uri_template);
+                let template_matches = match match_template(&parts, &query) {
+                    Some(TemplateMatch::Complete) => {
+                        vec![template_match(server, template, (query.clone(), false))]
+                    }
+                    Some(TemplateMatch::Prefix { filled, rest }) => {
+                        vec![template_match(server, template, expand(filled, "", rest))]
+                    }
+                    Some(TemplateMatch::Argument {
+                        filled,
+                        name,
+                        value,
+                        rest,
+                    }) => {
+                        let values = complete_argument(server, template, name, value).await;
+                        values
+                            .into_iter()
+                            .map(|value| {
+                                template_match(server, template, expand(filled, &value, rest))
+                            })
+                            .collect()
+                    }
+                    None => {
+                        candidates.push(StringMatchCandidate::new(
+                            candidate_matches.len(),
+                            &template.name,
+                        ));
+                        candidate_matches.push(template_match(
+                            server,
+                            template,
+                            expand("", "", &parts),
+                        ));
+                        Vec::new()
+                    }
+                };
+                matches.extend(template_matches);
+            }
+
+            for resource in &server.resources {
+                candidates.push(StringMatchCandidate::new(
+                    candidate_matches.len(),
+                    &format!("{} {}", resource.name, resource.uri),
+                ));
+                candidate_matches.push(ResourceMatch {
+                    server_id: server.server_id.clone(),
+                    name: resource.name.clone(),
+                    uri: resource.uri.to_string(),
+                    description: resource.description.clone(),
+                    is_template: false,
+                });
+            }
+        }
+
+        if query.is_empty() {
+            matches.extend(candidate_matches);
+        } else {
+            let fuzzy_matches = fuzzy::match_strings(
+                &candidates,
+                &query,
+                false,
+                true,
+                100,
+                &cancellation_flag,
+                executor,
+            )
+            .await;
+            matches.extend(
+                fuzzy_matches
+                    .into_iter()
+                    .map(|mat| candidate_matches[mat.candidate_id].clone()),
+            );
+        }
+        matches
+    })
+}
+
+fn template_match(
+    server: &ServerResources,
+    template: &types::ResourceTemplate,
+    (uri, is_template): (String, bool),
+) -> ResourceMatch {
+    ResourceMatch {
+        server_id: server.server_id.clone(),
+        name: if is_template {
+            template.name.clone()
+        } else {
+            uri.clone()
+        },
+        uri,
+        description: template.description.clone(),
+        is_template,
+    }
+}
+
+/// Returns the values the server suggests for a template argument, along with the value that
+/// was typed, so that arguments can be filled in by hand when the server has no suggestions.
+async fn complete_argument(
+    server: &ServerResources,
+    template: &types::ResourceTemplate,
+    name: &str,
+    value: &str,
+) -> Vec<String> {
+    let mut values = Vec::new();
+    if server.client.initialize.capabilities.completions.is_some() {
+        let response = server
+            .client
+            .request::<types::requests::CompletionComplete>(types::CompletionCompleteParams {
+                reference: types::CompletionReference::Resource(types::ResourceReference {
+                    ty: types::PromptReferenceType::Resource,
+                    uri: template.uri_template.clone(),
+                }),
+                argument: types::CompletionArgument {
+                    name: name.to_string(),
+                    value: value.to_string(),
+                },
+                meta: None,
+            })
+            .await;
+        if let Some(response) = response.log_err() {
+            values = response.completion.values;
+        }
+    }
+    if !value.is_empty() && !values.iter().any(|completion| completion == value) {
+        values.push(value.to_string());
+    }
+    values
+}
+
+#[derive(Debug, PartialEq)]
+enum TemplatePart {
+    Literal(String),
+    Argument(String),
+}
+
+/// Splits a URI template into its literal text and the names of its arguments. Only simple
+/// expressions are understood, so operators like the `+` in `{+path}` are ignored.
+fn parse_template(template: &str) -> Vec<TemplatePart> {
+    let mut parts = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{')
+        && let Some(len) = rest[start..].find('}')
+    {
+        if start > 0 {
+            parts.push(TemplatePart::Literal(rest[..start].to_string()));
+        }
+        let name =
+            rest[start + 1..start + len].trim_start_matches(['+', '#', '/', '.', ';', '?', '&']);
+        parts.push(TemplatePart::Argument(name.to_string()));
+        rest = &rest[start + len + 1..];
+    }
+    if !rest.is_empty() {
+        parts.push(TemplatePart::Literal(rest.to_string()));
+    }
+    parts
+}
+
+#[derive(Debug, PartialEq)]
+enum TemplateMatch<'a> {
+    /// The query ends within the template's literal text.
+    Prefix {
+        filled: &'a str,
+        rest: &'a [TemplatePart],
+    },
+    /// The query is a URI the template fully describes.
+    Complete,
+    /// The query ends within the value of an argument.
+    Argument {
+        filled: &'a str,
+        name: &'a str,
+        value: &'a str,
+        rest: &'a [TemplatePart],
+    },
+}
+
+fn match_template<'a>(parts: &'a [TemplatePart], query: &'a str) -> Option<TemplateMatch<'a>> {
+    let mut position = 0;
+    for (ix, part) in parts.iter().enumerate() {
+        let remaining = &query[position..];
+        match part {
+            TemplatePart::Literal(literal) => {
+                if remaining.starts_with(literal.as_str()) {
+                    position += literal.len();
+                } else if literal.starts_with(remaining) {
+                    return Some(TemplateMatch::Prefix {
+                        filled: &query[..position],
+                        rest: &parts[ix..],
+                    });
+                } else {
+                    return None;
+                }
+            }
+            TemplatePart::Argument(name) => {
+                // An argument's value ends where the literal text following it starts.
+                let end = match parts.get(ix + 1) {
+                    Some(TemplatePart::Literal(literal)) => remaining.find(literal.as_str()),
+                    _ => None,
+                };
+                match end {
+                    Some(end) => position += end,
+                    None => {
+                        return Some(TemplateMatch::Argument {
+                            filled: &query[..position],
+                            name,
+                            value: remaining,
+                            rest: &parts[ix + 1..],
+                        });
+                    }
+                }
+            }
+        }
+    }
+    (position == query.len()).then_some(TemplateMatch::Complete)
+}
+
+/// Fills in an argument with the given value, followed by the template's literal text up to
+/// its next argument. Returns the resulting text and whether any arguments remain.
+fn expand(filled: &str, value: &str, rest: &[TemplatePart]) -> (String, bool) {
+    let mut text = format!("{filled}{value}");
+    for part in rest {
+        match part {
+            TemplatePart::Literal(literal) => text.push_str(literal),
+            TemplatePart::Argument(_) => return (text, true),
+        }
+    }
+    (text, false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fill_template() {
+        let parts = parse_template("repo://{owner}/{+repo}/README");
+        assert_eq!(
+            parts,
+            [
+                TemplatePart::Literal("repo://".into()),
+                TemplatePart::Argument("owner".into()),
+                TemplatePart::Literal("/".into()),
+                TemplatePart::Argument("repo".into()),
+                TemplatePart::Literal("/README".into()),
+            ]
+        );
+
+        assert_eq!(
+            match_template(&parts, ""),
+            Some(TemplateMatch::Prefix {
+                filled: "",
+                rest: &parts[..],
+            })
+        );
+        assert_eq!(expand("", "", &parts), ("repo://".into(), true));
+        assert_eq!(match_template(&parts, "file://"), None);
+
+        let Some(TemplateMatch::Argument {
+            filled,
+            name,
+            value,
+            rest,
+        }) = match_template(&parts, "repo://ze")
+        else {
+            panic!("expected the owner to be completed");
+        };
+        assert_eq!((filled, name, value), ("repo://", "owner", "ze"));
+        assert_eq!(
+            expand(filled, "zed-industries", rest),
+            ("repo://zed-industries/".into(), true)
+        );
+
+        let Some(TemplateMatch::Argument {
+            filled,
+            name,
+            value,
+            rest,
+        }) = match_template(&parts, "repo://zed-industries/")
+        else {
+            panic!("expected the repository to be completed");
+        };
+        assert_eq!(
+            (filled, name, value),
+            ("repo://zed-industries/", "repo", "")
+        );
+        assert_eq!(
+            expand(filled, "zed", rest),
+            ("repo://zed-industries/zed/README".into(), false)
+        );
+        assert_eq!(
+            match_template(&parts, "repo://zed-industries/zed/README"),
+            Some(TemplateMatch::Complete)
+        );
+    }
+}
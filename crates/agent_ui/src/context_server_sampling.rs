@@ -0,0 +1,357 @@
+use std::fmt::Write as _;
+use std::sync::Arc;
+
+use agent_settings::{AgentSettings, ContextServerSamplingSettings};
+use anyhow::{Context as _, Result};
+use context_server::ContextServerId;
+use context_server::types::{self, Request as _, requests::CreateMessage};
+use fs::Fs;
+use futures::StreamExt as _;
+use gpui::{AnyWindowHandle, AsyncApp, PromptLevel, Task};
+use language_model::{
+    ConfiguredModel, LanguageModelId, LanguageModelProviderId, LanguageModelRegistry,
+    LanguageModelRequest, LanguageModelRequestMessage, MessageContent, Role,
+};
+use settings::{Settings as _, update_settings_file};
+use ui::prelude::*;
+use workspace::Workspace;
+
+/// Lets the context servers of every workspace's project ask for completions, which are answered
+/// by the model chosen for them once the user approves the request.
+pub(crate) fn init(fs: Arc<dyn Fs>, cx: &mut App) {
+    cx.observe_new(move |workspace: &mut Workspace, window, cx| {
+        let Some(window) = window else {
+            return;
+        };
+        let window_handle = window.window_handle();
+        let fs = fs.clone();
+        workspace
+            .project()
+            .read(cx)
+            .context_server_store()
+            .update(cx, |store, _| {
+                store.set_request_handler(
+                    CreateMessage::METHOD,
+                    Arc::new(move |server_id, params, cx| {
+                        create_message(server_id, params, window_handle, fs.clone(), cx)
+                    }),
+                );
+            });
+    })
+    .detach();
+}
+
+fn create_message(
+    server_id: ContextServerId,
+    params: serde_json::Value,
+    window_handle: AnyWindowHandle,
+    fs: Arc<dyn Fs>,
+    cx: AsyncApp,
+) -> Task<Result<serde_json::Value>> {
+    cx.spawn(async move |cx| {
+        let request: types::CreateMessageRequest = serde_json::from_value(params)?;
+        let (settings, model) = cx.update(|cx| {
+            let settings = AgentSettings::get_global(cx)
+                .context_server_sampling
+                .clone();
+            let model = sampling_model(&settings, cx);
+            (settings, model)
+        })?;
+        let ConfiguredModel { model, .. } =
+            model.context("No language model is configured to answer context servers")?;
+
+        let max_tokens = request.max_tokens.min(settings.max_tokens);
+        let completion_request = completion_request(&request);
+        let prompt_tokens = cx
+            .update(|cx| model.count_tokens(completion_request.clone(), cx))?
+            .await?;
+        anyhow::ensure!(
+            prompt_tokens <= settings.max_prompt_tokens,
+            "The request's {prompt_tokens} tokens exceed the limit of {} tokens",
+            settings.max_prompt_tokens
+        );
+
+        if !settings.is_auto_approved(&server_id.0) {
+            let answer = cx.update_window(window_handle, |_, window, cx| {
+                window.prompt(
+                    PromptLevel::Info,
+                    &format!("The {server_id} MCP server wants to use {}", model.name().0),
+                    Some(&describe_request(&request, max_tokens)),
+                    &["Allow", "Always Allow", "Deny"],
+                    cx,
+                )
+            })?;
+            match answer.await? {
+                0 => {}
+                1 => cx.update(|cx| {
+                    update_settings_file(fs, cx, move |settings, _| {
+                        settings
+                            .agent
+                            .get_or_insert_default()
+                            .context_server_sampling
+                            .get_or_insert_default()
+                            .add_auto_approved_server(server_id.0);
+                    })
+                })?,
+                _ => anyhow::bail!("The user declined the request"),
+            }
+        }
+
+        let mut stream = model
+            .stream_completion_text(completion_request, cx)
+            .await?
+            .stream;
+        let mut text = String::new();
+        let mut stop_reason = "endTurn";
+        while let Some(chunk) = stream.next().await {
+            text.push_str(&chunk?);
+            // Models can't be asked to stop at a number of tokens, so the response is cut short
+            // once it's estimated to have reached the limit, at about four bytes per token.
+            if text.len() / 4 >= max_tokens as usize {
+                stop_reason = "maxTokens";
+                break;
+            }
+        }
+
+        Ok(serde_json::to_value(types::CreateMessageResult {
+            role: types::Role::Assistant,
+            content: types::MessageContent::Text {
+                text,
+                annotations: None,
+            },
+            model: model.id().0.to_string(),
+            stop_reason: Some(stop_reason.to_string()),
+        })?)
+    })
+}
+
+fn sampling_model(settings: &ContextServerSamplingSettings, cx: &App) -> Option<ConfiguredModel> {
+    let registry = LanguageModelRegistry::read_global(cx);
+    let Some(selection) = settings.model.as_ref() else {
+        return registry.default_model();
+    };
+    let provider =
+        registry.provider(&LanguageModelProviderId::from(selection.provider.0.clone()))?;
+    let model_id = LanguageModelId::from(selection.model.clone());
+    let model = provider
+        .provided_models(cx)
+        .into_iter()
+        .find(|model| model.id() == model_id)?;
+    Some(ConfiguredModel { provider, model })
+}
+
+fn completion_request(request: &types::CreateMessageRequest) -> LanguageModelRequest {
+    let system_prompt =
+        request
+            .system_prompt
+            .as_ref()
+            .map(|system_prompt| LanguageModelRequestMessage {
+                role: Role::System,
+                content: vec![MessageContent::Text(system_prompt.clone())],
+                cache: false,
+            });
+    let messages = request
+        .messages
+        .iter()
+        .map(|message| LanguageModelRequestMessage {
+            role: match message.role {
+                types::Role::User => Role::User,
+                types::Role::Assistant => Role::Assistant,
+            },
+            content: vec![MessageContent::Text(message_text(&message.content))],
+            cache: false,
+        });
+
+    LanguageModelRequest {
+        messages: system_prompt.into_iter().chain(messages).collect(),
+        stop: request.stop_sequences.clone().unwrap_or_default(),
+        temperature: request.temperature.map(|temperature| temperature as f32),
+        ..Default::default()
+    }
+}
+
+/// Only text is passed on to the model, with other content replaced by a description of it.
+fn message_text(content: &types::MessageContent) -> String {
+    match content {
+        types::MessageContent::Text { text, .. } => text.clone(),
+        types::MessageContent::Image { mime_type, .. } => format!("[{mime_type} image omitted]"),
+        types::MessageContent::Audio { mime_type, .. } => format!("[{mime_type} audio omitted]"),
+        types::MessageContent::Resource { resource, .. } => format!("[resource {}]", resource.uri),
+    }
+}
+
+fn describe_request(request: &types::CreateMessageRequest, max_tokens: u32) -> String {
+    let mut description = String::new();
+    if let Some(system_prompt) = &request.system_prompt {
+        writeln!(description, "System: {system_prompt}\n").ok();
+    }
+    for message in &request.messages {
+        let role = match message.role {
+            types::Role::User => "User",
+            types::Role::Assistant => "Assistant",
+        };
+        writeln!(description, "{role}: {}\n", message_text(&message.content)).ok();
+    }
+    write!(description, "Up to {max_tokens} tokens may be generated.").ok();
+    description
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use context_server::{
+        ContextServer,
+        test::{FakeTransport, create_fake_transport},
+    };
+    use fs::FakeFs;
+    use gpui::{AnyWindowHandle, TestAppContext, VisualContext as _};
+    use serde_json::json;
+    use settings::SettingsStore;
+
+    const SERVER_ID: &str = "sampling-server";
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            AgentSettings::register(cx);
+            LanguageModelRegistry::test(cx);
+        });
+    }
+
+    async fn start_server(
+        fs: Arc<dyn Fs>,
+        window: AnyWindowHandle,
+        cx: &mut TestAppContext,
+    ) -> (ContextServer, Arc<FakeTransport>) {
+        let transport = Arc::new(create_fake_transport(SERVER_ID, cx.executor()));
+        let server = ContextServer::new(ContextServerId(SERVER_ID.into()), transport.clone());
+        server.set_request_handler(
+            CreateMessage::METHOD,
+            Arc::new(move |params, cx| {
+                create_message(
+                    ContextServerId(SERVER_ID.into()),
+                    params,
+                    window,
+                    fs.clone(),
+                    cx,
+                )
+            }),
+        );
+        server.start(&cx.to_async()).await.unwrap();
+        (server, transport)
+    }
+
+    fn request_message(transport: &FakeTransport, max_tokens: u32) {
+        transport.simulate_request(
+            1,
+            CreateMessage::METHOD,
+            json!({
+                "messages": [{ "role": "user", "content": { "type": "text", "text": "Hello" } }],
+                "maxTokens": max_tokens,
+            }),
+        );
+    }
+
+    fn fake_model(cx: &mut TestAppContext) -> Arc<dyn language_model::LanguageModel> {
+        cx.update(|cx| {
+            LanguageModelRegistry::read_global(cx)
+                .default_model()
+                .unwrap()
+                .model
+        })
+    }
+
+    #[gpui::test]
+    async fn test_declined_requests_are_not_sent_to_the_model(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        let window = cx.add_empty_window().window_handle();
+        let (_server, transport) = start_server(fs, window, cx).await;
+
+        request_message(&transport, 100);
+        cx.run_until_parked();
+        assert!(cx.has_pending_prompt());
+        cx.simulate_prompt_answer("Deny");
+        cx.run_until_parked();
+
+        assert_eq!(fake_model(cx).as_fake().completion_count(), 0);
+        assert_eq!(
+            transport.client_responses()[0]["error"]["message"],
+            "The user declined the request"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_always_allowing_a_server(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.create_dir(paths::settings_file().parent().unwrap())
+            .await
+            .unwrap();
+        fs.insert_file(paths::settings_file(), b"{}".to_vec()).await;
+        let window = cx.add_empty_window().window_handle();
+        let (_server, transport) = start_server(fs.clone(), window, cx).await;
+
+        request_message(&transport, 100);
+        cx.run_until_parked();
+        cx.simulate_prompt_answer("Always Allow");
+        cx.run_until_parked();
+
+        let model = fake_model(cx);
+        model
+            .as_fake()
+            .send_last_completion_stream_text_chunk("Hi there");
+        model.as_fake().end_last_completion_stream();
+        cx.run_until_parked();
+
+        let result = &transport.client_responses()[0]["result"];
+        assert_eq!(result["content"]["text"], "Hi there");
+        assert_eq!(result["stopReason"], "endTurn");
+
+        let settings_content = fs.load(paths::settings_file()).await.unwrap();
+        let settings: serde_json::Value = serde_json::from_str(&settings_content).unwrap();
+        assert_eq!(
+            settings["agent"]["context_server_sampling"]["auto_approve"],
+            json!([SERVER_ID])
+        );
+    }
+
+    #[gpui::test]
+    async fn test_responses_are_capped_at_max_tokens(cx: &mut TestAppContext) {
+        init_test(cx);
+        cx.update(|cx| {
+            SettingsStore::update_global(cx, |store, cx| {
+                store.update_user_settings(cx, |settings| {
+                    let sampling = settings
+                        .agent
+                        .get_or_insert_default()
+                        .context_server_sampling
+                        .get_or_insert_default();
+                    sampling.add_auto_approved_server(SERVER_ID.into());
+                    sampling.max_tokens = Some(2);
+                });
+            });
+        });
+        let fs = FakeFs::new(cx.executor());
+        let window = cx.add_empty_window().window_handle();
+        let (_server, transport) = start_server(fs, window, cx).await;
+
+        // The server's own limit is higher than the one in the settings, so the latter applies.
+        request_message(&transport, 100);
+        cx.run_until_parked();
+        assert!(!cx.has_pending_prompt());
+
+        let model = fake_model(cx);
+        for chunk in ["abcd", "efgh", "ijkl"] {
+            model
+                .as_fake()
+                .send_last_completion_stream_text_chunk(chunk);
+        }
+        cx.run_until_parked();
+
+        let result = &transport.client_responses()[0]["result"];
+        assert_eq!(result["content"]["text"], "abcdefgh");
+        assert_eq!(result["stopReason"], "maxTokens");
+    }
+}
@@ -875,6 +875,7 @@ impl ContextStore {
                     _ => {}
                 }
             }
+            project::context_server_store::Event::ResourceUpdated { .. } => {}
         }
     }
 
@@ -1577,6 +1577,7 @@ impl EditAgentTest {
             root_name: "root".to_string(),
             abs_path: Path::new("/path/to/root").into(),
             rules_file: None,
+            nested_rules_files: Vec::new(),
         }];
         let prompt_builder = PromptBuilder::new(None)?;
         let project_context = ProjectContext::new(worktrees, Vec::default());
@@ -36,7 +36,8 @@ pub const INTERNAL_ERROR: i32 = -32603;
 
 type ResponseHandler = Box<dyn Send + FnOnce(Result<String, Error>)>;
 type NotificationHandler = Box<dyn Send + FnMut(Value, AsyncApp)>;
-type RequestHandler = Box<dyn Send + FnMut(RequestId, &RawValue, AsyncApp)>;
+/// Answers a request that the context server sent to the client, given its params.
+pub type RequestHandler = Arc<dyn Send + Sync + Fn(Value, AsyncApp) -> Task<Result<Value>>>;
 
 #[derive(Debug, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
 #[serde(untagged)]
@@ -51,6 +52,7 @@ pub(crate) struct Client {
     outbound_tx: channel::Sender<String>,
     name: Arc<str>,
     notification_handlers: Arc<Mutex<HashMap<&'static str, NotificationHandler>>>,
+    request_handlers: Arc<Mutex<HashMap<&'static str, RequestHandler>>>,
     response_handlers: Arc<Mutex<Option<HashMap<RequestId, ResponseHandler>>>>,
     #[allow(clippy::type_complexity)]
     #[allow(dead_code)]
@@ -89,6 +91,16 @@ pub struct AnyRequest<'a> {
     pub params: Option<&'a RawValue>,
 }
 
+#[derive(Serialize)]
+struct OutgoingResponse {
+    jsonrpc: &'static str,
+    id: RequestId,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<Error>,
+}
+
 #[derive(Serialize, Deserialize)]
 struct AnyResponse<'a> {
     jsonrpc: &'a str,
@@ -202,12 +214,14 @@ impl Client {
             let response_handlers = response_handlers.clone();
             let request_handlers = request_handlers.clone();
             let transport = transport.clone();
+            let outbound_tx = outbound_tx.clone();
             async move |cx| {
                 Self::handle_input(
                     transport,
                     notification_handlers,
                     request_handlers,
                     response_handlers,
+                    outbound_tx,
                     cx,
                 )
                 .log_err()
@@ -237,6 +251,7 @@ impl Client {
         Ok(Self {
             server_id,
             notification_handlers,
+            request_handlers,
             response_handlers,
             name: server_name,
             next_id: Default::default(),
@@ -252,14 +267,16 @@ impl Client {
     /// Handles input from the server's stdout.
     ///
     /// This function continuously reads lines from the provided stdout stream,
-    /// parses them as JSON-RPC responses or notifications, and dispatches them
-    /// to the appropriate handlers. It processes both responses (which are matched
-    /// to pending requests) and notifications (which trigger registered handlers).
+    /// parses them as JSON-RPC requests, responses or notifications, and dispatches them
+    /// to the appropriate handlers. It processes requests (which are answered by
+    /// registered handlers), responses (which are matched to pending requests) and
+    /// notifications (which trigger registered handlers).
     async fn handle_input(
         transport: Arc<dyn Transport>,
         notification_handlers: Arc<Mutex<HashMap<&'static str, NotificationHandler>>>,
         request_handlers: Arc<Mutex<HashMap<&'static str, RequestHandler>>>,
         response_handlers: Arc<Mutex<Option<HashMap<RequestId, ResponseHandler>>>>,
+        outbound_tx: channel::Sender<String>,
         cx: &mut AsyncApp,
     ) -> anyhow::Result<()> {
         let mut receiver = transport.receive();
@@ -267,14 +284,43 @@ impl Client {
         while let Some(message) = receiver.next().await {
             log::trace!("recv: {}", &message);
             if let Ok(request) = serde_json::from_str::<AnyRequest>(&message) {
-                let mut request_handlers = request_handlers.lock();
-                if let Some(handler) = request_handlers.get_mut(request.method) {
-                    handler(
-                        request.id,
-                        request.params.unwrap_or(RawValue::NULL),
-                        cx.clone(),
-                    );
-                }
+                let handler = request_handlers.lock().get(request.method).cloned();
+                let response = match handler {
+                    Some(handler) => {
+                        let params = request
+                            .params
+                            .and_then(|params| serde_json::from_str(params.get()).ok())
+                            .unwrap_or(Value::Null);
+                        let response = handler(params, cx.clone());
+                        future::Either::Left(async move {
+                            response.await.map_err(|error| Error {
+                                message: format!("{error:#}"),
+                                code: INTERNAL_ERROR,
+                            })
+                        })
+                    }
+                    None => future::Either::Right(future::ready(Err(Error {
+                        message: format!("Method not found: {}", request.method),
+                        code: METHOD_NOT_FOUND,
+                    }))),
+                };
+                let id = request.id;
+                let outbound_tx = outbound_tx.clone();
+                cx.background_spawn(async move {
+                    let (result, error) = match response.await {
+                        Ok(result) => (Some(result), None),
+                        Err(error) => (None, Some(error)),
+                    };
+                    let response = serde_json::to_string(&OutgoingResponse {
+                        jsonrpc: JSON_RPC_VERSION,
+                        id,
+                        result,
+                        error,
+                    })
+                    .unwrap();
+                    outbound_tx.send(response).await.ok();
+                })
+                .detach();
             } else if let Ok(response) = serde_json::from_str::<AnyResponse>(&message) {
                 if let Some(handlers) = response_handlers.lock().as_mut()
                     && let Some(handler) = handlers.remove(&response.id)
@@ -458,6 +504,12 @@ impl Client {
     ) {
         self.notification_handlers.lock().insert(method, f);
     }
+
+    /// Registers a handler answering the context server's requests for the given method.
+    /// Requests without a handler are answered with a "method not found" error.
+    pub fn on_request(&self, method: &'static str, handler: RequestHandler) {
+        self.request_handlers.lock().insert(method, handler);
+    }
 }
 
 #[derive(Debug)]
@@ -485,3 +537,75 @@ impl fmt::Debug for Client {
             .finish_non_exhaustive()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::FakeTransport;
+    use gpui::TestAppContext;
+    use serde_json::json;
+
+    fn fake_client(transport: Arc<FakeTransport>, cx: &mut TestAppContext) -> Client {
+        Client::new(
+            ContextServerId("test".into()),
+            "test".into(),
+            transport,
+            None,
+            cx.to_async(),
+        )
+        .unwrap()
+    }
+
+    #[gpui::test]
+    async fn test_server_requests_are_answered_by_handlers(cx: &mut TestAppContext) {
+        let transport = Arc::new(FakeTransport::new(cx.executor()));
+        let client = fake_client(transport.clone(), cx);
+        client.on_request(
+            "roots/list",
+            Arc::new(|params, _| Task::ready(Ok(json!({ "echo": params })))),
+        );
+        client.on_request(
+            "sampling/createMessage",
+            Arc::new(|_, _| Task::ready(Err(anyhow!("declined")))),
+        );
+
+        transport.simulate_request(1, "roots/list", json!({ "cursor": "a" }));
+        transport.simulate_request(2, "sampling/createMessage", json!({}));
+        cx.run_until_parked();
+
+        let mut responses = transport.client_responses();
+        responses.sort_by_key(|response| response["id"].as_u64());
+        assert_eq!(
+            responses,
+            [
+                json!({ "jsonrpc": "2.0", "id": 1, "result": { "echo": { "cursor": "a" } } }),
+                json!({
+                    "jsonrpc": "2.0",
+                    "id": 2,
+                    "error": { "message": "declined", "code": INTERNAL_ERROR }
+                }),
+            ]
+        );
+    }
+
+    #[gpui::test]
+    async fn test_server_requests_without_handlers_are_not_found(cx: &mut TestAppContext) {
+        let transport = Arc::new(FakeTransport::new(cx.executor()));
+        let _client = fake_client(transport.clone(), cx);
+
+        transport.simulate_request(7, "elicitation/create", json!({}));
+        cx.run_until_parked();
+
+        assert_eq!(
+            transport.client_responses(),
+            [json!({
+                "jsonrpc": "2.0",
+                "id": 7,
+                "error": {
+                    "message": "Method not found: elicitation/create",
+                    "code": METHOD_NOT_FOUND
+                }
+            })]
+        );
+    }
+}
@@ -8,13 +8,20 @@ pub mod types;
 
 use std::path::Path;
 use std::sync::Arc;
+use std::time::Duration;
 use std::{fmt::Display, path::PathBuf};
 
-use anyhow::Result;
-use client::Client;
+use anyhow::{Context as _, Result};
+use client::{Client, RequestHandler};
+use collections::HashMap;
 use gpui::AsyncApp;
+use http_client::HttpClient;
 use parking_lot::RwLock;
-pub use settings::ContextServerCommand;
+pub use settings::{ContextServerCommand, ContextServerHttpConfig, ContextServerHttpTransport};
+use url::Url;
+
+use crate::transport::{SseTransport, StreamableHttpTransport, Transport};
+use crate::types::Request as _;
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct ContextServerId(pub Arc<str>);
@@ -27,6 +34,7 @@ impl Display for ContextServerId {
 
 enum ContextServerTransport {
     Stdio(ContextServerCommand, Option<PathBuf>),
+    Http(ContextServerHttpConfig, Arc<dyn HttpClient>),
     Custom(Arc<dyn crate::transport::Transport>),
 }
 
@@ -34,8 +42,12 @@ pub struct ContextServer {
     id: ContextServerId,
     client: RwLock<Option<Arc<crate::protocol::InitializedContextServerProtocol>>>,
     configuration: ContextServerTransport,
+    request_handlers: RwLock<HashMap<&'static str, RequestHandler>>,
+    notification_handlers: RwLock<HashMap<&'static str, NotificationHandler>>,
 }
 
+pub type NotificationHandler = Arc<dyn Send + Sync + Fn(serde_json::Value, AsyncApp)>;
+
 impl ContextServer {
     pub fn stdio(
         id: ContextServerId,
@@ -45,6 +57,8 @@ impl ContextServer {
         Self {
             id,
             client: RwLock::new(None),
+            request_handlers: RwLock::default(),
+            notification_handlers: RwLock::default(),
             configuration: ContextServerTransport::Stdio(
                 command,
                 working_directory.map(|directory| directory.to_path_buf()),
@@ -52,10 +66,26 @@ impl ContextServer {
         }
     }
 
+    pub fn http(
+        id: ContextServerId,
+        config: ContextServerHttpConfig,
+        http_client: Arc<dyn HttpClient>,
+    ) -> Self {
+        Self {
+            id,
+            client: RwLock::new(None),
+            request_handlers: RwLock::default(),
+            notification_handlers: RwLock::default(),
+            configuration: ContextServerTransport::Http(config, http_client),
+        }
+    }
+
     pub fn new(id: ContextServerId, transport: Arc<dyn crate::transport::Transport>) -> Self {
         Self {
             id,
             client: RwLock::new(None),
+            request_handlers: RwLock::default(),
+            notification_handlers: RwLock::default(),
             configuration: ContextServerTransport::Custom(transport),
         }
     }
@@ -88,8 +118,29 @@ impl ContextServer {
         self.initialize(client).await
     }
 
+    /// Answers the server's requests for the given method with the given handler.
+    ///
+    /// Handlers should be set before the server is started, so that the client
+    /// capabilities they provide are advertised to it during initialization.
+    pub fn set_request_handler(&self, method: &'static str, handler: RequestHandler) {
+        if let Some(client) = self.client() {
+            client.on_request(method, handler.clone());
+        }
+        self.request_handlers.write().insert(method, handler);
+    }
+
+    /// Handles the server's notifications for the given method with the given handler,
+    /// including those sent after the server is restarted.
+    pub fn set_notification_handler(&self, method: &'static str, handler: NotificationHandler) {
+        if let Some(client) = self.client() {
+            let handler = handler.clone();
+            client.on_notification(method, Box::new(move |params, cx| handler(params, cx)));
+        }
+        self.notification_handlers.write().insert(method, handler);
+    }
+
     fn new_client(&self, cx: &AsyncApp) -> Result<Client> {
-        Ok(match &self.configuration {
+        let client = match &self.configuration {
             ContextServerTransport::Stdio(command, working_directory) => Client::stdio(
                 client::ContextServerId(self.id.0.clone()),
                 client::ModelContextServerBinary {
@@ -101,6 +152,33 @@ impl ContextServer {
                 working_directory,
                 cx.clone(),
             )?,
+            ContextServerTransport::Http(config, http_client) => {
+                let url = Url::parse(&config.url)
+                    .with_context(|| format!("invalid context server URL {:?}", config.url))?;
+                let transport: Arc<dyn Transport> = match config.transport {
+                    ContextServerHttpTransport::StreamableHttp => {
+                        Arc::new(StreamableHttpTransport::new(
+                            http_client.clone(),
+                            url,
+                            config.headers.clone(),
+                            cx,
+                        ))
+                    }
+                    ContextServerHttpTransport::Sse => Arc::new(SseTransport::new(
+                        http_client.clone(),
+                        url,
+                        config.headers.clone(),
+                        cx,
+                    )),
+                };
+                Client::new(
+                    client::ContextServerId(self.id.0.clone()),
+                    self.id().0,
+                    transport,
+                    config.timeout.map(Duration::from_millis),
+                    cx.clone(),
+                )?
+            }
             ContextServerTransport::Custom(transport) => Client::new(
                 client::ContextServerId(self.id.0.clone()),
                 self.id().0,
@@ -108,7 +186,15 @@ impl ContextServer {
                 None,
                 cx.clone(),
             )?,
-        })
+        };
+        for (method, handler) in self.request_handlers.read().iter() {
+            client.on_request(*method, handler.clone());
+        }
+        for (method, handler) in self.notification_handlers.read().iter() {
+            let handler = handler.clone();
+            client.on_notification(*method, Box::new(move |params, cx| handler(params, cx)));
+        }
+        Ok(client)
     }
 
     async fn initialize(&self, client: Client) -> Result<()> {
@@ -118,7 +204,21 @@ impl ContextServer {
             name: "Zed".to_string(),
             version: env!("CARGO_PKG_VERSION").to_string(),
         };
-        let initialized_protocol = protocol.initialize(client_info).await?;
+        let capabilities = {
+            let request_handlers = self.request_handlers.read();
+            types::ClientCapabilities {
+                experimental: None,
+                sampling: request_handlers
+                    .contains_key(types::requests::CreateMessage::METHOD)
+                    .then(|| serde_json::json!({})),
+                roots: request_handlers
+                    .contains_key(types::requests::ListRoots::METHOD)
+                    .then_some(types::RootsCapabilities {
+                        list_changed: Some(true),
+                    }),
+            }
+        };
+        let initialized_protocol = protocol.initialize(client_info, capabilities).await?;
 
         log::debug!(
             "context server {} initialized: {:?}",
@@ -41,6 +41,7 @@ pub struct FakeTransport {
     >,
     tx: futures::channel::mpsc::UnboundedSender<String>,
     rx: Arc<Mutex<futures::channel::mpsc::UnboundedReceiver<String>>>,
+    client_responses: Arc<parking_lot::Mutex<Vec<serde_json::Value>>>,
     executor: BackgroundExecutor,
 }
 
@@ -51,10 +52,27 @@ impl FakeTransport {
             request_handlers: Default::default(),
             tx,
             rx: Arc::new(Mutex::new(rx)),
+            client_responses: Default::default(),
             executor,
         }
     }
 
+    /// Sends a request to the client, as if the server had made it.
+    pub fn simulate_request(&self, id: u64, method: &str, params: serde_json::Value) {
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": method,
+            "params": params,
+        });
+        self.tx.unbounded_send(request.to_string()).unwrap();
+    }
+
+    /// Returns the responses the client sent to the requests made with [`Self::simulate_request`].
+    pub fn client_responses(&self) -> Vec<serde_json::Value> {
+        self.client_responses.lock().clone()
+    }
+
     pub fn on_request<T, Fut>(
         mut self,
         handler: impl 'static + Send + Sync + Fn(T::Params) -> Fut,
@@ -101,6 +119,8 @@ impl Transport for FakeTransport {
                 } else {
                     log::debug!("No handler registered for MCP request '{method}'");
                 }
+            } else if msg.get("id").is_some() {
+                self.client_responses.lock().push(msg);
             }
         }
         Ok(())
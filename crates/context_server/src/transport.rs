@@ -1,3 +1,6 @@
+mod event_stream;
+mod http_transport;
+mod sse_transport;
 mod stdio_transport;
 
 use std::pin::Pin;
@@ -6,6 +9,8 @@ use anyhow::Result;
 use async_trait::async_trait;
 use futures::Stream;
 
+pub use http_transport::StreamableHttpTransport;
+pub use sse_transport::SseTransport;
 pub use stdio_transport::*;
 
 #[async_trait]
@@ -0,0 +1,112 @@
+use anyhow::Result;
+use futures::{AsyncBufReadExt as _, AsyncRead, Stream, StreamExt as _, io::BufReader, stream};
+
+/// A single event of a `text/event-stream` response.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Event {
+    /// The event type, which is empty for the default `message` type.
+    pub event: String,
+    pub data: String,
+    pub id: Option<String>,
+}
+
+impl Event {
+    pub fn is_message(&self) -> bool {
+        self.event.is_empty() || self.event == "message"
+    }
+}
+
+/// Parses the events of a `text/event-stream` body, as described in
+/// https://html.spec.whatwg.org/multipage/server-sent-events.html#event-stream-interpretation.
+pub fn parse_events<R>(reader: R) -> impl Stream<Item = Result<Event>> + Send
+where
+    R: AsyncRead + Unpin + Send + 'static,
+{
+    let lines = BufReader::new(reader).lines();
+    stream::unfold(Some(lines), |lines| async move {
+        let mut lines = lines?;
+        let mut event = Event::default();
+        let mut has_data = false;
+        loop {
+            let line = match lines.next().await {
+                // An event that isn't terminated by a blank line is discarded.
+                None => return None,
+                Some(Err(error)) => return Some((Err(error.into()), None)),
+                Some(Ok(line)) => line,
+            };
+
+            if line.is_empty() {
+                if has_data {
+                    return Some((Ok(event), Some(lines)));
+                }
+                event = Event::default();
+                continue;
+            }
+            if line.starts_with(':') {
+                continue;
+            }
+
+            let (field, value) = match line.split_once(':') {
+                Some((field, value)) => (field, value.strip_prefix(' ').unwrap_or(value)),
+                None => (line.as_str(), ""),
+            };
+            match field {
+                "event" => event.event = value.to_string(),
+                "data" => {
+                    if has_data {
+                        event.data.push('\n');
+                    }
+                    event.data.push_str(value);
+                    has_data = true;
+                }
+                "id" => event.id = Some(value.to_string()),
+                _ => {}
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_events() {
+        let body = concat!(
+            ": keep-alive\n",
+            "\n",
+            "event: endpoint\n",
+            "data: /messages?session=1\n",
+            "\n",
+            "id: 7\r\n",
+            "data: {\"a\":\r\n",
+            "data:1}\r\n",
+            "\r\n",
+            "data: unterminated\n",
+        );
+        let events = futures::executor::block_on(
+            parse_events(futures::io::Cursor::new(body.as_bytes().to_vec())).collect::<Vec<_>>(),
+        )
+        .into_iter()
+        .collect::<Result<Vec<_>>>()
+        .unwrap();
+
+        assert_eq!(
+            events,
+            [
+                Event {
+                    event: "endpoint".into(),
+                    data: "/messages?session=1".into(),
+                    id: None,
+                },
+                Event {
+                    event: String::new(),
+                    data: "{\"a\":\n1}".into(),
+                    id: Some("7".into()),
+                },
+            ]
+        );
+        assert!(!events[0].is_message());
+        assert!(events[1].is_message());
+    }
+}
@@ -0,0 +1,646 @@
+use std::pin::{Pin, pin};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{Context as _, Result, anyhow};
+use async_trait::async_trait;
+use collections::HashMap;
+use futures::{AsyncReadExt as _, Stream, StreamExt as _};
+use gpui::{AsyncApp, BackgroundExecutor, Task};
+use http_client::{
+    AsyncBody, HttpClient, HttpRequestExt as _, Method, Request, Response, StatusCode, http,
+};
+use parking_lot::Mutex;
+use serde_json::Value;
+use smol::channel;
+use url::Url;
+
+use crate::client::INTERNAL_ERROR;
+use crate::transport::Transport;
+use crate::transport::event_stream::parse_events;
+
+const SESSION_ID_HEADER: &str = "Mcp-Session-Id";
+pub(super) const INITIALIZED_NOTIFICATION: &str =
+    r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#;
+pub(super) const MAX_ATTEMPTS: u32 = 5;
+const INITIAL_BACKOFF: Duration = Duration::from_millis(250);
+
+/// A transport for context servers that speak MCP's Streamable HTTP transport, where every
+/// message is POSTed to a single endpoint and the server answers with either a JSON body or an
+/// event stream.
+///
+/// The session the server assigns is sent back with every request. When the server forgets it,
+/// for example because it restarted, a new session is started by replaying the `initialize`
+/// request the client sent, so that the client can carry on. The request that found the session
+/// expired fails rather than being sent again, as the server may have acted on it already.
+pub struct StreamableHttpTransport {
+    connection: Arc<Connection>,
+    inbound_rx: channel::Receiver<String>,
+    listen_task: Mutex<Option<Task<()>>>,
+}
+
+struct Connection {
+    http_client: Arc<dyn HttpClient>,
+    url: Url,
+    headers: HashMap<String, String>,
+    session_id: Mutex<Option<String>>,
+    initialize_request: Mutex<Option<String>>,
+    inbound_tx: channel::Sender<String>,
+    executor: BackgroundExecutor,
+}
+
+impl StreamableHttpTransport {
+    pub fn new(
+        http_client: Arc<dyn HttpClient>,
+        url: Url,
+        headers: HashMap<String, String>,
+        cx: &AsyncApp,
+    ) -> Self {
+        let (inbound_tx, inbound_rx) = channel::unbounded();
+        Self {
+            connection: Arc::new(Connection {
+                http_client,
+                url,
+                headers,
+                session_id: Mutex::new(None),
+                initialize_request: Mutex::new(None),
+                inbound_tx,
+                executor: cx.background_executor().clone(),
+            }),
+            inbound_rx,
+            listen_task: Mutex::new(None),
+        }
+    }
+}
+
+impl Connection {
+    fn request(&self, method: Method) -> http::request::Builder {
+        let mut request =
+            with_headers(Request::builder().method(method), &self.headers).uri(self.url.as_str());
+        if let Some(session_id) = self.session_id.lock().clone() {
+            request = request.header(SESSION_ID_HEADER, session_id);
+        }
+        request
+    }
+
+    fn post_request(&self, message: &str) -> Result<Request<AsyncBody>> {
+        Ok(self
+            .request(Method::POST)
+            .header("Content-Type", "application/json")
+            .header("Accept", "application/json, text/event-stream")
+            .body(AsyncBody::from(message.to_string()))?)
+    }
+
+    fn update_session_id(&self, response: &Response<AsyncBody>) {
+        if let Some(session_id) = response
+            .headers()
+            .get(SESSION_ID_HEADER)
+            .and_then(|value| value.to_str().ok())
+        {
+            *self.session_id.lock() = Some(session_id.to_string());
+        }
+    }
+
+    /// POSTs the message, starting a new session if the server no longer knows the current one.
+    ///
+    /// Only notifications are sent again after a failure: the server may have acted on a request
+    /// before failing, and sending a request like `tools/call` twice could repeat its effects.
+    async fn post(&self, message: &str) -> Result<Response<AsyncBody>> {
+        let can_retry = !is_request(message);
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            let has_session = self.session_id.lock().is_some();
+            let error = match self.http_client.send(self.post_request(message)?).await {
+                Ok(response) if response.status().is_success() => {
+                    self.update_session_id(&response);
+                    return Ok(response);
+                }
+                Ok(response)
+                    if response.status() == StatusCode::NOT_FOUND
+                        && has_session
+                        && attempt < MAX_ATTEMPTS =>
+                {
+                    *self.session_id.lock() = None;
+                    match self.reinitialize().await {
+                        Ok(()) if can_retry => continue,
+                        Ok(()) => anyhow::bail!(
+                            "the context server session expired, so the request was not handled"
+                        ),
+                        Err(error) => error,
+                    }
+                }
+                Ok(response) if is_transient(response.status()) => {
+                    anyhow!("server responded with {}", response.status())
+                }
+                Ok(response) => {
+                    let status = response.status();
+                    let body = read_body(response).await.unwrap_or_default();
+                    anyhow::bail!("server responded with {status}: {body}");
+                }
+                Err(error) => error,
+            };
+
+            if !can_retry || attempt >= MAX_ATTEMPTS {
+                return Err(error);
+            }
+            log::warn!(
+                "failed to reach context server at {}, retrying: {error:#}",
+                self.url
+            );
+            self.executor.timer(backoff(attempt)).await;
+        }
+    }
+
+    /// Starts a new session by replaying the client's initialization. The client already has the
+    /// server's answer to its `initialize` request, so the new one is discarded.
+    async fn reinitialize(&self) -> Result<()> {
+        let initialize = self
+            .initialize_request
+            .lock()
+            .clone()
+            .context("session expired before the server was initialized")?;
+        log::info!(
+            "context server session at {} expired, starting a new one",
+            self.url
+        );
+
+        for message in [initialize.as_str(), INITIALIZED_NOTIFICATION] {
+            let response = self.http_client.send(self.post_request(message)?).await?;
+            anyhow::ensure!(
+                response.status().is_success(),
+                "server responded with {}",
+                response.status()
+            );
+            self.update_session_id(&response);
+            read_body(response).await?;
+        }
+        Ok(())
+    }
+
+    async fn handle_response(self: &Arc<Self>, response: Response<AsyncBody>) -> Result<()> {
+        let is_event_stream = response
+            .headers()
+            .get("Content-Type")
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| value.starts_with("text/event-stream"));
+        if is_event_stream {
+            // The server may send requests and notifications of its own before answering, so the
+            // stream is read in the background to let other messages through in the meantime.
+            let inbound_tx = self.inbound_tx.clone();
+            self.executor
+                .spawn(async move {
+                    let mut events = pin!(parse_events(response.into_body()));
+                    while let Some(event) = events.next().await {
+                        match event {
+                            Ok(event) if event.is_message() => {
+                                forward_messages(&event.data, &inbound_tx).await;
+                            }
+                            Ok(_) => {}
+                            Err(error) => {
+                                log::error!("failed to read context server response: {error:#}");
+                                break;
+                            }
+                        }
+                    }
+                })
+                .detach();
+        } else {
+            let body = read_body(response).await?;
+            forward_messages(&body, &self.inbound_tx).await;
+        }
+        Ok(())
+    }
+
+    /// Listens for messages the server sends outside of any response, reconnecting whenever the
+    /// stream ends and resuming after the last event that was received.
+    async fn listen(self: Arc<Self>) {
+        let mut last_event_id = None;
+        let mut failures = 0;
+        loop {
+            let request = self
+                .request(Method::GET)
+                .header("Accept", "text/event-stream")
+                .when_some(last_event_id.clone(), |request, id: String| {
+                    request.header("Last-Event-ID", id)
+                })
+                .body(AsyncBody::empty());
+            let response = match request {
+                Ok(request) => self.http_client.send(request).await,
+                Err(error) => Err(error.into()),
+            };
+
+            match response {
+                // Servers don't have to offer this stream.
+                Ok(response) if response.status() == StatusCode::METHOD_NOT_ALLOWED => return,
+                Ok(response) if response.status().is_success() => {
+                    failures = 0;
+                    let mut events = pin!(parse_events(response.into_body()));
+                    while let Some(Ok(event)) = events.next().await {
+                        if event.id.is_some() {
+                            last_event_id = event.id.clone();
+                        }
+                        if event.is_message() {
+                            forward_messages(&event.data, &self.inbound_tx).await;
+                        }
+                    }
+                }
+                Ok(response) => {
+                    failures += 1;
+                    log::warn!(
+                        "context server at {} responded with {} to a GET request",
+                        self.url,
+                        response.status()
+                    );
+                }
+                Err(error) => {
+                    failures += 1;
+                    log::warn!("failed to reach context server at {}: {error:#}", self.url);
+                }
+            }
+
+            if failures >= MAX_ATTEMPTS {
+                log::error!(
+                    "giving up on listening to context server at {} after {failures} attempts",
+                    self.url
+                );
+                return;
+            }
+            self.executor.timer(backoff(failures.max(1))).await;
+        }
+    }
+}
+
+#[async_trait]
+impl Transport for StreamableHttpTransport {
+    async fn send(&self, message: String) -> Result<()> {
+        let method = message_method(&message);
+        if method.as_deref() == Some("initialize") {
+            *self.connection.session_id.lock() = None;
+            *self.connection.initialize_request.lock() = Some(message.clone());
+        }
+
+        // The message is POSTed in the background, so that a slow response or the retries of a
+        // message don't hold up the ones sent after it. Responses and failures are delivered
+        // through the inbound channel.
+        let connection = self.connection.clone();
+        let post = self.connection.executor.spawn(async move {
+            let result = match connection.post(&message).await {
+                Ok(response) => connection.handle_response(response).await,
+                Err(error) => Err(error),
+            };
+            if let Err(error) = result {
+                fail_message(&message, error, &connection.inbound_tx).await;
+            }
+        });
+
+        // Once initialized, the server can also send messages without being asked.
+        if method.as_deref() == Some("notifications/initialized") {
+            let mut listen_task = self.listen_task.lock();
+            if listen_task.is_none() {
+                let connection = self.connection.clone();
+                *listen_task = Some(self.connection.executor.spawn(async move {
+                    post.await;
+                    connection.listen().await
+                }));
+                return Ok(());
+            }
+        }
+        post.detach();
+        Ok(())
+    }
+
+    fn receive(&self) -> Pin<Box<dyn Stream<Item = String> + Send>> {
+        Box::pin(self.inbound_rx.clone())
+    }
+
+    fn receive_err(&self) -> Pin<Box<dyn Stream<Item = String> + Send>> {
+        Box::pin(futures::stream::empty())
+    }
+}
+
+impl Drop for StreamableHttpTransport {
+    fn drop(&mut self) {
+        // Let the server know that the session is over, so that it can release its resources.
+        let has_session = self.connection.session_id.lock().is_some();
+        if has_session
+            && let Ok(request) = self
+                .connection
+                .request(Method::DELETE)
+                .body(AsyncBody::empty())
+        {
+            self.connection
+                .executor
+                .spawn(self.connection.http_client.send(request))
+                .detach();
+        }
+    }
+}
+
+pub(super) fn with_headers(
+    mut request: http::request::Builder,
+    headers: &HashMap<String, String>,
+) -> http::request::Builder {
+    for (name, value) in headers {
+        request = request.header(name.as_str(), value.as_str());
+    }
+    request
+}
+
+pub(super) fn is_transient(status: StatusCode) -> bool {
+    status.is_server_error()
+        || status == StatusCode::TOO_MANY_REQUESTS
+        || status == StatusCode::REQUEST_TIMEOUT
+}
+
+pub(super) fn backoff(attempt: u32) -> Duration {
+    INITIAL_BACKOFF * 2u32.pow(attempt.saturating_sub(1))
+}
+
+/// Returns whether the message is a request, which the server answers, as opposed to a
+/// notification or a response to one of the server's requests.
+pub(super) fn is_request(message: &str) -> bool {
+    serde_json::from_str::<Value>(message).is_ok_and(|message| {
+        message.get("method").is_some() && message.get("id").is_some_and(|id| !id.is_null())
+    })
+}
+
+pub(super) fn message_method(message: &str) -> Option<String> {
+    let message = serde_json::from_str::<Value>(message).ok()?;
+    Some(message.get("method")?.as_str()?.to_string())
+}
+
+pub(super) async fn read_body(response: Response<AsyncBody>) -> Result<String> {
+    let mut body = String::new();
+    response.into_body().read_to_string(&mut body).await?;
+    Ok(body)
+}
+
+/// Passes the messages in a body received from the server on to the client, unpacking batches.
+pub(super) async fn forward_messages(body: &str, inbound_tx: &channel::Sender<String>) {
+    let body = body.trim();
+    if body.is_empty() {
+        return;
+    }
+    if let Ok(Value::Array(messages)) = serde_json::from_str::<Value>(body) {
+        for message in messages {
+            inbound_tx.send(message.to_string()).await.ok();
+        }
+    } else {
+        inbound_tx.send(body.to_string()).await.ok();
+    }
+}
+
+/// Reports a message that couldn't be delivered. Requests are answered with an error, so that
+/// the client doesn't wait for a response that will never come.
+pub(super) async fn fail_message(
+    message: &str,
+    error: anyhow::Error,
+    inbound_tx: &channel::Sender<String>,
+) {
+    log::error!("failed to send message to context server: {error:#}");
+    if let Ok(Value::Object(message)) = serde_json::from_str::<Value>(message)
+        && message.contains_key("method")
+        && let Some(id) = message.get("id")
+    {
+        let response = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": {
+                "code": INTERNAL_ERROR,
+                "message": format!("{error:#}"),
+            },
+        });
+        inbound_tx.send(response.to_string()).await.ok();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::TestAppContext;
+    use http_client::FakeHttpClient;
+    use serde_json::json;
+
+    #[derive(Default)]
+    struct FakeServer {
+        next_session: usize,
+        session: Option<String>,
+        methods: Vec<String>,
+    }
+
+    #[gpui::test]
+    async fn test_session_handling(cx: &mut TestAppContext) {
+        let server = Arc::new(Mutex::new(FakeServer::default()));
+        let http_client = FakeHttpClient::create({
+            let server = server.clone();
+            move |request| {
+                let server = server.clone();
+                async move {
+                    assert_eq!(request.headers()["Authorization"], "Bearer token");
+                    if request.method() != Method::POST {
+                        return Ok(Response::builder()
+                            .status(StatusCode::METHOD_NOT_ALLOWED)
+                            .body(AsyncBody::empty())?);
+                    }
+
+                    let session = request
+                        .headers()
+                        .get(SESSION_ID_HEADER)
+                        .map(|value| value.to_str().unwrap().to_string());
+                    let mut body = String::new();
+                    request.into_body().read_to_string(&mut body).await?;
+                    let message = serde_json::from_str::<Value>(&body)?;
+                    let method = message["method"].as_str().unwrap().to_string();
+
+                    let mut server = server.lock();
+                    server.methods.push(method.clone());
+                    if method == "initialize" {
+                        server.next_session += 1;
+                        let session = format!("session-{}", server.next_session);
+                        server.session = Some(session.clone());
+                        return Ok(Response::builder()
+                            .header("Content-Type", "application/json")
+                            .header(SESSION_ID_HEADER, session)
+                            .body(
+                                json!({ "jsonrpc": "2.0", "id": message["id"], "result": {} })
+                                    .to_string()
+                                    .into(),
+                            )?);
+                    }
+                    if session != server.session {
+                        return Ok(Response::builder()
+                            .status(StatusCode::NOT_FOUND)
+                            .body(AsyncBody::empty())?);
+                    }
+                    if message.get("id").is_none() {
+                        return Ok(Response::builder()
+                            .status(StatusCode::ACCEPTED)
+                            .body(AsyncBody::empty())?);
+                    }
+
+                    let progress = json!({ "jsonrpc": "2.0", "method": "notifications/progress" });
+                    let result = json!({ "jsonrpc": "2.0", "id": message["id"], "result": {} });
+                    Ok(Response::builder()
+                        .header("Content-Type", "text/event-stream")
+                        .body(format!("data: {progress}\n\ndata: {result}\n\n").into())?)
+                }
+            }
+        });
+
+        let transport = StreamableHttpTransport::new(
+            http_client,
+            Url::parse("http://localhost/mcp").unwrap(),
+            HashMap::from_iter([("Authorization".to_string(), "Bearer token".to_string())]),
+            &cx.to_async(),
+        );
+        let mut messages = transport.receive();
+
+        let request = |id: u32, method: &str| {
+            json!({ "jsonrpc": "2.0", "id": id, "method": method }).to_string()
+        };
+        let id_of =
+            |message: String| serde_json::from_str::<Value>(&message).unwrap()["id"].clone();
+
+        transport.send(request(0, "initialize")).await.unwrap();
+        assert_eq!(id_of(messages.next().await.unwrap()), json!(0));
+        transport
+            .send(INITIALIZED_NOTIFICATION.to_string())
+            .await
+            .unwrap();
+        cx.run_until_parked();
+
+        transport.send(request(1, "tools/list")).await.unwrap();
+        assert_eq!(
+            id_of(messages.next().await.unwrap()),
+            Value::Null,
+            "notifications sent before the response are passed on"
+        );
+        assert_eq!(id_of(messages.next().await.unwrap()), json!(1));
+
+        // Restarting the server forgets the session, which the transport replaces. The request
+        // that found it expired fails instead of being sent again.
+        server.lock().session = None;
+        transport.send(request(2, "tools/list")).await.unwrap();
+        let response = serde_json::from_str::<Value>(&messages.next().await.unwrap()).unwrap();
+        assert_eq!(response["id"], json!(2));
+        assert_eq!(response["error"]["code"], json!(INTERNAL_ERROR));
+        transport.send(request(3, "tools/list")).await.unwrap();
+        assert_eq!(id_of(messages.next().await.unwrap()), Value::Null);
+        assert_eq!(id_of(messages.next().await.unwrap()), json!(3));
+        assert_eq!(
+            server.lock().methods,
+            [
+                "initialize",
+                "notifications/initialized",
+                "tools/list",
+                "tools/list",
+                "initialize",
+                "notifications/initialized",
+                "tools/list",
+            ]
+        );
+        assert_eq!(
+            transport.connection.session_id.lock().as_deref(),
+            Some("session-2")
+        );
+    }
+
+    #[gpui::test]
+    async fn test_only_notifications_are_retried(cx: &mut TestAppContext) {
+        let methods = Arc::new(Mutex::new(Vec::new()));
+        let http_client = FakeHttpClient::create({
+            let methods = methods.clone();
+            move |request| {
+                let methods = methods.clone();
+                async move {
+                    let mut body = String::new();
+                    request.into_body().read_to_string(&mut body).await?;
+                    let message = serde_json::from_str::<Value>(&body)?;
+                    methods
+                        .lock()
+                        .push(message["method"].as_str().unwrap().to_string());
+                    Ok(Response::builder()
+                        .status(StatusCode::SERVICE_UNAVAILABLE)
+                        .body(AsyncBody::empty())?)
+                }
+            }
+        });
+        let transport = Arc::new(StreamableHttpTransport::new(
+            http_client,
+            Url::parse("http://localhost/mcp").unwrap(),
+            HashMap::default(),
+            &cx.to_async(),
+        ));
+        let mut messages = transport.receive();
+
+        transport
+            .send(json!({ "jsonrpc": "2.0", "id": 1, "method": "tools/call" }).to_string())
+            .await
+            .unwrap();
+        let response = serde_json::from_str::<Value>(&messages.next().await.unwrap()).unwrap();
+        assert_eq!(response["id"], json!(1));
+        assert_eq!(
+            *methods.lock(),
+            ["tools/call"],
+            "a request the server may have acted on isn't sent twice"
+        );
+
+        methods.lock().clear();
+        let send = cx.executor().spawn({
+            let transport = transport.clone();
+            async move {
+                let notification =
+                    json!({ "jsonrpc": "2.0", "method": "notifications/roots/list_changed" });
+                transport.send(notification.to_string()).await
+            }
+        });
+        for _ in 0..MAX_ATTEMPTS {
+            cx.executor().advance_clock(Duration::from_secs(10));
+            cx.run_until_parked();
+        }
+        send.await.unwrap();
+        assert_eq!(methods.lock().len(), MAX_ATTEMPTS as usize);
+    }
+
+    #[gpui::test]
+    async fn test_send_does_not_wait_for_responses(cx: &mut TestAppContext) {
+        let http_client = FakeHttpClient::create(|request| async move {
+            let mut body = String::new();
+            request.into_body().read_to_string(&mut body).await?;
+            let message = serde_json::from_str::<Value>(&body)?;
+            if message["method"] == "tools/call" {
+                futures::future::pending::<()>().await;
+            }
+            Ok(Response::builder()
+                .header("Content-Type", "application/json")
+                .body(
+                    json!({ "jsonrpc": "2.0", "id": message["id"], "result": {} })
+                        .to_string()
+                        .into(),
+                )?)
+        });
+        let transport = StreamableHttpTransport::new(
+            http_client,
+            Url::parse("http://localhost/mcp").unwrap(),
+            HashMap::default(),
+            &cx.to_async(),
+        );
+        let mut messages = transport.receive();
+
+        transport
+            .send(json!({ "jsonrpc": "2.0", "id": 1, "method": "tools/call" }).to_string())
+            .await
+            .unwrap();
+        transport
+            .send(json!({ "jsonrpc": "2.0", "id": 2, "method": "tools/list" }).to_string())
+            .await
+            .unwrap();
+        let response = serde_json::from_str::<Value>(&messages.next().await.unwrap()).unwrap();
+        assert_eq!(
+            response["id"],
+            json!(2),
+            "a request still waiting for its response shouldn't hold up the ones sent after it"
+        );
+    }
+}
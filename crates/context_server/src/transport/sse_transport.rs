@@ -0,0 +1,355 @@
+use std::pin::{Pin, pin};
+use std::sync::Arc;
+
+use anyhow::{Context as _, Result, anyhow};
+use async_trait::async_trait;
+use collections::HashMap;
+use futures::{Stream, StreamExt as _};
+use gpui::{AsyncApp, BackgroundExecutor, Task};
+use http_client::{AsyncBody, HttpClient, Method, Request, StatusCode};
+use parking_lot::Mutex;
+use postage::{prelude::Stream as _, sink::Sink as _, watch};
+use smol::channel;
+use url::Url;
+
+use crate::transport::Transport;
+use crate::transport::event_stream::parse_events;
+use crate::transport::http_transport::{
+    INITIALIZED_NOTIFICATION, MAX_ATTEMPTS, backoff, fail_message, forward_messages, is_request,
+    is_transient, message_method, read_body, with_headers,
+};
+
+/// A transport for context servers that speak MCP's legacy HTTP+SSE transport, where the
+/// server's messages arrive over a long-lived event stream and the client's are POSTed to an
+/// endpoint the server announces on that stream.
+///
+/// Whenever the stream is lost, it is reopened and the client's initialization is replayed to
+/// the new endpoint, since the server treats every stream as a new session.
+pub struct SseTransport {
+    connection: Arc<Connection>,
+    endpoint_rx: watch::Receiver<Option<Url>>,
+    inbound_rx: channel::Receiver<String>,
+    _listen_task: Task<()>,
+}
+
+struct Connection {
+    http_client: Arc<dyn HttpClient>,
+    url: Url,
+    headers: HashMap<String, String>,
+    initialize_request: Mutex<Option<String>>,
+    inbound_tx: channel::Sender<String>,
+    executor: BackgroundExecutor,
+}
+
+impl SseTransport {
+    pub fn new(
+        http_client: Arc<dyn HttpClient>,
+        url: Url,
+        headers: HashMap<String, String>,
+        cx: &AsyncApp,
+    ) -> Self {
+        let (inbound_tx, inbound_rx) = channel::unbounded();
+        let (endpoint_tx, endpoint_rx) = watch::channel();
+        let connection = Arc::new(Connection {
+            http_client,
+            url,
+            headers,
+            initialize_request: Mutex::new(None),
+            inbound_tx,
+            executor: cx.background_executor().clone(),
+        });
+        let listen_task = cx
+            .background_executor()
+            .spawn(connection.clone().listen(endpoint_tx));
+        Self {
+            connection,
+            endpoint_rx,
+            inbound_rx,
+            _listen_task: listen_task,
+        }
+    }
+
+    async fn endpoint(&self) -> Result<Url> {
+        let mut endpoint_rx = self.endpoint_rx.clone();
+        loop {
+            let endpoint = endpoint_rx.borrow().clone();
+            if let Some(endpoint) = endpoint {
+                return Ok(endpoint);
+            }
+            endpoint_rx
+                .recv()
+                .await
+                .context("lost the connection to the context server")?;
+        }
+    }
+
+    /// POSTs the message to the current endpoint, retrying the transient failures of
+    /// notifications, which includes the server having forgotten the session before the stream
+    /// was reopened. Requests aren't sent twice, as the server may have acted on them already.
+    async fn post(&self, message: &str) -> Result<()> {
+        let can_retry = !is_request(message);
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            let endpoint = self.endpoint().await?;
+            let error = match self.connection.post(&endpoint, message).await {
+                Ok(()) => return Ok(()),
+                Err(PostError::Fatal(error)) => return Err(error),
+                Err(PostError::Transient(error)) => error,
+            };
+
+            if !can_retry || attempt >= MAX_ATTEMPTS {
+                return Err(error);
+            }
+            log::warn!("failed to reach context server at {endpoint}, retrying: {error:#}");
+            self.connection.executor.timer(backoff(attempt)).await;
+        }
+    }
+}
+
+enum PostError {
+    Transient(anyhow::Error),
+    Fatal(anyhow::Error),
+}
+
+impl Connection {
+    async fn post(&self, endpoint: &Url, message: &str) -> Result<(), PostError> {
+        let request = with_headers(Request::builder().method(Method::POST), &self.headers)
+            .uri(endpoint.as_str())
+            .header("Content-Type", "application/json")
+            .body(AsyncBody::from(message.to_string()))
+            .map_err(|error| PostError::Fatal(error.into()))?;
+        match self.http_client.send(request).await {
+            Ok(response) if response.status().is_success() => Ok(()),
+            Ok(response)
+                if is_transient(response.status())
+                    || response.status() == StatusCode::NOT_FOUND =>
+            {
+                Err(PostError::Transient(anyhow!(
+                    "server responded with {}",
+                    response.status()
+                )))
+            }
+            Ok(response) => {
+                let status = response.status();
+                let body = read_body(response).await.unwrap_or_default();
+                Err(PostError::Fatal(anyhow!(
+                    "server responded with {status}: {body}"
+                )))
+            }
+            Err(error) => Err(PostError::Transient(error)),
+        }
+    }
+
+    /// Keeps the event stream open, publishing the endpoint to POST messages to and passing the
+    /// server's messages on to the client.
+    async fn listen(self: Arc<Self>, mut endpoint_tx: watch::Sender<Option<Url>>) {
+        let mut failures = 0;
+        loop {
+            let request = with_headers(Request::builder().method(Method::GET), &self.headers)
+                .uri(self.url.as_str())
+                .header("Accept", "text/event-stream")
+                .body(AsyncBody::empty());
+            let response = match request {
+                Ok(request) => self.http_client.send(request).await,
+                Err(error) => Err(error.into()),
+            };
+
+            match response {
+                Ok(response) if response.status().is_success() => {
+                    failures = 0;
+                    let mut events = pin!(parse_events(response.into_body()));
+                    while let Some(Ok(event)) = events.next().await {
+                        if event.event == "endpoint" {
+                            match self.url.join(event.data.trim()) {
+                                Ok(endpoint) => {
+                                    self.reinitialize(&endpoint).await;
+                                    endpoint_tx.send(Some(endpoint)).await.ok();
+                                }
+                                Err(error) => {
+                                    log::error!("context server sent an invalid endpoint: {error}")
+                                }
+                            }
+                        } else if event.is_message() {
+                            forward_messages(&event.data, &self.inbound_tx).await;
+                        }
+                    }
+                    endpoint_tx.send(None).await.ok();
+                }
+                Ok(response) => {
+                    failures += 1;
+                    log::warn!(
+                        "context server at {} responded with {}",
+                        self.url,
+                        response.status()
+                    );
+                }
+                Err(error) => {
+                    failures += 1;
+                    log::warn!("failed to reach context server at {}: {error:#}", self.url);
+                }
+            }
+
+            if failures >= MAX_ATTEMPTS {
+                log::error!(
+                    "giving up on context server at {} after {failures} attempts",
+                    self.url
+                );
+                return;
+            }
+            self.executor.timer(backoff(failures.max(1))).await;
+        }
+    }
+
+    /// Replays the client's initialization to a new endpoint, if the client was initialized over
+    /// an earlier stream. The server's answer arrives on the stream, where the client ignores it.
+    async fn reinitialize(&self, endpoint: &Url) {
+        let Some(initialize) = self.initialize_request.lock().clone() else {
+            return;
+        };
+        log::info!(
+            "reconnected to context server at {}, starting a new session",
+            self.url
+        );
+        for message in [initialize.as_str(), INITIALIZED_NOTIFICATION] {
+            if let Err(PostError::Transient(error) | PostError::Fatal(error)) =
+                self.post(endpoint, message).await
+            {
+                log::error!("failed to restore context server session: {error:#}");
+                return;
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl Transport for SseTransport {
+    async fn send(&self, message: String) -> Result<()> {
+        match self.post(&message).await {
+            // Only initialization that reached the server is replayed after reconnecting.
+            Ok(()) if message_method(&message).as_deref() == Some("initialize") => {
+                *self.connection.initialize_request.lock() = Some(message);
+            }
+            Ok(()) => {}
+            Err(error) => fail_message(&message, error, &self.connection.inbound_tx).await,
+        }
+        Ok(())
+    }
+
+    fn receive(&self) -> Pin<Box<dyn Stream<Item = String> + Send>> {
+        Box::pin(self.inbound_rx.clone())
+    }
+
+    fn receive_err(&self) -> Pin<Box<dyn Stream<Item = String> + Send>> {
+        Box::pin(futures::stream::empty())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::{AsyncReadExt as _, TryStreamExt as _, channel::mpsc};
+    use gpui::TestAppContext;
+    use http_client::{FakeHttpClient, Response};
+    use serde_json::{Value, json};
+    use std::time::Duration;
+
+    #[derive(Default)]
+    struct FakeServer {
+        streams: Vec<mpsc::UnboundedSender<Vec<u8>>>,
+        posts: Vec<(String, String)>,
+    }
+
+    #[gpui::test]
+    async fn test_reconnection(cx: &mut TestAppContext) {
+        let server = Arc::new(Mutex::new(FakeServer::default()));
+        let http_client = FakeHttpClient::create({
+            let server = server.clone();
+            move |request| {
+                let server = server.clone();
+                async move {
+                    if request.method() == Method::GET {
+                        let (tx, rx) = mpsc::unbounded();
+                        let mut server = server.lock();
+                        let session = server.streams.len() + 1;
+                        tx.unbounded_send(
+                            format!("event: endpoint\ndata: /messages?session={session}\n\n")
+                                .into_bytes(),
+                        )?;
+                        server.streams.push(tx);
+                        let body = rx.map(Ok::<_, std::io::Error>).into_async_read();
+                        return Ok(Response::builder()
+                            .header("Content-Type", "text/event-stream")
+                            .body(AsyncBody::from_reader(body))?);
+                    }
+
+                    let uri = request.uri().to_string();
+                    let mut body = String::new();
+                    request.into_body().read_to_string(&mut body).await?;
+                    let message = serde_json::from_str::<Value>(&body)?;
+
+                    let mut server = server.lock();
+                    server
+                        .posts
+                        .push((uri, message["method"].as_str().unwrap().into()));
+                    if message.get("id").is_some() {
+                        let result = json!({ "jsonrpc": "2.0", "id": message["id"], "result": {} });
+                        server
+                            .streams
+                            .last()
+                            .unwrap()
+                            .unbounded_send(format!("data: {result}\n\n").into_bytes())?;
+                    }
+                    Ok(Response::builder()
+                        .status(StatusCode::ACCEPTED)
+                        .body(AsyncBody::empty())?)
+                }
+            }
+        });
+
+        let transport = SseTransport::new(
+            http_client,
+            Url::parse("http://localhost/sse").unwrap(),
+            HashMap::default(),
+            &cx.to_async(),
+        );
+        let mut messages = transport.receive();
+
+        let request = |id: u32, method: &str| {
+            json!({ "jsonrpc": "2.0", "id": id, "method": method }).to_string()
+        };
+        let id_of =
+            |message: String| serde_json::from_str::<Value>(&message).unwrap()["id"].clone();
+
+        transport.send(request(0, "initialize")).await.unwrap();
+        assert_eq!(id_of(messages.next().await.unwrap()), json!(0));
+        transport
+            .send(INITIALIZED_NOTIFICATION.to_string())
+            .await
+            .unwrap();
+
+        // Losing the stream makes the transport reconnect and restore the session.
+        server.lock().streams[0].close_channel();
+        cx.executor().advance_clock(Duration::from_secs(1));
+        cx.run_until_parked();
+        assert_eq!(
+            id_of(messages.next().await.unwrap()),
+            json!(0),
+            "the replayed initialization is answered on the new stream"
+        );
+
+        transport.send(request(1, "tools/list")).await.unwrap();
+        assert_eq!(id_of(messages.next().await.unwrap()), json!(1));
+        assert_eq!(
+            server.lock().posts,
+            [
+                ("/messages?session=1", "initialize"),
+                ("/messages?session=1", "notifications/initialized"),
+                ("/messages?session=2", "initialize"),
+                ("/messages?session=2", "notifications/initialized"),
+                ("/messages?session=2", "tools/list"),
+            ]
+            .map(|(uri, method)| (format!("http://localhost{uri}"), method.to_string()))
+        );
+    }
+}
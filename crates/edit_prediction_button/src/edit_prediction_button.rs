@@ -234,6 +234,41 @@ impl Render for EditPredictionButton {
                 )
             }
 
+            EditPredictionProvider::Local => {
+                let enabled = self.editor_enabled.unwrap_or(true);
+                let this = cx.entity();
+
+                div().child(
+                    PopoverMenu::new("local-edit-prediction")
+                        .menu(move |window, cx| {
+                            Some(
+                                this.update(cx, |this, cx| {
+                                    this.build_local_context_menu(window, cx)
+                                }),
+                            )
+                        })
+                        .anchor(Corner::BottomRight)
+                        .trigger_with_tooltip(
+                            IconButton::new("local-edit-prediction-icon", IconName::Ai).icon_color(
+                                if enabled {
+                                    Color::Default
+                                } else {
+                                    Color::Muted
+                                },
+                            ),
+                            move |window, cx| {
+                                Tooltip::for_action(
+                                    "Local Edit Prediction",
+                                    &ToggleMenu,
+                                    window,
+                                    cx,
+                                )
+                            },
+                        )
+                        .with_handle(self.popover_menu_handle.clone()),
+                )
+            }
+
             EditPredictionProvider::Zed => {
                 let enabled = self.editor_enabled.unwrap_or(true);
 
@@ -493,6 +528,7 @@ impl EditPredictionButton {
             EditPredictionProvider::Zed
                 | EditPredictionProvider::Copilot
                 | EditPredictionProvider::Supermaven
+                | EditPredictionProvider::Local
         ) {
             menu = menu
                 .separator()
@@ -719,6 +755,23 @@ impl EditPredictionButton {
         })
     }
 
+    fn build_local_context_menu(
+        &self,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Entity<ContextMenu> {
+        ContextMenu::build(window, cx, |menu, window, cx| {
+            self.build_language_settings_menu(menu, window, cx)
+                .separator()
+                .entry("Use Zed AI instead", None, {
+                    let fs = self.fs.clone();
+                    move |_window, cx| {
+                        set_completion_provider(fs.clone(), cx, EditPredictionProvider::Zed)
+                    }
+                })
+        })
+    }
+
     fn build_zeta_context_menu(
         &self,
         window: &mut Window,
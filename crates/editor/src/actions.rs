@@ -518,6 +518,10 @@ actions!(
         InsertUuidV7,
         /// Joins the current line with the next line.
         JoinLines,
+        /// Jumps back to the previous location in the jump list, across files and panes.
+        JumpBack,
+        /// Jumps forward to the next location in the jump list, across files and panes.
+        JumpForward,
         /// Cuts to kill ring (Emacs-style).
         KillRingCut,
         /// Yanks from kill ring (Emacs-style).
@@ -0,0 +1,285 @@
+use std::ops::Range;
+
+use collections::{HashMap, HashSet};
+use gpui::{Context, HighlightStyle};
+use language::language_settings::language_settings;
+use multi_buffer::{Anchor, IndentGuide, MultiBufferSnapshot, ToOffset as _};
+use text::{Bias, Point};
+use theme::ActiveTheme;
+
+use crate::{DisplayPoint, DisplayRow, Editor};
+
+/// The number of rows whose brackets are colorized at once, so that scrolling through a large
+/// file only colorizes the rows being shown.
+const ROWS_PER_CHUNK: u32 = 64;
+
+enum ColorizedBracketHighlight {}
+
+/// Brackets colorized by their nesting depth, computed by chunks of rows as they get shown.
+#[derive(Default)]
+pub(crate) struct ColorizedBrackets {
+    /// The indices of the chunks of multi-buffer rows whose brackets are colorized.
+    chunks: HashSet<u32>,
+    brackets: Vec<(Range<Anchor>, u32)>,
+    /// The depth of the colorized brackets by the offset where they start, along with the
+    /// multi-buffer edit count they were resolved at, used to color indent guides on every frame.
+    depths_by_offset: Option<(usize, HashMap<usize, u32>)>,
+}
+
+impl Editor {
+    /// Colorizes the brackets of the visible rows that are not colorized yet, or of all of them
+    /// when `invalidate` is set, after the text, the excerpts or the theme changed.
+    pub(crate) fn colorize_brackets(&mut self, invalidate: bool, cx: &mut Context<Self>) {
+        if !self.mode.is_full() {
+            return;
+        }
+        let had_brackets = !self.colorized_brackets.brackets.is_empty();
+        if invalidate {
+            self.colorized_brackets = ColorizedBrackets::default();
+        }
+
+        let enabled = self.buffer.read(cx).all_buffers().iter().any(|buffer| {
+            let buffer = buffer.read(cx);
+            language_settings(buffer.language().map(|l| l.name()), buffer.file(), cx)
+                .colorize_brackets
+        });
+        if !enabled {
+            if had_brackets {
+                self.colorized_brackets = ColorizedBrackets::default();
+                self.clear_keyed_highlights::<ColorizedBracketHighlight>(cx);
+            }
+            return;
+        }
+
+        let display_snapshot = self.display_map.update(cx, |map, cx| map.snapshot(cx));
+        let buffer_snapshot = &display_snapshot.buffer_snapshot;
+        let scroll_top = self
+            .scroll_manager
+            .anchor()
+            .scroll_position(&display_snapshot)
+            .y;
+        let max_display_row = display_snapshot.max_point().row();
+        let visible_rows = DisplayRow(scroll_top as u32).min(max_display_row)
+            ..DisplayRow((scroll_top + self.visible_line_count().unwrap_or(0.)).ceil() as u32)
+                .min(max_display_row);
+        let first_row = display_snapshot
+            .display_point_to_point(DisplayPoint::new(visible_rows.start, 0), Bias::Left)
+            .row;
+        let last_row = display_snapshot
+            .display_point_to_point(DisplayPoint::new(visible_rows.end, 0), Bias::Left)
+            .row;
+
+        let max_point = buffer_snapshot.max_point();
+        let mut colorized_any = invalidate && had_brackets;
+        for chunk in first_row / ROWS_PER_CHUNK..=last_row / ROWS_PER_CHUNK {
+            if !self.colorized_brackets.chunks.insert(chunk) {
+                continue;
+            }
+            let chunk_start = Point::new(chunk * ROWS_PER_CHUNK, 0);
+            let chunk_end = Point::new((chunk + 1) * ROWS_PER_CHUNK, 0).min(max_point);
+            for (range, depth) in buffer_snapshot.colorized_bracket_ranges(chunk_start..chunk_end) {
+                if buffer_snapshot
+                    .language_settings_at(range.start, cx)
+                    .colorize_brackets
+                {
+                    self.colorized_brackets.brackets.push((
+                        buffer_snapshot.anchor_after(range.start)
+                            ..buffer_snapshot.anchor_before(range.end),
+                        depth,
+                    ));
+                    self.colorized_brackets.depths_by_offset = None;
+                    colorized_any = true;
+                }
+            }
+        }
+        if !colorized_any {
+            return;
+        }
+
+        let accents = cx.theme().accents().clone();
+        let mut ranges_by_color = vec![Vec::new(); accents.0.len()];
+        if ranges_by_color.is_empty() {
+            return;
+        }
+        for (range, depth) in &self.colorized_brackets.brackets {
+            let color_count = ranges_by_color.len();
+            ranges_by_color[*depth as usize % color_count].push(range.clone());
+        }
+        self.clear_keyed_highlights::<ColorizedBracketHighlight>(cx);
+        for (color_index, mut ranges) in ranges_by_color.into_iter().enumerate() {
+            if ranges.is_empty() {
+                continue;
+            }
+            ranges.sort_by(|a, b| a.start.cmp(&b.start, buffer_snapshot));
+            self.highlight_text_key::<ColorizedBracketHighlight>(
+                color_index,
+                ranges,
+                HighlightStyle {
+                    color: Some(accents.color_for_index(color_index as u32)),
+                    ..HighlightStyle::default()
+                },
+                cx,
+            );
+        }
+    }
+
+    /// Returns the nesting depth of the colorized bracket pair around each of the indent guides,
+    /// so that indent-aware guides take the color of the brackets enclosing their block.
+    pub(crate) fn indent_guide_bracket_depths(
+        &mut self,
+        indent_guides: &[IndentGuide],
+        snapshot: &MultiBufferSnapshot,
+    ) -> Vec<Option<u32>> {
+        let colorized_brackets = &mut self.colorized_brackets;
+        if colorized_brackets.brackets.is_empty() {
+            return vec![None; indent_guides.len()];
+        }
+        let edit_count = snapshot.edit_count();
+        if colorized_brackets
+            .depths_by_offset
+            .as_ref()
+            .is_none_or(|(cached_edit_count, _)| *cached_edit_count != edit_count)
+        {
+            let depths_by_offset = colorized_brackets
+                .brackets
+                .iter()
+                .map(|(range, depth)| (range.start.to_offset(snapshot), *depth))
+                .collect();
+            colorized_brackets.depths_by_offset = Some((edit_count, depths_by_offset));
+        }
+        let Some((_, depths_by_offset)) = &colorized_brackets.depths_by_offset else {
+            return vec![None; indent_guides.len()];
+        };
+        indent_guides
+            .iter()
+            .map(|indent_guide| {
+                let start = Point::new(indent_guide.start_row.0, 0);
+                let end = Point::new(
+                    indent_guide.end_row.0,
+                    snapshot.line_len(indent_guide.end_row),
+                );
+                let (open, _) = snapshot.innermost_enclosing_bracket_ranges(start..end, None)?;
+                depths_by_offset.get(&open.start).copied()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{editor_tests::init_test, test::editor_test_context::EditorTestContext};
+    use gpui::TestAppContext;
+    use indoc::indoc;
+    use language::{Language, LanguageConfig};
+    use multi_buffer::{MultiBufferRow, ToOffset as _};
+    use settings::SettingsStore;
+    use std::sync::Arc;
+
+    #[gpui::test]
+    async fn test_colorize_brackets(cx: &mut TestAppContext) {
+        init_test(cx, |settings| {
+            settings.defaults.colorize_brackets = Some(true);
+        });
+
+        let language = Arc::new(
+            Language::new(
+                LanguageConfig {
+                    name: "Rust".into(),
+                    ..LanguageConfig::default()
+                },
+                Some(tree_sitter_rust::LANGUAGE.into()),
+            )
+            .with_brackets_query(
+                r#"
+                    ("(" @open ")" @close)
+                    ("[" @open "]" @close)
+                    ("{" @open "}" @close)
+                    (("\"" @open "\"" @close) (#set! rainbow.exclude))
+                "#,
+            )
+            .unwrap(),
+        );
+
+        let mut cx = EditorTestContext::new(cx).await;
+        cx.update_buffer(|buffer, cx| buffer.set_language(Some(language), cx));
+        cx.set_state(r#"fn a() { b(c("d"), [e]) }ˇ"#);
+        cx.run_until_parked();
+
+        let colorized_brackets = |cx: &mut EditorTestContext| {
+            cx.update_editor(|editor, _, cx| {
+                let snapshot = editor.buffer.read(cx).snapshot(cx);
+                let mut brackets = editor
+                    .colorized_brackets
+                    .brackets
+                    .iter()
+                    .map(|(range, depth)| {
+                        (
+                            snapshot.text_for_range(range.clone()).collect::<String>(),
+                            range.start.to_offset(&snapshot),
+                            *depth,
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                brackets.sort_by_key(|(_, offset, _)| *offset);
+                brackets
+                    .into_iter()
+                    .map(|(text, _, depth)| (text, depth))
+                    .collect::<Vec<_>>()
+            })
+        };
+
+        assert_eq!(
+            colorized_brackets(&mut cx),
+            [
+                ("(", 0),
+                (")", 0),
+                ("{", 0),
+                ("(", 1),
+                ("(", 2),
+                (")", 2),
+                ("[", 2),
+                ("]", 2),
+                (")", 1),
+                ("}", 0),
+            ]
+            .map(|(text, depth)| (text.to_string(), depth))
+        );
+
+        cx.set_state(indoc! {"
+            fn a() {
+                b(|| {
+                    c();
+                });
+            }ˇ
+        "});
+        cx.run_until_parked();
+        cx.update_editor(|editor, window, cx| {
+            let snapshot = editor.snapshot(window, cx).display_snapshot;
+            let indent_guides = editor
+                .indent_guides(MultiBufferRow(0)..MultiBufferRow(5), &snapshot, cx)
+                .unwrap();
+            let bracket_depths =
+                editor.indent_guide_bracket_depths(&indent_guides, &snapshot.buffer_snapshot);
+            assert_eq!(
+                indent_guides
+                    .iter()
+                    .map(|indent_guide| indent_guide.depth)
+                    .zip(bracket_depths)
+                    .collect::<Vec<_>>(),
+                [(0, Some(0)), (1, Some(2))],
+                "Guides should follow the depth of the brackets around them, not their indentation"
+            );
+        });
+
+        cx.update(|_, cx| {
+            SettingsStore::update_global(cx, |store, cx| {
+                store.update_user_settings(cx, |settings| {
+                    settings.project.all_languages.defaults.colorize_brackets = Some(false);
+                });
+            });
+        });
+        cx.run_until_parked();
+        assert_eq!(colorized_brackets(&mut cx), Vec::new());
+    }
+}
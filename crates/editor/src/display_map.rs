@@ -520,6 +520,17 @@ impl DisplayMap {
         self.text_highlights.values()
     }
 
+    pub fn clear_keyed_highlights(&mut self, type_id: TypeId) -> bool {
+        let mut cleared = false;
+        self.text_highlights.retain(|key, _| {
+            let matches =
+                matches!(key, HighlightKey::TypePlus(key_type_id, _) if *key_type_id == type_id);
+            cleared |= matches;
+            !matches
+        });
+        cleared
+    }
+
     pub fn clear_highlights(&mut self, type_id: TypeId) -> bool {
         let mut cleared = self
             .text_highlights
@@ -14,6 +14,7 @@
 //! If you're looking to improve Vim mode, you should check out Vim crate that wraps Editor and overrides its behavior.
 pub mod actions;
 mod blink_manager;
+mod bracket_colorization;
 mod clangd_ext;
 pub mod code_context_menus;
 pub mod display_map;
@@ -28,6 +29,7 @@ mod indent_guides;
 mod inlay_hint_cache;
 pub mod items;
 mod jsx_tag_auto_close;
+pub mod jump_list;
 mod linked_editing_ranges;
 mod lsp_colors;
 mod lsp_ext;
@@ -35,9 +37,11 @@ mod mouse_context_menu;
 pub mod movement;
 mod persistence;
 mod proposed_changes_editor;
+mod replace_preview;
 mod rust_analyzer_ext;
 pub mod scroll;
 mod selections_collection;
+mod sticky_scroll;
 pub mod tasks;
 
 #[cfg(test)]
@@ -82,6 +86,7 @@ use ::git::{
 use aho_corasick::AhoCorasick;
 use anyhow::{Context as _, Result, anyhow};
 use blink_manager::BlinkManager;
+use bracket_colorization::ColorizedBrackets;
 use buffer_diff::DiffHunkStatus;
 use client::{Collaborator, ParticipantIndex, parse_zed_link};
 use clock::{AGENT_REPLICA_ID, ReplicaId};
@@ -148,8 +153,8 @@ use parking_lot::Mutex;
 use persistence::DB;
 use project::{
     BreakpointWithPosition, CodeAction, Completion, CompletionDisplayOptions, CompletionIntent,
-    CompletionResponse, CompletionSource, DisableAiSettings, DocumentHighlight, InlayHint,
-    Location, LocationLink, PrepareRenameResponse, Project, ProjectItem, ProjectPath,
+    CompletionResponse, CompletionSource, DisableAiSettings, DocumentHighlight, DocumentLink,
+    InlayHint, Location, LocationLink, PrepareRenameResponse, Project, ProjectItem, ProjectPath,
     ProjectTransaction, TaskSourceKind,
     debugger::{
         breakpoint_store::{
@@ -166,7 +171,7 @@ use rand::seq::SliceRandom;
 use rpc::{ErrorCode, ErrorExt, proto::PeerId};
 use scroll::{Autoscroll, OngoingScroll, ScrollAnchor, ScrollManager};
 use selections_collection::{
-    MutableSelectionsCollection, SelectionsCollection, resolve_selections,
+    MutableSelectionsCollection, SelectionsCollection, VirtualSpace, resolve_selections,
 };
 use serde::{Deserialize, Serialize};
 use settings::{GitGutterSetting, Settings, SettingsLocation, SettingsStore, update_settings_file};
@@ -217,7 +222,6 @@ pub const FILE_HEADER_HEIGHT: u32 = 2;
 pub const MULTI_BUFFER_EXCERPT_HEADER_HEIGHT: u32 = 1;
 const CURSOR_BLINK_INTERVAL: Duration = Duration::from_millis(500);
 const MAX_LINE_LEN: usize = 1024;
-const MIN_NAVIGATION_HISTORY_ROW_DELTA: i64 = 10;
 const MAX_SELECTION_HISTORY_LEN: usize = 1024;
 pub(crate) const CURSORS_VISIBLE_FOR: Duration = Duration::from_millis(2000);
 #[doc(hidden)]
@@ -281,6 +285,7 @@ impl InlineValueCache {
 pub enum InlayId {
     EditPrediction(u32),
     DebuggerValue(u32),
+    ReplacePreview(u32),
     // LSP
     Hint(u32),
     Color(u32),
@@ -291,6 +296,7 @@ impl InlayId {
         match self {
             Self::EditPrediction(id) => *id,
             Self::DebuggerValue(id) => *id,
+            Self::ReplacePreview(id) => *id,
             Self::Hint(id) => *id,
             Self::Color(id) => *id,
         }
@@ -356,12 +362,14 @@ pub fn init(cx: &mut App) {
     workspace::register_serializable_item::<Editor>(cx);
 
     cx.observe_new(
-        |workspace: &mut Workspace, _: Option<&mut Window>, _cx: &mut Context<Workspace>| {
+        |workspace: &mut Workspace, _: Option<&mut Window>, cx: &mut Context<Workspace>| {
             workspace.register_action(Editor::new_file);
             workspace.register_action(Editor::new_file_vertical);
             workspace.register_action(Editor::new_file_horizontal);
             workspace.register_action(Editor::cancel_language_server_work);
             workspace.register_action(Editor::toggle_focus);
+            jump_list::register_actions(workspace);
+            jump_list::restore_jump_list(workspace, cx);
         },
     )
     .detach();
@@ -1190,6 +1198,8 @@ pub struct Editor {
     inline_value_cache: InlineValueCache,
     selection_drag_state: SelectionDragState,
     colors: Option<LspColorData>,
+    colorized_brackets: ColorizedBrackets,
+    replace_preview_inlays: Vec<InlayId>,
     folding_newlines: Task<()>,
     pub lookup_key: Option<Box<dyn Any + Send + Sync>>,
 }
@@ -2245,6 +2255,8 @@ impl Editor {
             tasks_update_task: None,
             pull_diagnostics_task: Task::ready(()),
             colors: None,
+            colorized_brackets: ColorizedBrackets::default(),
+            replace_preview_inlays: Vec::new(),
             next_color_inlay_id: 0,
             linked_edit_ranges: Default::default(),
             in_project_search: false,
@@ -3844,11 +3856,13 @@ impl Editor {
         let start_column = cmp::min(tail.column(), goal_column);
         let end_column = cmp::max(tail.column(), goal_column);
         let reversed = start_column < tail.column();
+        let virtual_space = EditorSettings::get_global(cx).column_selection_virtual_space;
 
         let selection_ranges = (start_row.0..=end_row.0)
             .map(DisplayRow)
             .filter_map(|row| {
                 if (matches!(columnar_state, ColumnarSelectionState::FromMouse { .. })
+                    || virtual_space
                     || start_column <= display_map.line_len(row))
                     && !display_map.is_block_line(row)
                 {
@@ -3858,11 +3872,15 @@ impl Editor {
                     let end = display_map
                         .clip_point(DisplayPoint::new(row, end_column), Bias::Right)
                         .to_point(display_map);
-                    if reversed {
-                        Some(end..start)
+                    let virtual_space = if virtual_space {
+                        VirtualSpace {
+                            start: movement::virtual_columns_at(display_map, row, start_column),
+                            end: movement::virtual_columns_at(display_map, row, end_column),
+                        }
                     } else {
-                        Some(start..end)
-                    }
+                        VirtualSpace::default()
+                    };
+                    Some((start..end, virtual_space))
                 } else {
                     None
                 }
@@ -3873,7 +3891,10 @@ impl Editor {
             ColumnarSelectionState::FromMouse { .. } => {
                 let mut non_empty_ranges = selection_ranges
                     .iter()
-                    .filter(|selection_range| selection_range.start != selection_range.end)
+                    .filter(|(selection_range, virtual_space)| {
+                        selection_range.start != selection_range.end
+                            || virtual_space.start != virtual_space.end
+                    })
                     .peekable();
                 if non_empty_ranges.peek().is_some() {
                     non_empty_ranges.cloned().collect()
@@ -3885,11 +3906,55 @@ impl Editor {
         };
 
         self.change_selections(SelectionEffects::no_scroll(), window, cx, |s| {
-            s.select_ranges(ranges);
+            let selections = ranges
+                .into_iter()
+                .map(|(range, virtual_space)| {
+                    let is_empty =
+                        range.start == range.end && virtual_space.start == virtual_space.end;
+                    let selection = Selection {
+                        id: s.new_selection_id(),
+                        start: range.start,
+                        end: range.end,
+                        reversed: reversed && !is_empty,
+                        goal: SelectionGoal::None,
+                    };
+                    (selection, virtual_space)
+                })
+                .collect();
+            s.select_with_virtual_space(selections);
         });
         cx.notify();
     }
 
+    /// Fills the virtual space that selections extend into past the end of their lines with
+    /// spaces, so that text inserted at them lands at their columns.
+    fn fill_virtual_space(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if !self.selections.has_virtual_space() {
+            return;
+        }
+
+        let mut edits = Vec::new();
+        let mut selections = self.selections.all::<Point>(cx);
+        for selection in &mut selections {
+            let padding = self.selections.virtual_space(selection.id).start;
+            if padding > 0 {
+                edits.push((
+                    selection.start..selection.start,
+                    " ".repeat(padding as usize),
+                ));
+                let point = Point::new(selection.start.row, selection.start.column + padding);
+                selection.start = point;
+                selection.end = point;
+            }
+        }
+
+        self.buffer
+            .update(cx, |buffer, cx| buffer.edit(edits, None, cx));
+        self.change_selections(SelectionEffects::no_scroll(), window, cx, |s| {
+            s.select(selections)
+        });
+    }
+
     pub fn has_non_empty_selection(&self, cx: &mut App) -> bool {
         self.selections
             .all_adjusted(cx)
@@ -4041,6 +4106,14 @@ impl Editor {
 
         self.hide_mouse_cursor(HideMouseCursorOrigin::TypingAction, cx);
 
+        if self.selections.has_virtual_space() {
+            self.transact(window, cx, |this, window, cx| {
+                this.fill_virtual_space(window, cx);
+                this.handle_input(&text, window, cx);
+            });
+            return;
+        }
+
         let selections = self.selections.all_adjusted(cx);
         let mut bracket_inserted = false;
         let mut edits = Vec::new();
@@ -12580,6 +12653,7 @@ impl Editor {
         let clipboard_text = Cow::Borrowed(text.as_str());
 
         self.transact(window, cx, |this, window, cx| {
+            this.fill_virtual_space(window, cx);
             let had_active_edit_prediction = this.has_active_edit_prediction();
             let old_selections = this.selections.all::<usize>(cx);
             let cursor_offset = this.selections.last::<usize>(cx).head();
@@ -14046,7 +14120,8 @@ impl Editor {
 
             if let Some(new_position) = new_position {
                 let row_delta = (new_position.row as i64 - cursor_position.row as i64).abs();
-                if row_delta == 0 || (row_delta < MIN_NAVIGATION_HISTORY_ROW_DELTA && !always) {
+                let min_row_delta = EditorSettings::get_global(cx).jump_list.min_row_delta as i64;
+                if row_delta == 0 || (row_delta < min_row_delta && !always) {
                     return;
                 }
             }
@@ -14063,7 +14138,8 @@ impl Editor {
             cx.emit(EditorEvent::PushedToNavHistory {
                 anchor: cursor_anchor,
                 is_deactivate,
-            })
+            });
+            self.record_jump(cursor_anchor, cx);
         }
     }
 
@@ -14184,6 +14260,16 @@ impl Editor {
         let display_map = self.display_map.update(cx, |map, cx| map.snapshot(cx));
         let all_selections = self.selections.all::<Point>(cx);
         let text_layout_details = self.text_layout_details(window);
+        let virtual_space = EditorSettings::get_global(cx).column_selection_virtual_space;
+        let mut virtual_spaces = all_selections
+            .iter()
+            .map(|selection| (selection.id, self.selections.virtual_space(selection.id)))
+            .filter(|(_, virtual_space)| !virtual_space.is_empty())
+            .collect::<HashMap<_, _>>();
+        let x_for_display_point = |point: DisplayPoint, virtual_columns: u32| {
+            display_map.x_for_display_point(point, &text_layout_details)
+                + movement::virtual_space_width(virtual_columns, &text_layout_details)
+        };
 
         let (mut columnar_selections, new_selections_to_columnarize) = {
             if let Some(state) = self.add_selections_state.as_ref() {
@@ -14209,19 +14295,26 @@ impl Editor {
 
         for selection in new_selections_to_columnarize {
             let range = selection.display_range(&display_map).sorted();
-            let start_x = display_map.x_for_display_point(range.start, &text_layout_details);
-            let end_x = display_map.x_for_display_point(range.end, &text_layout_details);
+            let selection_virtual_space = virtual_spaces.remove(&selection.id).unwrap_or_default();
+            let start_x = x_for_display_point(range.start, selection_virtual_space.start);
+            let end_x = x_for_display_point(range.end, selection_virtual_space.end);
             let positions = start_x.min(end_x)..start_x.max(end_x);
             let mut stack = Vec::new();
             for row in range.start.row().0..=range.end.row().0 {
-                if let Some(selection) = self.selections.build_columnar_selection(
-                    &display_map,
-                    DisplayRow(row),
-                    &positions,
-                    selection.reversed,
-                    &text_layout_details,
-                ) {
+                if let Some((selection, selection_virtual_space)) =
+                    self.selections.build_columnar_selection(
+                        &display_map,
+                        DisplayRow(row),
+                        &positions,
+                        selection.reversed,
+                        virtual_space,
+                        &text_layout_details,
+                    )
+                {
                     stack.push(selection.id);
+                    if !selection_virtual_space.is_empty() {
+                        virtual_spaces.insert(selection.id, selection_virtual_space);
+                    }
                     columnar_selections.push(selection);
                 }
             }
@@ -14257,10 +14350,13 @@ impl Editor {
                         if let SelectionGoal::HorizontalRange { start, end } = selection.goal {
                             px(start)..px(end)
                         } else {
+                            let selection_virtual_space = virtual_spaces
+                                .get(&selection.id)
+                                .copied()
+                                .unwrap_or_default();
                             let start_x =
-                                display_map.x_for_display_point(range.start, &text_layout_details);
-                            let end_x =
-                                display_map.x_for_display_point(range.end, &text_layout_details);
+                                x_for_display_point(range.start, selection_virtual_space.start);
+                            let end_x = x_for_display_point(range.end, selection_virtual_space.end);
                             start_x.min(end_x)..start_x.max(end_x)
                         };
 
@@ -14271,13 +14367,19 @@ impl Editor {
                         } else {
                             row.0 += 1;
                         }
-                        if let Some(new_selection) = self.selections.build_columnar_selection(
-                            &display_map,
-                            row,
-                            &positions,
-                            selection.reversed,
-                            &text_layout_details,
-                        ) {
+                        if let Some((new_selection, new_virtual_space)) =
+                            self.selections.build_columnar_selection(
+                                &display_map,
+                                row,
+                                &positions,
+                                selection.reversed,
+                                virtual_space,
+                                &text_layout_details,
+                            )
+                        {
+                            if !new_virtual_space.is_empty() {
+                                virtual_spaces.insert(new_selection.id, new_virtual_space);
+                            }
                             maybe_new_selection = Some(new_selection);
                             break;
                         }
@@ -14304,7 +14406,18 @@ impl Editor {
         }
 
         self.change_selections(Default::default(), window, cx, |s| {
-            s.select(final_selections);
+            s.select_with_virtual_space(
+                final_selections
+                    .into_iter()
+                    .map(|selection| {
+                        let virtual_space = virtual_spaces
+                            .get(&selection.id)
+                            .copied()
+                            .unwrap_or_default();
+                        (selection, virtual_space)
+                    })
+                    .collect(),
+            );
         });
 
         let final_selection_ids: HashSet<_> = self
@@ -20528,6 +20641,16 @@ impl Editor {
         self.display_map.read(cx).text_highlights(TypeId::of::<T>())
     }
 
+    /// Clears the text highlights set with [`Editor::highlight_text_key`] for `T`, whatever their key.
+    pub fn clear_keyed_highlights<T: 'static>(&mut self, cx: &mut Context<Self>) {
+        let cleared = self
+            .display_map
+            .update(cx, |map, _| map.clear_keyed_highlights(TypeId::of::<T>()));
+        if cleared {
+            cx.notify();
+        }
+    }
+
     pub fn clear_highlights<T: 'static>(&mut self, cx: &mut Context<Self>) {
         let cleared = self
             .display_map
@@ -20762,12 +20885,14 @@ impl Editor {
                     excerpts: excerpts.clone(),
                 });
                 self.refresh_inlay_hints(InlayHintRefreshReason::NewLinesShown, cx);
+                self.colorize_brackets(true, cx);
             }
             multi_buffer::Event::ExcerptsRemoved {
                 ids,
                 removed_buffer_ids,
             } => {
                 self.refresh_inlay_hints(InlayHintRefreshReason::ExcerptsRemoved(ids.clone()), cx);
+                self.colorize_brackets(true, cx);
                 let buffer = self.buffer.read(cx);
                 self.registered_buffers
                     .retain(|buffer_id, _| buffer.buffer(*buffer_id).is_some());
@@ -20790,10 +20915,12 @@ impl Editor {
             }
             multi_buffer::Event::ExcerptsExpanded { ids } => {
                 self.refresh_inlay_hints(InlayHintRefreshReason::NewLinesShown, cx);
+                self.colorize_brackets(true, cx);
                 cx.emit(EditorEvent::ExcerptsExpanded { ids: ids.clone() })
             }
             multi_buffer::Event::Reparsed(buffer_id) => {
                 self.tasks_update_task = Some(self.refresh_runnables(window, cx));
+                self.colorize_brackets(true, cx);
                 jsx_tag_auto_close::refresh_enabled_in_any_buffer(self, multibuffer, cx);
 
                 cx.emit(EditorEvent::Reparsed(*buffer_id));
@@ -20945,6 +21072,7 @@ impl Editor {
             }
             self.refresh_colors(false, None, window, cx);
         }
+        self.colorize_brackets(true, cx);
 
         cx.notify();
     }
@@ -22573,6 +22701,19 @@ pub trait SemanticsProvider {
         cx: &mut App,
     ) -> Option<Task<Result<Option<Vec<LocationLink>>>>>;
 
+    fn document_links(
+        &self,
+        buffer: &Entity<Buffer>,
+        cx: &mut App,
+    ) -> Option<Task<Result<Option<Vec<DocumentLink>>>>>;
+
+    fn resolve_document_link(
+        &self,
+        link: DocumentLink,
+        buffer: Entity<Buffer>,
+        cx: &mut App,
+    ) -> Option<Task<Result<DocumentLink>>>;
+
     fn range_for_rename(
         &self,
         buffer: &Entity<Buffer>,
@@ -23021,6 +23162,25 @@ impl SemanticsProvider for Entity<Project> {
         }))
     }
 
+    fn document_links(
+        &self,
+        buffer: &Entity<Buffer>,
+        cx: &mut App,
+    ) -> Option<Task<Result<Option<Vec<DocumentLink>>>>> {
+        Some(self.update(cx, |project, cx| project.document_links(buffer, cx)))
+    }
+
+    fn resolve_document_link(
+        &self,
+        link: DocumentLink,
+        buffer: Entity<Buffer>,
+        cx: &mut App,
+    ) -> Option<Task<Result<DocumentLink>>> {
+        Some(self.update(cx, |project, cx| {
+            project.resolve_document_link(link, buffer, cx)
+        }))
+    }
+
     fn supports_inlay_hints(&self, buffer: &Entity<Buffer>, cx: &mut App) -> bool {
         self.update(cx, |project, cx| {
             if project
@@ -29,6 +29,8 @@ pub struct EditorSettings {
     pub toolbar: Toolbar,
     pub scrollbar: Scrollbar,
     pub minimap: Minimap,
+    pub sticky_scroll: StickyScroll,
+    pub jump_list: JumpList,
     pub gutter: Gutter,
     pub scroll_beyond_last_line: ScrollBeyondLastLine,
     pub vertical_scroll_margin: f32,
@@ -40,6 +42,7 @@ pub struct EditorSettings {
     pub seed_search_query_from_cursor: SeedQuerySetting,
     pub use_smartcase_search: bool,
     pub multi_cursor_modifier: MultiCursorModifier,
+    pub column_selection_virtual_space: bool,
     pub redact_private_values: bool,
     pub expand_excerpt_lines: u32,
     pub excerpt_context_lines: u32,
@@ -67,6 +70,30 @@ pub struct Jupyter {
     pub enabled: bool,
 }
 
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct StickyScroll {
+    /// Whether to pin the lines of the scopes enclosing the top of the viewport at the top of the editor.
+    ///
+    /// Default: false
+    pub enabled: bool,
+    /// Maximum number of nested scopes to pin at the top of the editor.
+    ///
+    /// Default: 5
+    pub max_depth: u32,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct JumpList {
+    /// The minimum number of rows the cursor must move within a file for the move to be recorded as a jump.
+    ///
+    /// Default: 10
+    pub min_row_delta: u32,
+    /// Maximum number of jumps to remember.
+    ///
+    /// Default: 100
+    pub max_entries: usize,
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct StatusBar {
     /// Whether to display the active language button in the status bar.
@@ -174,6 +201,8 @@ pub struct SearchSettings {
     pub case_sensitive: bool,
     pub include_ignored: bool,
     pub regex: bool,
+    /// Whether to save the files changed by replacing all matches of a project search.
+    pub save_after_replace: bool,
 }
 
 impl EditorSettings {
@@ -193,6 +222,8 @@ impl Settings for EditorSettings {
         let editor = content.editor.clone();
         let scrollbar = editor.scrollbar.unwrap();
         let minimap = editor.minimap.unwrap();
+        let sticky_scroll = editor.sticky_scroll.unwrap();
+        let jump_list = editor.jump_list.unwrap();
         let gutter = editor.gutter.unwrap();
         let axes = scrollbar.axes.unwrap();
         let status_bar = editor.status_bar.unwrap();
@@ -240,6 +271,14 @@ impl Settings for EditorSettings {
                 current_line_highlight: minimap.current_line_highlight,
                 max_width_columns: minimap.max_width_columns.unwrap(),
             },
+            sticky_scroll: StickyScroll {
+                enabled: sticky_scroll.enabled.unwrap(),
+                max_depth: sticky_scroll.max_depth.unwrap(),
+            },
+            jump_list: JumpList {
+                min_row_delta: jump_list.min_row_delta.unwrap(),
+                max_entries: jump_list.max_entries.unwrap(),
+            },
             gutter: Gutter {
                 min_line_number_digits: gutter.min_line_number_digits.unwrap(),
                 line_numbers: gutter.line_numbers.unwrap(),
@@ -257,6 +296,7 @@ impl Settings for EditorSettings {
             seed_search_query_from_cursor: editor.seed_search_query_from_cursor.unwrap(),
             use_smartcase_search: editor.use_smartcase_search.unwrap(),
             multi_cursor_modifier: editor.multi_cursor_modifier.unwrap(),
+            column_selection_virtual_space: editor.column_selection_virtual_space.unwrap(),
             redact_private_values: editor.redact_private_values.unwrap(),
             expand_excerpt_lines: editor.expand_excerpt_lines.unwrap(),
             excerpt_context_lines: editor.excerpt_context_lines.unwrap(),
@@ -269,6 +309,7 @@ impl Settings for EditorSettings {
                 case_sensitive: search.case_sensitive.unwrap(),
                 include_ignored: search.include_ignored.unwrap(),
                 regex: search.regex.unwrap(),
+                save_after_replace: search.save_after_replace.unwrap(),
             },
             auto_signature_help: editor.auto_signature_help.unwrap(),
             show_signature_help_after_edits: editor.show_signature_help_after_edits.unwrap(),
@@ -491,5 +532,15 @@ impl Settings for EditorSettings {
         if minimap != settings::MinimapContent::default() {
             current.editor.minimap = Some(minimap)
         }
+
+        let mut sticky_scroll = settings::StickyScrollContent::default();
+        vscode.bool_setting("editor.stickyScroll.enabled", &mut sticky_scroll.enabled);
+        vscode.u32_setting(
+            "editor.stickyScroll.maxLineCount",
+            &mut sticky_scroll.max_depth,
+        );
+        if sticky_scroll != settings::StickyScrollContent::default() {
+            current.editor.sticky_scroll = Some(sticky_scroll)
+        }
     }
 }
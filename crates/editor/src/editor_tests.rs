@@ -7756,6 +7756,118 @@ async fn test_add_selection_above_below(cx: &mut TestAppContext) {
     ));
 }
 
+#[gpui::test]
+async fn test_add_selection_below_into_virtual_space(cx: &mut TestAppContext) {
+    init_test(cx, |_| {});
+    cx.update(|cx| {
+        cx.update_global::<SettingsStore, _>(|settings, cx| {
+            settings.update_user_settings(cx, |settings| {
+                settings.editor.column_selection_virtual_space = Some(true);
+            });
+        });
+    });
+
+    let mut cx = EditorTestContext::new(cx).await;
+    cx.set_state(indoc!(
+        r#"abcdefˇgh
+           ab
+           abcdefgh"#
+    ));
+
+    cx.update_editor(|editor, window, cx| {
+        editor.add_selection_below(&Default::default(), window, cx);
+        editor.add_selection_below(&Default::default(), window, cx);
+    });
+    cx.assert_editor_state(indoc!(
+        r#"abcdefˇgh
+           abˇ
+           abcdefˇgh"#
+    ));
+    cx.update_editor(|editor, _, _| {
+        let selection = &editor.selections.disjoint_anchors()[1];
+        assert_eq!(
+            editor.selections.virtual_space(selection.id),
+            VirtualSpace { start: 4, end: 4 }
+        );
+    });
+
+    cx.update_editor(|editor, window, cx| editor.handle_input("X", window, cx));
+    cx.assert_editor_state(indoc!(
+        r#"abcdefXˇgh
+           ab    Xˇ
+           abcdefXˇgh"#
+    ));
+    cx.update_editor(|editor, _, _| assert!(!editor.selections.has_virtual_space()));
+
+    cx.update_editor(|editor, window, cx| editor.undo(&Default::default(), window, cx));
+    cx.assert_editor_state(indoc!(
+        r#"abcdefˇgh
+           abˇ
+           abcdefˇgh"#
+    ));
+}
+
+#[gpui::test]
+async fn test_columnar_mouse_selection_into_virtual_space(cx: &mut TestAppContext) {
+    init_test(cx, |_| {});
+    cx.update(|cx| {
+        cx.update_global::<SettingsStore, _>(|settings, cx| {
+            settings.update_user_settings(cx, |settings| {
+                settings.editor.column_selection_virtual_space = Some(true);
+            });
+        });
+    });
+
+    let mut cx = EditorTestContext::new(cx).await;
+    cx.set_state(indoc!(
+        r#"abcdefˇgh
+           ab
+           abcdefgh"#
+    ));
+
+    cx.update_editor(|editor, window, cx| {
+        editor.select(
+            SelectPhase::BeginColumnar {
+                position: DisplayPoint::new(DisplayRow(0), 6),
+                reset: true,
+                mode: ColumnarMode::FromMouse,
+                goal_column: 6,
+            },
+            window,
+            cx,
+        );
+        editor.select(
+            SelectPhase::Update {
+                position: DisplayPoint::new(DisplayRow(2), 8),
+                goal_column: 8,
+                scroll_delta: gpui::Point::default(),
+            },
+            window,
+            cx,
+        );
+        editor.select(SelectPhase::End, window, cx);
+    });
+    cx.assert_editor_state(indoc!(
+        r#"abcdef«ghˇ»
+           abˇ
+           abcdef«ghˇ»"#
+    ));
+    cx.update_editor(|editor, _, _| {
+        let selection = &editor.selections.disjoint_anchors()[1];
+        assert_eq!(
+            editor.selections.virtual_space(selection.id),
+            VirtualSpace { start: 4, end: 6 }
+        );
+    });
+
+    cx.update_editor(|editor, window, cx| editor.handle_input("X", window, cx));
+    cx.assert_editor_state(indoc!(
+        r#"abcdefXˇ
+           ab    Xˇ
+           abcdefXˇ"#
+    ));
+}
+
 #[gpui::test]
 async fn test_add_selection_above_below_multi_cursor(cx: &mut TestAppContext) {
     init_test(cx, |_| {});
@@ -26299,6 +26411,84 @@ async fn test_paste_url_from_other_app_creates_markdown_link_selectively_in_mult
     ));
 }
 
+#[gpui::test]
+async fn test_sticky_scopes(cx: &mut TestAppContext) {
+    init_test(cx, |_| {});
+
+    let language = Arc::new(
+        Language::new(
+            LanguageConfig {
+                name: "Rust".into(),
+                ..LanguageConfig::default()
+            },
+            Some(tree_sitter_rust::LANGUAGE.into()),
+        )
+        .with_outline_query(
+            r#"
+                (mod_item "mod" @context name: (_) @name) @item
+                (impl_item "impl" @context type: (_) @name) @item
+                (function_item "fn" @context name: (_) @name) @item
+            "#,
+        )
+        .unwrap(),
+    );
+
+    let mut cx = EditorTestContext::new(cx).await;
+    cx.update_buffer(|buffer, cx| buffer.set_language(Some(language), cx));
+    cx.set_state(indoc! {"
+        ˇmod outer {
+            impl Foo {
+                fn first() {
+                    let a = 1;
+                    let b = 2;
+                }
+                fn second() {
+                    let c = 3;
+                }
+            }
+        }
+    "});
+    cx.run_until_parked();
+
+    let sticky_scopes = |scroll_top: f32, max_depth: usize, cx: &mut EditorTestContext| {
+        cx.update_editor(|editor, window, cx| {
+            editor
+                .snapshot(window, cx)
+                .sticky_scopes(scroll_top, max_depth, None)
+                .into_iter()
+                .map(|scope| (scope.row.0, scope.text))
+                .collect::<Vec<_>>()
+        })
+    };
+
+    assert_eq!(sticky_scopes(0., 5, &mut cx), Vec::new());
+    assert_eq!(
+        sticky_scopes(1., 5, &mut cx),
+        vec![
+            (0, "mod outer".to_string()),
+            (1, "    impl Foo".to_string()),
+            (2, "        fn first".to_string()),
+        ]
+    );
+    // The last line of `first` is hidden by the pinned lines, which are then those enclosing `second`.
+    assert_eq!(
+        sticky_scopes(4., 5, &mut cx),
+        vec![
+            (0, "mod outer".to_string()),
+            (1, "    impl Foo".to_string()),
+            (6, "        fn second".to_string()),
+        ]
+    );
+    assert_eq!(
+        sticky_scopes(4., 2, &mut cx),
+        vec![
+            (0, "mod outer".to_string()),
+            (1, "    impl Foo".to_string()),
+        ]
+    );
+    assert_eq!(sticky_scopes(11., 5, &mut cx), Vec::new());
+}
+
 #[track_caller]
 fn extract_color_inlays(editor: &Editor, cx: &App) -> Vec<Rgba> {
     editor
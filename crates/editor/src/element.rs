@@ -29,6 +29,7 @@ use crate::{
     items::BufferSearchHighlights,
     mouse_context_menu::{self, MenuPosition},
     scroll::{ActiveScrollbarState, ScrollbarThumbState, scroll_amount::ScrollAmount},
+    selections_collection::VirtualSpace,
 };
 use buffer_diff::{DiffHunkStatus, DiffHunkStatusKind};
 use collections::{BTreeMap, HashMap};
@@ -42,13 +43,13 @@ use gpui::{
     Action, Along, AnyElement, App, AppContext, AvailableSpace, Axis as ScrollbarAxis, BorderStyle,
     Bounds, ClickEvent, ClipboardItem, ContentMask, Context, Corner, Corners, CursorStyle,
     DispatchPhase, Edges, Element, ElementInputHandler, Entity, Focusable as _, FontId,
-    GlobalElementId, Hitbox, HitboxBehavior, Hsla, InteractiveElement, IntoElement, IsZero,
-    KeybindingKeystroke, Length, Modifiers, ModifiersChangedEvent, MouseButton, MouseClickEvent,
-    MouseDownEvent, MouseMoveEvent, MouseUpEvent, PaintQuad, ParentElement, Pixels, ScrollDelta,
-    ScrollHandle, ScrollWheelEvent, ShapedLine, SharedString, Size, StatefulInteractiveElement,
-    Style, Styled, TextRun, TextStyleRefinement, WeakEntity, Window, anchored, deferred, div, fill,
-    linear_color_stop, linear_gradient, outline, point, px, quad, relative, size, solid_background,
-    transparent_black,
+    GlobalElementId, HighlightStyle, Hitbox, HitboxBehavior, Hsla, InteractiveElement, IntoElement,
+    IsZero, KeybindingKeystroke, Length, Modifiers, ModifiersChangedEvent, MouseButton,
+    MouseClickEvent, MouseDownEvent, MouseMoveEvent, MouseUpEvent, PaintQuad, ParentElement,
+    Pixels, ScrollDelta, ScrollHandle, ScrollWheelEvent, ShapedLine, SharedString, Size,
+    StatefulInteractiveElement, Style, Styled, StyledText, TextRun, TextStyleRefinement,
+    WeakEntity, Window, anchored, deferred, div, fill, linear_color_stop, linear_gradient, outline,
+    point, px, quad, relative, size, solid_background, transparent_black,
 };
 use itertools::Itertools;
 use language::{IndentGuideSettings, language_settings::ShowWhitespaceSetting};
@@ -179,6 +180,17 @@ impl SelectionLayout {
             user_name,
         }
     }
+
+    /// Moves the ends of the selection that lie in virtual space past the end of their line.
+    fn extend_into_virtual_space(&mut self, virtual_space: VirtualSpace, reversed: bool) {
+        *self.range.start.column_mut() += virtual_space.start;
+        *self.range.end.column_mut() += virtual_space.end;
+        self.head = if reversed {
+            self.range.start
+        } else {
+            self.range.end
+        };
+    }
 }
 
 pub struct EditorElement {
@@ -1368,8 +1380,10 @@ impl EditorElement {
                 for selection in local_selections.iter().cloned() {
                     let is_empty = selection.start == selection.end;
                     let is_newest = selection == newest;
+                    let virtual_space = editor.selections.virtual_space(selection.id);
+                    let reversed = selection.reversed;
 
-                    let layout = SelectionLayout::new(
+                    let mut layout = SelectionLayout::new(
                         selection,
                         editor.selections.line_mode(),
                         editor.cursor_shape,
@@ -1378,6 +1392,9 @@ impl EditorElement {
                         editor.leader_id.is_none(),
                         None,
                     );
+                    if !virtual_space.is_empty() {
+                        layout.extend_into_virtual_space(virtual_space, reversed);
+                    }
                     if is_newest {
                         newest_selection_head = Some(layout.head);
                     }
@@ -1602,9 +1619,11 @@ impl EditorElement {
                         [cursor_position.row().minus(visible_display_row_range.start) as usize];
                     let cursor_column = cursor_position.column() as usize;
 
-                    let cursor_character_x = cursor_row_layout.x_for_index(cursor_column);
-                    let mut block_width =
-                        cursor_row_layout.x_for_index(cursor_column + 1) - cursor_character_x;
+                    let cursor_character_x =
+                        cursor_row_layout.x_for_virtual_index(cursor_column, em_advance);
+                    let mut block_width = cursor_row_layout
+                        .x_for_virtual_index(cursor_column + 1, em_advance)
+                        - cursor_character_x;
                     if block_width == Pixels::ZERO {
                         block_width = em_advance;
                     }
@@ -2677,12 +2696,16 @@ impl EditorElement {
                 .find_active_indent_guide_indices(&indent_guides, snapshot, window, cx)
                 .unwrap_or_default()
         });
+        let bracket_depths = self.editor.update(cx, |editor, _| {
+            editor.indent_guide_bracket_depths(&indent_guides, &snapshot.buffer_snapshot)
+        });
 
         Some(
             indent_guides
                 .into_iter()
+                .zip(bracket_depths)
                 .enumerate()
-                .filter_map(|(i, indent_guide)| {
+                .filter_map(|(i, (indent_guide, bracket_depth))| {
                     let single_indent_width =
                         self.column_pixels(indent_guide.tab_size as usize, window);
                     let total_width = single_indent_width * indent_guide.depth as f32;
@@ -2701,6 +2724,7 @@ impl EditorElement {
                             length,
                             single_indent_width,
                             depth: indent_guide.depth,
+                            color_depth: bracket_depth.unwrap_or(indent_guide.depth),
                             active: active_indent_guide_indices.contains(&i),
                             settings: indent_guide.settings,
                         })
@@ -4403,6 +4427,113 @@ impl EditorElement {
         header
     }
 
+    fn layout_sticky_scopes(
+        &self,
+        snapshot: &EditorSnapshot,
+        scroll_position: gpui::Point<f32>,
+        scroll_pixel_position: gpui::Point<Pixels>,
+        line_height: Pixels,
+        right_margin: Pixels,
+        hitbox: &Hitbox,
+        content_origin: gpui::Point<Pixels>,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Option<AnyElement> {
+        let sticky_scroll = EditorSettings::get_global(cx).sticky_scroll;
+        if !sticky_scroll.enabled || !snapshot.mode.is_full() {
+            return None;
+        }
+        let scopes = snapshot.sticky_scopes(
+            scroll_position.y,
+            sticky_scroll.max_depth as usize,
+            Some(cx.theme().syntax()),
+        );
+        if scopes.is_empty() {
+            return None;
+        }
+
+        let colors = cx.theme().colors();
+        let text_style = self.style.text.clone();
+        let gutter_width = content_origin.x - hitbox.origin.x;
+        let available_width = hitbox.bounds.size.width - right_margin;
+        let scope_count = scopes.len();
+        // Render the innermost scopes first, so that they slide under the outer ones
+        // when pushed up by the end of their scope.
+        let lines = scopes
+            .into_iter()
+            .enumerate()
+            .rev()
+            .map(|(depth, scope)| {
+                let top = (depth as f32).min(scope.end_display_row.as_f32() - scroll_position.y)
+                    * line_height;
+                let line_number = (scope.row.0 + 1).to_string();
+                let line_number = StyledText::new(line_number.clone()).with_default_highlights(
+                    &text_style,
+                    [(
+                        0..line_number.len(),
+                        HighlightStyle::color(colors.editor_line_number),
+                    )],
+                );
+                let text = StyledText::new(scope.text.clone())
+                    .with_default_highlights(&text_style, scope.highlight_ranges.clone());
+                h_flex()
+                    .id(("sticky-scope", depth))
+                    .absolute()
+                    .top(top)
+                    .left_0()
+                    .w(available_width)
+                    .h(line_height)
+                    .bg(colors.editor_background)
+                    .hover(|style| style.bg(colors.editor_active_line_background))
+                    .when(depth + 1 == scope_count, |line| {
+                        line.border_b_1().border_color(colors.border_variant)
+                    })
+                    .occlude()
+                    .cursor_pointer()
+                    .child(
+                        h_flex()
+                            .flex_none()
+                            .w(gutter_width)
+                            .h_full()
+                            .justify_end()
+                            .pr(gutter_width.min(line_height))
+                            .child(line_number),
+                    )
+                    .child(
+                        div()
+                            .flex_1()
+                            .h_full()
+                            .overflow_hidden()
+                            .child(div().ml(-scroll_pixel_position.x).child(text)),
+                    )
+                    .on_mouse_down(MouseButton::Left, |_, _, cx| cx.stop_propagation())
+                    .on_click(window.listener_for(&self.editor, {
+                        move |editor, _: &ClickEvent, window, cx| {
+                            editor.jump_to_sticky_scope(&scope, depth, window, cx);
+                        }
+                    }))
+            })
+            .collect::<Vec<_>>();
+
+        let mut element = div()
+            .relative()
+            .overflow_hidden()
+            .w(available_width)
+            .h(scope_count as f32 * line_height)
+            .children(lines)
+            .into_any_element();
+        element.prepaint_as_root(
+            hitbox.origin,
+            size(
+                AvailableSpace::Definite(available_width),
+                AvailableSpace::Definite(scope_count as f32 * line_height),
+            ),
+            window,
+            cx,
+        );
+        Some(element)
+    }
+
     fn layout_cursor_popovers(
         &self,
         line_height: Pixels,
@@ -5679,7 +5810,10 @@ impl EditorElement {
         };
 
         for indent_guide in indent_guides {
-            let indent_accent_colors = cx.theme().accents().color_for_index(indent_guide.depth);
+            let indent_accent_colors = cx
+                .theme()
+                .accents()
+                .color_for_index(indent_guide.color_depth);
             let settings = &indent_guide.settings;
 
             // TODO fixed for now, expose them through themes later
@@ -6757,7 +6891,10 @@ impl EditorElement {
                         HighlightedRangeLine {
                             start_x: if row == range.start.row() {
                                 layout.content_origin.x
-                                    + line_layout.x_for_index(range.start.column() as usize)
+                                    + line_layout.x_for_virtual_index(
+                                        range.start.column() as usize,
+                                        layout.position_map.em_advance,
+                                    )
                                     - layout.position_map.scroll_pixel_position.x
                             } else {
                                 layout.content_origin.x
@@ -6765,7 +6902,10 @@ impl EditorElement {
                             },
                             end_x: if row == range.end.row() {
                                 layout.content_origin.x
-                                    + line_layout.x_for_index(range.end.column() as usize)
+                                    + line_layout.x_for_virtual_index(
+                                        range.end.column() as usize,
+                                        layout.position_map.em_advance,
+                                    )
                                     - layout.position_map.scroll_pixel_position.x
                             } else {
                                 layout.content_origin.x + line_layout.width + line_end_overshoot
@@ -8131,6 +8271,16 @@ impl LineWithInvisibles {
         fragment_start_x
     }
 
+    /// Like [`Self::x_for_index`], but continues past the end of the line in columns of
+    /// `em_advance`, for positions in virtual space.
+    pub fn x_for_virtual_index(&self, index: usize, em_advance: Pixels) -> Pixels {
+        if index > self.len {
+            self.width + em_advance * (index - self.len) as f32
+        } else {
+            self.x_for_index(index)
+        }
+    }
+
     pub fn index_for_x(&self, x: Pixels) -> Option<usize> {
         let mut fragment_start_x = Pixels::ZERO;
         let mut fragment_start_index = 0;
@@ -8982,6 +9132,23 @@ impl Element for EditorElement {
                         scroll_position.x * em_advance,
                         scroll_position.y * line_height,
                     );
+                    let sticky_scopes = (!is_minimap)
+                        .then(|| {
+                            window.with_element_namespace("sticky_scopes", |window| {
+                                self.layout_sticky_scopes(
+                                    &snapshot,
+                                    scroll_position,
+                                    scroll_pixel_position,
+                                    line_height,
+                                    right_margin,
+                                    &hitbox,
+                                    content_origin,
+                                    window,
+                                    cx,
+                                )
+                            })
+                        })
+                        .flatten();
                     let indent_guides = self.layout_indent_guides(
                         content_origin,
                         text_hitbox.origin,
@@ -9448,6 +9615,7 @@ impl Element for EditorElement {
                         tab_invisible,
                         space_invisible,
                         sticky_buffer_header,
+                        sticky_scopes,
                         expand_toggles,
                     }
                 })
@@ -9518,6 +9686,12 @@ impl Element for EditorElement {
                         }
                     });
 
+                    window.with_element_namespace("sticky_scopes", |window| {
+                        if let Some(mut sticky_scopes) = layout.sticky_scopes.take() {
+                            sticky_scopes.paint(window, cx)
+                        }
+                    });
+
                     self.paint_minimap(layout, window, cx);
                     self.paint_scrollbars(layout, window, cx);
                     self.paint_edit_prediction_popover(layout, window, cx);
@@ -9626,6 +9800,7 @@ pub struct EditorLayout {
     tab_invisible: ShapedLine,
     space_invisible: ShapedLine,
     sticky_buffer_header: Option<AnyElement>,
+    sticky_scopes: Option<AnyElement>,
     document_colors: Option<(DocumentColorsRenderMode, Vec<(Range<DisplayPoint>, Hsla)>)>,
 }
 
@@ -10191,6 +10366,9 @@ pub struct IndentGuideLayout {
     length: Pixels,
     single_indent_width: Pixels,
     depth: u32,
+    /// The depth picking the guide's accent color: the nesting depth of the colorized brackets
+    /// around it, or its indentation depth when brackets aren't colorized.
+    color_depth: u32,
     active: bool,
     settings: IndentGuideSettings,
 }
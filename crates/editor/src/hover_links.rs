@@ -1,7 +1,7 @@
 use crate::{
     Anchor, Editor, EditorSettings, EditorSnapshot, FindAllReferences, GoToDefinition,
     GoToDefinitionSplit, GoToTypeDefinition, GoToTypeDefinitionSplit, GotoDefinitionKind, InlayId,
-    Navigated, PointForPosition, SelectPhase,
+    Navigated, PointForPosition, SelectPhase, SemanticsProvider,
     editor_settings::GoToDefinitionFallback,
     hover_popover::{self, InlayHover},
     scroll::ScrollAmount,
@@ -15,7 +15,7 @@ use project::{
     ResolveState, ResolvedPath,
 };
 use settings::Settings;
-use std::ops::Range;
+use std::{ops::Range, rc::Rc};
 use theme::ActiveTheme as _;
 use util::{ResultExt, TryFutureExt as _, maybe};
 
@@ -543,7 +543,24 @@ pub fn show_link_definition(
         async move {
             let result = match &trigger_point {
                 TriggerPoint::Text(_) => {
-                    if let Some((url_range, url)) = find_url(&buffer, buffer_position, cx.clone()) {
+                    if let Some((link_range, link)) = find_document_link(
+                        &buffer,
+                        provider.as_ref(),
+                        project.as_ref(),
+                        buffer_position,
+                        cx,
+                    )
+                    .await
+                    {
+                        let range = maybe!({
+                            let start = snapshot.anchor_in_excerpt(excerpt_id, link_range.start)?;
+                            let end = snapshot.anchor_in_excerpt(excerpt_id, link_range.end)?;
+                            Some(RangeInEditor::Text(start..end))
+                        });
+                        Some((range, vec![link]))
+                    } else if let Some((url_range, url)) =
+                        find_url(&buffer, buffer_position, cx.clone())
+                    {
                         this.read_with(cx, |_, _| {
                             let range = maybe!({
                                 let start =
@@ -663,6 +680,57 @@ pub fn show_link_definition(
     editor.hovered_link_state = Some(hovered_link_state);
 }
 
+/// Finds a link reported by the language server (`textDocument/documentLink`) at the given position,
+/// resolving its target lazily if the server has not provided one upfront.
+pub(crate) async fn find_document_link(
+    buffer: &Entity<language::Buffer>,
+    provider: Option<&Rc<dyn SemanticsProvider>>,
+    project: Option<&Entity<Project>>,
+    position: text::Anchor,
+    cx: &mut AsyncWindowContext,
+) -> Option<(Range<text::Anchor>, HoverLink)> {
+    let provider = provider?;
+    let links = cx
+        .update(|_, cx| provider.document_links(buffer, cx))
+        .ok()??
+        .await
+        .log_err()??;
+    let snapshot = buffer.read_with(cx, |buffer, _| buffer.snapshot()).ok()?;
+    let mut link = links.into_iter().find(|link| {
+        link.range.start.cmp(&position, &snapshot).is_le()
+            && link.range.end.cmp(&position, &snapshot).is_ge()
+    })?;
+    if link.target().is_none() && !link.resolved {
+        link = cx
+            .update(|_, cx| provider.resolve_document_link(link, buffer.clone(), cx))
+            .ok()??
+            .await
+            .log_err()?;
+    }
+
+    let range = link.range.clone();
+    let target = link.target()?;
+    let Ok(abs_path) = target.to_file_path() else {
+        return Some((range, HoverLink::Url(target.to_string())));
+    };
+    let abs_path = abs_path.to_string_lossy().into_owned();
+    let resolved_path = match project {
+        Some(project) => project
+            .update(cx, |project, cx| {
+                project.resolve_path_in_buffer(&abs_path, buffer, cx)
+            })
+            .ok()?
+            .await
+            .filter(|resolved_path| resolved_path.is_file()),
+        None => None,
+    };
+    let resolved_path = resolved_path.unwrap_or(ResolvedPath::AbsPath {
+        path: abs_path,
+        is_dir: false,
+    });
+    Some((range, HoverLink::File(resolved_path)))
+}
+
 pub(crate) fn find_url(
     buffer: &Entity<language::Buffer>,
     position: text::Anchor,
@@ -1462,6 +1530,63 @@ mod tests {
         );
     }
 
+    #[gpui::test]
+    async fn test_document_links(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                document_link_provider: Some(lsp::DocumentLinkOptions {
+                    resolve_provider: Some(true),
+                    work_done_progress_options: Default::default(),
+                }),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+
+        cx.set_state(indoc! {"
+            use serde::Deserializeˇ;
+        "});
+        let link_range = cx.lsp_range(indoc! {"
+            use «serde»::Deserialize;
+        "});
+        let screen_coord = cx.pixel_position(indoc! {"
+            use serˇde::Deserialize;
+        "});
+
+        let mut link_requests = cx.set_request_handler::<lsp::request::DocumentLinkRequest, _, _>(
+            move |_, _, _| async move {
+                Ok(Some(vec![lsp::DocumentLink {
+                    range: link_range,
+                    target: None,
+                    tooltip: None,
+                    data: Some(serde_json::json!({ "crate": "serde" })),
+                }]))
+            },
+        );
+        let mut resolve_requests = cx
+            .set_request_handler::<lsp::request::DocumentLinkResolve, _, _>(
+                move |_, link, _| async move {
+                    Ok(lsp::DocumentLink {
+                        target: Some("https://docs.rs/serde".parse().unwrap()),
+                        ..link
+                    })
+                },
+            );
+
+        cx.simulate_mouse_move(screen_coord, None, Modifiers::secondary_key());
+        link_requests.next().await;
+        resolve_requests.next().await;
+        cx.run_until_parked();
+        cx.assert_editor_text_highlights::<HoveredLinkState>(indoc! {"
+            use «serde»::Deserialize;
+        "});
+
+        cx.simulate_click(screen_coord, Modifiers::secondary_key());
+        assert_eq!(cx.opened_url(), Some("https://docs.rs/serde".into()));
+    }
+
     #[gpui::test]
     async fn test_urls_at_beginning_of_buffer(cx: &mut gpui::TestAppContext) {
         init_test(cx, |_| {});
@@ -1774,6 +1774,10 @@ impl SearchableItem for Editor {
                     );
                 }
             }
+            if query.is_fuzzy() {
+                // Fuzzy matches are ranked by score, but they are navigated in order.
+                ranges.sort_by(|a, b| a.start.cmp(&b.start, &buffer));
+            }
 
             ranges
         })
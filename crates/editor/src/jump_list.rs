@@ -0,0 +1,382 @@
+use std::{collections::VecDeque, path::Path, sync::Arc};
+
+use anyhow::Result;
+use collections::HashMap;
+use gpui::{App, AppContext as _, Context, EntityId, Global, Task, Window};
+use language::{Bias, Point};
+use multi_buffer::ToPoint as _;
+use settings::Settings;
+use util::ResultExt as _;
+use workspace::{OpenOptions, OpenVisible, SERIALIZATION_THROTTLE_TIME, Workspace, WorkspaceId};
+
+use crate::{
+    Anchor, Editor, EditorSettings, SelectionEffects,
+    actions::{JumpBack, JumpForward},
+    persistence::DB,
+    scroll::Autoscroll,
+};
+
+/// A location the cursor jumped from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Jump {
+    pub abs_path: Arc<Path>,
+    pub point: Point,
+}
+
+/// The locations the cursor jumped from in a workspace, across all of its panes and files.
+#[derive(Default)]
+pub struct JumpList {
+    jumps: VecDeque<Jump>,
+    /// The index of the jump last moved to, or the number of jumps when not moving through
+    /// the list.
+    position: usize,
+    /// Whether the cursor is being moved by the jump list, which isn't recorded as a jump.
+    navigating: bool,
+    /// The write of the jump list to the database, delayed so that a burst of jumps is
+    /// persisted at once.
+    pending_save: Option<Task<()>>,
+}
+
+#[derive(Default)]
+struct JumpLists(HashMap<EntityId, JumpList>);
+
+impl Global for JumpLists {}
+
+impl JumpList {
+    /// Returns the jump list of the given workspace.
+    pub fn for_workspace(workspace: EntityId, cx: &App) -> Option<&JumpList> {
+        cx.try_global::<JumpLists>()?.0.get(&workspace)
+    }
+
+    fn update<R>(workspace: EntityId, cx: &mut App, f: impl FnOnce(&mut JumpList) -> R) -> R {
+        f(cx.default_global::<JumpLists>()
+            .0
+            .entry(workspace)
+            .or_default())
+    }
+
+    /// Returns the jumps, oldest first.
+    pub fn jumps(&self) -> impl DoubleEndedIterator<Item = &Jump> + ExactSizeIterator {
+        self.jumps.iter()
+    }
+
+    /// Returns the index of the jump last moved to, or the number of jumps when not moving
+    /// through the list.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    fn push(&mut self, jump: Jump, max_entries: usize) {
+        if self.navigating {
+            return;
+        }
+        // Jumping away from a location reached by moving back forgets the jumps after it.
+        self.jumps.truncate(self.position);
+        if self
+            .jumps
+            .back()
+            .is_some_and(|last| last.is_on_row_of(&jump))
+        {
+            self.jumps.pop_back();
+        }
+        self.jumps.push_back(jump);
+        while self.jumps.len() > max_entries {
+            self.jumps.pop_front();
+        }
+        self.position = self.jumps.len();
+    }
+
+    /// Puts previously persisted jumps, oldest first, before the ones recorded since, keeping
+    /// the most recent ones when there are more than `max_entries`.
+    fn restore(&mut self, jumps: impl IntoIterator<Item = Jump>, max_entries: usize) {
+        let recorded = std::mem::take(&mut self.jumps);
+        self.jumps.extend(jumps);
+        self.position += self.jumps.len();
+        self.jumps.extend(recorded);
+        while self.jumps.len() > max_entries {
+            self.jumps.pop_front();
+            self.position = self.position.saturating_sub(1);
+        }
+    }
+
+    /// Moves to the previous jump. When not already moving through the list, the current
+    /// location is recorded first, so that moving forward comes back to it.
+    fn back(&mut self, current: Option<Jump>) -> Option<Jump> {
+        if self.position >= self.jumps.len()
+            && let Some(current) = current
+        {
+            if !self
+                .jumps
+                .back()
+                .is_some_and(|last| last.is_on_row_of(&current))
+            {
+                self.jumps.push_back(current);
+            }
+            self.position = self.jumps.len() - 1;
+        }
+        self.position = self.position.checked_sub(1)?;
+        self.jumps.get(self.position).cloned()
+    }
+
+    fn forward(&mut self) -> Option<Jump> {
+        let position = self.position + 1;
+        let jump = self.jumps.get(position)?.clone();
+        self.position = position;
+        Some(jump)
+    }
+
+    fn select(&mut self, ix: usize) -> Option<Jump> {
+        let jump = self.jumps.get(ix)?.clone();
+        self.position = ix;
+        Some(jump)
+    }
+}
+
+impl Jump {
+    fn is_on_row_of(&self, other: &Jump) -> bool {
+        self.abs_path == other.abs_path && self.point.row == other.point.row
+    }
+}
+
+pub(crate) fn register_actions(workspace: &mut Workspace) {
+    workspace.register_action(|workspace, _: &JumpBack, window, cx| {
+        let current = workspace
+            .active_item(cx)
+            .and_then(|item| item.act_as::<Editor>(cx))
+            .and_then(|editor| {
+                let editor = editor.read(cx);
+                editor.jump_at(editor.selections.newest_anchor().head(), cx)
+            });
+        let workspace_entity = cx.entity_id();
+        if let Some(jump) =
+            JumpList::update(workspace_entity, cx, |jump_list| jump_list.back(current))
+        {
+            save_jump_list(workspace.database_id(), workspace_entity, cx);
+            open_jump(workspace, jump, window, cx).detach_and_log_err(cx);
+        }
+    });
+    workspace.register_action(|workspace, _: &JumpForward, window, cx| {
+        let workspace_entity = cx.entity_id();
+        if let Some(jump) = JumpList::update(workspace_entity, cx, JumpList::forward) {
+            save_jump_list(workspace.database_id(), workspace_entity, cx);
+            open_jump(workspace, jump, window, cx).detach_and_log_err(cx);
+        }
+    });
+}
+
+/// Restores the jump list persisted by the previous sessions of the workspace, and drops it
+/// once the workspace is released.
+pub(crate) fn restore_jump_list(workspace: &Workspace, cx: &mut Context<Workspace>) {
+    let workspace_entity = cx.entity_id();
+    cx.on_release(move |_, cx| {
+        if cx.has_global::<JumpLists>()
+            && let Some(jump_list) = cx.global_mut::<JumpLists>().0.remove(&workspace_entity)
+            && let Some(pending_save) = jump_list.pending_save
+        {
+            pending_save.detach();
+        }
+    })
+    .detach();
+
+    let Some(workspace_id) = workspace.database_id() else {
+        return;
+    };
+    cx.spawn(async move |workspace, cx| {
+        let jumps = cx
+            .background_spawn(async move { DB.get_jump_list(workspace_id) })
+            .await?;
+        let jumps = jumps.into_iter().map(|(abs_path, row, column)| Jump {
+            abs_path: abs_path.into(),
+            point: Point::new(row, column),
+        });
+        workspace.update(cx, |_, cx| {
+            let max_entries = EditorSettings::get_global(cx).jump_list.max_entries;
+            JumpList::update(workspace_entity, cx, |jump_list| {
+                jump_list.restore(jumps, max_entries)
+            })
+        })
+    })
+    .detach_and_log_err(cx);
+}
+
+/// Moves to the jump at the given index of the workspace's jump list.
+pub fn open_jump_at(
+    workspace: &mut Workspace,
+    ix: usize,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) -> Task<Result<()>> {
+    match JumpList::update(cx.entity_id(), cx, |jump_list| jump_list.select(ix)) {
+        Some(jump) => open_jump(workspace, jump, window, cx),
+        None => Task::ready(Ok(())),
+    }
+}
+
+fn open_jump(
+    workspace: &mut Workspace,
+    jump: Jump,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) -> Task<Result<()>> {
+    let workspace_entity = cx.entity_id();
+    JumpList::update(workspace_entity, cx, |jump_list| {
+        jump_list.navigating = true
+    });
+    let open = workspace.open_abs_path(
+        jump.abs_path.to_path_buf(),
+        OpenOptions {
+            visible: Some(OpenVisible::None),
+            ..Default::default()
+        },
+        window,
+        cx,
+    );
+    cx.spawn_in(window, async move |_, cx| {
+        let item = open.await;
+        cx.update(|window, cx| {
+            JumpList::update(workspace_entity, cx, |jump_list| {
+                jump_list.navigating = false
+            });
+            let Some(editor) = item?.act_as::<Editor>(cx) else {
+                return anyhow::Ok(());
+            };
+            editor.update(cx, |editor, cx| {
+                let point = editor
+                    .buffer()
+                    .read(cx)
+                    .snapshot(cx)
+                    .clip_point(jump.point, Bias::Left);
+                editor.change_selections(
+                    SelectionEffects::scroll(Autoscroll::center()).nav_history(false),
+                    window,
+                    cx,
+                    |selections| selections.select_ranges([point..point]),
+                );
+            });
+            anyhow::Ok(())
+        })?
+    })
+}
+
+fn save_jump_list(workspace_id: Option<WorkspaceId>, workspace_entity: EntityId, cx: &mut App) {
+    let Some(workspace_id) = workspace_id else {
+        return;
+    };
+    let Some(jump_list) = JumpList::for_workspace(workspace_entity, cx) else {
+        return;
+    };
+    let jumps = jump_list
+        .jumps()
+        .map(|jump| {
+            (
+                jump.abs_path.to_path_buf(),
+                jump.point.row,
+                jump.point.column,
+            )
+        })
+        .collect::<Vec<_>>();
+    let background_executor = cx.background_executor().clone();
+    let pending_save = cx.background_spawn(async move {
+        background_executor.timer(SERIALIZATION_THROTTLE_TIME).await;
+        DB.save_jump_list(workspace_id, jumps).await.log_err();
+    });
+    // Replacing the previous save cancels it, as the new one writes the whole list.
+    JumpList::update(workspace_entity, cx, |jump_list| {
+        jump_list.pending_save = Some(pending_save)
+    });
+}
+
+impl Editor {
+    /// Returns the location of the given position, in its file, if that file is on disk.
+    pub(crate) fn jump_at(&self, position: Anchor, cx: &App) -> Option<Jump> {
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        let (buffer, point, _) = snapshot.point_to_buffer_point(position.to_point(&snapshot))?;
+        let file = buffer.file()?.as_local()?;
+        Some(Jump {
+            abs_path: file.abs_path(cx).into(),
+            point,
+        })
+    }
+
+    /// Records a jump from the given position in the jump list of the editor's workspace.
+    pub(crate) fn record_jump(&self, position: Anchor, cx: &mut App) {
+        let Some((workspace, workspace_id)) = self.workspace.as_ref() else {
+            return;
+        };
+        let Some(jump) = self.jump_at(position, cx) else {
+            return;
+        };
+        let workspace_entity = workspace.entity_id();
+        let max_entries = EditorSettings::get_global(cx).jump_list.max_entries;
+        JumpList::update(workspace_entity, cx, |jump_list| {
+            jump_list.push(jump, max_entries)
+        });
+        save_jump_list(*workspace_id, workspace_entity, cx);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn jump(path: &str, row: u32) -> Jump {
+        Jump {
+            abs_path: Path::new(path).into(),
+            point: Point::new(row, 0),
+        }
+    }
+
+    #[test]
+    fn test_jump_list_navigation() {
+        let mut jump_list = JumpList::default();
+        jump_list.push(jump("/a.rs", 1), 100);
+        jump_list.push(jump("/b.rs", 20), 100);
+        jump_list.push(jump("/b.rs", 20), 100);
+        assert_eq!(jump_list.jumps().count(), 2);
+
+        assert_eq!(
+            jump_list.back(Some(jump("/c.rs", 5))),
+            Some(jump("/b.rs", 20))
+        );
+        assert_eq!(
+            jump_list.back(Some(jump("/b.rs", 20))),
+            Some(jump("/a.rs", 1))
+        );
+        assert_eq!(jump_list.back(Some(jump("/a.rs", 1))), None);
+        assert_eq!(jump_list.forward(), Some(jump("/b.rs", 20)));
+        assert_eq!(jump_list.forward(), Some(jump("/c.rs", 5)));
+        assert_eq!(jump_list.forward(), None);
+
+        jump_list.back(None);
+        jump_list.back(None);
+        jump_list.push(jump("/a.rs", 40), 100);
+        assert_eq!(
+            jump_list.jumps().cloned().collect::<Vec<_>>(),
+            [jump("/a.rs", 40)]
+        );
+    }
+
+    #[test]
+    fn test_jump_list_restore_and_trim() {
+        let mut jump_list = JumpList::default();
+        jump_list.push(jump("/b.rs", 2), 3);
+        jump_list.restore([jump("/a.rs", 0), jump("/a.rs", 1)], 3);
+        assert_eq!(jump_list.position(), 3);
+        jump_list.push(jump("/c.rs", 3), 3);
+        assert_eq!(
+            jump_list.jumps().cloned().collect::<Vec<_>>(),
+            [jump("/a.rs", 1), jump("/b.rs", 2), jump("/c.rs", 3)]
+        );
+        assert_eq!(jump_list.position(), 3);
+
+        let mut jump_list = JumpList::default();
+        jump_list.push(jump("/c.rs", 3), 2);
+        jump_list.restore([jump("/a.rs", 0), jump("/a.rs", 1), jump("/b.rs", 2)], 2);
+        assert_eq!(
+            jump_list.jumps().cloned().collect::<Vec<_>>(),
+            [jump("/b.rs", 2), jump("/c.rs", 3)],
+            "Restored jumps beyond the maximum should be dropped, oldest first"
+        );
+        assert_eq!(jump_list.position(), 2);
+    }
+}
@@ -258,6 +258,61 @@ pub fn line_end(
     }
 }
 
+/// Returns the number of columns between the end of the given row and the given column, when the
+/// column lies past the end of a line. Positions there are in virtual space, which column
+/// selections can extend into. Soft-wrapped rows and block rows have no virtual space.
+pub fn virtual_columns_at(map: &DisplaySnapshot, row: DisplayRow, column: u32) -> u32 {
+    let line_len = map.line_len(row);
+    if column <= line_len || !ends_buffer_line(map, row) {
+        return 0;
+    }
+    column - line_len
+}
+
+/// Returns the number of columns of virtual space between the end of the given row and the given
+/// x position, counting columns in the advance width of the editor font.
+pub fn virtual_columns_at_x(
+    map: &DisplaySnapshot,
+    row: DisplayRow,
+    x: Pixels,
+    text_layout_details: &TextLayoutDetails,
+) -> u32 {
+    let line = map.layout_row(row, text_layout_details);
+    if x <= line.width || !ends_buffer_line(map, row) {
+        return 0;
+    }
+    match em_advance(text_layout_details) {
+        Some(em_advance) => ((x - line.width) / em_advance).round() as u32,
+        None => 0,
+    }
+}
+
+/// Returns the width of the given number of columns of virtual space.
+pub fn virtual_space_width(columns: u32, text_layout_details: &TextLayoutDetails) -> Pixels {
+    em_advance(text_layout_details).map_or(Pixels::ZERO, |em_advance| em_advance * columns as f32)
+}
+
+fn em_advance(text_layout_details: &TextLayoutDetails) -> Option<Pixels> {
+    let text_style = &text_layout_details.editor_style.text;
+    let font_id = text_layout_details
+        .text_system
+        .resolve_font(&text_style.font());
+    let font_size = text_style.font_size.to_pixels(text_layout_details.rem_size);
+    text_layout_details
+        .text_system
+        .em_advance(font_id, font_size)
+        .ok()
+        .filter(|em_advance| *em_advance > Pixels::ZERO)
+}
+
+fn ends_buffer_line(map: &DisplaySnapshot, row: DisplayRow) -> bool {
+    if map.is_block_line(row) {
+        return false;
+    }
+    let line_end = DisplayPoint::new(row, map.line_len(row)).to_point(map);
+    line_end.column == map.buffer_snapshot.line_len(MultiBufferRow(line_end.row))
+}
+
 /// Returns a position of the previous word boundary, where a word character is defined as either
 /// uppercase letter, lowercase letter, '_' character or language-specific word character (like '-' in CSS).
 pub fn previous_word_start(map: &DisplaySnapshot, point: DisplayPoint) -> DisplayPoint {
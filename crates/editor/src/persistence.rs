@@ -197,6 +197,18 @@ impl Domain for EditorDb {
                 ON DELETE CASCADE
             ) STRICT;
         ),
+        sql! (
+            CREATE TABLE jump_list (
+                workspace_id INTEGER NOT NULL,
+                position INTEGER NOT NULL,
+                path BLOB NOT NULL,
+                row INTEGER NOT NULL,
+                column INTEGER NOT NULL,
+                PRIMARY KEY(workspace_id, position),
+                FOREIGN KEY(workspace_id) REFERENCES workspaces(workspace_id)
+                ON DELETE CASCADE
+            ) STRICT;
+        ),
     ];
 }
 
@@ -333,6 +345,37 @@ VALUES {placeholders};
         Ok(())
     }
 
+    query! {
+        pub fn get_jump_list(workspace_id: WorkspaceId) -> Result<Vec<(PathBuf, u32, u32)>> {
+            SELECT path, row, column
+            FROM jump_list
+            WHERE workspace_id = ?
+            ORDER BY position
+        }
+    }
+
+    pub async fn save_jump_list(
+        &self,
+        workspace_id: WorkspaceId,
+        jumps: Vec<(PathBuf, u32, u32)>,
+    ) -> Result<()> {
+        log::debug!("Saving jump list in workspace {workspace_id:?}");
+        self.write(move |conn| {
+            conn.exec_bound(sql!(
+                DELETE FROM jump_list WHERE workspace_id = ?;
+            ))?(workspace_id)?;
+            let mut insert = conn.exec_bound(sql!(
+                INSERT INTO jump_list (workspace_id, position, path, row, column)
+                VALUES (?1, ?2, ?3, ?4, ?5);
+            ))?;
+            for (position, (path, row, column)) in jumps.into_iter().enumerate() {
+                insert((workspace_id, position, path, row, column))?;
+            }
+            Ok(())
+        })
+        .await
+    }
+
     pub async fn save_editor_folds(
         &self,
         editor_id: ItemId,
@@ -465,4 +508,25 @@ mod tests {
             .unwrap();
         assert_eq!(have, serialized_editor);
     }
+
+    #[gpui::test]
+    async fn test_save_and_get_jump_list() {
+        let workspace_id = workspace::WORKSPACE_DB.next_id().await.unwrap();
+        assert_eq!(DB.get_jump_list(workspace_id).unwrap(), []);
+
+        let jumps = vec![
+            (PathBuf::from("/root/a.rs"), 10, 4),
+            (PathBuf::from("/root/b.rs"), 0, 0),
+            (PathBuf::from("/root/a.rs"), 120, 8),
+        ];
+        DB.save_jump_list(workspace_id, jumps.clone())
+            .await
+            .unwrap();
+        assert_eq!(DB.get_jump_list(workspace_id).unwrap(), jumps);
+
+        DB.save_jump_list(workspace_id, jumps[1..].to_vec())
+            .await
+            .unwrap();
+        assert_eq!(DB.get_jump_list(workspace_id).unwrap(), &jumps[1..]);
+    }
 }
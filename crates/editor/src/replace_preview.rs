@@ -0,0 +1,78 @@
+use std::ops::Range;
+
+use gpui::{Context, HighlightStyle, StrikethroughStyle, px};
+use multi_buffer::{Anchor, ToOffset as _};
+use theme::ActiveTheme;
+use util::post_inc;
+
+use crate::{Editor, display_map::Inlay, hover_links::InlayHighlight};
+
+enum ReplacePreviewHighlight {}
+
+impl Editor {
+    /// Previews replacements without editing the buffer: each replaced range is struck through
+    /// and followed by its replacement text, like an inline diff.
+    pub fn show_replace_preview(
+        &mut self,
+        mut replacements: Vec<(Range<Anchor>, String)>,
+        cx: &mut Context<Self>,
+    ) {
+        self.clear_replace_preview(cx);
+        if replacements.is_empty() {
+            return;
+        }
+
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        replacements.sort_by(|(a, _), (b, _)| a.start.cmp(&b.start, &snapshot));
+        let mut inlays = Vec::with_capacity(replacements.len());
+        let mut inlay_highlights = Vec::with_capacity(replacements.len());
+        for (range, replacement) in &replacements {
+            if replacement.is_empty() {
+                continue;
+            }
+            let inlay = Inlay::replace_preview(
+                post_inc(&mut self.next_inlay_id),
+                range.end,
+                replacement.as_str(),
+            );
+            inlay_highlights.push(InlayHighlight {
+                inlay: inlay.id,
+                inlay_position: range.end,
+                range: 0..replacement.len(),
+            });
+            self.replace_preview_inlays.push(inlay.id);
+            inlays.push(inlay);
+        }
+
+        let status = cx.theme().status();
+        let deleted_style = HighlightStyle {
+            background_color: Some(status.deleted_background),
+            strikethrough: Some(StrikethroughStyle {
+                thickness: px(1.),
+                color: None,
+            }),
+            ..HighlightStyle::default()
+        };
+        let created_style = HighlightStyle {
+            background_color: Some(status.created_background),
+            ..HighlightStyle::default()
+        };
+        let replaced_ranges = replacements
+            .into_iter()
+            .map(|(range, _)| range)
+            .filter(|range| range.start.to_offset(&snapshot) < range.end.to_offset(&snapshot))
+            .collect();
+        self.highlight_text::<ReplacePreviewHighlight>(replaced_ranges, deleted_style, cx);
+        self.splice_inlays(&[], inlays, cx);
+        self.highlight_inlays::<ReplacePreviewHighlight>(inlay_highlights, created_style, cx);
+    }
+
+    /// Removes the replacements shown by [`Editor::show_replace_preview`].
+    pub fn clear_replace_preview(&mut self, cx: &mut Context<Self>) {
+        let inlays = std::mem::take(&mut self.replace_preview_inlays);
+        if !inlays.is_empty() {
+            self.splice_inlays(&inlays, Vec::new(), cx);
+        }
+        self.clear_highlights::<ReplacePreviewHighlight>(cx);
+    }
+}
@@ -486,7 +486,8 @@ impl Editor {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
-        let opened_first_time = self.scroll_manager.visible_line_count.is_none();
+        let old_lines = self.scroll_manager.visible_line_count;
+        let opened_first_time = old_lines.is_none();
         self.scroll_manager.visible_line_count = Some(lines);
         if opened_first_time {
             cx.spawn_in(window, async move |editor, cx| {
@@ -494,10 +495,13 @@ impl Editor {
                     .update_in(cx, |editor, window, cx| {
                         editor.refresh_inlay_hints(InlayHintRefreshReason::NewLinesShown, cx);
                         editor.refresh_colors(false, None, window, cx);
+                        editor.colorize_brackets(false, cx);
                     })
                     .ok()
             })
             .detach()
+        } else if old_lines.is_some_and(|old_lines| old_lines < lines) {
+            self.colorize_brackets(false, cx);
         }
     }
 
@@ -609,6 +613,7 @@ impl Editor {
 
         self.refresh_inlay_hints(InlayHintRefreshReason::NewLinesShown, cx);
         self.refresh_colors(false, None, window, cx);
+        self.colorize_brackets(false, cx);
         editor_was_scrolled
     }
 
@@ -5,7 +5,7 @@ use std::{
     sync::Arc,
 };
 
-use collections::HashMap;
+use collections::{HashMap, HashSet};
 use gpui::{App, Entity, Pixels};
 use itertools::Itertools;
 use language::{Bias, Point, Selection, SelectionGoal, TextDimension};
@@ -15,7 +15,7 @@ use crate::{
     Anchor, DisplayPoint, DisplayRow, ExcerptId, MultiBuffer, MultiBufferSnapshot, SelectMode,
     ToOffset, ToPoint,
     display_map::{DisplayMap, DisplaySnapshot, ToDisplayPoint},
-    movement::TextLayoutDetails,
+    movement::{self, TextLayoutDetails},
 };
 
 #[derive(Debug, Clone)]
@@ -24,6 +24,22 @@ pub struct PendingSelection {
     pub mode: SelectMode,
 }
 
+/// The columns past the end of its line that a selection extends over, as block selections
+/// can when `column_selection_virtual_space` is enabled.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct VirtualSpace {
+    /// The number of columns between the selection's start and the end of its line.
+    pub start: u32,
+    /// The number of columns between the selection's end and the end of its line.
+    pub end: u32,
+}
+
+impl VirtualSpace {
+    pub fn is_empty(&self) -> bool {
+        self.start == 0 && self.end == 0
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct SelectionsCollection {
     display_map: Entity<DisplayMap>,
@@ -35,6 +51,9 @@ pub struct SelectionsCollection {
     disjoint: Arc<[Selection<Anchor>]>,
     /// A pending selection, such as when the mouse is being dragged
     pending: Option<PendingSelection>,
+    /// The virtual space of the disjoint selections that extend past the end of their lines,
+    /// by selection id. Cleared whenever the selections are replaced.
+    virtual_space: Arc<HashMap<usize, VirtualSpace>>,
 }
 
 impl SelectionsCollection {
@@ -55,6 +74,7 @@ impl SelectionsCollection {
                 },
                 mode: SelectMode::Character,
             }),
+            virtual_space: Arc::default(),
         }
     }
 
@@ -71,6 +91,7 @@ impl SelectionsCollection {
         self.line_mode = other.line_mode;
         self.disjoint = other.disjoint.clone();
         self.pending.clone_from(&other.pending);
+        self.virtual_space = other.virtual_space.clone();
     }
 
     pub fn count(&self) -> usize {
@@ -132,6 +153,19 @@ impl SelectionsCollection {
         resolve_selections(self.pending_anchor(), &map).next()
     }
 
+    /// The columns past the end of its line that the given selection extends over.
+    pub fn virtual_space(&self, selection_id: usize) -> VirtualSpace {
+        self.virtual_space
+            .get(&selection_id)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Whether any selection extends past the end of its line.
+    pub fn has_virtual_space(&self) -> bool {
+        !self.virtual_space.is_empty()
+    }
+
     pub(crate) fn pending_mode(&self) -> Option<SelectMode> {
         self.pending.as_ref().map(|pending| pending.mode.clone())
     }
@@ -364,33 +398,53 @@ impl SelectionsCollection {
             .collect()
     }
 
+    /// Builds a selection spanning the given horizontal positions in the given row. When
+    /// `virtual_space` is true, positions past the end of the line are kept as virtual space
+    /// rather than clipped to it.
     pub fn build_columnar_selection(
         &mut self,
         display_map: &DisplaySnapshot,
         row: DisplayRow,
         positions: &Range<Pixels>,
         reversed: bool,
+        virtual_space: bool,
         text_layout_details: &TextLayoutDetails,
-    ) -> Option<Selection<Point>> {
+    ) -> Option<(Selection<Point>, VirtualSpace)> {
         let is_empty = positions.start == positions.end;
         let line_len = display_map.line_len(row);
         let line = display_map.layout_row(row, text_layout_details);
         let start_col = line.closest_index_for_x(positions.start) as u32;
+        let virtual_columns_at = |x| {
+            if virtual_space {
+                movement::virtual_columns_at_x(display_map, row, x, text_layout_details)
+            } else {
+                0
+            }
+        };
 
-        let (start, end) = if is_empty {
+        let (start, end, virtual_space) = if is_empty {
             let point = DisplayPoint::new(row, std::cmp::min(start_col, line_len));
-            (point, point)
+            let virtual_columns = virtual_columns_at(positions.start);
+            let virtual_space = VirtualSpace {
+                start: virtual_columns,
+                end: virtual_columns,
+            };
+            (point, point, virtual_space)
         } else {
-            if start_col >= line_len {
+            let virtual_space = VirtualSpace {
+                start: virtual_columns_at(positions.start),
+                end: virtual_columns_at(positions.end),
+            };
+            if start_col >= line_len && virtual_space.end == 0 {
                 return None;
             }
             let start = DisplayPoint::new(row, start_col);
             let end_col = line.closest_index_for_x(positions.end) as u32;
             let end = DisplayPoint::new(row, end_col);
-            (start, end)
+            (start, end, virtual_space)
         };
 
-        Some(Selection {
+        let selection = Selection {
             id: post_inc(&mut self.next_selection_id),
             start: start.to_point(display_map),
             end: end.to_point(display_map),
@@ -399,7 +453,8 @@ impl SelectionsCollection {
                 start: positions.start.into(),
                 end: positions.end.into(),
             },
-        })
+        };
+        Some((selection, virtual_space))
     }
 
     pub fn change_with<R>(
@@ -451,6 +506,7 @@ impl<'a> MutableSelectionsCollection<'a> {
 
     pub fn clear_disjoint(&mut self) {
         self.collection.disjoint = Arc::default();
+        self.collection.virtual_space = Arc::default();
     }
 
     pub fn delete(&mut self, selection_id: usize) {
@@ -465,6 +521,9 @@ impl<'a> MutableSelectionsCollection<'a> {
             })
             .cloned()
             .collect();
+        if changed && self.collection.virtual_space.contains_key(&selection_id) {
+            Arc::make_mut(&mut self.collection.virtual_space).remove(&selection_id);
+        }
 
         self.selections_changed |= changed;
     }
@@ -517,6 +576,12 @@ impl<'a> MutableSelectionsCollection<'a> {
 
         let mut oldest = self.oldest_anchor().clone();
         if self.count() > 1 {
+            let virtual_space = self.virtual_space(oldest.id);
+            self.collection.virtual_space = if virtual_space.is_empty() {
+                Arc::default()
+            } else {
+                Arc::new(HashMap::from_iter([(oldest.id, virtual_space)]))
+            };
             self.collection.disjoint = Arc::from([oldest]);
             self.selections_changed = true;
             return true;
@@ -526,6 +591,7 @@ impl<'a> MutableSelectionsCollection<'a> {
             let head = oldest.head();
             oldest.start = head;
             oldest.end = head;
+            self.collection.virtual_space = Arc::default();
             self.collection.disjoint = Arc::from([oldest]);
             self.selections_changed = true;
             return true;
@@ -585,9 +651,41 @@ impl<'a> MutableSelectionsCollection<'a> {
                 .map(|selection| selection_to_anchor_selection(selection, &buffer)),
         );
         self.collection.pending = None;
+        self.collection.virtual_space = Arc::default();
         self.selections_changed = true;
     }
 
+    /// Selects the given selections, keeping the virtual space of those that extend past the
+    /// end of their lines.
+    pub fn select_with_virtual_space<T>(&mut self, selections: Vec<(Selection<T>, VirtualSpace)>)
+    where
+        T: ToOffset + ToPoint + Ord + std::marker::Copy + std::fmt::Debug,
+    {
+        let virtual_space = selections
+            .iter()
+            .filter(|(_, virtual_space)| !virtual_space.is_empty())
+            .map(|(selection, virtual_space)| (selection.id, *virtual_space))
+            .collect::<HashMap<_, _>>();
+        self.select(
+            selections
+                .into_iter()
+                .map(|(selection, _)| selection)
+                .collect(),
+        );
+        let selection_ids = self
+            .collection
+            .disjoint
+            .iter()
+            .map(|selection| selection.id)
+            .collect::<HashSet<_>>();
+        self.collection.virtual_space = Arc::new(
+            virtual_space
+                .into_iter()
+                .filter(|(id, _)| selection_ids.contains(id))
+                .collect(),
+        );
+    }
+
     pub fn select_anchors(&mut self, selections: Vec<Selection<Anchor>>) {
         let map = self.display_map();
         let resolved_selections =
@@ -0,0 +1,124 @@
+use std::ops::Range;
+
+use gpui::{Context, HighlightStyle, Window};
+use multi_buffer::{Anchor, MultiBufferRow, ToPoint as _};
+use text::Bias;
+use theme::SyntaxTheme;
+
+use crate::{
+    DisplayPoint, DisplayRow, Editor, EditorSnapshot, SelectionEffects, scroll::Autoscroll,
+};
+
+/// The first line of a scope (function, impl, class, ...) enclosing the top of the viewport,
+/// pinned at the top of the editor while the scope is scrolled through.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StickyScope {
+    /// Where the scope starts, moved to when the pinned line is clicked.
+    pub start: Anchor,
+    /// The buffer row of the scope's first line.
+    pub row: MultiBufferRow,
+    /// The display row of the scope's last line, which the pinned line is never drawn below.
+    pub end_display_row: DisplayRow,
+    /// The scope's outline text, indented like the scope's first line.
+    pub text: String,
+    pub highlight_ranges: Vec<(Range<usize>, HighlightStyle)>,
+}
+
+impl EditorSnapshot {
+    /// Returns the scopes to pin at the top of the editor when scrolled to `scroll_top`,
+    /// outermost first: those enclosing the first line left visible below the pinned lines,
+    /// and starting above it.
+    pub fn sticky_scopes(
+        &self,
+        scroll_top: f32,
+        max_depth: usize,
+        theme: Option<&SyntaxTheme>,
+    ) -> Vec<StickyScope> {
+        let mut scopes = Vec::new();
+        if max_depth == 0 || self.buffer_snapshot.show_headers() {
+            return scopes;
+        }
+
+        let top_row = scroll_top.max(0.).floor() as u32;
+        let max_row = self.display_snapshot.max_point().row();
+        // Each pinned line hides the line below it, so look for the scopes enclosing the first
+        // line that is not hidden, until pinning them does not hide any additional line.
+        loop {
+            let row = DisplayRow(top_row + scopes.len() as u32);
+            if row > max_row {
+                break;
+            }
+            let point = self
+                .display_snapshot
+                .display_point_to_point(DisplayPoint::new(row, 0), Bias::Left);
+            let Some((_, items)) = self.buffer_snapshot.symbols_containing(point, theme) else {
+                break;
+            };
+
+            let enclosing_scopes = items
+                .into_iter()
+                .filter_map(|item| {
+                    let start = item.range.start.to_point(&self.buffer_snapshot);
+                    if start.row >= point.row {
+                        return None;
+                    }
+                    let end = item.range.end.to_point(&self.buffer_snapshot);
+                    let indent = " ".repeat(
+                        self.display_snapshot
+                            .point_to_display_point(start, Bias::Left)
+                            .column() as usize,
+                    );
+                    let highlight_ranges = item
+                        .highlight_ranges
+                        .into_iter()
+                        .map(|(range, style)| {
+                            (range.start + indent.len()..range.end + indent.len(), style)
+                        })
+                        .collect();
+                    Some(StickyScope {
+                        start: item.range.start,
+                        row: MultiBufferRow(start.row),
+                        end_display_row: self
+                            .display_snapshot
+                            .point_to_display_point(end, Bias::Left)
+                            .row(),
+                        text: indent + &item.text,
+                        highlight_ranges,
+                    })
+                })
+                .take(max_depth)
+                .collect::<Vec<_>>();
+
+            // Fewer scopes enclose the line below the pinned ones when the innermost pinned scope
+            // ends among the hidden lines: keep it pinned, it will be pushed up by its end.
+            if enclosing_scopes.len() < scopes.len() {
+                break;
+            }
+            let settled = enclosing_scopes.len() == scopes.len();
+            scopes = enclosing_scopes;
+            if settled {
+                break;
+            }
+        }
+        scopes
+    }
+}
+
+impl Editor {
+    /// Moves the cursor to the start of a pinned scope, scrolling so that its line stays
+    /// where it was pinned, below the lines of the scopes enclosing it.
+    pub(crate) fn jump_to_sticky_scope(
+        &mut self,
+        scope: &StickyScope,
+        depth: usize,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.change_selections(
+            SelectionEffects::scroll(Autoscroll::top_relative(depth)),
+            window,
+            cx,
+            |selections| selections.select_anchor_ranges([scope.start..scope.start]),
+        );
+    }
+}
@@ -990,6 +990,12 @@ impl ExtensionImports for WasmState {
                                 command: None,
                                 settings: Some(settings),
                             })?),
+                            project::project_settings::ContextServerSettings::Http { .. } => {
+                                Ok(serde_json::to_string(&settings::ContextServerSettings {
+                                    command: None,
+                                    settings: None,
+                                })?)
+                            }
                         }
                     }
                     _ => {
@@ -4,9 +4,11 @@ mod file_finder_tests;
 mod open_path_prompt_tests;
 
 pub mod file_finder_settings;
+mod jump_list_picker;
 mod open_path_prompt;
 
 use futures::future::join_all;
+pub use jump_list_picker::JumpListPicker;
 pub use open_path_prompt::OpenPathDelegate;
 
 use collections::HashMap;
@@ -95,6 +97,7 @@ pub fn init_settings(cx: &mut App) {
 pub fn init(cx: &mut App) {
     init_settings(cx);
     cx.observe_new(FileFinder::register).detach();
+    cx.observe_new(JumpListPicker::register).detach();
     cx.observe_new(OpenPathPrompt::register).detach();
     cx.observe_new(OpenPathPrompt::register_new_path).detach();
 }
@@ -0,0 +1,235 @@
+use editor::jump_list::{self, JumpList};
+use fuzzy::{StringMatch, StringMatchCandidate, match_strings};
+use gpui::{
+    App, Context, DismissEvent, Entity, EventEmitter, FocusHandle, Focusable, ParentElement,
+    Render, Styled, Task, WeakEntity, Window, actions, rems,
+};
+use picker::{Picker, PickerDelegate};
+use std::sync::Arc;
+use ui::{HighlightedLabel, ListItem, ListItemSpacing, prelude::*};
+use util::ResultExt as _;
+use workspace::{ModalView, Workspace};
+
+actions!(
+    jump_list,
+    [
+        /// Toggles the picker over the locations in the jump list.
+        Toggle
+    ]
+);
+
+/// A picker over the workspace's jump list, most recent jump first.
+pub struct JumpListPicker {
+    picker: Entity<Picker<JumpListPickerDelegate>>,
+}
+
+impl JumpListPicker {
+    pub(crate) fn register(
+        workspace: &mut Workspace,
+        _window: Option<&mut Window>,
+        _: &mut Context<Workspace>,
+    ) {
+        workspace.register_action(|workspace, _: &Toggle, window, cx| {
+            Self::toggle(workspace, window, cx);
+        });
+    }
+
+    fn toggle(workspace: &mut Workspace, window: &mut Window, cx: &mut Context<Workspace>) {
+        let jumps = JumpList::for_workspace(cx.entity_id(), cx)
+            .map(|jump_list| {
+                let current = jump_list.position();
+                jump_list
+                    .jumps()
+                    .enumerate()
+                    .rev()
+                    .map(|(ix, jump)| JumpEntry {
+                        ix,
+                        label: format!("{}:{}", jump.abs_path.display(), jump.point.row + 1),
+                        is_current: ix == current,
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        let weak_workspace = cx.entity().downgrade();
+        workspace.toggle_modal(window, cx, move |window, cx| {
+            let delegate =
+                JumpListPickerDelegate::new(cx.entity().downgrade(), weak_workspace, jumps);
+            let picker = cx.new(|cx| Picker::uniform_list(delegate, window, cx));
+            Self { picker }
+        });
+    }
+}
+
+impl Render for JumpListPicker {
+    fn render(&mut self, _window: &mut Window, _cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .key_context("JumpListPicker")
+            .w(rems(34.))
+            .child(self.picker.clone())
+    }
+}
+
+impl Focusable for JumpListPicker {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl EventEmitter<DismissEvent> for JumpListPicker {}
+impl ModalView for JumpListPicker {}
+
+struct JumpEntry {
+    /// The index of the jump in the jump list.
+    ix: usize,
+    label: String,
+    /// Whether the jump list was last moved to this jump.
+    is_current: bool,
+}
+
+pub struct JumpListPickerDelegate {
+    jump_list_picker: WeakEntity<JumpListPicker>,
+    workspace: WeakEntity<Workspace>,
+    jumps: Vec<JumpEntry>,
+    matches: Vec<StringMatch>,
+    selected_index: usize,
+}
+
+impl JumpListPickerDelegate {
+    fn new(
+        jump_list_picker: WeakEntity<JumpListPicker>,
+        workspace: WeakEntity<Workspace>,
+        jumps: Vec<JumpEntry>,
+    ) -> Self {
+        Self {
+            jump_list_picker,
+            workspace,
+            jumps,
+            matches: Vec::new(),
+            selected_index: 0,
+        }
+    }
+}
+
+impl PickerDelegate for JumpListPickerDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _window: &mut Window, _cx: &mut App) -> Arc<str> {
+        "Jump to a previous location…".into()
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn confirm(&mut self, _: bool, window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        let Some(jump) = self
+            .matches
+            .get(self.selected_index)
+            .and_then(|mat| self.jumps.get(mat.candidate_id))
+        else {
+            return;
+        };
+        let ix = jump.ix;
+        self.workspace
+            .update(cx, |workspace, cx| {
+                jump_list::open_jump_at(workspace, ix, window, cx).detach_and_log_err(cx)
+            })
+            .log_err();
+        self.dismissed(window, cx);
+    }
+
+    fn dismissed(&mut self, _: &mut Window, cx: &mut Context<Picker<Self>>) {
+        self.jump_list_picker
+            .update(cx, |_, cx| cx.emit(DismissEvent))
+            .log_err();
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(
+        &mut self,
+        ix: usize,
+        _window: &mut Window,
+        _: &mut Context<Picker<Self>>,
+    ) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(
+        &mut self,
+        query: String,
+        window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Task<()> {
+        let background = cx.background_executor().clone();
+        let candidates = self
+            .jumps
+            .iter()
+            .enumerate()
+            .map(|(id, jump)| StringMatchCandidate::new(id, &jump.label))
+            .collect::<Vec<_>>();
+        cx.spawn_in(window, async move |this, cx| {
+            let matches = if query.is_empty() {
+                candidates
+                    .into_iter()
+                    .map(|candidate| StringMatch {
+                        candidate_id: candidate.id,
+                        string: candidate.string,
+                        positions: Vec::new(),
+                        score: 0.0,
+                    })
+                    .collect()
+            } else {
+                let mut matches = match_strings(
+                    &candidates,
+                    &query,
+                    false,
+                    true,
+                    100,
+                    &Default::default(),
+                    background,
+                )
+                .await;
+                // Keep the most recent jumps first rather than ordering them by score.
+                matches.sort_unstable_by_key(|mat| mat.candidate_id);
+                matches
+            };
+
+            this.update(cx, |this, cx| {
+                let delegate = &mut this.delegate;
+                delegate.matches = matches;
+                delegate.selected_index = delegate
+                    .selected_index
+                    .min(delegate.matches.len().saturating_sub(1));
+                cx.notify();
+            })
+            .log_err();
+        })
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _: &mut Window,
+        _: &mut Context<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let mat = self.matches.get(ix)?;
+        let jump = self.jumps.get(mat.candidate_id)?;
+        Some(
+            ListItem::new(ix)
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .toggle_state(selected)
+                .child(HighlightedLabel::new(
+                    mat.string.clone(),
+                    mat.positions.clone(),
+                ))
+                .when(jump.is_current, |item| {
+                    item.end_slot(Icon::new(IconName::Check).color(Color::Accent))
+                }),
+        )
+    }
+}
@@ -30,7 +30,8 @@ use rope::Rope;
 use serde::{Deserialize, Serialize};
 use smol::io::AsyncWriteExt;
 use std::{
-    io::{self, Write},
+    io::{self, Read as _, Seek as _, Write},
+    ops::Range,
     path::{Component, Path, PathBuf},
     pin::Pin,
     sync::Arc,
@@ -98,6 +99,8 @@ pub trait Fs: Send + Sync {
     ) -> Result<()>;
     async fn copy_file(&self, source: &Path, target: &Path, options: CopyOptions) -> Result<()>;
     async fn rename(&self, source: &Path, target: &Path, options: RenameOptions) -> Result<()>;
+    /// Gives `target` the permissions of `source`, such as its executable bit.
+    async fn copy_permissions(&self, source: &Path, target: &Path) -> Result<()>;
     async fn remove_dir(&self, path: &Path, options: RemoveOptions) -> Result<()>;
     async fn trash_dir(&self, path: &Path, options: RemoveOptions) -> Result<()> {
         self.remove_dir(path, options).await
@@ -112,6 +115,14 @@ pub trait Fs: Send + Sync {
         Ok(String::from_utf8(self.load_bytes(path).await?)?)
     }
     async fn load_bytes(&self, path: &Path) -> Result<Vec<u8>>;
+    /// Loads the given byte range of a file, cut short at the end of the file, without loading
+    /// the rest of it.
+    async fn load_bytes_range(&self, path: &Path, range: Range<u64>) -> Result<Vec<u8>> {
+        let bytes = self.load_bytes(path).await?;
+        let start = (range.start as usize).min(bytes.len());
+        let end = (range.end as usize).clamp(start, bytes.len());
+        Ok(bytes[start..end].to_vec())
+    }
     async fn atomic_write(&self, path: PathBuf, text: String) -> Result<()>;
     async fn save(&self, path: &Path, text: &Rope, line_ending: LineEnding) -> Result<()>;
     async fn write(&self, path: &Path, content: &[u8]) -> Result<()>;
@@ -420,6 +431,12 @@ impl Fs for RealFs {
         Ok(())
     }
 
+    async fn copy_permissions(&self, source: &Path, target: &Path) -> Result<()> {
+        let permissions = smol::fs::metadata(source).await?.permissions();
+        smol::fs::set_permissions(target, permissions).await?;
+        Ok(())
+    }
+
     async fn remove_dir(&self, path: &Path, options: RemoveOptions) -> Result<()> {
         let result = if options.recursive {
             smol::fs::remove_dir_all(path).await
@@ -567,6 +584,19 @@ impl Fs for RealFs {
         Ok(bytes)
     }
 
+    async fn load_bytes_range(&self, path: &Path, range: Range<u64>) -> Result<Vec<u8>> {
+        let path = path.to_path_buf();
+        smol::unblock(move || {
+            let mut file = std::fs::File::open(path)?;
+            file.seek(io::SeekFrom::Start(range.start))?;
+            let mut bytes = Vec::new();
+            file.take(range.end.saturating_sub(range.start))
+                .read_to_end(&mut bytes)?;
+            anyhow::Ok(bytes)
+        })
+        .await
+    }
+
     #[cfg(not(target_os = "windows"))]
     async fn atomic_write(&self, path: PathBuf, data: String) -> Result<()> {
         smol::unblock(move || {
@@ -2131,6 +2161,16 @@ impl Fs for FakeFs {
         Ok(())
     }
 
+    async fn copy_permissions(&self, source: &Path, target: &Path) -> Result<()> {
+        self.simulate_random_delay().await;
+
+        // Permissions aren't simulated, so only check that both paths exist.
+        let mut state = self.state.lock();
+        state.entry(&normalize_path(source))?;
+        state.entry(&normalize_path(target))?;
+        Ok(())
+    }
+
     async fn copy_file(&self, source: &Path, target: &Path, options: CopyOptions) -> Result<()> {
         self.simulate_random_delay().await;
 
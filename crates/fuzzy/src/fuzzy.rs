@@ -7,4 +7,4 @@ pub use char_bag::CharBag;
 pub use paths::{
     PathMatch, PathMatchCandidate, PathMatchCandidateSet, match_fixed_path_set, match_path_sets,
 };
-pub use strings::{StringMatch, StringMatchCandidate, match_strings};
+pub use strings::{StringMatch, StringMatchCandidate, match_strings, match_strings_sync};
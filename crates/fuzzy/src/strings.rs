@@ -197,3 +197,48 @@ where
     util::truncate_to_bottom_n_sorted_by(&mut results, max_results, &|a, b| b.cmp(a));
     results
 }
+
+/// Matches the candidates on the calling thread, for callers that already run in the background
+/// and match too few candidates at once to benefit from [`match_strings`] spreading the work.
+pub fn match_strings_sync<T>(
+    candidates: &[T],
+    query: &str,
+    smart_case: bool,
+    penalize_length: bool,
+    max_results: usize,
+) -> Vec<StringMatch>
+where
+    T: Borrow<StringMatchCandidate>,
+{
+    if candidates.is_empty() || max_results == 0 || query.is_empty() {
+        return Vec::new();
+    }
+
+    let lowercase_query = query.to_lowercase().chars().collect::<Vec<_>>();
+    let query = query.chars().collect::<Vec<_>>();
+    let query_char_bag = CharBag::from(&lowercase_query[..]);
+    let mut matcher = Matcher::new(
+        &query,
+        &lowercase_query,
+        query_char_bag,
+        smart_case,
+        penalize_length,
+    );
+
+    let mut results = Vec::new();
+    matcher.match_candidates(
+        &[],
+        &[],
+        candidates.iter().map(|candidate| candidate.borrow()),
+        &mut results,
+        &AtomicBool::new(false),
+        |candidate: &&StringMatchCandidate, score, positions| StringMatch {
+            candidate_id: candidate.id,
+            score,
+            positions: positions.clone(),
+            string: candidate.string.to_string(),
+        },
+    );
+    util::truncate_to_bottom_n_sorted_by(&mut results, max_results, &|a, b| b.cmp(a));
+    results
+}
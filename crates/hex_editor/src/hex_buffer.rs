@@ -0,0 +1,623 @@
+use std::{
+    io,
+    ops::Range,
+    path::{Path, PathBuf},
+    pin::Pin,
+    sync::Arc,
+};
+
+use anyhow::{Context as _, Result};
+use collections::{BTreeMap, HashMap, HashSet};
+use fs::{Fs, RenameOptions};
+use futures::{StreamExt as _, TryStreamExt as _, stream};
+use gpui::{App, AppContext as _, Context, Entity, EventEmitter, Global, Task, WeakEntity};
+use project::{Project, ProjectEntryId, ProjectPath};
+use util::ResultExt as _;
+
+use crate::piece_table::{Checkpoint, PieceTable, Span};
+
+/// The size of the pages the file on disk is read in.
+pub const PAGE_SIZE: u64 = 64 * 1024;
+/// The number of pages kept in memory, beyond which the pages furthest from the ones last read
+/// are dropped.
+const MAX_CACHED_PAGES: usize = 256;
+/// The size of the chunks the contents are streamed in when saving or searching.
+const CHUNK_SIZE: u64 = 1024 * 1024;
+/// The number of edits that can be undone, beyond which the oldest ones are forgotten.
+const MAX_UNDO_STEPS: usize = 1000;
+
+/// A file opened in the hex editor.
+///
+/// Its contents are never loaded as a whole: the file on disk is read in pages as they are
+/// displayed, and edits are kept in a [`PieceTable`] on top of it until they are saved.
+pub struct HexBuffer {
+    project_path: ProjectPath,
+    entry_id: Option<ProjectEntryId>,
+    abs_path: Arc<Path>,
+    fs: Arc<dyn Fs>,
+    table: PieceTable,
+    pages: BTreeMap<u64, Arc<[u8]>>,
+    loading_pages: HashSet<u64>,
+    /// Incremented whenever the file on disk is read anew, so that pages read before are
+    /// discarded when they finish loading.
+    pages_generation: usize,
+    undo_stack: Vec<(Checkpoint, usize)>,
+    redo_stack: Vec<(Checkpoint, usize)>,
+    version: usize,
+    next_version: usize,
+    saved_version: usize,
+    /// Whether the contents are being written to disk, during which they can't be edited.
+    saving: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HexBufferEvent {
+    Edited,
+    PageLoaded,
+    Saved,
+    Reloaded,
+}
+
+impl EventEmitter<HexBufferEvent> for HexBuffer {}
+
+/// The hex buffers that are open, so that a file opened twice is edited in a single buffer.
+#[derive(Default)]
+struct OpenHexBuffers(HashMap<Arc<Path>, WeakEntity<HexBuffer>>);
+
+impl Global for OpenHexBuffers {}
+
+/// The contents of a [`HexBuffer`] at some point, which can be read in the background.
+#[derive(Clone)]
+pub struct HexBufferSnapshot {
+    abs_path: Arc<Path>,
+    fs: Arc<dyn Fs>,
+    table: PieceTable,
+}
+
+impl HexBuffer {
+    /// Opens the file at the given path, reusing the buffer it is already open in.
+    pub fn open(
+        project: &Entity<Project>,
+        project_path: ProjectPath,
+        cx: &mut App,
+    ) -> Task<Result<Entity<HexBuffer>>> {
+        let project = project.read(cx);
+        if !project.is_local() {
+            return Task::ready(Err(anyhow::anyhow!(
+                "the hex editor only supports local projects"
+            )));
+        }
+        let Some(abs_path) = project.absolute_path(&project_path, cx) else {
+            return Task::ready(Err(anyhow::anyhow!(
+                "no worktree found for {project_path:?}"
+            )));
+        };
+        let abs_path: Arc<Path> = abs_path.into();
+        if let Some(buffer) = cx
+            .try_global::<OpenHexBuffers>()
+            .and_then(|buffers| buffers.0.get(&abs_path)?.upgrade())
+        {
+            return Task::ready(Ok(buffer));
+        }
+
+        let entry_id = project
+            .entry_for_path(&project_path, cx)
+            .map(|entry| entry.id);
+        let fs = project.fs().clone();
+        cx.spawn(async move |cx| {
+            let metadata = fs
+                .metadata(&abs_path)
+                .await?
+                .with_context(|| format!("{abs_path:?} does not exist"))?;
+            anyhow::ensure!(!metadata.is_dir, "{abs_path:?} is a directory");
+            cx.update(|cx| {
+                let buffer = cx.new(|_| HexBuffer {
+                    project_path,
+                    entry_id,
+                    abs_path: abs_path.clone(),
+                    fs,
+                    table: PieceTable::new(metadata.len),
+                    pages: BTreeMap::default(),
+                    loading_pages: HashSet::default(),
+                    pages_generation: 0,
+                    undo_stack: Vec::new(),
+                    redo_stack: Vec::new(),
+                    version: 0,
+                    next_version: 1,
+                    saved_version: 0,
+                    saving: false,
+                });
+                let weak_buffer = buffer.downgrade();
+                cx.default_global::<OpenHexBuffers>()
+                    .0
+                    .insert(abs_path.clone(), weak_buffer);
+                cx.observe_release(&buffer, move |_, cx| {
+                    cx.default_global::<OpenHexBuffers>()
+                        .0
+                        .retain(|_, buffer| buffer.upgrade().is_some());
+                })
+                .detach();
+                buffer
+            })
+        })
+    }
+
+    pub fn abs_path(&self) -> &Arc<Path> {
+        &self.abs_path
+    }
+
+    pub fn len(&self) -> u64 {
+        self.table.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.table.is_empty()
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        self.version != self.saved_version
+    }
+
+    pub fn is_saving(&self) -> bool {
+        self.saving
+    }
+
+    pub fn snapshot(&self) -> HexBufferSnapshot {
+        HexBufferSnapshot {
+            abs_path: self.abs_path.clone(),
+            fs: self.fs.clone(),
+            table: self.table.clone(),
+        }
+    }
+
+    /// Returns the bytes in the given range, or `None` for the bytes whose page is still being
+    /// read from disk, in which case [`HexBufferEvent::PageLoaded`] is emitted once it's read.
+    pub fn read(&mut self, range: Range<u64>, cx: &mut Context<Self>) -> Vec<Option<u8>> {
+        let mut bytes = Vec::with_capacity((range.end - range.start) as usize);
+        let mut missing_pages = Vec::new();
+        for span in self.table.spans(range) {
+            match span {
+                Span::Added(added) => bytes.extend(added.iter().copied().map(Some)),
+                Span::Original(range) => {
+                    let mut offset = range.start;
+                    while offset < range.end {
+                        let page = offset / PAGE_SIZE;
+                        let page_end = ((page + 1) * PAGE_SIZE).min(range.end);
+                        match self.pages.get(&page) {
+                            Some(contents) => {
+                                let start = (offset - page * PAGE_SIZE) as usize;
+                                let end = (page_end - page * PAGE_SIZE) as usize;
+                                let available =
+                                    &contents[start.min(contents.len())..end.min(contents.len())];
+                                bytes.extend(available.iter().copied().map(Some));
+                                // The file may have been truncated since it was opened.
+                                bytes.extend(std::iter::repeat_n(
+                                    None,
+                                    end - start - available.len(),
+                                ));
+                            }
+                            None => {
+                                bytes.extend(std::iter::repeat_n(
+                                    None,
+                                    (page_end - offset) as usize,
+                                ));
+                                missing_pages.push(page);
+                            }
+                        }
+                        offset = page_end;
+                    }
+                }
+            }
+        }
+        for page in missing_pages {
+            self.load_page(page, cx);
+        }
+        bytes
+    }
+
+    fn load_page(&mut self, page: u64, cx: &mut Context<Self>) {
+        if !self.loading_pages.insert(page) {
+            return;
+        }
+        let fs = self.fs.clone();
+        let abs_path = self.abs_path.clone();
+        let generation = self.pages_generation;
+        cx.spawn(async move |this, cx| {
+            let contents = fs
+                .load_bytes_range(&abs_path, page * PAGE_SIZE..(page + 1) * PAGE_SIZE)
+                .await;
+            this.update(cx, |this, cx| {
+                if this.pages_generation != generation {
+                    return Ok(());
+                }
+                this.loading_pages.remove(&page);
+                let contents = contents?;
+                this.pages.insert(page, contents.into());
+                while this.pages.len() > MAX_CACHED_PAGES {
+                    let first = *this.pages.first_key_value().unwrap().0;
+                    let last = *this.pages.last_key_value().unwrap().0;
+                    if page - first > last - page {
+                        this.pages.pop_first();
+                    } else {
+                        this.pages.pop_last();
+                    }
+                }
+                cx.emit(HexBufferEvent::PageLoaded);
+                anyhow::Ok(())
+            })?
+        })
+        .detach_and_log_err(cx);
+    }
+
+    /// Replaces the given range with the given bytes, as a single undoable edit.
+    pub fn edit(&mut self, range: Range<u64>, bytes: &[u8], cx: &mut Context<Self>) {
+        let len = self.len();
+        let range = range.start.min(len)..range.end.min(len);
+        if self.saving || (range.is_empty() && bytes.is_empty()) {
+            return;
+        }
+        self.push_undo_step();
+        self.redo_stack.clear();
+        self.table.edit(range, bytes);
+        self.version = self.next_version;
+        self.next_version += 1;
+        cx.emit(HexBufferEvent::Edited);
+    }
+
+    /// Undoes the last edit, returning whether there was one.
+    pub fn undo(&mut self, cx: &mut Context<Self>) -> bool {
+        if self.saving {
+            return false;
+        }
+        let Some((table, version)) = self.undo_stack.pop() else {
+            return false;
+        };
+        self.redo_stack
+            .push((self.table.checkpoint(), self.version));
+        self.table.restore(&table);
+        self.version = version;
+        cx.emit(HexBufferEvent::Edited);
+        true
+    }
+
+    /// Redoes the last undone edit, returning whether there was one.
+    pub fn redo(&mut self, cx: &mut Context<Self>) -> bool {
+        if self.saving {
+            return false;
+        }
+        let Some((table, version)) = self.redo_stack.pop() else {
+            return false;
+        };
+        self.push_undo_step();
+        self.table.restore(&table);
+        self.version = version;
+        cx.emit(HexBufferEvent::Edited);
+        true
+    }
+
+    fn push_undo_step(&mut self) {
+        if self.undo_stack.len() == MAX_UNDO_STEPS {
+            self.undo_stack.remove(0);
+        }
+        self.undo_stack
+            .push((self.table.checkpoint(), self.version));
+    }
+
+    /// Writes the contents to disk, streaming them into a file next to the edited one which
+    /// then replaces it, so that the pages still to be read aren't overwritten while saving.
+    pub fn save(&mut self, cx: &mut Context<Self>) -> Task<Result<()>> {
+        if self.saving {
+            return Task::ready(Err(anyhow::anyhow!(
+                "{:?} is already being saved",
+                self.abs_path
+            )));
+        }
+        self.saving = true;
+        let snapshot = self.snapshot();
+        let fs = self.fs.clone();
+        let abs_path = self.abs_path.clone();
+        cx.spawn(async move |this, cx| {
+            let temp_path = temp_path_for(&abs_path);
+            let len = snapshot.len();
+            let chunks = stream::unfold(0, move |offset| {
+                let snapshot = snapshot.clone();
+                async move {
+                    if offset >= len {
+                        return None;
+                    }
+                    let end = (offset + CHUNK_SIZE).min(len);
+                    let chunk = snapshot
+                        .read(offset..end)
+                        .await
+                        .map_err(|error| io::Error::other(error.to_string()));
+                    Some((chunk, end))
+                }
+            });
+            let mut reader = chunks.boxed().into_async_read();
+            let result = async {
+                if let Err(error) = fs.create_file_with(&temp_path, Pin::new(&mut reader)).await {
+                    fs.remove_file(&temp_path, Default::default()).await.ok();
+                    return Err(error);
+                }
+                // The new file replaces the original, so it keeps the original's permissions.
+                fs.copy_permissions(&abs_path, &temp_path).await.log_err();
+                fs.rename(
+                    &temp_path,
+                    &abs_path,
+                    RenameOptions {
+                        overwrite: true,
+                        ignore_if_exists: false,
+                    },
+                )
+                .await
+            }
+            .await;
+
+            this.update(cx, |this, cx| {
+                this.saving = false;
+                result?;
+                this.reset(len);
+                this.saved_version = this.version;
+                cx.emit(HexBufferEvent::Saved);
+                anyhow::Ok(())
+            })?
+        })
+    }
+
+    /// Discards the edits and reads the file from disk again.
+    pub fn reload(&mut self, cx: &mut Context<Self>) -> Task<Result<()>> {
+        let fs = self.fs.clone();
+        let abs_path = self.abs_path.clone();
+        cx.spawn(async move |this, cx| {
+            let metadata = fs
+                .metadata(&abs_path)
+                .await?
+                .with_context(|| format!("{abs_path:?} does not exist"))?;
+            this.update(cx, |this, cx| {
+                this.reset(metadata.len);
+                this.version = this.next_version;
+                this.next_version += 1;
+                this.saved_version = this.version;
+                cx.emit(HexBufferEvent::Reloaded);
+            })
+        })
+    }
+
+    /// Makes the file on disk, of the given length, the original contents of the buffer.
+    fn reset(&mut self, len: u64) {
+        self.table = PieceTable::new(len);
+        self.pages.clear();
+        self.loading_pages.clear();
+        self.pages_generation += 1;
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+    }
+
+    /// Searches the contents for the given pattern, starting at the given offset and wrapping
+    /// around at the end, returning the offset of the first match.
+    pub fn find(&self, pattern: Vec<u8>, from: u64, cx: &App) -> Task<Result<Option<u64>>> {
+        let snapshot = self.snapshot();
+        cx.background_spawn(async move {
+            if pattern.is_empty() {
+                return Ok(None);
+            }
+            let len = snapshot.len();
+            let from = from.min(len);
+            if let Some(offset) = snapshot.find_in_range(&pattern, from..len).await? {
+                return Ok(Some(offset));
+            }
+            snapshot.find_in_range(&pattern, 0..from).await
+        })
+    }
+}
+
+impl HexBufferSnapshot {
+    pub fn len(&self) -> u64 {
+        self.table.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.table.is_empty()
+    }
+
+    /// Reads the given range of the contents.
+    pub async fn read(&self, range: Range<u64>) -> Result<Vec<u8>> {
+        let mut bytes = Vec::with_capacity((range.end - range.start) as usize);
+        for span in self.table.spans(range) {
+            match span {
+                Span::Added(added) => bytes.extend_from_slice(added),
+                Span::Original(range) => {
+                    let original = self
+                        .fs
+                        .load_bytes_range(&self.abs_path, range.clone())
+                        .await?;
+                    anyhow::ensure!(
+                        original.len() as u64 == range.end - range.start,
+                        "{:?} was truncated while being edited",
+                        self.abs_path
+                    );
+                    bytes.extend_from_slice(&original);
+                }
+            }
+        }
+        Ok(bytes)
+    }
+
+    /// Returns the offset of the first match of the pattern starting in the given range.
+    async fn find_in_range(&self, pattern: &[u8], range: Range<u64>) -> Result<Option<u64>> {
+        let overlap = pattern.len() as u64 - 1;
+        let mut offset = range.start;
+        while offset < range.end {
+            let chunk_end = (offset + CHUNK_SIZE).min(range.end);
+            let read_end = (chunk_end + overlap).min(self.len());
+            let chunk = self.read(offset..read_end).await?;
+            if let Some(ix) = chunk
+                .windows(pattern.len())
+                .position(|window| window == pattern)
+                .filter(|ix| offset + (*ix as u64) < chunk_end)
+            {
+                return Ok(Some(offset + ix as u64));
+            }
+            offset = chunk_end;
+        }
+        Ok(None)
+    }
+}
+
+fn temp_path_for(abs_path: &Path) -> PathBuf {
+    let mut file_name = abs_path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(".zed-hex-save");
+    abs_path.with_file_name(file_name)
+}
+
+impl project::ProjectItem for HexBuffer {
+    fn try_open(
+        _project: &Entity<Project>,
+        _path: &ProjectPath,
+        _cx: &mut App,
+    ) -> Option<Task<Result<Entity<Self>>>> {
+        // Files are only opened in the hex editor on request, never by path.
+        None
+    }
+
+    fn entry_id(&self, _: &App) -> Option<ProjectEntryId> {
+        self.entry_id
+    }
+
+    fn project_path(&self, _: &App) -> Option<ProjectPath> {
+        Some(self.project_path.clone())
+    }
+
+    fn is_dirty(&self) -> bool {
+        HexBuffer::is_dirty(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fs::FakeFs;
+    use gpui::TestAppContext;
+    use settings::SettingsStore;
+    use util::path;
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            language::init(cx);
+            Project::init_settings(cx);
+        });
+    }
+
+    #[gpui::test]
+    async fn test_edit_and_save(cx: &mut TestAppContext) {
+        init_test(cx);
+        let original = (0..3 * PAGE_SIZE).map(|ix| ix as u8).collect::<Vec<_>>();
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(path!("/root"), serde_json::json!({})).await;
+        fs.insert_file(path!("/root/data.bin"), original.clone())
+            .await;
+        let project = Project::test(fs.clone(), [path!("/root").as_ref()], cx).await;
+        let project_path = project.read_with(cx, |project, cx| {
+            project
+                .find_project_path(path!("/root/data.bin"), cx)
+                .unwrap()
+        });
+        let buffer = cx
+            .update(|cx| HexBuffer::open(&project, project_path.clone(), cx))
+            .await
+            .unwrap();
+        let same_buffer = cx
+            .update(|cx| HexBuffer::open(&project, project_path, cx))
+            .await
+            .unwrap();
+        assert_eq!(buffer.entity_id(), same_buffer.entity_id());
+
+        let offset = PAGE_SIZE - 2;
+        let bytes = buffer.update(cx, |buffer, cx| buffer.read(offset..offset + 4, cx));
+        assert_eq!(bytes, [None; 4]);
+        cx.run_until_parked();
+        let bytes = buffer.update(cx, |buffer, cx| buffer.read(offset..offset + 4, cx));
+        assert_eq!(bytes, [Some(0xFE), Some(0xFF), Some(0x00), Some(0x01)]);
+
+        buffer.update(cx, |buffer, cx| {
+            buffer.edit(offset..offset + 2, b"xyz", cx);
+            buffer.edit(0..1, b"", cx);
+            assert!(buffer.is_dirty());
+            assert_eq!(buffer.len(), 3 * PAGE_SIZE);
+            assert_eq!(
+                buffer.read(offset - 1..offset + 3, cx),
+                [Some(b'x'), Some(b'y'), Some(b'z'), Some(0x00)]
+            );
+        });
+
+        let found = buffer
+            .read_with(cx, |buffer, cx| buffer.find(b"yz\x00\x01".to_vec(), 0, cx))
+            .await
+            .unwrap();
+        assert_eq!(found, Some(offset));
+        let found = buffer
+            .read_with(cx, |buffer, cx| buffer.find(b"xyz".to_vec(), offset, cx))
+            .await
+            .unwrap();
+        assert_eq!(found, Some(offset - 1), "search wraps around");
+
+        buffer.update(cx, |buffer, cx| {
+            assert!(buffer.undo(cx));
+            assert!(buffer.undo(cx));
+            assert!(!buffer.is_dirty());
+            assert!(buffer.redo(cx));
+            assert!(buffer.redo(cx));
+        });
+        buffer
+            .update(cx, |buffer, cx| buffer.save(cx))
+            .await
+            .unwrap();
+        let mut expected = original;
+        expected.splice(offset as usize..offset as usize + 2, *b"xyz");
+        expected.remove(0);
+        assert_eq!(
+            fs.load_bytes(path!("/root/data.bin").as_ref())
+                .await
+                .unwrap(),
+            expected
+        );
+        buffer.read_with(cx, |buffer, _| {
+            assert!(!buffer.is_dirty());
+            assert_eq!(buffer.len(), expected.len() as u64);
+        });
+    }
+
+    #[gpui::test]
+    async fn test_pages_loaded_before_reload_are_discarded(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(path!("/root"), serde_json::json!({})).await;
+        fs.insert_file(path!("/root/data.bin"), b"abc".to_vec())
+            .await;
+        let project = Project::test(fs.clone(), [path!("/root").as_ref()], cx).await;
+        let project_path = project.read_with(cx, |project, cx| {
+            project
+                .find_project_path(path!("/root/data.bin"), cx)
+                .unwrap()
+        });
+        let buffer = cx
+            .update(|cx| HexBuffer::open(&project, project_path, cx))
+            .await
+            .unwrap();
+
+        // The file is read anew while its first page is being loaded.
+        buffer.update(cx, |buffer, cx| {
+            assert_eq!(buffer.read(0..3, cx), [None; 3]);
+            buffer.reset(3);
+        });
+        cx.run_until_parked();
+        buffer.read_with(cx, |buffer, _| {
+            assert!(
+                buffer.pages.is_empty(),
+                "Pages loaded before the file was read anew should be discarded"
+            );
+        });
+    }
+}
@@ -0,0 +1,1071 @@
+mod hex_buffer;
+mod piece_table;
+
+use std::path::Path;
+
+use anyhow::Context as _;
+use editor::{Editor, EditorSettings, items::entry_git_aware_label_color};
+use file_icons::FileIcons;
+use gpui::{
+    AnyElement, App, Context, Entity, EventEmitter, FocusHandle, Focusable, InteractiveElement,
+    IntoElement, KeyDownEvent, MouseButton, ParentElement, Render, ScrollWheelEvent, SharedString,
+    Styled, Subscription, Task, WeakEntity, Window, actions, canvas, div,
+};
+use persistence::HEX_EDITOR;
+use project::{Project, ProjectPath};
+use settings::Settings;
+use theme::Theme;
+use ui::prelude::*;
+use util::paths::PathExt;
+use workspace::{
+    ItemId, ItemSettings, Pane, ToolbarItemLocation, Workspace, WorkspaceId, delete_unloaded_items,
+    item::{
+        BreadcrumbText, Item, ItemEvent, ProjectItem, SaveOptions, SerializableItem,
+        TabContentParams,
+    },
+};
+use zed_actions::hex_editor::OpenFile;
+
+pub use crate::hex_buffer::*;
+
+actions!(
+    hex_editor,
+    [
+        /// Moves the cursor to the previous byte.
+        MoveLeft,
+        /// Moves the cursor to the next byte.
+        MoveRight,
+        /// Moves the cursor to the previous row.
+        MoveUp,
+        /// Moves the cursor to the next row.
+        MoveDown,
+        /// Moves the cursor up by a page.
+        PageUp,
+        /// Moves the cursor down by a page.
+        PageDown,
+        /// Moves the cursor to the first byte of its row.
+        MoveToStartOfRow,
+        /// Moves the cursor to the last byte of its row.
+        MoveToEndOfRow,
+        /// Moves the cursor to the start of the file.
+        MoveToStart,
+        /// Moves the cursor to the end of the file.
+        MoveToEnd,
+        /// Deletes the byte before the cursor in insert mode, or moves back in overwrite mode.
+        Backspace,
+        /// Deletes the byte at the cursor.
+        Delete,
+        /// Toggles between inserting and overwriting bytes.
+        ToggleInsertMode,
+        /// Switches typing between the hex and ASCII columns.
+        SwitchColumn,
+        /// Shows the bar to search for a byte pattern.
+        Find,
+        /// Moves to the next match of the last searched byte pattern.
+        FindNext,
+        /// Undoes the last edit.
+        Undo,
+        /// Redoes the last undone edit.
+        Redo,
+    ]
+);
+
+const BYTES_PER_ROW: u64 = 16;
+
+static HEX_BYTES: std::sync::LazyLock<[SharedString; 256]> = std::sync::LazyLock::new(|| {
+    std::array::from_fn(|byte| SharedString::from(format!("{byte:02X}")))
+});
+static UNKNOWN_BYTE: SharedString = SharedString::new_static("??");
+
+/// The column the bytes typed in the hex editor are written to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Column {
+    Hex,
+    Ascii,
+}
+
+/// Whether typing in the hex editor inserts bytes or overwrites them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EditMode {
+    Overwrite,
+    Insert,
+}
+
+/// An editor showing the bytes of a file as offset, hex and ASCII columns.
+pub struct HexEditor {
+    buffer: Entity<HexBuffer>,
+    project: Entity<Project>,
+    focus_handle: FocusHandle,
+    cursor: u64,
+    /// The high nibble typed in the hex column for the byte at the cursor.
+    pending_nibble: Option<u8>,
+    column: Column,
+    mode: EditMode,
+    /// The row shown at the top of the viewport, kept as an integer so that files of any size
+    /// can be scrolled through precisely.
+    scroll_row: u64,
+    scroll_remainder: Pixels,
+    visible_rows: u64,
+    find_editor: Entity<Editor>,
+    find_bar_visible: bool,
+    last_pattern: Option<Vec<u8>>,
+    last_match: Option<u64>,
+    find_status: Option<SharedString>,
+    pending_find: Task<()>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl HexEditor {
+    pub fn new(
+        buffer: Entity<HexBuffer>,
+        project: Entity<Project>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let find_editor = cx.new(|cx| {
+            let mut editor = Editor::single_line(window, cx);
+            editor.set_placeholder_text(
+                "Hex bytes, like 7F 45 4C 46, or \"quoted text\"",
+                window,
+                cx,
+            );
+            editor
+        });
+        let subscriptions = vec![cx.subscribe(&buffer, Self::on_buffer_event)];
+        Self {
+            buffer,
+            project,
+            focus_handle: cx.focus_handle(),
+            cursor: 0,
+            pending_nibble: None,
+            column: Column::Hex,
+            mode: EditMode::Overwrite,
+            scroll_row: 0,
+            scroll_remainder: Pixels::ZERO,
+            visible_rows: 0,
+            find_editor,
+            find_bar_visible: false,
+            last_pattern: None,
+            last_match: None,
+            find_status: None,
+            pending_find: Task::ready(()),
+            _subscriptions: subscriptions,
+        }
+    }
+
+    pub fn buffer(&self) -> &Entity<HexBuffer> {
+        &self.buffer
+    }
+
+    pub fn cursor(&self) -> u64 {
+        self.cursor
+    }
+
+    fn on_buffer_event(
+        &mut self,
+        _: Entity<HexBuffer>,
+        event: &HexBufferEvent,
+        cx: &mut Context<Self>,
+    ) {
+        match event {
+            HexBufferEvent::PageLoaded => {}
+            HexBufferEvent::Edited | HexBufferEvent::Saved | HexBufferEvent::Reloaded => {
+                self.cursor = self.cursor.min(self.buffer.read(cx).len());
+                self.last_match = None;
+                cx.emit(ItemEvent::UpdateTab);
+            }
+        }
+        cx.notify();
+    }
+
+    fn total_rows(&self, cx: &App) -> u64 {
+        // The row after the last byte is kept visible, for appending bytes.
+        self.buffer.read(cx).len() / BYTES_PER_ROW + 1
+    }
+
+    fn move_cursor_to(&mut self, offset: u64, cx: &mut Context<Self>) {
+        self.cursor = offset.min(self.buffer.read(cx).len());
+        self.pending_nibble = None;
+        self.autoscroll(cx);
+        cx.notify();
+    }
+
+    fn autoscroll(&mut self, cx: &App) {
+        let row = self.cursor / BYTES_PER_ROW;
+        let visible_rows = self.visible_rows.max(1);
+        if row < self.scroll_row {
+            self.scroll_row = row;
+        } else if row >= self.scroll_row + visible_rows {
+            self.scroll_row = row + 1 - visible_rows;
+        }
+        self.scroll_row = self.scroll_row.min(self.max_scroll_row(cx));
+    }
+
+    fn max_scroll_row(&self, cx: &App) -> u64 {
+        self.total_rows(cx).saturating_sub(self.visible_rows.max(1))
+    }
+
+    fn move_left(&mut self, _: &MoveLeft, _: &mut Window, cx: &mut Context<Self>) {
+        self.move_cursor_to(self.cursor.saturating_sub(1), cx);
+    }
+
+    fn move_right(&mut self, _: &MoveRight, _: &mut Window, cx: &mut Context<Self>) {
+        self.move_cursor_to(self.cursor + 1, cx);
+    }
+
+    fn move_up(&mut self, _: &MoveUp, _: &mut Window, cx: &mut Context<Self>) {
+        if self.cursor >= BYTES_PER_ROW {
+            self.move_cursor_to(self.cursor - BYTES_PER_ROW, cx);
+        }
+    }
+
+    fn move_down(&mut self, _: &MoveDown, _: &mut Window, cx: &mut Context<Self>) {
+        self.move_cursor_to(self.cursor + BYTES_PER_ROW, cx);
+    }
+
+    fn page_up(&mut self, _: &PageUp, _: &mut Window, cx: &mut Context<Self>) {
+        let page = self.visible_rows.max(1) * BYTES_PER_ROW;
+        self.scroll_row = self.scroll_row.saturating_sub(self.visible_rows.max(1));
+        self.move_cursor_to(self.cursor.saturating_sub(page), cx);
+    }
+
+    fn page_down(&mut self, _: &PageDown, _: &mut Window, cx: &mut Context<Self>) {
+        let page = self.visible_rows.max(1) * BYTES_PER_ROW;
+        self.scroll_row = (self.scroll_row + self.visible_rows.max(1)).min(self.max_scroll_row(cx));
+        self.move_cursor_to(self.cursor + page, cx);
+    }
+
+    fn move_to_start_of_row(
+        &mut self,
+        _: &MoveToStartOfRow,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.move_cursor_to(self.cursor - self.cursor % BYTES_PER_ROW, cx);
+    }
+
+    fn move_to_end_of_row(&mut self, _: &MoveToEndOfRow, _: &mut Window, cx: &mut Context<Self>) {
+        self.move_cursor_to(
+            self.cursor - self.cursor % BYTES_PER_ROW + BYTES_PER_ROW - 1,
+            cx,
+        );
+    }
+
+    fn move_to_start(&mut self, _: &MoveToStart, _: &mut Window, cx: &mut Context<Self>) {
+        self.move_cursor_to(0, cx);
+    }
+
+    fn move_to_end(&mut self, _: &MoveToEnd, _: &mut Window, cx: &mut Context<Self>) {
+        self.move_cursor_to(u64::MAX, cx);
+    }
+
+    fn backspace(&mut self, _: &Backspace, _: &mut Window, cx: &mut Context<Self>) {
+        if self.cursor == 0 {
+            return;
+        }
+        if self.mode == EditMode::Insert {
+            let cursor = self.cursor;
+            self.buffer
+                .update(cx, |buffer, cx| buffer.edit(cursor - 1..cursor, &[], cx));
+        }
+        self.move_cursor_to(self.cursor - 1, cx);
+    }
+
+    fn delete(&mut self, _: &Delete, _: &mut Window, cx: &mut Context<Self>) {
+        let cursor = self.cursor;
+        self.pending_nibble = None;
+        self.buffer
+            .update(cx, |buffer, cx| buffer.edit(cursor..cursor + 1, &[], cx));
+    }
+
+    fn toggle_insert_mode(&mut self, _: &ToggleInsertMode, _: &mut Window, cx: &mut Context<Self>) {
+        self.mode = match self.mode {
+            EditMode::Overwrite => EditMode::Insert,
+            EditMode::Insert => EditMode::Overwrite,
+        };
+        self.pending_nibble = None;
+        cx.notify();
+    }
+
+    fn switch_column(&mut self, _: &SwitchColumn, _: &mut Window, cx: &mut Context<Self>) {
+        self.column = match self.column {
+            Column::Hex => Column::Ascii,
+            Column::Ascii => Column::Hex,
+        };
+        self.pending_nibble = None;
+        cx.notify();
+    }
+
+    fn undo(&mut self, _: &Undo, _: &mut Window, cx: &mut Context<Self>) {
+        self.pending_nibble = None;
+        self.buffer.update(cx, |buffer, cx| buffer.undo(cx));
+    }
+
+    fn redo(&mut self, _: &Redo, _: &mut Window, cx: &mut Context<Self>) {
+        self.pending_nibble = None;
+        self.buffer.update(cx, |buffer, cx| buffer.redo(cx));
+    }
+
+    fn key_down(&mut self, event: &KeyDownEvent, window: &mut Window, cx: &mut Context<Self>) {
+        // Keys typed in the find bar are for its editor.
+        if !self.focus_handle.is_focused(window) {
+            return;
+        }
+        let modifiers = &event.keystroke.modifiers;
+        if modifiers.control || modifiers.platform || modifiers.function {
+            return;
+        }
+        let mut chars = event
+            .keystroke
+            .key_char
+            .as_deref()
+            .unwrap_or_default()
+            .chars();
+        let (Some(character), None) = (chars.next(), chars.next()) else {
+            return;
+        };
+        let handled = match self.column {
+            Column::Hex => character
+                .to_digit(16)
+                .is_some_and(|digit| self.type_nibble(digit as u8, cx)),
+            Column::Ascii => {
+                (character.is_ascii_graphic() || character == ' ')
+                    && self.type_byte(character as u8, cx)
+            }
+        };
+        if handled {
+            cx.stop_propagation();
+        }
+    }
+
+    /// Writes a hex digit at the cursor, the first one typed for a byte being its high nibble.
+    fn type_nibble(&mut self, digit: u8, cx: &mut Context<Self>) -> bool {
+        if self.buffer.read(cx).is_saving() {
+            return false;
+        }
+        let cursor = self.cursor;
+        if let Some(high) = self.pending_nibble.take() {
+            self.buffer.update(cx, |buffer, cx| {
+                buffer.edit(cursor..cursor + 1, &[(high << 4) | digit], cx)
+            });
+            self.move_cursor_to(cursor + 1, cx);
+        } else {
+            let mode = self.mode;
+            self.buffer.update(cx, |buffer, cx| match mode {
+                EditMode::Insert => buffer.edit(cursor..cursor, &[digit << 4], cx),
+                EditMode::Overwrite => {
+                    let low = buffer
+                        .read(cursor..cursor + 1, cx)
+                        .first()
+                        .copied()
+                        .flatten()
+                        .unwrap_or(0)
+                        & 0x0F;
+                    buffer.edit(cursor..cursor + 1, &[(digit << 4) | low], cx)
+                }
+            });
+            self.pending_nibble = Some(digit);
+        }
+        true
+    }
+
+    fn type_byte(&mut self, byte: u8, cx: &mut Context<Self>) -> bool {
+        if self.buffer.read(cx).is_saving() {
+            return false;
+        }
+        let cursor = self.cursor;
+        let range = match self.mode {
+            EditMode::Insert => cursor..cursor,
+            EditMode::Overwrite => cursor..cursor + 1,
+        };
+        self.buffer
+            .update(cx, |buffer, cx| buffer.edit(range, &[byte], cx));
+        self.move_cursor_to(cursor + 1, cx);
+        true
+    }
+
+    fn scroll(&mut self, event: &ScrollWheelEvent, window: &mut Window, cx: &mut Context<Self>) {
+        let row_height = window.line_height();
+        self.scroll_remainder -= event.delta.pixel_delta(row_height).y;
+        let rows = (self.scroll_remainder / row_height).trunc();
+        if rows == 0. {
+            return;
+        }
+        self.scroll_remainder -= row_height * rows;
+        self.scroll_row = if rows < 0. {
+            self.scroll_row.saturating_sub(-rows as u64)
+        } else {
+            (self.scroll_row + rows as u64).min(self.max_scroll_row(cx))
+        };
+        cx.notify();
+    }
+
+    fn set_visible_rows(&mut self, visible_rows: u64, cx: &mut Context<Self>) {
+        if self.visible_rows != visible_rows {
+            self.visible_rows = visible_rows;
+            self.scroll_row = self.scroll_row.min(self.max_scroll_row(cx));
+            cx.notify();
+        }
+    }
+
+    fn find(&mut self, _: &Find, window: &mut Window, cx: &mut Context<Self>) {
+        self.find_bar_visible = true;
+        self.find_editor.update(cx, |editor, cx| {
+            editor.select_all(&Default::default(), window, cx)
+        });
+        window.focus(&self.find_editor.focus_handle(cx));
+        cx.notify();
+    }
+
+    fn find_next(&mut self, _: &FindNext, _: &mut Window, cx: &mut Context<Self>) {
+        if let Some(pattern) = self.last_pattern.clone() {
+            self.search(pattern, self.cursor + 1, cx);
+        }
+    }
+
+    fn confirm_find(&mut self, _: &menu::Confirm, _: &mut Window, cx: &mut Context<Self>) {
+        match parse_pattern(&self.find_editor.read(cx).text(cx)) {
+            Ok(pattern) => {
+                let from = if self.last_pattern.as_ref() == Some(&pattern)
+                    && self.last_match == Some(self.cursor)
+                {
+                    self.cursor + 1
+                } else {
+                    self.cursor
+                };
+                self.last_pattern = Some(pattern.clone());
+                self.search(pattern, from, cx);
+            }
+            Err(error) => {
+                self.find_status = Some(error);
+                cx.notify();
+            }
+        }
+    }
+
+    fn cancel_find(&mut self, _: &menu::Cancel, window: &mut Window, cx: &mut Context<Self>) {
+        self.find_bar_visible = false;
+        self.find_status = None;
+        window.focus(&self.focus_handle);
+        cx.notify();
+    }
+
+    fn search(&mut self, pattern: Vec<u8>, from: u64, cx: &mut Context<Self>) {
+        let find = self.buffer.read(cx).find(pattern, from, cx);
+        self.find_status = Some("Searching…".into());
+        self.pending_find = cx.spawn(async move |this, cx| {
+            let result = find.await;
+            this.update(cx, |this, cx| {
+                match result {
+                    Ok(Some(offset)) => {
+                        this.find_status = None;
+                        this.move_cursor_to(offset, cx);
+                        this.last_match = Some(offset);
+                    }
+                    Ok(None) => this.find_status = Some("No matches".into()),
+                    Err(error) => this.find_status = Some(format!("{error:#}").into()),
+                }
+                cx.notify();
+            })
+            .ok();
+        });
+        cx.notify();
+    }
+
+    fn click(&mut self, offset: u64, column: Column, window: &mut Window, cx: &mut Context<Self>) {
+        self.column = column;
+        self.move_cursor_to(offset, cx);
+        window.focus(&self.focus_handle);
+    }
+
+    fn render_rows(&mut self, window: &mut Window, cx: &mut Context<Self>) -> Vec<AnyElement> {
+        let row_height = window.line_height();
+        let len = self.buffer.read(cx).len();
+        let first_row = self.scroll_row.min(self.max_scroll_row(cx));
+        let last_row = (first_row + self.visible_rows.max(1)).min(self.total_rows(cx));
+        let start = first_row * BYTES_PER_ROW;
+        let end = (last_row * BYTES_PER_ROW).min(len);
+        let bytes = self
+            .buffer
+            .update(cx, |buffer, cx| buffer.read(start..end, cx));
+        let is_focused = self.focus_handle.is_focused(window);
+        let colors = cx.theme().colors();
+        let cursor_color = cx.theme().players().local().cursor;
+        let selection_color = cx.theme().players().local().selection;
+        let border_color = colors.border_variant;
+
+        (first_row..last_row)
+            .map(|row| {
+                let row_start = row * BYTES_PER_ROW;
+                let cell_highlight = |offset: u64, column: Column| {
+                    (offset == self.cursor).then(|| {
+                        if column == self.column && is_focused {
+                            cursor_color.opacity(0.4)
+                        } else {
+                            selection_color
+                        }
+                    })
+                };
+                // Cells past the end are blank, except for the cursor sitting after the last
+                // byte, so that the columns stay aligned on the last row.
+                let offsets = row_start..row_start + BYTES_PER_ROW;
+                let byte_at = |offset: u64| bytes.get((offset - start) as usize).copied().flatten();
+
+                h_flex()
+                    .h(row_height)
+                    .gap_x_2()
+                    .child(
+                        div().px_1().border_r_1().border_color(border_color).child(
+                            Label::new(format!("{row_start:08X}"))
+                                .buffer_font(cx)
+                                .size(LabelSize::Small)
+                                .color(Color::Muted),
+                        ),
+                    )
+                    .child(h_flex().px_1().children(offsets.clone().map(|offset| {
+                        let text = if offset >= len {
+                            SharedString::new_static("  ")
+                        } else {
+                            byte_at(offset)
+                                .map(|byte| HEX_BYTES[byte as usize].clone())
+                                .unwrap_or_else(|| UNKNOWN_BYTE.clone())
+                        };
+                        div()
+                            .px_0p5()
+                            .when(offset % 8 == 0 && offset % BYTES_PER_ROW != 0, |cell| {
+                                cell.ml_1()
+                            })
+                            .when_some(cell_highlight(offset, Column::Hex), |cell, color| {
+                                cell.bg(color)
+                            })
+                            .when(offset <= len, |cell| {
+                                cell.on_mouse_down(
+                                    MouseButton::Left,
+                                    cx.listener(move |this, _, window, cx| {
+                                        this.click(offset, Column::Hex, window, cx)
+                                    }),
+                                )
+                            })
+                            .child(
+                                Label::new(text)
+                                    .buffer_font(cx)
+                                    .size(LabelSize::Small)
+                                    .when(offset < len && byte_at(offset).is_none(), |label| {
+                                        label.color(Color::Muted)
+                                    }),
+                            )
+                    })))
+                    .child(
+                        h_flex()
+                            .px_1()
+                            .border_l_1()
+                            .border_color(border_color)
+                            .children(offsets.map(|offset| {
+                                let character = match byte_at(offset) {
+                                    Some(byte) if byte.is_ascii_graphic() || byte == b' ' => {
+                                        char::from(byte)
+                                    }
+                                    Some(_) => '·',
+                                    None => ' ',
+                                };
+                                div()
+                                    .when_some(
+                                        cell_highlight(offset, Column::Ascii),
+                                        |cell, color| cell.bg(color),
+                                    )
+                                    .when(offset <= len, |cell| {
+                                        cell.on_mouse_down(
+                                            MouseButton::Left,
+                                            cx.listener(move |this, _, window, cx| {
+                                                this.click(offset, Column::Ascii, window, cx)
+                                            }),
+                                        )
+                                    })
+                                    .child(
+                                        Label::new(character.to_string())
+                                            .buffer_font(cx)
+                                            .size(LabelSize::Small),
+                                    )
+                            })),
+                    )
+                    .into_any_element()
+            })
+            .collect()
+    }
+
+    fn render_find_bar(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        h_flex()
+            .key_context("HexEditorFindBar")
+            .on_action(cx.listener(Self::confirm_find))
+            .on_action(cx.listener(Self::cancel_find))
+            .gap_2()
+            .px_2()
+            .py_1()
+            .border_b_1()
+            .border_color(cx.theme().colors().border_variant)
+            .child(Icon::new(IconName::MagnifyingGlass).color(Color::Muted))
+            .child(div().flex_1().child(self.find_editor.clone()))
+    }
+
+    fn render_status(&self, cx: &App) -> impl IntoElement {
+        let buffer = self.buffer.read(cx);
+        let mode = match self.mode {
+            EditMode::Overwrite => "Overwrite",
+            EditMode::Insert => "Insert",
+        };
+        h_flex()
+            .gap_3()
+            .px_2()
+            .py_0p5()
+            .border_t_1()
+            .border_color(cx.theme().colors().border_variant)
+            .child(
+                Label::new(format!("0x{:X} / 0x{:X}", self.cursor, buffer.len()))
+                    .buffer_font(cx)
+                    .size(LabelSize::Small)
+                    .color(Color::Muted),
+            )
+            .child(Label::new(mode).size(LabelSize::Small).color(Color::Muted))
+            .when_some(self.find_status.clone(), |this, status| {
+                this.child(
+                    Label::new(status)
+                        .size(LabelSize::Small)
+                        .color(Color::Muted),
+                )
+            })
+    }
+}
+
+/// Parses a search query as whitespace-separated hex bytes, or as text when quoted.
+fn parse_pattern(query: &str) -> Result<Vec<u8>, SharedString> {
+    let query = query.trim();
+    if let Some(text) = query.strip_prefix('"') {
+        let text = text.strip_suffix('"').unwrap_or(text);
+        if text.is_empty() {
+            return Err("Enter the text to search for".into());
+        }
+        return Ok(text.as_bytes().to_vec());
+    }
+    let digits = query
+        .chars()
+        .filter(|char| !char.is_whitespace())
+        .collect::<Vec<_>>();
+    if digits.is_empty()
+        || digits.len() % 2 != 0
+        || !digits.iter().all(|char| char.is_ascii_hexdigit())
+    {
+        return Err("Expected hex bytes, like 7F 45 4C 46, or quoted text".into());
+    }
+    Ok(digits
+        .chunks(2)
+        .map(|pair| ((pair[0].to_digit(16).unwrap() << 4) | pair[1].to_digit(16).unwrap()) as u8)
+        .collect())
+}
+
+impl EventEmitter<ItemEvent> for HexEditor {}
+
+impl Item for HexEditor {
+    type Event = ItemEvent;
+
+    fn to_item_events(event: &Self::Event, mut f: impl FnMut(ItemEvent)) {
+        f(*event)
+    }
+
+    fn for_each_project_item(
+        &self,
+        cx: &App,
+        f: &mut dyn FnMut(gpui::EntityId, &dyn project::ProjectItem),
+    ) {
+        f(self.buffer.entity_id(), self.buffer.read(cx))
+    }
+
+    fn is_singleton(&self, _cx: &App) -> bool {
+        true
+    }
+
+    fn tab_tooltip_text(&self, cx: &App) -> Option<SharedString> {
+        let abs_path = self.buffer.read(cx).abs_path();
+        Some(abs_path.compact().to_string_lossy().into_owned().into())
+    }
+
+    fn tab_content(&self, params: TabContentParams, _window: &Window, cx: &App) -> AnyElement {
+        let project_path = project::ProjectItem::project_path(self.buffer.read(cx), cx);
+
+        let label_color = match project_path {
+            Some(project_path) if ItemSettings::get_global(cx).git_status => {
+                let git_status = self
+                    .project
+                    .read(cx)
+                    .project_path_git_status(&project_path, cx)
+                    .map(|status| status.summary())
+                    .unwrap_or_default();
+
+                self.project
+                    .read(cx)
+                    .entry_for_path(&project_path, cx)
+                    .map(|entry| {
+                        entry_git_aware_label_color(git_status, entry.is_ignored, params.selected)
+                    })
+                    .unwrap_or_else(|| params.text_color())
+            }
+            _ => params.text_color(),
+        };
+
+        Label::new(self.tab_content_text(params.detail.unwrap_or_default(), cx))
+            .single_line()
+            .color(label_color)
+            .when(params.preview, |this| this.italic())
+            .into_any_element()
+    }
+
+    fn tab_content_text(&self, _: usize, cx: &App) -> SharedString {
+        self.buffer
+            .read(cx)
+            .abs_path()
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .into_owned()
+            .into()
+    }
+
+    fn tab_icon(&self, _: &Window, cx: &App) -> Option<Icon> {
+        let path = self.buffer.read(cx).abs_path();
+        ItemSettings::get_global(cx)
+            .file_icons
+            .then(|| FileIcons::get_icon(path, cx))
+            .flatten()
+            .map(Icon::from_path)
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        Some("Hex Editor Opened")
+    }
+
+    fn breadcrumb_location(&self, cx: &App) -> ToolbarItemLocation {
+        let show_breadcrumb = EditorSettings::get_global(cx).toolbar.breadcrumbs;
+        if show_breadcrumb {
+            ToolbarItemLocation::PrimaryLeft
+        } else {
+            ToolbarItemLocation::Hidden
+        }
+    }
+
+    fn breadcrumbs(&self, _theme: &Theme, cx: &App) -> Option<Vec<BreadcrumbText>> {
+        let text = self
+            .buffer
+            .read(cx)
+            .abs_path()
+            .compact()
+            .to_string_lossy()
+            .into_owned();
+        Some(vec![BreadcrumbText {
+            text,
+            highlights: None,
+            font: None,
+        }])
+    }
+
+    fn clone_on_split(
+        &self,
+        _workspace_id: Option<WorkspaceId>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Option<Entity<Self>>
+    where
+        Self: Sized,
+    {
+        let buffer = self.buffer.clone();
+        let project = self.project.clone();
+        Some(cx.new(|cx| Self::new(buffer, project, window, cx)))
+    }
+
+    fn is_dirty(&self, cx: &App) -> bool {
+        self.buffer.read(cx).is_dirty()
+    }
+
+    fn can_save(&self, _: &App) -> bool {
+        true
+    }
+
+    fn save(
+        &mut self,
+        _options: SaveOptions,
+        _project: Entity<Project>,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Task<anyhow::Result<()>> {
+        self.pending_nibble = None;
+        self.buffer.update(cx, |buffer, cx| buffer.save(cx))
+    }
+
+    fn reload(
+        &mut self,
+        _project: Entity<Project>,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Task<anyhow::Result<()>> {
+        self.pending_nibble = None;
+        self.buffer.update(cx, |buffer, cx| buffer.reload(cx))
+    }
+}
+
+impl SerializableItem for HexEditor {
+    fn serialized_item_kind() -> &'static str {
+        "HexEditor"
+    }
+
+    fn deserialize(
+        project: Entity<Project>,
+        _workspace: WeakEntity<Workspace>,
+        workspace_id: WorkspaceId,
+        item_id: ItemId,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Task<anyhow::Result<Entity<Self>>> {
+        window.spawn(cx, async move |cx| {
+            let abs_path = HEX_EDITOR
+                .get_path(item_id, workspace_id)?
+                .context("No path found")?;
+            let project_path = find_or_create_project_path(&project, &abs_path, cx).await?;
+            let buffer = cx
+                .update(|_, cx| HexBuffer::open(&project, project_path, cx))?
+                .await?;
+            cx.update(|window, cx| Ok(cx.new(|cx| HexEditor::new(buffer, project, window, cx))))?
+        })
+    }
+
+    fn cleanup(
+        workspace_id: WorkspaceId,
+        alive_items: Vec<ItemId>,
+        _window: &mut Window,
+        cx: &mut App,
+    ) -> Task<anyhow::Result<()>> {
+        delete_unloaded_items(alive_items, workspace_id, "hex_editors", &HEX_EDITOR, cx)
+    }
+
+    fn serialize(
+        &mut self,
+        workspace: &mut Workspace,
+        item_id: ItemId,
+        _closing: bool,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Option<Task<anyhow::Result<()>>> {
+        let workspace_id = workspace.database_id()?;
+        let abs_path = self.buffer.read(cx).abs_path().to_path_buf();
+        Some(cx.background_spawn(async move {
+            log::debug!("Saving hex editor at path {abs_path:?}");
+            HEX_EDITOR.save_path(item_id, workspace_id, abs_path).await
+        }))
+    }
+
+    fn should_serialize(&self, _event: &Self::Event) -> bool {
+        false
+    }
+}
+
+impl Focusable for HexEditor {
+    fn focus_handle(&self, _cx: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Render for HexEditor {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let row_height = window.line_height();
+        let rows = self.render_rows(window, cx);
+        let this = cx.entity();
+        v_flex()
+            .key_context("HexEditor")
+            .track_focus(&self.focus_handle)
+            .on_action(cx.listener(Self::move_left))
+            .on_action(cx.listener(Self::move_right))
+            .on_action(cx.listener(Self::move_up))
+            .on_action(cx.listener(Self::move_down))
+            .on_action(cx.listener(Self::page_up))
+            .on_action(cx.listener(Self::page_down))
+            .on_action(cx.listener(Self::move_to_start_of_row))
+            .on_action(cx.listener(Self::move_to_end_of_row))
+            .on_action(cx.listener(Self::move_to_start))
+            .on_action(cx.listener(Self::move_to_end))
+            .on_action(cx.listener(Self::backspace))
+            .on_action(cx.listener(Self::delete))
+            .on_action(cx.listener(Self::toggle_insert_mode))
+            .on_action(cx.listener(Self::switch_column))
+            .on_action(cx.listener(Self::find))
+            .on_action(cx.listener(Self::find_next))
+            .on_action(cx.listener(Self::undo))
+            .on_action(cx.listener(Self::redo))
+            .on_key_down(cx.listener(Self::key_down))
+            .size_full()
+            .bg(cx.theme().colors().editor_background)
+            .when(self.find_bar_visible, |this| {
+                this.child(self.render_find_bar(cx))
+            })
+            .child(
+                div()
+                    .id("hex-editor-rows")
+                    .relative()
+                    .flex_1()
+                    .overflow_hidden()
+                    .on_scroll_wheel(cx.listener(Self::scroll))
+                    .child(
+                        canvas(
+                            move |bounds, _, cx| {
+                                let visible_rows = (bounds.size.height / row_height).floor() as u64;
+                                this.update(cx, |this, cx| this.set_visible_rows(visible_rows, cx));
+                            },
+                            |_, _, _, _| {},
+                        )
+                        .absolute()
+                        .size_full(),
+                    )
+                    .child(v_flex().p_1().children(rows)),
+            )
+            .child(self.render_status(cx))
+    }
+}
+
+impl ProjectItem for HexEditor {
+    type Item = HexBuffer;
+
+    fn for_project_item(
+        project: Entity<Project>,
+        _: Option<&Pane>,
+        item: Entity<Self::Item>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self
+    where
+        Self: Sized,
+    {
+        Self::new(item, project, window, cx)
+    }
+}
+
+async fn find_or_create_project_path(
+    project: &Entity<Project>,
+    abs_path: &Path,
+    cx: &mut gpui::AsyncWindowContext,
+) -> anyhow::Result<ProjectPath> {
+    let (worktree, path) = project
+        .update(cx, |project, cx| {
+            project.find_or_create_worktree(abs_path, false, cx)
+        })?
+        .await
+        .context("Path not found")?;
+    let worktree_id = worktree.update(cx, |worktree, _| worktree.id())?;
+    Ok(ProjectPath { worktree_id, path })
+}
+
+/// Opens the given file, or the file of the active item, in the hex editor.
+fn open_file(
+    workspace: &mut Workspace,
+    action: &OpenFile,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    let project = workspace.project().clone();
+    let abs_path = match &action.abs_path {
+        Some(abs_path) => Some(abs_path.clone()),
+        None => workspace
+            .active_item(cx)
+            .and_then(|item| item.project_path(cx))
+            .and_then(|project_path| project.read(cx).absolute_path(&project_path, cx)),
+    };
+    let Some(abs_path) = abs_path else {
+        return;
+    };
+    let pane = workspace.active_pane().clone();
+    cx.spawn_in(window, async move |workspace, cx| {
+        let project_path = find_or_create_project_path(&project, &abs_path, cx).await?;
+        let buffer = cx
+            .update(|_, cx| HexBuffer::open(&project, project_path, cx))?
+            .await?;
+        workspace.update_in(cx, |workspace, window, cx| {
+            workspace.open_project_item::<HexEditor>(pane, buffer, true, true, window, cx);
+        })
+    })
+    .detach_and_log_err(cx);
+}
+
+pub fn init(cx: &mut App) {
+    workspace::register_serializable_item::<HexEditor>(cx);
+    cx.observe_new(|workspace: &mut Workspace, _, _| {
+        workspace.register_action(open_file);
+    })
+    .detach();
+}
+
+mod persistence {
+    use std::path::PathBuf;
+
+    use db::{
+        query,
+        sqlez::{domain::Domain, thread_safe_connection::ThreadSafeConnection},
+        sqlez_macros::sql,
+    };
+    use workspace::{ItemId, WorkspaceDb, WorkspaceId};
+
+    pub struct HexEditorDb(ThreadSafeConnection);
+
+    impl Domain for HexEditorDb {
+        const NAME: &str = stringify!(HexEditorDb);
+
+        const MIGRATIONS: &[&str] = &[sql!(
+                CREATE TABLE hex_editors (
+                    workspace_id INTEGER,
+                    item_id INTEGER UNIQUE,
+
+                    path BLOB,
+
+                    PRIMARY KEY(workspace_id, item_id),
+                    FOREIGN KEY(workspace_id) REFERENCES workspaces(workspace_id)
+                    ON DELETE CASCADE
+                ) STRICT;
+        )];
+    }
+
+    db::static_connection!(HEX_EDITOR, HexEditorDb, [WorkspaceDb]);
+
+    impl HexEditorDb {
+        query! {
+            pub async fn save_path(
+                item_id: ItemId,
+                workspace_id: WorkspaceId,
+                path: PathBuf
+            ) -> Result<()> {
+                INSERT OR REPLACE INTO hex_editors(item_id, workspace_id, path)
+                VALUES (?, ?, ?)
+            }
+        }
+
+        query! {
+            pub fn get_path(item_id: ItemId, workspace_id: WorkspaceId) -> Result<Option<PathBuf>> {
+                SELECT path
+                FROM hex_editors
+                WHERE item_id = ? AND workspace_id = ?
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_pattern() {
+        assert_eq!(
+            parse_pattern("7F 45 4c46"),
+            Ok(vec![0x7F, 0x45, 0x4C, 0x46])
+        );
+        assert_eq!(parse_pattern(" \"ELF\" "), Ok(b"ELF".to_vec()));
+        assert_eq!(parse_pattern("\"a b"), Ok(b"a b".to_vec()));
+        assert!(parse_pattern("7F4").is_err());
+        assert!(parse_pattern("zz").is_err());
+        assert!(parse_pattern("").is_err());
+        assert!(parse_pattern("\"\"").is_err());
+    }
+}
@@ -0,0 +1,279 @@
+use std::{ops::Range, sync::Arc};
+
+/// Where the bytes of a [`Piece`] come from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Source {
+    /// The file as it was last loaded from disk.
+    Original,
+    /// The bytes inserted since.
+    Added,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Piece {
+    source: Source,
+    start: u64,
+    len: u64,
+}
+
+/// A contiguous run of the contents of a [`PieceTable`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Span<'a> {
+    /// A range of the file on disk, which has to be read to get its bytes.
+    Original(Range<u64>),
+    /// Bytes that were inserted since the file was loaded.
+    Added(&'a [u8]),
+}
+
+/// The contents of a file being edited, described as the pieces of the file on disk that are
+/// kept and the bytes inserted between them, so that editing never requires loading the file.
+///
+/// Inserted bytes are only ever appended, so restoring earlier pieces restores earlier contents,
+/// which is what undo relies on.
+#[derive(Clone, Debug, Default)]
+pub struct PieceTable {
+    pieces: Vec<Piece>,
+    added: AddedBytes,
+}
+
+/// The pieces of a [`PieceTable`] at some point, to be restored with [`PieceTable::restore`].
+#[derive(Clone, Debug)]
+pub struct Checkpoint {
+    pieces: Vec<Piece>,
+}
+
+/// The bytes inserted into a [`PieceTable`], in one chunk per edit. Chunks are never modified
+/// once added, so they are shared by the clones of the table instead of being copied.
+#[derive(Clone, Debug, Default)]
+struct AddedBytes {
+    /// The chunks, along with the offset they start at.
+    chunks: Vec<(u64, Arc<[u8]>)>,
+    len: u64,
+}
+
+impl AddedBytes {
+    /// Appends the given bytes, returning the offset they start at.
+    fn push(&mut self, bytes: &[u8]) -> u64 {
+        let start = self.len;
+        self.chunks.push((start, bytes.into()));
+        self.len += bytes.len() as u64;
+        start
+    }
+
+    /// Returns the bytes in the given range, as one slice per chunk it overlaps.
+    fn slices(&self, range: Range<u64>) -> impl Iterator<Item = &[u8]> {
+        let first_ix = self
+            .chunks
+            .partition_point(|(start, _)| *start <= range.start)
+            .saturating_sub(1);
+        self.chunks[first_ix..]
+            .iter()
+            .take_while(move |(start, _)| *start < range.end)
+            .map(move |(start, chunk)| {
+                let chunk_start = range.start.saturating_sub(*start) as usize;
+                let chunk_end = (range.end - start).min(chunk.len() as u64) as usize;
+                &chunk[chunk_start..chunk_end]
+            })
+    }
+}
+
+impl PieceTable {
+    pub fn new(original_len: u64) -> Self {
+        let pieces = if original_len > 0 {
+            vec![Piece {
+                source: Source::Original,
+                start: 0,
+                len: original_len,
+            }]
+        } else {
+            Vec::new()
+        };
+        Self {
+            pieces,
+            added: AddedBytes::default(),
+        }
+    }
+
+    pub fn len(&self) -> u64 {
+        self.pieces.iter().map(|piece| piece.len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pieces.is_empty()
+    }
+
+    /// Returns the spans making up the given range of the contents, in order.
+    pub fn spans(&self, range: Range<u64>) -> Vec<Span<'_>> {
+        let mut spans = Vec::new();
+        let mut piece_start = 0;
+        for piece in &self.pieces {
+            let piece_end = piece_start + piece.len;
+            if piece_end > range.start && piece_start < range.end {
+                let start = range.start.max(piece_start) - piece_start + piece.start;
+                let end = range.end.min(piece_end) - piece_start + piece.start;
+                match piece.source {
+                    Source::Original => spans.push(Span::Original(start..end)),
+                    Source::Added => spans.extend(self.added.slices(start..end).map(Span::Added)),
+                }
+            }
+            if piece_end >= range.end {
+                break;
+            }
+            piece_start = piece_end;
+        }
+        spans
+    }
+
+    /// Replaces the given range of the contents with the given bytes.
+    pub fn edit(&mut self, range: Range<u64>, bytes: &[u8]) {
+        let start_ix = self.split_at(range.start);
+        let end_ix = self.split_at(range.end);
+        let mut inserted = Vec::new();
+        if !bytes.is_empty() {
+            inserted.push(Piece {
+                source: Source::Added,
+                start: self.added.push(bytes),
+                len: bytes.len() as u64,
+            });
+        }
+        self.pieces.splice(start_ix..end_ix, inserted);
+        self.merge_adjacent_pieces();
+    }
+
+    /// Returns a copy of the pieces, to be restored with [`Self::restore`].
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint {
+            pieces: self.pieces.clone(),
+        }
+    }
+
+    /// Restores the contents at the given checkpoint, keeping the bytes added since.
+    pub fn restore(&mut self, checkpoint: &Checkpoint) {
+        self.pieces = checkpoint.pieces.clone();
+    }
+
+    /// Splits the piece containing the given offset so that a piece starts there, returning the
+    /// index of that piece.
+    fn split_at(&mut self, offset: u64) -> usize {
+        let mut piece_start = 0;
+        for (ix, piece) in self.pieces.iter_mut().enumerate() {
+            if piece_start == offset {
+                return ix;
+            }
+            let piece_end = piece_start + piece.len;
+            if offset < piece_end {
+                let head_len = offset - piece_start;
+                let tail = Piece {
+                    source: piece.source,
+                    start: piece.start + head_len,
+                    len: piece.len - head_len,
+                };
+                piece.len = head_len;
+                self.pieces.insert(ix + 1, tail);
+                return ix + 1;
+            }
+            piece_start = piece_end;
+        }
+        self.pieces.len()
+    }
+
+    fn merge_adjacent_pieces(&mut self) {
+        self.pieces.dedup_by(|next, previous| {
+            if previous.source == next.source && previous.start + previous.len == next.start {
+                previous.len += next.len;
+                true
+            } else {
+                false
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn contents(table: &PieceTable, original: &[u8]) -> Vec<u8> {
+        table
+            .spans(0..table.len())
+            .into_iter()
+            .flat_map(|span| match span {
+                Span::Original(range) => {
+                    original[range.start as usize..range.end as usize].to_vec()
+                }
+                Span::Added(bytes) => bytes.to_vec(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_edits() {
+        let original = b"0123456789";
+        let mut table = PieceTable::new(original.len() as u64);
+        assert_eq!(contents(&table, original), b"0123456789");
+
+        table.edit(3..4, b"x");
+        assert_eq!(contents(&table, original), b"012x456789");
+        table.edit(0..0, b"ab");
+        assert_eq!(contents(&table, original), b"ab012x456789");
+        table.edit(10..12, b"");
+        assert_eq!(contents(&table, original), b"ab012x4567");
+        table.edit(2..10, b"");
+        assert_eq!(contents(&table, original), b"ab");
+        table.edit(2..2, b"cd");
+        assert_eq!(contents(&table, original), b"abcd");
+        assert_eq!(table.pieces.len(), 1);
+        assert_eq!(table.len(), 4);
+    }
+
+    #[test]
+    fn test_spans() {
+        let original = b"0123456789";
+        let mut table = PieceTable::new(original.len() as u64);
+        table.edit(4..6, b"xyz");
+        assert_eq!(
+            table.spans(2..9),
+            [
+                Span::Original(2..4),
+                Span::Added(b"xyz"),
+                Span::Original(6..8)
+            ]
+        );
+        assert_eq!(table.spans(5..6), [Span::Added(b"y")]);
+        assert!(table.spans(20..30).is_empty());
+    }
+
+    #[test]
+    fn test_restore() {
+        let original = b"0123";
+        let mut table = PieceTable::new(original.len() as u64);
+        let checkpoint = table.checkpoint();
+        table.edit(1..3, b"ab");
+        let edited = table.checkpoint();
+        table.edit(0..4, b"");
+        assert!(table.is_empty());
+
+        table.restore(&edited);
+        assert_eq!(contents(&table, original), b"0ab3");
+        table.restore(&checkpoint);
+        assert_eq!(contents(&table, original), b"0123");
+    }
+
+    #[test]
+    fn test_added_bytes_are_shared() {
+        let original = b"0123";
+        let mut table = PieceTable::new(original.len() as u64);
+        table.edit(1..1, b"ab");
+        let snapshot = table.clone();
+        table.edit(3..3, b"cd");
+        assert_eq!(contents(&snapshot, original), b"0ab123");
+        assert_eq!(contents(&table, original), b"0abcd123");
+        assert!(Arc::ptr_eq(
+            &snapshot.added.chunks[0].1,
+            &table.added.chunks[0].1
+        ));
+        // Bytes inserted by consecutive edits are contiguous, even across chunks.
+        assert_eq!(table.pieces.len(), 3);
+        assert_eq!(table.spans(1..5), [Span::Added(b"ab"), Span::Added(b"cd")]);
+    }
+}
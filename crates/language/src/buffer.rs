@@ -821,6 +821,9 @@ pub struct BracketMatch {
     pub open_range: Range<usize>,
     pub close_range: Range<usize>,
     pub newline_only: bool,
+    /// Whether the pair is left out of the colorization of brackets by nesting depth,
+    /// like quotes or keyword pairs.
+    pub rainbow_exclude: bool,
 }
 
 impl Buffer {
@@ -4090,6 +4093,7 @@ impl BufferSnapshot {
                     open_range,
                     close_range,
                     newline_only: pattern.newline_only,
+                    rainbow_exclude: pattern.newline_only || pattern.rainbow_exclude,
                 });
             }
             None
@@ -4107,6 +4111,38 @@ impl BufferSnapshot {
             .filter(|pair| !pair.newline_only)
     }
 
+    /// Returns the bracket pairs overlapping `range` that are colorized by their nesting depth,
+    /// along with that depth: the number of colorized pairs enclosing them.
+    pub fn colorized_bracket_ranges<T: ToOffset>(
+        &self,
+        range: Range<T>,
+    ) -> Vec<(BracketMatch, u32)> {
+        let range = range.start.to_offset(self)..range.end.to_offset(self);
+        // The pairs enclosing the range are matched too, so depths don't depend on the range.
+        let mut pairs = self
+            .all_bracket_ranges(range)
+            .filter(|pair| !pair.rainbow_exclude)
+            .collect::<Vec<_>>();
+        pairs.sort_by_key(|pair| (pair.open_range.start, Reverse(pair.close_range.end)));
+        pairs.dedup_by(|a, b| a.open_range == b.open_range && a.close_range == b.close_range);
+
+        let mut enclosing_pair_ends = Vec::new();
+        pairs
+            .into_iter()
+            .map(|pair| {
+                while enclosing_pair_ends
+                    .last()
+                    .is_some_and(|end| *end <= pair.open_range.start)
+                {
+                    enclosing_pair_ends.pop();
+                }
+                let depth = enclosing_pair_ends.len() as u32;
+                enclosing_pair_ends.push(pair.close_range.end);
+                (pair, depth)
+            })
+            .collect()
+    }
+
     pub fn debug_variables_query<T: ToOffset>(
         &self,
         range: Range<T>,
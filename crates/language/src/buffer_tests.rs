@@ -1251,6 +1251,60 @@ fn test_enclosing_bracket_ranges_where_brackets_are_not_outermost_children(cx: &
     );
 }
 
+#[gpui::test]
+fn test_colorized_bracket_ranges(cx: &mut App) {
+    let language = Language::new(
+        LanguageConfig {
+            name: "Rust".into(),
+            ..Default::default()
+        },
+        Some(tree_sitter_rust::LANGUAGE.into()),
+    )
+    .with_brackets_query(
+        r#"
+        ("(" @open ")" @close)
+        ("[" @open "]" @close)
+        ("{" @open "}" @close)
+        (("\"" @open "\"" @close) (#set! rainbow.exclude))
+        "#,
+    )
+    .unwrap();
+
+    let text = r#"fn a() { b(["c"], { d() }) }"#;
+    let buffer = cx.new(|cx| Buffer::local(text, cx).with_language(Arc::new(language), cx));
+    let snapshot = buffer.read(cx).snapshot();
+    let colorized_pairs = |range: Range<usize>| {
+        snapshot
+            .colorized_bracket_ranges(range)
+            .into_iter()
+            .map(|(pair, depth)| (&text[pair.open_range.start..pair.close_range.end], depth))
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        colorized_pairs(0..text.len()),
+        vec![
+            ("()", 0),
+            (r#"{ b(["c"], { d() }) }"#, 0),
+            (r#"(["c"], { d() })"#, 1),
+            (r#"["c"]"#, 2),
+            ("{ d() }", 2),
+            ("()", 3),
+        ]
+    );
+    // Depths account for the pairs enclosing the range.
+    let call_start = text.find("d()").unwrap();
+    assert_eq!(
+        colorized_pairs(call_start..call_start + "d()".len()),
+        vec![
+            (r#"{ b(["c"], { d() }) }"#, 0),
+            (r#"(["c"], { d() })"#, 1),
+            ("{ d() }", 2),
+            ("()", 3),
+        ]
+    );
+}
+
 #[gpui::test]
 fn test_range_for_syntax_ancestor(cx: &mut App) {
     cx.new(|cx| {
@@ -14,6 +14,7 @@ pub mod language_settings;
 mod manifest;
 mod outline;
 pub mod proto;
+mod structural_search;
 mod syntax_map;
 mod task_context;
 mod text_diff;
@@ -82,6 +83,7 @@ pub use language_registry::{
 };
 pub use lsp::{LanguageServerId, LanguageServerName};
 pub use outline::*;
+pub use structural_search::{StructuralMatch, StructuralPattern};
 pub use syntax_map::{
     OwnedSyntaxLayer, SyntaxLayer, SyntaxMapMatches, ToTreeSitterPoint, TreeSitterOptions,
 };
@@ -1307,6 +1309,7 @@ struct BracketsConfig {
 #[derive(Clone, Debug, Default)]
 struct BracketsPatternConfig {
     newline_only: bool,
+    rainbow_exclude: bool,
 }
 
 pub struct DebugVariablesConfig {
@@ -1613,8 +1616,10 @@ impl Language {
                 .map(|ix| {
                     let mut config = BracketsPatternConfig::default();
                     for setting in query.property_settings(ix) {
-                        if setting.key.as_ref() == "newline.only" {
-                            config.newline_only = true
+                        match setting.key.as_ref() {
+                            "newline.only" => config.newline_only = true,
+                            "rainbow.exclude" => config.rainbow_exclude = true,
+                            _ => {}
                         }
                     }
                     config
@@ -11,9 +11,10 @@ use gpui::{App, Modifiers, SharedString};
 use itertools::{Either, Itertools};
 
 pub use settings::{
-    CompletionSettingsContent, EditPredictionProvider, EditPredictionsMode, FormatOnSave,
-    Formatter, FormatterList, InlayHintKind, LanguageSettingsContent, LspInsertMode,
-    RewrapBehavior, SelectedFormatter, ShowWhitespaceSetting, SoftWrap, WordsCompletionMode,
+    CompletionSettingsContent, EditPredictionProvider, EditPredictionsMode, FimFormat,
+    FormatOnSave, Formatter, FormatterList, InlayHintKind, LanguageSettingsContent,
+    LocalEditPredictionApi, LspInsertMode, RewrapBehavior, SelectedFormatter,
+    ShowWhitespaceSetting, SoftWrap, WordsCompletionMode,
 };
 use settings::{ExtendingVec, Settings, SettingsContent, SettingsLocation, SettingsStore};
 use shellexpand;
@@ -133,6 +134,8 @@ pub struct LanguageSettings {
     pub use_autoclose: bool,
     /// Whether to automatically surround text with brackets.
     pub use_auto_surround: bool,
+    /// Whether to color bracket pairs by their nesting depth.
+    pub colorize_brackets: bool,
     /// Whether to use additional LSP queries to format (and amend) the code after
     /// every "trigger" symbol input, defined by LSP server capabilities.
     pub use_on_type_format: bool,
@@ -260,7 +263,9 @@ impl LanguageSettings {
         Self::resolve_language_servers(&self.language_servers, available_language_servers)
     }
 
-    pub(crate) fn resolve_language_servers(
+    /// Resolves a list of language servers written in the `language_servers` syntax
+    /// (with `!name` exclusions and the `...` placeholder) against the available ones.
+    pub fn resolve_language_servers(
         configured_language_servers: &[String],
         available_language_servers: &[LanguageServerName],
     ) -> Vec<LanguageServerName> {
@@ -377,6 +382,8 @@ pub struct EditPredictionSettings {
     pub mode: settings::EditPredictionsMode,
     /// Settings specific to GitHub Copilot.
     pub copilot: CopilotSettings,
+    /// Settings specific to the local model provider.
+    pub local: LocalEditPredictionSettings,
     /// Whether edit predictions are enabled in the assistant panel.
     /// This setting has no effect if globally disabled.
     pub enabled_in_text_threads: bool,
@@ -412,6 +419,21 @@ pub struct CopilotSettings {
     pub enterprise_uri: Option<String>,
 }
 
+#[derive(Clone, Debug, Default)]
+pub struct LocalEditPredictionSettings {
+    /// The API the model is served through.
+    pub api: LocalEditPredictionApi,
+    /// The URL of the server, without the API's path.
+    pub api_url: String,
+    /// The model to request completions from.
+    pub model: String,
+    /// The special tokens the model uses for fill-in-the-middle prompts, if not detected from
+    /// its name.
+    pub fim_format: Option<FimFormat>,
+    /// The most tokens to generate for a prediction.
+    pub max_tokens: u32,
+}
+
 impl AllLanguageSettings {
     /// Returns the [`LanguageSettings`] for the language with the specified name.
     pub fn language<'a>(
@@ -562,6 +584,7 @@ impl settings::Settings for AllLanguageSettings {
                 },
                 use_autoclose: settings.use_autoclose.unwrap(),
                 use_auto_surround: settings.use_auto_surround.unwrap(),
+                colorize_brackets: settings.colorize_brackets.unwrap(),
                 use_on_type_format: settings.use_on_type_format.unwrap(),
                 auto_indent: settings.auto_indent.unwrap(),
                 auto_indent_on_paste: settings.auto_indent_on_paste.unwrap(),
@@ -622,6 +645,15 @@ impl settings::Settings for AllLanguageSettings {
             enterprise_uri: copilot.enterprise_uri,
         };
 
+        let local = edit_predictions.local.unwrap();
+        let local_settings = LocalEditPredictionSettings {
+            api: local.api.unwrap(),
+            api_url: local.api_url.unwrap(),
+            model: local.model.unwrap(),
+            fim_format: local.fim_format,
+            max_tokens: local.max_tokens.unwrap(),
+        };
+
         let enabled_in_text_threads = edit_predictions.enabled_in_text_threads.unwrap();
 
         let mut file_types: FxHashMap<Arc<str>, GlobSet> = FxHashMap::default();
@@ -655,6 +687,7 @@ impl settings::Settings for AllLanguageSettings {
                     .collect(),
                 mode: edit_predictions_mode,
                 copilot: copilot_settings,
+                local: local_settings,
                 enabled_in_text_threads,
             },
             defaults: default_language_settings,
@@ -731,6 +764,10 @@ impl settings::Settings for AllLanguageSettings {
             },
         );
         vscode.bool_setting("editor.formatOnType", &mut d.use_on_type_format);
+        vscode.bool_setting(
+            "editor.bracketPairColorization.enabled",
+            &mut d.colorize_brackets,
+        );
         vscode.bool_setting("editor.linkedEditing", &mut d.linked_edits);
         vscode.bool_setting("editor.formatOnPaste", &mut d.auto_indent_on_paste);
         vscode.bool_setting(
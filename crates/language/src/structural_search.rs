@@ -0,0 +1,610 @@
+use std::{fmt, ops::Range, sync::Arc};
+
+use anyhow::Result;
+use collections::HashMap;
+use parking_lot::Mutex;
+use streaming_iterator::StreamingIterator;
+use text::Rope;
+use tree_sitter::{Node, Query, Tree};
+
+use crate::{
+    BufferSnapshot, Language, LanguageId,
+    syntax_map::{QueryCursorHandle, TextProvider},
+    with_parser,
+};
+
+/// The identifier metavariables are replaced with before parsing a template, so that it parses
+/// like code in most languages.
+const METAVARIABLE_PREFIX: &str = "zed_metavariable_";
+
+/// The name of the metavariable matching any node without binding it.
+const WILDCARD: &str = "_";
+
+/// What is appended to templates and to matched text when parsing them on their own, as some
+/// grammars only parse expressions as statements.
+const STANDALONE_SUFFIXES: [&str; 2] = ["", ";"];
+
+/// A search pattern matched against syntax trees rather than text.
+///
+/// It is either a code template, in which `$name` metavariables match any syntax node (and the
+/// same text wherever the same name is repeated) like `$x.unwrap()`, or a tree-sitter query like
+/// `(call_expression function: (identifier) @name) @match`, matching the node captured as
+/// `@match` if any, or all of the captured nodes otherwise.
+pub struct StructuralPattern {
+    source: String,
+    kind: PatternKind,
+    compiled: Mutex<HashMap<LanguageId, (Arc<Language>, Option<Arc<CompiledPattern>>)>>,
+}
+
+enum PatternKind {
+    Template { source_with_placeholders: String },
+    Query,
+}
+
+enum CompiledPattern {
+    Template {
+        tree: Tree,
+        source: String,
+        template_len: usize,
+    },
+    Query(Query),
+}
+
+/// A match of a [`StructuralPattern`], with the ranges of the nodes bound to its metavariables,
+/// or captured by its query.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StructuralMatch {
+    pub range: Range<usize>,
+    pub bindings: Vec<(String, Range<usize>)>,
+}
+
+impl fmt::Debug for StructuralPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StructuralPattern")
+            .field("source", &self.source)
+            .field("is_query", &self.is_query())
+            .finish_non_exhaustive()
+    }
+}
+
+impl StructuralPattern {
+    pub fn new(source: &str) -> Result<Self> {
+        let source = source.trim();
+        anyhow::ensure!(!source.is_empty(), "empty structural search pattern");
+
+        let kind = if source.starts_with('(') && source.contains('@') {
+            PatternKind::Query
+        } else {
+            let mut source_with_placeholders = String::with_capacity(source.len());
+            let mut chars = source.char_indices().peekable();
+            while let Some((ix, char)) = chars.next() {
+                if char != '$' {
+                    source_with_placeholders.push(char);
+                    continue;
+                }
+                if chars.next_if(|(_, char)| *char == '$').is_some() {
+                    source_with_placeholders.push('$');
+                    continue;
+                }
+                let name_start = ix + 1;
+                let mut name_end = name_start;
+                while let Some((ix, char)) = chars.next_if(|(_, char)| is_word_char(*char)) {
+                    name_end = ix + char.len_utf8();
+                }
+                let name = &source[name_start..name_end];
+                anyhow::ensure!(
+                    !name.is_empty(),
+                    "expected a metavariable name after `$` at offset {ix}"
+                );
+                source_with_placeholders.push_str(METAVARIABLE_PREFIX);
+                source_with_placeholders.push_str(name);
+            }
+            anyhow::ensure!(
+                metavariable_name(&source_with_placeholders).is_none(),
+                "a structural search pattern must contain more than a metavariable"
+            );
+            PatternKind::Template {
+                source_with_placeholders,
+            }
+        };
+
+        Ok(Self {
+            source: source.to_string(),
+            kind,
+            compiled: Mutex::default(),
+        })
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+
+    pub fn is_query(&self) -> bool {
+        matches!(self.kind, PatternKind::Query)
+    }
+
+    /// Returns words that any text matching this pattern contains, to skip files without them
+    /// before parsing them.
+    pub fn required_words(&self) -> Vec<&str> {
+        match &self.kind {
+            PatternKind::Template {
+                source_with_placeholders,
+            } => source_with_placeholders
+                .split(|char: char| !is_word_char(char))
+                .filter(|word| !word.is_empty() && !word.starts_with(METAVARIABLE_PREFIX))
+                .collect(),
+            PatternKind::Query => query_required_words(&self.source),
+        }
+    }
+
+    /// Returns the non-overlapping matches of this pattern within `range` of the buffer, in all
+    /// of its syntax layers.
+    pub fn matches(&self, buffer: &BufferSnapshot, range: Range<usize>) -> Vec<StructuralMatch> {
+        let mut matches = Vec::new();
+        for layer in buffer.syntax_layers() {
+            if let Some(compiled) = self.compiled_for(layer.language) {
+                compiled.find(layer.node(), buffer.as_rope(), range.clone(), &mut matches);
+            }
+        }
+        sort_and_remove_overlaps(&mut matches);
+        matches
+    }
+
+    /// Matches this pattern against the whole of `text`, parsed as a standalone snippet in each
+    /// of the languages this pattern was matched in so far, such as a match found by
+    /// [`Self::matches`] in a buffer.
+    pub fn match_text(&self, text: &str) -> Option<StructuralMatch> {
+        let trimmed_range = text.len() - text.trim_start().len()..text.trim_end().len();
+        let compiled = self
+            .compiled
+            .lock()
+            .values()
+            .filter_map(|(language, compiled)| Some((language.clone(), compiled.clone()?)))
+            .collect::<Vec<_>>();
+        compiled.into_iter().find_map(|(language, compiled)| {
+            STANDALONE_SUFFIXES.into_iter().find_map(|suffix| {
+                let text = format!("{text}{suffix}");
+                let tree = parse(&language, &text)?;
+                let mut matches = Vec::new();
+                compiled.find(
+                    tree.root_node(),
+                    &Rope::from(text.as_str()),
+                    trimmed_range.clone(),
+                    &mut matches,
+                );
+                matches.into_iter().find(|mat| mat.range == trimmed_range)
+            })
+        })
+    }
+
+    fn compiled_for(&self, language: &Arc<Language>) -> Option<Arc<CompiledPattern>> {
+        self.compiled
+            .lock()
+            .entry(language.id())
+            .or_insert_with(|| {
+                let compiled = self.compile(language).map(Arc::new);
+                (language.clone(), compiled)
+            })
+            .1
+            .clone()
+    }
+
+    fn compile(&self, language: &Arc<Language>) -> Option<CompiledPattern> {
+        match &self.kind {
+            PatternKind::Template {
+                source_with_placeholders,
+            } => {
+                let template_len = source_with_placeholders.len();
+                STANDALONE_SUFFIXES.into_iter().find_map(|suffix| {
+                    let source = format!("{source_with_placeholders}{suffix}");
+                    let tree = parse(language, &source)?;
+                    let template = tree
+                        .root_node()
+                        .descendant_for_byte_range(0, template_len)?;
+                    if template.byte_range() != (0..template_len) || template.has_error() {
+                        return None;
+                    }
+                    Some(CompiledPattern::Template {
+                        tree,
+                        source,
+                        template_len,
+                    })
+                })
+            }
+            PatternKind::Query => {
+                let grammar = language.grammar()?;
+                match Query::new(&grammar.ts_language, &self.source) {
+                    Ok(query) => Some(CompiledPattern::Query(query)),
+                    Err(error) => {
+                        log::debug!(
+                            "structural search query is invalid for {}: {error}",
+                            language.name()
+                        );
+                        None
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl StructuralMatch {
+    /// Expands the `$name` metavariables of a replacement with the text bound to them, `$$`
+    /// standing for a literal `$`. Unbound metavariables are left as is.
+    pub fn expand_replacement(
+        &self,
+        replacement: &str,
+        text_for_range: impl Fn(Range<usize>) -> String,
+    ) -> String {
+        let mut expanded = String::with_capacity(replacement.len());
+        let mut chars = replacement.char_indices().peekable();
+        while let Some((ix, char)) = chars.next() {
+            if char != '$' {
+                expanded.push(char);
+                continue;
+            }
+            if chars.next_if(|(_, char)| *char == '$').is_some() {
+                expanded.push('$');
+                continue;
+            }
+            let name_start = ix + 1;
+            let mut name_end = name_start;
+            while let Some((ix, char)) = chars.next_if(|(_, char)| is_word_char(*char)) {
+                name_end = ix + char.len_utf8();
+            }
+            let name = &replacement[name_start..name_end];
+            match self
+                .bindings
+                .iter()
+                .find(|(bound_name, _)| bound_name == name)
+            {
+                Some((_, range)) => expanded.push_str(&text_for_range(range.clone())),
+                None => expanded.push_str(&replacement[ix..name_end]),
+            }
+        }
+        expanded
+    }
+}
+
+impl CompiledPattern {
+    fn find(
+        &self,
+        root: Node,
+        text: &Rope,
+        range: Range<usize>,
+        matches: &mut Vec<StructuralMatch>,
+    ) {
+        match self {
+            CompiledPattern::Template {
+                tree,
+                source,
+                template_len,
+            } => {
+                let Some(template) = tree.root_node().descendant_for_byte_range(0, *template_len)
+                else {
+                    return;
+                };
+                let mut cursor = root.walk();
+                loop {
+                    let node = cursor.node();
+                    let mut descend =
+                        node.start_byte() < range.end && range.start < node.end_byte();
+                    if descend
+                        && range.start <= node.start_byte()
+                        && node.end_byte() <= range.end
+                        && node.kind_id() == template.kind_id()
+                    {
+                        let mut bindings = Vec::new();
+                        if match_template(template, source, node, text, &mut bindings) {
+                            matches.push(StructuralMatch {
+                                range: node.byte_range(),
+                                bindings,
+                            });
+                            descend = false;
+                        }
+                    }
+                    if descend && cursor.goto_first_child() {
+                        continue;
+                    }
+                    while !cursor.goto_next_sibling() {
+                        if !cursor.goto_parent() {
+                            return;
+                        }
+                    }
+                }
+            }
+            CompiledPattern::Query(query) => {
+                let match_capture_ix = query.capture_index_for_name("match");
+                let mut cursor = QueryCursorHandle::new();
+                cursor.set_byte_range(range.clone());
+                let mut query_matches = cursor.matches(query, root, TextProvider(text));
+                while let Some(query_match) = query_matches.next() {
+                    let mut match_range = None::<Range<usize>>;
+                    let mut bindings = Vec::new();
+                    for capture in query_match.captures {
+                        let capture_range = capture.node.byte_range();
+                        if Some(capture.index) == match_capture_ix {
+                            match_range = Some(capture_range);
+                            continue;
+                        }
+                        if match_capture_ix.is_none() {
+                            match_range = Some(match match_range {
+                                Some(match_range) => {
+                                    match_range.start.min(capture_range.start)
+                                        ..match_range.end.max(capture_range.end)
+                                }
+                                None => capture_range.clone(),
+                            });
+                        }
+                        let name = query.capture_names()[capture.index as usize];
+                        bindings.push((name.to_string(), capture_range));
+                    }
+                    if let Some(match_range) = match_range
+                        && range.start <= match_range.start
+                        && match_range.end <= range.end
+                    {
+                        matches.push(StructuralMatch {
+                            range: match_range,
+                            bindings,
+                        });
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn match_template(
+    template: Node,
+    template_source: &str,
+    node: Node,
+    text: &Rope,
+    bindings: &mut Vec<(String, Range<usize>)>,
+) -> bool {
+    let template_text = &template_source[template.byte_range()];
+    if let Some(name) = metavariable_name(template_text) {
+        if name == WILDCARD {
+            return true;
+        }
+        if let Some((_, bound_range)) = bindings.iter().find(|(bound_name, _)| bound_name == name) {
+            return text_for_range(text, bound_range.clone())
+                == text_for_range(text, node.byte_range());
+        }
+        bindings.push((name.to_string(), node.byte_range()));
+        return true;
+    }
+
+    if template.kind_id() != node.kind_id() {
+        return false;
+    }
+    let template_children = significant_children(template);
+    let children = significant_children(node);
+    if template_children.is_empty() && children.is_empty() {
+        return template_text == text_for_range(text, node.byte_range());
+    }
+    template_children.len() == children.len()
+        && template_children
+            .into_iter()
+            .zip(children)
+            .all(|(template_child, child)| {
+                match_template(template_child, template_source, child, text, bindings)
+            })
+}
+
+/// Returns the quoted strings of a query that appear verbatim in the text it matches: anonymous
+/// nodes and the arguments of `#eq?` predicates. Strings in alternations, in optional or
+/// repeated nodes, and in any other predicate, like the regexes of `#match?`, are left out.
+fn query_required_words(source: &str) -> Vec<&str> {
+    #[derive(Default)]
+    struct Group<'a> {
+        words: Vec<&'a str>,
+        is_alternation: bool,
+        predicate: Option<&'a str>,
+    }
+
+    let is_quantifier = |(_, char): &(usize, char)| matches!(char, '?' | '*');
+    let mut groups = vec![Group::default()];
+    let mut chars = source.char_indices().peekable();
+    while let Some((ix, char)) = chars.next() {
+        match char {
+            ';' => while chars.next_if(|(_, char)| *char != '\n').is_some() {},
+            '(' | '[' => {
+                let rest = source[ix + 1..].trim_start();
+                let predicate = (char == '(' && rest.starts_with('#')).then(|| {
+                    rest.split(|char: char| char.is_whitespace() || char == ')')
+                        .next()
+                        .unwrap_or_default()
+                });
+                groups.push(Group {
+                    words: Vec::new(),
+                    is_alternation: char == '[',
+                    predicate,
+                });
+            }
+            ')' | ']' if groups.len() > 1 => {
+                let group = groups.pop().unwrap();
+                let is_optional = chars.next_if(is_quantifier).is_some();
+                if !is_optional && !group.is_alternation {
+                    groups.last_mut().unwrap().words.extend(group.words);
+                }
+            }
+            '"' => {
+                let start = ix + 1;
+                let mut end = None;
+                let mut has_escapes = false;
+                while let Some((ix, char)) = chars.next() {
+                    match char {
+                        '\\' => {
+                            has_escapes = true;
+                            chars.next();
+                        }
+                        '"' => {
+                            end = Some(ix);
+                            break;
+                        }
+                        _ => {}
+                    }
+                }
+                let Some(end) = end else { break };
+                let is_optional = chars.next_if(is_quantifier).is_some();
+                let group = groups.last_mut().unwrap();
+                let is_required = match group.predicate {
+                    Some(predicate) => predicate == "#eq?",
+                    None => !is_optional,
+                };
+                if is_required && !has_escapes && start < end {
+                    group.words.push(&source[start..end]);
+                }
+            }
+            _ => {}
+        }
+    }
+    groups.swap_remove(0).words
+}
+
+/// The children of a node, without the extra ones like comments that can appear anywhere.
+fn significant_children(node: Node) -> Vec<Node> {
+    let mut cursor = node.walk();
+    node.children(&mut cursor)
+        .filter(|child| !child.is_extra())
+        .collect()
+}
+
+fn metavariable_name(text: &str) -> Option<&str> {
+    text.strip_prefix(METAVARIABLE_PREFIX)
+        .filter(|name| !name.is_empty() && name.chars().all(is_word_char))
+}
+
+fn is_word_char(char: char) -> bool {
+    char.is_alphanumeric() || char == '_'
+}
+
+fn text_for_range(text: &Rope, range: Range<usize>) -> String {
+    text.chunks_in_range(range).collect()
+}
+
+fn parse(language: &Language, text: &str) -> Option<Tree> {
+    let grammar = language.grammar()?;
+    with_parser(|parser| {
+        parser.set_language(&grammar.ts_language).ok()?;
+        parser.parse(text, None)
+    })
+}
+
+fn sort_and_remove_overlaps(matches: &mut Vec<StructuralMatch>) {
+    matches.sort_by_key(|mat| (mat.range.start, std::cmp::Reverse(mat.range.end)));
+    let mut last_end = 0;
+    matches.retain(|mat| {
+        if mat.range.start < last_end {
+            return false;
+        }
+        last_end = mat.range.end;
+        true
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Buffer, LanguageConfig};
+    use gpui::{App, AppContext as _};
+
+    fn rust_lang() -> Arc<Language> {
+        Arc::new(Language::new(
+            LanguageConfig {
+                name: "Rust".into(),
+                ..LanguageConfig::default()
+            },
+            Some(tree_sitter_rust::LANGUAGE.into()),
+        ))
+    }
+
+    fn matched_texts(pattern: &str, text: &str, cx: &mut App) -> Vec<String> {
+        let pattern = StructuralPattern::new(pattern).unwrap();
+        let buffer = cx.new(|cx| Buffer::local(text, cx).with_language(rust_lang(), cx));
+        let snapshot = buffer.read(cx).snapshot();
+        pattern
+            .matches(&snapshot, 0..snapshot.len())
+            .into_iter()
+            .map(|mat| text[mat.range].to_string())
+            .collect()
+    }
+
+    #[gpui::test]
+    fn test_template_matches(cx: &mut App) {
+        let text = "fn main() {\n    let a = foo.unwrap();\n    let b = bar(1)\n        .unwrap();\n    a.unwrap_or(b);\n}\n";
+        assert_eq!(
+            matched_texts("$x.unwrap()", text, cx),
+            ["foo.unwrap()", "bar(1)\n        .unwrap()"]
+        );
+        assert_eq!(
+            matched_texts("$x.unwrap_or($x)", text, cx),
+            Vec::<String>::new()
+        );
+        assert_eq!(
+            matched_texts("$a.unwrap_or($_)", text, cx),
+            ["a.unwrap_or(b)"]
+        );
+    }
+
+    #[gpui::test]
+    fn test_query_matches(cx: &mut App) {
+        let text = "fn one() {}\nfn two() {}\n";
+        assert_eq!(
+            matched_texts("(function_item name: (identifier) @name)", text, cx),
+            ["one", "two"]
+        );
+        assert_eq!(
+            matched_texts(
+                r#"(function_item name: (identifier) @name (#eq? @name "two")) @match"#,
+                text,
+                cx
+            ),
+            ["fn two() {}"]
+        );
+    }
+
+    #[gpui::test]
+    fn test_replacement(cx: &mut App) {
+        let pattern = StructuralPattern::new("$x.unwrap()").unwrap();
+        let text = "fn main() { foo(1).unwrap(); }";
+        let buffer = cx.new(|cx| Buffer::local(text, cx).with_language(rust_lang(), cx));
+        let snapshot = buffer.read(cx).snapshot();
+        let matches = pattern.matches(&snapshot, 0..snapshot.len());
+        assert_eq!(matches.len(), 1);
+        assert_eq!(
+            matches[0].expand_replacement("$x?", |range| text[range].to_string()),
+            "foo(1)?"
+        );
+
+        // Matches found in buffers can be matched again from their text alone.
+        let matched_text = &text[matches[0].range.clone()];
+        let text_match = pattern.match_text(matched_text).unwrap();
+        assert_eq!(
+            text_match.expand_replacement("$x? /* $$x */", |range| matched_text[range].to_string()),
+            "foo(1)? /* $x */"
+        );
+    }
+
+    #[test]
+    fn test_invalid_patterns() {
+        assert!(StructuralPattern::new("  ").is_err());
+        assert!(StructuralPattern::new("$x").is_err());
+        assert!(StructuralPattern::new("foo($)").is_err());
+        assert_eq!(
+            StructuralPattern::new("$x.unwrap_or(default)")
+                .unwrap()
+                .required_words(),
+            ["unwrap_or", "default"]
+        );
+        assert_eq!(
+            StructuralPattern::new(
+                r#"((binary_expression operator: ["+" "-"] right: (_) @right "!"?) @match
+                (#eq? @right "one") (#match? @right "^o") (#not-eq? @right "two"))"#
+            )
+            .unwrap()
+            .required_words(),
+            ["one"]
+        );
+    }
+}
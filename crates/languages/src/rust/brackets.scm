@@ -2,6 +2,6 @@
 ("[" @open "]" @close)
 ("{" @open "}" @close)
 ("<" @open ">" @close)
-("\"" @open "\"" @close)
-(closure_parameters "|" @open "|" @close)
-("'" @open "'" @close)
+(("\"" @open "\"" @close) (#set! rainbow.exclude))
+((closure_parameters "|" @open "|" @close) (#set! rainbow.exclude))
+(("'" @open "'" @close) (#set! rainbow.exclude))
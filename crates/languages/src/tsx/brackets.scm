@@ -2,10 +2,10 @@
 ("[" @open "]" @close)
 ("{" @open "}" @close)
 ("<" @open ">" @close)
-("<" @open "/>" @close)
-("</" @open ">" @close)
-("\"" @open "\"" @close)
-("'" @open "'" @close)
-("`" @open "`" @close)
+(("<" @open "/>" @close) (#set! rainbow.exclude))
+(("</" @open ">" @close) (#set! rainbow.exclude))
+(("\"" @open "\"" @close) (#set! rainbow.exclude))
+(("'" @open "'" @close) (#set! rainbow.exclude))
+(("`" @open "`" @close) (#set! rainbow.exclude))
 
 ((jsx_element (jsx_opening_element) @open (jsx_closing_element) @close) (#set! newline.only))
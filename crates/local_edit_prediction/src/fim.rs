@@ -0,0 +1,200 @@
+use language::{Bias, BufferSnapshot, Point, language_settings::FimFormat};
+
+const BYTES_PER_TOKEN_GUESS: usize = 3;
+
+/// The code surrounding the cursor, which the model fills in the middle of.
+#[derive(Debug, PartialEq)]
+pub struct FimExcerpt {
+    pub prefix: String,
+    pub suffix: String,
+}
+
+/// Returns the whole lines around the cursor that fit within the token limit, adding lines above
+/// and below in turn so the model sees both the code leading to the cursor and the code after it.
+///
+/// When the cursor's line doesn't fit on its own, such as in minified code, only the part of it
+/// around the cursor is returned.
+pub fn excerpt_for_cursor_position(
+    position: Point,
+    snapshot: &BufferSnapshot,
+    token_limit: usize,
+) -> FimExcerpt {
+    let line_len = snapshot.line_len(position.row);
+    let mut range = Point::new(position.row, 0)..Point::new(position.row, line_len);
+    let max_line_len = (token_limit * BYTES_PER_TOKEN_GUESS) as u32;
+    let mut remaining_tokens = if line_len > max_line_len {
+        let start = position
+            .column
+            .saturating_sub(max_line_len / 2)
+            .min(line_len - max_line_len);
+        range.start = snapshot.clip_point(Point::new(position.row, start), Bias::Left);
+        range.end =
+            snapshot.clip_point(Point::new(position.row, start + max_line_len), Bias::Right);
+        0
+    } else {
+        token_limit.saturating_sub(guess_token_count(line_len as usize))
+    };
+    loop {
+        let mut expanded = false;
+
+        if remaining_tokens > 0 && range.start.row > 0 {
+            range.start.row -= 1;
+            let line_tokens = guess_token_count(snapshot.line_len(range.start.row) as usize);
+            remaining_tokens = remaining_tokens.saturating_sub(line_tokens);
+            expanded = true;
+        }
+
+        if remaining_tokens > 0 && range.end.row < snapshot.max_point().row {
+            range.end.row += 1;
+            range.end.column = snapshot.line_len(range.end.row);
+            let line_tokens = guess_token_count(range.end.column as usize);
+            remaining_tokens = remaining_tokens.saturating_sub(line_tokens);
+            expanded = true;
+        }
+
+        if !expanded {
+            break;
+        }
+    }
+
+    FimExcerpt {
+        prefix: snapshot.text_for_range(range.start..position).collect(),
+        suffix: snapshot.text_for_range(position..range.end).collect(),
+    }
+}
+
+/// Guesses the format from the name of the model, defaulting to Qwen's, which is also used by
+/// many other recent models.
+pub fn detect_fim_format(model: &str) -> FimFormat {
+    let model = model.to_lowercase();
+    if model.contains("codellama") || model.contains("code-llama") {
+        FimFormat::CodeLlama
+    } else if model.contains("starcoder") {
+        FimFormat::StarCoder
+    } else if model.contains("deepseek") {
+        FimFormat::DeepSeek
+    } else if model.contains("codestral") {
+        FimFormat::Codestral
+    } else {
+        FimFormat::Qwen
+    }
+}
+
+pub fn fim_prompt(format: FimFormat, excerpt: &FimExcerpt) -> String {
+    let FimExcerpt { prefix, suffix } = excerpt;
+    match format {
+        FimFormat::Qwen => format!("<|fim_prefix|>{prefix}<|fim_suffix|>{suffix}<|fim_middle|>"),
+        FimFormat::CodeLlama => format!("<PRE> {prefix} <SUF>{suffix} <MID>"),
+        FimFormat::StarCoder => format!("<fim_prefix>{prefix}<fim_suffix>{suffix}<fim_middle>"),
+        FimFormat::DeepSeek => {
+            format!("<｜fim▁begin｜>{prefix}<｜fim▁hole｜>{suffix}<｜fim▁end｜>")
+        }
+        FimFormat::Codestral => format!("[SUFFIX]{suffix}[PREFIX]{prefix}"),
+    }
+}
+
+/// The tokens that end the middle, with the model's end of text first.
+pub fn stop_tokens(format: FimFormat) -> &'static [&'static str] {
+    match format {
+        FimFormat::Qwen => &[
+            "<|endoftext|>",
+            "<|fim_prefix|>",
+            "<|fim_suffix|>",
+            "<|fim_middle|>",
+            "<|file_sep|>",
+        ],
+        FimFormat::CodeLlama => &["<EOT>", "<PRE>", "<SUF>", "<MID>"],
+        FimFormat::StarCoder => &[
+            "<|endoftext|>",
+            "<fim_prefix>",
+            "<fim_suffix>",
+            "<fim_middle>",
+            "<file_sep>",
+        ],
+        FimFormat::DeepSeek => &[
+            "<｜end▁of▁sentence｜>",
+            "<｜fim▁begin｜>",
+            "<｜fim▁hole｜>",
+            "<｜fim▁end｜>",
+        ],
+        FimFormat::Codestral => &["</s>", "[PREFIX]", "[SUFFIX]"],
+    }
+}
+
+fn guess_token_count(bytes: usize) -> usize {
+    bytes / BYTES_PER_TOKEN_GUESS
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::{App, AppContext as _};
+    use indoc::indoc;
+    use language::Buffer;
+
+    #[gpui::test]
+    fn test_excerpt_for_cursor_position(cx: &mut App) {
+        let text = indoc! {"
+            fn main() {
+                let x = 1;
+                let y = 2;
+                println!(\"{}\", x + y);
+            }
+        "};
+        let buffer = cx.new(|cx| Buffer::local(text, cx));
+        let snapshot = buffer.read(cx).snapshot();
+
+        assert_eq!(
+            excerpt_for_cursor_position(Point::new(2, 12), &snapshot, 1000),
+            FimExcerpt {
+                prefix: "fn main() {\n    let x = 1;\n    let y".into(),
+                suffix: " = 2;\n    println!(\"{}\", x + y);\n}\n".into(),
+            }
+        );
+
+        // Lines are added above and below the cursor in turn until the limit is reached.
+        assert_eq!(
+            excerpt_for_cursor_position(Point::new(2, 12), &snapshot, 10),
+            FimExcerpt {
+                prefix: "    let x = 1;\n    let y".into(),
+                suffix: " = 2;\n    println!(\"{}\", x + y);".into(),
+            }
+        );
+
+        // A line longer than the limit is cut down to the text around the cursor.
+        let buffer = cx.new(|cx| Buffer::local("abcdefghijkl\nmnop", cx));
+        let snapshot = buffer.read(cx).snapshot();
+        assert_eq!(
+            excerpt_for_cursor_position(Point::new(0, 6), &snapshot, 2),
+            FimExcerpt {
+                prefix: "def".into(),
+                suffix: "ghi".into(),
+            }
+        );
+        assert_eq!(
+            excerpt_for_cursor_position(Point::new(0, 11), &snapshot, 2),
+            FimExcerpt {
+                prefix: "ghijk".into(),
+                suffix: "l".into(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_fim_prompt() {
+        let excerpt = FimExcerpt {
+            prefix: "fn add(x: i32, y: i32) -> i32 {\n    ".into(),
+            suffix: "\n}".into(),
+        };
+        assert_eq!(detect_fim_format("qwen2.5-coder:1.5b"), FimFormat::Qwen);
+        assert_eq!(detect_fim_format("codellama:7b-code"), FimFormat::CodeLlama);
+        assert_eq!(
+            fim_prompt(detect_fim_format("qwen2.5-coder:1.5b"), &excerpt),
+            "<|fim_prefix|>fn add(x: i32, y: i32) -> i32 {\n    <|fim_suffix|>\n}<|fim_middle|>"
+        );
+        assert_eq!(
+            fim_prompt(FimFormat::Codestral, &excerpt),
+            "[SUFFIX]\n}[PREFIX]fn add(x: i32, y: i32) -> i32 {\n    "
+        );
+    }
+}
@@ -0,0 +1,477 @@
+mod fim;
+
+use anyhow::{Context as _, Result};
+use edit_prediction::{Direction, EditPrediction, EditPredictionProvider};
+use fim::FimExcerpt;
+use futures::AsyncReadExt as _;
+use gpui::{App, Context, Entity, EntityId, Task};
+use http_client::{AsyncBody, HttpClient, Method, Request as HttpRequest};
+use language::{
+    Anchor, Buffer, Point, ToOffset as _, ToPoint as _,
+    language_settings::{
+        LocalEditPredictionApi, LocalEditPredictionSettings, all_language_settings,
+    },
+};
+use serde::{Deserialize, Serialize};
+use std::{sync::Arc, time::Duration};
+
+pub const DEBOUNCE_TIMEOUT: Duration = Duration::from_millis(150);
+
+/// How many tokens of the code around the cursor to send, which keeps prompts quick to process
+/// for small local models.
+const CONTEXT_TOKEN_LIMIT: usize = 1024;
+
+/// The most stop sequences OpenAI's `/completions` endpoint accepts.
+const MAX_OPEN_AI_STOP_SEQUENCES: usize = 4;
+
+struct LocalPrediction {
+    buffer_id: EntityId,
+    /// Where the cursor was when the prediction was requested, which stays before any text
+    /// typed after it.
+    position: Anchor,
+    text: String,
+}
+
+/// Predicts the code at the cursor with a fill-in-the-middle completion from a model running
+/// locally, such as in Ollama.
+pub struct LocalEditPredictionProvider {
+    http_client: Arc<dyn HttpClient>,
+    prediction: Option<LocalPrediction>,
+    pending_refresh: Option<Task<Result<()>>>,
+}
+
+impl LocalEditPredictionProvider {
+    pub fn new(http_client: Arc<dyn HttpClient>) -> Self {
+        Self {
+            http_client,
+            prediction: None,
+            pending_refresh: None,
+        }
+    }
+}
+
+impl EditPredictionProvider for LocalEditPredictionProvider {
+    fn name() -> &'static str {
+        "local"
+    }
+
+    fn display_name() -> &'static str {
+        "Local Model"
+    }
+
+    fn show_completions_in_menu() -> bool {
+        true
+    }
+
+    fn show_tab_accept_marker() -> bool {
+        true
+    }
+
+    fn supports_jump_to_edit() -> bool {
+        false
+    }
+
+    fn is_enabled(&self, _buffer: &Entity<Buffer>, _cursor_position: Anchor, _cx: &App) -> bool {
+        true
+    }
+
+    fn is_refreshing(&self) -> bool {
+        self.pending_refresh.is_some()
+    }
+
+    fn refresh(
+        &mut self,
+        buffer: Entity<Buffer>,
+        cursor_position: Anchor,
+        debounce: bool,
+        cx: &mut Context<Self>,
+    ) {
+        let http_client = self.http_client.clone();
+        // Replacing the pending refresh drops it, cancelling its request.
+        self.pending_refresh = Some(cx.spawn(async move |this, cx| {
+            if debounce {
+                cx.background_executor().timer(DEBOUNCE_TIMEOUT).await;
+            }
+
+            let (snapshot, settings) = buffer.read_with(cx, |buffer, cx| {
+                (
+                    buffer.snapshot(),
+                    all_language_settings(None, cx)
+                        .edit_predictions
+                        .local
+                        .clone(),
+                )
+            })?;
+            let position = snapshot.anchor_before(cursor_position.to_offset(&snapshot));
+            let excerpt = fim::excerpt_for_cursor_position(
+                cursor_position.to_point(&snapshot),
+                &snapshot,
+                CONTEXT_TOKEN_LIMIT,
+            );
+            let text = request_completion(http_client.as_ref(), &settings, &excerpt).await;
+
+            this.update(cx, |this, cx| {
+                this.pending_refresh = None;
+                // A failed request clears the previous prediction, which was for an earlier
+                // position.
+                let result = match text {
+                    Ok(text) => {
+                        this.prediction = Some(LocalPrediction {
+                            buffer_id: buffer.entity_id(),
+                            position,
+                            text,
+                        });
+                        Ok(())
+                    }
+                    Err(error) => {
+                        this.prediction = None;
+                        Err(error)
+                    }
+                };
+                cx.notify();
+                result
+            })?
+        }));
+    }
+
+    fn cycle(
+        &mut self,
+        _buffer: Entity<Buffer>,
+        _cursor_position: Anchor,
+        _direction: Direction,
+        _cx: &mut Context<Self>,
+    ) {
+    }
+
+    fn accept(&mut self, _cx: &mut Context<Self>) {
+        self.prediction = None;
+    }
+
+    fn discard(&mut self, _cx: &mut Context<Self>) {
+        self.prediction = None;
+    }
+
+    fn suggest(
+        &mut self,
+        buffer: &Entity<Buffer>,
+        cursor_position: Anchor,
+        cx: &mut Context<Self>,
+    ) -> Option<EditPrediction> {
+        let prediction = self.prediction.as_ref()?;
+        if prediction.buffer_id != buffer.entity_id() {
+            return None;
+        }
+        let buffer = buffer.read(cx);
+        if !prediction.position.is_valid(buffer) {
+            return None;
+        }
+
+        // Keep showing the rest of the prediction while the user types it.
+        let start = prediction.position.to_offset(buffer);
+        let cursor = cursor_position.to_offset(buffer);
+        if cursor < start {
+            return None;
+        }
+        let typed_text = buffer.text_for_range(start..cursor).collect::<String>();
+        let text = prediction.text.strip_prefix(typed_text.as_str())?;
+
+        // Models sometimes repeat the rest of the line, such as closing brackets.
+        let cursor_point = cursor_position.to_point(buffer);
+        let line_end = Point::new(cursor_point.row, buffer.line_len(cursor_point.row));
+        let rest_of_line = buffer
+            .text_for_range(cursor_point..line_end)
+            .collect::<String>();
+        let text = text.strip_suffix(rest_of_line.as_str()).unwrap_or(text);
+
+        let text = text.trim_end();
+        if text.trim().is_empty() {
+            return None;
+        }
+        let position = cursor_position.bias_right(buffer);
+        Some(EditPrediction::Local {
+            id: None,
+            edits: vec![(position..position, text.to_string())],
+            edit_preview: None,
+        })
+    }
+}
+
+async fn request_completion(
+    http_client: &dyn HttpClient,
+    settings: &LocalEditPredictionSettings,
+    excerpt: &FimExcerpt,
+) -> Result<String> {
+    let fim_format = settings
+        .fim_format
+        .unwrap_or_else(|| fim::detect_fim_format(&settings.model));
+    let prompt = fim::fim_prompt(fim_format, excerpt);
+    let mut stop = fim::stop_tokens(fim_format)
+        .iter()
+        .map(|token| token.to_string())
+        .collect::<Vec<_>>();
+
+    match settings.api {
+        LocalEditPredictionApi::Ollama => {
+            let request = ollama::GenerateRequest {
+                model: settings.model.clone(),
+                prompt,
+                raw: true,
+                stream: false,
+                keep_alive: Default::default(),
+                options: Some(ollama::ChatOptions {
+                    num_predict: Some(settings.max_tokens as isize),
+                    stop: Some(stop),
+                    temperature: Some(0.),
+                    ..Default::default()
+                }),
+            };
+            ollama::generate(http_client, &settings.api_url, None, request).await
+        }
+        LocalEditPredictionApi::OpenAiCompatible => {
+            stop.truncate(MAX_OPEN_AI_STOP_SEQUENCES);
+            let request = CompletionRequest {
+                model: &settings.model,
+                prompt,
+                max_tokens: settings.max_tokens,
+                temperature: 0.,
+                stop,
+                stream: false,
+            };
+            complete_open_ai_compatible(http_client, &settings.api_url, request).await
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+struct CompletionRequest<'a> {
+    model: &'a str,
+    prompt: String,
+    max_tokens: u32,
+    temperature: f32,
+    stop: Vec<String>,
+    stream: bool,
+}
+
+#[derive(Deserialize, Debug)]
+struct CompletionResponse {
+    choices: Vec<CompletionChoice>,
+}
+
+#[derive(Deserialize, Debug)]
+struct CompletionChoice {
+    text: String,
+}
+
+async fn complete_open_ai_compatible(
+    http_client: &dyn HttpClient,
+    api_url: &str,
+    request: CompletionRequest<'_>,
+) -> Result<String> {
+    let request = HttpRequest::builder()
+        .method(Method::POST)
+        .uri(format!("{api_url}/v1/completions"))
+        .header("Content-Type", "application/json")
+        .body(AsyncBody::from(serde_json::to_string(&request)?))?;
+
+    let mut response = http_client.send(request).await?;
+    let mut body = String::new();
+    response.body_mut().read_to_string(&mut body).await?;
+
+    anyhow::ensure!(
+        response.status().is_success(),
+        "Failed to request completion: {} {}",
+        response.status(),
+        body,
+    );
+    let response: CompletionResponse =
+        serde_json::from_str(&body).context("Unable to parse completion response")?;
+    Ok(response
+        .choices
+        .into_iter()
+        .next()
+        .map(|choice| choice.text)
+        .unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::{
+        AsyncReadExt as _, StreamExt as _,
+        channel::{mpsc, oneshot},
+    };
+    use gpui::{AppContext as _, TestAppContext};
+    use http_client::{FakeHttpClient, Response};
+    use language::language_settings::FimFormat;
+    use serde_json::json;
+    use settings::SettingsStore;
+    use std::sync::Mutex;
+
+    #[gpui::test]
+    fn test_suggest(cx: &mut App) {
+        let buffer = cx.new(|cx| Buffer::local("let x = ;\n", cx));
+        let provider =
+            cx.new(|_| LocalEditPredictionProvider::new(FakeHttpClient::with_404_response()));
+        let set_prediction = |text: &str, cx: &mut App| {
+            let position = buffer.read(cx).anchor_before(8);
+            provider.update(cx, |provider, _| {
+                provider.prediction = Some(LocalPrediction {
+                    buffer_id: buffer.entity_id(),
+                    position,
+                    text: text.into(),
+                })
+            });
+        };
+        let suggest = |offset: usize, cx: &mut App| {
+            let cursor_position = buffer.read(cx).anchor_after(offset);
+            let prediction = provider.update(cx, |provider, cx| {
+                provider.suggest(&buffer, cursor_position, cx)
+            });
+            match prediction {
+                Some(EditPrediction::Local { edits, .. }) => Some(edits[0].1.clone()),
+                _ => None,
+            }
+        };
+
+        // The rest of the line isn't repeated.
+        set_prediction("42;", cx);
+        assert_eq!(suggest(8, cx).as_deref(), Some("42"));
+
+        // The prediction shrinks while the user types it, and goes away when they don't.
+        buffer.update(cx, |buffer, cx| buffer.edit([(8..8, "4")], None, cx));
+        assert_eq!(suggest(9, cx).as_deref(), Some("2"));
+        buffer.update(cx, |buffer, cx| buffer.edit([(9..9, "3")], None, cx));
+        assert_eq!(suggest(10, cx), None);
+
+        provider.update(cx, |provider, cx| provider.accept(cx));
+        assert_eq!(suggest(10, cx), None);
+    }
+
+    #[gpui::test]
+    async fn test_request_completion() {
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let http_client = FakeHttpClient::create({
+            let requests = requests.clone();
+            move |request| {
+                let requests = requests.clone();
+                async move {
+                    let uri = request.uri().to_string();
+                    let mut body = String::new();
+                    request.into_body().read_to_string(&mut body).await?;
+                    requests.lock().unwrap().push((
+                        uri.clone(),
+                        serde_json::from_str::<serde_json::Value>(&body)?,
+                    ));
+
+                    let response = if uri.ends_with("/api/generate") {
+                        json!({ "response": "x + y" })
+                    } else {
+                        json!({ "choices": [{ "text": "x + y" }] })
+                    };
+                    Ok(Response::builder()
+                        .status(200)
+                        .body(response.to_string().into())?)
+                }
+            }
+        });
+        let excerpt = FimExcerpt {
+            prefix: "fn add(x: i32, y: i32) -> i32 {\n    ".into(),
+            suffix: "\n}".into(),
+        };
+        let mut settings = LocalEditPredictionSettings {
+            api: LocalEditPredictionApi::Ollama,
+            api_url: "http://localhost:11434".into(),
+            model: "qwen2.5-coder:1.5b".into(),
+            fim_format: None,
+            max_tokens: 64,
+        };
+
+        let text = request_completion(http_client.as_ref(), &settings, &excerpt).await;
+        assert_eq!(text.unwrap(), "x + y");
+        let (uri, body) = requests.lock().unwrap().pop().unwrap();
+        assert_eq!(uri, "http://localhost:11434/api/generate");
+        assert_eq!(body["model"], "qwen2.5-coder:1.5b");
+        assert_eq!(
+            body["prompt"],
+            fim::fim_prompt(FimFormat::Qwen, &excerpt).as_str()
+        );
+        assert_eq!(body["raw"], true);
+        assert_eq!(body["options"]["num_predict"], 64);
+        assert_eq!(
+            body["options"]["stop"],
+            json!(fim::stop_tokens(FimFormat::Qwen))
+        );
+
+        settings.api = LocalEditPredictionApi::OpenAiCompatible;
+        settings.api_url = "http://localhost:8080".into();
+        settings.fim_format = Some(FimFormat::CodeLlama);
+        let text = request_completion(http_client.as_ref(), &settings, &excerpt).await;
+        assert_eq!(text.unwrap(), "x + y");
+        let (uri, body) = requests.lock().unwrap().pop().unwrap();
+        assert_eq!(uri, "http://localhost:8080/v1/completions");
+        assert_eq!(
+            body["prompt"],
+            fim::fim_prompt(FimFormat::CodeLlama, &excerpt).as_str()
+        );
+        assert_eq!(body["max_tokens"], 64);
+        // Only as many stop tokens as the endpoint accepts are sent, starting with the end of
+        // text.
+        assert_eq!(body["stop"], json!(["<EOT>", "<PRE>", "<SUF>", "<MID>"]));
+    }
+
+    #[gpui::test]
+    async fn test_refresh(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            language::init(cx);
+        });
+
+        // Each request waits for the test to respond to it.
+        let (requests_tx, mut requests_rx) = mpsc::unbounded::<oneshot::Sender<String>>();
+        let http_client = FakeHttpClient::create(move |_| {
+            let requests_tx = requests_tx.clone();
+            async move {
+                let (response_tx, response_rx) = oneshot::channel();
+                requests_tx.unbounded_send(response_tx)?;
+                let text = response_rx.await?;
+                Ok(Response::builder()
+                    .status(200)
+                    .body(json!({ "response": text }).to_string().into())?)
+            }
+        });
+        let buffer = cx.new(|cx| Buffer::local("let x = ;\n", cx));
+        let provider = cx.new(|_| LocalEditPredictionProvider::new(http_client));
+        let refresh = |cx: &mut TestAppContext| {
+            provider.update(cx, |provider, cx| {
+                let cursor_position = buffer.read(cx).anchor_before(8);
+                provider.refresh(buffer.clone(), cursor_position, false, cx);
+            });
+            cx.run_until_parked();
+        };
+        let prediction_text = |cx: &mut TestAppContext| {
+            provider.read_with(cx, |provider, _| {
+                provider
+                    .prediction
+                    .as_ref()
+                    .map(|prediction| prediction.text.clone())
+            })
+        };
+
+        // Refreshing again cancels the pending request.
+        refresh(cx);
+        let first_response = requests_rx.next().await.unwrap();
+        refresh(cx);
+        assert!(first_response.is_canceled());
+        requests_rx.next().await.unwrap().send("42".into()).unwrap();
+        cx.run_until_parked();
+        assert_eq!(prediction_text(cx).as_deref(), Some("42"));
+        assert!(!provider.read_with(cx, |provider, _| provider.is_refreshing()));
+
+        // A failed request clears the previous prediction.
+        refresh(cx);
+        drop(requests_rx.next().await.unwrap());
+        cx.run_until_parked();
+        assert_eq!(prediction_text(cx), None);
+    }
+}
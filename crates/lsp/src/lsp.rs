@@ -698,9 +698,12 @@ impl LanguageServer {
                     }),
                     file_operations: Some(WorkspaceFileOperationsClientCapabilities {
                         dynamic_registration: Some(true),
+                        did_create: Some(true),
+                        will_create: Some(true),
                         did_rename: Some(true),
                         will_rename: Some(true),
-                        ..WorkspaceFileOperationsClientCapabilities::default()
+                        did_delete: Some(true),
+                        will_delete: Some(true),
                     }),
                     apply_edit: Some(true),
                     execute_command: Some(ExecuteCommandClientCapabilities {
@@ -854,6 +857,10 @@ impl LanguageServer {
                     color_provider: Some(DocumentColorClientCapabilities {
                         dynamic_registration: Some(true),
                     }),
+                    document_link: Some(DocumentLinkClientCapabilities {
+                        dynamic_registration: Some(true),
+                        tooltip_support: Some(true),
+                    }),
                     ..TextDocumentClientCapabilities::default()
                 }),
                 experimental: Some(json!({
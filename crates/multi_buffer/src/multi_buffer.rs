@@ -5559,6 +5559,26 @@ impl MultiBufferSnapshot {
         )
     }
 
+    /// Returns the ranges of the brackets in the given `range` that are colorized by their
+    /// nesting depth, along with that depth.
+    pub fn colorized_bracket_ranges<T: ToOffset>(
+        &self,
+        range: Range<T>,
+    ) -> impl Iterator<Item = (Range<usize>, u32)> + '_ {
+        let range = range.start.to_offset(self)..range.end.to_offset(self);
+        self.lift_buffer_metadata(range, |buffer, range| {
+            let mut brackets = buffer
+                .colorized_bracket_ranges(range.clone())
+                .into_iter()
+                .flat_map(|(pair, depth)| [(pair.open_range, depth), (pair.close_range, depth)])
+                .filter(|(bracket, _)| range.start <= bracket.start && bracket.end <= range.end)
+                .collect::<Vec<_>>();
+            brackets.sort_by_key(|(bracket, _)| bracket.start);
+            Some(brackets.into_iter())
+        })
+        .map(|(range, depth, _)| (range, depth))
+    }
+
     pub fn redacted_ranges<'a, T: ToOffset>(
         &'a self,
         range: Range<T>,
@@ -279,6 +279,98 @@ pub async fn get_models(
     Ok(response.models)
 }
 
+#[derive(Serialize, Debug)]
+pub struct EmbedRequest {
+    pub model: String,
+    pub input: Vec<String>,
+    pub keep_alive: KeepAlive,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct EmbedResponse {
+    pub embeddings: Vec<Vec<f32>>,
+}
+
+/// Computes an embedding for each of the request's inputs, in the same order.
+pub async fn embed(
+    client: &dyn HttpClient,
+    api_url: &str,
+    api_key: Option<&str>,
+    request: EmbedRequest,
+) -> Result<Vec<Vec<f32>>> {
+    let uri = format!("{api_url}/api/embed");
+    let request = HttpRequest::builder()
+        .method(Method::POST)
+        .uri(uri)
+        .header("Content-Type", "application/json")
+        .when_some(api_key, |builder, api_key| {
+            builder.header("Authorization", format!("Bearer {api_key}"))
+        })
+        .body(AsyncBody::from(serde_json::to_string(&request)?))?;
+
+    let mut response = client.send(request).await?;
+    let mut body = String::new();
+    response.body_mut().read_to_string(&mut body).await?;
+
+    anyhow::ensure!(
+        response.status().is_success(),
+        "Failed to connect to Ollama API: {} {}",
+        response.status(),
+        body,
+    );
+    let response: EmbedResponse =
+        serde_json::from_str(&body).context("Unable to parse Ollama embed response")?;
+    Ok(response.embeddings)
+}
+
+#[derive(Serialize, Debug)]
+pub struct GenerateRequest {
+    pub model: String,
+    pub prompt: String,
+    /// Whether to send the prompt as-is, without applying the model's prompt template.
+    pub raw: bool,
+    pub stream: bool,
+    pub keep_alive: KeepAlive,
+    pub options: Option<ChatOptions>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct GenerateResponse {
+    pub response: String,
+}
+
+/// Generates a completion of the request's prompt, waiting for the whole of it.
+pub async fn generate(
+    client: &dyn HttpClient,
+    api_url: &str,
+    api_key: Option<&str>,
+    request: GenerateRequest,
+) -> Result<String> {
+    let uri = format!("{api_url}/api/generate");
+    let request = HttpRequest::builder()
+        .method(Method::POST)
+        .uri(uri)
+        .header("Content-Type", "application/json")
+        .when_some(api_key, |builder, api_key| {
+            builder.header("Authorization", format!("Bearer {api_key}"))
+        })
+        .body(AsyncBody::from(serde_json::to_string(&request)?))?;
+
+    let mut response = client.send(request).await?;
+    let mut body = String::new();
+    response.body_mut().read_to_string(&mut body).await?;
+
+    anyhow::ensure!(
+        response.status().is_success(),
+        "Failed to connect to Ollama API: {} {}",
+        response.status(),
+        body,
+    );
+    let response: GenerateResponse =
+        serde_json::from_str(&body).context("Unable to parse Ollama generate response")?;
+    Ok(response.response)
+}
+
 /// Fetch details of a model, used to determine model capabilities
 pub async fn show_model(
     client: &dyn HttpClient,
@@ -472,6 +564,37 @@ mod tests {
         assert!(result.capabilities.contains(&"completion".to_string()));
     }
 
+    #[test]
+    fn parse_embed_response() {
+        let response = serde_json::json!({
+            "model": "nomic-embed-text",
+            "embeddings": [[0.1, -0.2, 0.3], [0.4, 0.5, -0.6]],
+            "total_duration": 14143917,
+            "load_duration": 1019500,
+            "prompt_eval_count": 8
+        });
+
+        let result: EmbedResponse = serde_json::from_value(response).unwrap();
+        assert_eq!(result.embeddings.len(), 2);
+        assert_eq!(result.embeddings[1], vec![0.4, 0.5, -0.6]);
+    }
+
+    #[test]
+    fn parse_generate_response() {
+        let response = serde_json::json!({
+            "model": "qwen2.5-coder:1.5b",
+            "created_at": "2025-01-01T00:00:00.000000Z",
+            "response": "x + y\n}",
+            "done": true,
+            "done_reason": "stop",
+            "total_duration": 14143917,
+            "eval_count": 5
+        });
+
+        let result: GenerateResponse = serde_json::from_value(response).unwrap();
+        assert_eq!(result.response, "x + y\n}");
+    }
+
     #[test]
     fn serialize_chat_request_with_images() {
         let base64_image = "iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAYAAAAfFcSJAAAADUlEQVR42mNk+M9QDwADhgGAWjR9awAAAABJRU5ErkJggg==";
@@ -1151,6 +1151,75 @@ impl BufferStore {
         rx
     }
 
+    /// Opens the buffers for the files of a [`SearchScope`](crate::search::SearchScope) that pass
+    /// the query's path filters, as candidates for a search restricted to that scope.
+    pub fn find_scoped_search_candidates(
+        &mut self,
+        query: &SearchQuery,
+        project_paths: Vec<ProjectPath>,
+        limit: usize,
+        cx: &mut Context<Self>,
+    ) -> Receiver<Entity<Buffer>> {
+        let (tx, rx) = smol::channel::unbounded();
+        let worktree_store = self.worktree_store.read(cx);
+        let project_paths = project_paths
+            .into_iter()
+            .filter(|project_path| {
+                let Some(worktree) = worktree_store.worktree_for_id(project_path.worktree_id, cx)
+                else {
+                    return false;
+                };
+                let worktree = worktree.read(cx);
+                let Some(entry) = worktree.entry_for_path(&project_path.path) else {
+                    return false;
+                };
+                if !worktree.is_visible()
+                    || !entry.is_file()
+                    || (entry.is_ignored && !query.include_ignored())
+                {
+                    return false;
+                }
+                if query.filters_path() {
+                    let matched_path = if query.match_full_paths() {
+                        let mut full_path = worktree.root_name().as_std_path().to_owned();
+                        full_path.push(project_path.path.as_std_path());
+                        query.match_path(&full_path)
+                    } else {
+                        query.match_path(project_path.path.as_std_path())
+                    };
+                    if !matched_path {
+                        return false;
+                    }
+                }
+                self.get_by_path(project_path).is_none_or(|buffer| {
+                    !self
+                        .non_searchable_buffers
+                        .contains(&buffer.read(cx).remote_id())
+                })
+            })
+            .take(limit)
+            .collect::<Vec<_>>();
+
+        cx.spawn(async move |this, cx| {
+            let buffers = this.update(cx, |this, cx| {
+                project_paths
+                    .into_iter()
+                    .map(|project_path| this.open_buffer(project_path, cx))
+                    .collect::<Vec<_>>()
+            })?;
+            for buffer_task in buffers {
+                if let Some(buffer) = buffer_task.await.log_err()
+                    && tx.send(buffer).await.is_err()
+                {
+                    break;
+                }
+            }
+            anyhow::Ok(())
+        })
+        .detach();
+        rx
+    }
+
     fn on_buffer_event(
         &mut self,
         buffer: Entity<Buffer>,
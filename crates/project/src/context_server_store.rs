@@ -5,17 +5,21 @@ use std::sync::Arc;
 
 use anyhow::{Context as _, Result};
 use collections::{HashMap, HashSet};
-use context_server::{ContextServer, ContextServerCommand, ContextServerId};
+use context_server::{
+    ContextServer, ContextServerCommand, ContextServerHttpConfig, ContextServerId,
+    types::{self, Notification as _, Request as _},
+};
 use futures::{FutureExt as _, future::join_all};
 use gpui::{App, AsyncApp, Context, Entity, EventEmitter, Subscription, Task, WeakEntity, actions};
 use registry::ContextServerDescriptorRegistry;
 use settings::{Settings as _, SettingsStore};
+use url::Url;
 use util::{ResultExt as _, rel_path::RelPath};
 
 use crate::{
     Project,
     project_settings::{ContextServerSettings, ProjectSettings},
-    worktree_store::WorktreeStore,
+    worktree_store::{WorktreeStore, WorktreeStoreEvent},
 };
 
 pub fn init(cx: &mut App) {
@@ -99,13 +103,18 @@ pub enum ContextServerConfiguration {
         command: ContextServerCommand,
         settings: serde_json::Value,
     },
+    Http {
+        http: ContextServerHttpConfig,
+    },
 }
 
 impl ContextServerConfiguration {
-    pub fn command(&self) -> &ContextServerCommand {
+    /// The command that runs the server, unless the server is reached over HTTP.
+    pub fn command(&self) -> Option<&ContextServerCommand> {
         match self {
-            ContextServerConfiguration::Custom { command } => command,
-            ContextServerConfiguration::Extension { command, .. } => command,
+            ContextServerConfiguration::Custom { command } => Some(command),
+            ContextServerConfiguration::Extension { command, .. } => Some(command),
+            ContextServerConfiguration::Http { .. } => None,
         }
     }
 
@@ -121,6 +130,9 @@ impl ContextServerConfiguration {
                 enabled: _,
                 command,
             } => Some(ContextServerConfiguration::Custom { command }),
+            ContextServerSettings::Http { enabled: _, http } => {
+                Some(ContextServerConfiguration::Http { http })
+            }
             ContextServerSettings::Extension {
                 enabled: _,
                 settings,
@@ -141,6 +153,13 @@ impl ContextServerConfiguration {
 pub type ContextServerFactory =
     Box<dyn Fn(ContextServerId, Arc<ContextServerConfiguration>) -> Arc<ContextServer>>;
 
+/// Answers a request that the given context server sent to Zed, given its params.
+pub type ContextServerRequestHandler = Arc<
+    dyn Send
+        + Sync
+        + Fn(ContextServerId, serde_json::Value, AsyncApp) -> Task<Result<serde_json::Value>>,
+>;
+
 pub struct ContextServerStore {
     context_server_settings: HashMap<Arc<str>, ContextServerSettings>,
     servers: HashMap<ContextServerId, ContextServerState>,
@@ -149,6 +168,10 @@ pub struct ContextServerStore {
     registry: Entity<ContextServerDescriptorRegistry>,
     update_servers_task: Option<Task<Result<()>>>,
     context_server_factory: Option<ContextServerFactory>,
+    request_handlers: HashMap<&'static str, ContextServerRequestHandler>,
+    /// The number of subscribers to each resource, which the server is only asked to stop
+    /// reporting changes to once the last one unsubscribes.
+    resource_subscriptions: HashMap<(ContextServerId, String), usize>,
     needs_server_update: bool,
     _subscriptions: Vec<Subscription>,
 }
@@ -158,6 +181,11 @@ pub enum Event {
         server_id: ContextServerId,
         status: ContextServerStatus,
     },
+    /// A server reported that the contents of a resource it was subscribed to changed.
+    ResourceUpdated {
+        server_id: ContextServerId,
+        uri: String,
+    },
 }
 
 impl EventEmitter<Event> for ContextServerStore {}
@@ -223,7 +251,7 @@ impl ContextServerStore {
         weak_project: WeakEntity<Project>,
         cx: &mut Context<Self>,
     ) -> Self {
-        let subscriptions = if maintain_server_loop {
+        let mut subscriptions = if maintain_server_loop {
             vec![
                 cx.observe(&registry, |this, _registry, cx| {
                     this.available_context_servers_changed(cx);
@@ -240,6 +268,20 @@ impl ContextServerStore {
         } else {
             Vec::new()
         };
+        subscriptions.push(cx.subscribe(&worktree_store, |this, _, event, cx| {
+            let roots_changed = match event {
+                WorktreeStoreEvent::WorktreeAdded(worktree) => worktree.read(cx).is_visible(),
+                WorktreeStoreEvent::WorktreeRemoved(..) => true,
+                _ => false,
+            };
+            if roots_changed {
+                this.notify_roots_changed();
+            }
+        }));
+        let request_handlers = HashMap::from_iter([(
+            types::requests::ListRoots::METHOD,
+            Self::list_roots_handler(worktree_store.downgrade()),
+        )]);
 
         let mut this = Self {
             _subscriptions: subscriptions,
@@ -252,6 +294,8 @@ impl ContextServerStore {
             servers: HashMap::default(),
             update_servers_task: None,
             context_server_factory,
+            request_handlers,
+            resource_subscriptions: HashMap::default(),
         };
         if maintain_server_loop {
             this.available_context_servers_changed(cx);
@@ -401,6 +445,27 @@ impl ContextServerStore {
         ) {
             self.stop_server(&id, cx).log_err();
         }
+        for (method, handler) in &self.request_handlers {
+            server.set_request_handler(*method, Self::server_request_handler(&server, handler));
+        }
+        server.set_notification_handler(types::notifications::ResourcesUpdated::METHOD, {
+            let this = cx.weak_entity();
+            let server_id = id.clone();
+            Arc::new(move |params, mut cx| {
+                let Some(params) =
+                    serde_json::from_value::<types::ResourcesUpdatedParams>(params).log_err()
+                else {
+                    return;
+                };
+                this.update(&mut cx, |_, cx| {
+                    cx.emit(Event::ResourceUpdated {
+                        server_id: server_id.clone(),
+                        uri: params.uri,
+                    })
+                })
+                .ok();
+            })
+        });
 
         let task = cx.spawn({
             let id = server.id();
@@ -412,6 +477,17 @@ impl ContextServerStore {
                         debug_assert!(server.client().is_some());
 
                         this.update(cx, |this, cx| {
+                            // A restarted server forgot the subscriptions made to it before.
+                            for (server_id, uri) in this.resource_subscriptions.keys() {
+                                if *server_id == id {
+                                    cx.background_spawn(set_resource_subscription(
+                                        server.clone(),
+                                        uri.clone(),
+                                        true,
+                                    ))
+                                    .detach_and_log_err(cx);
+                                }
+                            }
                             this.update_server_state(
                                 id.clone(),
                                 ContextServerState::Running {
@@ -492,14 +568,131 @@ impl ContextServerStore {
         if let Some(factory) = self.context_server_factory.as_ref() {
             factory(id, configuration)
         } else {
-            Arc::new(ContextServer::stdio(
-                id,
-                configuration.command().clone(),
-                root_path,
-            ))
+            match configuration.as_ref() {
+                ContextServerConfiguration::Custom { command }
+                | ContextServerConfiguration::Extension { command, .. } => {
+                    Arc::new(ContextServer::stdio(id, command.clone(), root_path))
+                }
+                ContextServerConfiguration::Http { http } => {
+                    Arc::new(ContextServer::http(id, http.clone(), cx.http_client()))
+                }
+            }
+        }
+    }
+
+    /// Subscribes to changes to a resource of a running server, which are reported with
+    /// [`Event::ResourceUpdated`]. Each call must be matched by a call to
+    /// [`Self::unsubscribe_from_resource`], and the server is only asked to subscribe once.
+    pub fn subscribe_to_resource(
+        &mut self,
+        server_id: &ContextServerId,
+        uri: String,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        let Some(server) = self.get_running_server(server_id) else {
+            return Task::ready(Err(anyhow::anyhow!(
+                "MCP server {server_id} is not running"
+            )));
+        };
+        let subscribers = self
+            .resource_subscriptions
+            .entry((server_id.clone(), uri.clone()))
+            .or_default();
+        *subscribers += 1;
+        if *subscribers > 1 {
+            return Task::ready(Ok(()));
+        }
+        cx.background_spawn(set_resource_subscription(server, uri, true))
+    }
+
+    /// Releases a subscription taken with [`Self::subscribe_to_resource`], unsubscribing from the
+    /// resource once it has no subscribers left.
+    pub fn unsubscribe_from_resource(
+        &mut self,
+        server_id: &ContextServerId,
+        uri: String,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        let key = (server_id.clone(), uri);
+        let Some(subscribers) = self.resource_subscriptions.get_mut(&key) else {
+            return Task::ready(Ok(()));
+        };
+        *subscribers -= 1;
+        if *subscribers > 0 {
+            return Task::ready(Ok(()));
+        }
+        self.resource_subscriptions.remove(&key);
+        let (_, uri) = key;
+        match self.get_running_server(server_id) {
+            Some(server) => cx.background_spawn(set_resource_subscription(server, uri, false)),
+            None => Task::ready(Ok(())),
+        }
+    }
+
+    /// Answers requests for the given method from every context server with the given handler.
+    ///
+    /// Servers that are already running only advertise the capabilities the handler provides
+    /// once they are restarted.
+    pub fn set_request_handler(
+        &mut self,
+        method: &'static str,
+        handler: ContextServerRequestHandler,
+    ) {
+        for state in self.servers.values() {
+            let server = state.server();
+            server.set_request_handler(method, Self::server_request_handler(&server, &handler));
+        }
+        self.request_handlers.insert(method, handler);
+    }
+
+    /// Answers `roots/list` with the project's visible worktrees, so that servers
+    /// scoped to the filesystem can follow the folders that are open.
+    fn list_roots_handler(
+        worktree_store: WeakEntity<WorktreeStore>,
+    ) -> ContextServerRequestHandler {
+        Arc::new(move |_, _, cx| {
+            let roots = worktree_store.read_with(&cx, |worktree_store, cx| {
+                worktree_store
+                    .visible_worktrees(cx)
+                    .filter_map(|worktree| {
+                        let worktree = worktree.read(cx);
+                        Some(types::Root {
+                            uri: Url::from_file_path(worktree.abs_path()).ok()?,
+                            name: Some(worktree.root_name_str().to_string()),
+                        })
+                    })
+                    .collect()
+            });
+            Task::ready(roots.and_then(|roots| {
+                Ok(serde_json::to_value(types::ListRootsResponse {
+                    roots,
+                    meta: None,
+                })?)
+            }))
+        })
+    }
+
+    fn notify_roots_changed(&self) {
+        for state in self.servers.values() {
+            if let ContextServerState::Running { server, .. } = state
+                && let Some(client) = server.client()
+            {
+                client
+                    .notify::<types::notifications::RootsListChanged>(())
+                    .log_err();
+            }
         }
     }
 
+    fn server_request_handler(
+        server: &ContextServer,
+        handler: &ContextServerRequestHandler,
+    ) -> context_server::client::RequestHandler {
+        let id = server.id();
+        let handler = handler.clone();
+        Arc::new(move |params, cx| handler(id.clone(), params, cx))
+    }
+
     fn resolve_context_server_settings<'a>(
         worktree_store: &'a Entity<WorktreeStore>,
         cx: &'a App,
@@ -636,6 +829,43 @@ impl ContextServerStore {
     }
 }
 
+/// Asks the server to start or stop reporting changes to a resource, if it supports it.
+async fn set_resource_subscription(
+    server: Arc<ContextServer>,
+    uri: String,
+    subscribe: bool,
+) -> Result<()> {
+    let Some(client) = server.client() else {
+        return Ok(());
+    };
+    let can_subscribe = client
+        .initialize
+        .capabilities
+        .resources
+        .as_ref()
+        .and_then(|resources| resources.subscribe)
+        .unwrap_or(false);
+    if !can_subscribe {
+        return Ok(());
+    }
+    let uri = Url::parse(&uri)?;
+    if subscribe {
+        client
+            .request::<types::requests::ResourcesSubscribe>(types::ResourcesSubscribeParams {
+                uri,
+                meta: None,
+            })
+            .await
+    } else {
+        client
+            .request::<types::requests::ResourcesUnsubscribe>(types::ResourcesUnsubscribeParams {
+                uri,
+                meta: None,
+            })
+            .await
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -643,7 +873,7 @@ mod tests {
         FakeFs, Project, context_server_store::registry::ContextServerDescriptor,
         project_settings::ProjectSettings,
     };
-    use context_server::test::create_fake_transport;
+    use context_server::test::{FakeTransport, create_fake_transport};
     use gpui::{AppContext, TestAppContext, UpdateGlobal as _};
     use serde_json::json;
     use std::{cell::RefCell, path::PathBuf, rc::Rc};
@@ -1237,6 +1467,202 @@ mod tests {
         }
     }
 
+    #[gpui::test]
+    async fn test_context_server_roots(cx: &mut TestAppContext) {
+        const SERVER_1_ID: &str = "mcp-1";
+
+        let (fs, project) = setup_context_server_test(
+            cx,
+            json!({"code.rs": ""}),
+            vec![(SERVER_1_ID.into(), dummy_server_settings())],
+        )
+        .await;
+        fs.insert_tree(path!("/other"), json!({"lib.rs": ""})).await;
+
+        let registry = cx.new(|_| ContextServerDescriptorRegistry::new());
+        let store = cx.new(|cx| {
+            ContextServerStore::test(
+                registry.clone(),
+                project.read(cx).worktree_store(),
+                project.downgrade(),
+                cx,
+            )
+        });
+
+        let roots_capability = Arc::new(std::sync::Mutex::new(None));
+        let transport = FakeTransport::new(cx.executor())
+            .on_request::<types::requests::Initialize, _>({
+                let roots_capability = roots_capability.clone();
+                move |params| {
+                    *roots_capability.lock().unwrap() = params.capabilities.roots;
+                    async move {
+                        types::InitializeResponse {
+                            protocol_version: types::ProtocolVersion(
+                                types::LATEST_PROTOCOL_VERSION.to_string(),
+                            ),
+                            server_info: types::Implementation {
+                                name: SERVER_1_ID.to_string(),
+                                version: "1.0.0".to_string(),
+                            },
+                            capabilities: types::ServerCapabilities::default(),
+                            meta: None,
+                        }
+                    }
+                }
+            });
+        let server_1_id = ContextServerId(SERVER_1_ID.into());
+        let server_1 = Arc::new(ContextServer::new(server_1_id.clone(), Arc::new(transport)));
+        store.update(cx, |store, cx| store.start_server(server_1, cx));
+        cx.run_until_parked();
+
+        assert_eq!(
+            roots_capability
+                .lock()
+                .unwrap()
+                .as_ref()
+                .and_then(|roots| roots.list_changed),
+            Some(true)
+        );
+
+        let list_roots = |cx: &mut TestAppContext| {
+            let handler = store.read_with(cx, |store, _| {
+                store.request_handlers[types::requests::ListRoots::METHOD].clone()
+            });
+            let response = cx
+                .update(|cx| handler(server_1_id.clone(), serde_json::Value::Null, cx.to_async()));
+            async move {
+                let response: types::ListRootsResponse =
+                    serde_json::from_value(response.await.unwrap()).unwrap();
+                response
+                    .roots
+                    .into_iter()
+                    .map(|root| (root.uri.to_file_path().unwrap(), root.name.unwrap()))
+                    .collect::<Vec<_>>()
+            }
+        };
+
+        assert_eq!(
+            list_roots(cx).await,
+            [(PathBuf::from(path!("/test")), "test".to_string())]
+        );
+
+        project
+            .update(cx, |project, cx| {
+                project.find_or_create_worktree(path!("/other"), true, cx)
+            })
+            .await
+            .unwrap();
+        cx.run_until_parked();
+        assert_eq!(
+            list_roots(cx).await,
+            [
+                (PathBuf::from(path!("/test")), "test".to_string()),
+                (PathBuf::from(path!("/other")), "other".to_string()),
+            ]
+        );
+    }
+
+    #[gpui::test]
+    async fn test_resource_subscriptions_are_reference_counted(cx: &mut TestAppContext) {
+        const SERVER_1_ID: &str = "mcp-1";
+        const URI: &str = "file:///notes.md";
+
+        let (_fs, project) = setup_context_server_test(
+            cx,
+            json!({"code.rs": ""}),
+            vec![(SERVER_1_ID.into(), dummy_server_settings())],
+        )
+        .await;
+        let registry = cx.new(|_| ContextServerDescriptorRegistry::new());
+        let store = cx.new(|cx| {
+            ContextServerStore::test(
+                registry.clone(),
+                project.read(cx).worktree_store(),
+                project.downgrade(),
+                cx,
+            )
+        });
+
+        let requests = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let transport = FakeTransport::new(cx.executor())
+            .on_request::<types::requests::Initialize, _>(|_| async move {
+                types::InitializeResponse {
+                    protocol_version: types::ProtocolVersion(
+                        types::LATEST_PROTOCOL_VERSION.to_string(),
+                    ),
+                    server_info: types::Implementation {
+                        name: SERVER_1_ID.to_string(),
+                        version: "1.0.0".to_string(),
+                    },
+                    capabilities: types::ServerCapabilities {
+                        resources: Some(types::ResourcesCapabilities {
+                            subscribe: Some(true),
+                            list_changed: None,
+                        }),
+                        ..types::ServerCapabilities::default()
+                    },
+                    meta: None,
+                }
+            })
+            .on_request::<types::requests::ResourcesSubscribe, _>({
+                let requests = requests.clone();
+                move |params| {
+                    requests
+                        .lock()
+                        .unwrap()
+                        .push(format!("subscribe {}", params.uri));
+                    async {}
+                }
+            })
+            .on_request::<types::requests::ResourcesUnsubscribe, _>({
+                let requests = requests.clone();
+                move |params| {
+                    requests
+                        .lock()
+                        .unwrap()
+                        .push(format!("unsubscribe {}", params.uri));
+                    async {}
+                }
+            });
+        let server_1_id = ContextServerId(SERVER_1_ID.into());
+        let server_1 = Arc::new(ContextServer::new(server_1_id.clone(), Arc::new(transport)));
+        store.update(cx, |store, cx| store.start_server(server_1, cx));
+        cx.run_until_parked();
+
+        for _ in 0..2 {
+            store
+                .update(cx, |store, cx| {
+                    store.subscribe_to_resource(&server_1_id, URI.to_string(), cx)
+                })
+                .await
+                .unwrap();
+        }
+        assert_eq!(*requests.lock().unwrap(), [format!("subscribe {URI}")]);
+
+        store
+            .update(cx, |store, cx| {
+                store.unsubscribe_from_resource(&server_1_id, URI.to_string(), cx)
+            })
+            .await
+            .unwrap();
+        assert_eq!(
+            *requests.lock().unwrap(),
+            [format!("subscribe {URI}")],
+            "The resource should stay subscribed to while it has subscribers"
+        );
+
+        store
+            .update(cx, |store, cx| {
+                store.unsubscribe_from_resource(&server_1_id, URI.to_string(), cx)
+            })
+            .await
+            .unwrap();
+        assert_eq!(
+            *requests.lock().unwrap(),
+            [format!("subscribe {URI}"), format!("unsubscribe {URI}")]
+        );
+    }
+
     fn dummy_server_settings() -> ContextServerSettings {
         ContextServerSettings::Custom {
             enabled: true,
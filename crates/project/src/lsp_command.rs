@@ -2,11 +2,12 @@ mod signature_help;
 
 use crate::{
     CodeAction, CompletionSource, CoreCompletion, CoreCompletionResponse, DocumentColor,
-    DocumentHighlight, DocumentSymbol, Hover, HoverBlock, HoverBlockKind, InlayHint,
+    DocumentHighlight, DocumentLink, DocumentSymbol, Hover, HoverBlock, HoverBlockKind, InlayHint,
     InlayHintLabel, InlayHintLabelPart, InlayHintLabelPartTooltip, InlayHintTooltip, Location,
     LocationLink, LspAction, LspPullDiagnostics, MarkupContent, PrepareRenameResponse,
     ProjectTransaction, PulledDiagnostics, ResolveState,
     lsp_store::{LocalLspStore, LspStore},
+    project_settings::LspFeature,
 };
 use anyhow::{Context as _, Result};
 use async_trait::async_trait;
@@ -85,6 +86,12 @@ pub trait LspCommand: 'static + Sized + Send + std::fmt::Debug {
         None
     }
 
+    /// The feature whose server priority, configured in `global_lsp_settings.feature_servers`,
+    /// applies to this request.
+    fn lsp_feature(&self) -> Option<LspFeature> {
+        None
+    }
+
     fn to_lsp_params_or_response(
         &self,
         path: &Path,
@@ -245,6 +252,9 @@ pub(crate) struct GetCodeLens;
 #[derive(Debug, Copy, Clone)]
 pub(crate) struct GetDocumentColor;
 
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct GetDocumentLinks;
+
 impl GetCodeLens {
     pub(crate) fn can_resolve_lens(capabilities: &ServerCapabilities) -> bool {
         capabilities
@@ -255,6 +265,61 @@ impl GetCodeLens {
     }
 }
 
+impl GetDocumentLinks {
+    pub(crate) fn can_resolve_links(capabilities: &ServerCapabilities) -> bool {
+        capabilities
+            .document_link_provider
+            .as_ref()
+            .and_then(|document_link_options| document_link_options.resolve_provider)
+            .unwrap_or(false)
+    }
+
+    pub(crate) fn lsp_to_project_link(
+        lsp_link: lsp::DocumentLink,
+        snapshot: &BufferSnapshot,
+        server_id: LanguageServerId,
+        resolved: bool,
+    ) -> DocumentLink {
+        let link_range = range_from_lsp(lsp_link.range);
+        let start = snapshot.clip_point_utf16(link_range.start, Bias::Left);
+        let end = snapshot.clip_point_utf16(link_range.end, Bias::Right);
+        DocumentLink {
+            server_id,
+            range: snapshot.anchor_before(start)..snapshot.anchor_after(end),
+            lsp_link,
+            resolved,
+        }
+    }
+
+    pub(crate) fn project_to_proto_link(link: &DocumentLink) -> Result<proto::DocumentLink> {
+        Ok(proto::DocumentLink {
+            start: Some(serialize_anchor(&link.range.start)),
+            end: Some(serialize_anchor(&link.range.end)),
+            server_id: link.server_id.to_proto(),
+            lsp_link: serde_json::to_vec(&link.lsp_link).context("serializing document link")?,
+            resolved: link.resolved,
+        })
+    }
+
+    pub(crate) fn proto_to_project_link(link: proto::DocumentLink) -> Result<DocumentLink> {
+        let start = link
+            .start
+            .and_then(deserialize_anchor)
+            .context("invalid document link start")?;
+        let end = link
+            .end
+            .and_then(deserialize_anchor)
+            .context("invalid document link end")?;
+        Ok(DocumentLink {
+            server_id: LanguageServerId::from_proto(link.server_id),
+            range: start..end,
+            lsp_link: serde_json::from_slice(&link.lsp_link)
+                .context("deserializing document link")?,
+            resolved: link.resolved,
+        })
+    }
+}
+
 #[derive(Debug)]
 pub(crate) struct LinkedEditingRange {
     pub position: Anchor,
@@ -275,6 +340,10 @@ impl LspCommand for PrepareRename {
         "Prepare rename"
     }
 
+    fn lsp_feature(&self) -> Option<LspFeature> {
+        Some(LspFeature::Rename)
+    }
+
     fn check_capabilities(&self, capabilities: AdapterServerCapabilities) -> bool {
         capabilities
             .server_capabilities
@@ -467,6 +536,10 @@ impl LspCommand for PerformRename {
         "Rename"
     }
 
+    fn lsp_feature(&self) -> Option<LspFeature> {
+        Some(LspFeature::Rename)
+    }
+
     fn check_capabilities(&self, capabilities: AdapterServerCapabilities) -> bool {
         capabilities
             .server_capabilities
@@ -1920,6 +1993,10 @@ impl LspCommand for GetHover {
         "Get hover"
     }
 
+    fn lsp_feature(&self) -> Option<LspFeature> {
+        Some(LspFeature::Hover)
+    }
+
     fn check_capabilities(&self, capabilities: AdapterServerCapabilities) -> bool {
         match capabilities.server_capabilities.hover_provider {
             Some(lsp::HoverProviderCapability::Simple(enabled)) => enabled,
@@ -2160,6 +2237,10 @@ impl LspCommand for GetCompletions {
         "Get completion"
     }
 
+    fn lsp_feature(&self) -> Option<LspFeature> {
+        Some(LspFeature::Completions)
+    }
+
     fn check_capabilities(&self, capabilities: AdapterServerCapabilities) -> bool {
         capabilities
             .server_capabilities
@@ -2520,6 +2601,10 @@ impl LspCommand for GetCodeActions {
         "Get code actions"
     }
 
+    fn lsp_feature(&self) -> Option<LspFeature> {
+        Some(LspFeature::CodeActions)
+    }
+
     fn check_capabilities(&self, capabilities: AdapterServerCapabilities) -> bool {
         match &capabilities.server_capabilities.code_action_provider {
             None => false,
@@ -4345,6 +4430,125 @@ impl LspCommand for GetDocumentColor {
     }
 }
 
+#[async_trait(?Send)]
+impl LspCommand for GetDocumentLinks {
+    type Response = Vec<DocumentLink>;
+    type LspRequest = lsp::request::DocumentLinkRequest;
+    type ProtoRequest = proto::GetDocumentLinks;
+
+    fn display_name(&self) -> &str {
+        "Document links"
+    }
+
+    fn check_capabilities(&self, capabilities: AdapterServerCapabilities) -> bool {
+        capabilities
+            .server_capabilities
+            .document_link_provider
+            .is_some()
+    }
+
+    fn to_lsp(
+        &self,
+        path: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &App,
+    ) -> Result<lsp::DocumentLinkParams> {
+        Ok(lsp::DocumentLinkParams {
+            text_document: make_text_document_identifier(path)?,
+            work_done_progress_params: lsp::WorkDoneProgressParams::default(),
+            partial_result_params: lsp::PartialResultParams::default(),
+        })
+    }
+
+    async fn response_from_lsp(
+        self,
+        message: Option<Vec<lsp::DocumentLink>>,
+        lsp_store: Entity<LspStore>,
+        buffer: Entity<Buffer>,
+        server_id: LanguageServerId,
+        cx: AsyncApp,
+    ) -> Result<Vec<DocumentLink>> {
+        let snapshot = buffer.read_with(&cx, |buffer, _| buffer.snapshot())?;
+        let can_resolve = lsp_store.read_with(&cx, |lsp_store, _| {
+            lsp_store
+                .lsp_server_capabilities
+                .get(&server_id)
+                .is_some_and(Self::can_resolve_links)
+        })?;
+        Ok(message
+            .unwrap_or_default()
+            .into_iter()
+            .map(|lsp_link| {
+                let resolved = !can_resolve || lsp_link.target.is_some();
+                Self::lsp_to_project_link(lsp_link, &snapshot, server_id, resolved)
+            })
+            .collect())
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetDocumentLinks {
+        proto::GetDocumentLinks {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            version: serialize_version(&buffer.version()),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::GetDocumentLinks,
+        _: Entity<LspStore>,
+        buffer: Entity<Buffer>,
+        mut cx: AsyncApp,
+    ) -> Result<Self> {
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        Ok(Self)
+    }
+
+    fn response_to_proto(
+        response: Vec<DocumentLink>,
+        _: &mut LspStore,
+        _: PeerId,
+        buffer_version: &clock::Global,
+        _: &mut App,
+    ) -> proto::GetDocumentLinksResponse {
+        proto::GetDocumentLinksResponse {
+            links: response
+                .iter()
+                .filter_map(|link| Self::project_to_proto_link(link).log_err())
+                .collect(),
+            version: serialize_version(buffer_version),
+        }
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::GetDocumentLinksResponse,
+        _: Entity<LspStore>,
+        buffer: Entity<Buffer>,
+        mut cx: AsyncApp,
+    ) -> Result<Vec<DocumentLink>> {
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        message
+            .links
+            .into_iter()
+            .map(Self::proto_to_project_link)
+            .collect::<Result<Vec<_>>>()
+            .context("deserializing proto document links response")
+    }
+
+    fn buffer_id_from_proto(message: &proto::GetDocumentLinks) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
 fn process_related_documents(
     diagnostics: &mut HashMap<lsp::Uri, LspPullDiagnostics>,
     server_id: LanguageServerId,
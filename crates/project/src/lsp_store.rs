@@ -17,8 +17,8 @@ pub mod rust_analyzer_ext;
 
 use crate::{
     CodeAction, ColorPresentation, Completion, CompletionDisplayOptions, CompletionResponse,
-    CompletionSource, CoreCompletion, DocumentColor, Hover, InlayHint, LocationLink, LspAction,
-    LspPullDiagnostics, ManifestProvidersStore, Project, ProjectItem, ProjectPath,
+    CompletionSource, CoreCompletion, DocumentColor, DocumentLink, Hover, InlayHint, LocationLink,
+    LspAction, LspPullDiagnostics, ManifestProvidersStore, Project, ProjectItem, ProjectPath,
     ProjectTransaction, PulledDiagnostics, ResolveState, Symbol,
     buffer_store::{BufferStore, BufferStoreEvent},
     environment::ProjectEnvironment,
@@ -32,7 +32,7 @@ use crate::{
         ManifestTree,
     },
     prettier_store::{self, PrettierStore, PrettierStoreEvent},
-    project_settings::{LspSettings, ProjectSettings},
+    project_settings::{LspFeature, LspSettings, ProjectSettings},
     toolchain_store::{LocalToolchainStore, ToolchainStoreEvent},
     worktree_store::{WorktreeStore, WorktreeStoreEvent},
     yarn::YarnPathStore,
@@ -72,14 +72,16 @@ use language::{
     range_from_lsp, range_to_lsp,
 };
 use lsp::{
-    AdapterServerCapabilities, CodeActionKind, CompletionContext, DiagnosticSeverity,
-    DiagnosticTag, DidChangeWatchedFilesRegistrationOptions, Edit, FileOperationFilter,
-    FileOperationPatternKind, FileOperationRegistrationOptions, FileRename, FileSystemWatcher,
-    LSP_REQUEST_TIMEOUT, LanguageServer, LanguageServerBinary, LanguageServerBinaryOptions,
-    LanguageServerId, LanguageServerName, LanguageServerSelector, LspRequestFuture,
-    MessageActionItem, MessageType, OneOf, RenameFilesParams, SymbolKind,
-    TextDocumentSyncSaveOptions, TextEdit, Uri, WillRenameFiles, WorkDoneProgressCancelParams,
-    WorkspaceFolder, notification::DidRenameFiles,
+    AdapterServerCapabilities, CodeActionKind, CompletionContext, CreateFilesParams,
+    DeleteFilesParams, DiagnosticSeverity, DiagnosticTag, DidChangeWatchedFilesRegistrationOptions,
+    Edit, FileCreate, FileDelete, FileOperationFilter, FileOperationPatternKind,
+    FileOperationRegistrationOptions, FileRename, FileSystemWatcher, LSP_REQUEST_TIMEOUT,
+    LanguageServer, LanguageServerBinary, LanguageServerBinaryOptions, LanguageServerId,
+    LanguageServerName, LanguageServerSelector, LspRequestFuture, MessageActionItem, MessageType,
+    OneOf, RenameFilesParams, SymbolKind, TextDocumentSyncSaveOptions, TextEdit, Uri,
+    WillCreateFiles, WillDeleteFiles, WillRenameFiles, WorkDoneProgressCancelParams, WorkspaceEdit,
+    WorkspaceFileOperationsServerCapabilities, WorkspaceFolder,
+    notification::{DidCreateFiles, DidDeleteFiles, DidRenameFiles},
 };
 use node_runtime::read_package_installed_version;
 use parking_lot::Mutex;
@@ -198,8 +200,11 @@ pub struct LocalLspStore {
     last_workspace_edits_by_language_server: HashMap<LanguageServerId, ProjectTransaction>,
     language_server_watched_paths: HashMap<LanguageServerId, LanguageServerWatchedPaths>,
     watched_manifest_filenames: HashSet<ManifestName>,
-    language_server_paths_watched_for_rename:
-        HashMap<LanguageServerId, RenamePathsWatchedForServer>,
+    language_server_paths_watched_for_file_operations:
+        HashMap<LanguageServerId, FileOperationsWatchedForServer>,
+    /// The paths affected by the file operations being performed, for telling the worktree changes
+    /// they cause apart from the ones made outside of Zed.
+    file_operations_in_progress: HashMap<PathBuf, usize>,
     language_server_watcher_registrations:
         HashMap<LanguageServerId, HashMap<String, Vec<FileSystemWatcher>>>,
     supplementary_language_servers:
@@ -221,7 +226,7 @@ pub struct LocalLspStore {
     lsp_tree: LanguageServerTree,
     registered_buffers: HashMap<BufferId, usize>,
     buffers_opened_in_servers: HashMap<BufferId, HashSet<LanguageServerId>>,
-    buffer_pull_diagnostics_result_ids: HashMap<LanguageServerId, HashMap<PathBuf, Option<String>>>,
+    pull_diagnostics_result_ids: HashMap<LanguageServerId, HashMap<PathBuf, Option<String>>>,
 }
 
 impl LocalLspStore {
@@ -1454,7 +1459,18 @@ impl LocalLspStore {
                             }
                         })
                     } else {
-                        adapters_and_servers.first().map(|e| e.1.clone())
+                        cx.update(|cx| {
+                            let servers = adapters_and_servers
+                                .iter()
+                                .map(|(adapter, server)| (adapter.name.clone(), server));
+                            prioritize_servers_for_feature(
+                                Some(LspFeature::Formatting),
+                                servers,
+                                cx,
+                            )
+                            .first()
+                            .map(|server| Arc::clone(server))
+                        })?
                     };
 
                     let Some(language_server) = language_server else {
@@ -2286,7 +2302,7 @@ impl LocalLspStore {
         let set = DiagnosticSet::new(sanitized_diagnostics, &snapshot);
         buffer.update(cx, |buffer, cx| {
             if let Some(abs_path) = File::from_dyn(buffer.file()).map(|f| f.abs_path(cx)) {
-                self.buffer_pull_diagnostics_result_ids
+                self.pull_diagnostics_result_ids
                     .entry(server_id)
                     .or_default()
                     .insert(abs_path, result_id);
@@ -3126,13 +3142,12 @@ impl LocalLspStore {
         for server_id_to_remove in &servers_to_remove {
             self.language_server_watched_paths
                 .remove(server_id_to_remove);
-            self.language_server_paths_watched_for_rename
+            self.language_server_paths_watched_for_file_operations
                 .remove(server_id_to_remove);
             self.last_workspace_edits_by_language_server
                 .remove(server_id_to_remove);
             self.language_servers.remove(server_id_to_remove);
-            self.buffer_pull_diagnostics_result_ids
-                .remove(server_id_to_remove);
+            self.pull_diagnostics_result_ids.remove(server_id_to_remove);
             for buffer_servers in self.buffers_opened_in_servers.values_mut() {
                 buffer_servers.remove(server_id_to_remove);
             }
@@ -3493,6 +3508,7 @@ pub struct LspStore {
     pub lsp_server_capabilities: HashMap<LanguageServerId, lsp::ServerCapabilities>,
     lsp_document_colors: HashMap<BufferId, DocumentColorData>,
     lsp_code_lens: HashMap<BufferId, CodeLensData>,
+    lsp_document_links: HashMap<BufferId, DocumentLinkData>,
     running_lsp_requests: HashMap<TypeId, (Global, HashMap<LspRequestId, Task<()>>)>,
 }
 
@@ -3504,6 +3520,8 @@ pub struct DocumentColors {
 
 type DocumentColorTask = Shared<Task<std::result::Result<DocumentColors, Arc<anyhow::Error>>>>;
 type CodeLensTask = Shared<Task<std::result::Result<Option<Vec<CodeAction>>, Arc<anyhow::Error>>>>;
+type DocumentLinkTask =
+    Shared<Task<std::result::Result<Option<Vec<DocumentLink>>, Arc<anyhow::Error>>>>;
 
 #[derive(Debug, Default)]
 struct DocumentColorData {
@@ -3520,6 +3538,13 @@ struct CodeLensData {
     update: Option<(Global, CodeLensTask)>,
 }
 
+#[derive(Debug, Default)]
+struct DocumentLinkData {
+    links_for_version: Global,
+    links: HashMap<LanguageServerId, Vec<DocumentLink>>,
+    update: Option<(Global, DocumentLinkTask)>,
+}
+
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum LspFetchStrategy {
     IgnoreCache,
@@ -3617,6 +3642,7 @@ impl LspStore {
         client.add_entity_request_handler(Self::handle_inlay_hints);
         client.add_entity_request_handler(Self::handle_get_project_symbols);
         client.add_entity_request_handler(Self::handle_resolve_inlay_hint);
+        client.add_entity_request_handler(Self::handle_resolve_document_link);
         client.add_entity_request_handler(Self::handle_get_color_presentation);
         client.add_entity_request_handler(Self::handle_open_buffer_for_symbol);
         client.add_entity_request_handler(Self::handle_refresh_inlay_hints);
@@ -3624,6 +3650,9 @@ impl LspStore {
         client.add_entity_request_handler(Self::handle_on_type_formatting);
         client.add_entity_request_handler(Self::handle_apply_additional_edits_for_completion);
         client.add_entity_request_handler(Self::handle_register_buffer_with_language_servers);
+        client.add_entity_request_handler(Self::handle_create_project_entry);
+        client.add_entity_request_handler(Self::handle_copy_project_entry);
+        client.add_entity_request_handler(Self::handle_delete_project_entry);
         client.add_entity_request_handler(Self::handle_rename_project_entry);
         client.add_entity_request_handler(Self::handle_pull_workspace_diagnostics);
         client.add_entity_request_handler(Self::handle_lsp_command::<GetCompletions>);
@@ -3727,7 +3756,8 @@ impl LspStore {
                 language_servers: Default::default(),
                 last_workspace_edits_by_language_server: Default::default(),
                 language_server_watched_paths: Default::default(),
-                language_server_paths_watched_for_rename: Default::default(),
+                language_server_paths_watched_for_file_operations: Default::default(),
+                file_operations_in_progress: Default::default(),
                 language_server_watcher_registrations: Default::default(),
                 buffers_being_formatted: Default::default(),
                 buffer_snapshots: Default::default(),
@@ -3751,7 +3781,7 @@ impl LspStore {
                 toolchain_store,
                 registered_buffers: HashMap::default(),
                 buffers_opened_in_servers: HashMap::default(),
-                buffer_pull_diagnostics_result_ids: HashMap::default(),
+                pull_diagnostics_result_ids: HashMap::default(),
                 watched_manifest_filenames: ManifestProvidersStore::global(cx)
                     .manifest_file_names(),
             }),
@@ -3766,6 +3796,7 @@ impl LspStore {
             lsp_server_capabilities: HashMap::default(),
             lsp_document_colors: HashMap::default(),
             lsp_code_lens: HashMap::default(),
+            lsp_document_links: HashMap::default(),
             running_lsp_requests: HashMap::default(),
             active_entry: None,
             _maintain_workspace_config,
@@ -3827,6 +3858,7 @@ impl LspStore {
             lsp_server_capabilities: HashMap::default(),
             lsp_document_colors: HashMap::default(),
             lsp_code_lens: HashMap::default(),
+            lsp_document_links: HashMap::default(),
             running_lsp_requests: HashMap::default(),
             active_entry: None,
 
@@ -3887,9 +3919,13 @@ impl LspStore {
                     worktree::Event::UpdatedGitRepositories(_)
                     | worktree::Event::DeletedEntry(_) => {}
                 })
-                .detach()
+                .detach();
+                self.restart_all_workspace_diagnostics();
+            }
+            WorktreeStoreEvent::WorktreeRemoved(_, id) => {
+                self.remove_worktree(*id, cx);
+                self.restart_all_workspace_diagnostics();
             }
-            WorktreeStoreEvent::WorktreeRemoved(_, id) => self.remove_worktree(*id, cx),
             WorktreeStoreEvent::WorktreeUpdateSent(worktree) => {
                 worktree.update(cx, |worktree, _cx| self.send_diagnostic_summaries(worktree));
             }
@@ -4026,6 +4062,7 @@ impl LspStore {
                     if refcount == 0 {
                         lsp_store.lsp_document_colors.remove(&buffer_id);
                         lsp_store.lsp_code_lens.remove(&buffer_id);
+                        lsp_store.lsp_document_links.remove(&buffer_id);
                         let local = lsp_store.as_local_mut().unwrap();
                         local.registered_buffers.remove(&buffer_id);
                         local.buffers_opened_in_servers.remove(&buffer_id);
@@ -4362,12 +4399,13 @@ impl LspStore {
 
         let Some(language_server) = buffer.update(cx, |buffer, cx| match server {
             LanguageServerToQuery::FirstCapable => self.as_local().and_then(|local| {
-                local
+                let servers = local
                     .language_servers_for_buffer(buffer, cx)
-                    .find(|(_, server)| {
-                        request.check_capabilities(server.adapter_server_capabilities())
-                    })
-                    .map(|(_, server)| server.clone())
+                    .map(|(adapter, server)| (adapter.name.clone(), server.clone()))
+                    .collect::<Vec<_>>();
+                prioritize_servers_for_feature(request.lsp_feature(), servers, cx)
+                    .into_iter()
+                    .find(|server| request.check_capabilities(server.adapter_server_capabilities()))
             }),
             LanguageServerToQuery::Other(id) => self
                 .language_server_for_local_buffer(buffer, id, cx)
@@ -5519,11 +5557,18 @@ impl LspStore {
                 cx,
             );
             cx.background_spawn(async move {
+                let mut seen_actions = HashSet::default();
                 Ok(Some(
                     all_actions_task
                         .await
                         .into_iter()
                         .flat_map(|(_, actions)| actions)
+                        .filter(|action| {
+                            seen_actions.insert((
+                                action.range.clone(),
+                                action.lsp_action.title().to_owned(),
+                            ))
+                        })
                         .collect(),
                 ))
             })
@@ -5679,6 +5724,228 @@ impl LspStore {
         }
     }
 
+    pub fn document_links(
+        &mut self,
+        buffer: &Entity<Buffer>,
+        cx: &mut Context<Self>,
+    ) -> DocumentLinkTask {
+        let version_queried_for = buffer.read(cx).version();
+        let buffer_id = buffer.read(cx).remote_id();
+
+        if let Some(cached_data) = self.lsp_document_links.get(&buffer_id)
+            && !version_queried_for.changed_since(&cached_data.links_for_version)
+        {
+            let has_different_servers = self.as_local().is_some_and(|local| {
+                local
+                    .buffers_opened_in_servers
+                    .get(&buffer_id)
+                    .cloned()
+                    .unwrap_or_default()
+                    != cached_data.links.keys().copied().collect()
+            });
+            if !has_different_servers {
+                return Task::ready(Ok(Some(
+                    cached_data.links.values().flatten().cloned().collect(),
+                )))
+                .shared();
+            }
+        }
+
+        let lsp_data = self.lsp_document_links.entry(buffer_id).or_default();
+        if let Some((updating_for, running_update)) = &lsp_data.update
+            && !version_queried_for.changed_since(updating_for)
+        {
+            return running_update.clone();
+        }
+        let buffer = buffer.clone();
+        let query_version_queried_for = version_queried_for.clone();
+        let new_task = cx
+            .spawn(async move |lsp_store, cx| {
+                let fetched_links = lsp_store
+                    .update(cx, |lsp_store, cx| {
+                        lsp_store.fetch_document_links(&buffer, cx)
+                    })
+                    .map_err(Arc::new)?
+                    .await
+                    .context("fetching document links")
+                    .map_err(Arc::new);
+                let fetched_links = match fetched_links {
+                    Ok(fetched_links) => fetched_links,
+                    Err(e) => {
+                        lsp_store
+                            .update(cx, |lsp_store, _| {
+                                lsp_store
+                                    .lsp_document_links
+                                    .entry(buffer_id)
+                                    .or_default()
+                                    .update = None;
+                            })
+                            .ok();
+                        return Err(e);
+                    }
+                };
+
+                lsp_store
+                    .update(cx, |lsp_store, _| {
+                        let lsp_data = lsp_store.lsp_document_links.entry(buffer_id).or_default();
+                        if let Some(fetched_links) = fetched_links {
+                            if lsp_data.links_for_version == query_version_queried_for {
+                                lsp_data.links.extend(fetched_links);
+                            } else if !lsp_data
+                                .links_for_version
+                                .changed_since(&query_version_queried_for)
+                            {
+                                lsp_data.links_for_version = query_version_queried_for;
+                                lsp_data.links = fetched_links;
+                            }
+                        }
+                        lsp_data.update = None;
+                        Some(lsp_data.links.values().flatten().cloned().collect())
+                    })
+                    .map_err(Arc::new)
+            })
+            .shared();
+        lsp_data.update = Some((version_queried_for, new_task.clone()));
+        new_task
+    }
+
+    fn fetch_document_links(
+        &mut self,
+        buffer: &Entity<Buffer>,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Option<HashMap<LanguageServerId, Vec<DocumentLink>>>>> {
+        if let Some((upstream_client, project_id)) = self.upstream_client() {
+            let request = GetDocumentLinks;
+            if !self.is_capable_for_proto_request(buffer, &request, cx) {
+                return Task::ready(Ok(None));
+            }
+            let request_task = upstream_client.request_lsp(
+                project_id,
+                LSP_REQUEST_TIMEOUT,
+                cx.background_executor().clone(),
+                request.to_proto(project_id, buffer.read(cx)),
+            );
+            let buffer = buffer.clone();
+            cx.spawn(async move |weak_lsp_store, cx| {
+                let Some(lsp_store) = weak_lsp_store.upgrade() else {
+                    return Ok(None);
+                };
+                let Some(responses) = request_task.await? else {
+                    return Ok(None);
+                };
+
+                let document_links = join_all(responses.payload.into_iter().map(|response| {
+                    let lsp_store = lsp_store.clone();
+                    let buffer = buffer.clone();
+                    let cx = cx.clone();
+                    async move {
+                        (
+                            LanguageServerId::from_proto(response.server_id),
+                            GetDocumentLinks
+                                .response_from_proto(response.response, lsp_store, buffer, cx)
+                                .await,
+                        )
+                    }
+                }))
+                .await;
+
+                let mut has_errors = false;
+                let document_links = document_links
+                    .into_iter()
+                    .filter_map(|(server_id, links)| match links {
+                        Ok(links) => Some((server_id, links)),
+                        Err(e) => {
+                            has_errors = true;
+                            log::error!("{e:#}");
+                            None
+                        }
+                    })
+                    .collect::<HashMap<_, _>>();
+                anyhow::ensure!(
+                    !has_errors || !document_links.is_empty(),
+                    "Failed to fetch document links"
+                );
+                Ok(Some(document_links))
+            })
+        } else {
+            let document_links_task =
+                self.request_multiple_lsp_locally(buffer, None::<usize>, GetDocumentLinks, cx);
+            cx.background_spawn(
+                async move { Ok(Some(document_links_task.await.into_iter().collect())) },
+            )
+        }
+    }
+
+    pub fn resolve_document_link(
+        &mut self,
+        link: DocumentLink,
+        buffer: Entity<Buffer>,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<DocumentLink>> {
+        if link.resolved {
+            return Task::ready(Ok(link));
+        }
+
+        if let Some((upstream_client, project_id)) = self.upstream_client() {
+            let request = match GetDocumentLinks::project_to_proto_link(&link) {
+                Ok(proto_link) => proto::ResolveDocumentLink {
+                    project_id,
+                    buffer_id: buffer.read(cx).remote_id().into(),
+                    link: Some(proto_link),
+                },
+                Err(e) => return Task::ready(Err(e)),
+            };
+            cx.background_spawn(async move {
+                let response = upstream_client
+                    .request(request)
+                    .await
+                    .context("document link resolve proto request")?;
+                match response.link {
+                    Some(resolved_link) => GetDocumentLinks::proto_to_project_link(resolved_link)
+                        .context("document link proto resolve response conversion"),
+                    None => Ok(link),
+                }
+            })
+        } else {
+            let server_id = link.server_id;
+            let Some(lang_server) = buffer.update(cx, |buffer, cx| {
+                self.language_server_for_local_buffer(buffer, server_id, cx)
+                    .map(|(_, server)| server.clone())
+            }) else {
+                return Task::ready(Ok(link));
+            };
+            if !GetDocumentLinks::can_resolve_links(&lang_server.capabilities()) {
+                return Task::ready(Ok(link));
+            }
+            let buffer_id = buffer.read(cx).remote_id();
+            let buffer_snapshot = buffer.read(cx).snapshot();
+            cx.spawn(async move |lsp_store, cx| {
+                let resolved_lsp_link = lang_server
+                    .request::<lsp::request::DocumentLinkResolve>(link.lsp_link.clone())
+                    .await
+                    .into_response()
+                    .context("document link resolve LSP request")?;
+                let resolved_link = GetDocumentLinks::lsp_to_project_link(
+                    resolved_lsp_link,
+                    &buffer_snapshot,
+                    server_id,
+                    true,
+                );
+                lsp_store.update(cx, |lsp_store, _| {
+                    if let Some(cached_link) = lsp_store
+                        .lsp_document_links
+                        .get_mut(&buffer_id)
+                        .and_then(|data| data.links.get_mut(&server_id))
+                        .and_then(|links| links.iter_mut().find(|cached| **cached == link))
+                    {
+                        *cached_link = resolved_link.clone();
+                    }
+                })?;
+                Ok(resolved_link)
+            })
+        }
+    }
+
     #[inline(never)]
     pub fn completions(
         &self,
@@ -5744,7 +6011,7 @@ impl LspStore {
             }
 
             let server_ids: Vec<_> = buffer.update(cx, |buffer, cx| {
-                local
+                let servers = local
                     .language_servers_for_buffer(buffer, cx)
                     .filter(|(_, server)| server.capabilities().completion_provider.is_some())
                     .filter(|(adapter, _)| {
@@ -5753,8 +6020,9 @@ impl LspStore {
                             .map(|scope| scope.language_allowed(&adapter.name))
                             .unwrap_or(true)
                     })
-                    .map(|(_, server)| server.server_id())
-                    .collect()
+                    .map(|(adapter, server)| (adapter.name.clone(), server.server_id()))
+                    .collect::<Vec<_>>();
+                prioritize_servers_for_feature(Some(LspFeature::Completions), servers, cx)
             });
 
             let buffer = buffer.clone();
@@ -5824,7 +6092,7 @@ impl LspStore {
 
                 let responses: Vec<Option<CompletionResponse>> = join_all(futures).await;
 
-                Ok(responses.into_iter().flatten().collect())
+                Ok(dedup_completion_responses(responses.into_iter().flatten().collect()))
             })
         } else {
             Task::ready(Err(anyhow!("No upstream client or local language server")))
@@ -6850,11 +7118,18 @@ impl LspStore {
                 cx,
             );
             cx.background_spawn(async move {
+                let mut seen_blocks = HashSet::default();
                 Some(
                     all_actions_task
                         .await
                         .into_iter()
-                        .filter_map(|(_, hover)| remove_empty_hover_blocks(hover?))
+                        .filter_map(|(_, hover)| {
+                            let mut hover = hover?;
+                            hover
+                                .contents
+                                .retain(|block| seen_blocks.insert(block.text.trim().to_owned()));
+                            remove_empty_hover_blocks(hover)
+                        })
                         .collect::<Vec<Hover>>(),
                 )
             })
@@ -7546,7 +7821,7 @@ impl LspStore {
                 self.worktree_store.read(cx).find_worktree(abs_path, cx)
             else {
                 log::warn!("skipping diagnostics update, no worktree found for path {abs_path:?}");
-                return Ok(());
+                continue;
             };
 
             let worktree_id = worktree.read(cx).id();
@@ -7585,6 +7860,14 @@ impl LspStore {
                     )?;
 
                 update.diagnostics.diagnostics.extend(reused_diagnostics);
+            } else if let Some(local) = self.as_local_mut() {
+                // Workspace diagnostics may report files that are not opened,
+                // keep their result ids to send them back on the next workspace pull.
+                local
+                    .pull_diagnostics_result_ids
+                    .entry(server_id)
+                    .or_default()
+                    .insert(abs_path.clone(), update.result_id);
             }
 
             let updated = worktree.update(cx, |worktree, cx| {
@@ -7875,7 +8158,7 @@ impl LspStore {
         let scope = position.and_then(|position| snapshot.language_scope_at(position));
 
         let server_ids = buffer.update(cx, |buffer, cx| {
-            local
+            let servers = local
                 .language_servers_for_buffer(buffer, cx)
                 .filter(|(adapter, _)| {
                     scope
@@ -7883,8 +8166,8 @@ impl LspStore {
                         .map(|scope| scope.language_allowed(&adapter.name))
                         .unwrap_or(true)
                 })
-                .map(|(_, server)| server.server_id())
-                .filter(|server_id| {
+                .map(|(adapter, server)| (adapter.name.clone(), server.server_id()))
+                .filter(|(_, server_id)| {
                     self.as_local().is_none_or(|local| {
                         local
                             .buffers_opened_in_servers
@@ -7892,11 +8175,13 @@ impl LspStore {
                             .is_some_and(|servers| servers.contains(server_id))
                     })
                 })
-                .collect::<Vec<_>>()
+                .collect::<Vec<_>>();
+            prioritize_servers_for_feature(request.lsp_feature(), servers, cx)
         });
 
         let mut response_results = server_ids
-            .into_iter()
+            .iter()
+            .copied()
             .map(|server_id| {
                 let task = self.request_lsp(
                     buffer.clone(),
@@ -7915,6 +8200,9 @@ impl LspStore {
                     responses.push((server_id, response));
                 }
             }
+            // Keep the merged results in the servers' priority order, regardless of which responded first.
+            responses
+                .sort_by_key(|(server_id, _)| server_ids.iter().position(|id| id == server_id));
             responses
         })
     }
@@ -8043,6 +8331,17 @@ impl LspStore {
                 )
                 .await?;
             }
+            Request::GetDocumentLinks(get_document_links) => {
+                Self::query_lsp_locally::<GetDocumentLinks>(
+                    lsp_store,
+                    sender_id,
+                    lsp_request_id,
+                    get_document_links,
+                    None,
+                    cx.clone(),
+                )
+                .await?;
+            }
             Request::GetDefinition(get_definition) => {
                 let position = get_definition.position.clone().and_then(deserialize_anchor);
                 Self::query_lsp_locally::<GetDefinitions>(
@@ -8271,31 +8570,139 @@ impl LspStore {
         let new_abs_path =
             new_worktree.read_with(&cx, |worktree, _| worktree.absolutize(&new_path))?;
 
-        let _transaction = Self::will_rename_entry(
+        let operation = FileOperation::Rename {
+            old_path: old_abs_path,
+            new_path: new_abs_path,
+            is_dir: old_entry.is_dir(),
+        };
+        let (response, _) = Self::perform_file_operation(
             this.downgrade(),
             old_worktree_id,
-            &old_abs_path,
-            &new_abs_path,
-            old_entry.is_dir(),
-            cx.clone(),
+            operation,
+            &mut cx,
+            async move |cx| {
+                WorktreeStore::handle_rename_project_entry(
+                    worktree_store,
+                    envelope.payload,
+                    cx.clone(),
+                )
+                .await
+            },
         )
-        .await;
-        let response = WorktreeStore::handle_rename_project_entry(
-            worktree_store,
-            envelope.payload,
-            cx.clone(),
+        .await?;
+        Ok(response)
+    }
+
+    async fn handle_create_project_entry(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::CreateProjectEntry>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::ProjectEntryResponse> {
+        let worktree_id = WorktreeId::from_proto(envelope.payload.worktree_id);
+        let path = RelPath::from_proto(&envelope.payload.path).context("invalid relative path")?;
+        let (worktree_store, abs_path) = this
+            .update(&mut cx, |this, cx| {
+                let worktree = this
+                    .worktree_store
+                    .read(cx)
+                    .worktree_for_id(worktree_id, cx)?;
+                let abs_path = worktree.read(cx).absolutize(&path);
+                Some((this.worktree_store.clone(), abs_path))
+            })?
+            .context("worktree not found")?;
+
+        let operation = FileOperation::Create {
+            path: abs_path,
+            is_dir: envelope.payload.is_directory,
+        };
+        let (response, _) = Self::perform_file_operation(
+            this.downgrade(),
+            worktree_id,
+            operation,
+            &mut cx,
+            async move |cx| {
+                WorktreeStore::handle_create_project_entry(worktree_store, envelope, cx.clone())
+                    .await
+            },
         )
-        .await;
-        this.read_with(&cx, |this, _| {
-            this.did_rename_entry(
-                old_worktree_id,
-                &old_abs_path,
-                &new_abs_path,
-                old_entry.is_dir(),
-            );
-        })
-        .ok();
-        response
+        .await?;
+        Ok(response)
+    }
+
+    async fn handle_copy_project_entry(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::CopyProjectEntry>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::ProjectEntryResponse> {
+        let entry_id = ProjectEntryId::from_proto(envelope.payload.entry_id);
+        let new_worktree_id = WorktreeId::from_proto(envelope.payload.new_worktree_id);
+        let new_path =
+            RelPath::from_proto(&envelope.payload.new_path).context("invalid relative path")?;
+        let (worktree_store, new_abs_path, is_dir) = this
+            .update(&mut cx, |this, cx| {
+                let worktree_store = this.worktree_store.read(cx);
+                let (_, entry) = worktree_store.worktree_and_entry_for_id(entry_id, cx)?;
+                let new_worktree = worktree_store.worktree_for_id(new_worktree_id, cx)?;
+                let new_abs_path = new_worktree.read(cx).absolutize(&new_path);
+                Some((this.worktree_store.clone(), new_abs_path, entry.is_dir()))
+            })?
+            .context("worktree not found")?;
+
+        let operation = FileOperation::Create {
+            path: new_abs_path,
+            is_dir,
+        };
+        let (response, _) = Self::perform_file_operation(
+            this.downgrade(),
+            new_worktree_id,
+            operation,
+            &mut cx,
+            async move |cx| {
+                WorktreeStore::handle_copy_project_entry(worktree_store, envelope, cx.clone()).await
+            },
+        )
+        .await?;
+        Ok(response)
+    }
+
+    async fn handle_delete_project_entry(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::DeleteProjectEntry>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::ProjectEntryResponse> {
+        let entry_id = ProjectEntryId::from_proto(envelope.payload.entry_id);
+        let (worktree_store, worktree_id, abs_path, is_dir) = this
+            .update(&mut cx, |this, cx| {
+                let (worktree, entry) = this
+                    .worktree_store
+                    .read(cx)
+                    .worktree_and_entry_for_id(entry_id, cx)?;
+                let worktree = worktree.read(cx);
+                Some((
+                    this.worktree_store.clone(),
+                    worktree.id(),
+                    worktree.absolutize(&entry.path),
+                    entry.is_dir(),
+                ))
+            })?
+            .context("worktree not found")?;
+
+        let operation = FileOperation::Delete {
+            path: abs_path,
+            is_dir,
+        };
+        let (response, _) = Self::perform_file_operation(
+            this.downgrade(),
+            worktree_id,
+            operation,
+            &mut cx,
+            async move |cx| {
+                WorktreeStore::handle_delete_project_entry(worktree_store, envelope, cx.clone())
+                    .await
+            },
+        )
+        .await?;
+        Ok(response)
     }
 
     async fn handle_update_diagnostic_summary(
@@ -8710,87 +9117,100 @@ impl LspStore {
             .map(|(key, value)| (*key, value))
     }
 
-    pub(super) fn did_rename_entry(
-        &self,
+    /// Runs a file system operation on project entries, keeping the language servers in sync:
+    /// the edits returned for `workspace/will*Files` are applied before the operation is performed,
+    /// and `workspace/did*Files` is sent once it succeeds.
+    pub(crate) async fn perform_file_operation<T>(
+        this: WeakEntity<Self>,
         worktree_id: WorktreeId,
-        old_path: &Path,
-        new_path: &Path,
-        is_dir: bool,
-    ) {
+        operation: FileOperation,
+        cx: &mut AsyncApp,
+        perform: impl AsyncFnOnce(&mut AsyncApp) -> Result<T>,
+    ) -> Result<(T, ProjectTransaction)> {
+        this.update(cx, |this, _| {
+            if let Some(local) = this.as_local_mut() {
+                for path in operation.paths() {
+                    *local
+                        .file_operations_in_progress
+                        .entry(path.to_path_buf())
+                        .or_default() += 1;
+                }
+            }
+        })
+        .ok();
+        let transaction =
+            Self::will_perform_file_operation(this.clone(), worktree_id, &operation, cx.clone())
+                .await;
+        let output = perform(cx).await;
+        this.update(cx, |this, _| {
+            if let Some(local) = this.as_local_mut() {
+                for path in operation.paths() {
+                    if let Some(count) = local.file_operations_in_progress.get_mut(path) {
+                        *count -= 1;
+                        if *count == 0 {
+                            local.file_operations_in_progress.remove(path);
+                        }
+                    }
+                }
+            }
+            if output.is_ok() {
+                this.did_perform_file_operation(worktree_id, &operation);
+            }
+        })
+        .ok();
+        Ok((output?, transaction))
+    }
+
+    fn did_perform_file_operation(&self, worktree_id: WorktreeId, operation: &FileOperation) {
         maybe!({
             let local_store = self.as_local()?;
-
-            let old_uri = lsp::Uri::from_file_path(old_path)
-                .ok()
-                .map(|uri| uri.to_string())?;
-            let new_uri = lsp::Uri::from_file_path(new_path)
-                .ok()
-                .map(|uri| uri.to_string())?;
+            let (uri, params) = operation.to_lsp()?;
 
             for language_server in local_store.language_servers_for_worktree(worktree_id) {
                 let Some(filter) = local_store
-                    .language_server_paths_watched_for_rename
+                    .language_server_paths_watched_for_file_operations
                     .get(&language_server.server_id())
                 else {
                     continue;
                 };
 
-                if filter.should_send_did_rename(&old_uri, is_dir) {
-                    language_server
-                        .notify::<DidRenameFiles>(&RenameFilesParams {
-                            files: vec![FileRename {
-                                old_uri: old_uri.clone(),
-                                new_uri: new_uri.clone(),
-                            }],
-                        })
-                        .ok();
+                if filter.should_send_did(operation, &uri) {
+                    params.notify_did(language_server).log_err();
                 }
             }
             Some(())
         });
     }
 
-    pub(super) fn will_rename_entry(
+    fn will_perform_file_operation(
         this: WeakEntity<Self>,
         worktree_id: WorktreeId,
-        old_path: &Path,
-        new_path: &Path,
-        is_dir: bool,
+        operation: &FileOperation,
         cx: AsyncApp,
     ) -> Task<ProjectTransaction> {
-        let old_uri = lsp::Uri::from_file_path(old_path)
-            .ok()
-            .map(|uri| uri.to_string());
-        let new_uri = lsp::Uri::from_file_path(new_path)
-            .ok()
-            .map(|uri| uri.to_string());
+        let operation = operation.clone();
         cx.spawn(async move |cx| {
             let mut tasks = vec![];
             this.update(cx, |this, cx| {
                 let local_store = this.as_local()?;
-                let old_uri = old_uri?;
-                let new_uri = new_uri?;
+                let (uri, params) = operation.to_lsp()?;
                 for language_server in local_store.language_servers_for_worktree(worktree_id) {
                     let Some(filter) = local_store
-                        .language_server_paths_watched_for_rename
+                        .language_server_paths_watched_for_file_operations
                         .get(&language_server.server_id())
                     else {
                         continue;
                     };
 
-                    if filter.should_send_will_rename(&old_uri, is_dir) {
+                    if filter.should_send_will(&operation, &uri) {
                         let apply_edit = cx.spawn({
-                            let old_uri = old_uri.clone();
-                            let new_uri = new_uri.clone();
+                            let params = params.clone();
                             let language_server = language_server.clone();
                             async move |this, cx| {
-                                let edit = language_server
-                                    .request::<WillRenameFiles>(RenameFilesParams {
-                                        files: vec![FileRename { old_uri, new_uri }],
-                                    })
+                                let edit = params
+                                    .request_will(&language_server)
                                     .await
-                                    .into_response()
-                                    .context("will rename files")
+                                    .with_context(|| format!("{} files", params.will_method_name()))
                                     .log_err()
                                     .flatten()?;
 
@@ -9351,6 +9771,32 @@ impl LspStore {
         })
     }
 
+    async fn handle_resolve_document_link(
+        lsp_store: Entity<Self>,
+        envelope: TypedEnvelope<proto::ResolveDocumentLink>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::ResolveDocumentLinkResponse> {
+        let link = GetDocumentLinks::proto_to_project_link(
+            envelope
+                .payload
+                .link
+                .context("invalid document link resolve request: missing the link")?,
+        )?;
+        let buffer = lsp_store.update(&mut cx, |lsp_store, cx| {
+            let buffer_id = BufferId::new(envelope.payload.buffer_id)?;
+            lsp_store.buffer_store.read(cx).get_existing(buffer_id)
+        })??;
+        let resolved_link = lsp_store
+            .update(&mut cx, |lsp_store, cx| {
+                lsp_store.resolve_document_link(link, buffer, cx)
+            })?
+            .await
+            .context("resolving document link")?;
+        Ok(proto::ResolveDocumentLinkResponse {
+            link: Some(GetDocumentLinks::project_to_proto_link(&resolved_link)?),
+        })
+    }
+
     async fn handle_refresh_code_lens(
         this: Entity<Self>,
         _: TypedEnvelope<proto::RefreshCodeLens>,
@@ -10423,16 +10869,12 @@ impl LspStore {
             .as_ref()
             .and_then(|ws| ws.file_operations.as_ref())
         {
-            let did_rename_caps = file_ops_caps.did_rename.as_ref();
-            let will_rename_caps = file_ops_caps.will_rename.as_ref();
-            if did_rename_caps.or(will_rename_caps).is_some() {
-                let watcher = RenamePathsWatchedForServer::default()
-                    .with_did_rename_patterns(did_rename_caps)
-                    .with_will_rename_patterns(will_rename_caps);
-                local
-                    .language_server_paths_watched_for_rename
-                    .insert(server_id, watcher);
-            }
+            local
+                .language_server_paths_watched_for_file_operations
+                .insert(
+                    server_id,
+                    FileOperationsWatchedForServer::new(file_ops_caps),
+                );
         }
 
         self.language_server_statuses.insert(
@@ -10751,6 +11193,29 @@ impl LspStore {
         });
 
         let worktree_id = worktree_handle.read(cx).id();
+
+        // Files created, deleted or renamed outside of Zed are reported to the servers with
+        // `workspace/did*Files` only: the operation has already happened, so there is no point in
+        // asking for the edits `workspace/will*Files` would return, and applying them unprompted
+        // could rewrite files the user never touched in Zed.
+        let external_file_operations = if local
+            .language_server_paths_watched_for_file_operations
+            .is_empty()
+        {
+            Vec::new()
+        } else {
+            file_operations_for_worktree_changes(worktree_handle.read(cx), changes)
+                .into_iter()
+                .filter(|operation| {
+                    operation
+                        .paths()
+                        .all(|path| !local.file_operations_in_progress.contains_key(path))
+                })
+                .collect()
+        };
+        for operation in &external_file_operations {
+            self.did_perform_file_operation(worktree_id, operation);
+        }
         let mut language_server_ids = local
             .language_server_ids
             .iter()
@@ -10759,6 +11224,7 @@ impl LspStore {
         language_server_ids.sort();
         language_server_ids.dedup();
 
+        let mut servers_with_changes = Vec::new();
         // let abs_path = worktree_handle.read(cx).abs_path();
         for server_id in &language_server_ids {
             if let Some(LanguageServerState::Running { server, .. }) =
@@ -10794,9 +11260,11 @@ impl LspStore {
                     server
                         .notify::<lsp::notification::DidChangeWatchedFiles>(&params)
                         .ok();
+                    servers_with_changes.push(*server_id);
                 }
             }
         }
+        self.restart_workspace_diagnostics(servers_with_changes);
         for (path, _, _) in changes {
             if let Some(file_name) = path.file_name()
                 && local.watched_manifest_filenames.contains(file_name)
@@ -11076,8 +11544,11 @@ impl LspStore {
         for buffer_lens in self.lsp_code_lens.values_mut() {
             buffer_lens.lens.remove(&for_server);
         }
+        for buffer_links in self.lsp_document_links.values_mut() {
+            buffer_links.links.remove(&for_server);
+        }
         if let Some(local) = self.as_local_mut() {
-            local.buffer_pull_diagnostics_result_ids.remove(&for_server);
+            local.pull_diagnostics_result_ids.remove(&for_server);
             for buffer_servers in local.buffers_opened_in_servers.values_mut() {
                 buffer_servers.remove(&for_server);
             }
@@ -11097,7 +11568,7 @@ impl LspStore {
             .and_then(|b| File::from_dyn(b.read(cx).file()))
             .map(|f| f.abs_path(cx))?;
         self.as_local()?
-            .buffer_pull_diagnostics_result_ids
+            .pull_diagnostics_result_ids
             .get(&server_id)?
             .get(&abs_path)?
             .clone()
@@ -11108,7 +11579,7 @@ impl LspStore {
             return HashMap::default();
         };
         local
-            .buffer_pull_diagnostics_result_ids
+            .pull_diagnostics_result_ids
             .get(&server_id)
             .into_iter()
             .flatten()
@@ -11128,6 +11599,35 @@ impl LspStore {
         }
     }
 
+    /// Cancels the in-flight workspace diagnostics pulls of the given language servers and pulls the diagnostics again,
+    /// as the workspace has changed since the pulls were started.
+    fn restart_workspace_diagnostics(
+        &mut self,
+        server_ids: impl IntoIterator<Item = LanguageServerId>,
+    ) {
+        let Some(local) = self.as_local_mut() else {
+            return;
+        };
+        for server_id in server_ids {
+            if let Some(LanguageServerState::Running {
+                workspace_refresh_task: Some(workspace_refresh_task),
+                ..
+            }) = local.language_servers.get_mut(&server_id)
+            {
+                workspace_refresh_task.cancel_tx.try_send(()).ok();
+                workspace_refresh_task.refresh_tx.try_send(()).ok();
+            }
+        }
+    }
+
+    fn restart_all_workspace_diagnostics(&mut self) {
+        let Some(local) = self.as_local() else {
+            return;
+        };
+        let server_ids = local.language_servers.keys().copied().collect::<Vec<_>>();
+        self.restart_workspace_diagnostics(server_ids);
+    }
+
     pub fn pull_workspace_diagnostics_for_buffer(&mut self, buffer_id: BufferId, cx: &mut App) {
         let Some(buffer) = self.buffer_store().read(cx).get_existing(buffer_id).ok() else {
             return;
@@ -11291,6 +11791,11 @@ impl LspStore {
                 "workspace/fileOperations" => {
                     if let Some(options) = reg.register_options {
                         let caps = serde_json::from_value(options)?;
+                        if let Some(local) = self.as_local_mut() {
+                            local
+                                .language_server_paths_watched_for_file_operations
+                                .insert(server_id, FileOperationsWatchedForServer::new(&caps));
+                        }
                         server.update_capabilities(|capabilities| {
                             capabilities
                                 .workspace
@@ -11467,6 +11972,18 @@ impl LspStore {
                         notify_server_capabilities_updated(&server, cx);
                     }
                 }
+                "textDocument/documentLink" => {
+                    if let Some(caps) = reg
+                        .register_options
+                        .map(serde_json::from_value)
+                        .transpose()?
+                    {
+                        server.update_capabilities(|capabilities| {
+                            capabilities.document_link_provider = Some(caps);
+                        });
+                        notify_server_capabilities_updated(&server, cx);
+                    }
+                }
                 "textDocument/diagnostic" => {
                     if let Some(caps) = reg
                         .register_options
@@ -11542,6 +12059,11 @@ impl LspStore {
                     notify_server_capabilities_updated(&server, cx);
                 }
                 "workspace/fileOperations" => {
+                    if let Some(local) = self.as_local_mut() {
+                        local
+                            .language_server_paths_watched_for_file_operations
+                            .remove(&server_id);
+                    }
                     server.update_capabilities(|capabilities| {
                         capabilities
                             .workspace
@@ -11635,6 +12157,12 @@ impl LspStore {
                     });
                     notify_server_capabilities_updated(&server, cx);
                 }
+                "textDocument/documentLink" => {
+                    server.update_capabilities(|capabilities| {
+                        capabilities.document_link_provider = None;
+                    });
+                    notify_server_capabilities_updated(&server, cx);
+                }
                 "textDocument/diagnostic" => {
                     server.update_capabilities(|capabilities| {
                         capabilities.diagnostic_provider = None;
@@ -11845,6 +12373,7 @@ fn lsp_workspace_diagnostics_refresh(
 
     let (progress_tx, mut progress_rx) = mpsc::channel(1);
     let (mut refresh_tx, mut refresh_rx) = mpsc::channel(1);
+    let (cancel_tx, mut cancel_rx) = mpsc::channel(1);
     refresh_tx.try_send(()).ok();
 
     let workspace_query_language_server = cx.spawn(async move |lsp_store, cx| {
@@ -11857,6 +12386,32 @@ fn lsp_workspace_diagnostics_refresh(
                 return;
             };
 
+            let Ok(settings) = cx.update(|cx| {
+                ProjectSettings::get_global(cx)
+                    .diagnostics
+                    .lsp_pull_diagnostics
+            }) else {
+                return;
+            };
+            if !settings.enabled || !settings.workspace_diagnostics {
+                continue;
+            }
+
+            // Coalesce the refreshes coming in a quick succession (e.g. typing) into a single pull.
+            let debounce = Duration::from_millis(settings.workspace_debounce_ms);
+            if !debounce.is_zero() {
+                loop {
+                    let timer = pin!(cx.background_executor().timer(debounce));
+                    let refresh = pin!(refresh_rx.recv());
+                    match select(timer, refresh).await {
+                        Either::Left(..) => break,
+                        Either::Right((Some(()), _)) => continue,
+                        Either::Right((None, _)) => return,
+                    }
+                }
+            }
+            cancel_rx.try_recv().ok();
+
             'request: loop {
                 requests += 1;
                 if attempts > max_attempts {
@@ -11865,10 +12420,12 @@ fn lsp_workspace_diagnostics_refresh(
                     );
                     return;
                 }
-                let backoff_millis = (50 * (1 << attempts)).clamp(30, 1000);
-                cx.background_executor()
-                    .timer(Duration::from_millis(backoff_millis))
-                    .await;
+                if attempts > 0 {
+                    let backoff_millis = (50 * (1 << attempts)).clamp(30, 1000);
+                    cx.background_executor()
+                        .timer(Duration::from_millis(backoff_millis))
+                        .await;
+                }
                 attempts += 1;
 
                 let Ok(previous_result_ids) = lsp_store.update(cx, |lsp_store, _| {
@@ -11893,22 +12450,34 @@ fn lsp_workspace_diagnostics_refresh(
                 let timer =
                     LanguageServer::default_request_timer(cx.background_executor().clone()).fuse();
                 let progress = pin!(progress_rx.recv().fuse());
-                let response_result = server
-                    .request_with_timer::<lsp::WorkspaceDiagnosticRequest, _>(
-                        lsp::WorkspaceDiagnosticParams {
-                            previous_result_ids,
-                            identifier: identifier.clone(),
-                            work_done_progress_params: Default::default(),
-                            partial_result_params: lsp::PartialResultParams {
-                                partial_result_token: Some(lsp::ProgressToken::String(token)),
-                            },
+                let request = server.request_with_timer::<lsp::WorkspaceDiagnosticRequest, _>(
+                    lsp::WorkspaceDiagnosticParams {
+                        previous_result_ids,
+                        identifier: identifier.clone(),
+                        work_done_progress_params: Default::default(),
+                        partial_result_params: lsp::PartialResultParams {
+                            partial_result_token: Some(lsp::ProgressToken::String(token)),
                         },
-                        select(timer, progress).then(|either| match either {
-                            Either::Left((message, ..)) => ready(message).left_future(),
-                            Either::Right(..) => pending::<String>().right_future(),
-                        }),
-                    )
-                    .await;
+                    },
+                    select(timer, progress).then(|either| match either {
+                        Either::Left((message, ..)) => ready(message).left_future(),
+                        Either::Right(..) => pending::<String>().right_future(),
+                    }),
+                );
+
+                // The workspace has changed (e.g. worktrees were added or removed, files were
+                // changed on disk), so the in-flight pull is outdated: drop it, which cancels the
+                // request on the server side, and pull again for the refresh sent along with the
+                // cancellation, once the debounce is over.
+                let response_result = match select(pin!(request), pin!(cancel_rx.recv())).await {
+                    Either::Left((response_result, _)) => response_result,
+                    Either::Right((Some(()), _)) => {
+                        log::debug!("Workspace changed, restarting the workspace diagnostics pull");
+                        attempts = 0;
+                        break 'request;
+                    }
+                    Either::Right((None, _)) => return,
+                };
 
                 // https://microsoft.github.io/language-server-protocol/specifications/lsp/3.17/specification/#diagnostic_refresh
                 // >  If a server closes a workspace diagnostic pull request the client should re-trigger the request.
@@ -11948,6 +12517,7 @@ fn lsp_workspace_diagnostics_refresh(
 
     Some(WorkspaceRefreshTask {
         refresh_tx,
+        cancel_tx,
         progress_tx,
         task: workspace_query_language_server,
     })
@@ -11989,6 +12559,37 @@ fn resolve_word_completion(snapshot: &BufferSnapshot, completion: &mut Completio
 
 impl EventEmitter<LspStoreEvent> for LspStore {}
 
+/// Orders the servers by their priority for the feature, as configured in
+/// `global_lsp_settings.feature_servers`, dropping the excluded ones.
+fn prioritize_servers_for_feature<T>(
+    feature: Option<LspFeature>,
+    servers: impl IntoIterator<Item = (LanguageServerName, T)>,
+    cx: &App,
+) -> Vec<T> {
+    match feature {
+        Some(feature) => ProjectSettings::get_global(cx)
+            .global_lsp_settings
+            .feature_servers
+            .prioritize(feature, servers),
+        None => servers.into_iter().map(|(_, server)| server).collect(),
+    }
+}
+
+/// Drops the completions already returned by a higher priority server.
+fn dedup_completion_responses(mut responses: Vec<CompletionResponse>) -> Vec<CompletionResponse> {
+    let mut seen_completions = HashSet::default();
+    for response in &mut responses {
+        response.completions.retain(|completion| {
+            seen_completions.insert((
+                completion.replace_range.clone(),
+                completion.new_text.clone(),
+                completion.label.text.clone(),
+            ))
+        });
+    }
+    responses
+}
+
 fn remove_empty_hover_blocks(mut hover: Hover) -> Option<Hover> {
     hover
         .contents
@@ -12075,49 +12676,266 @@ pub enum LanguageServerToQuery {
     Other(LanguageServerId),
 }
 
-#[derive(Default)]
-struct RenamePathsWatchedForServer {
-    did_rename: Vec<RenameActionPredicate>,
-    will_rename: Vec<RenameActionPredicate>,
+/// A file system operation on project entries that language servers can be notified about,
+/// via the `workspace/will*Files` requests and `workspace/did*Files` notifications.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FileOperation {
+    Create {
+        path: PathBuf,
+        is_dir: bool,
+    },
+    Delete {
+        path: PathBuf,
+        is_dir: bool,
+    },
+    Rename {
+        old_path: PathBuf,
+        new_path: PathBuf,
+        is_dir: bool,
+    },
 }
 
-impl RenamePathsWatchedForServer {
-    fn with_did_rename_patterns(
-        mut self,
-        did_rename: Option<&FileOperationRegistrationOptions>,
-    ) -> Self {
-        if let Some(did_rename) = did_rename {
-            self.did_rename = did_rename
-                .filters
-                .iter()
-                .filter_map(|filter| filter.try_into().log_err())
-                .collect();
+impl FileOperation {
+    /// The path of the created or deleted entry, or the one the entry is renamed from.
+    fn path(&self) -> &Path {
+        match self {
+            Self::Create { path, .. } | Self::Delete { path, .. } => path,
+            Self::Rename { old_path, .. } => old_path,
         }
-        self
     }
-    fn with_will_rename_patterns(
-        mut self,
-        will_rename: Option<&FileOperationRegistrationOptions>,
-    ) -> Self {
-        if let Some(will_rename) = will_rename {
-            self.will_rename = will_rename
-                .filters
-                .iter()
-                .filter_map(|filter| filter.try_into().log_err())
-                .collect();
+
+    fn paths(&self) -> impl Iterator<Item = &Path> {
+        let new_path = match self {
+            Self::Rename { new_path, .. } => Some(new_path.as_path()),
+            Self::Create { .. } | Self::Delete { .. } => None,
+        };
+        iter::once(self.path()).chain(new_path)
+    }
+
+    fn is_dir(&self) -> bool {
+        match self {
+            Self::Create { is_dir, .. }
+            | Self::Delete { is_dir, .. }
+            | Self::Rename { is_dir, .. } => *is_dir,
         }
-        self
     }
 
-    fn should_send_did_rename(&self, path: &str, is_dir: bool) -> bool {
-        self.did_rename.iter().any(|pred| pred.eval(path, is_dir))
+    /// Returns the URI that servers' file operation filters are matched against,
+    /// along with the parameters to send them.
+    fn to_lsp(&self) -> Option<(String, FileOperationParams)> {
+        let uri = |path: &Path| {
+            lsp::Uri::from_file_path(path)
+                .ok()
+                .map(|uri| uri.to_string())
+        };
+        match self {
+            Self::Create { path, .. } => {
+                let uri = uri(path)?;
+                let params = CreateFilesParams {
+                    files: vec![FileCreate { uri: uri.clone() }],
+                };
+                Some((uri, FileOperationParams::Create(params)))
+            }
+            Self::Delete { path, .. } => {
+                let uri = uri(path)?;
+                let params = DeleteFilesParams {
+                    files: vec![FileDelete { uri: uri.clone() }],
+                };
+                Some((uri, FileOperationParams::Delete(params)))
+            }
+            Self::Rename {
+                old_path, new_path, ..
+            } => {
+                let old_uri = uri(old_path)?;
+                let params = RenameFilesParams {
+                    files: vec![FileRename {
+                        old_uri: old_uri.clone(),
+                        new_uri: uri(new_path)?,
+                    }],
+                };
+                Some((old_uri, FileOperationParams::Rename(params)))
+            }
+        }
+    }
+}
+
+/// Infers the file operations behind the changes a worktree saw on disk. An entry that was
+/// removed and added back at another path keeps its id, so the pair is reported as a rename.
+/// The entries inside a created, deleted or renamed directory are left out.
+fn file_operations_for_worktree_changes(
+    worktree: &Worktree,
+    changes: &[(Arc<RelPath>, ProjectEntryId, PathChange)],
+) -> Vec<FileOperation> {
+    let mut added_paths = HashMap::default();
+    let mut removed_ids = HashSet::default();
+    let mut removed_paths = Vec::new();
+    for (path, entry_id, change) in changes {
+        match change {
+            PathChange::Added => {
+                added_paths.insert(*entry_id, path);
+            }
+            PathChange::Removed => {
+                removed_ids.insert(*entry_id);
+                removed_paths.push(worktree.absolutize(path));
+            }
+            PathChange::Loaded | PathChange::Updated | PathChange::AddedOrUpdated => {}
+        }
+    }
+    let is_dir = |entry_id| {
+        worktree
+            .entry_for_id(entry_id)
+            .is_some_and(|entry| entry.is_dir())
+    };
+
+    let mut operations = Vec::new();
+    for (path, entry_id, change) in changes {
+        let operation = match change {
+            PathChange::Removed => {
+                let path = worktree.absolutize(path);
+                match added_paths.get(entry_id) {
+                    Some(new_path) => FileOperation::Rename {
+                        old_path: path,
+                        new_path: worktree.absolutize(new_path),
+                        is_dir: is_dir(*entry_id),
+                    },
+                    None => FileOperation::Delete {
+                        // The entry is gone, but a directory's children are removed along with it.
+                        is_dir: removed_paths.iter().any(|removed_path| {
+                            removed_path != &path && removed_path.starts_with(&path)
+                        }),
+                        path,
+                    },
+                }
+            }
+            PathChange::Added if !removed_ids.contains(entry_id) => FileOperation::Create {
+                path: worktree.absolutize(path),
+                is_dir: is_dir(*entry_id),
+            },
+            _ => continue,
+        };
+        operations.push(operation);
+    }
+
+    let operation_roots = operations
+        .iter()
+        .map(|operation| (mem::discriminant(operation), operation.path().to_path_buf()))
+        .collect::<HashSet<_>>();
+    operations.retain(|operation| {
+        let discriminant = mem::discriminant(operation);
+        !operation
+            .path()
+            .ancestors()
+            .skip(1)
+            .any(|ancestor| operation_roots.contains(&(discriminant, ancestor.to_path_buf())))
+    });
+    operations
+}
+
+#[derive(Clone)]
+enum FileOperationParams {
+    Create(CreateFilesParams),
+    Delete(DeleteFilesParams),
+    Rename(RenameFilesParams),
+}
+
+impl FileOperationParams {
+    fn will_method_name(&self) -> &'static str {
+        match self {
+            Self::Create(_) => "will create",
+            Self::Delete(_) => "will delete",
+            Self::Rename(_) => "will rename",
+        }
+    }
+
+    async fn request_will(
+        &self,
+        language_server: &LanguageServer,
+    ) -> Result<Option<WorkspaceEdit>> {
+        match self {
+            Self::Create(params) => language_server
+                .request::<WillCreateFiles>(params.clone())
+                .await
+                .into_response(),
+            Self::Delete(params) => language_server
+                .request::<WillDeleteFiles>(params.clone())
+                .await
+                .into_response(),
+            Self::Rename(params) => language_server
+                .request::<WillRenameFiles>(params.clone())
+                .await
+                .into_response(),
+        }
+    }
+
+    fn notify_did(&self, language_server: &LanguageServer) -> Result<()> {
+        match self {
+            Self::Create(params) => language_server.notify::<DidCreateFiles>(params),
+            Self::Delete(params) => language_server.notify::<DidDeleteFiles>(params),
+            Self::Rename(params) => language_server.notify::<DidRenameFiles>(params),
+        }
+    }
+}
+
+#[derive(Default)]
+struct FileOperationsWatchedForServer {
+    will_create: Vec<FileOperationPredicate>,
+    did_create: Vec<FileOperationPredicate>,
+    will_delete: Vec<FileOperationPredicate>,
+    did_delete: Vec<FileOperationPredicate>,
+    will_rename: Vec<FileOperationPredicate>,
+    did_rename: Vec<FileOperationPredicate>,
+}
+
+impl FileOperationsWatchedForServer {
+    fn new(capabilities: &WorkspaceFileOperationsServerCapabilities) -> Self {
+        fn predicates(
+            options: Option<&FileOperationRegistrationOptions>,
+        ) -> Vec<FileOperationPredicate> {
+            options
+                .map(|options| {
+                    options
+                        .filters
+                        .iter()
+                        .filter_map(|filter| filter.try_into().log_err())
+                        .collect()
+                })
+                .unwrap_or_default()
+        }
+
+        Self {
+            will_create: predicates(capabilities.will_create.as_ref()),
+            did_create: predicates(capabilities.did_create.as_ref()),
+            will_delete: predicates(capabilities.will_delete.as_ref()),
+            did_delete: predicates(capabilities.did_delete.as_ref()),
+            will_rename: predicates(capabilities.will_rename.as_ref()),
+            did_rename: predicates(capabilities.did_rename.as_ref()),
+        }
     }
-    fn should_send_will_rename(&self, path: &str, is_dir: bool) -> bool {
-        self.will_rename.iter().any(|pred| pred.eval(path, is_dir))
+
+    fn should_send_will(&self, operation: &FileOperation, uri: &str) -> bool {
+        let predicates = match operation {
+            FileOperation::Create { .. } => &self.will_create,
+            FileOperation::Delete { .. } => &self.will_delete,
+            FileOperation::Rename { .. } => &self.will_rename,
+        };
+        predicates
+            .iter()
+            .any(|pred| pred.eval(uri, operation.is_dir()))
+    }
+
+    fn should_send_did(&self, operation: &FileOperation, uri: &str) -> bool {
+        let predicates = match operation {
+            FileOperation::Create { .. } => &self.did_create,
+            FileOperation::Delete { .. } => &self.did_delete,
+            FileOperation::Rename { .. } => &self.did_rename,
+        };
+        predicates
+            .iter()
+            .any(|pred| pred.eval(uri, operation.is_dir()))
     }
 }
 
-impl TryFrom<&FileOperationFilter> for RenameActionPredicate {
+impl TryFrom<&FileOperationFilter> for FileOperationPredicate {
     type Error = globset::Error;
     fn try_from(ops: &FileOperationFilter) -> Result<Self, globset::Error> {
         Ok(Self {
@@ -12134,12 +12952,12 @@ impl TryFrom<&FileOperationFilter> for RenameActionPredicate {
         })
     }
 }
-struct RenameActionPredicate {
+struct FileOperationPredicate {
     glob: GlobMatcher,
     kind: Option<FileOperationPatternKind>,
 }
 
-impl RenameActionPredicate {
+impl FileOperationPredicate {
     // Returns true if language server should be notified
     fn eval(&self, path: &str, is_dir: bool) -> bool {
         self.kind.as_ref().is_none_or(|kind| {
@@ -12341,6 +13159,8 @@ impl LanguageServerLogType {
 
 pub struct WorkspaceRefreshTask {
     refresh_tx: mpsc::Sender<()>,
+    /// Cancels the in-flight workspace diagnostics request, if any, and starts a new one.
+    cancel_tx: mpsc::Sender<()>,
     progress_tx: mpsc::Sender<()>,
     #[allow(dead_code)]
     task: Task<()>,
@@ -77,9 +77,9 @@ use gpui::{
     Task, WeakEntity, Window,
 };
 use language::{
-    Buffer, BufferEvent, Capability, CodeLabel, CursorShape, Language, LanguageName,
-    LanguageRegistry, PointUtf16, ToOffset, ToPointUtf16, Toolchain, ToolchainMetadata,
-    ToolchainScope, Transaction, Unclipped, language_settings::InlayHintKind,
+    Buffer, BufferEvent, Capability, CodeLabel, CursorShape, DiskState, Language, LanguageName,
+    LanguageRegistry, ParseStatus, PointUtf16, ToOffset, ToPointUtf16, Toolchain,
+    ToolchainMetadata, ToolchainScope, Transaction, Unclipped, language_settings::InlayHintKind,
     proto::split_operations,
 };
 use lsp::{
@@ -142,9 +142,9 @@ pub use task_inventory::{
 
 pub use buffer_store::ProjectTransaction;
 pub use lsp_store::{
-    DiagnosticSummary, LanguageServerLogType, LanguageServerProgress, LanguageServerPromptRequest,
-    LanguageServerStatus, LanguageServerToQuery, LspStore, LspStoreEvent,
-    SERVER_PROGRESS_THROTTLE_TIMEOUT,
+    DiagnosticSummary, FileOperation, LanguageServerLogType, LanguageServerProgress,
+    LanguageServerPromptRequest, LanguageServerStatus, LanguageServerToQuery, LspStore,
+    LspStoreEvent, SERVER_PROGRESS_THROTTLE_TIMEOUT,
 };
 pub use toolchain_store::{ToolchainStore, Toolchains};
 const MAX_PROJECT_SEARCH_HISTORY_SIZE: usize = 500;
@@ -208,6 +208,7 @@ pub struct Project {
     search_history: SearchHistory,
     search_included_history: SearchHistory,
     search_excluded_history: SearchHistory,
+    search_replaced_history: SearchHistory,
     snippets: Entity<SnippetProvider>,
     environment: Entity<ProjectEnvironment>,
     settings_observer: Entity<SettingsObserver>,
@@ -846,6 +847,27 @@ impl std::hash::Hash for ColorPresentation {
     }
 }
 
+/// A range in a buffer that a language server reported as a link via `textDocument/documentLink`.
+///
+/// Servers may omit the `target` and provide it later via `documentLink/resolve`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DocumentLink {
+    pub server_id: LanguageServerId,
+    pub range: Range<language::Anchor>,
+    pub lsp_link: lsp::DocumentLink,
+    pub resolved: bool,
+}
+
+impl DocumentLink {
+    pub fn target(&self) -> Option<&lsp::Uri> {
+        self.lsp_link.target.as_ref()
+    }
+
+    pub fn tooltip(&self) -> Option<&str> {
+        self.lsp_link.tooltip.as_deref()
+    }
+}
+
 #[derive(Clone)]
 pub enum DirectoryLister {
     Project(Entity<Project>),
@@ -1195,6 +1217,7 @@ impl Project {
 
                 search_included_history: Self::new_search_history(),
                 search_excluded_history: Self::new_search_history(),
+                search_replaced_history: Self::new_search_history(),
 
                 toolchain_store: Some(toolchain_store),
 
@@ -1378,6 +1401,7 @@ impl Project {
 
                 search_included_history: Self::new_search_history(),
                 search_excluded_history: Self::new_search_history(),
+                search_replaced_history: Self::new_search_history(),
 
                 toolchain_store: Some(toolchain_store),
                 agent_location: None,
@@ -1629,6 +1653,7 @@ impl Project {
                 search_history: Self::new_search_history(),
                 search_included_history: Self::new_search_history(),
                 search_excluded_history: Self::new_search_history(),
+                search_replaced_history: Self::new_search_history(),
                 environment,
                 remotely_created_models: Arc::new(Mutex::new(RemotelyCreatedModels::default())),
                 toolchain_store: None,
@@ -1984,6 +2009,7 @@ impl Project {
             SearchInputKind::Query => &self.search_history,
             SearchInputKind::Include => &self.search_included_history,
             SearchInputKind::Exclude => &self.search_excluded_history,
+            SearchInputKind::Replace => &self.search_replaced_history,
         }
     }
 
@@ -1992,6 +2018,7 @@ impl Project {
             SearchInputKind::Query => &mut self.search_history,
             SearchInputKind::Include => &mut self.search_included_history,
             SearchInputKind::Exclude => &mut self.search_excluded_history,
+            SearchInputKind::Replace => &mut self.search_replaced_history,
         }
     }
 
@@ -2122,8 +2149,27 @@ impl Project {
                 "No worktree for path {project_path:?}"
             ))));
         };
-        worktree.update(cx, |worktree, cx| {
-            worktree.create_entry(project_path.path, is_directory, None, cx)
+        let operation = FileOperation::Create {
+            path: worktree.read(cx).absolutize(&project_path.path),
+            is_dir: is_directory,
+        };
+        let lsp_store = self.lsp_store().downgrade();
+        cx.spawn(async move |_, cx| {
+            let (entry, _) = LspStore::perform_file_operation(
+                lsp_store,
+                project_path.worktree_id,
+                operation,
+                cx,
+                async move |cx| {
+                    worktree
+                        .update(cx, |worktree, cx| {
+                            worktree.create_entry(project_path.path, is_directory, None, cx)
+                        })?
+                        .await
+                },
+            )
+            .await?;
+            Ok(entry)
         })
     }
 
@@ -2133,8 +2179,41 @@ impl Project {
         new_project_path: ProjectPath,
         cx: &mut Context<Self>,
     ) -> Task<Result<Option<Entry>>> {
-        self.worktree_store.update(cx, |worktree_store, cx| {
-            worktree_store.copy_entry(entry_id, new_project_path, cx)
+        let Some(is_dir) = self
+            .worktree_store
+            .read(cx)
+            .entry_for_id(entry_id, cx)
+            .map(|entry| entry.is_dir())
+        else {
+            return Task::ready(Err(anyhow!(format!("No worktree for entry {entry_id:?}"))));
+        };
+        let Some(new_worktree) = self.worktree_for_id(new_project_path.worktree_id, cx) else {
+            return Task::ready(Err(anyhow!(format!(
+                "No worktree for path {new_project_path:?}"
+            ))));
+        };
+        let operation = FileOperation::Create {
+            path: new_worktree.read(cx).absolutize(&new_project_path.path),
+            is_dir,
+        };
+        let worktree_store = self.worktree_store.clone();
+        let lsp_store = self.lsp_store().downgrade();
+        cx.spawn(async move |_, cx| {
+            let (entry, _) = LspStore::perform_file_operation(
+                lsp_store,
+                new_project_path.worktree_id,
+                operation,
+                cx,
+                async move |cx| {
+                    worktree_store
+                        .update(cx, |worktree_store, cx| {
+                            worktree_store.copy_entry(entry_id, new_project_path, cx)
+                        })?
+                        .await
+                },
+            )
+            .await?;
+            Ok(entry)
         })
     }
 
@@ -2174,33 +2253,31 @@ impl Project {
                 };
                 (root_path.join(old_path.as_std_path()), new_abs_path)
             };
-            let transaction = LspStore::will_rename_entry(
-                lsp_store.clone(),
-                worktree_id,
-                &old_abs_path,
-                &new_abs_path,
+            let operation = FileOperation::Rename {
+                old_path: old_abs_path,
+                new_path: new_abs_path,
                 is_dir,
-                cx.clone(),
+            };
+            let (entry, transaction) = LspStore::perform_file_operation(
+                lsp_store,
+                worktree_id,
+                operation,
+                cx,
+                async move |cx| {
+                    worktree_store
+                        .update(cx, |worktree_store, cx| {
+                            worktree_store.rename_entry(entry_id, new_path, cx)
+                        })?
+                        .await
+                },
             )
-            .await;
-
-            let entry = worktree_store
-                .update(cx, |worktree_store, cx| {
-                    worktree_store.rename_entry(entry_id, new_path.clone(), cx)
-                })?
-                .await?;
+            .await?;
 
             project
                 .update(cx, |_, cx| {
                     cx.emit(Event::EntryRenamed(transaction));
                 })
                 .ok();
-
-            lsp_store
-                .read_with(cx, |this, _| {
-                    this.did_rename_entry(worktree_id, &old_abs_path, &new_abs_path, is_dir);
-                })
-                .ok();
             Ok(entry)
         })
     }
@@ -2222,10 +2299,32 @@ impl Project {
         cx: &mut Context<Self>,
     ) -> Option<Task<Result<()>>> {
         let worktree = self.worktree_for_entry(entry_id, cx)?;
-        cx.emit(Event::DeletedEntry(worktree.read(cx).id(), entry_id));
-        worktree.update(cx, |worktree, cx| {
-            worktree.delete_entry(entry_id, trash, cx)
-        })
+        let worktree_id = worktree.read(cx).id();
+        let entry = worktree.read(cx).entry_for_id(entry_id)?;
+        let operation = FileOperation::Delete {
+            path: worktree.read(cx).absolutize(&entry.path),
+            is_dir: entry.is_dir(),
+        };
+        cx.emit(Event::DeletedEntry(worktree_id, entry_id));
+        let lsp_store = self.lsp_store().downgrade();
+        Some(cx.spawn(async move |_, cx| {
+            LspStore::perform_file_operation(
+                lsp_store,
+                worktree_id,
+                operation,
+                cx,
+                async move |cx| {
+                    worktree
+                        .update(cx, |worktree, cx| {
+                            worktree.delete_entry(entry_id, trash, cx)
+                        })?
+                        .context("invalid entry")?
+                        .await
+                },
+            )
+            .await?;
+            Ok(())
+        }))
     }
 
     pub fn expand_entry(
@@ -2714,8 +2813,39 @@ impl Project {
     }
 
     pub fn save_buffer(&self, buffer: Entity<Buffer>, cx: &mut Context<Self>) -> Task<Result<()>> {
-        self.buffer_store
-            .update(cx, |buffer_store, cx| buffer_store.save_buffer(buffer, cx))
+        let new_file = File::from_dyn(buffer.read(cx).file())
+            .filter(|file| file.disk_state == DiskState::New)
+            .map(|file| {
+                let operation = FileOperation::Create {
+                    path: file.worktree.read(cx).absolutize(&file.path),
+                    is_dir: false,
+                };
+                (file.worktree_id(cx), operation)
+            });
+        let Some((worktree_id, operation)) = new_file else {
+            return self
+                .buffer_store
+                .update(cx, |buffer_store, cx| buffer_store.save_buffer(buffer, cx));
+        };
+
+        // Saving a buffer for a file that does not exist on disk yet creates it.
+        let buffer_store = self.buffer_store.clone();
+        let lsp_store = self.lsp_store().downgrade();
+        cx.spawn(async move |_, cx| {
+            LspStore::perform_file_operation(
+                lsp_store,
+                worktree_id,
+                operation,
+                cx,
+                async move |cx| {
+                    buffer_store
+                        .update(cx, |buffer_store, cx| buffer_store.save_buffer(buffer, cx))?
+                        .await
+                },
+            )
+            .await?;
+            Ok(())
+        })
     }
 
     pub fn save_buffer_as(
@@ -3790,6 +3920,32 @@ impl Project {
         })
     }
 
+    pub fn document_links(
+        &mut self,
+        buffer: &Entity<Buffer>,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Option<Vec<DocumentLink>>>> {
+        let document_links = self
+            .lsp_store
+            .update(cx, |lsp_store, cx| lsp_store.document_links(buffer, cx));
+        cx.background_spawn(async move {
+            document_links
+                .await
+                .map_err(|e| anyhow!("document links fetch failed: {e:#}"))
+        })
+    }
+
+    pub fn resolve_document_link(
+        &self,
+        link: DocumentLink,
+        buffer: Entity<Buffer>,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<DocumentLink>> {
+        self.lsp_store.update(cx, |lsp_store, cx| {
+            lsp_store.resolve_document_link(link, buffer, cx)
+        })
+    }
+
     pub fn apply_code_action(
         &self,
         buffer_handle: Entity<Buffer>,
@@ -3945,6 +4101,23 @@ impl Project {
             // ranges in the buffer matched by the query.
             let mut chunks = pin!(chunks);
             'outer: while let Some(matching_buffer_chunk) = chunks.next().await {
+                if query.is_structural() {
+                    // Structural patterns match syntax trees, which need to be up to date.
+                    let parse_statuses = matching_buffer_chunk
+                        .iter()
+                        .map(|buffer| buffer.read_with(cx, |buffer, _| buffer.parse_status()))
+                        .collect::<Result<Vec<_>>>()?;
+                    futures::future::try_join_all(parse_statuses.into_iter().map(
+                        |mut parse_status| async move {
+                            while *parse_status.borrow() != ParseStatus::Idle {
+                                parse_status.changed().await?;
+                            }
+                            anyhow::Ok(())
+                        },
+                    ))
+                    .await?;
+                }
+
                 let mut chunk_results = Vec::with_capacity(matching_buffer_chunk.len());
                 for buffer in matching_buffer_chunk {
                     let query = query.clone();
@@ -3999,6 +4172,16 @@ impl Project {
         cx: &mut Context<Project>,
     ) -> Receiver<Entity<Buffer>> {
         if self.is_local() {
+            if let Some(project_paths) = query.scope().project_paths(
+                &self.buffer_store,
+                &self.git_store,
+                &self.lsp_store,
+                cx,
+            ) {
+                return self.buffer_store.update(cx, |buffer_store, cx| {
+                    buffer_store.find_scoped_search_candidates(query, project_paths, limit, cx)
+                });
+            }
             let fs = self.fs.clone();
             self.buffer_store.update(cx, |buffer_store, cx| {
                 buffer_store.find_search_candidates(query, limit, fs, cx)
@@ -1,10 +1,11 @@
 use anyhow::Context as _;
 use collections::HashMap;
-use context_server::ContextServerCommand;
+use context_server::{ContextServerCommand, ContextServerHttpConfig};
 use dap::adapters::DebugAdapterName;
 use fs::Fs;
 use futures::StreamExt as _;
 use gpui::{App, AsyncApp, BorrowAppContext, Context, Entity, EventEmitter, Subscription, Task};
+use language::language_settings::LanguageSettings;
 use lsp::LanguageServerName;
 use paths::{
     EDITORCONFIG_NAME, local_debug_file_relative_path, local_settings_file_relative_path,
@@ -112,6 +113,68 @@ pub struct GlobalLspSettings {
     ///
     /// Default: `true`
     pub button: bool,
+    /// Which language servers answer each LSP feature, and in what order.
+    pub feature_servers: LspFeatureServers,
+}
+
+/// LSP features whose language servers can be prioritized or excluded
+/// via `global_lsp_settings.feature_servers`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LspFeature {
+    Completions,
+    Hover,
+    Formatting,
+    Rename,
+    CodeActions,
+}
+
+/// Per-feature language server priority, in the `language_servers` syntax.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LspFeatureServers {
+    pub completions: Vec<String>,
+    pub hover: Vec<String>,
+    pub formatting: Vec<String>,
+    pub rename: Vec<String>,
+    pub code_actions: Vec<String>,
+}
+
+impl LspFeatureServers {
+    pub fn configured(&self, feature: LspFeature) -> &[String] {
+        match feature {
+            LspFeature::Completions => &self.completions,
+            LspFeature::Hover => &self.hover,
+            LspFeature::Formatting => &self.formatting,
+            LspFeature::Rename => &self.rename,
+            LspFeature::CodeActions => &self.code_actions,
+        }
+    }
+
+    /// Orders the given servers by their priority for the feature,
+    /// dropping the excluded ones.
+    pub fn prioritize<T>(
+        &self,
+        feature: LspFeature,
+        servers: impl IntoIterator<Item = (LanguageServerName, T)>,
+    ) -> Vec<T> {
+        let mut servers = servers.into_iter().collect::<Vec<_>>();
+        let available = servers
+            .iter()
+            .map(|(name, _)| name.clone())
+            .collect::<Vec<_>>();
+        let resolved =
+            LanguageSettings::resolve_language_servers(self.configured(feature), &available);
+        let mut prioritized = Vec::with_capacity(servers.len());
+        for name in resolved {
+            // The same server may be attached to a buffer several times, e.g. once per worktree.
+            while let Some(ix) = servers
+                .iter()
+                .position(|(server_name, _)| *server_name == name)
+            {
+                prioritized.push(servers.remove(ix).1);
+            }
+        }
+        prioritized
+    }
 }
 
 #[derive(Deserialize, Serialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
@@ -135,6 +198,14 @@ pub enum ContextServerSettings {
         /// are supported.
         settings: serde_json::Value,
     },
+    Http {
+        /// Whether the context server is enabled.
+        #[serde(default = "default_true")]
+        enabled: bool,
+
+        #[serde(flatten)]
+        http: ContextServerHttpConfig,
+    },
 }
 
 impl From<settings::ContextServerSettingsContent> for ContextServerSettings {
@@ -146,6 +217,9 @@ impl From<settings::ContextServerSettingsContent> for ContextServerSettings {
             settings::ContextServerSettingsContent::Extension { enabled, settings } => {
                 ContextServerSettings::Extension { enabled, settings }
             }
+            settings::ContextServerSettingsContent::Http { enabled, http } => {
+                ContextServerSettings::Http { enabled, http }
+            }
         }
     }
 }
@@ -158,6 +232,9 @@ impl Into<settings::ContextServerSettingsContent> for ContextServerSettings {
             ContextServerSettings::Extension { enabled, settings } => {
                 settings::ContextServerSettingsContent::Extension { enabled, settings }
             }
+            ContextServerSettings::Http { enabled, http } => {
+                settings::ContextServerSettingsContent::Http { enabled, http }
+            }
         }
     }
 }
@@ -174,6 +251,7 @@ impl ContextServerSettings {
         match self {
             ContextServerSettings::Custom { enabled, .. } => *enabled,
             ContextServerSettings::Extension { enabled, .. } => *enabled,
+            ContextServerSettings::Http { enabled, .. } => *enabled,
         }
     }
 
@@ -181,6 +259,7 @@ impl ContextServerSettings {
         match self {
             ContextServerSettings::Custom { enabled: e, .. } => *e = enabled,
             ContextServerSettings::Extension { enabled: e, .. } => *e = enabled,
+            ContextServerSettings::Http { enabled: e, .. } => *e = enabled,
         }
     }
 }
@@ -434,6 +513,14 @@ pub struct LspPullDiagnosticsSettings {
     ///
     /// Default: 50
     pub debounce_ms: u64,
+    /// Whether to pull diagnostics for the whole workspace, including files that are not open.
+    ///
+    /// Default: true
+    pub workspace_diagnostics: bool,
+    /// Minimum time to wait between two consecutive workspace diagnostics pulls.
+    ///
+    /// Default: 1000
+    pub workspace_debounce_ms: u64,
 }
 
 impl Settings for ProjectSettings {
@@ -491,6 +578,22 @@ impl Settings for ProjectSettings {
                     .unwrap()
                     .button
                     .unwrap(),
+                feature_servers: {
+                    let feature_servers = content
+                        .global_lsp_settings
+                        .as_ref()
+                        .unwrap()
+                        .feature_servers
+                        .clone()
+                        .unwrap();
+                    LspFeatureServers {
+                        completions: feature_servers.completions.unwrap(),
+                        hover: feature_servers.hover.unwrap(),
+                        formatting: feature_servers.formatting.unwrap(),
+                        rename: feature_servers.rename.unwrap(),
+                        code_actions: feature_servers.code_actions.unwrap(),
+                    }
+                },
             },
             dap: project
                 .dap
@@ -504,6 +607,8 @@ impl Settings for ProjectSettings {
                 lsp_pull_diagnostics: LspPullDiagnosticsSettings {
                     enabled: lsp_pull_diagnostics.enabled.unwrap(),
                     debounce_ms: lsp_pull_diagnostics.debounce_ms.unwrap(),
+                    workspace_diagnostics: lsp_pull_diagnostics.workspace_diagnostics.unwrap(),
+                    workspace_debounce_ms: lsp_pull_diagnostics.workspace_debounce_ms.unwrap(),
                 },
                 inline: InlineDiagnosticsSettings {
                     enabled: inline_diagnostics.enabled.unwrap(),
@@ -552,29 +657,48 @@ impl Settings for ProjectSettings {
             env: Option<HashMap<String, String>>,
             // note: we don't support envFile and type
         }
+        #[derive(Deserialize)]
+        struct VsCodeContextServerUrl {
+            url: String,
+            headers: Option<HashMap<String, String>>,
+            #[serde(rename = "type")]
+            kind: Option<String>,
+        }
         if let Some(mcp) = vscode.read_value("mcp").and_then(|v| v.as_object()) {
             current
                 .project
                 .context_servers
                 .extend(mcp.iter().filter_map(|(k, v)| {
-                    Some((
-                        k.clone().into(),
+                    let settings = if let Ok(cmd) =
+                        serde_json::from_value::<VsCodeContextServerCommand>(v.clone())
+                    {
                         settings::ContextServerSettingsContent::Custom {
                             enabled: true,
-                            command: serde_json::from_value::<VsCodeContextServerCommand>(
-                                v.clone(),
-                            )
-                            .ok()
-                            .map(|cmd| {
-                                settings::ContextServerCommand {
-                                    path: cmd.command,
-                                    args: cmd.args.unwrap_or_default(),
-                                    env: cmd.env,
-                                    timeout: None,
-                                }
-                            })?,
-                        },
-                    ))
+                            command: settings::ContextServerCommand {
+                                path: cmd.command,
+                                args: cmd.args.unwrap_or_default(),
+                                env: cmd.env,
+                                timeout: None,
+                            },
+                        }
+                    } else {
+                        let server =
+                            serde_json::from_value::<VsCodeContextServerUrl>(v.clone()).ok()?;
+                        settings::ContextServerSettingsContent::Http {
+                            enabled: true,
+                            http: settings::ContextServerHttpConfig {
+                                url: server.url,
+                                headers: server.headers.unwrap_or_default(),
+                                transport: if server.kind.as_deref() == Some("sse") {
+                                    settings::ContextServerHttpTransport::Sse
+                                } else {
+                                    settings::ContextServerHttpTransport::StreamableHttp
+                                },
+                                timeout: None,
+                            },
+                        }
+                    };
+                    Some((k.clone().into(), settings))
                 }));
         }
 
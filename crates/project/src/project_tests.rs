@@ -2719,6 +2719,201 @@ async fn test_diagnostics_from_multiple_language_servers(cx: &mut gpui::TestAppC
     });
 }
 
+#[gpui::test]
+async fn test_workspace_diagnostics_for_unopened_files(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        path!("/dir"),
+        json!({
+            "a.rs": "fn a() {}",
+            "b.rs": "fn b() {}",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, [path!("/dir").as_ref()], cx).await;
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    let mut fake_servers = language_registry.register_fake_lsp(
+        "Rust",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                diagnostic_provider: Some(lsp::DiagnosticServerCapabilities::Options(
+                    lsp::DiagnosticOptions {
+                        workspace_diagnostics: true,
+                        ..lsp::DiagnosticOptions::default()
+                    },
+                )),
+                ..lsp::ServerCapabilities::default()
+            },
+            ..FakeLspAdapter::default()
+        },
+    );
+
+    let (buffer, _handle) = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer_with_lsp(path!("/dir/a.rs"), cx)
+        })
+        .await
+        .unwrap();
+
+    let fake_server = fake_servers.next().await.unwrap();
+    let previous_result_ids = Arc::new(Mutex::new(Vec::new()));
+    let mut workspace_pulls = fake_server
+        .set_request_handler::<lsp::request::WorkspaceDiagnosticRequest, _, _>({
+            let previous_result_ids = previous_result_ids.clone();
+            move |params, _| {
+                previous_result_ids.lock().push(params.previous_result_ids);
+                async move {
+                    Ok(lsp::WorkspaceDiagnosticReportResult::Report(
+                        lsp::WorkspaceDiagnosticReport {
+                            items: vec![lsp::WorkspaceDocumentDiagnosticReport::Full(
+                                lsp::WorkspaceFullDocumentDiagnosticReport {
+                                    uri: Uri::from_file_path(path!("/dir/b.rs")).unwrap(),
+                                    version: None,
+                                    full_document_diagnostic_report:
+                                        lsp::FullDocumentDiagnosticReport {
+                                            result_id: Some("b_result".to_string()),
+                                            items: vec![lsp::Diagnostic {
+                                                range: lsp::Range::new(
+                                                    lsp::Position::new(0, 3),
+                                                    lsp::Position::new(0, 4),
+                                                ),
+                                                severity: Some(DiagnosticSeverity::ERROR),
+                                                message: "error in b".to_string(),
+                                                ..lsp::Diagnostic::default()
+                                            }],
+                                        },
+                                },
+                            )],
+                        },
+                    ))
+                }
+            }
+        });
+
+    workspace_pulls.next().await.unwrap();
+    cx.executor().run_until_parked();
+    project.update(cx, |project, cx| {
+        assert_eq!(
+            project.diagnostic_summary(false, cx),
+            DiagnosticSummary {
+                error_count: 1,
+                warning_count: 0,
+            },
+            "Workspace diagnostics should be reported for files that are not opened"
+        );
+    });
+    assert_eq!(previous_result_ids.lock().len(), 1);
+    assert!(previous_result_ids.lock()[0].is_empty());
+
+    buffer.update(cx, |buffer, cx| buffer.edit([(0..0, "// ")], None, cx));
+    workspace_pulls.next().await.unwrap();
+    cx.executor().run_until_parked();
+    assert_eq!(
+        previous_result_ids.lock().len(),
+        2,
+        "Buffer edits should trigger a single, debounced workspace diagnostics pull"
+    );
+    assert_eq!(
+        previous_result_ids.lock()[1],
+        vec![lsp::PreviousResultId {
+            uri: Uri::from_file_path(path!("/dir/b.rs")).unwrap(),
+            value: "b_result".to_string(),
+        }],
+        "Result ids of the files that are not opened should be sent back to the server"
+    );
+}
+
+#[gpui::test]
+async fn test_cancelled_workspace_diagnostics_pulls_are_debounced(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(path!("/dir"), json!({ "a.rs": "fn a() {}" }))
+        .await;
+    fs.insert_tree(path!("/other"), json!({ "b.rs": "fn b() {}" }))
+        .await;
+    fs.insert_tree(path!("/another"), json!({ "c.rs": "fn c() {}" }))
+        .await;
+
+    let project = Project::test(fs, [path!("/dir").as_ref()], cx).await;
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    let mut fake_servers = language_registry.register_fake_lsp(
+        "Rust",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                diagnostic_provider: Some(lsp::DiagnosticServerCapabilities::Options(
+                    lsp::DiagnosticOptions {
+                        workspace_diagnostics: true,
+                        ..lsp::DiagnosticOptions::default()
+                    },
+                )),
+                ..lsp::ServerCapabilities::default()
+            },
+            ..FakeLspAdapter::default()
+        },
+    );
+
+    let (_buffer, _handle) = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer_with_lsp(path!("/dir/a.rs"), cx)
+        })
+        .await
+        .unwrap();
+
+    // The server never answers, so every pull is in flight when the workspace changes.
+    let fake_server = fake_servers.next().await.unwrap();
+    let (pulls_tx, mut workspace_pulls) = futures::channel::mpsc::unbounded();
+    fake_server.set_request_handler::<lsp::request::WorkspaceDiagnosticRequest, _, _>(
+        move |_, _| {
+            pulls_tx.unbounded_send(()).ok();
+            future::pending()
+        },
+    );
+    workspace_pulls.next().await.unwrap();
+
+    let debounce = cx.update(|cx| {
+        std::time::Duration::from_millis(
+            ProjectSettings::get_global(cx)
+                .diagnostics
+                .lsp_pull_diagnostics
+                .workspace_debounce_ms,
+        )
+    });
+    let mut add_worktree = |path: &'static str| {
+        let worktree = project.update(cx, |project, cx| {
+            project.find_or_create_worktree(path, true, cx)
+        });
+        cx.executor().run_until_parked();
+        worktree
+    };
+
+    // Each added worktree cancels the in-flight pull, and the pulls that replace them wait for
+    // the debounce instead of being sent right away.
+    let other_worktree = add_worktree(path!("/other"));
+    let another_worktree = add_worktree(path!("/another"));
+    assert!(
+        workspace_pulls.try_next().is_err(),
+        "Cancelled workspace diagnostics pulls should not be restarted before the debounce"
+    );
+
+    cx.executor().advance_clock(debounce);
+    cx.executor().run_until_parked();
+    workspace_pulls.next().await.unwrap();
+    cx.executor().run_until_parked();
+    assert!(
+        workspace_pulls.try_next().is_err(),
+        "Workspace changes during the debounce should be coalesced into a single pull"
+    );
+
+    other_worktree.await.unwrap();
+    another_worktree.await.unwrap();
+}
+
 #[gpui::test]
 async fn test_edits_from_lsp2_with_past_version(cx: &mut gpui::TestAppContext) {
     init_test(cx);
@@ -5223,6 +5418,233 @@ async fn test_lsp_rename_notifications(cx: &mut gpui::TestAppContext) {
     assert_eq!(resolved_workspace_edit.get(), Some(&expected_edit));
 }
 
+#[gpui::test]
+async fn test_lsp_create_and_delete_notifications(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        path!("/dir"),
+        json!({
+            "one.rs": "const ONE: usize = 1;",
+            "two.rs": "const TWO: usize = one::ONE + one::ONE;"
+        }),
+    )
+    .await;
+    let project = Project::test(fs.clone(), [path!("/dir").as_ref()], cx).await;
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    let watched_paths = lsp::FileOperationRegistrationOptions {
+        filters: vec![FileOperationFilter {
+            scheme: Some("file".to_owned()),
+            pattern: lsp::FileOperationPattern {
+                glob: "**/*.rs".to_owned(),
+                matches: Some(lsp::FileOperationPatternKind::File),
+                options: None,
+            },
+        }],
+    };
+    let mut fake_servers = language_registry.register_fake_lsp(
+        "Rust",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                workspace: Some(lsp::WorkspaceServerCapabilities {
+                    workspace_folders: None,
+                    file_operations: Some(lsp::WorkspaceFileOperationsServerCapabilities {
+                        did_create: Some(watched_paths.clone()),
+                        will_create: Some(watched_paths.clone()),
+                        did_delete: Some(watched_paths.clone()),
+                        will_delete: Some(watched_paths),
+                        ..Default::default()
+                    }),
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    );
+
+    let (buffer, _handle) = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer_with_lsp(path!("/dir/two.rs"), cx)
+        })
+        .await
+        .unwrap();
+
+    let fake_server = fake_servers.next().await.unwrap();
+    let response = project.update(cx, |project, cx| {
+        let worktree_id = project.worktrees(cx).next().unwrap().read(cx).id();
+        project.create_entry((worktree_id, rel_path("three.rs")), false, cx)
+    });
+    fake_server
+        .set_request_handler::<lsp::WillCreateFiles, _, _>(|params, _| async move {
+            assert_eq!(params.files.len(), 1);
+            assert_eq!(params.files[0].uri, uri!("file:///dir/three.rs"));
+            Ok(None)
+        })
+        .next()
+        .await
+        .unwrap();
+    response.await.unwrap();
+    fake_server
+        .handle_notification::<lsp::notification::DidCreateFiles, _>(|params, _| {
+            assert_eq!(params.files.len(), 1);
+            assert_eq!(params.files[0].uri, uri!("file:///dir/three.rs"));
+        })
+        .next()
+        .await
+        .unwrap();
+    assert!(fs.is_file(path!("/dir/three.rs").as_ref()).await);
+
+    let response = project.update(cx, |project, cx| {
+        let worktree = project.worktrees(cx).next().unwrap();
+        let entry = worktree
+            .read(cx)
+            .entry_for_path(rel_path("one.rs"))
+            .unwrap();
+        project.delete_entry(entry.id, false, cx).unwrap()
+    });
+    fake_server
+        .set_request_handler::<lsp::WillDeleteFiles, _, _>(|params, _| async move {
+            assert_eq!(params.files.len(), 1);
+            assert_eq!(params.files[0].uri, uri!("file:///dir/one.rs"));
+            Ok(Some(lsp::WorkspaceEdit {
+                changes: Some(std::collections::HashMap::from_iter([(
+                    Uri::from_str(uri!("file:///dir/two.rs")).unwrap(),
+                    vec![lsp::TextEdit::new(
+                        lsp::Range::new(lsp::Position::new(0, 19), lsp::Position::new(0, 38)),
+                        "2".to_owned(),
+                    )],
+                )])),
+                ..Default::default()
+            }))
+        })
+        .next()
+        .await
+        .unwrap();
+    response.await.unwrap();
+    fake_server
+        .handle_notification::<lsp::notification::DidDeleteFiles, _>(|params, _| {
+            assert_eq!(params.files.len(), 1);
+            assert_eq!(params.files[0].uri, uri!("file:///dir/one.rs"));
+        })
+        .next()
+        .await
+        .unwrap();
+    assert!(!fs.is_file(path!("/dir/one.rs").as_ref()).await);
+    buffer.read_with(cx, |buffer, _| {
+        assert_eq!(
+            buffer.text(),
+            "const TWO: usize = 2;",
+            "Edits returned for willDelete should be applied before the file is deleted"
+        );
+    });
+}
+
+#[gpui::test]
+async fn test_lsp_notifications_for_external_file_operations(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        path!("/dir"),
+        json!({
+            "one.rs": "const ONE: usize = 1;",
+            "two.rs": "const TWO: usize = one::ONE + one::ONE;"
+        }),
+    )
+    .await;
+    let project = Project::test(fs.clone(), [path!("/dir").as_ref()], cx).await;
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    let watched_paths = lsp::FileOperationRegistrationOptions {
+        filters: vec![FileOperationFilter {
+            scheme: Some("file".to_owned()),
+            pattern: lsp::FileOperationPattern {
+                glob: "**/*.rs".to_owned(),
+                matches: Some(lsp::FileOperationPatternKind::File),
+                options: None,
+            },
+        }],
+    };
+    let mut fake_servers = language_registry.register_fake_lsp(
+        "Rust",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                workspace: Some(lsp::WorkspaceServerCapabilities {
+                    workspace_folders: None,
+                    file_operations: Some(lsp::WorkspaceFileOperationsServerCapabilities {
+                        did_rename: Some(watched_paths.clone()),
+                        will_rename: Some(watched_paths),
+                        ..Default::default()
+                    }),
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    );
+
+    let (buffer, _handle) = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer_with_lsp(path!("/dir/two.rs"), cx)
+        })
+        .await
+        .unwrap();
+    let fake_server = fake_servers.next().await.unwrap();
+    let mut will_rename_requests =
+        fake_server.set_request_handler::<lsp::WillRenameFiles, _, _>(|params, _| async move {
+            assert_eq!(params.files.len(), 1);
+            assert_eq!(params.files[0].old_uri, uri!("file:///dir/one.rs"));
+            assert_eq!(params.files[0].new_uri, uri!("file:///dir/three.rs"));
+            Ok(Some(lsp::WorkspaceEdit {
+                changes: Some(std::collections::HashMap::from_iter([(
+                    Uri::from_str(uri!("file:///dir/two.rs")).unwrap(),
+                    vec![
+                        lsp::TextEdit::new(
+                            lsp::Range::new(lsp::Position::new(0, 19), lsp::Position::new(0, 22)),
+                            "three".to_owned(),
+                        ),
+                        lsp::TextEdit::new(
+                            lsp::Range::new(lsp::Position::new(0, 30), lsp::Position::new(0, 33)),
+                            "three".to_owned(),
+                        ),
+                    ],
+                )])),
+                ..Default::default()
+            }))
+        });
+    let mut did_rename_notifications = fake_server
+        .handle_notification::<lsp::notification::DidRenameFiles, _>(|params, _| {
+            assert_eq!(params.files.len(), 1);
+            assert_eq!(params.files[0].old_uri, uri!("file:///dir/one.rs"));
+            assert_eq!(params.files[0].new_uri, uri!("file:///dir/three.rs"));
+        });
+
+    fs.rename(
+        path!("/dir/one.rs").as_ref(),
+        path!("/dir/three.rs").as_ref(),
+        Default::default(),
+    )
+    .await
+    .unwrap();
+    did_rename_notifications.next().await.unwrap();
+    cx.executor().run_until_parked();
+    assert!(
+        will_rename_requests.try_next().is_err(),
+        "Files renamed outside of Zed should not be reported with willRename"
+    );
+    buffer.read_with(cx, |buffer, _| {
+        assert_eq!(
+            buffer.text(),
+            "const TWO: usize = one::ONE + one::ONE;",
+            "No server edits should be applied for files renamed outside of Zed"
+        );
+    });
+}
+
 #[gpui::test]
 async fn test_rename(cx: &mut gpui::TestAppContext) {
     // hi
@@ -5440,6 +5862,199 @@ async fn test_search(cx: &mut gpui::TestAppContext) {
     );
 }
 
+#[gpui::test]
+async fn test_structural_search(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        path!("/dir"),
+        json!({
+            "one.rs": "fn one() { let a = b.unwrap(); }",
+            "two.rs": "fn two() { c.unwrap_or(d); e(f).unwrap(); }",
+            "three.txt": "g.unwrap()",
+        }),
+    )
+    .await;
+    let project = Project::test(fs.clone(), [path!("/dir").as_ref()], cx).await;
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+
+    assert_eq!(
+        search(
+            &project,
+            SearchQuery::structural(
+                "$x.unwrap()",
+                false,
+                Default::default(),
+                Default::default(),
+                false,
+                None,
+            )
+            .unwrap(),
+            cx
+        )
+        .await
+        .unwrap(),
+        HashMap::from_iter([
+            (path!("dir/one.rs").to_string(), vec![19..29]),
+            (path!("dir/two.rs").to_string(), vec![27..40]),
+            // Contains the pattern's words, but has no syntax tree to match against.
+            (path!("dir/three.txt").to_string(), vec![]),
+        ])
+    );
+}
+
+#[gpui::test]
+async fn test_fuzzy_search(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        path!("/dir"),
+        json!({
+            "one.rs": "config_loader()\nlet cfg_ld = load();\nfn main() {}",
+            "two.rs": "const CONFIG: &str = \"\";",
+        }),
+    )
+    .await;
+    let project = Project::test(fs.clone(), [path!("/dir").as_ref()], cx).await;
+
+    assert_eq!(
+        search(
+            &project,
+            SearchQuery::fuzzy(
+                "cfgld",
+                false,
+                false,
+                Default::default(),
+                Default::default(),
+                false,
+                None,
+            )
+            .unwrap(),
+            cx
+        )
+        .await
+        .unwrap(),
+        HashMap::from_iter([(path!("dir/one.rs").to_string(), vec![20..26, 0..11])]),
+        "Each matching line should be a single match, with the best scoring lines first"
+    );
+}
+
+#[gpui::test]
+async fn test_search_scopes(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        path!("/dir"),
+        json!({
+            ".git": {},
+            "a.rs": "const A: usize = 1;",
+            "b.rs": "const B: usize = A;",
+            "c.rs": "const C: usize = A;",
+        }),
+    )
+    .await;
+    fs.set_head_and_index_for_repo(
+        path!("/dir/.git").as_ref(),
+        &[
+            ("a.rs", "const A: usize = 1;".into()),
+            ("b.rs", "const B: usize = 2;".into()),
+            ("c.rs", "const C: usize = A;".into()),
+        ],
+    );
+    let project = Project::test(fs.clone(), [path!("/dir").as_ref()], cx).await;
+    cx.run_until_parked();
+
+    let query_in_scope = |scope| {
+        SearchQuery::text(
+            "usize",
+            false,
+            true,
+            false,
+            Default::default(),
+            Default::default(),
+            false,
+            None,
+        )
+        .unwrap()
+        .with_scope(scope)
+    };
+
+    let _buffer_a = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer(path!("/dir/a.rs"), cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(
+        search(
+            &project,
+            query_in_scope(search::SearchScope::OpenBuffers),
+            cx
+        )
+        .await
+        .unwrap(),
+        HashMap::from_iter([(path!("dir/a.rs").to_string(), vec![9..14])])
+    );
+
+    assert_eq!(
+        search(&project, query_in_scope(search::SearchScope::All), cx)
+            .await
+            .unwrap(),
+        HashMap::from_iter([
+            (path!("dir/a.rs").to_string(), vec![9..14]),
+            (path!("dir/b.rs").to_string(), vec![9..14]),
+            (path!("dir/c.rs").to_string(), vec![9..14]),
+        ])
+    );
+    assert_eq!(
+        search(
+            &project,
+            query_in_scope(search::SearchScope::GitChanged),
+            cx
+        )
+        .await
+        .unwrap(),
+        HashMap::from_iter([(path!("dir/b.rs").to_string(), vec![9..14])])
+    );
+
+    let lsp_store = project.read_with(cx, |project, _| project.lsp_store());
+    lsp_store.update(cx, |lsp_store, cx| {
+        lsp_store
+            .update_diagnostics(
+                LanguageServerId(0),
+                lsp::PublishDiagnosticsParams {
+                    uri: Uri::from_file_path(path!("/dir/c.rs")).unwrap(),
+                    version: None,
+                    diagnostics: vec![lsp::Diagnostic {
+                        range: lsp::Range::new(lsp::Position::new(0, 6), lsp::Position::new(0, 7)),
+                        severity: Some(DiagnosticSeverity::ERROR),
+                        message: "error".to_string(),
+                        ..Default::default()
+                    }],
+                },
+                None,
+                DiagnosticSourceKind::Pushed,
+                &[],
+                cx,
+            )
+            .unwrap();
+    });
+    assert_eq!(
+        search(
+            &project,
+            query_in_scope(search::SearchScope::WithErrors),
+            cx
+        )
+        .await
+        .unwrap(),
+        HashMap::from_iter([(path!("dir/c.rs").to_string(), vec![9..14])])
+    );
+}
+
 #[gpui::test]
 async fn test_search_with_inclusions(cx: &mut gpui::TestAppContext) {
     init_test(cx);
@@ -6435,6 +7050,113 @@ async fn test_multiple_language_server_hovers(cx: &mut gpui::TestAppContext) {
     );
 }
 
+#[gpui::test]
+async fn test_language_server_hover_priority(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+    cx.update(|cx| {
+        SettingsStore::update_global(cx, |settings, cx| {
+            settings.update_user_settings(cx, |settings| {
+                settings.global_lsp_settings = Some(settings::GlobalLspSettingsContent {
+                    button: None,
+                    feature_servers: Some(settings::LspFeatureServersContent {
+                        hover: Some(vec![
+                            "TailwindServer".to_string(),
+                            "!ESLintServer".to_string(),
+                            "...".to_string(),
+                        ]),
+                        ..Default::default()
+                    }),
+                });
+            });
+        });
+    });
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        path!("/dir"),
+        json!({
+            "a.tsx": "a",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, [path!("/dir").as_ref()], cx).await;
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(tsx_lang());
+    let language_server_names = ["TypeScriptServer", "TailwindServer", "ESLintServer"];
+    let mut language_servers = language_server_names.map(|name| {
+        language_registry.register_fake_lsp(
+            "tsx",
+            FakeLspAdapter {
+                name,
+                capabilities: lsp::ServerCapabilities {
+                    hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                    ..lsp::ServerCapabilities::default()
+                },
+                ..FakeLspAdapter::default()
+            },
+        )
+    });
+
+    let (buffer, _handle) = project
+        .update(cx, |p, cx| {
+            p.open_local_buffer_with_lsp(path!("/dir/a.tsx"), cx)
+        })
+        .await
+        .unwrap();
+    cx.executor().run_until_parked();
+
+    let mut hover_requests = Vec::new();
+    for language_server in &mut language_servers {
+        let new_server = language_server.next().await.unwrap();
+        let new_server_name = new_server.server.name();
+        match new_server_name.as_ref() {
+            "ESLintServer" => {
+                let _never_handled = new_server
+                    .set_request_handler::<lsp::request::HoverRequest, _, _>(|_, _| async move {
+                        panic!("Should not request hovers from an excluded server")
+                    });
+            }
+            _ => {
+                hover_requests.push(
+                    new_server.set_request_handler::<lsp::request::HoverRequest, _, _>(
+                        move |_, _| {
+                            let name = new_server_name.clone();
+                            async move {
+                                Ok(Some(lsp::Hover {
+                                    contents: lsp::HoverContents::Array(vec![
+                                        lsp::MarkedString::String(format!("{name} hover")),
+                                        lsp::MarkedString::String("Shared docs".to_string()),
+                                    ]),
+                                    range: None,
+                                }))
+                            }
+                        },
+                    ),
+                );
+            }
+        }
+    }
+
+    let hover_task = project.update(cx, |project, cx| {
+        project.hover(&buffer, Point::new(0, 0), cx)
+    });
+    for mut hover_request in hover_requests {
+        hover_request.next().await.unwrap();
+    }
+    assert_eq!(
+        vec!["TailwindServer hover|Shared docs", "TypeScriptServer hover"],
+        hover_task
+            .await
+            .into_iter()
+            .flatten()
+            .map(|hover| hover.contents.iter().map(|block| &block.text).join("|"))
+            .collect::<Vec<_>>(),
+        "Hovers should follow the configured server priority, without excluded servers and duplicate blocks"
+    );
+}
+
 #[gpui::test]
 async fn test_hovers_with_empty_parts(cx: &mut gpui::TestAppContext) {
     init_test(cx);
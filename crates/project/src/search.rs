@@ -1,9 +1,11 @@
 use aho_corasick::{AhoCorasick, AhoCorasickBuilder};
 use anyhow::Result;
 use client::proto;
+use collections::HashSet;
 use fancy_regex::{Captures, Regex, RegexBuilder};
-use gpui::Entity;
-use language::{Buffer, BufferSnapshot, CharKind};
+use fuzzy::StringMatchCandidate;
+use gpui::{App, Entity};
+use language::{Buffer, BufferSnapshot, CharKind, StructuralPattern};
 use smol::future::yield_now;
 use std::{
     borrow::Cow,
@@ -15,6 +17,8 @@ use std::{
 use text::Anchor;
 use util::paths::{PathMatcher, PathStyle};
 
+use crate::{ProjectPath, buffer_store::BufferStore, git_store::GitStore, lsp_store::LspStore};
+
 #[derive(Debug)]
 pub enum SearchResult {
     Buffer {
@@ -24,11 +28,95 @@ pub enum SearchResult {
     LimitReached,
 }
 
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum SearchInputKind {
     Query,
     Include,
     Exclude,
+    Replace,
+}
+
+/// The subset of the project's files a search looks into, on top of the include and exclude
+/// path filters.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SearchScope {
+    /// Every file of the project.
+    #[default]
+    All,
+    /// Files added, modified or untracked relative to the HEAD of their repository.
+    GitChanged,
+    /// Files open in a buffer.
+    OpenBuffers,
+    /// Files for which a language server reports errors.
+    WithErrors,
+}
+
+impl SearchScope {
+    pub fn from_proto(scope: proto::search_query::Scope) -> Self {
+        match scope {
+            proto::search_query::Scope::All => Self::All,
+            proto::search_query::Scope::GitChanged => Self::GitChanged,
+            proto::search_query::Scope::OpenBuffers => Self::OpenBuffers,
+            proto::search_query::Scope::WithErrors => Self::WithErrors,
+        }
+    }
+
+    pub fn to_proto(self) -> proto::search_query::Scope {
+        match self {
+            Self::All => proto::search_query::Scope::All,
+            Self::GitChanged => proto::search_query::Scope::GitChanged,
+            Self::OpenBuffers => proto::search_query::Scope::OpenBuffers,
+            Self::WithErrors => proto::search_query::Scope::WithErrors,
+        }
+    }
+
+    /// Returns the paths of the files in this scope, sorted, or `None` for [`SearchScope::All`].
+    pub fn project_paths(
+        self,
+        buffer_store: &Entity<BufferStore>,
+        git_store: &Entity<GitStore>,
+        lsp_store: &Entity<LspStore>,
+        cx: &App,
+    ) -> Option<Vec<ProjectPath>> {
+        let paths: HashSet<ProjectPath> = match self {
+            Self::All => return None,
+            Self::GitChanged => git_store
+                .read(cx)
+                .repositories()
+                .values()
+                .flat_map(|repository| {
+                    let repository = repository.read(cx);
+                    repository
+                        .cached_status()
+                        .filter(|entry| !entry.status.is_deleted())
+                        .filter_map(|entry| {
+                            repository.repo_path_to_project_path(&entry.repo_path, cx)
+                        })
+                        .collect::<Vec<_>>()
+                })
+                .collect(),
+            Self::OpenBuffers => buffer_store
+                .read(cx)
+                .buffers()
+                .filter_map(|buffer| {
+                    let file = buffer.read(cx).file()?;
+                    Some(ProjectPath {
+                        worktree_id: file.worktree_id(cx),
+                        path: file.path().clone(),
+                    })
+                })
+                .collect(),
+            Self::WithErrors => lsp_store
+                .read(cx)
+                .diagnostic_summaries(true, cx)
+                .filter(|(_, _, summary)| summary.error_count > 0)
+                .map(|(path, _, _)| path)
+                .collect(),
+        };
+        let mut paths = paths.into_iter().collect::<Vec<_>>();
+        paths.sort();
+        Some(paths)
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -38,6 +126,7 @@ pub struct SearchInputs {
     files_to_exclude: PathMatcher,
     match_full_paths: bool,
     buffers: Option<Vec<Entity<Buffer>>>,
+    scope: SearchScope,
 }
 
 impl SearchInputs {
@@ -53,6 +142,9 @@ impl SearchInputs {
     pub fn buffers(&self) -> &Option<Vec<Entity<Buffer>>> {
         &self.buffers
     }
+    pub fn scope(&self) -> SearchScope {
+        self.scope
+    }
 }
 #[derive(Clone, Debug)]
 pub enum SearchQuery {
@@ -74,8 +166,24 @@ pub enum SearchQuery {
         one_match_per_line: bool,
         inner: SearchInputs,
     },
+    Structural {
+        pattern: Arc<StructuralPattern>,
+        replacement: Option<String>,
+        include_ignored: bool,
+        inner: SearchInputs,
+    },
+    Fuzzy {
+        replacement: Option<String>,
+        case_sensitive: bool,
+        include_ignored: bool,
+        inner: SearchInputs,
+    },
 }
 
+/// The maximum number of lines matched by a fuzzy query in a single buffer, keeping the lines
+/// with the best scores.
+const MAX_FUZZY_MATCHING_LINES: usize = 100;
+
 static WORD_MATCH_TEST: LazyLock<Regex> = LazyLock::new(|| {
     RegexBuilder::new(r"\B")
         .build()
@@ -123,6 +231,7 @@ impl SearchQuery {
             files_to_include,
             match_full_paths,
             buffers,
+            scope: SearchScope::All,
         };
         Ok(Self::Text {
             search,
@@ -186,6 +295,7 @@ impl SearchQuery {
             files_to_include,
             match_full_paths,
             buffers,
+            scope: SearchScope::All,
         };
         Ok(Self::Regex {
             regex,
@@ -199,6 +309,68 @@ impl SearchQuery {
         })
     }
 
+    /// Create a structural query, matching code templates with `$name` metavariables or
+    /// tree-sitter query patterns against the syntax trees of the searched buffers.
+    ///
+    /// If `match_full_paths` is true, include/exclude patterns will be matched against fully qualified project paths
+    /// beginning with a project root name.
+    pub fn structural(
+        query: impl ToString,
+        include_ignored: bool,
+        files_to_include: PathMatcher,
+        files_to_exclude: PathMatcher,
+        match_full_paths: bool,
+        buffers: Option<Vec<Entity<Buffer>>>,
+    ) -> Result<Self> {
+        let query = query.to_string();
+        let pattern = StructuralPattern::new(&query)?;
+        let inner = SearchInputs {
+            query: query.into(),
+            files_to_exclude,
+            files_to_include,
+            match_full_paths,
+            buffers,
+            scope: SearchScope::All,
+        };
+        Ok(Self::Structural {
+            pattern: Arc::new(pattern),
+            replacement: None,
+            include_ignored,
+            inner,
+        })
+    }
+
+    /// Create a fuzzy query, matching the lines that contain the query's characters in order,
+    /// such as "cfgld" for "config_loader", and keeping the best scoring ones. Its matches span
+    /// the matched characters of each line and are ranked by score rather than by position.
+    ///
+    /// If `match_full_paths` is true, include/exclude patterns will be matched against fully qualified project paths
+    /// beginning with a project root name.
+    pub fn fuzzy(
+        query: impl ToString,
+        case_sensitive: bool,
+        include_ignored: bool,
+        files_to_include: PathMatcher,
+        files_to_exclude: PathMatcher,
+        match_full_paths: bool,
+        buffers: Option<Vec<Entity<Buffer>>>,
+    ) -> Result<Self> {
+        let inner = SearchInputs {
+            query: query.to_string().into(),
+            files_to_exclude,
+            files_to_include,
+            match_full_paths,
+            buffers,
+            scope: SearchScope::All,
+        };
+        Ok(Self::Fuzzy {
+            replacement: None,
+            case_sensitive,
+            include_ignored,
+            inner,
+        })
+    }
+
     /// Extracts case sensitivity settings from pattern items in the provided
     /// query and returns the same query, with the pattern items removed.
     ///
@@ -239,6 +411,7 @@ impl SearchQuery {
     }
 
     pub fn from_proto(message: proto::SearchQuery, path_style: PathStyle) -> Result<Self> {
+        let scope = SearchScope::from_proto(message.scope());
         let files_to_include = if message.files_to_include.is_empty() {
             message
                 .files_to_include_legacy
@@ -263,7 +436,26 @@ impl SearchQuery {
             message.files_to_exclude
         };
 
-        if message.regex {
+        let query = if message.fuzzy {
+            Self::fuzzy(
+                message.query,
+                message.case_sensitive,
+                message.include_ignored,
+                PathMatcher::new(files_to_include, path_style)?,
+                PathMatcher::new(files_to_exclude, path_style)?,
+                message.match_full_paths,
+                None, // search opened only don't need search remote
+            )
+        } else if message.structural {
+            Self::structural(
+                message.query,
+                message.include_ignored,
+                PathMatcher::new(files_to_include, path_style)?,
+                PathMatcher::new(files_to_exclude, path_style)?,
+                message.match_full_paths,
+                None, // search opened only don't need search remote
+            )
+        } else if message.regex {
             Self::regex(
                 message.query,
                 message.whole_word,
@@ -286,7 +478,8 @@ impl SearchQuery {
                 false,
                 None, // search opened only don't need search remote
             )
-        }
+        };
+        Ok(query?.with_scope(scope))
     }
 
     pub fn with_replacement(mut self, new_replacement: String) -> Self {
@@ -298,6 +491,14 @@ impl SearchQuery {
             | Self::Regex {
                 ref mut replacement,
                 ..
+            }
+            | Self::Structural {
+                ref mut replacement,
+                ..
+            }
+            | Self::Fuzzy {
+                ref mut replacement,
+                ..
             } => {
                 *replacement = Some(new_replacement);
                 self
@@ -311,12 +512,15 @@ impl SearchQuery {
         proto::SearchQuery {
             query: self.as_str().to_string(),
             regex: self.is_regex(),
+            structural: self.is_structural(),
+            fuzzy: self.is_fuzzy(),
             whole_word: self.whole_word(),
             case_sensitive: self.case_sensitive(),
             include_ignored: self.include_ignored(),
             files_to_include: files_to_include.clone(),
             files_to_exclude: files_to_exclude.clone(),
             match_full_paths: self.match_full_paths(),
+            scope: self.scope().to_proto().into(),
             // Populate legacy fields for backwards compatibility
             files_to_include_legacy: files_to_include.join(","),
             files_to_exclude_legacy: files_to_exclude.join(","),
@@ -360,20 +564,54 @@ impl SearchQuery {
                     Ok(false)
                 }
             }
+            Self::Structural { pattern, .. } => {
+                let mut text = String::new();
+                reader.read_to_string(&mut text)?;
+                Ok(pattern
+                    .required_words()
+                    .into_iter()
+                    .all(|word| text.contains(word)))
+            }
+            Self::Fuzzy { case_sensitive, .. } => {
+                let query = if *case_sensitive {
+                    self.as_str().to_string()
+                } else {
+                    self.as_str().to_lowercase()
+                };
+                for line in reader.lines() {
+                    let line = line?;
+                    let line = if *case_sensitive {
+                        line
+                    } else {
+                        line.to_lowercase()
+                    };
+                    let mut line_chars = line.chars();
+                    if query
+                        .chars()
+                        .all(|query_char| line_chars.any(|line_char| line_char == query_char))
+                    {
+                        return Ok(true);
+                    }
+                }
+                Ok(false)
+            }
         }
     }
     /// Returns the replacement text for this `SearchQuery`.
     pub fn replacement(&self) -> Option<&str> {
         match self {
-            SearchQuery::Text { replacement, .. } | SearchQuery::Regex { replacement, .. } => {
-                replacement.as_deref()
-            }
+            SearchQuery::Text { replacement, .. }
+            | SearchQuery::Regex { replacement, .. }
+            | SearchQuery::Structural { replacement, .. }
+            | SearchQuery::Fuzzy { replacement, .. } => replacement.as_deref(),
         }
     }
     /// Replaces search hits if replacement is set. `text` is assumed to be a string that matches this `SearchQuery` exactly, without any leftovers on either side.
     pub fn replacement_for<'a>(&self, text: &'a str) -> Option<Cow<'a, str>> {
         match self {
-            SearchQuery::Text { replacement, .. } => replacement.clone().map(Cow::from),
+            SearchQuery::Text { replacement, .. } | SearchQuery::Fuzzy { replacement, .. } => {
+                replacement.clone().map(Cow::from)
+            }
             SearchQuery::Regex {
                 regex, replacement, ..
             } => {
@@ -394,6 +632,17 @@ impl SearchQuery {
                     None
                 }
             }
+            SearchQuery::Structural {
+                pattern,
+                replacement,
+                ..
+            } => {
+                let replacement = replacement.as_ref()?;
+                let mat = pattern.match_text(text)?;
+                Some(Cow::Owned(mat.expand_replacement(replacement, |range| {
+                    text[range].to_string()
+                })))
+            }
         }
     }
 
@@ -492,6 +741,49 @@ impl SearchQuery {
                     }
                 }
             }
+
+            Self::Structural { pattern, .. } => {
+                let range = range_offset..range_offset + rope.len();
+                matches.extend(
+                    pattern
+                        .matches(buffer, range)
+                        .into_iter()
+                        .map(|mat| mat.range.start - range_offset..mat.range.end - range_offset),
+                );
+            }
+
+            Self::Fuzzy { case_sensitive, .. } => {
+                let mut candidates = Vec::new();
+                let mut line_offsets = Vec::new();
+                let mut line_offset = 0;
+                for (ix, line) in rope.to_string().split('\n').enumerate() {
+                    if (ix + 1) % YIELD_INTERVAL == 0 {
+                        yield_now().await;
+                    }
+                    candidates.push(StringMatchCandidate::new(ix, line));
+                    line_offsets.push(line_offset);
+                    line_offset += line.len() + 1;
+                }
+
+                // The lines are ranked by score, and each of them is reported as a single match
+                // spanning its matched characters.
+                let line_matches = fuzzy::match_strings_sync(
+                    &candidates,
+                    self.as_str(),
+                    *case_sensitive,
+                    false,
+                    MAX_FUZZY_MATCHING_LINES,
+                );
+                for mat in &line_matches {
+                    let mut ranges = mat.ranges();
+                    let Some(first_range) = ranges.next() else {
+                        continue;
+                    };
+                    let end = ranges.last().map_or(first_range.end, |range| range.end);
+                    let line_offset = line_offsets[mat.candidate_id];
+                    matches.push(line_offset + first_range.start..line_offset + end);
+                }
+            }
         }
 
         matches
@@ -509,6 +801,7 @@ impl SearchQuery {
         match self {
             Self::Text { whole_word, .. } => *whole_word,
             Self::Regex { whole_word, .. } => *whole_word,
+            Self::Structural { .. } | Self::Fuzzy { .. } => false,
         }
     }
 
@@ -516,6 +809,8 @@ impl SearchQuery {
         match self {
             Self::Text { case_sensitive, .. } => *case_sensitive,
             Self::Regex { case_sensitive, .. } => *case_sensitive,
+            Self::Structural { .. } => true,
+            Self::Fuzzy { case_sensitive, .. } => *case_sensitive,
         }
     }
 
@@ -527,6 +822,12 @@ impl SearchQuery {
             Self::Regex {
                 include_ignored, ..
             } => *include_ignored,
+            Self::Structural {
+                include_ignored, ..
+            } => *include_ignored,
+            Self::Fuzzy {
+                include_ignored, ..
+            } => *include_ignored,
         }
     }
 
@@ -534,6 +835,14 @@ impl SearchQuery {
         matches!(self, Self::Regex { .. })
     }
 
+    pub fn is_structural(&self) -> bool {
+        matches!(self, Self::Structural { .. })
+    }
+
+    pub fn is_fuzzy(&self) -> bool {
+        matches!(self, Self::Fuzzy { .. })
+    }
+
     pub fn files_to_include(&self) -> &PathMatcher {
         self.as_inner().files_to_include()
     }
@@ -550,6 +859,23 @@ impl SearchQuery {
         self.as_inner().buffers.is_some()
     }
 
+    pub fn scope(&self) -> SearchScope {
+        self.as_inner().scope
+    }
+
+    /// Restricts the search to the files in the given scope.
+    pub fn with_scope(mut self, scope: SearchScope) -> Self {
+        match &mut self {
+            Self::Text { inner, .. }
+            | Self::Regex { inner, .. }
+            | Self::Structural { inner, .. }
+            | Self::Fuzzy { inner, .. } => {
+                inner.scope = scope;
+            }
+        }
+        self
+    }
+
     pub fn filters_path(&self) -> bool {
         !(self.files_to_exclude().sources().is_empty()
             && self.files_to_include().sources().is_empty())
@@ -577,20 +903,23 @@ impl SearchQuery {
     }
     pub fn as_inner(&self) -> &SearchInputs {
         match self {
-            Self::Regex { inner, .. } | Self::Text { inner, .. } => inner,
+            Self::Regex { inner, .. }
+            | Self::Text { inner, .. }
+            | Self::Structural { inner, .. }
+            | Self::Fuzzy { inner, .. } => inner,
         }
     }
 
     /// Whether this search should replace only one match per line, instead of
     /// all matches.
-    /// Returns `None` for text searches, as only regex searches support this
-    /// option.
+    /// Returns `None` for text, structural and fuzzy searches, as only regex searches support
+    /// this option.
     pub fn one_match_per_line(&self) -> Option<bool> {
         match self {
             Self::Regex {
                 one_match_per_line, ..
             } => Some(*one_match_per_line),
-            Self::Text { .. } => None,
+            Self::Text { .. } | Self::Structural { .. } | Self::Fuzzy { .. } => None,
         }
     }
 }
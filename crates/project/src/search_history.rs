@@ -64,6 +64,27 @@ impl SearchHistory {
         cursor.selection = Some(self.history.len() - 1);
     }
 
+    /// Puts previously persisted queries, oldest first, in front of the ones added since.
+    /// Queries beyond the maximum history length are dropped, starting with the oldest.
+    pub fn restore(&mut self, queries: impl IntoIterator<Item = String>) {
+        let added = std::mem::take(&mut self.history);
+        self.history.extend(queries);
+        self.history.extend(added);
+        if let Some(max_history_len) = self.max_history_len {
+            let excess = self.history.len().saturating_sub(max_history_len);
+            self.history.drain(..excess);
+        }
+    }
+
+    /// Returns the queries in the history, oldest first.
+    pub fn queries(&self) -> impl Iterator<Item = &str> {
+        self.history.iter().map(String::as_str)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.history.is_empty()
+    }
+
     pub fn next(&mut self, cursor: &mut SearchHistoryCursor) -> Option<&str> {
         let selected = cursor.selection?;
         let next_index = selected + 1;
@@ -191,6 +212,30 @@ mod tests {
         assert_eq!(search_history.current(&cursor), Some("TypeScript"));
     }
 
+    #[test]
+    fn test_restore() {
+        let mut search_history = SearchHistory::new(Some(3), QueryInsertionBehavior::AlwaysInsert);
+        let mut cursor = SearchHistoryCursor::default();
+
+        search_history.add(&mut cursor, "TypeScript".to_string());
+        search_history.restore(["Rust".to_string(), "JavaScript".to_string()]);
+        assert_eq!(
+            search_history.queries().collect::<Vec<_>>(),
+            ["Rust", "JavaScript", "TypeScript"],
+            "Restored queries should come before the ones added since"
+        );
+
+        search_history.restore(["C".to_string()]);
+        assert_eq!(
+            search_history.queries().collect::<Vec<_>>(),
+            ["Rust", "JavaScript", "TypeScript"],
+            "Queries beyond the maximum length should be dropped, oldest first"
+        );
+
+        cursor.reset();
+        assert_eq!(search_history.previous(&mut cursor), Some("TypeScript"));
+    }
+
     #[test]
     fn test_reset_selection() {
         let mut search_history = SearchHistory::new(None, QueryInsertionBehavior::AlwaysInsert);
@@ -269,6 +269,8 @@ actions!(
         OpenSplitVertical,
         /// Opens the selected file in a horizontal split.
         OpenSplitHorizontal,
+        /// Opens the selected file in the hex editor.
+        OpenInHexEditor,
         /// Toggles focus on the project panel.
         ToggleFocus,
         /// Toggles visibility of git-ignored files.
@@ -956,6 +958,9 @@ impl ProjectPanel {
                             .when(is_local, |menu| {
                                 menu.action("Open in Default App", Box::new(OpenWithSystem))
                             })
+                            .when(is_local && !is_dir, |menu| {
+                                menu.action("Open in Hex Editor", Box::new(OpenInHexEditor))
+                            })
                             .action("Open in Terminal", Box::new(OpenInTerminal))
                             .when(is_dir, |menu| {
                                 menu.separator()
@@ -2761,6 +2766,26 @@ impl ProjectPanel {
         }
     }
 
+    fn open_in_hex_editor(
+        &mut self,
+        _: &OpenInHexEditor,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if let Some((worktree, entry)) = self.selected_sub_entry(cx)
+            && entry.is_file()
+        {
+            let abs_path = worktree.read(cx).absolutize(&entry.path);
+            window.dispatch_action(
+                zed_actions::hex_editor::OpenFile {
+                    abs_path: Some(abs_path),
+                }
+                .boxed_clone(),
+                cx,
+            )
+        }
+    }
+
     fn open_in_terminal(
         &mut self,
         _: &OpenInTerminal,
@@ -5324,6 +5349,7 @@ impl Render for ProjectPanel {
                 .when(project.is_local(), |el| {
                     el.on_action(cx.listener(Self::reveal_in_finder))
                         .on_action(cx.listener(Self::open_system))
+                        .on_action(cx.listener(Self::open_in_hex_editor))
                         .on_action(cx.listener(Self::open_in_terminal))
                 })
                 .when(project.is_via_remote_server(), |el| {
@@ -21,7 +21,8 @@ use crate::UserPromptId;
 #[derive(Default, Debug, Clone, Serialize)]
 pub struct ProjectContext {
     pub worktrees: Vec<WorktreeContext>,
-    /// Whether any worktree has a rules_file. Provided as a field because handlebars can't do this.
+    /// Whether any worktree has a rules_file or nested rules files. Provided as a field because
+    /// handlebars can't do this.
     pub has_rules: bool,
     pub user_rules: Vec<UserRulesContext>,
     /// `!user_rules.is_empty()` - provided as a field because handlebars can't do this.
@@ -33,9 +34,7 @@ pub struct ProjectContext {
 
 impl ProjectContext {
     pub fn new(worktrees: Vec<WorktreeContext>, default_user_rules: Vec<UserRulesContext>) -> Self {
-        let has_rules = worktrees
-            .iter()
-            .any(|worktree| worktree.rules_file.is_some());
+        let has_rules = Self::any_rules(&worktrees);
         Self {
             worktrees,
             has_rules,
@@ -46,6 +45,28 @@ impl ProjectContext {
             shell: get_system_shell(),
         }
     }
+
+    /// Keeps only the nested rules files whose directories contain one of the given paths, so
+    /// that the rules of a subdirectory apply once files beneath it are worked on.
+    pub fn retain_nested_rules_for_paths(&mut self, abs_paths: &[PathBuf]) {
+        for worktree in &mut self.worktrees {
+            let abs_path = worktree.abs_path.clone();
+            worktree.nested_rules_files.retain(|rules_file| {
+                let Some(directory) = rules_file.path_in_worktree.parent() else {
+                    return false;
+                };
+                let directory = abs_path.join(directory.as_std_path());
+                abs_paths.iter().any(|path| path.starts_with(&directory))
+            });
+        }
+        self.has_rules = Self::any_rules(&self.worktrees);
+    }
+
+    fn any_rules(worktrees: &[WorktreeContext]) -> bool {
+        worktrees.iter().any(|worktree| {
+            worktree.rules_file.is_some() || !worktree.nested_rules_files.is_empty()
+        })
+    }
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -76,6 +97,9 @@ pub struct WorktreeContext {
     pub root_name: String,
     pub abs_path: Arc<Path>,
     pub rules_file: Option<RulesFileContext>,
+    /// Rules files in subdirectories, ordered so that a directory's rules come before those of
+    /// the directories within it.
+    pub nested_rules_files: Vec<RulesFileContext>,
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, Serialize)]
@@ -460,6 +484,7 @@ mod test {
                 text: "".into(),
                 project_entry_id: 0,
             }),
+            nested_rules_files: Vec::new(),
         }];
         let default_user_rules = vec![UserRulesContext {
             uuid: UserPromptId(Uuid::nil()),
@@ -480,12 +505,47 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_retain_nested_rules_for_paths() {
+        let rules_file = |path: &str| RulesFileContext {
+            path_in_worktree: rel_path(path).into(),
+            text: path.into(),
+            project_entry_id: 0,
+        };
+        let worktrees = vec![WorktreeContext {
+            root_name: "root".into(),
+            abs_path: Path::new("/root").into(),
+            rules_file: None,
+            nested_rules_files: vec![
+                rules_file("a/AGENTS.md"),
+                rules_file("a/b/AGENTS.md"),
+                rules_file("c/.rules"),
+            ],
+        }];
+        let project_context = ProjectContext::new(worktrees, Vec::new());
+        assert!(project_context.has_rules);
+
+        let mut active_context = project_context.clone();
+        active_context.retain_nested_rules_for_paths(&[PathBuf::from("/root/a/b/lib.rs")]);
+        assert_eq!(
+            active_context.worktrees[0].nested_rules_files,
+            vec![rules_file("a/AGENTS.md"), rules_file("a/b/AGENTS.md")]
+        );
+        assert!(active_context.has_rules);
+
+        let mut active_context = project_context;
+        active_context.retain_nested_rules_for_paths(&[PathBuf::from("/root/main.rs")]);
+        assert_eq!(active_context.worktrees[0].nested_rules_files, Vec::new());
+        assert!(!active_context.has_rules);
+    }
+
     #[test]
     fn test_assistant_system_prompt_depends_on_enabled_tools() {
         let worktrees = vec![WorktreeContext {
             root_name: "path".into(),
             abs_path: Path::new("/path/to/root").into(),
             rules_file: None,
+            nested_rules_files: Vec::new(),
         }];
         let default_user_rules = vec![];
         let project_context = ProjectContext::new(worktrees, default_user_rules);
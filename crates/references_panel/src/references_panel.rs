@@ -0,0 +1,1694 @@
+mod references_panel_settings;
+
+use anyhow::Context as _;
+use collections::{HashMap, HashSet};
+use db::kvp::KEY_VALUE_STORE;
+use editor::{Editor, SelectionEffects, scroll::Autoscroll};
+use futures::future::join_all;
+use gpui::{
+    Action, App, AsyncWindowContext, ClickEvent, Context, Entity, EntityId, EventEmitter,
+    FocusHandle, Focusable, KeyContext, ListSizingBehavior, Pixels, Render, ScrollStrategy,
+    SharedString, Subscription, Task, UniformListScrollHandle, WeakEntity, Window, actions,
+    uniform_list,
+};
+use itertools::Itertools as _;
+use language::{
+    Anchor, Buffer, BufferSnapshot, OffsetRangeExt as _, Point, PointUtf16, ToOffset as _,
+    ToPointUtf16 as _,
+};
+use menu::{Confirm, SelectFirst, SelectLast, SelectNext, SelectPrevious};
+use project::{DocumentHighlight, DocumentSymbol, Fs, Project};
+use references_panel_settings::{DockSide, ReferencesPanelSettings};
+use serde::{Deserialize, Serialize};
+use settings::{Settings, SettingsStore};
+use std::{ops::Range, sync::Arc};
+use ui::{ContextMenu, HighlightedLabel, ListItem, PopoverMenu, Tooltip, prelude::*};
+use util::ResultExt as _;
+use workspace::{
+    Workspace,
+    dock::{DockPosition, Panel, PanelEvent},
+};
+
+actions!(
+    references_panel,
+    [
+        /// Finds usages of the symbol under the cursor and shows them in the references panel.
+        FindUsages,
+        /// Shows the previous search from the references panel history.
+        PreviousSearch,
+        /// Shows the next search from the references panel history.
+        NextSearch,
+        /// Clears the references panel history.
+        ClearHistory,
+        /// Excludes the selected references from the results, or brings them back.
+        ToggleExcluded,
+        /// Pins the selected reference to the top of the panel, or unpins it.
+        TogglePinned,
+        /// Toggles focus on the references panel.
+        ToggleFocus,
+    ]
+);
+
+const REFERENCES_PANEL_KEY: &str = "ReferencesPanel";
+const SYMBOL_PATH_SEPARATOR: &str = " › ";
+
+pub fn init_settings(cx: &mut App) {
+    ReferencesPanelSettings::register(cx);
+}
+
+pub fn init(cx: &mut App) {
+    init_settings(cx);
+
+    cx.observe_new(|workspace: &mut Workspace, _, _| {
+        workspace.register_action(|workspace, _: &ToggleFocus, window, cx| {
+            workspace.toggle_panel_focus::<ReferencesPanel>(window, cx);
+        });
+        workspace.register_action(find_usages);
+    })
+    .detach();
+}
+
+fn find_usages(
+    workspace: &mut Workspace,
+    _: &FindUsages,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    let Some(editor) = workspace.active_item_as::<Editor>(cx) else {
+        return;
+    };
+    let Some(panel) = workspace.panel::<ReferencesPanel>(cx) else {
+        return;
+    };
+    let Some((buffer, position)) = editor.update(cx, |editor, cx| {
+        let head = editor.selections.newest_anchor().head();
+        editor.buffer().read(cx).text_anchor_for_position(head, cx)
+    }) else {
+        return;
+    };
+    panel.update(cx, |panel, cx| {
+        panel.find_usages(buffer, position, window, cx);
+    });
+    workspace.focus_panel::<ReferencesPanel>(window, cx);
+}
+
+/// How a reference accesses the symbol, as reported by the language server's document highlights.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ReferenceAccess {
+    Read,
+    Write,
+    Text,
+}
+
+impl ReferenceAccess {
+    fn from_lsp(kind: lsp::DocumentHighlightKind) -> Self {
+        match kind {
+            lsp::DocumentHighlightKind::READ => Self::Read,
+            lsp::DocumentHighlightKind::WRITE => Self::Write,
+            _ => Self::Text,
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            Self::Read => "read",
+            Self::Write => "write",
+            Self::Text => "text",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Reference {
+    buffer: Entity<Buffer>,
+    range: Range<Anchor>,
+    row: u32,
+    line: SharedString,
+    /// Byte range of the reference within the `line`.
+    highlight: Range<usize>,
+    access: Option<ReferenceAccess>,
+    excluded: bool,
+}
+
+impl Reference {
+    fn new(
+        buffer: Entity<Buffer>,
+        range: Range<Anchor>,
+        snapshot: &BufferSnapshot,
+        highlights: &[DocumentHighlight],
+    ) -> Self {
+        let point_range = range.to_point(snapshot);
+        let row = point_range.start.row;
+        let line_len = snapshot.line_len(row);
+        let line = snapshot
+            .text_for_range(Point::new(row, 0)..Point::new(row, line_len))
+            .collect::<String>();
+        let indent = line.len() - line.trim_start().len();
+        let highlight_end = if point_range.end.row == row {
+            point_range.end.column
+        } else {
+            line_len
+        };
+        let highlight = (point_range.start.column as usize).saturating_sub(indent)
+            ..(highlight_end as usize).saturating_sub(indent);
+
+        let start_offset = range.start.to_offset(snapshot);
+        let access = highlights
+            .iter()
+            .find(|highlight| highlight.range.start.to_offset(snapshot) == start_offset)
+            .map(|highlight| ReferenceAccess::from_lsp(highlight.kind));
+
+        Self {
+            buffer,
+            range,
+            row,
+            line: line.trim().to_string().into(),
+            highlight,
+            access,
+            excluded: false,
+        }
+    }
+
+    fn is_same_location(&self, other: &Self) -> bool {
+        self.buffer == other.buffer && self.range == other.range
+    }
+}
+
+#[derive(Debug)]
+struct SymbolReferences {
+    /// The path to the symbol containing the references, e.g. `impl Foo › fn bar`,
+    /// or `None` for the references outside of any symbol.
+    symbol: Option<SharedString>,
+    references: Vec<Reference>,
+}
+
+#[derive(Debug)]
+struct FileReferences {
+    path: SharedString,
+    symbols: Vec<SymbolReferences>,
+    collapsed: bool,
+}
+
+impl FileReferences {
+    fn references(&self) -> impl Iterator<Item = &Reference> {
+        self.symbols
+            .iter()
+            .flat_map(|symbol| symbol.references.iter())
+    }
+}
+
+#[derive(Debug)]
+enum SearchStatus {
+    Loading,
+    Done,
+    Cancelled,
+    Failed(SharedString),
+}
+
+#[derive(Debug)]
+struct ReferencesSearch {
+    id: usize,
+    query: SharedString,
+    files: Vec<FileReferences>,
+    status: SearchStatus,
+}
+
+impl ReferencesSearch {
+    fn references(&self) -> impl Iterator<Item = &Reference> {
+        self.files.iter().flat_map(|file| file.references())
+    }
+
+    fn title(&self) -> SharedString {
+        match self.status {
+            SearchStatus::Loading => format!("Searching for `{}`…", self.query).into(),
+            SearchStatus::Cancelled => format!("Search for `{}` cancelled", self.query).into(),
+            SearchStatus::Failed(_) | SearchStatus::Done => {
+                let count = self
+                    .references()
+                    .filter(|reference| !reference.excluded)
+                    .count();
+                let suffix = if count == 1 { "" } else { "s" };
+                format!("{count} usage{suffix} of `{}`", self.query).into()
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PanelEntry {
+    PinnedHeader,
+    Pinned(usize),
+    File {
+        file_ix: usize,
+    },
+    Symbol {
+        file_ix: usize,
+        symbol_ix: usize,
+    },
+    Reference {
+        file_ix: usize,
+        symbol_ix: usize,
+        reference_ix: usize,
+    },
+}
+
+#[derive(Serialize, Deserialize)]
+struct SerializedReferencesPanel {
+    width: Option<Pixels>,
+    active: Option<bool>,
+}
+
+pub struct ReferencesPanel {
+    fs: Arc<dyn Fs>,
+    project: Entity<Project>,
+    workspace: WeakEntity<Workspace>,
+    focus_handle: FocusHandle,
+    scroll_handle: UniformListScrollHandle,
+    width: Option<Pixels>,
+    active: bool,
+    pending_serialization: Task<Option<()>>,
+    history: Vec<ReferencesSearch>,
+    active_search: Option<usize>,
+    next_search_id: usize,
+    pinned: Vec<Reference>,
+    entries: Vec<PanelEntry>,
+    selected_entry: Option<usize>,
+    search_task: Task<()>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl ReferencesPanel {
+    pub async fn load(
+        workspace: WeakEntity<Workspace>,
+        mut cx: AsyncWindowContext,
+    ) -> anyhow::Result<Entity<Self>> {
+        let serialized_panel = match workspace
+            .read_with(&cx, |workspace, _| {
+                ReferencesPanel::serialization_key(workspace)
+            })
+            .ok()
+            .flatten()
+        {
+            Some(serialization_key) => cx
+                .background_spawn(async move { KEY_VALUE_STORE.read_kvp(&serialization_key) })
+                .await
+                .context("loading references panel")
+                .log_err()
+                .flatten()
+                .map(|panel| serde_json::from_str::<SerializedReferencesPanel>(&panel))
+                .transpose()
+                .log_err()
+                .flatten(),
+            None => None,
+        };
+
+        workspace.update_in(&mut cx, |workspace, window, cx| {
+            let panel = Self::new(workspace, window, cx);
+            if let Some(serialized_panel) = serialized_panel {
+                panel.update(cx, |panel, cx| {
+                    panel.width = serialized_panel.width.map(|px| px.round());
+                    panel.active = serialized_panel.active.unwrap_or(false);
+                    cx.notify();
+                });
+            }
+            panel
+        })
+    }
+
+    fn new(
+        workspace: &mut Workspace,
+        _window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) -> Entity<Self> {
+        let project = workspace.project().clone();
+        let workspace_handle = cx.entity().downgrade();
+        cx.new(|cx| {
+            let mut group_by_symbol = ReferencesPanelSettings::get_global(cx).group_by_symbol;
+            let settings_subscription = cx.observe_global::<SettingsStore>(move |panel, cx| {
+                let settings = ReferencesPanelSettings::get_global(cx);
+                if group_by_symbol != settings.group_by_symbol {
+                    group_by_symbol = settings.group_by_symbol;
+                    panel.update_entries(cx);
+                }
+                panel.trim_history(cx);
+                cx.notify();
+            });
+
+            Self {
+                fs: workspace.app_state().fs.clone(),
+                project,
+                workspace: workspace_handle,
+                focus_handle: cx.focus_handle(),
+                scroll_handle: UniformListScrollHandle::new(),
+                width: None,
+                active: false,
+                pending_serialization: Task::ready(None),
+                history: Vec::new(),
+                active_search: None,
+                next_search_id: 0,
+                pinned: Vec::new(),
+                entries: Vec::new(),
+                selected_entry: None,
+                search_task: Task::ready(()),
+                _subscriptions: vec![settings_subscription],
+            }
+        })
+    }
+
+    fn serialization_key(workspace: &Workspace) -> Option<String> {
+        workspace
+            .database_id()
+            .map(|id| i64::from(id).to_string())
+            .or(workspace.session_id())
+            .map(|id| format!("{}-{:?}", REFERENCES_PANEL_KEY, id))
+    }
+
+    fn serialize(&mut self, cx: &mut Context<Self>) {
+        let Some(serialization_key) = self
+            .workspace
+            .read_with(cx, |workspace, _| {
+                ReferencesPanel::serialization_key(workspace)
+            })
+            .ok()
+            .flatten()
+        else {
+            return;
+        };
+        let width = self.width;
+        let active = Some(self.active);
+        self.pending_serialization = cx.background_spawn(
+            async move {
+                KEY_VALUE_STORE
+                    .write_kvp(
+                        serialization_key,
+                        serde_json::to_string(&SerializedReferencesPanel { width, active })?,
+                    )
+                    .await?;
+                anyhow::Ok(())
+            }
+            .log_err(),
+        );
+    }
+
+    /// Queries the references of the symbol at the given position, adding a new search to the history.
+    pub fn find_usages(
+        &mut self,
+        buffer: Entity<Buffer>,
+        position: Anchor,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let snapshot = buffer.read(cx).snapshot();
+        let (word_range, _) = snapshot.surrounding_word(position.to_offset(&snapshot), None);
+        let query = snapshot.text_for_range(word_range).collect::<String>();
+        let query = if query.trim().is_empty() {
+            "symbol".to_string()
+        } else {
+            query
+        };
+
+        // The new search replaces the task of any search still in progress, which stops it.
+        for search in &mut self.history {
+            if let SearchStatus::Loading = search.status {
+                search.status = SearchStatus::Cancelled;
+            }
+        }
+
+        let search_id = self.next_search_id;
+        self.next_search_id += 1;
+        self.history.push(ReferencesSearch {
+            id: search_id,
+            query: query.into(),
+            files: Vec::new(),
+            status: SearchStatus::Loading,
+        });
+        self.trim_history(cx);
+        self.active_search = Some(self.history.len() - 1);
+        self.selected_entry = None;
+        self.update_entries(cx);
+
+        let references = self
+            .project
+            .update(cx, |project, cx| project.references(&buffer, position, cx));
+        self.search_task = cx.spawn_in(window, async move |panel, cx| {
+            let locations = match references.await {
+                Ok(locations) => locations.unwrap_or_default(),
+                Err(e) => {
+                    panel
+                        .update(cx, |panel, cx| {
+                            if let Some(search) = panel.search_mut(search_id) {
+                                search.status = SearchStatus::Failed(format!("{e:#}").into());
+                            }
+                            panel.update_entries(cx);
+                        })
+                        .ok();
+                    return;
+                }
+            };
+
+            let Ok(file_tasks) = panel.update(cx, |panel, cx| {
+                let mut ranges_by_buffer = HashMap::<EntityId, (Entity<Buffer>, Vec<_>)>::default();
+                for location in locations {
+                    ranges_by_buffer
+                        .entry(location.buffer.entity_id())
+                        .or_insert_with(|| (location.buffer.clone(), Vec::new()))
+                        .1
+                        .push(location.range);
+                }
+
+                ranges_by_buffer
+                    .into_values()
+                    .map(|(buffer, ranges)| {
+                        let (symbols, highlights) = panel.project.update(cx, |project, cx| {
+                            let symbols = project.document_symbols(&buffer, cx);
+                            let highlights = ranges
+                                .first()
+                                .map(|range| project.document_highlights(&buffer, range.start, cx));
+                            (symbols, highlights)
+                        });
+                        async move {
+                            let symbols = symbols.await.log_err().unwrap_or_default();
+                            let highlights = match highlights {
+                                Some(highlights) => highlights.await.log_err().unwrap_or_default(),
+                                None => Vec::new(),
+                            };
+                            (buffer, ranges, symbols, highlights)
+                        }
+                    })
+                    .collect::<Vec<_>>()
+            }) else {
+                return;
+            };
+            let files = join_all(file_tasks).await;
+
+            panel
+                .update(cx, |panel, cx| {
+                    let files = files
+                        .into_iter()
+                        .map(|(buffer, ranges, symbols, highlights)| {
+                            group_file_references(buffer, ranges, &symbols, &highlights, cx)
+                        })
+                        .sorted_by(|a, b| a.path.cmp(&b.path))
+                        .collect();
+                    if let Some(search) = panel.search_mut(search_id) {
+                        search.files = files;
+                        search.status = SearchStatus::Done;
+                    }
+                    panel.update_entries(cx);
+                })
+                .ok();
+        });
+    }
+
+    fn search_mut(&mut self, search_id: usize) -> Option<&mut ReferencesSearch> {
+        self.history
+            .iter_mut()
+            .find(|search| search.id == search_id)
+    }
+
+    fn active_search(&self) -> Option<&ReferencesSearch> {
+        self.history.get(self.active_search?)
+    }
+
+    fn trim_history(&mut self, cx: &App) {
+        let max_history = ReferencesPanelSettings::get_global(cx).max_history.max(1);
+        if self.history.len() > max_history {
+            let excess = self.history.len() - max_history;
+            self.history.drain(..excess);
+            self.active_search = self
+                .active_search
+                .map(|ix| ix.saturating_sub(excess))
+                .filter(|_| !self.history.is_empty());
+        }
+    }
+
+    fn activate_search(&mut self, ix: usize, cx: &mut Context<Self>) {
+        if ix < self.history.len() && self.active_search != Some(ix) {
+            self.active_search = Some(ix);
+            self.selected_entry = None;
+            self.update_entries(cx);
+        }
+    }
+
+    fn previous_search(&mut self, _: &PreviousSearch, _: &mut Window, cx: &mut Context<Self>) {
+        if let Some(ix) = self.active_search.and_then(|ix| ix.checked_sub(1)) {
+            self.activate_search(ix, cx);
+        }
+    }
+
+    fn next_search(&mut self, _: &NextSearch, _: &mut Window, cx: &mut Context<Self>) {
+        if let Some(ix) = self.active_search {
+            self.activate_search(ix + 1, cx);
+        }
+    }
+
+    fn clear_history(&mut self, _: &ClearHistory, _: &mut Window, cx: &mut Context<Self>) {
+        self.history.clear();
+        self.active_search = None;
+        self.selected_entry = None;
+        self.search_task = Task::ready(());
+        self.update_entries(cx);
+    }
+
+    fn update_entries(&mut self, cx: &mut Context<Self>) {
+        let group_by_symbol = ReferencesPanelSettings::get_global(cx).group_by_symbol;
+        let selected_entry = self
+            .selected_entry
+            .and_then(|ix| self.entries.get(ix))
+            .copied();
+
+        let mut entries = Vec::new();
+        if !self.pinned.is_empty() {
+            entries.push(PanelEntry::PinnedHeader);
+            entries.extend((0..self.pinned.len()).map(PanelEntry::Pinned));
+        }
+        if let Some(search) = self.active_search() {
+            for (file_ix, file) in search.files.iter().enumerate() {
+                entries.push(PanelEntry::File { file_ix });
+                if file.collapsed {
+                    continue;
+                }
+                for (symbol_ix, symbol) in file.symbols.iter().enumerate() {
+                    if group_by_symbol && symbol.symbol.is_some() {
+                        entries.push(PanelEntry::Symbol { file_ix, symbol_ix });
+                    }
+                    entries.extend((0..symbol.references.len()).map(|reference_ix| {
+                        PanelEntry::Reference {
+                            file_ix,
+                            symbol_ix,
+                            reference_ix,
+                        }
+                    }));
+                }
+            }
+        }
+
+        self.selected_entry = selected_entry
+            .and_then(|selected_entry| entries.iter().position(|entry| *entry == selected_entry));
+        self.entries = entries;
+        cx.notify();
+    }
+
+    fn reference_for_entry(&self, entry: PanelEntry) -> Option<&Reference> {
+        match entry {
+            PanelEntry::Pinned(ix) => self.pinned.get(ix),
+            PanelEntry::Reference {
+                file_ix,
+                symbol_ix,
+                reference_ix,
+            } => self
+                .active_search()?
+                .files
+                .get(file_ix)?
+                .symbols
+                .get(symbol_ix)?
+                .references
+                .get(reference_ix),
+            PanelEntry::PinnedHeader | PanelEntry::File { .. } | PanelEntry::Symbol { .. } => None,
+        }
+    }
+
+    fn select_entry(
+        &mut self,
+        ix: usize,
+        preview: bool,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if ix >= self.entries.len() {
+            return;
+        }
+        self.selected_entry = Some(ix);
+        self.scroll_handle
+            .scroll_to_item(ix, ScrollStrategy::Center);
+        if preview {
+            self.open_entry(ix, false, window, cx);
+        }
+        cx.notify();
+    }
+
+    fn select_next(&mut self, _: &SelectNext, window: &mut Window, cx: &mut Context<Self>) {
+        let ix = self.selected_entry.map_or(0, |ix| ix + 1);
+        self.select_entry(ix, true, window, cx);
+    }
+
+    fn select_previous(&mut self, _: &SelectPrevious, window: &mut Window, cx: &mut Context<Self>) {
+        let ix = self.selected_entry.map_or(0, |ix| ix.saturating_sub(1));
+        self.select_entry(ix, true, window, cx);
+    }
+
+    fn select_first(&mut self, _: &SelectFirst, window: &mut Window, cx: &mut Context<Self>) {
+        self.select_entry(0, true, window, cx);
+    }
+
+    fn select_last(&mut self, _: &SelectLast, window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(ix) = self.entries.len().checked_sub(1) {
+            self.select_entry(ix, true, window, cx);
+        }
+    }
+
+    fn confirm(&mut self, _: &Confirm, window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(ix) = self.selected_entry {
+            self.open_entry(ix, true, window, cx);
+        }
+    }
+
+    /// Opens the reference of the entry in an editor: previews it without moving the focus out of the panel,
+    /// or focuses the editor.
+    fn open_entry(&mut self, ix: usize, focus: bool, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(entry) = self.entries.get(ix).copied() else {
+            return;
+        };
+        if let PanelEntry::File { file_ix } = entry {
+            if focus {
+                self.toggle_collapsed(file_ix, cx);
+            }
+            return;
+        }
+        let Some(reference) = self.reference_for_entry(entry).cloned() else {
+            return;
+        };
+        let Some(workspace) = self.workspace.upgrade() else {
+            return;
+        };
+        workspace.update(cx, |workspace, cx| {
+            let pane = workspace.active_pane().clone();
+            let editor = workspace.open_project_item::<Editor>(
+                pane,
+                reference.buffer.clone(),
+                true,
+                focus,
+                window,
+                cx,
+            );
+            let range = reference
+                .range
+                .to_point(&reference.buffer.read(cx).snapshot());
+            editor.update(cx, |editor, cx| {
+                editor.change_selections(
+                    SelectionEffects::scroll(Autoscroll::center()),
+                    window,
+                    cx,
+                    |s| s.select_ranges([range]),
+                );
+            });
+        });
+    }
+
+    fn toggle_collapsed(&mut self, file_ix: usize, cx: &mut Context<Self>) {
+        let Some(search) = self.active_search.and_then(|ix| self.history.get_mut(ix)) else {
+            return;
+        };
+        if let Some(file) = search.files.get_mut(file_ix) {
+            file.collapsed = !file.collapsed;
+            self.update_entries(cx);
+        }
+    }
+
+    fn toggle_excluded(&mut self, _: &ToggleExcluded, _: &mut Window, cx: &mut Context<Self>) {
+        if let Some(entry) = self.selected_entry.and_then(|ix| self.entries.get(ix)) {
+            self.toggle_entry_excluded(*entry, cx);
+        }
+    }
+
+    /// Excludes a reference, or all references of a file or a symbol, from the results.
+    /// If all of them are excluded already, brings them back instead.
+    fn toggle_entry_excluded(&mut self, entry: PanelEntry, cx: &mut Context<Self>) {
+        let Some(search) = self.active_search.and_then(|ix| self.history.get_mut(ix)) else {
+            return;
+        };
+        let references = match entry {
+            PanelEntry::File { file_ix } => search
+                .files
+                .get_mut(file_ix)
+                .map(|file| {
+                    file.symbols
+                        .iter_mut()
+                        .flat_map(|symbol| symbol.references.iter_mut())
+                        .collect::<Vec<_>>()
+                })
+                .unwrap_or_default(),
+            PanelEntry::Symbol { file_ix, symbol_ix } => search
+                .files
+                .get_mut(file_ix)
+                .and_then(|file| file.symbols.get_mut(symbol_ix))
+                .map(|symbol| symbol.references.iter_mut().collect())
+                .unwrap_or_default(),
+            PanelEntry::Reference {
+                file_ix,
+                symbol_ix,
+                reference_ix,
+            } => search
+                .files
+                .get_mut(file_ix)
+                .and_then(|file| file.symbols.get_mut(symbol_ix))
+                .and_then(|symbol| symbol.references.get_mut(reference_ix))
+                .into_iter()
+                .collect(),
+            PanelEntry::PinnedHeader | PanelEntry::Pinned(_) => Vec::new(),
+        };
+        let exclude = references.iter().any(|reference| !reference.excluded);
+        for reference in references {
+            reference.excluded = exclude;
+        }
+        cx.notify();
+    }
+
+    fn toggle_pinned(&mut self, _: &TogglePinned, _: &mut Window, cx: &mut Context<Self>) {
+        if let Some(entry) = self.selected_entry.and_then(|ix| self.entries.get(ix)) {
+            self.toggle_entry_pinned(*entry, cx);
+        }
+    }
+
+    /// Pins a reference to the top of the panel, where it stays across the searches, or unpins it.
+    fn toggle_entry_pinned(&mut self, entry: PanelEntry, cx: &mut Context<Self>) {
+        let Some(reference) = self.reference_for_entry(entry).cloned() else {
+            return;
+        };
+        if let Some(ix) = self
+            .pinned
+            .iter()
+            .position(|pinned| pinned.is_same_location(&reference))
+        {
+            self.pinned.remove(ix);
+        } else {
+            self.pinned.push(Reference {
+                excluded: false,
+                ..reference
+            });
+        }
+        self.update_entries(cx);
+    }
+
+    fn is_pinned(&self, reference: &Reference) -> bool {
+        self.pinned
+            .iter()
+            .any(|pinned| pinned.is_same_location(reference))
+    }
+
+    fn dispatch_context(&self) -> KeyContext {
+        let mut dispatch_context = KeyContext::new_with_defaults();
+        dispatch_context.add("ReferencesPanel");
+        dispatch_context.add("menu");
+        dispatch_context
+    }
+
+    fn render_header(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let title = self
+            .active_search()
+            .map(|search| search.title())
+            .unwrap_or_else(|| "No usages searched".into());
+        let can_go_back = self.active_search.is_some_and(|ix| ix > 0);
+        let can_go_forward = self
+            .active_search
+            .is_some_and(|ix| ix + 1 < self.history.len());
+        let panel = cx.entity().downgrade();
+        let history = self
+            .history
+            .iter()
+            .enumerate()
+            .rev()
+            .map(|(ix, search)| (ix, search.title(), Some(ix) == self.active_search))
+            .collect::<Vec<_>>();
+
+        h_flex()
+            .h(DynamicSpacing::Base32.px(cx))
+            .px_2()
+            .gap_1()
+            .flex_shrink_0()
+            .border_b_1()
+            .border_color(cx.theme().colors().border)
+            .child(
+                IconButton::new("references-previous-search", IconName::ArrowLeft)
+                    .icon_size(IconSize::Small)
+                    .disabled(!can_go_back)
+                    .tooltip(Tooltip::for_action_title(
+                        "Previous Search",
+                        &PreviousSearch,
+                    ))
+                    .on_click(|_, window, cx| {
+                        window.dispatch_action(PreviousSearch.boxed_clone(), cx)
+                    }),
+            )
+            .child(
+                IconButton::new("references-next-search", IconName::ArrowRight)
+                    .icon_size(IconSize::Small)
+                    .disabled(!can_go_forward)
+                    .tooltip(Tooltip::for_action_title("Next Search", &NextSearch))
+                    .on_click(|_, window, cx| window.dispatch_action(NextSearch.boxed_clone(), cx)),
+            )
+            .child(
+                div()
+                    .flex_1()
+                    .min_w_0()
+                    .child(Label::new(title).size(LabelSize::Small).truncate()),
+            )
+            .child(
+                PopoverMenu::new("references-history")
+                    .trigger_with_tooltip(
+                        IconButton::new("references-history-trigger", IconName::HistoryRerun)
+                            .icon_size(IconSize::Small)
+                            .disabled(history.is_empty()),
+                        Tooltip::text("Search History"),
+                    )
+                    .menu(move |window, cx| {
+                        let panel = panel.clone();
+                        let history = history.clone();
+                        Some(ContextMenu::build(window, cx, move |mut menu, _, _| {
+                            for (ix, title, active) in history {
+                                let panel = panel.clone();
+                                menu = menu.toggleable_entry(
+                                    title,
+                                    active,
+                                    IconPosition::Start,
+                                    None,
+                                    move |_, cx| {
+                                        panel
+                                            .update(cx, |panel, cx| panel.activate_search(ix, cx))
+                                            .ok();
+                                    },
+                                );
+                            }
+                            menu.separator()
+                                .action("Clear History", ClearHistory.boxed_clone())
+                        }))
+                    }),
+            )
+    }
+
+    fn render_entry(
+        &self,
+        ix: usize,
+        entry: PanelEntry,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Option<AnyElement> {
+        let group_by_symbol = ReferencesPanelSettings::get_global(cx).group_by_symbol;
+        let search = self.active_search();
+        let (indent_level, content, end_slot): (usize, AnyElement, Option<AnyElement>) = match entry
+        {
+            PanelEntry::PinnedHeader => (
+                0,
+                h_flex()
+                    .gap_1()
+                    .child(
+                        Icon::new(IconName::Pin)
+                            .size(IconSize::Small)
+                            .color(Color::Muted),
+                    )
+                    .child(
+                        Label::new("Pinned")
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    )
+                    .into_any_element(),
+                None,
+            ),
+            PanelEntry::File { file_ix } => {
+                let file = search?.files.get(file_ix)?;
+                let count = file
+                    .references()
+                    .filter(|reference| !reference.excluded)
+                    .count();
+                let all_excluded = count == 0;
+                (
+                    0,
+                    h_flex()
+                        .gap_1()
+                        .child(
+                            Icon::new(if file.collapsed {
+                                IconName::ChevronRight
+                            } else {
+                                IconName::ChevronDown
+                            })
+                            .size(IconSize::Small)
+                            .color(Color::Muted),
+                        )
+                        .child(
+                            Label::new(file.path.clone())
+                                .when(all_excluded, |label| label.strikethrough())
+                                .truncate(),
+                        )
+                        .child(
+                            Label::new(count.to_string())
+                                .size(LabelSize::Small)
+                                .color(Color::Muted),
+                        )
+                        .into_any_element(),
+                    Some(self.render_exclude_button(ix, entry, all_excluded, cx)),
+                )
+            }
+            PanelEntry::Symbol { file_ix, symbol_ix } => {
+                let symbol = search?.files.get(file_ix)?.symbols.get(symbol_ix)?;
+                let all_excluded = symbol.references.iter().all(|reference| reference.excluded);
+                (
+                    1,
+                    Label::new(symbol.symbol.clone()?)
+                        .size(LabelSize::Small)
+                        .color(Color::Muted)
+                        .buffer_font(cx)
+                        .when(all_excluded, |label| label.strikethrough())
+                        .truncate()
+                        .into_any_element(),
+                    Some(self.render_exclude_button(ix, entry, all_excluded, cx)),
+                )
+            }
+            PanelEntry::Reference {
+                file_ix, symbol_ix, ..
+            } => {
+                let reference = self.reference_for_entry(entry)?;
+                let nested_in_symbol = group_by_symbol
+                    && search?
+                        .files
+                        .get(file_ix)?
+                        .symbols
+                        .get(symbol_ix)?
+                        .symbol
+                        .is_some();
+                (
+                    if nested_in_symbol { 2 } else { 1 },
+                    self.render_reference(reference, None, cx),
+                    Some(
+                        h_flex()
+                            .gap_0p5()
+                            .child(self.render_pin_button(ix, entry, reference, cx))
+                            .child(self.render_exclude_button(ix, entry, reference.excluded, cx))
+                            .into_any_element(),
+                    ),
+                )
+            }
+            PanelEntry::Pinned(_) => {
+                let reference = self.reference_for_entry(entry)?;
+                let path = reference
+                    .buffer
+                    .read(cx)
+                    .file()
+                    .map(|file| SharedString::from(file.file_name(cx).to_string()));
+                (
+                    1,
+                    self.render_reference(reference, path, cx),
+                    Some(self.render_pin_button(ix, entry, reference, cx)),
+                )
+            }
+        };
+
+        let is_selected = self.selected_entry == Some(ix);
+        Some(
+            ListItem::new(("references-panel-entry", ix))
+                .indent_level(indent_level)
+                .indent_step_size(px(12.))
+                .toggle_state(is_selected)
+                .child(content)
+                .end_hover_slot(end_slot)
+                .on_click(cx.listener(move |panel, event: &ClickEvent, window, cx| {
+                    if event.click_count() > 1 {
+                        panel.selected_entry = Some(ix);
+                        panel.open_entry(ix, true, window, cx);
+                    } else if let PanelEntry::File { file_ix } = entry {
+                        panel.selected_entry = Some(ix);
+                        panel.toggle_collapsed(file_ix, cx);
+                    } else {
+                        panel.select_entry(ix, true, window, cx);
+                    }
+                }))
+                .when(
+                    is_selected && self.focus_handle.contains_focused(window, cx),
+                    |item| item.focused(true),
+                )
+                .into_any_element(),
+        )
+    }
+
+    fn render_reference(
+        &self,
+        reference: &Reference,
+        path: Option<SharedString>,
+        cx: &App,
+    ) -> AnyElement {
+        let highlight_indices = reference
+            .highlight
+            .clone()
+            .filter(|ix| reference.line.is_char_boundary(*ix))
+            .collect::<Vec<_>>();
+        let location = match path {
+            Some(path) => format!("{path}:{}", reference.row + 1),
+            None => (reference.row + 1).to_string(),
+        };
+        h_flex()
+            .gap_1p5()
+            .min_w_0()
+            .child(
+                Label::new(location)
+                    .size(LabelSize::Small)
+                    .color(Color::Muted),
+            )
+            .when_some(reference.access, |this, access| {
+                this.child(
+                    Label::new(access.label())
+                        .size(LabelSize::XSmall)
+                        .color(match access {
+                            ReferenceAccess::Write => Color::Warning,
+                            ReferenceAccess::Read => Color::Accent,
+                            ReferenceAccess::Text => Color::Muted,
+                        }),
+                )
+            })
+            .child(
+                HighlightedLabel::new(reference.line.clone(), highlight_indices)
+                    .buffer_font(cx)
+                    .when(reference.excluded, |label| {
+                        label.strikethrough().color(Color::Disabled)
+                    })
+                    .truncate(),
+            )
+            .into_any_element()
+    }
+
+    fn render_exclude_button(
+        &self,
+        ix: usize,
+        entry: PanelEntry,
+        excluded: bool,
+        cx: &mut Context<Self>,
+    ) -> AnyElement {
+        IconButton::new(("references-exclude", ix), IconName::Eye)
+            .icon_size(IconSize::Small)
+            .toggle_state(excluded)
+            .tooltip(Tooltip::for_action_title(
+                if excluded { "Include" } else { "Exclude" },
+                &ToggleExcluded,
+            ))
+            .on_click(cx.listener(move |panel, _, _, cx| {
+                panel.toggle_entry_excluded(entry, cx);
+            }))
+            .into_any_element()
+    }
+
+    fn render_pin_button(
+        &self,
+        ix: usize,
+        entry: PanelEntry,
+        reference: &Reference,
+        cx: &mut Context<Self>,
+    ) -> AnyElement {
+        let pinned = self.is_pinned(reference);
+        IconButton::new(
+            ("references-pin", ix),
+            if pinned {
+                IconName::Unpin
+            } else {
+                IconName::Pin
+            },
+        )
+        .icon_size(IconSize::Small)
+        .tooltip(Tooltip::for_action_title(
+            if pinned { "Unpin" } else { "Pin" },
+            &TogglePinned,
+        ))
+        .on_click(cx.listener(move |panel, _, _, cx| {
+            panel.toggle_entry_pinned(entry, cx);
+        }))
+        .into_any_element()
+    }
+
+    fn render_empty_state(&self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let message: SharedString = match self.active_search().map(|search| &search.status) {
+            Some(SearchStatus::Loading) => "Searching…".into(),
+            Some(SearchStatus::Failed(error)) => format!("Failed to find usages: {error}").into(),
+            Some(SearchStatus::Done) => "No usages found".into(),
+            Some(SearchStatus::Cancelled) => "Search cancelled".into(),
+            None => "Find usages of a symbol to see them here".into(),
+        };
+        v_flex()
+            .flex_1()
+            .size_full()
+            .justify_center()
+            .items_center()
+            .gap_1()
+            .child(Label::new(message).color(Color::Muted))
+            .when(self.active_search.is_none(), |this| {
+                this.child(
+                    Button::new("references-find-usages", "Find Usages")
+                        .key_binding(ui::KeyBinding::for_action_in(
+                            &FindUsages,
+                            &self.focus_handle,
+                            window,
+                            cx,
+                        ))
+                        .on_click(|_, window, cx| {
+                            window.dispatch_action(FindUsages.boxed_clone(), cx)
+                        }),
+                )
+            })
+    }
+}
+
+/// Groups the references of a single buffer by the innermost document symbol containing them,
+/// falling back to the syntax tree outline when the language server does not provide document symbols.
+fn group_file_references(
+    buffer: Entity<Buffer>,
+    mut ranges: Vec<Range<Anchor>>,
+    document_symbols: &[DocumentSymbol],
+    highlights: &[DocumentHighlight],
+    cx: &App,
+) -> FileReferences {
+    let snapshot = buffer.read(cx).snapshot();
+    let path = buffer
+        .read(cx)
+        .file()
+        .map(|file| file.full_path(cx).to_string_lossy().to_string())
+        .unwrap_or_else(|| "untitled".to_string());
+
+    ranges.sort_by(|a, b| a.start.cmp(&b.start, &snapshot));
+    ranges.dedup_by(|a, b| a.start.cmp(&b.start, &snapshot).is_eq());
+
+    let mut symbols = Vec::<SymbolReferences>::new();
+    let mut seen_symbols = HashSet::default();
+    for range in ranges {
+        let position = range.start.to_point_utf16(&snapshot);
+        let symbol = if document_symbols.is_empty() {
+            outline_symbol_path(&snapshot, &range)
+        } else {
+            document_symbol_path(document_symbols, position)
+        }
+        .map(SharedString::from);
+        let reference = Reference::new(buffer.clone(), range, &snapshot, highlights);
+
+        match symbols.last_mut() {
+            Some(last) if last.symbol == symbol => last.references.push(reference),
+            _ => {
+                if let Some(existing) = seen_symbols
+                    .contains(&symbol)
+                    .then(|| symbols.iter_mut().find(|group| group.symbol == symbol))
+                    .flatten()
+                {
+                    existing.references.push(reference);
+                } else {
+                    seen_symbols.insert(symbol.clone());
+                    symbols.push(SymbolReferences {
+                        symbol,
+                        references: vec![reference],
+                    });
+                }
+            }
+        }
+    }
+
+    FileReferences {
+        path: path.into(),
+        symbols,
+        collapsed: false,
+    }
+}
+
+fn document_symbol_path(symbols: &[DocumentSymbol], position: PointUtf16) -> Option<String> {
+    let mut path = Vec::new();
+    let mut level = symbols;
+    while let Some(symbol) = level
+        .iter()
+        .find(|symbol| symbol.range.start.0 <= position && position <= symbol.range.end.0)
+    {
+        path.push(symbol.name.as_str());
+        level = &symbol.children;
+    }
+    (!path.is_empty()).then(|| path.join(SYMBOL_PATH_SEPARATOR))
+}
+
+fn outline_symbol_path(snapshot: &BufferSnapshot, range: &Range<Anchor>) -> Option<String> {
+    let symbols = snapshot.symbols_containing(range.start, None);
+    (!symbols.is_empty()).then(|| {
+        symbols
+            .iter()
+            .map(|symbol| symbol.text.as_str())
+            .join(SYMBOL_PATH_SEPARATOR)
+    })
+}
+
+impl Panel for ReferencesPanel {
+    fn persistent_name() -> &'static str {
+        "References Panel"
+    }
+
+    fn position(&self, _: &Window, cx: &App) -> DockPosition {
+        match ReferencesPanelSettings::get_global(cx).dock {
+            DockSide::Left => DockPosition::Left,
+            DockSide::Right => DockPosition::Right,
+        }
+    }
+
+    fn position_is_valid(&self, position: DockPosition) -> bool {
+        matches!(position, DockPosition::Left | DockPosition::Right)
+    }
+
+    fn set_position(&mut self, position: DockPosition, _: &mut Window, cx: &mut Context<Self>) {
+        settings::update_settings_file(self.fs.clone(), cx, move |settings, _| {
+            let dock = match position {
+                DockPosition::Left | DockPosition::Bottom => DockSide::Left,
+                DockPosition::Right => DockSide::Right,
+            };
+            settings.references_panel.get_or_insert_default().dock = Some(dock);
+        });
+    }
+
+    fn size(&self, _: &Window, cx: &App) -> Pixels {
+        self.width
+            .unwrap_or_else(|| ReferencesPanelSettings::get_global(cx).default_width)
+    }
+
+    fn set_size(&mut self, size: Option<Pixels>, window: &mut Window, cx: &mut Context<Self>) {
+        self.width = size;
+        cx.notify();
+        cx.defer_in(window, |this, _, cx| {
+            this.serialize(cx);
+        });
+    }
+
+    fn icon(&self, _: &Window, cx: &App) -> Option<IconName> {
+        ReferencesPanelSettings::get_global(cx)
+            .button
+            .then_some(IconName::Crosshair)
+    }
+
+    fn icon_tooltip(&self, _window: &Window, _: &App) -> Option<&'static str> {
+        Some("References Panel")
+    }
+
+    fn toggle_action(&self) -> Box<dyn Action> {
+        Box::new(ToggleFocus)
+    }
+
+    fn starts_open(&self, _window: &Window, _: &App) -> bool {
+        self.active
+    }
+
+    fn set_active(&mut self, active: bool, _: &mut Window, cx: &mut Context<Self>) {
+        if self.active != active {
+            self.active = active;
+            self.serialize(cx);
+        }
+    }
+
+    fn activation_priority(&self) -> u32 {
+        11
+    }
+}
+
+impl Focusable for ReferencesPanel {
+    fn focus_handle(&self, _: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl EventEmitter<PanelEvent> for ReferencesPanel {}
+
+impl Render for ReferencesPanel {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .id("references-panel")
+            .size_full()
+            .overflow_hidden()
+            .key_context(self.dispatch_context())
+            .track_focus(&self.focus_handle)
+            .on_action(cx.listener(Self::select_next))
+            .on_action(cx.listener(Self::select_previous))
+            .on_action(cx.listener(Self::select_first))
+            .on_action(cx.listener(Self::select_last))
+            .on_action(cx.listener(Self::confirm))
+            .on_action(cx.listener(Self::previous_search))
+            .on_action(cx.listener(Self::next_search))
+            .on_action(cx.listener(Self::clear_history))
+            .on_action(cx.listener(Self::toggle_excluded))
+            .on_action(cx.listener(Self::toggle_pinned))
+            .child(self.render_header(cx))
+            .map(|this| {
+                if self.entries.is_empty() {
+                    this.child(self.render_empty_state(window, cx))
+                } else {
+                    this.child(
+                        uniform_list(
+                            "references-panel-entries",
+                            self.entries.len(),
+                            cx.processor(|panel, range: Range<usize>, window, cx| {
+                                range
+                                    .filter_map(|ix| {
+                                        let entry = *panel.entries.get(ix)?;
+                                        panel.render_entry(ix, entry, window, cx)
+                                    })
+                                    .collect()
+                            }),
+                        )
+                        .flex_1()
+                        .size_full()
+                        .with_sizing_behavior(ListSizingBehavior::Infer)
+                        .track_scroll(self.scroll_handle.clone()),
+                    )
+                }
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt as _;
+    use gpui::{TestAppContext, VisualTestContext, WindowHandle};
+    use language::{FakeLspAdapter, Language, LanguageConfig, LanguageMatcher, tree_sitter_rust};
+    use pretty_assertions::assert_eq;
+    use project::FakeFs;
+    use serde_json::json;
+    use util::path;
+
+    #[gpui::test]
+    async fn test_references_grouping_exclusion_and_history(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            path!("/dir"),
+            json!({
+                "a.rs": "fn one() {\n    two();\n    two();\n}\n",
+                "b.rs": "fn two() {}\nfn three() {\n    two();\n}\n",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), [path!("/dir").as_ref()], cx).await;
+        let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+        language_registry.add(rust_lang());
+        let mut fake_servers = language_registry.register_fake_lsp(
+            "Rust",
+            FakeLspAdapter {
+                capabilities: lsp::ServerCapabilities {
+                    references_provider: Some(lsp::OneOf::Left(true)),
+                    document_highlight_provider: Some(lsp::OneOf::Left(true)),
+                    document_symbol_provider: Some(lsp::OneOf::Left(true)),
+                    ..lsp::ServerCapabilities::default()
+                },
+                ..FakeLspAdapter::default()
+            },
+        );
+
+        let (buffer, _handle) = project
+            .update(cx, |project, cx| {
+                project.open_local_buffer_with_lsp(path!("/dir/b.rs"), cx)
+            })
+            .await
+            .unwrap();
+        let fake_server = fake_servers.next().await.unwrap();
+
+        let uri_a = lsp::Uri::from_file_path(path!("/dir/a.rs")).unwrap();
+        let uri_b = lsp::Uri::from_file_path(path!("/dir/b.rs")).unwrap();
+        let location = |uri: &lsp::Uri, line, start, end| lsp::Location {
+            uri: uri.clone(),
+            range: lsp::Range::new(
+                lsp::Position::new(line, start),
+                lsp::Position::new(line, end),
+            ),
+        };
+        fake_server.set_request_handler::<lsp::request::References, _, _>({
+            let references = vec![
+                location(&uri_b, 0, 3, 6),
+                location(&uri_b, 2, 4, 7),
+                location(&uri_a, 1, 4, 7),
+                location(&uri_a, 2, 4, 7),
+            ];
+            move |_, _| {
+                let references = references.clone();
+                async move { Ok(Some(references)) }
+            }
+        });
+        fake_server.set_request_handler::<lsp::request::DocumentHighlightRequest, _, _>({
+            let uri_b = uri_b.clone();
+            move |params, _| {
+                let is_b = params.text_document_position_params.text_document.uri == uri_b;
+                async move {
+                    let highlight = |line, kind| lsp::DocumentHighlight {
+                        range: lsp::Range::new(
+                            lsp::Position::new(line, if line == 0 { 3 } else { 4 }),
+                            lsp::Position::new(line, if line == 0 { 6 } else { 7 }),
+                        ),
+                        kind: Some(kind),
+                    };
+                    Ok(Some(if is_b {
+                        vec![
+                            highlight(0, lsp::DocumentHighlightKind::WRITE),
+                            highlight(2, lsp::DocumentHighlightKind::READ),
+                        ]
+                    } else {
+                        vec![
+                            highlight(1, lsp::DocumentHighlightKind::READ),
+                            highlight(2, lsp::DocumentHighlightKind::READ),
+                        ]
+                    }))
+                }
+            }
+        });
+        fake_server.set_request_handler::<lsp::request::DocumentSymbolRequest, _, _>({
+            let uri_b = uri_b.clone();
+            move |params, _| {
+                let is_b = params.text_document.uri == uri_b;
+                async move {
+                    #[allow(deprecated)]
+                    let symbol = |name: &str, start_line, end_line| lsp::DocumentSymbol {
+                        name: name.to_string(),
+                        detail: None,
+                        kind: lsp::SymbolKind::FUNCTION,
+                        tags: None,
+                        deprecated: None,
+                        range: lsp::Range::new(
+                            lsp::Position::new(start_line, 0),
+                            lsp::Position::new(end_line, 20),
+                        ),
+                        selection_range: lsp::Range::new(
+                            lsp::Position::new(start_line, 3),
+                            lsp::Position::new(start_line, 6),
+                        ),
+                        children: None,
+                    };
+                    let symbols = if is_b {
+                        vec![symbol("two", 0, 0), symbol("three", 1, 3)]
+                    } else {
+                        vec![symbol("one", 0, 3)]
+                    };
+                    Ok(Some(lsp::DocumentSymbolResponse::Nested(symbols)))
+                }
+            }
+        });
+
+        let workspace = add_references_panel(&project, cx).await;
+        let cx = &mut VisualTestContext::from_window(*workspace, cx);
+        let panel = references_panel(&workspace, cx);
+
+        panel.update_in(cx, |panel, window, cx| {
+            let position = buffer.read(cx).anchor_before(Point::new(0, 4));
+            panel.find_usages(buffer.clone(), position, window, cx);
+        });
+        cx.run_until_parked();
+        assert_eq!(
+            display_entries(&panel, cx),
+            r#"4 usages of `two`
+dir/a.rs (2)
+  one
+    2 [read] two();
+    3 [read] two();
+dir/b.rs (2)
+  two
+    1 [write] fn two() {}
+  three
+    3 [read] two();"#
+        );
+
+        panel.update(cx, |panel, cx| {
+            panel.toggle_entry_excluded(
+                PanelEntry::Symbol {
+                    file_ix: 1,
+                    symbol_ix: 1,
+                },
+                cx,
+            );
+            panel.toggle_entry_pinned(
+                PanelEntry::Reference {
+                    file_ix: 0,
+                    symbol_ix: 0,
+                    reference_ix: 1,
+                },
+                cx,
+            );
+        });
+        assert_eq!(
+            display_entries(&panel, cx),
+            r#"3 usages of `two`
+Pinned
+  a.rs:3 [read] two();
+dir/a.rs (2)
+  one
+    2 [read] two();
+    3 [read] two();
+dir/b.rs (1)
+  two
+    1 [write] fn two() {}
+  three
+    3 [read] two(); (excluded)"#
+        );
+
+        panel.update_in(cx, |panel, window, cx| {
+            let position = buffer.read(cx).anchor_before(Point::new(1, 4));
+            panel.find_usages(buffer.clone(), position, window, cx);
+        });
+        cx.run_until_parked();
+        panel.update(cx, |panel, _| {
+            assert_eq!(panel.history.len(), 2);
+            assert_eq!(panel.active_search, Some(1));
+        });
+        panel.update_in(cx, |panel, window, cx| {
+            panel.previous_search(&PreviousSearch, window, cx)
+        });
+        assert_eq!(
+            display_entries(&panel, cx).lines().next(),
+            Some("3 usages of `two`"),
+            "Going back in history should restore the previous search along with its exclusions"
+        );
+        panel.update_in(cx, |panel, window, cx| {
+            panel.clear_history(&ClearHistory, window, cx)
+        });
+        assert_eq!(
+            display_entries(&panel, cx),
+            "No usages searched\nPinned\n  a.rs:3 [read] two();",
+            "Pinned references should survive the history clearing"
+        );
+
+        panel.update_in(cx, |panel, window, cx| {
+            let position = buffer.read(cx).anchor_before(Point::new(0, 4));
+            panel.find_usages(buffer.clone(), position, window, cx);
+            panel.find_usages(buffer.clone(), position, window, cx);
+        });
+        cx.run_until_parked();
+        panel.update(cx, |panel, _| {
+            assert_eq!(
+                panel
+                    .history
+                    .iter()
+                    .map(|search| search.title().to_string())
+                    .collect::<Vec<_>>(),
+                ["Search for `two` cancelled", "4 usages of `two`"],
+                "A search replaced before finishing should not stay in progress"
+            );
+        });
+    }
+
+    fn display_entries(panel: &Entity<ReferencesPanel>, cx: &mut VisualTestContext) -> String {
+        panel.read_with(cx, |panel, cx| {
+            let mut lines = vec![
+                panel
+                    .active_search()
+                    .map(|search| search.title())
+                    .unwrap_or_else(|| "No usages searched".into())
+                    .to_string(),
+            ];
+            for entry in &panel.entries {
+                let line = match *entry {
+                    PanelEntry::PinnedHeader => "Pinned".to_string(),
+                    PanelEntry::File { file_ix } => {
+                        let file = &panel.active_search().unwrap().files[file_ix];
+                        let count = file
+                            .references()
+                            .filter(|reference| !reference.excluded)
+                            .count();
+                        format!("{} ({count})", file.path)
+                    }
+                    PanelEntry::Symbol { file_ix, symbol_ix } => {
+                        let file = &panel.active_search().unwrap().files[file_ix];
+                        format!("  {}", file.symbols[symbol_ix].symbol.as_ref().unwrap())
+                    }
+                    PanelEntry::Reference { .. } | PanelEntry::Pinned(_) => {
+                        let reference = panel.reference_for_entry(*entry).unwrap();
+                        let location = if let PanelEntry::Pinned(_) = entry {
+                            format!(
+                                "  {}:{}",
+                                reference.buffer.read(cx).file().unwrap().file_name(cx),
+                                reference.row + 1
+                            )
+                        } else {
+                            format!("    {}", reference.row + 1)
+                        };
+                        let access = reference
+                            .access
+                            .map(|access| format!(" [{}]", access.label()))
+                            .unwrap_or_default();
+                        let excluded = if reference.excluded {
+                            " (excluded)"
+                        } else {
+                            ""
+                        };
+                        format!("{location}{access} {}{excluded}", reference.line)
+                    }
+                };
+                lines.push(line);
+            }
+            lines.join("\n")
+        })
+    }
+
+    async fn add_references_panel(
+        project: &Entity<Project>,
+        cx: &mut TestAppContext,
+    ) -> WindowHandle<Workspace> {
+        let window = cx.add_window(|window, cx| Workspace::test_new(project.clone(), window, cx));
+        let panel = window
+            .update(cx, |_, window, cx| {
+                cx.spawn_in(window, async |this, cx| {
+                    ReferencesPanel::load(this, cx.clone()).await
+                })
+            })
+            .unwrap()
+            .await
+            .expect("Failed to load references panel");
+        window
+            .update(cx, |workspace, window, cx| {
+                workspace.add_panel(panel, window, cx);
+            })
+            .unwrap();
+        window
+    }
+
+    fn references_panel(
+        workspace: &WindowHandle<Workspace>,
+        cx: &mut TestAppContext,
+    ) -> Entity<ReferencesPanel> {
+        workspace
+            .update(cx, |workspace, _, cx| {
+                workspace
+                    .panel::<ReferencesPanel>(cx)
+                    .expect("no references panel")
+            })
+            .unwrap()
+    }
+
+    fn rust_lang() -> Arc<Language> {
+        Arc::new(Language::new(
+            LanguageConfig {
+                name: "Rust".into(),
+                matcher: LanguageMatcher {
+                    path_suffixes: vec!["rs".to_string()],
+                    ..LanguageMatcher::default()
+                },
+                ..LanguageConfig::default()
+            },
+            Some(tree_sitter_rust::LANGUAGE.into()),
+        ))
+    }
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings = SettingsStore::test(cx);
+            cx.set_global(settings);
+
+            theme::init(theme::LoadThemes::JustBase, cx);
+
+            language::init(cx);
+            editor::init(cx);
+            workspace::init_settings(cx);
+            Project::init_settings(cx);
+            super::init(cx);
+        });
+    }
+}
@@ -0,0 +1,25 @@
+use gpui::{App, Pixels};
+pub use settings::DockSide;
+use settings::Settings;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReferencesPanelSettings {
+    pub button: bool,
+    pub default_width: Pixels,
+    pub dock: DockSide,
+    pub group_by_symbol: bool,
+    pub max_history: usize,
+}
+
+impl Settings for ReferencesPanelSettings {
+    fn from_settings(content: &settings::SettingsContent, _cx: &mut App) -> Self {
+        let panel = content.references_panel.as_ref().unwrap();
+        Self {
+            button: panel.button.unwrap(),
+            default_width: panel.default_width.map(gpui::px).unwrap(),
+            dock: panel.dock.unwrap(),
+            group_by_symbol: panel.group_by_symbol.unwrap(),
+            max_history: panel.max_history.unwrap(),
+        }
+    }
+}
@@ -629,8 +629,28 @@ impl HeadlessProject {
             PathStyle::local(),
         )?;
         let results = this.update(&mut cx, |this, cx| {
+            let scoped_paths = query.scope().project_paths(
+                &this.buffer_store,
+                &this.git_store,
+                &this.lsp_store,
+                cx,
+            );
             this.buffer_store.update(cx, |buffer_store, cx| {
-                buffer_store.find_search_candidates(&query, message.limit as _, this.fs.clone(), cx)
+                if let Some(project_paths) = scoped_paths {
+                    buffer_store.find_scoped_search_candidates(
+                        &query,
+                        project_paths,
+                        message.limit as _,
+                        cx,
+                    )
+                } else {
+                    buffer_store.find_search_candidates(
+                        &query,
+                        message.limit as _,
+                        this.fs.clone(),
+                        cx,
+                    )
+                }
             })
         })?;
 
@@ -3,7 +3,7 @@ mod registrar;
 use crate::{
     FocusSearch, NextHistoryQuery, PreviousHistoryQuery, ReplaceAll, ReplaceNext, SearchOption,
     SearchOptions, SearchSource, SelectAllMatches, SelectNextMatch, SelectPreviousMatch,
-    ToggleCaseSensitive, ToggleRegex, ToggleReplace, ToggleSelection, ToggleWholeWord,
+    ToggleCaseSensitive, ToggleFuzzy, ToggleRegex, ToggleReplace, ToggleSelection, ToggleWholeWord,
     search_bar::{ActionButtonState, input_base_styles, render_action_button, render_text_input},
 };
 use any_vec::AnyVec;
@@ -232,6 +232,11 @@ impl Render for BufferSearchBar {
                                 SearchSource::Buffer,
                                 focus_handle.clone(),
                             ))
+                            .child(SearchOption::Fuzzy.as_button(
+                                self.search_options,
+                                SearchSource::Buffer,
+                                focus_handle.clone(),
+                            ))
                         }),
                 )
             });
@@ -452,6 +457,7 @@ impl Render for BufferSearchBar {
             })
             .when(regex, |this| {
                 this.on_action(cx.listener(Self::toggle_regex))
+                    .on_action(cx.listener(Self::toggle_fuzzy))
             })
             .when(selection, |this| {
                 this.on_action(cx.listener(Self::toggle_selection))
@@ -537,6 +543,11 @@ impl BufferSearchBar {
                 this.toggle_regex(action, window, cx);
             }
         }));
+        registrar.register_handler(ForDeployed(|this, action: &ToggleFuzzy, window, cx| {
+            if this.supported_options(cx).regex {
+                this.toggle_fuzzy(action, window, cx);
+            }
+        }));
         registrar.register_handler(ForDeployed(|this, action: &ToggleSelection, window, cx| {
             if this.supported_options(cx).selection {
                 this.toggle_selection(action, window, cx);
@@ -908,7 +919,7 @@ impl BufferSearchBar {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
-        self.search_options.toggle(search_option);
+        self.search_options.toggle_exclusive(search_option);
         self.default_options = self.search_options;
         drop(self.update_matches(false, false, window, cx));
         self.adjust_query_regex_language(cx);
@@ -1160,6 +1171,10 @@ impl BufferSearchBar {
         self.toggle_search_option(SearchOptions::REGEX, window, cx)
     }
 
+    fn toggle_fuzzy(&mut self, _: &ToggleFuzzy, window: &mut Window, cx: &mut Context<Self>) {
+        self.toggle_search_option(SearchOptions::FUZZY, window, cx)
+    }
+
     fn clear_active_searchable_item_matches(&mut self, window: &mut Window, cx: &mut App) {
         if let Some(active_searchable_item) = self.active_searchable_item.as_ref() {
             self.active_match_index = None;
@@ -1216,7 +1231,25 @@ impl BufferSearchBar {
                 } else {
                     // Value doesn't matter, we only construct empty matchers with it
 
-                    if self.search_options.contains(SearchOptions::REGEX) {
+                    if self.search_options.contains(SearchOptions::FUZZY) {
+                        match SearchQuery::fuzzy(
+                            query,
+                            self.search_options.contains(SearchOptions::CASE_SENSITIVE),
+                            false,
+                            PathMatcher::default(),
+                            PathMatcher::default(),
+                            false,
+                            None,
+                        ) {
+                            Ok(query) => query.with_replacement(self.replacement(cx)),
+                            Err(e) => {
+                                self.query_error = Some(e.to_string());
+                                self.clear_active_searchable_item_matches(window, cx);
+                                cx.notify();
+                                return done_rx;
+                            }
+                        }
+                    } else if self.search_options.contains(SearchOptions::REGEX) {
                         match SearchQuery::regex(
                             query,
                             self.search_options.contains(SearchOptions::WHOLE_WORD),
@@ -2789,6 +2822,7 @@ mod tests {
                 case_sensitive: false,
                 include_ignored: false,
                 regex: false,
+                save_after_replace: false,
             },
             cx,
         );
@@ -2851,6 +2885,7 @@ mod tests {
                 case_sensitive: true,
                 include_ignored: false,
                 regex: false,
+                save_after_replace: false,
             },
             cx,
         );
@@ -2888,6 +2923,7 @@ mod tests {
                 case_sensitive: true,
                 include_ignored: false,
                 regex: false,
+                save_after_replace: false,
             },
             cx,
         );
@@ -2914,6 +2950,7 @@ mod tests {
                         case_sensitive: Some(search_settings.case_sensitive),
                         include_ignored: Some(search_settings.include_ignored),
                         regex: Some(search_settings.regex),
+                        save_after_replace: Some(search_settings.save_after_replace),
                     });
                 });
             });
@@ -0,0 +1,279 @@
+use anyhow::Result;
+use db::{
+    query,
+    sqlez::{
+        bindable::Column, domain::Domain, statement::Statement,
+        thread_safe_connection::ThreadSafeConnection,
+    },
+    sqlez_macros::sql,
+};
+use project::search::SearchInputKind;
+use workspace::{WorkspaceDb, WorkspaceId};
+
+use crate::SearchOptions;
+
+/// A project search stored under a name, to be run again later.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SavedSearch {
+    pub name: String,
+    pub query: String,
+    pub options: SearchOptions,
+    pub files_to_include: String,
+    pub files_to_exclude: String,
+}
+
+impl Column for SavedSearch {
+    fn column(statement: &mut Statement, start_index: i32) -> Result<(Self, i32)> {
+        let (name, next_index): (String, i32) = Column::column(statement, start_index)?;
+        let (query, next_index): (String, i32) = Column::column(statement, next_index)?;
+        let (options, next_index): (u32, i32) = Column::column(statement, next_index)?;
+        let (files_to_include, next_index): (String, i32) = Column::column(statement, next_index)?;
+        let (files_to_exclude, next_index): (String, i32) = Column::column(statement, next_index)?;
+        let saved_search = Self {
+            name,
+            query,
+            options: SearchOptions::from_bits_truncate(options as u16),
+            files_to_include,
+            files_to_exclude,
+        };
+        Ok((saved_search, next_index))
+    }
+}
+
+pub struct SearchDb(ThreadSafeConnection);
+
+impl Domain for SearchDb {
+    const NAME: &str = stringify!(SearchDb);
+
+    const MIGRATIONS: &[&str] = &[sql!(
+        CREATE TABLE search_history(
+            workspace_id INTEGER NOT NULL,
+            kind TEXT NOT NULL,
+            position INTEGER NOT NULL,
+            query TEXT NOT NULL,
+            PRIMARY KEY(workspace_id, kind, position),
+            FOREIGN KEY(workspace_id) REFERENCES workspaces(workspace_id)
+            ON DELETE CASCADE
+        ) STRICT;
+
+        CREATE TABLE saved_searches(
+            workspace_id INTEGER NOT NULL,
+            name TEXT NOT NULL,
+            query TEXT NOT NULL,
+            options INTEGER NOT NULL,
+            files_to_include TEXT NOT NULL,
+            files_to_exclude TEXT NOT NULL,
+            PRIMARY KEY(workspace_id, name),
+            FOREIGN KEY(workspace_id) REFERENCES workspaces(workspace_id)
+            ON DELETE CASCADE
+        ) STRICT;
+    )];
+}
+
+db::static_connection!(SEARCH_DB, SearchDb, [WorkspaceDb]);
+
+fn history_kind(kind: SearchInputKind) -> &'static str {
+    match kind {
+        SearchInputKind::Query => "query",
+        SearchInputKind::Include => "include",
+        SearchInputKind::Exclude => "exclude",
+        SearchInputKind::Replace => "replace",
+    }
+}
+
+impl SearchDb {
+    /// Returns the persisted history of the given search input, oldest query first.
+    pub fn search_history(
+        &self,
+        workspace_id: WorkspaceId,
+        kind: SearchInputKind,
+    ) -> Result<Vec<String>> {
+        self.search_history_queries(workspace_id, history_kind(kind))
+    }
+
+    query! {
+        fn search_history_queries(workspace_id: WorkspaceId, kind: &str) -> Result<Vec<String>> {
+            SELECT query
+            FROM search_history
+            WHERE workspace_id = ?1 AND kind = ?2
+            ORDER BY position
+        }
+    }
+
+    /// Replaces the persisted history of the given search input.
+    pub async fn save_search_history(
+        &self,
+        workspace_id: WorkspaceId,
+        kind: SearchInputKind,
+        queries: Vec<String>,
+    ) -> Result<()> {
+        let kind = history_kind(kind);
+        log::debug!("Saving {kind} search history in workspace {workspace_id:?}");
+        self.write(move |conn| {
+            conn.exec_bound(sql!(
+                DELETE FROM search_history WHERE workspace_id = ?1 AND kind = ?2;
+            ))?((workspace_id, kind))?;
+            let mut insert = conn.exec_bound(sql!(
+                INSERT INTO search_history (workspace_id, kind, position, query)
+                VALUES (?1, ?2, ?3, ?4);
+            ))?;
+            for (position, query) in queries.into_iter().enumerate() {
+                insert((workspace_id, kind, position, query))?;
+            }
+            Ok(())
+        })
+        .await
+    }
+
+    query! {
+        pub fn saved_searches(workspace_id: WorkspaceId) -> Result<Vec<SavedSearch>> {
+            SELECT name, query, options, files_to_include, files_to_exclude
+            FROM saved_searches
+            WHERE workspace_id = ?
+            ORDER BY name
+        }
+    }
+
+    /// Saves the search, replacing any saved search with the same name.
+    pub async fn save_search(
+        &self,
+        workspace_id: WorkspaceId,
+        saved_search: SavedSearch,
+    ) -> Result<()> {
+        self.write(move |conn| {
+            conn.exec_bound(sql!(
+                INSERT OR REPLACE INTO saved_searches
+                    (workspace_id, name, query, options, files_to_include, files_to_exclude)
+                VALUES
+                    (?1, ?2, ?3, ?4, ?5, ?6);
+            ))?((
+                workspace_id,
+                saved_search.name,
+                saved_search.query,
+                saved_search.options.bits() as u32,
+                saved_search.files_to_include,
+                saved_search.files_to_exclude,
+            ))
+        })
+        .await
+    }
+
+    query! {
+        pub async fn delete_saved_search(workspace_id: WorkspaceId, name: String) -> Result<()> {
+            DELETE FROM saved_searches
+            WHERE workspace_id = ?1 AND name = ?2
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[gpui::test]
+    async fn test_search_history() {
+        let workspace_id = workspace::WORKSPACE_DB.next_id().await.unwrap();
+
+        assert!(
+            SEARCH_DB
+                .search_history(workspace_id, SearchInputKind::Query)
+                .unwrap()
+                .is_empty()
+        );
+
+        SEARCH_DB
+            .save_search_history(
+                workspace_id,
+                SearchInputKind::Query,
+                vec!["foo".to_string(), "bar".to_string()],
+            )
+            .await
+            .unwrap();
+        SEARCH_DB
+            .save_search_history(
+                workspace_id,
+                SearchInputKind::Replace,
+                vec!["baz".to_string()],
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            SEARCH_DB
+                .search_history(workspace_id, SearchInputKind::Query)
+                .unwrap(),
+            ["foo", "bar"]
+        );
+        assert_eq!(
+            SEARCH_DB
+                .search_history(workspace_id, SearchInputKind::Replace)
+                .unwrap(),
+            ["baz"]
+        );
+
+        SEARCH_DB
+            .save_search_history(
+                workspace_id,
+                SearchInputKind::Query,
+                vec!["bar".to_string()],
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            SEARCH_DB
+                .search_history(workspace_id, SearchInputKind::Query)
+                .unwrap(),
+            ["bar"]
+        );
+    }
+
+    #[gpui::test]
+    async fn test_saved_searches() {
+        let workspace_id = workspace::WORKSPACE_DB.next_id().await.unwrap();
+
+        let todos = SavedSearch {
+            name: "todos".to_string(),
+            query: "TODO|FIXME".to_string(),
+            options: SearchOptions::REGEX | SearchOptions::CASE_SENSITIVE,
+            files_to_include: "src/**".to_string(),
+            files_to_exclude: String::new(),
+        };
+        let unwraps = SavedSearch {
+            name: "unwraps".to_string(),
+            query: "$x.unwrap()".to_string(),
+            options: SearchOptions::STRUCTURAL,
+            files_to_include: String::new(),
+            files_to_exclude: "tests/**".to_string(),
+        };
+        SEARCH_DB
+            .save_search(workspace_id, unwraps.clone())
+            .await
+            .unwrap();
+        SEARCH_DB
+            .save_search(workspace_id, todos.clone())
+            .await
+            .unwrap();
+        assert_eq!(
+            SEARCH_DB.saved_searches(workspace_id).unwrap(),
+            [todos.clone(), unwraps.clone()]
+        );
+
+        let todos = SavedSearch {
+            query: "TODO".to_string(),
+            ..todos
+        };
+        SEARCH_DB
+            .save_search(workspace_id, todos.clone())
+            .await
+            .unwrap();
+        assert_eq!(
+            SEARCH_DB.saved_searches(workspace_id).unwrap(),
+            [todos.clone(), unwraps]
+        );
+
+        SEARCH_DB
+            .delete_saved_search(workspace_id, "unwraps".to_string())
+            .await
+            .unwrap();
+        assert_eq!(SEARCH_DB.saved_searches(workspace_id).unwrap(), [todos]);
+    }
+}
@@ -1,32 +1,38 @@
 use crate::{
     BufferSearchBar, FocusSearch, NextHistoryQuery, PreviousHistoryQuery, ReplaceAll, ReplaceNext,
     SearchOption, SearchOptions, SearchSource, SelectNextMatch, SelectPreviousMatch,
-    ToggleCaseSensitive, ToggleIncludeIgnored, ToggleRegex, ToggleReplace, ToggleWholeWord,
+    ToggleCaseSensitive, ToggleFuzzy, ToggleIncludeIgnored, ToggleMatchExcluded, ToggleRegex,
+    ToggleReplace, ToggleReplacePreview, ToggleSemantic, ToggleStructural, ToggleWholeWord,
     buffer_search::Deploy,
+    persistence::{SEARCH_DB, SavedSearch},
+    saved_searches::SavedSearches,
     search_bar::{ActionButtonState, input_base_styles, render_action_button, render_text_input},
 };
 use anyhow::Context as _;
-use collections::HashMap;
+use collections::{HashMap, HashSet};
 use editor::{
     Anchor, Editor, EditorEvent, EditorSettings, MAX_TAB_TITLE_LEN, MultiBuffer, SelectionEffects,
     actions::{Backtab, SelectAll, Tab},
     items::active_match_index,
     multibuffer_context_lines,
 };
-use futures::{StreamExt, stream::FuturesOrdered};
+use futures::{Stream, StreamExt, channel::mpsc, stream::FuturesOrdered};
 use gpui::{
     Action, AnyElement, AnyView, App, Axis, Context, Entity, EntityId, EventEmitter, FocusHandle,
     Focusable, Global, Hsla, InteractiveElement, IntoElement, KeyContext, ParentElement, Point,
     Render, SharedString, Styled, Subscription, Task, UpdateGlobal, WeakEntity, Window, actions,
     div,
 };
-use language::{Buffer, Language};
+use language::{Bias, Buffer, Language};
 use menu::Confirm;
 use project::{
     Project, ProjectPath,
-    search::{SearchInputKind, SearchQuery},
+    search::{SearchInputKind, SearchQuery, SearchScope},
     search_history::SearchHistoryCursor,
 };
+use schemars::JsonSchema;
+use semantic_index::{ProjectIndex, SemanticIndex};
+use serde::Deserialize;
 use settings::Settings;
 use std::{
     any::{Any, TypeId},
@@ -38,9 +44,10 @@ use std::{
 use ui::{IconButtonShape, KeyBinding, Toggleable, Tooltip, prelude::*, utils::SearchInputWidth};
 use util::{ResultExt as _, paths::PathMatcher, rel_path::RelPath};
 use workspace::{
-    DeploySearch, ItemNavHistory, NewSearch, ToolbarItemEvent, ToolbarItemLocation,
+    DeploySearch, ItemNavHistory, NewSearch, Toast, ToolbarItemEvent, ToolbarItemLocation,
     ToolbarItemView, Workspace, WorkspaceId,
     item::{BreadcrumbText, Item, ItemEvent, ItemHandle, SaveOptions},
+    notifications::NotificationId,
     searchable::{Direction, SearchableItem, SearchableItemHandle},
 };
 
@@ -54,10 +61,40 @@ actions!(
         /// Moves to the next input field.
         NextField,
         /// Toggles the search filters panel.
-        ToggleFilters
+        ToggleFilters,
+        /// Opens the saved searches picker.
+        ToggleSavedSearches
     ]
 );
 
+/// Saves the active project search, with its options and path filters, to be run again later.
+#[derive(PartialEq, Clone, Default, Debug, Deserialize, JsonSchema, Action)]
+#[action(namespace = project_search)]
+#[serde(deny_unknown_fields)]
+pub struct SaveSearch {
+    /// The name to save the search under. Defaults to the search query.
+    #[serde(default)]
+    pub name: Option<String>,
+}
+
+/// Runs the project search saved under the given name.
+#[derive(PartialEq, Clone, Default, Debug, Deserialize, JsonSchema, Action)]
+#[action(namespace = project_search)]
+#[serde(deny_unknown_fields)]
+pub struct RunSavedSearch {
+    pub name: String,
+}
+
+const SEARCH_HISTORY_KINDS: [SearchInputKind; 4] = [
+    SearchInputKind::Query,
+    SearchInputKind::Include,
+    SearchInputKind::Exclude,
+    SearchInputKind::Replace,
+];
+
+/// The most chunks of code shown for a semantic search.
+const SEMANTIC_SEARCH_LIMIT: usize = 32;
+
 #[derive(Default)]
 struct ActiveSettings(HashMap<WeakEntity<Project>, ProjectSearchSettings>);
 
@@ -65,7 +102,8 @@ impl Global for ActiveSettings {}
 
 pub fn init(cx: &mut App) {
     cx.set_global(ActiveSettings::default());
-    cx.observe_new(|workspace: &mut Workspace, _window, _cx| {
+    cx.observe_new(|workspace: &mut Workspace, _window, cx| {
+        restore_search_history(workspace, cx);
         register_workspace_action(workspace, move |search_bar, _: &Deploy, window, cx| {
             search_bar.focus_search(window, cx);
         });
@@ -93,12 +131,39 @@ pub fn init(cx: &mut App) {
         register_workspace_action(workspace, move |search_bar, _: &ToggleRegex, window, cx| {
             search_bar.toggle_search_option(SearchOptions::REGEX, window, cx);
         });
+        register_workspace_action(
+            workspace,
+            move |search_bar, _: &ToggleStructural, window, cx| {
+                search_bar.toggle_search_option(SearchOptions::STRUCTURAL, window, cx);
+            },
+        );
+        register_workspace_action(workspace, move |search_bar, _: &ToggleFuzzy, window, cx| {
+            search_bar.toggle_search_option(SearchOptions::FUZZY, window, cx);
+        });
+        register_workspace_action(
+            workspace,
+            move |search_bar, _: &ToggleSemantic, window, cx| {
+                search_bar.toggle_search_option(SearchOptions::SEMANTIC, window, cx);
+            },
+        );
         register_workspace_action(
             workspace,
             move |search_bar, action: &ToggleReplace, window, cx| {
                 search_bar.toggle_replace(action, window, cx)
             },
         );
+        register_workspace_action(
+            workspace,
+            move |search_bar, action: &ToggleReplacePreview, window, cx| {
+                search_bar.toggle_replace_preview(action, window, cx)
+            },
+        );
+        register_workspace_action(
+            workspace,
+            move |search_bar, action: &ToggleMatchExcluded, window, cx| {
+                search_bar.toggle_match_excluded(action, window, cx)
+            },
+        );
         register_workspace_action(
             workspace,
             move |search_bar, action: &SelectPreviousMatch, window, cx| {
@@ -168,10 +233,53 @@ pub fn init(cx: &mut App) {
             ProjectSearchView::new_search(workspace, action, window, cx);
             cx.notify();
         });
+        workspace.register_action(ProjectSearchView::save_search);
+        workspace.register_action(|workspace, action: &RunSavedSearch, window, cx| {
+            let Some(workspace_id) = workspace.database_id() else {
+                return;
+            };
+            let saved_search = SEARCH_DB
+                .saved_searches(workspace_id)
+                .log_err()
+                .into_iter()
+                .flatten()
+                .find(|saved_search| saved_search.name == action.name);
+            if let Some(saved_search) = saved_search {
+                ProjectSearchView::run_saved_search(workspace, &saved_search, window, cx);
+            }
+        });
+        workspace.register_action(|workspace, _: &ToggleSavedSearches, window, cx| {
+            SavedSearches::toggle(workspace, window, cx);
+        });
     })
     .detach();
 }
 
+/// Restores the search histories persisted by the previous sessions of the workspace, so they
+/// can be navigated again.
+fn restore_search_history(workspace: &Workspace, cx: &mut Context<Workspace>) {
+    let Some(workspace_id) = workspace.database_id() else {
+        return;
+    };
+    let project = workspace.project().clone();
+    cx.spawn(async move |_, cx| {
+        let histories = cx
+            .background_spawn(async move {
+                SEARCH_HISTORY_KINDS
+                    .into_iter()
+                    .map(|kind| anyhow::Ok((kind, SEARCH_DB.search_history(workspace_id, kind)?)))
+                    .collect::<anyhow::Result<Vec<_>>>()
+            })
+            .await?;
+        project.update(cx, |project, _| {
+            for (kind, queries) in histories {
+                project.search_history_mut(kind).restore(queries);
+            }
+        })
+    })
+    .detach_and_log_err(cx);
+}
+
 fn contains_uppercase(str: &str) -> bool {
     str.chars().any(|c| c.is_uppercase())
 }
@@ -189,6 +297,7 @@ pub struct ProjectSearch {
     search_history_cursor: SearchHistoryCursor,
     search_included_history_cursor: SearchHistoryCursor,
     search_excluded_history_cursor: SearchHistoryCursor,
+    search_replaced_history_cursor: SearchHistoryCursor,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -214,7 +323,11 @@ pub struct ProjectSearchView {
     excluded_files_editor: Entity<Editor>,
     filters_enabled: bool,
     replace_enabled: bool,
+    replace_preview_enabled: bool,
+    /// The indices of the matches left out when replacing all matches.
+    excluded_matches: HashSet<usize>,
     included_opened_only: bool,
+    search_scope: SearchScope,
     regex_language: Option<Arc<Language>>,
     _subscriptions: Vec<Subscription>,
 }
@@ -247,6 +360,7 @@ impl ProjectSearch {
             search_history_cursor: Default::default(),
             search_included_history_cursor: Default::default(),
             search_excluded_history_cursor: Default::default(),
+            search_replaced_history_cursor: Default::default(),
         }
     }
 
@@ -266,6 +380,7 @@ impl ProjectSearch {
             search_history_cursor: self.search_history_cursor.clone(),
             search_included_history_cursor: self.search_included_history_cursor.clone(),
             search_excluded_history_cursor: self.search_excluded_history_cursor.clone(),
+            search_replaced_history_cursor: self.search_replaced_history_cursor.clone(),
         })
     }
     fn cursor(&self, kind: SearchInputKind) -> &SearchHistoryCursor {
@@ -273,6 +388,7 @@ impl ProjectSearch {
             SearchInputKind::Query => &self.search_history_cursor,
             SearchInputKind::Include => &self.search_included_history_cursor,
             SearchInputKind::Exclude => &self.search_excluded_history_cursor,
+            SearchInputKind::Replace => &self.search_replaced_history_cursor,
         }
     }
     fn cursor_mut(&mut self, kind: SearchInputKind) -> &mut SearchHistoryCursor {
@@ -280,6 +396,7 @@ impl ProjectSearch {
             SearchInputKind::Query => &mut self.search_history_cursor,
             SearchInputKind::Include => &mut self.search_included_history_cursor,
             SearchInputKind::Exclude => &mut self.search_excluded_history_cursor,
+            SearchInputKind::Replace => &mut self.search_replaced_history_cursor,
         }
     }
 
@@ -303,8 +420,84 @@ impl ProjectSearch {
             project.search(query.clone(), cx)
         });
         self.last_search_query_text = Some(query.as_str().to_string());
-        self.search_id += 1;
         self.active_query = Some(query);
+        self.show_results(search, cx);
+    }
+
+    /// Searches the project's semantic index for the code most related to the query's meaning,
+    /// showing the most similar chunks first.
+    fn semantic_search(
+        &mut self,
+        query: String,
+        project_index: Entity<ProjectIndex>,
+        cx: &mut Context<Self>,
+    ) {
+        self.project.update(cx, |project, _| {
+            project
+                .search_history_mut(SearchInputKind::Query)
+                .add(&mut self.search_history_cursor, query.clone());
+        });
+        self.last_search_query_text = Some(query.clone());
+        self.active_query = None;
+
+        let project = self.project.clone();
+        let search = project_index
+            .read(cx)
+            .search(query, SEMANTIC_SEARCH_LIMIT, cx);
+        let (results_tx, results_rx) = mpsc::unbounded();
+        cx.spawn(async move |_, cx| {
+            let results = search.await?;
+            // Chunks of the same buffer are shown together, in the order of their best match.
+            let mut buffers_with_ranges =
+                Vec::<(Entity<Buffer>, Vec<Range<language::Anchor>>)>::new();
+            for result in results {
+                let project_path = ProjectPath {
+                    worktree_id: result.worktree_id,
+                    path: result.path,
+                };
+                let Some(buffer) = project
+                    .update(cx, |project, cx| project.open_buffer(project_path, cx))?
+                    .await
+                    .log_err()
+                else {
+                    continue;
+                };
+                let range = buffer.read_with(cx, |buffer, _| {
+                    // The file may have changed since it was indexed.
+                    let start =
+                        buffer.clip_offset(result.range.start.min(buffer.len()), Bias::Left);
+                    let end = buffer.clip_offset(result.range.end.min(buffer.len()), Bias::Right);
+                    buffer.anchor_after(start)..buffer.anchor_before(end)
+                })?;
+                match buffers_with_ranges
+                    .iter_mut()
+                    .find(|(existing, _)| *existing == buffer)
+                {
+                    Some((_, ranges)) => ranges.push(range),
+                    None => buffers_with_ranges.push((buffer, vec![range])),
+                }
+            }
+
+            for (buffer, mut ranges) in buffers_with_ranges {
+                buffer.read_with(cx, |buffer, _| {
+                    ranges.sort_by(|a, b| a.start.cmp(&b.start, buffer));
+                })?;
+                results_tx
+                    .unbounded_send(project::search::SearchResult::Buffer { buffer, ranges })
+                    .ok();
+            }
+            anyhow::Ok(())
+        })
+        .detach_and_log_err(cx);
+        self.show_results(results_rx, cx);
+    }
+
+    fn show_results(
+        &mut self,
+        search: impl Stream<Item = project::search::SearchResult> + 'static,
+        cx: &mut Context<Self>,
+    ) {
+        self.search_id += 1;
         self.match_ranges.clear();
         self.pending_search = Some(cx.spawn(async move |project_search, cx| {
             let mut matches = pin!(search.ready_chunks(1024));
@@ -338,7 +531,10 @@ impl ProjectSearch {
                         project_search.excerpts.update(cx, |excerpts, cx| {
                             buffers_with_ranges
                                 .into_iter()
-                                .map(|(buffer, ranges)| {
+                                .map(|(buffer, mut ranges)| {
+                                    // Fuzzy matches are ranked by score, but excerpts are in order.
+                                    let snapshot = buffer.read(cx);
+                                    ranges.sort_by(|a, b| a.start.cmp(&b.start, snapshot));
                                     excerpts.set_anchored_excerpts_for_path(
                                         buffer,
                                         ranges,
@@ -664,7 +860,7 @@ impl ProjectSearchView {
     }
 
     fn toggle_search_option(&mut self, option: SearchOptions, cx: &mut Context<Self>) {
-        self.search_options.toggle(option);
+        self.search_options.toggle_exclusive(option);
         ActiveSettings::update_global(cx, |settings, cx| {
             settings.0.insert(
                 self.entity.read(cx).project.downgrade(),
@@ -678,6 +874,14 @@ impl ProjectSearchView {
         self.included_opened_only = !self.included_opened_only;
     }
 
+    fn toggle_search_scope(&mut self, scope: SearchScope) {
+        self.search_scope = if self.search_scope == scope {
+            SearchScope::All
+        } else {
+            scope
+        };
+    }
+
     pub fn replacement(&self, cx: &App) -> String {
         self.replacement_editor.read(cx).text(cx)
     }
@@ -699,6 +903,7 @@ impl ProjectSearchView {
 
         let query = self.entity.read(cx).active_query.clone();
         if let Some(query) = query {
+            self.add_replacement_to_history(cx);
             let query = query.with_replacement(self.replacement(cx));
 
             // TODO: Do we need the clone here?
@@ -724,6 +929,7 @@ impl ProjectSearchView {
             return;
         };
         let query = query.clone().with_replacement(self.replacement(cx));
+        self.add_replacement_to_history(cx);
 
         let match_ranges = self
             .entity
@@ -732,13 +938,134 @@ impl ProjectSearchView {
             return;
         }
 
+        let excluded_matches = mem::take(&mut self.excluded_matches);
+        let mut replaced_ranges = match_ranges
+            .iter()
+            .enumerate()
+            .filter(|(ix, _)| !excluded_matches.contains(ix))
+            .map(|(_, range)| range);
         self.results_editor.update(cx, |editor, cx| {
-            editor.replace_all(&mut match_ranges.iter(), &query, window, cx);
+            editor.clear_replace_preview(cx);
+            // All matches are replaced in a single transaction, undone at once.
+            editor.replace_all(&mut replaced_ranges, &query, window, cx);
         });
+        self.replace_preview_enabled = false;
+
+        if EditorSettings::get_global(cx).search.save_after_replace {
+            let excerpts = self.entity.read(cx).excerpts.read(cx);
+            let buffers = match_ranges
+                .iter()
+                .enumerate()
+                .filter(|(ix, _)| !excluded_matches.contains(ix))
+                .filter_map(|(_, range)| excerpts.buffer(range.start.buffer_id?))
+                .collect::<HashSet<_>>();
+            let project = self.entity.read(cx).project.clone();
+            project
+                .update(cx, |project, cx| project.save_buffers(buffers, cx))
+                .detach_and_log_err(cx);
+        }
 
         self.entity.update(cx, |model, _cx| {
             model.match_ranges = match_ranges;
         });
+        cx.notify();
+    }
+
+    fn add_replacement_to_history(&mut self, cx: &mut Context<Self>) {
+        let replacement = self.replacement(cx);
+        if replacement.is_empty() {
+            return;
+        }
+        self.entity.update(cx, |model, cx| {
+            model.project.update(cx, |project, _| {
+                project
+                    .search_history_mut(SearchInputKind::Replace)
+                    .add(&mut model.search_replaced_history_cursor, replacement);
+            });
+        });
+        self.save_search_history(cx);
+    }
+
+    /// Persists the project's search histories in the workspace database.
+    fn save_search_history(&self, cx: &mut Context<Self>) {
+        let workspace = self.workspace.clone();
+        let project = self.entity.read(cx).project.clone();
+        cx.spawn(async move |_, cx| {
+            let Some(workspace_id) =
+                workspace.read_with(cx, |workspace, _| workspace.database_id())?
+            else {
+                return anyhow::Ok(());
+            };
+            let histories = project.read_with(cx, |project, _| {
+                SEARCH_HISTORY_KINDS.map(|kind| {
+                    let queries = project
+                        .search_history(kind)
+                        .queries()
+                        .map(ToOwned::to_owned)
+                        .collect::<Vec<_>>();
+                    (kind, queries)
+                })
+            })?;
+            for (kind, queries) in histories {
+                SEARCH_DB
+                    .save_search_history(workspace_id, kind, queries)
+                    .await?;
+            }
+            anyhow::Ok(())
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn toggle_replace_preview(&mut self, cx: &mut Context<Self>) {
+        self.replace_preview_enabled = !self.replace_preview_enabled;
+        self.refresh_replace_preview(cx);
+        cx.notify();
+    }
+
+    fn toggle_match_excluded(&mut self, cx: &mut Context<Self>) {
+        let Some(active_index) = self.active_match_index else {
+            return;
+        };
+        if !self.excluded_matches.remove(&active_index) {
+            self.excluded_matches.insert(active_index);
+        }
+        self.refresh_replace_preview(cx);
+        cx.notify();
+    }
+
+    /// Shows what replacing all matches would do, inline in the results, leaving out the
+    /// excluded matches.
+    fn refresh_replace_preview(&mut self, cx: &mut Context<Self>) {
+        let query = self
+            .entity
+            .read(cx)
+            .active_query
+            .clone()
+            .filter(|_| self.replace_enabled && self.replace_preview_enabled);
+        let Some(query) = query else {
+            self.results_editor
+                .update(cx, |editor, cx| editor.clear_replace_preview(cx));
+            return;
+        };
+
+        let query = query.with_replacement(self.replacement(cx));
+        let snapshot = self.entity.read(cx).excerpts.read(cx).snapshot(cx);
+        let replacements = self
+            .entity
+            .read(cx)
+            .match_ranges
+            .iter()
+            .enumerate()
+            .filter(|(ix, _)| !self.excluded_matches.contains(ix))
+            .filter_map(|(_, range)| {
+                let text = snapshot.text_for_range(range.clone()).collect::<String>();
+                let replacement = query.replacement_for(&text)?;
+                Some((range.clone(), replacement.into_owned()))
+            })
+            .collect();
+        self.results_editor.update(cx, |editor, cx| {
+            editor.show_replace_preview(replacements, cx)
+        });
     }
 
     pub fn new(
@@ -808,6 +1135,14 @@ impl ProjectSearchView {
             }
             editor
         });
+        subscriptions.push(cx.subscribe(
+            &replacement_editor,
+            |this, _, event: &EditorEvent, cx| {
+                if let EditorEvent::Edited { .. } = event {
+                    this.refresh_replace_preview(cx);
+                }
+            },
+        ));
         let results_editor = cx.new(|cx| {
             let mut editor = Editor::for_multibuffer(excerpts, Some(project.clone()), window, cx);
             editor.set_searchable(false);
@@ -897,7 +1232,10 @@ impl ProjectSearchView {
             excluded_files_editor,
             filters_enabled,
             replace_enabled: false,
+            replace_preview_enabled: false,
+            excluded_matches: HashSet::default(),
             included_opened_only: false,
+            search_scope: SearchScope::All,
             regex_language: None,
             _subscriptions: subscriptions,
         };
@@ -968,6 +1306,7 @@ impl ProjectSearchView {
                         editor.set_text(old_query.as_str(), window, cx);
                     });
                     search_view.search_options = SearchOptions::from_query(&old_query);
+                    search_view.search_scope = old_query.scope();
                     search_view.adjust_query_regex_language(cx);
                 }
                 new_query
@@ -999,7 +1338,7 @@ impl ProjectSearchView {
         window: &mut Window,
         cx: &mut Context<Workspace>,
     ) {
-        Self::existing_or_new_search(workspace, None, &DeploySearch::find(), window, cx)
+        Self::existing_or_new_search(workspace, None, &DeploySearch::find(), window, cx);
     }
 
     fn existing_or_new_search(
@@ -1008,7 +1347,7 @@ impl ProjectSearchView {
         action: &workspace::DeploySearch,
         window: &mut Window,
         cx: &mut Context<Workspace>,
-    ) {
+    ) -> Entity<ProjectSearchView> {
         let query = workspace.active_item(cx).and_then(|item| {
             if let Some(buffer_search_query) = buffer_search_query(workspace, item.as_ref(), cx) {
                 return Some(buffer_search_query);
@@ -1066,6 +1405,7 @@ impl ProjectSearchView {
             }
             search.focus_query_editor(window, cx)
         });
+        search
     }
 
     fn prompt_to_save_if_dirty_then_search(
@@ -1143,11 +1483,139 @@ impl ProjectSearchView {
         } else {
             None
         };
-        if let Some(query) = self.build_search_query(cx, open_buffers) {
+        if self.search_options.contains(SearchOptions::SEMANTIC) {
+            let query = self.search_query_text(cx);
+            if query.is_empty() {
+                return;
+            }
+            let project = self.entity.read(cx).project.clone();
+            let Some(project_index) = SemanticIndex::global(cx)
+                .and_then(|semantic_index| semantic_index.read(cx).project_index(&project))
+            else {
+                self.panels_with_errors.insert(
+                    InputPanel::Query,
+                    "The semantic index isn't enabled for this project".to_string(),
+                );
+                cx.notify();
+                return;
+            };
+            self.panels_with_errors.remove(&InputPanel::Query);
+            self.entity.update(cx, |model, cx| {
+                model.semantic_search(query, project_index, cx)
+            });
+            self.save_search_history(cx);
+        } else if let Some(query) = self.build_search_query(cx, open_buffers) {
             self.entity.update(cx, |model, cx| model.search(query, cx));
+            self.save_search_history(cx);
         }
     }
 
+    fn saved_search(&self, name: String, cx: &App) -> SavedSearch {
+        SavedSearch {
+            name,
+            query: self.search_query_text(cx),
+            options: self.search_options,
+            files_to_include: self.included_files_editor.read(cx).text(cx),
+            files_to_exclude: self.excluded_files_editor.read(cx).text(cx),
+        }
+    }
+
+    fn apply_saved_search(
+        &mut self,
+        saved_search: &SavedSearch,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.set_search_editor(SearchInputKind::Query, &saved_search.query, window, cx);
+        self.set_search_editor(
+            SearchInputKind::Include,
+            &saved_search.files_to_include,
+            window,
+            cx,
+        );
+        self.set_search_editor(
+            SearchInputKind::Exclude,
+            &saved_search.files_to_exclude,
+            window,
+            cx,
+        );
+        self.search_options = saved_search.options;
+        if !saved_search.files_to_include.is_empty() || !saved_search.files_to_exclude.is_empty() {
+            self.filters_enabled = true;
+        }
+        self.adjust_query_regex_language(cx);
+        // Searching may read the workspace, which can be in the middle of an update here.
+        cx.defer_in(window, |this, _, cx| this.search(cx));
+        cx.notify();
+    }
+
+    /// Returns the current search of the workspace's active project search, under the given name.
+    pub(crate) fn active_saved_search(
+        workspace: &Workspace,
+        name: Option<String>,
+        cx: &App,
+    ) -> Option<SavedSearch> {
+        let search_view = workspace.active_item(cx)?.downcast::<ProjectSearchView>()?;
+        let search_view = search_view.read(cx);
+        let name = name.unwrap_or_else(|| search_view.search_query_text(cx));
+        let saved_search = search_view.saved_search(name, cx);
+        (!saved_search.name.is_empty() && !saved_search.query.is_empty()).then_some(saved_search)
+    }
+
+    fn save_search(
+        workspace: &mut Workspace,
+        action: &SaveSearch,
+        _: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) {
+        let Some(saved_search) = Self::active_saved_search(workspace, action.name.clone(), cx)
+        else {
+            return;
+        };
+        Self::write_saved_search(workspace, saved_search, cx);
+    }
+
+    pub(crate) fn write_saved_search(
+        workspace: &mut Workspace,
+        saved_search: SavedSearch,
+        cx: &mut Context<Workspace>,
+    ) {
+        let Some(workspace_id) = workspace.database_id() else {
+            return;
+        };
+        let message = format!("Saved search “{}”", saved_search.name);
+        cx.spawn(async move |workspace, cx| {
+            SEARCH_DB.save_search(workspace_id, saved_search).await?;
+            workspace.update(cx, |workspace, cx| {
+                struct SavedSearchToast;
+                workspace.show_toast(
+                    Toast::new(NotificationId::unique::<SavedSearchToast>(), message).autohide(),
+                    cx,
+                );
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    /// Runs the saved search in the active pane's project search, creating one if needed.
+    pub(crate) fn run_saved_search(
+        workspace: &mut Workspace,
+        saved_search: &SavedSearch,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) {
+        let existing = workspace
+            .active_pane()
+            .read(cx)
+            .items()
+            .find_map(|item| item.downcast::<ProjectSearchView>());
+        let search =
+            Self::existing_or_new_search(workspace, existing, &DeploySearch::find(), window, cx);
+        search.update(cx, |search, cx| {
+            search.apply_saved_search(saved_search, window, cx)
+        });
+    }
+
     pub fn search_query_text(&self, cx: &App) -> String {
         self.query_editor.read(cx).text(cx)
     }
@@ -1223,8 +1691,27 @@ impl ProjectSearchView {
             .count()
             > 1;
 
-        let query = if self.search_options.contains(SearchOptions::REGEX) {
-            match SearchQuery::regex(
+        let query = if self.search_options.contains(SearchOptions::STRUCTURAL) {
+            SearchQuery::structural(
+                text,
+                self.search_options.contains(SearchOptions::INCLUDE_IGNORED),
+                included_files,
+                excluded_files,
+                match_full_paths,
+                open_buffers,
+            )
+        } else if self.search_options.contains(SearchOptions::FUZZY) {
+            SearchQuery::fuzzy(
+                text,
+                self.search_options.contains(SearchOptions::CASE_SENSITIVE),
+                self.search_options.contains(SearchOptions::INCLUDE_IGNORED),
+                included_files,
+                excluded_files,
+                match_full_paths,
+                open_buffers,
+            )
+        } else if self.search_options.contains(SearchOptions::REGEX) {
+            SearchQuery::regex(
                 text,
                 self.search_options.contains(SearchOptions::WHOLE_WORD),
                 self.search_options.contains(SearchOptions::CASE_SENSITIVE),
@@ -1235,28 +1722,9 @@ impl ProjectSearchView {
                 excluded_files,
                 match_full_paths,
                 open_buffers,
-            ) {
-                Ok(query) => {
-                    let should_unmark_error = self.panels_with_errors.remove(&InputPanel::Query);
-                    if should_unmark_error.is_some() {
-                        cx.notify();
-                    }
-
-                    Some(query)
-                }
-                Err(e) => {
-                    let should_mark_error = self
-                        .panels_with_errors
-                        .insert(InputPanel::Query, e.to_string());
-                    if should_mark_error.is_none() {
-                        cx.notify();
-                    }
-
-                    None
-                }
-            }
+            )
         } else {
-            match SearchQuery::text(
+            SearchQuery::text(
                 text,
                 self.search_options.contains(SearchOptions::WHOLE_WORD),
                 self.search_options.contains(SearchOptions::CASE_SENSITIVE),
@@ -1265,25 +1733,26 @@ impl ProjectSearchView {
                 excluded_files,
                 match_full_paths,
                 open_buffers,
-            ) {
-                Ok(query) => {
-                    let should_unmark_error = self.panels_with_errors.remove(&InputPanel::Query);
-                    if should_unmark_error.is_some() {
-                        cx.notify();
-                    }
-
-                    Some(query)
+            )
+        };
+        let query = match query.map(|query| query.with_scope(self.search_scope)) {
+            Ok(query) => {
+                let should_unmark_error = self.panels_with_errors.remove(&InputPanel::Query);
+                if should_unmark_error.is_some() {
+                    cx.notify();
                 }
-                Err(e) => {
-                    let should_mark_error = self
-                        .panels_with_errors
-                        .insert(InputPanel::Query, e.to_string());
-                    if should_mark_error.is_none() {
-                        cx.notify();
-                    }
 
-                    None
+                Some(query)
+            }
+            Err(e) => {
+                let should_mark_error = self
+                    .panels_with_errors
+                    .insert(InputPanel::Query, e.to_string());
+                if should_mark_error.is_none() {
+                    cx.notify();
                 }
+
+                None
             }
         };
         if !self.panels_with_errors.is_empty() {
@@ -1372,8 +1841,8 @@ impl ProjectSearchView {
         let editor = match kind {
             SearchInputKind::Query => &self.query_editor,
             SearchInputKind::Include => &self.included_files_editor,
-
             SearchInputKind::Exclude => &self.excluded_files_editor,
+            SearchInputKind::Replace => &self.replacement_editor,
         };
         editor.update(cx, |included_editor, cx| {
             included_editor.set_text(text, window, cx)
@@ -1403,6 +1872,9 @@ impl ProjectSearchView {
             self.update_match_index(cx);
             let prev_search_id = mem::replace(&mut self.search_id, self.entity.read(cx).search_id);
             let is_new_search = self.search_id != prev_search_id;
+            if is_new_search {
+                self.excluded_matches.clear();
+            }
             self.results_editor.update(cx, |editor, cx| {
                 if is_new_search {
                     let range_to_select = match_ranges
@@ -1424,6 +1896,7 @@ impl ProjectSearchView {
             }
         }
 
+        self.refresh_replace_preview(cx);
         cx.emit(ViewEvent::UpdateTab);
         cx.notify();
     }
@@ -1725,11 +2198,34 @@ impl ProjectSearchBar {
                     this.query_editor.focus_handle(cx)
                 };
                 window.focus(&editor_to_focus);
+                this.refresh_replace_preview(cx);
                 cx.notify();
             });
         }
     }
 
+    fn toggle_replace_preview(
+        &mut self,
+        _: &ToggleReplacePreview,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if let Some(search) = &self.active_project_search {
+            search.update(cx, |this, cx| this.toggle_replace_preview(cx));
+        }
+    }
+
+    fn toggle_match_excluded(
+        &mut self,
+        _: &ToggleMatchExcluded,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if let Some(search) = &self.active_project_search {
+            search.update(cx, |this, cx| this.toggle_match_excluded(cx));
+        }
+    }
+
     fn toggle_filters(&mut self, window: &mut Window, cx: &mut Context<Self>) -> bool {
         if let Some(search_view) = self.active_project_search.as_ref() {
             search_view.update(cx, |search_view, cx| {
@@ -1780,6 +2276,49 @@ impl ProjectSearchBar {
         true
     }
 
+    fn toggle_search_scope(
+        &mut self,
+        scope: SearchScope,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> bool {
+        if self.active_project_search.is_none() {
+            return false;
+        }
+
+        cx.spawn_in(window, async move |this, cx| {
+            let task = this.update_in(cx, |this, window, cx| {
+                let search_view = this.active_project_search.as_ref()?;
+                search_view.update(cx, |search_view, cx| {
+                    search_view.toggle_search_scope(scope);
+                    search_view
+                        .entity
+                        .read(cx)
+                        .active_query
+                        .is_some()
+                        .then(|| search_view.prompt_to_save_if_dirty_then_search(window, cx))
+                })
+            })?;
+            if let Some(task) = task {
+                task.await?;
+            }
+            this.update(cx, |_, cx| {
+                cx.notify();
+            })?;
+            anyhow::Ok(())
+        })
+        .detach();
+        true
+    }
+
+    fn search_scope(&self, cx: &App) -> SearchScope {
+        if let Some(search_view) = self.active_project_search.as_ref() {
+            search_view.read(cx).search_scope
+        } else {
+            SearchScope::All
+        }
+    }
+
     fn is_opened_only_enabled(&self, cx: &App) -> bool {
         if let Some(search_view) = self.active_project_search.as_ref() {
             search_view.read(cx).included_opened_only
@@ -1815,6 +2354,10 @@ impl ProjectSearchBar {
                         search_view.excluded_files_editor.clone(),
                         SearchInputKind::Exclude,
                     ),
+                    (
+                        search_view.replacement_editor.clone(),
+                        SearchInputKind::Replace,
+                    ),
                 ] {
                     if editor.focus_handle(cx).is_focused(window) {
                         let new_query = search_view.entity.update(cx, |model, cx| {
@@ -1857,6 +2400,10 @@ impl ProjectSearchBar {
                         search_view.excluded_files_editor.clone(),
                         SearchInputKind::Exclude,
                     ),
+                    (
+                        search_view.replacement_editor.clone(),
+                        SearchInputKind::Replace,
+                    ),
                 ] {
                     if editor.focus_handle(cx).is_focused(window) {
                         if editor.read(cx).text(cx).is_empty()
@@ -1925,6 +2472,13 @@ impl Render for ProjectSearchBar {
         let search = search.read(cx);
         let focus_handle = search.focus_handle(cx);
 
+        let has_semantic_index = SemanticIndex::global(cx).is_some_and(|semantic_index| {
+            semantic_index
+                .read(cx)
+                .project_index(&search.entity.read(cx).project)
+                .is_some()
+        });
+
         let container_width = window.viewport_size().width;
         let input_width = SearchInputWidth::calc_width(container_width);
 
@@ -1992,7 +2546,24 @@ impl Render for ProjectSearchBar {
                         search.search_options,
                         SearchSource::Project(cx),
                         focus_handle.clone(),
-                    )),
+                    ))
+                    .child(SearchOption::Structural.as_button(
+                        search.search_options,
+                        SearchSource::Project(cx),
+                        focus_handle.clone(),
+                    ))
+                    .child(SearchOption::Fuzzy.as_button(
+                        search.search_options,
+                        SearchSource::Project(cx),
+                        focus_handle.clone(),
+                    ))
+                    .when(has_semantic_index, |this| {
+                        this.child(SearchOption::Semantic.as_button(
+                            search.search_options,
+                            SearchSource::Project(cx),
+                            focus_handle.clone(),
+                        ))
+                    }),
             );
 
         let query_focus = search.query_editor.focus_handle(cx);
@@ -2099,9 +2670,34 @@ impl Render for ProjectSearchBar {
 
             let focus_handle = search.replacement_editor.read(cx).focus_handle(cx);
 
+            let active_match_excluded = search
+                .active_match_index
+                .is_some_and(|ix| search.excluded_matches.contains(&ix));
             let replace_actions = h_flex()
                 .min_w_64()
                 .gap_1()
+                .child(render_action_button(
+                    "project-search-replace-button",
+                    IconName::Eye,
+                    search
+                        .replace_preview_enabled
+                        .then_some(ActionButtonState::Toggled),
+                    "Preview Replacements",
+                    &ToggleReplacePreview,
+                    focus_handle.clone(),
+                ))
+                .child(render_action_button(
+                    "project-search-replace-button",
+                    IconName::Dash,
+                    if search.active_match_index.is_none() {
+                        Some(ActionButtonState::Disabled)
+                    } else {
+                        active_match_excluded.then_some(ActionButtonState::Toggled)
+                    },
+                    "Exclude Match From Replacements",
+                    &ToggleMatchExcluded,
+                    focus_handle.clone(),
+                ))
                 .child(render_action_button(
                     "project-search-replace-button",
                     IconName::ReplaceNext,
@@ -2155,6 +2751,24 @@ impl Render for ProjectSearchBar {
                             this.toggle_opened_only(window, cx);
                         })),
                 )
+                .child(
+                    IconButton::new("project-search-git-changed-only", IconName::GitBranch)
+                        .shape(IconButtonShape::Square)
+                        .toggle_state(self.search_scope(cx) == SearchScope::GitChanged)
+                        .tooltip(Tooltip::text("Only Search Files Changed Since HEAD"))
+                        .on_click(cx.listener(|this, _, window, cx| {
+                            this.toggle_search_scope(SearchScope::GitChanged, window, cx);
+                        })),
+                )
+                .child(
+                    IconButton::new("project-search-with-errors-only", IconName::XCircle)
+                        .shape(IconButtonShape::Square)
+                        .toggle_state(self.search_scope(cx) == SearchScope::WithErrors)
+                        .tooltip(Tooltip::text("Only Search Files With Errors"))
+                        .on_click(cx.listener(|this, _, window, cx| {
+                            this.toggle_search_scope(SearchScope::WithErrors, window, cx);
+                        })),
+                )
                 .child(SearchOption::IncludeIgnored.as_button(
                     search.search_options,
                     SearchSource::Project(cx),
@@ -2500,6 +3114,75 @@ pub mod tests {
             .unwrap();
     }
 
+    #[gpui::test]
+    async fn test_replace_preview(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            path!("/dir"),
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), [path!("/dir").as_ref()], cx).await;
+        let window = cx.add_window(|window, cx| Workspace::test_new(project.clone(), window, cx));
+        let workspace = window.root(cx).unwrap();
+        let search = cx.new(|cx| ProjectSearch::new(project.clone(), cx));
+        let search_view = cx.add_window(|window, cx| {
+            ProjectSearchView::new(workspace.downgrade(), search.clone(), window, cx, None)
+        });
+
+        perform_search(search_view, "ONE", cx);
+        search_view
+            .update(cx, |search_view, window, cx| {
+                search_view.replace_enabled = true;
+                search_view
+                    .replacement_editor
+                    .update(cx, |editor, cx| editor.set_text("UNO", window, cx));
+                search_view.toggle_replace_preview(cx);
+                assert_eq!(
+                    search_view
+                        .results_editor
+                        .update(cx, |editor, cx| editor.display_text(cx)),
+                    "\n\nconst ONEUNO: usize = 1;\n\n\nconst TWO: usize = one::ONEUNO + one::ONEUNO;"
+                );
+
+                search_view.active_match_index = Some(1);
+                search_view.toggle_match_excluded(cx);
+                assert_eq!(
+                    search_view
+                        .results_editor
+                        .update(cx, |editor, cx| editor.display_text(cx)),
+                    "\n\nconst ONEUNO: usize = 1;\n\n\nconst TWO: usize = one::ONE + one::ONEUNO;"
+                );
+
+                search_view.replace_all(&ReplaceAll, window, cx);
+                assert_eq!(
+                    search_view
+                        .results_editor
+                        .update(cx, |editor, cx| editor.display_text(cx)),
+                    "\n\nconst UNO: usize = 1;\n\n\nconst TWO: usize = one::ONE + one::UNO;"
+                );
+                assert!(!search_view.replace_preview_enabled);
+                assert!(search_view.excluded_matches.is_empty());
+
+                // All replacements are undone at once.
+                search_view.results_editor.update(cx, |editor, cx| {
+                    editor.undo(&editor::actions::Undo, window, cx)
+                });
+                assert_eq!(
+                    search_view
+                        .results_editor
+                        .update(cx, |editor, cx| editor.display_text(cx)),
+                    "\n\nconst ONE: usize = 1;\n\n\nconst TWO: usize = one::ONE + one::ONE;"
+                );
+            })
+            .unwrap();
+    }
+
     #[gpui::test]
     async fn test_deploy_project_search_focus(cx: &mut TestAppContext) {
         init_test(cx);
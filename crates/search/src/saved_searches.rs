@@ -0,0 +1,301 @@
+use fuzzy::{StringMatch, StringMatchCandidate, match_strings};
+use gpui::{
+    App, Context, DismissEvent, Entity, EventEmitter, FocusHandle, Focusable, ParentElement,
+    Render, Styled, Task, WeakEntity, Window,
+};
+use picker::{Picker, PickerDelegate};
+use std::sync::Arc;
+use ui::{HighlightedLabel, ListItem, ListItemSpacing, prelude::*};
+use util::ResultExt as _;
+use workspace::{ModalView, Workspace, WorkspaceId};
+
+use crate::{
+    ProjectSearchView,
+    persistence::{SEARCH_DB, SavedSearch},
+};
+
+/// A picker over the project searches saved in the workspace.
+///
+/// Confirming a saved search runs it, confirming it with the secondary modifier deletes it.
+/// When the picker's query names no saved search, the active project search can be saved
+/// under that name.
+pub struct SavedSearches {
+    picker: Entity<Picker<SavedSearchesDelegate>>,
+}
+
+impl SavedSearches {
+    pub(crate) fn toggle(
+        workspace: &mut Workspace,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) {
+        let Some(workspace_id) = workspace.database_id() else {
+            return;
+        };
+        let saved_searches = SEARCH_DB
+            .saved_searches(workspace_id)
+            .log_err()
+            .unwrap_or_default();
+        let active_search = ProjectSearchView::active_saved_search(workspace, None, cx);
+        let weak_workspace = cx.entity().downgrade();
+        workspace.toggle_modal(window, cx, move |window, cx| {
+            let delegate = SavedSearchesDelegate::new(
+                cx.entity().downgrade(),
+                weak_workspace,
+                workspace_id,
+                saved_searches,
+                active_search,
+            );
+            let picker = cx.new(|cx| Picker::uniform_list(delegate, window, cx));
+            Self { picker }
+        });
+    }
+}
+
+impl Render for SavedSearches {
+    fn render(&mut self, _window: &mut Window, _cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .key_context("SavedSearches")
+            .w(rems(34.))
+            .child(self.picker.clone())
+    }
+}
+
+impl Focusable for SavedSearches {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl EventEmitter<DismissEvent> for SavedSearches {}
+impl ModalView for SavedSearches {}
+
+pub struct SavedSearchesDelegate {
+    saved_searches_modal: WeakEntity<SavedSearches>,
+    workspace: WeakEntity<Workspace>,
+    workspace_id: WorkspaceId,
+    saved_searches: Vec<SavedSearch>,
+    /// The active project search, saved under the picker's query when confirmed.
+    active_search: Option<SavedSearch>,
+    /// The name to save the active project search under, shown before the matches.
+    new_name: Option<String>,
+    matches: Vec<StringMatch>,
+    selected_index: usize,
+}
+
+impl SavedSearchesDelegate {
+    fn new(
+        saved_searches_modal: WeakEntity<SavedSearches>,
+        workspace: WeakEntity<Workspace>,
+        workspace_id: WorkspaceId,
+        saved_searches: Vec<SavedSearch>,
+        active_search: Option<SavedSearch>,
+    ) -> Self {
+        Self {
+            saved_searches_modal,
+            workspace,
+            workspace_id,
+            saved_searches,
+            active_search,
+            new_name: None,
+            matches: Vec::new(),
+            selected_index: 0,
+        }
+    }
+
+    fn candidates(&self) -> Vec<StringMatchCandidate> {
+        self.saved_searches
+            .iter()
+            .enumerate()
+            .map(|(ix, saved_search)| StringMatchCandidate::new(ix, &saved_search.name))
+            .collect()
+    }
+
+    fn selected_match(&self) -> Option<&StringMatch> {
+        let ix = self
+            .selected_index
+            .checked_sub(self.new_name.is_some() as usize)?;
+        self.matches.get(ix)
+    }
+}
+
+impl PickerDelegate for SavedSearchesDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _window: &mut Window, _cx: &mut App) -> Arc<str> {
+        if self.active_search.is_some() {
+            "Run a saved search, or name the current one…".into()
+        } else {
+            "Run a saved search…".into()
+        }
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len() + self.new_name.is_some() as usize
+    }
+
+    fn confirm(&mut self, secondary: bool, window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        if let Some(name) = self.new_name.clone().filter(|_| self.selected_index == 0) {
+            if let Some(active_search) = self.active_search.clone() {
+                self.workspace
+                    .update(cx, |workspace, cx| {
+                        ProjectSearchView::write_saved_search(
+                            workspace,
+                            SavedSearch {
+                                name,
+                                ..active_search
+                            },
+                            cx,
+                        )
+                    })
+                    .log_err();
+            }
+            self.dismissed(window, cx);
+            return;
+        }
+
+        let Some(candidate_id) = self.selected_match().map(|mat| mat.candidate_id) else {
+            return;
+        };
+        if secondary {
+            let saved_search = self.saved_searches.remove(candidate_id);
+            cx.background_spawn(
+                SEARCH_DB.delete_saved_search(self.workspace_id, saved_search.name),
+            )
+            .detach_and_log_err(cx);
+            self.matches.retain(|mat| mat.candidate_id != candidate_id);
+            for mat in &mut self.matches {
+                if mat.candidate_id > candidate_id {
+                    mat.candidate_id -= 1;
+                }
+            }
+            self.selected_index = self
+                .selected_index
+                .min(self.match_count().saturating_sub(1));
+            cx.notify();
+            return;
+        }
+
+        let saved_search = self.saved_searches[candidate_id].clone();
+        self.workspace
+            .update(cx, |workspace, cx| {
+                ProjectSearchView::run_saved_search(workspace, &saved_search, window, cx)
+            })
+            .log_err();
+        self.dismissed(window, cx);
+    }
+
+    fn dismissed(&mut self, _: &mut Window, cx: &mut Context<Picker<Self>>) {
+        self.saved_searches_modal
+            .update(cx, |_, cx| cx.emit(DismissEvent))
+            .log_err();
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(
+        &mut self,
+        ix: usize,
+        _window: &mut Window,
+        _: &mut Context<Picker<Self>>,
+    ) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(
+        &mut self,
+        query: String,
+        window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Task<()> {
+        let background = cx.background_executor().clone();
+        let candidates = self.candidates();
+        let new_name = (self.active_search.is_some()
+            && !query.is_empty()
+            && !self
+                .saved_searches
+                .iter()
+                .any(|saved_search| saved_search.name == query))
+        .then(|| query.clone());
+        cx.spawn_in(window, async move |this, cx| {
+            let matches = if query.is_empty() {
+                candidates
+                    .into_iter()
+                    .map(|candidate| StringMatch {
+                        candidate_id: candidate.id,
+                        string: candidate.string,
+                        positions: Vec::new(),
+                        score: 0.0,
+                    })
+                    .collect()
+            } else {
+                match_strings(
+                    &candidates,
+                    &query,
+                    false,
+                    true,
+                    100,
+                    &Default::default(),
+                    background,
+                )
+                .await
+            };
+
+            this.update(cx, |this, cx| {
+                let delegate = &mut this.delegate;
+                delegate.matches = matches;
+                delegate.new_name = new_name;
+                delegate.selected_index = delegate
+                    .selected_index
+                    .min(delegate.match_count().saturating_sub(1));
+                cx.notify();
+            })
+            .log_err();
+        })
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _: &mut Window,
+        _: &mut Context<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        if let Some(name) = self.new_name.as_ref().filter(|_| ix == 0) {
+            return Some(
+                ListItem::new(ix)
+                    .inset(true)
+                    .spacing(ListItemSpacing::Sparse)
+                    .toggle_state(selected)
+                    .start_slot(Icon::new(IconName::Plus).color(Color::Muted))
+                    .child(Label::new(format!("Save current search as “{name}”"))),
+            );
+        }
+
+        let mat = self.matches.get(ix - self.new_name.is_some() as usize)?;
+        let saved_search = self.saved_searches.get(mat.candidate_id)?;
+        Some(
+            ListItem::new(ix)
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .toggle_state(selected)
+                .start_slot(Icon::new(IconName::MagnifyingGlass).color(Color::Muted))
+                .child(
+                    h_flex()
+                        .gap_2()
+                        .child(HighlightedLabel::new(
+                            mat.string.clone(),
+                            mat.positions.clone(),
+                        ))
+                        .child(
+                            Label::new(saved_search.query.clone())
+                                .size(LabelSize::Small)
+                                .color(Color::Muted)
+                                .truncate(),
+                        ),
+                ),
+        )
+    }
+}
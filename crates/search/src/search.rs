@@ -14,7 +14,9 @@ pub use search_status_button::SEARCH_ICON;
 use crate::project_search::ProjectSearchBar;
 
 pub mod buffer_search;
+mod persistence;
 pub mod project_search;
+mod saved_searches;
 pub(crate) mod search_bar;
 pub mod search_status_button;
 
@@ -37,8 +39,19 @@ actions!(
         ToggleIncludeIgnored,
         /// Toggles regular expression mode.
         ToggleRegex,
+        /// Toggles structural search mode, matching code templates or tree-sitter queries.
+        ToggleStructural,
+        /// Toggles fuzzy search mode, matching lines containing the query's characters in order.
+        ToggleFuzzy,
+        /// Toggles semantic search mode, finding code related to the query's meaning in the project's
+        /// semantic index.
+        ToggleSemantic,
         /// Toggles the replace interface.
         ToggleReplace,
+        /// Toggles previewing the replacement of every match before replacing them all.
+        ToggleReplacePreview,
+        /// Excludes the active match from the matches replaced all at once, or includes it back.
+        ToggleMatchExcluded,
         /// Toggles searching within selection only.
         ToggleSelection,
         /// Selects the next search match.
@@ -62,7 +75,7 @@ actions!(
 
 bitflags! {
     #[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
-    pub struct SearchOptions: u8 {
+    pub struct SearchOptions: u16 {
         const NONE = 0;
         const WHOLE_WORD = 1 << SearchOption::WholeWord as u8;
         const CASE_SENSITIVE = 1 << SearchOption::CaseSensitive as u8;
@@ -71,6 +84,9 @@ bitflags! {
         const ONE_MATCH_PER_LINE = 1 << SearchOption::OneMatchPerLine as u8;
         /// If set, reverse direction when finding the active match
         const BACKWARDS = 1 << SearchOption::Backwards as u8;
+        const STRUCTURAL = 1 << SearchOption::Structural as u8;
+        const FUZZY = 1 << SearchOption::Fuzzy as u8;
+        const SEMANTIC = 1 << SearchOption::Semantic as u8;
     }
 }
 
@@ -83,6 +99,9 @@ pub enum SearchOption {
     Regex,
     OneMatchPerLine,
     Backwards,
+    Structural,
+    Fuzzy,
+    Semantic,
 }
 
 pub(crate) enum SearchSource<'a, 'b> {
@@ -103,6 +122,9 @@ impl SearchOption {
             SearchOption::Regex => "Use Regular Expressions",
             SearchOption::OneMatchPerLine => "One Match Per Line",
             SearchOption::Backwards => "Search Backwards",
+            SearchOption::Structural => "Use Structural Search Patterns",
+            SearchOption::Fuzzy => "Use Fuzzy Matching",
+            SearchOption::Semantic => "Search by Meaning",
         }
     }
 
@@ -112,6 +134,9 @@ impl SearchOption {
             SearchOption::CaseSensitive => ui::IconName::CaseSensitive,
             SearchOption::IncludeIgnored => ui::IconName::Sliders,
             SearchOption::Regex => ui::IconName::Regex,
+            SearchOption::Structural => ui::IconName::Code,
+            SearchOption::Fuzzy => ui::IconName::Sparkle,
+            SearchOption::Semantic => ui::IconName::Ai,
             _ => panic!("{self:?} is not a named SearchOption"),
         }
     }
@@ -122,6 +147,9 @@ impl SearchOption {
             SearchOption::CaseSensitive => &ToggleCaseSensitive,
             SearchOption::IncludeIgnored => &ToggleIncludeIgnored,
             SearchOption::Regex => &ToggleRegex,
+            SearchOption::Structural => &ToggleStructural,
+            SearchOption::Fuzzy => &ToggleFuzzy,
+            SearchOption::Semantic => &ToggleSemantic,
             _ => panic!("{self:?} is not a toggle action"),
         }
     }
@@ -175,9 +203,24 @@ impl SearchOptions {
         options.set(SearchOptions::CASE_SENSITIVE, query.case_sensitive());
         options.set(SearchOptions::INCLUDE_IGNORED, query.include_ignored());
         options.set(SearchOptions::REGEX, query.is_regex());
+        options.set(SearchOptions::STRUCTURAL, query.is_structural());
+        options.set(SearchOptions::FUZZY, query.is_fuzzy());
         options
     }
 
+    /// Toggles `option`, turning off the other ways of interpreting the query (regex,
+    /// structural, fuzzy or semantic) when it is one of them and gets turned on.
+    pub fn toggle_exclusive(&mut self, option: SearchOptions) {
+        self.toggle(option);
+        let query_modes = SearchOptions::REGEX
+            | SearchOptions::STRUCTURAL
+            | SearchOptions::FUZZY
+            | SearchOptions::SEMANTIC;
+        if query_modes.contains(option) && self.contains(option) {
+            self.remove(query_modes.difference(option));
+        }
+    }
+
     pub fn from_settings(settings: &SearchSettings) -> SearchOptions {
         let mut options = SearchOptions::NONE;
         options.set(SearchOptions::WHOLE_WORD, settings.whole_word);
@@ -0,0 +1,140 @@
+use collections::HashMap;
+use language::{BufferSnapshot, Point, ToPoint as _};
+use std::ops::Range;
+
+/// The most bytes of code in a chunk, keeping each chunk focused on a few related items and
+/// within the context of embedding models.
+const MAX_CHUNK_SIZE: usize = 1500;
+
+/// Splits a file into chunks of whole lines, preferring to split where items of the language's
+/// outline, such as functions and types, start.
+pub fn chunk_buffer(snapshot: &BufferSnapshot) -> Vec<Range<usize>> {
+    let boundaries = snapshot
+        .outline_items_containing(0..snapshot.len(), false, None)
+        .into_iter()
+        .map(|item| {
+            let row = item.range.start.to_point(snapshot).row;
+            (snapshot.point_to_offset(Point::new(row, 0)), item.depth)
+        })
+        .collect::<Vec<_>>();
+    chunk_text(&snapshot.text(), &boundaries)
+}
+
+/// Splits the text into chunks of whole lines, splitting at the line starting the shallowest of
+/// the given `(offset, depth)` boundaries when a chunk gets too large.
+fn chunk_text(text: &str, boundaries: &[(usize, usize)]) -> Vec<Range<usize>> {
+    let mut depths = HashMap::default();
+    for (offset, depth) in boundaries {
+        depths
+            .entry(*offset)
+            .and_modify(|existing: &mut usize| *existing = (*existing).min(*depth))
+            .or_insert(*depth);
+    }
+
+    let mut chunks = Vec::new();
+    let mut push_chunk = |range: Range<usize>| {
+        if !text[range.clone()].trim().is_empty() {
+            chunks.push(range);
+        }
+    };
+    let mut start = 0;
+    // The `(depth, offset)` boundaries the current chunk could be split at.
+    let mut splits: Vec<(usize, usize)> = Vec::new();
+    let mut line_start = 0;
+    while line_start < text.len() {
+        let line_end = text[line_start..]
+            .find('\n')
+            .map_or(text.len(), |ix| line_start + ix + 1);
+
+        if line_start > start
+            && let Some(depth) = depths.get(&line_start)
+        {
+            splits.push((*depth, line_start));
+        }
+
+        // Splitting at an earlier boundary can leave the rest of the chunk too large, in which
+        // case it's split again at the boundaries after that one.
+        while line_end - start > MAX_CHUNK_SIZE && line_start > start {
+            let split = splits
+                .iter()
+                .rev()
+                .min_by_key(|(depth, _)| *depth)
+                .map_or(line_start, |(_, offset)| *offset);
+            push_chunk(start..split);
+            start = split;
+            splits.retain(|(_, offset)| *offset > split);
+        }
+
+        line_start = line_end;
+    }
+    push_chunk(start..text.len());
+
+    chunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chunk_text() {
+        let function =
+            |name: &str| format!("fn {name}() {{\n{}}}\n", "    let x = 1;\n".repeat(40));
+        let text = [function("a"), function("b"), function("c")].concat();
+        let function_len = function("a").len();
+
+        // Chunks end where the last function that fits in them starts.
+        let boundaries = [(0, 0), (function_len, 0), (2 * function_len, 0)];
+        assert_eq!(
+            chunk_text(&text, &boundaries),
+            vec![0..2 * function_len, 2 * function_len..text.len()]
+        );
+
+        // Without any outline, chunks are split on the line that doesn't fit.
+        let chunks = chunk_text(&text, &[]);
+        assert_eq!(chunks.first().map(|chunk| chunk.start), Some(0));
+        assert_eq!(chunks.last().map(|chunk| chunk.end), Some(text.len()));
+        for chunk in &chunks {
+            assert!(chunk.len() <= MAX_CHUNK_SIZE);
+            assert!(text[..chunk.end].ends_with('\n'));
+        }
+
+        assert_eq!(chunk_text("\n\n  \n", &[]), Vec::<Range<usize>>::new());
+    }
+
+    #[test]
+    fn test_chunk_text_after_splitting_at_an_earlier_boundary() {
+        let line = "    let x = 1;\n";
+        let method = |name: &str, lines: usize| {
+            format!("    fn {name}() {{\n{}    }}\n", line.repeat(lines))
+        };
+        let function = format!("fn a() {{\n{}}}\n", line.repeat(10));
+        let impl_start = function.len();
+        let first_method_start = impl_start + "impl S {\n".len();
+        let second_method_start = first_method_start + method("b", 45).len();
+        let text = [
+            function,
+            "impl S {\n".into(),
+            method("b", 45),
+            method("c", 150),
+            "}\n".into(),
+        ]
+        .concat();
+        let boundaries = [
+            (0, 0),
+            (impl_start, 0),
+            (first_method_start, 1),
+            (second_method_start, 1),
+        ];
+
+        // The first chunk ends where the impl starts, as it's shallower than its methods. The
+        // methods' boundaries are still used to split the rest of the impl.
+        let chunks = chunk_text(&text, &boundaries);
+        assert_eq!(chunks[0], 0..impl_start);
+        assert_eq!(chunks[1], impl_start..second_method_start);
+        assert_eq!(chunks.last().map(|chunk| chunk.end), Some(text.len()));
+        for chunk in &chunks {
+            assert!(chunk.len() <= MAX_CHUNK_SIZE);
+        }
+    }
+}
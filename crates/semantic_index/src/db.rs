@@ -0,0 +1,308 @@
+use crate::Embedding;
+use anyhow::Result;
+use collections::HashMap;
+use db::{
+    sqlez::{
+        connection::Connection, domain::Domain, statement::Statement,
+        thread_safe_connection::ThreadSafeConnection,
+    },
+    sqlez_macros::sql,
+};
+use fs::MTime;
+use std::{
+    cmp::{Ordering, Reverse},
+    collections::BinaryHeap,
+    ops::Range,
+    sync::Arc,
+};
+use util::rel_path::RelPath;
+
+/// A chunk of a file and its embedding.
+pub struct EmbeddedChunk {
+    pub range: Range<usize>,
+    pub embedding: Embedding,
+}
+
+pub struct DbSearchResult {
+    pub worktree: Arc<str>,
+    pub path: Arc<RelPath>,
+    pub range: Range<usize>,
+    pub score: f32,
+}
+
+/// Stores the embeddings of the chunks of each indexed file, keyed by the absolute path of the
+/// worktree containing it and the model that computed them.
+pub struct SemanticDb(ThreadSafeConnection);
+
+impl Domain for SemanticDb {
+    const NAME: &str = stringify!(SemanticDb);
+
+    const MIGRATIONS: &[&str] = &[sql!(
+        CREATE TABLE semantic_index_files(
+            worktree TEXT NOT NULL,
+            model TEXT NOT NULL,
+            path TEXT NOT NULL,
+            mtime_seconds INTEGER,
+            mtime_nanos INTEGER,
+            PRIMARY KEY(worktree, model, path)
+        ) STRICT;
+
+        CREATE TABLE semantic_index_chunks(
+            worktree TEXT NOT NULL,
+            model TEXT NOT NULL,
+            path TEXT NOT NULL,
+            start_offset INTEGER NOT NULL,
+            end_offset INTEGER NOT NULL,
+            embedding BLOB NOT NULL
+        ) STRICT;
+
+        CREATE INDEX semantic_index_chunks_by_file
+        ON semantic_index_chunks(worktree, model, path);
+    )];
+}
+
+db::static_connection!(SEMANTIC_DB, SemanticDb, []);
+
+impl SemanticDb {
+    /// Returns the modification time each indexed file of the worktree had when it was indexed.
+    pub async fn file_mtimes(
+        &self,
+        worktree: Arc<str>,
+        model: Arc<str>,
+    ) -> Result<HashMap<Arc<RelPath>, Option<MTime>>> {
+        self.write(move |connection| {
+            let mut select = connection
+                .select_bound::<(Arc<str>, Arc<str>), (String, Option<u64>, Option<u32>)>(sql!(
+                    SELECT path, mtime_seconds, mtime_nanos FROM semantic_index_files
+                    WHERE worktree = ?1 AND model = ?2
+                ))?;
+
+            let mut mtimes = HashMap::default();
+            for (path, seconds, nanos) in select((worktree, model))? {
+                let mtime = seconds
+                    .zip(nanos)
+                    .map(|(seconds, nanos)| MTime::from_seconds_and_nanos(seconds, nanos));
+                mtimes.insert(RelPath::unix(&path)?.into_arc(), mtime);
+            }
+            Ok(mtimes)
+        })
+        .await
+    }
+
+    /// Replaces the chunks of the file with the given ones.
+    pub async fn save_file(
+        &self,
+        worktree: Arc<str>,
+        model: Arc<str>,
+        path: Arc<RelPath>,
+        mtime: Option<MTime>,
+        chunks: Vec<EmbeddedChunk>,
+    ) -> Result<()> {
+        let path = path.as_unix_str().to_string();
+        let mtime = mtime.and_then(|mtime| mtime.to_seconds_and_nanos_for_persistence());
+
+        self.write(move |connection| {
+            connection.with_savepoint("save_file", || {
+                Self::delete_file_rows(connection, &worktree, &model, &path)?;
+
+                connection.exec_bound::<(Arc<str>, Arc<str>, &str, Option<u64>, Option<u32>)>(
+                    sql!(
+                        INSERT INTO semantic_index_files
+                            (worktree, model, path, mtime_seconds, mtime_nanos)
+                        VALUES (?1, ?2, ?3, ?4, ?5)
+                    ),
+                )?((
+                    worktree.clone(),
+                    model.clone(),
+                    &path,
+                    mtime.map(|(seconds, _)| seconds),
+                    mtime.map(|(_, nanos)| nanos),
+                ))?;
+
+                let mut insert_chunk =
+                    connection.exec_bound::<(Arc<str>, Arc<str>, &str, usize, usize, Vec<u8>)>(
+                        sql!(
+                            INSERT INTO semantic_index_chunks
+                                (worktree, model, path, start_offset, end_offset, embedding)
+                            VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+                        ),
+                    )?;
+                for chunk in chunks {
+                    insert_chunk((
+                        worktree.clone(),
+                        model.clone(),
+                        &path,
+                        chunk.range.start,
+                        chunk.range.end,
+                        chunk.embedding.to_bytes(),
+                    ))?;
+                }
+                Ok(())
+            })
+        })
+        .await
+    }
+
+    pub async fn delete_file(
+        &self,
+        worktree: Arc<str>,
+        model: Arc<str>,
+        path: Arc<RelPath>,
+    ) -> Result<()> {
+        self.write(move |connection| {
+            Self::delete_file_rows(connection, &worktree, &model, path.as_unix_str())
+        })
+        .await
+    }
+
+    fn delete_file_rows(
+        connection: &Connection,
+        worktree: &Arc<str>,
+        model: &Arc<str>,
+        path: &str,
+    ) -> Result<()> {
+        connection.exec_bound::<(Arc<str>, Arc<str>, &str)>(sql!(
+            DELETE FROM semantic_index_files WHERE worktree = ?1 AND model = ?2 AND path = ?3
+        ))?((worktree.clone(), model.clone(), path))?;
+        connection.exec_bound::<(Arc<str>, Arc<str>, &str)>(sql!(
+            DELETE FROM semantic_index_chunks WHERE worktree = ?1 AND model = ?2 AND path = ?3
+        ))?((worktree.clone(), model.clone(), path))?;
+        Ok(())
+    }
+
+    /// Returns the chunks of the worktrees most similar to the query, most similar first.
+    ///
+    /// This reads through the calling thread's connection rather than waiting on writes, so it
+    /// should be called on a background thread.
+    pub fn search(
+        &self,
+        worktrees: &[Arc<str>],
+        model: &str,
+        query: &Embedding,
+        limit: usize,
+    ) -> Result<Vec<DbSearchResult>> {
+        if limit == 0 {
+            return Ok(Vec::new());
+        }
+
+        let mut statement = Statement::prepare(
+            self,
+            sql!(
+                SELECT path, start_offset, end_offset, embedding
+                FROM semantic_index_chunks
+                WHERE worktree = ?1 AND model = ?2
+            ),
+        )?;
+
+        // The most similar chunks so far, with the least similar on top so that it's the one
+        // dropped when a more similar chunk is found.
+        let mut results = BinaryHeap::<Reverse<ScoredChunk>>::with_capacity(limit + 1);
+        for worktree in worktrees {
+            statement
+                .with_bindings(&(worktree.clone(), model))?
+                .map(|row| {
+                    let score = query.similarity(&Embedding::from_bytes(row.column_blob(3)?));
+                    if results.len() == limit
+                        && results
+                            .peek()
+                            .is_some_and(|Reverse(least)| least.0.score >= score)
+                    {
+                        return Ok(());
+                    }
+
+                    results.push(Reverse(ScoredChunk(DbSearchResult {
+                        worktree: worktree.clone(),
+                        path: RelPath::unix(row.column_text(0)?)?.into_arc(),
+                        range: row.column_int64(1)? as usize..row.column_int64(2)? as usize,
+                        score,
+                    })));
+                    if results.len() > limit {
+                        results.pop();
+                    }
+                    Ok(())
+                })?;
+        }
+
+        Ok(results
+            .into_sorted_vec()
+            .into_iter()
+            .map(|Reverse(ScoredChunk(result))| result)
+            .collect())
+    }
+}
+
+/// Orders search results by their score.
+struct ScoredChunk(DbSearchResult);
+
+impl PartialEq for ScoredChunk {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other).is_eq()
+    }
+}
+
+impl Eq for ScoredChunk {}
+
+impl PartialOrd for ScoredChunk {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for ScoredChunk {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.score.total_cmp(&other.0.score)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[gpui::test]
+    async fn test_search() {
+        let db = SemanticDb::open_test_db("test_search").await;
+        let worktree: Arc<str> = "/root".into();
+        let model: Arc<str> = "model".into();
+        let path = RelPath::unix("src/main.rs").unwrap().into_arc();
+        let chunk = |range: Range<usize>, dimensions: Vec<f32>| EmbeddedChunk {
+            range,
+            embedding: Embedding::new(dimensions),
+        };
+
+        db.save_file(
+            worktree.clone(),
+            model.clone(),
+            path.clone(),
+            None,
+            vec![
+                chunk(0..10, vec![0., 1.]),
+                chunk(10..20, vec![1., 0.]),
+                chunk(20..30, vec![1., 1.]),
+            ],
+        )
+        .await
+        .unwrap();
+        // Embeddings from other models aren't comparable, so they're never returned.
+        db.save_file(
+            worktree.clone(),
+            "other model".into(),
+            path,
+            None,
+            vec![chunk(30..40, vec![1., 0.])],
+        )
+        .await
+        .unwrap();
+
+        let query = Embedding::new(vec![1., 0.]);
+        let ranges = |limit| {
+            db.search(&[worktree.clone()], &model, &query, limit)
+                .unwrap()
+                .into_iter()
+                .map(|result| result.range)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(ranges(2), vec![10..20, 20..30]);
+        assert_eq!(ranges(5), vec![10..20, 20..30, 0..10]);
+        assert_eq!(ranges(0), Vec::new());
+    }
+}
@@ -0,0 +1,121 @@
+use anyhow::Result;
+use futures::{FutureExt as _, future::BoxFuture};
+use http_client::HttpClient;
+use std::sync::Arc;
+
+/// An embedding vector, normalized so that the similarity of two embeddings is their dot product.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Embedding(Vec<f32>);
+
+impl Embedding {
+    pub fn new(mut dimensions: Vec<f32>) -> Self {
+        let norm = dimensions
+            .iter()
+            .map(|dimension| dimension * dimension)
+            .sum::<f32>()
+            .sqrt();
+        if norm > 0. {
+            for dimension in &mut dimensions {
+                *dimension /= norm;
+            }
+        }
+        Self(dimensions)
+    }
+
+    /// Returns the cosine similarity of the two embeddings, from -1 for opposite meanings to 1 for
+    /// the same one.
+    pub fn similarity(&self, other: &Self) -> f32 {
+        self.0
+            .iter()
+            .zip(&other.0)
+            .map(|(left, right)| left * right)
+            .sum()
+    }
+
+    pub(crate) fn to_bytes(&self) -> Vec<u8> {
+        self.0
+            .iter()
+            .flat_map(|dimension| dimension.to_le_bytes())
+            .collect()
+    }
+
+    pub(crate) fn from_bytes(bytes: &[u8]) -> Self {
+        Self(
+            bytes
+                .chunks_exact(4)
+                .map(|dimension| {
+                    f32::from_le_bytes([dimension[0], dimension[1], dimension[2], dimension[3]])
+                })
+                .collect(),
+        )
+    }
+}
+
+pub trait EmbeddingProvider: Send + Sync {
+    /// The model computing the embeddings, which are only comparable with others from it.
+    fn model(&self) -> &str;
+    /// How many texts to embed in a single request.
+    fn batch_size(&self) -> usize;
+    /// Computes an embedding for each of the texts, in the same order.
+    fn embed<'a>(&'a self, texts: &'a [String]) -> BoxFuture<'a, Result<Vec<Embedding>>>;
+}
+
+pub struct OllamaEmbeddingProvider {
+    client: Arc<dyn HttpClient>,
+    api_url: String,
+    model: String,
+}
+
+impl OllamaEmbeddingProvider {
+    pub fn new(client: Arc<dyn HttpClient>, api_url: String, model: String) -> Self {
+        Self {
+            client,
+            api_url,
+            model,
+        }
+    }
+}
+
+impl EmbeddingProvider for OllamaEmbeddingProvider {
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    fn batch_size(&self) -> usize {
+        32
+    }
+
+    fn embed<'a>(&'a self, texts: &'a [String]) -> BoxFuture<'a, Result<Vec<Embedding>>> {
+        let request = ollama::EmbedRequest {
+            model: self.model.clone(),
+            input: texts.to_vec(),
+            keep_alive: Default::default(),
+        };
+        async move {
+            let embeddings =
+                ollama::embed(self.client.as_ref(), &self.api_url, None, request).await?;
+            anyhow::ensure!(
+                embeddings.len() == texts.len(),
+                "Expected {} embeddings from Ollama, got {}",
+                texts.len(),
+                embeddings.len()
+            );
+            Ok(embeddings.into_iter().map(Embedding::new).collect())
+        }
+        .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_similarity() {
+        let embedding = Embedding::new(vec![3., 4.]);
+        assert_eq!(embedding, Embedding::new(vec![0.6, 0.8]));
+        assert!((embedding.similarity(&embedding) - 1.).abs() < f32::EPSILON);
+        assert!((embedding.similarity(&Embedding::new(vec![-3., -4.])) + 1.).abs() < f32::EPSILON);
+        assert_eq!(Embedding::from_bytes(&embedding.to_bytes()), embedding);
+    }
+}
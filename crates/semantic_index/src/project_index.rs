@@ -0,0 +1,153 @@
+use crate::{
+    EmbeddingProvider,
+    db::SEMANTIC_DB,
+    worktree_index::{WorktreeIndex, WorktreeIndexStatus},
+};
+use anyhow::{Context as _, Result};
+use collections::HashMap;
+use fs::Fs;
+use gpui::{App, AppContext as _, Context, Entity, Subscription, Task};
+use language::LanguageRegistry;
+use project::{Project, WorktreeId};
+use std::{ops::Range, sync::Arc};
+use util::rel_path::RelPath;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Status {
+    /// Every file of the project is indexed.
+    Idle,
+    /// The project's worktrees are being scanned for files that changed since they were indexed.
+    Scanning,
+    /// Files are being chunked and embedded.
+    Indexing { remaining_files: usize },
+}
+
+/// A chunk of a file in the project that is similar to a search query.
+#[derive(Clone, Debug)]
+pub struct SearchResult {
+    pub worktree_id: WorktreeId,
+    pub path: Arc<RelPath>,
+    /// The byte range of the chunk, as of when the file was indexed.
+    pub range: Range<usize>,
+    pub score: f32,
+}
+
+pub struct ProjectIndex {
+    embedding_provider: Arc<dyn EmbeddingProvider>,
+    fs: Arc<dyn Fs>,
+    languages: Arc<LanguageRegistry>,
+    worktree_indices: HashMap<WorktreeId, Entity<WorktreeIndex>>,
+    _subscription: Subscription,
+}
+
+impl ProjectIndex {
+    pub(crate) fn new(
+        project: &Entity<Project>,
+        embedding_provider: Arc<dyn EmbeddingProvider>,
+        fs: Arc<dyn Fs>,
+        languages: Arc<LanguageRegistry>,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let subscription = cx.subscribe(project, |this, project, event, cx| match event {
+            project::Event::WorktreeAdded(_) | project::Event::WorktreeRemoved(_) => {
+                this.update_worktree_indices(&project, cx);
+            }
+            project::Event::WorktreeUpdatedEntries(worktree_id, changes) => {
+                if let Some(worktree_index) = this.worktree_indices.get(worktree_id) {
+                    worktree_index.update(cx, |worktree_index, cx| {
+                        worktree_index.paths_changed(changes, cx)
+                    });
+                }
+            }
+            _ => {}
+        });
+
+        let mut this = Self {
+            embedding_provider,
+            fs,
+            languages,
+            worktree_indices: HashMap::default(),
+            _subscription: subscription,
+        };
+        this.update_worktree_indices(project, cx);
+        this
+    }
+
+    pub fn status(&self, cx: &App) -> Status {
+        let mut remaining_files = 0;
+        for worktree_index in self.worktree_indices.values() {
+            match worktree_index.read(cx).status() {
+                WorktreeIndexStatus::Scanning => return Status::Scanning,
+                WorktreeIndexStatus::Indexing { remaining_files: n } => remaining_files += n,
+                WorktreeIndexStatus::Idle => {}
+            }
+        }
+        if remaining_files > 0 {
+            Status::Indexing { remaining_files }
+        } else {
+            Status::Idle
+        }
+    }
+
+    /// Returns the chunks of the project's code most similar in meaning to the query, most
+    /// similar first.
+    pub fn search(&self, query: String, limit: usize, cx: &App) -> Task<Result<Vec<SearchResult>>> {
+        let worktrees = self
+            .worktree_indices
+            .iter()
+            .map(|(worktree_id, worktree_index)| (worktree_index.read(cx).key(), *worktree_id))
+            .collect::<HashMap<_, _>>();
+        let embedding_provider = self.embedding_provider.clone();
+        cx.background_spawn(async move {
+            let query = embedding_provider
+                .embed(&[query])
+                .await?
+                .pop()
+                .context("No embedding was computed for the query")?;
+            let worktree_keys = worktrees.keys().cloned().collect::<Vec<_>>();
+            let results =
+                SEMANTIC_DB.search(&worktree_keys, embedding_provider.model(), &query, limit)?;
+
+            Ok(results
+                .into_iter()
+                .filter_map(|result| {
+                    Some(SearchResult {
+                        worktree_id: *worktrees.get(&result.worktree)?,
+                        path: result.path,
+                        range: result.range,
+                        score: result.score,
+                    })
+                })
+                .collect())
+        })
+    }
+
+    fn update_worktree_indices(&mut self, project: &Entity<Project>, cx: &mut Context<Self>) {
+        let worktrees = project
+            .read(cx)
+            .visible_worktrees(cx)
+            .filter(|worktree| worktree.read(cx).is_local())
+            .map(|worktree| (worktree.read(cx).id(), worktree))
+            .collect::<HashMap<_, _>>();
+
+        self.worktree_indices
+            .retain(|worktree_id, _| worktrees.contains_key(worktree_id));
+        for (worktree_id, worktree) in worktrees {
+            if self.worktree_indices.contains_key(&worktree_id) {
+                continue;
+            }
+            let worktree_index = cx.new(|cx| {
+                WorktreeIndex::new(
+                    worktree,
+                    self.embedding_provider.clone(),
+                    self.fs.clone(),
+                    self.languages.clone(),
+                    cx,
+                )
+            });
+            cx.observe(&worktree_index, |_, _, cx| cx.notify()).detach();
+            self.worktree_indices.insert(worktree_id, worktree_index);
+        }
+        cx.notify();
+    }
+}
@@ -0,0 +1,171 @@
+mod chunking;
+mod db;
+mod embedding;
+mod project_index;
+mod worktree_index;
+
+use collections::HashMap;
+use fs::Fs;
+use gpui::{App, AppContext as _, Context, Entity, EntityId, Global, WeakEntity};
+use http_client::HttpClient;
+use language::LanguageRegistry;
+use project::Project;
+use settings::{Settings, SettingsStore};
+use std::sync::Arc;
+
+pub use embedding::*;
+pub use project_index::{ProjectIndex, SearchResult, Status};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SemanticIndexSettings {
+    /// Whether to index the code of local projects so that it can be searched by meaning.
+    pub enabled: bool,
+    /// The URL of the Ollama server that computes the embeddings.
+    pub api_url: String,
+    /// The Ollama embedding model to use.
+    pub model: String,
+}
+
+impl Settings for SemanticIndexSettings {
+    fn from_settings(content: &settings::SettingsContent, _cx: &mut App) -> Self {
+        let semantic_index = content.semantic_index.clone().unwrap();
+
+        Self {
+            enabled: semantic_index.enabled.unwrap(),
+            api_url: semantic_index.api_url.unwrap(),
+            model: semantic_index.model.unwrap(),
+        }
+    }
+}
+
+pub fn init(
+    fs: Arc<dyn Fs>,
+    http_client: Arc<dyn HttpClient>,
+    languages: Arc<LanguageRegistry>,
+    cx: &mut App,
+) {
+    SemanticIndexSettings::register(cx);
+
+    let semantic_index = cx.new(|cx| SemanticIndex::new(fs, http_client, languages, cx));
+    cx.set_global(GlobalSemanticIndex(semantic_index.clone()));
+
+    cx.observe_new(move |_: &mut Project, _, cx| {
+        let project = cx.weak_entity();
+        let semantic_index = semantic_index.clone();
+        // The project can't be read while it's being created.
+        cx.defer(move |cx| {
+            if let Some(project) = project.upgrade() {
+                semantic_index.update(cx, |semantic_index, cx| {
+                    semantic_index.add_project(project, cx)
+                });
+            }
+        });
+    })
+    .detach();
+}
+
+struct GlobalSemanticIndex(Entity<SemanticIndex>);
+
+impl Global for GlobalSemanticIndex {}
+
+/// Keeps an index of every open local project up to date while the semantic index is enabled.
+pub struct SemanticIndex {
+    fs: Arc<dyn Fs>,
+    http_client: Arc<dyn HttpClient>,
+    languages: Arc<LanguageRegistry>,
+    settings: SemanticIndexSettings,
+    projects: Vec<WeakEntity<Project>>,
+    project_indices: HashMap<EntityId, Entity<ProjectIndex>>,
+}
+
+impl SemanticIndex {
+    pub fn global(cx: &App) -> Option<Entity<Self>> {
+        cx.try_global::<GlobalSemanticIndex>()
+            .map(|semantic_index| semantic_index.0.clone())
+    }
+
+    fn new(
+        fs: Arc<dyn Fs>,
+        http_client: Arc<dyn HttpClient>,
+        languages: Arc<LanguageRegistry>,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        cx.observe_global::<SettingsStore>(|this, cx| {
+            let settings = SemanticIndexSettings::get_global(cx).clone();
+            if settings != this.settings {
+                this.settings = settings;
+                this.project_indices.clear();
+                this.refresh(cx);
+            }
+        })
+        .detach();
+
+        Self {
+            fs,
+            http_client,
+            languages,
+            settings: SemanticIndexSettings::get_global(cx).clone(),
+            projects: Vec::new(),
+            project_indices: HashMap::default(),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.settings.enabled
+    }
+
+    /// Returns the index of the project, or `None` when the semantic index is disabled or the
+    /// project isn't local.
+    pub fn project_index(&self, project: &Entity<Project>) -> Option<Entity<ProjectIndex>> {
+        self.project_indices.get(&project.entity_id()).cloned()
+    }
+
+    fn add_project(&mut self, project: Entity<Project>, cx: &mut Context<Self>) {
+        let project_id = project.entity_id();
+        cx.observe_release(&project, move |this, _, cx| {
+            this.project_indices.remove(&project_id);
+            cx.notify();
+        })
+        .detach();
+        self.projects.push(project.downgrade());
+        self.refresh(cx);
+    }
+
+    fn refresh(&mut self, cx: &mut Context<Self>) {
+        self.projects.retain(|project| project.is_upgradable());
+        if !self.settings.enabled {
+            self.project_indices.clear();
+            cx.notify();
+            return;
+        }
+
+        let embedding_provider: Arc<dyn EmbeddingProvider> =
+            Arc::new(OllamaEmbeddingProvider::new(
+                self.http_client.clone(),
+                self.settings.api_url.clone(),
+                self.settings.model.clone(),
+            ));
+        for project in self.projects.clone() {
+            let Some(project) = project.upgrade() else {
+                continue;
+            };
+            if self.project_indices.contains_key(&project.entity_id())
+                || !project.read(cx).is_local()
+            {
+                continue;
+            }
+
+            let index = cx.new(|cx| {
+                ProjectIndex::new(
+                    &project,
+                    embedding_provider.clone(),
+                    self.fs.clone(),
+                    self.languages.clone(),
+                    cx,
+                )
+            });
+            self.project_indices.insert(project.entity_id(), index);
+        }
+        cx.notify();
+    }
+}
@@ -0,0 +1,247 @@
+use crate::{
+    EmbeddingProvider,
+    chunking::chunk_buffer,
+    db::{EmbeddedChunk, SEMANTIC_DB},
+};
+use anyhow::{Result, anyhow};
+use fs::Fs;
+use futures::{StreamExt as _, channel::mpsc};
+use gpui::{AsyncApp, Context, Entity, Task, WeakEntity};
+use language::{Buffer, LanguageRegistry, Rope};
+use project::{UpdatedEntriesSet, Worktree};
+use std::{collections::BTreeSet, sync::Arc};
+use util::{ResultExt as _, rel_path::RelPath};
+
+/// Files larger than this are mostly generated or data, which isn't worth embedding.
+const MAX_FILE_SIZE: u64 = 512 * 1024;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum WorktreeIndexStatus {
+    Idle,
+    Scanning,
+    Indexing { remaining_files: usize },
+}
+
+/// Keeps the embeddings of a local worktree's files up to date, re-indexing the files whose
+/// modification time differs from when they were last indexed.
+pub(crate) struct WorktreeIndex {
+    /// The absolute path of the worktree, which its files are stored under in the database.
+    key: Arc<str>,
+    scanning: bool,
+    pending_paths: BTreeSet<Arc<RelPath>>,
+    pending_paths_tx: mpsc::UnboundedSender<()>,
+    _indexing: Task<()>,
+}
+
+struct IndexingContext {
+    worktree: Entity<Worktree>,
+    key: Arc<str>,
+    embedding_provider: Arc<dyn EmbeddingProvider>,
+    fs: Arc<dyn Fs>,
+    languages: Arc<LanguageRegistry>,
+}
+
+impl WorktreeIndex {
+    pub(crate) fn new(
+        worktree: Entity<Worktree>,
+        embedding_provider: Arc<dyn EmbeddingProvider>,
+        fs: Arc<dyn Fs>,
+        languages: Arc<LanguageRegistry>,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let key: Arc<str> = worktree.read(cx).abs_path().to_string_lossy().into();
+        let (pending_paths_tx, pending_paths_rx) = mpsc::unbounded();
+        let indexing = cx.spawn({
+            let key = key.clone();
+            async move |this, cx| {
+                let context = IndexingContext {
+                    worktree,
+                    key,
+                    embedding_provider,
+                    fs,
+                    languages,
+                };
+                Self::index(this, context, pending_paths_rx, cx)
+                    .await
+                    .log_err();
+            }
+        });
+
+        Self {
+            key,
+            scanning: true,
+            pending_paths: BTreeSet::new(),
+            pending_paths_tx,
+            _indexing: indexing,
+        }
+    }
+
+    pub(crate) fn key(&self) -> Arc<str> {
+        self.key.clone()
+    }
+
+    pub(crate) fn status(&self) -> WorktreeIndexStatus {
+        if self.scanning {
+            WorktreeIndexStatus::Scanning
+        } else if self.pending_paths.is_empty() {
+            WorktreeIndexStatus::Idle
+        } else {
+            WorktreeIndexStatus::Indexing {
+                remaining_files: self.pending_paths.len(),
+            }
+        }
+    }
+
+    pub(crate) fn paths_changed(&mut self, changes: &UpdatedEntriesSet, cx: &mut Context<Self>) {
+        self.pending_paths
+            .extend(changes.iter().map(|(path, _, _)| path.clone()));
+        self.pending_paths_tx.unbounded_send(()).ok();
+        cx.notify();
+    }
+
+    async fn index(
+        this: WeakEntity<Self>,
+        context: IndexingContext,
+        mut pending_paths_rx: mpsc::UnboundedReceiver<()>,
+        cx: &mut AsyncApp,
+    ) -> Result<()> {
+        let scan_complete = context.worktree.read_with(cx, |worktree, _| {
+            worktree.as_local().map(|worktree| worktree.scan_complete())
+        })?;
+        if let Some(scan_complete) = scan_complete {
+            scan_complete.await;
+        }
+
+        let model: Arc<str> = context.embedding_provider.model().into();
+        let mut indexed_mtimes = SEMANTIC_DB
+            .file_mtimes(context.key.clone(), model.clone())
+            .await?;
+        let files = context.worktree.read_with(cx, |worktree, _| {
+            worktree
+                .files(false, 0)
+                .filter(|entry| entry.size <= MAX_FILE_SIZE)
+                .map(|entry| (entry.path.clone(), entry.mtime))
+                .collect::<Vec<_>>()
+        })?;
+        let mut changed_paths = Vec::new();
+        for (path, mtime) in files {
+            if indexed_mtimes.remove(&path) != Some(mtime) {
+                changed_paths.push(path);
+            }
+        }
+        // Whatever is left was deleted, or ignored, since it was indexed.
+        changed_paths.extend(indexed_mtimes.into_keys());
+        this.update(cx, |this, cx| {
+            this.scanning = false;
+            this.pending_paths.extend(changed_paths);
+            cx.notify();
+        })?;
+
+        loop {
+            // Paths that change again while they're being indexed are queued again.
+            while let Some(path) = this.update(cx, |this, cx| {
+                cx.notify();
+                this.pending_paths.pop_first()
+            })? {
+                Self::index_path(&context, &model, path, cx).await.log_err();
+            }
+            if pending_paths_rx.next().await.is_none() {
+                return Ok(());
+            }
+        }
+    }
+
+    async fn index_path(
+        context: &IndexingContext,
+        model: &Arc<str>,
+        path: Arc<RelPath>,
+        cx: &mut AsyncApp,
+    ) -> Result<()> {
+        let (entry, abs_path) = context.worktree.read_with(cx, |worktree, _| {
+            (
+                worktree.entry_for_path(&path).cloned(),
+                worktree.absolutize(&path),
+            )
+        })?;
+        let Some(entry) = entry
+            .filter(|entry| entry.is_file() && !entry.is_ignored && entry.size <= MAX_FILE_SIZE)
+        else {
+            return SEMANTIC_DB
+                .delete_file(context.key.clone(), model.clone(), path)
+                .await;
+        };
+
+        // Files that can't be read as text, such as binary ones, aren't indexed.
+        let Some(text) = context.fs.load(&abs_path).await.ok() else {
+            return SEMANTIC_DB
+                .delete_file(context.key.clone(), model.clone(), path)
+                .await;
+        };
+        let language = context
+            .languages
+            .load_language_for_file_path(&abs_path)
+            .await
+            .ok();
+        let snapshot = cx
+            .update(|cx| {
+                Buffer::build_snapshot(
+                    Rope::from(text.as_str()),
+                    language,
+                    Some(context.languages.clone()),
+                    cx,
+                )
+            })?
+            .await;
+
+        let embedding_provider = context.embedding_provider.clone();
+        let chunks = cx
+            .background_spawn({
+                let path = path.clone();
+                async move {
+                    let ranges = chunk_buffer(&snapshot);
+                    // The path gives the embedding model context about what the code is for.
+                    let texts = ranges
+                        .iter()
+                        .map(|range| {
+                            format!(
+                                "{}\n\n{}",
+                                path.as_unix_str(),
+                                snapshot.text_for_range(range.clone()).collect::<String>()
+                            )
+                        })
+                        .collect::<Vec<_>>();
+
+                    let mut embeddings = Vec::with_capacity(texts.len());
+                    for batch in texts.chunks(embedding_provider.batch_size()) {
+                        embeddings.extend(embedding_provider.embed(batch).await?);
+                    }
+                    if embeddings.len() != ranges.len() {
+                        return Err(anyhow!(
+                            "Expected {} embeddings for {}, but the model returned {}",
+                            ranges.len(),
+                            path.as_unix_str(),
+                            embeddings.len()
+                        ));
+                    }
+                    anyhow::Ok(
+                        ranges
+                            .into_iter()
+                            .zip(embeddings)
+                            .map(|(range, embedding)| EmbeddedChunk { range, embedding })
+                            .collect::<Vec<_>>(),
+                    )
+                }
+            })
+            .await?;
+
+        SEMANTIC_DB
+            .save_file(
+                context.key.clone(),
+                model.clone(),
+                path,
+                entry.mtime,
+                chunks,
+            )
+            .await
+    }
+}
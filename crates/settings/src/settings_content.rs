@@ -119,6 +119,8 @@ pub struct SettingsContent {
 
     pub project_panel: Option<ProjectPanelSettingsContent>,
 
+    pub references_panel: Option<ReferencesPanelSettingsContent>,
+
     /// Configuration for the Message Editor
     pub message_editor: Option<MessageEditorSettings>,
 
@@ -130,6 +132,9 @@ pub struct SettingsContent {
 
     pub proxy: Option<String>,
 
+    /// Configuration for the semantic index of the project's code.
+    pub semantic_index: Option<SemanticIndexSettingsContent>,
+
     /// The URL of the Zed server to connect to.
     pub server_url: Option<String>,
 
@@ -651,6 +656,24 @@ pub enum HourFormat {
     Hour24,
 }
 
+/// Settings for the semantic index of the project's code.
+#[skip_serializing_none]
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, MergeFrom, PartialEq)]
+pub struct SemanticIndexSettingsContent {
+    /// Whether to index the code of local projects so that it can be searched by meaning.
+    ///
+    /// Default: false
+    pub enabled: Option<bool>,
+    /// The URL of the Ollama server that computes the embeddings.
+    ///
+    /// Default: "http://localhost:11434"
+    pub api_url: Option<String>,
+    /// The Ollama embedding model to use. Changing it re-indexes all projects.
+    ///
+    /// Default: "nomic-embed-text"
+    pub model: Option<String>,
+}
+
 #[skip_serializing_none]
 #[derive(Clone, Default, Serialize, Deserialize, JsonSchema, MergeFrom, Debug, PartialEq)]
 pub struct OutlinePanelSettingsContent {
@@ -706,6 +729,31 @@ pub struct OutlinePanelSettingsContent {
     pub expand_outlines_with_depth: Option<usize>,
 }
 
+#[skip_serializing_none]
+#[derive(Clone, Default, Serialize, Deserialize, JsonSchema, MergeFrom, Debug, PartialEq)]
+pub struct ReferencesPanelSettingsContent {
+    /// Whether to show the references panel button in the status bar.
+    ///
+    /// Default: true
+    pub button: Option<bool>,
+    /// Customize default width (in pixels) taken by references panel
+    ///
+    /// Default: 300
+    pub default_width: Option<f32>,
+    /// The position of references panel
+    ///
+    /// Default: right
+    pub dock: Option<DockSide>,
+    /// Whether to group the references of each file by the symbol containing them.
+    ///
+    /// Default: true
+    pub group_by_symbol: Option<bool>,
+    /// How many past searches to keep in the references panel history.
+    ///
+    /// Default: 20
+    pub max_history: Option<usize>,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, MergeFrom, Copy, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub enum DockSide {
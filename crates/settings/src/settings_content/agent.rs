@@ -109,6 +109,8 @@ pub struct AgentSettingsContent {
     ///
     /// Default: 4
     pub message_editor_min_lines: Option<usize>,
+    /// How context servers may use your language models when they ask for a completion.
+    pub context_server_sampling: Option<ContextServerSamplingSettingsContent>,
 }
 
 impl AgentSettingsContent {
@@ -168,6 +170,76 @@ impl AgentSettingsContent {
     }
 }
 
+#[skip_serializing_none]
+#[derive(Clone, PartialEq, Serialize, Deserialize, JsonSchema, MergeFrom, Debug, Default)]
+pub struct ContextServerSamplingSettingsContent {
+    /// The model that answers context servers' requests for completions.
+    /// Defaults to default_model when not specified.
+    pub model: Option<LanguageModelSelection>,
+    /// The context servers whose requests are answered without asking for your approval first.
+    ///
+    /// Default: []
+    pub auto_approve: Option<Vec<Arc<str>>>,
+    /// The most tokens a context server may have generated for a single request.
+    ///
+    /// Default: 4096
+    pub max_tokens: Option<u32>,
+    /// The most tokens a context server may send to the model in a single request.
+    ///
+    /// Default: 32000
+    pub max_prompt_tokens: Option<u64>,
+}
+
+impl ContextServerSamplingSettingsContent {
+    pub fn add_auto_approved_server(&mut self, server_id: Arc<str>) {
+        let auto_approve = self.auto_approve.get_or_insert_default();
+        if !auto_approve.contains(&server_id) {
+            auto_approve.push(server_id);
+        }
+    }
+}
+
+/// A rule deciding whether the agent may use a tool without asking first.
+#[skip_serializing_none]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize, JsonSchema, MergeFrom)]
+pub struct ToolPermissionRuleContent {
+    /// The name of the tool the rule applies to, such as `edit_file` or `terminal`, or `*` for
+    /// every tool.
+    pub tool: Arc<str>,
+    /// A glob matched against the path the tool works on, relative to its worktree. When set, the
+    /// rule only applies to tools that work on paths.
+    pub path: Option<String>,
+    /// A glob matched against the command the tool runs. When set, the rule only applies to tools
+    /// that run commands.
+    pub command: Option<String>,
+    /// What to do when the rule applies. When several rules apply, `deny` wins over `ask`, which
+    /// wins over `allow`.
+    pub action: ToolPermissionAction,
+}
+
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+    MergeFrom,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolPermissionAction {
+    /// Use the tool without asking.
+    Allow,
+    /// Ask before using the tool, even when tool actions are always allowed.
+    Ask,
+    /// Never use the tool.
+    Deny,
+}
+
 #[skip_serializing_none]
 #[derive(Debug, PartialEq, Clone, Serialize, Deserialize, JsonSchema, MergeFrom)]
 pub struct AgentProfileContent {
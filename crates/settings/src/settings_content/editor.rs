@@ -62,6 +62,10 @@ pub struct EditorSettingsContent {
     pub scrollbar: Option<ScrollbarContent>,
     /// Minimap related settings
     pub minimap: Option<MinimapContent>,
+    /// Sticky scroll related settings
+    pub sticky_scroll: Option<StickyScrollContent>,
+    /// Jump list related settings
+    pub jump_list: Option<JumpListContent>,
     /// Gutter related settings
     pub gutter: Option<GutterContent>,
     /// Whether the editor will scroll beyond the last line.
@@ -104,6 +108,11 @@ pub struct EditorSettingsContent {
     ///
     /// Default: alt
     pub multi_cursor_modifier: Option<MultiCursorModifier>,
+    /// Whether column selections can extend past the end of shorter lines, placing cursors in
+    /// virtual space that is filled with spaces when text is inserted.
+    ///
+    /// Default: false
+    pub column_selection_virtual_space: Option<bool>,
     /// Hide the values of variables in `private` files, as defined by the
     /// private_files setting. This only changes the visual representation,
     /// the values are still present in the file and can be selected / copied / pasted
@@ -305,6 +314,38 @@ pub struct MinimapContent {
     pub max_width_columns: Option<num::NonZeroU32>,
 }
 
+/// Sticky scroll related settings
+#[skip_serializing_none]
+#[derive(Clone, Default, Debug, Serialize, Deserialize, JsonSchema, MergeFrom, PartialEq, Eq)]
+pub struct StickyScrollContent {
+    /// Whether to pin the lines of the scopes enclosing the top of the viewport,
+    /// such as functions, impls or classes, at the top of the editor.
+    ///
+    /// Default: false
+    pub enabled: Option<bool>,
+
+    /// Maximum number of nested scopes to pin at the top of the editor.
+    ///
+    /// Default: 5
+    pub max_depth: Option<u32>,
+}
+
+/// Jump list related settings
+#[skip_serializing_none]
+#[derive(Clone, Default, Debug, Serialize, Deserialize, JsonSchema, MergeFrom, PartialEq, Eq)]
+pub struct JumpListContent {
+    /// The minimum number of rows the cursor must move within a file
+    /// for the move to be recorded as a jump.
+    ///
+    /// Default: 10
+    pub min_row_delta: Option<u32>,
+
+    /// Maximum number of jumps to remember, the oldest being forgotten first.
+    ///
+    /// Default: 100
+    pub max_entries: Option<usize>,
+}
+
 /// Forcefully enable or disable the scrollbar for each axis
 #[skip_serializing_none]
 #[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, MergeFrom, PartialEq, Default)]
@@ -570,6 +611,11 @@ pub struct SearchSettingsContent {
     pub case_sensitive: Option<bool>,
     pub include_ignored: Option<bool>,
     pub regex: Option<bool>,
+    /// Whether to save the files changed by replacing all matches of a project search,
+    /// instead of leaving them dirty.
+    ///
+    /// Default: false
+    pub save_after_replace: Option<bool>,
 }
 
 #[skip_serializing_none]
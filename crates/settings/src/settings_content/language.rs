@@ -82,6 +82,9 @@ pub enum EditPredictionProvider {
     Copilot,
     Supermaven,
     Zed,
+    /// A model serving fill-in-the-middle completions locally, through Ollama or an
+    /// OpenAI-compatible server.
+    Local,
 }
 
 impl EditPredictionProvider {
@@ -90,7 +93,8 @@ impl EditPredictionProvider {
             EditPredictionProvider::Zed => true,
             EditPredictionProvider::None
             | EditPredictionProvider::Copilot
-            | EditPredictionProvider::Supermaven => false,
+            | EditPredictionProvider::Supermaven
+            | EditPredictionProvider::Local => false,
         }
     }
 }
@@ -108,6 +112,8 @@ pub struct EditPredictionSettingsContent {
     pub mode: Option<EditPredictionsMode>,
     /// Settings specific to GitHub Copilot.
     pub copilot: Option<CopilotSettingsContent>,
+    /// Settings specific to the local model provider.
+    pub local: Option<LocalEditPredictionSettingsContent>,
     /// Whether edit predictions are enabled in the assistant prompt editor.
     /// This has no effect if globally disabled.
     pub enabled_in_text_threads: Option<bool>,
@@ -130,6 +136,60 @@ pub struct CopilotSettingsContent {
     pub enterprise_uri: Option<String>,
 }
 
+#[skip_serializing_none]
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema, MergeFrom, PartialEq)]
+pub struct LocalEditPredictionSettingsContent {
+    /// The API the model is served through.
+    ///
+    /// Default: ollama
+    pub api: Option<LocalEditPredictionApi>,
+    /// The URL of the server, without the API's path.
+    ///
+    /// Default: "http://localhost:11434"
+    pub api_url: Option<String>,
+    /// The model to request completions from, which must support fill-in-the-middle.
+    ///
+    /// Default: "qwen2.5-coder:1.5b"
+    pub model: Option<String>,
+    /// The special tokens the model uses for fill-in-the-middle prompts.
+    ///
+    /// Default: detected from the model's name
+    pub fim_format: Option<FimFormat>,
+    /// The most tokens to generate for a prediction.
+    ///
+    /// Default: 128
+    pub max_tokens: Option<u32>,
+}
+
+/// An API serving completions of raw prompts.
+#[derive(
+    Copy, Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize, JsonSchema, MergeFrom,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum LocalEditPredictionApi {
+    /// Ollama's `/api/generate` endpoint.
+    #[default]
+    Ollama,
+    /// The `/completions` endpoint of an OpenAI-compatible server, such as llama.cpp or vLLM.
+    OpenAiCompatible,
+}
+
+/// The family of special tokens a model was trained with for fill-in-the-middle prompts.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize, JsonSchema, MergeFrom)]
+#[serde(rename_all = "snake_case")]
+pub enum FimFormat {
+    /// `<|fim_prefix|>`, `<|fim_suffix|>` and `<|fim_middle|>`, as used by Qwen2.5-Coder.
+    Qwen,
+    /// `<PRE>`, `<SUF>` and `<MID>`, as used by Code Llama.
+    CodeLlama,
+    /// `<fim_prefix>`, `<fim_suffix>` and `<fim_middle>`, as used by StarCoder.
+    StarCoder,
+    /// `<｜fim▁begin｜>`, `<｜fim▁hole｜>` and `<｜fim▁end｜>`, as used by DeepSeek Coder.
+    DeepSeek,
+    /// `[SUFFIX]` and `[PREFIX]`, as used by Codestral.
+    Codestral,
+}
+
 /// The mode in which edit predictions should be displayed.
 #[derive(
     Copy, Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize, JsonSchema, MergeFrom,
@@ -287,6 +347,10 @@ pub struct LanguageSettingsContent {
     ///
     /// Default: false
     pub always_treat_brackets_as_autoclosed: Option<bool>,
+    /// Whether to color bracket pairs by their nesting depth, using the theme's accent colors.
+    ///
+    /// Default: false
+    pub colorize_brackets: Option<bool>,
     /// Whether to use additional LSP queries to format (and amend) the code after
     /// every "trigger" symbol input, defined by LSP server capabilities.
     ///
@@ -7,7 +7,10 @@ use serde_with::skip_serializing_none;
 use settings_macros::MergeFrom;
 use util::serde::default_true;
 
-use crate::{AllLanguageSettingsContent, ExtendingVec, SlashCommandSettings};
+use crate::{
+    AllLanguageSettingsContent, ExtendingVec, SlashCommandSettings, ToolPermissionAction,
+    ToolPermissionRuleContent,
+};
 
 #[skip_serializing_none]
 #[derive(Debug, PartialEq, Clone, Default, Serialize, Deserialize, JsonSchema, MergeFrom)]
@@ -37,6 +40,13 @@ pub struct ProjectSettingsContent {
     #[serde(default)]
     pub context_servers: HashMap<Arc<str>, ContextServerSettingsContent>,
 
+    /// Rules deciding which tools the agent may use without asking, by tool, path and command.
+    /// Rules from project settings are added to those from user settings, except for `allow`
+    /// rules, which are only honored in user settings.
+    ///
+    /// Default: []
+    pub agent_tool_permissions: Option<ExtendingVec<ToolPermissionRuleContent>>,
+
     /// Configuration for how direnv configuration should be loaded
     pub load_direnv: Option<DirenvSettings>,
 
@@ -47,6 +57,18 @@ pub struct ProjectSettingsContent {
     pub git_hosting_providers: Option<ExtendingVec<GitHostingProviderConfig>>,
 }
 
+impl ProjectSettingsContent {
+    /// Drops the `allow` tool permission rules of a project's settings file, so that opening a
+    /// project can't let the agent do more without asking.
+    pub(crate) fn remove_allowed_tool_permissions(&mut self) {
+        if let Some(rules) = self.agent_tool_permissions.as_mut() {
+            rules
+                .0
+                .retain(|rule| rule.action != ToolPermissionAction::Allow);
+        }
+    }
+}
+
 #[skip_serializing_none]
 #[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema, MergeFrom)]
 pub struct WorktreeSettingsContent {
@@ -140,6 +162,42 @@ pub struct GlobalLspSettingsContent {
     ///
     /// Default: `true`
     pub button: Option<bool>,
+    /// Which language servers answer each LSP feature, and in what order.
+    pub feature_servers: Option<LspFeatureServersContent>,
+}
+
+/// Per-feature language server priority.
+///
+/// Each list uses the same syntax as the `language_servers` language setting:
+/// servers are queried in the listed order, `!name` excludes a server and
+/// `...` stands for all the remaining servers attached to the buffer.
+#[skip_serializing_none]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema, MergeFrom)]
+pub struct LspFeatureServersContent {
+    /// Servers to request completions from, with the earlier ones listed first
+    /// and winning over the later ones when the same completion is returned.
+    ///
+    /// Default: `["..."]`
+    pub completions: Option<Vec<String>>,
+    /// Servers to request hover information from, in the order it is displayed.
+    ///
+    /// Default: `["..."]`
+    pub hover: Option<Vec<String>>,
+    /// Servers to format with when no server is named in the `formatter` setting.
+    /// The first server of the list that is running for the buffer is used.
+    ///
+    /// Default: `["..."]`
+    pub formatting: Option<Vec<String>>,
+    /// Servers to rename symbols with.
+    /// The first server of the list that supports renaming is used.
+    ///
+    /// Default: `["..."]`
+    pub rename: Option<Vec<String>>,
+    /// Servers to request code actions from, with the earlier ones listed first
+    /// and winning over the later ones when the same action is returned.
+    ///
+    /// Default: `["..."]`
+    pub code_actions: Option<Vec<String>>,
 }
 
 #[skip_serializing_none]
@@ -186,6 +244,14 @@ pub enum ContextServerSettingsContent {
         /// are supported.
         settings: serde_json::Value,
     },
+    Http {
+        /// Whether the context server is enabled.
+        #[serde(default = "default_true")]
+        enabled: bool,
+
+        #[serde(flatten)]
+        http: ContextServerHttpConfig,
+    },
 }
 impl ContextServerSettingsContent {
     pub fn set_enabled(&mut self, enabled: bool) {
@@ -200,6 +266,10 @@ impl ContextServerSettingsContent {
                 enabled: ext_enabled,
                 settings: _,
             } => *ext_enabled = enabled,
+            ContextServerSettingsContent::Http {
+                enabled: http_enabled,
+                http: _,
+            } => *http_enabled = enabled,
         }
     }
 }
@@ -240,6 +310,56 @@ impl std::fmt::Debug for ContextServerCommand {
     }
 }
 
+/// How to reach a context server that is served over HTTP rather than run locally.
+#[skip_serializing_none]
+#[derive(Deserialize, Serialize, Clone, PartialEq, Eq, JsonSchema, MergeFrom)]
+pub struct ContextServerHttpConfig {
+    /// The URL of the server's MCP endpoint.
+    pub url: String,
+    /// Headers to send with every request, such as an `Authorization` header
+    /// for servers that require a token.
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    /// The transport the server speaks.
+    ///
+    /// Default: streamable_http
+    #[serde(default)]
+    pub transport: ContextServerHttpTransport,
+    /// Timeout for tool calls in milliseconds. Defaults to 60000 (60 seconds) if not specified.
+    pub timeout: Option<u64>,
+}
+
+impl std::fmt::Debug for ContextServerHttpConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Header values are usually credentials, so only their names are shown.
+        let redacted_headers = self
+            .headers
+            .keys()
+            .map(|name| (name, "[REDACTED]"))
+            .collect::<Vec<_>>();
+
+        f.debug_struct("ContextServerHttpConfig")
+            .field("url", &self.url)
+            .field("headers", &redacted_headers)
+            .field("transport", &self.transport)
+            .finish()
+    }
+}
+
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema, MergeFrom,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum ContextServerHttpTransport {
+    /// The Streamable HTTP transport, where every message is POSTed to the
+    /// server's endpoint.
+    #[default]
+    StreamableHttp,
+    /// The legacy HTTP+SSE transport, where the server's messages arrive over
+    /// a long-lived event stream.
+    Sse,
+}
+
 #[skip_serializing_none]
 #[derive(Copy, Clone, Debug, PartialEq, Default, Serialize, Deserialize, JsonSchema, MergeFrom)]
 pub struct GitSettings {
@@ -365,6 +485,16 @@ pub struct LspPullDiagnosticsSettingsContent {
     ///
     /// Default: 50
     pub debounce_ms: Option<u64>,
+    /// Whether to pull diagnostics for the whole workspace, including files that are not open,
+    /// from the language servers that support `workspace/diagnostic` requests.
+    ///
+    /// Default: true
+    pub workspace_diagnostics: Option<bool>,
+    /// Minimum time to wait between two consecutive workspace diagnostics pulls.
+    /// Changes made in the meantime cancel the pending pull and are coalesced into the next one.
+    ///
+    /// Default: 1000
+    pub workspace_debounce_ms: Option<u64>,
 }
 
 #[skip_serializing_none]
@@ -737,13 +737,14 @@ impl SettingsStore {
                     .remove(&(root_id, directory_path.clone()));
             }
             (LocalSettingsKind::Settings, Some(settings_contents)) => {
-                let new_settings = parse_json_with_comments::<ProjectSettingsContent>(
+                let mut new_settings = parse_json_with_comments::<ProjectSettingsContent>(
                     settings_contents,
                 )
                 .map_err(|e| InvalidSettingsError::LocalSettings {
                     path: directory_path.join(local_settings_file_relative_path()),
                     message: e.to_string(),
                 })?;
+                new_settings.remove_allowed_tool_permissions();
                 match self.local_settings.entry((root_id, directory_path.clone())) {
                     btree_map::Entry::Vacant(v) => {
                         v.insert(SettingsContent {
@@ -1,12 +1,12 @@
 use std::{path::Path, sync::Arc};
 
-use gpui::{EventEmitter, FocusHandle, Focusable};
+use gpui::{Action as _, EventEmitter, FocusHandle, Focusable};
 use ui::{
     App, Button, ButtonCommon, ButtonStyle, Clickable, Context, FluentBuilder, InteractiveElement,
     KeyBinding, Label, LabelCommon, LabelSize, ParentElement, Render, SharedString, Styled as _,
     Window, h_flex, v_flex,
 };
-use zed_actions::workspace::OpenWithSystem;
+use zed_actions::{hex_editor, workspace::OpenWithSystem};
 
 use crate::Item;
 
@@ -77,6 +77,9 @@ impl Focusable for InvalidBufferView {
 impl Render for InvalidBufferView {
     fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl gpui::IntoElement {
         let abs_path = self.abs_path.clone();
+        let open_in_hex_editor = hex_editor::OpenFile {
+            abs_path: Some(abs_path.to_path_buf()),
+        };
         v_flex()
             .size_full()
             .track_focus(&self.focus_handle(cx))
@@ -97,18 +100,31 @@ impl Render for InvalidBufferView {
                         )
                         .when(self.is_local, |contents| {
                             contents.child(
-                                h_flex().justify_center().child(
-                                    Button::new("open-with-system", "Open in Default App")
-                                        .on_click(move |_, _, cx| {
-                                            cx.open_with_system(&abs_path);
-                                        })
-                                        .style(ButtonStyle::Outlined)
-                                        .key_binding(KeyBinding::for_action(
-                                            &OpenWithSystem,
-                                            window,
-                                            cx,
-                                        )),
-                                ),
+                                h_flex()
+                                    .justify_center()
+                                    .gap_2()
+                                    .child(
+                                        Button::new("open-with-system", "Open in Default App")
+                                            .on_click(move |_, _, cx| {
+                                                cx.open_with_system(&abs_path);
+                                            })
+                                            .style(ButtonStyle::Outlined)
+                                            .key_binding(KeyBinding::for_action(
+                                                &OpenWithSystem,
+                                                window,
+                                                cx,
+                                            )),
+                                    )
+                                    .child(
+                                        Button::new("open-in-hex-editor", "Open in Hex Editor")
+                                            .on_click(move |_, window, cx| {
+                                                window.dispatch_action(
+                                                    open_in_hex_editor.boxed_clone(),
+                                                    cx,
+                                                );
+                                            })
+                                            .style(ButtonStyle::Outlined),
+                                    ),
                             )
                         }),
                 ),
@@ -51,6 +51,7 @@ use project_panel::ProjectPanel;
 use prompt_store::PromptBuilder;
 use quick_action_bar::QuickActionBar;
 use recent_projects::open_remote_project;
+use references_panel::ReferencesPanel;
 use release_channel::{AppCommitSha, ReleaseChannel};
 use rope::Rope;
 use search::project_search::ProjectSearchBar;
@@ -566,6 +567,7 @@ fn initialize_panels(
     cx.spawn_in(window, async move |workspace_handle, cx| {
         let project_panel = ProjectPanel::load(workspace_handle.clone(), cx.clone());
         let outline_panel = OutlinePanel::load(workspace_handle.clone(), cx.clone());
+        let references_panel = ReferencesPanel::load(workspace_handle.clone(), cx.clone());
         let terminal_panel = TerminalPanel::load(workspace_handle.clone(), cx.clone());
         let git_panel = GitPanel::load(workspace_handle.clone(), cx.clone());
         let channels_panel =
@@ -579,6 +581,7 @@ fn initialize_panels(
         let (
             project_panel,
             outline_panel,
+            references_panel,
             terminal_panel,
             git_panel,
             channels_panel,
@@ -587,6 +590,7 @@ fn initialize_panels(
         ) = futures::try_join!(
             project_panel,
             outline_panel,
+            references_panel,
             git_panel,
             terminal_panel,
             channels_panel,
@@ -597,6 +601,7 @@ fn initialize_panels(
         workspace_handle.update_in(cx, |workspace, window, cx| {
             workspace.add_panel(project_panel, window, cx);
             workspace.add_panel(outline_panel, window, cx);
+            workspace.add_panel(references_panel, window, cx);
             workspace.add_panel(terminal_panel, window, cx);
             workspace.add_panel(git_panel, window, cx);
             workspace.add_panel(channels_panel, window, cx);
@@ -4505,6 +4510,7 @@ mod tests {
                 "project_search",
                 "project_symbols",
                 "projects",
+                "references_panel",
                 "repl",
                 "rules_library",
                 "search",
@@ -4661,6 +4667,7 @@ mod tests {
             git_ui::init(cx);
             project_panel::init(cx);
             outline_panel::init(cx);
+            references_panel::init(cx);
             terminal_view::init(cx);
             copilot::copilot_chat::init(
                 app_state.fs.clone(),
@@ -4669,6 +4676,7 @@ mod tests {
                 cx,
             );
             image_viewer::init(cx);
+            hex_editor::init(cx);
             language_model::init(app_state.client.clone(), cx);
             language_models::init(app_state.user_store.clone(), app_state.client.clone(), cx);
             web_search::init(cx);
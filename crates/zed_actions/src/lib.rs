@@ -452,6 +452,24 @@ pub mod outline {
     pub static TOGGLE_OUTLINE: OnceLock<fn(AnyView, &mut Window, &mut App)> = OnceLock::new();
 }
 
+pub mod hex_editor {
+    use std::path::PathBuf;
+
+    use gpui::Action;
+    use schemars::JsonSchema;
+    use serde::Deserialize;
+
+    /// Opens a file in the hex editor.
+    #[derive(PartialEq, Clone, Default, Debug, Deserialize, JsonSchema, Action)]
+    #[action(namespace = hex_editor)]
+    #[serde(deny_unknown_fields)]
+    pub struct OpenFile {
+        /// The absolute path of the file to open. Defaults to the file of the active item.
+        #[serde(default)]
+        pub abs_path: Option<PathBuf>,
+    }
+}
+
 actions!(
     zed_predict_onboarding,
     [